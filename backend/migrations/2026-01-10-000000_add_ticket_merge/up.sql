@@ -0,0 +1,6 @@
+-- Redirect marker for tickets that were merged into another ticket
+-- A merged ticket is closed and points at the ticket that absorbed its comments,
+-- devices, projects, links and notes
+ALTER TABLE tickets ADD COLUMN merged_into_id INT REFERENCES tickets(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tickets_merged_into ON tickets(merged_into_id) WHERE merged_into_id IS NOT NULL;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS change_details;
+DROP TABLE IF EXISTS problem_details;
+
+DROP INDEX IF EXISTS idx_tickets_ticket_type;
+ALTER TABLE tickets DROP COLUMN IF EXISTS ticket_type;
@@ -0,0 +1,39 @@
+-- Problem and change management records
+-- Problems and changes are tickets with a ticket_type and a detail row, so they reuse
+-- comments, notes, linked_tickets (incidents are linked to a problem as caused_by),
+-- ticket_devices (devices a change touches) and project_tickets
+
+ALTER TABLE tickets ADD COLUMN ticket_type VARCHAR(20) NOT NULL DEFAULT 'incident'; -- 'incident', 'problem', 'change'
+CREATE INDEX idx_tickets_ticket_type ON tickets(ticket_type);
+
+CREATE TABLE problem_details (
+    ticket_id INT PRIMARY KEY REFERENCES tickets(id) ON DELETE CASCADE,
+    root_cause TEXT,
+    workaround TEXT,
+    -- A known error has a documented root cause and workaround
+    is_known_error BOOLEAN NOT NULL DEFAULT FALSE,
+    known_error_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_problem_details_known_error ON problem_details(is_known_error) WHERE is_known_error = TRUE;
+
+CREATE TABLE change_details (
+    ticket_id INT PRIMARY KEY REFERENCES tickets(id) ON DELETE CASCADE,
+    risk_level VARCHAR(20) NOT NULL DEFAULT 'low', -- 'low', 'medium', 'high', 'critical'
+    planned_start TIMESTAMPTZ,
+    planned_end TIMESTAMPTZ,
+    implementation_plan TEXT,
+    backout_plan TEXT,
+    approval_status VARCHAR(20) NOT NULL DEFAULT 'draft', -- 'draft', 'pending', 'approved', 'rejected'
+    approved_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    approved_at TIMESTAMPTZ,
+    approval_note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT change_window_order CHECK (planned_start IS NULL OR planned_end IS NULL OR planned_end > planned_start)
+);
+
+CREATE INDEX idx_change_details_approval_status ON change_details(approval_status);
+CREATE INDEX idx_change_details_planned_start ON change_details(planned_start);
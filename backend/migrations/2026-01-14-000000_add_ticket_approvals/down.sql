@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS ticket_approval_events;
+DROP TABLE IF EXISTS ticket_approvals;
+DROP TABLE IF EXISTS approval_chain_steps;
+DROP TABLE IF EXISTS approval_chains;
+
+DROP INDEX IF EXISTS idx_tickets_approval_status;
+ALTER TABLE tickets DROP COLUMN IF EXISTS approval_status;
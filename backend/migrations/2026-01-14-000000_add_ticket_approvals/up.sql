@@ -0,0 +1,70 @@
+-- Approval workflows
+-- Admins configure an ordered approval chain per category. When a ticket enters a category
+-- with an active chain, one ticket_approvals row is created per step and the ticket is held
+-- in approval_status = 'pending' until every step approves or any step rejects.
+
+-- NULL when the ticket needs no approval
+ALTER TABLE tickets ADD COLUMN approval_status VARCHAR(20); -- 'pending', 'approved', 'rejected'
+CREATE INDEX idx_tickets_approval_status ON tickets(approval_status) WHERE approval_status IS NOT NULL;
+
+CREATE TABLE approval_chains (
+    id SERIAL PRIMARY KEY,
+    category_id INT NOT NULL UNIQUE REFERENCES ticket_categories(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID REFERENCES users(uuid) ON DELETE SET NULL
+);
+
+CREATE TABLE approval_chain_steps (
+    id SERIAL PRIMARY KEY,
+    chain_id INT NOT NULL REFERENCES approval_chains(id) ON DELETE CASCADE,
+    step_order INT NOT NULL,
+    approver_type VARCHAR(20) NOT NULL, -- 'user', 'manager', 'group'
+    approver_user_uuid UUID REFERENCES users(uuid) ON DELETE CASCADE,
+    approver_group_id INT REFERENCES groups(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (chain_id, step_order),
+    CONSTRAINT approval_step_target CHECK (
+        (approver_type = 'user' AND approver_user_uuid IS NOT NULL)
+        OR (approver_type = 'group' AND approver_group_id IS NOT NULL)
+        OR approver_type = 'manager'
+    )
+);
+
+CREATE INDEX idx_approval_chain_steps_chain ON approval_chain_steps(chain_id);
+
+-- Steps copied onto a ticket; approvers are resolved when the approval starts
+-- (a 'manager' step stores the requester's manager at that time)
+CREATE TABLE ticket_approvals (
+    id SERIAL PRIMARY KEY,
+    ticket_id INT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    step_order INT NOT NULL,
+    approver_type VARCHAR(20) NOT NULL,
+    approver_user_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    approver_group_id INT REFERENCES groups(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'waiting', -- 'waiting', 'pending', 'approved', 'rejected', 'skipped', 'cancelled'
+    decided_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    decided_at TIMESTAMPTZ,
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ticket_approvals_ticket ON ticket_approvals(ticket_id);
+CREATE INDEX idx_ticket_approvals_pending_user ON ticket_approvals(approver_user_uuid) WHERE status = 'pending';
+CREATE INDEX idx_ticket_approvals_pending_group ON ticket_approvals(approver_group_id) WHERE status = 'pending';
+
+-- Audit trail of every approval action
+CREATE TABLE ticket_approval_events (
+    id SERIAL PRIMARY KEY,
+    ticket_id INT NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    approval_id INT REFERENCES ticket_approvals(id) ON DELETE SET NULL,
+    action VARCHAR(20) NOT NULL, -- 'requested', 'approved', 'rejected', 'cancelled'
+    actor_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    channel VARCHAR(20) NOT NULL DEFAULT 'web', -- 'web', 'email', 'system'
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ticket_approval_events_ticket ON ticket_approval_events(ticket_id, created_at);
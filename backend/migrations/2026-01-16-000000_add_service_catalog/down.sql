@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS catalog_submissions;
+DROP TABLE IF EXISTS catalog_items;
@@ -0,0 +1,41 @@
+-- Service catalog
+-- Catalog items belong to a category and carry a form schema. Submitting the form creates a
+-- 'service_request' ticket; the answers are kept alongside a copy of the schema they were
+-- given against, so later edits to the item do not change how old submissions read.
+-- Item visibility follows the category's category_group_visibility rows.
+
+CREATE TABLE catalog_items (
+    id SERIAL PRIMARY KEY,
+    uuid UUID NOT NULL DEFAULT gen_random_uuid() UNIQUE,
+    category_id INT NOT NULL REFERENCES ticket_categories(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    icon VARCHAR(50),
+    -- {"fields": [{"key", "label", "type", "required", "help_text", "options", "visible_when"}]}
+    form_schema JSONB NOT NULL DEFAULT '{"fields": []}',
+    -- Ticket title; {field_key} placeholders are replaced with answers
+    title_template VARCHAR(255),
+    default_priority ticket_priority NOT NULL DEFAULT 'medium',
+    -- Link the requester's own devices to the ticket
+    link_requester_devices BOOLEAN NOT NULL DEFAULT FALSE,
+    display_order INT NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID REFERENCES users(uuid) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_catalog_items_category ON catalog_items(category_id);
+CREATE INDEX idx_catalog_items_active ON catalog_items(is_active, display_order);
+
+CREATE TABLE catalog_submissions (
+    id SERIAL PRIMARY KEY,
+    ticket_id INT NOT NULL UNIQUE REFERENCES tickets(id) ON DELETE CASCADE,
+    catalog_item_id INT REFERENCES catalog_items(id) ON DELETE SET NULL,
+    form_schema JSONB NOT NULL,
+    answers JSONB NOT NULL,
+    submitted_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_catalog_submissions_item ON catalog_submissions(catalog_item_id);
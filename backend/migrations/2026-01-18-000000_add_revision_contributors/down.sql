@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_article_content_revisions_created_at;
+DROP INDEX IF EXISTS idx_documentation_revisions_contributors;
+ALTER TABLE documentation_revisions DROP COLUMN IF EXISTS contributed_by;
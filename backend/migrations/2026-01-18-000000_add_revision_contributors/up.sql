@@ -0,0 +1,9 @@
+-- Contributor attribution for documentation revisions
+-- Ticket revisions already record every contributor in article_content_revisions.contributed_by;
+-- documentation revisions only kept a single created_by. Existing rows are backfilled with it.
+ALTER TABLE documentation_revisions ADD COLUMN contributed_by UUID[] NOT NULL DEFAULT '{}';
+UPDATE documentation_revisions SET contributed_by = ARRAY[created_by];
+CREATE INDEX idx_documentation_revisions_contributors ON documentation_revisions USING GIN(contributed_by);
+
+-- Retention thins revisions by age per document
+CREATE INDEX idx_article_content_revisions_created_at ON article_content_revisions(article_content_id, created_at);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_documentation_pages_templates;
+ALTER TABLE ticket_categories DROP COLUMN IF EXISTS documentation_template_id;
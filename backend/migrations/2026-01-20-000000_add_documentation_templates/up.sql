@@ -0,0 +1,9 @@
+-- Documentation templates
+-- Pages flagged is_template can be instantiated into new pages; a ticket category can name the
+-- template used when a documentation page is created from one of its tickets.
+ALTER TABLE ticket_categories
+    ADD COLUMN documentation_template_id INTEGER REFERENCES documentation_pages(id) ON DELETE SET NULL;
+
+-- Template listings filter on unarchived templates and sort by title
+CREATE INDEX idx_documentation_pages_templates ON documentation_pages(title)
+    WHERE is_template = TRUE AND archived_at IS NULL;
@@ -0,0 +1,18 @@
+-- Knowledge base suggestion clicks
+-- Suggested articles are shown before a ticket is submitted ('pre_submit') and on ticket
+-- detail ('ticket'). Each click on a suggestion is recorded so deflection can be measured:
+-- a pre-submit click that isn't followed by a ticket from the same user is a deflected ticket.
+
+CREATE TABLE kb_suggestion_clicks (
+    id SERIAL PRIMARY KEY,
+    page_id INT NOT NULL REFERENCES documentation_pages(id) ON DELETE CASCADE,
+    user_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    ticket_id INT REFERENCES tickets(id) ON DELETE SET NULL,
+    source VARCHAR(20) NOT NULL CHECK (source IN ('pre_submit', 'ticket')),
+    -- Title/description the suggestions were made for (pre-submit only, truncated)
+    query TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_kb_suggestion_clicks_page ON kb_suggestion_clicks(page_id);
+CREATE INDEX idx_kb_suggestion_clicks_created_at ON kb_suggestion_clicks(created_at);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_backup_jobs_schedule;
+ALTER TABLE backup_jobs DROP COLUMN IF EXISTS remote_path;
+ALTER TABLE backup_jobs DROP COLUMN IF EXISTS schedule_id;
+DROP TABLE IF EXISTS backup_schedules;
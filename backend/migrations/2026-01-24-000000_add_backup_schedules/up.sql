@@ -0,0 +1,34 @@
+-- Scheduled backups
+-- A schedule runs an export on a cron expression (5 fields, UTC). Passwords for sensitive
+-- exports are never stored: the schedule names an environment variable or a secret file to
+-- read the password from at run time. Finished archives can be copied to the off-site
+-- target configured with BACKUP_S3_* and are pruned to the newest backup of each of the
+-- last keep_daily days and keep_weekly weeks (0 and 0 keeps everything).
+
+CREATE TABLE backup_schedules (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    cron_expression VARCHAR(100) NOT NULL,
+    include_sensitive BOOLEAN NOT NULL DEFAULT FALSE,
+    password_env VARCHAR(255),
+    password_file TEXT,
+    keep_daily INT NOT NULL DEFAULT 7 CHECK (keep_daily >= 0),
+    keep_weekly INT NOT NULL DEFAULT 4 CHECK (keep_weekly >= 0),
+    upload_remote BOOLEAN NOT NULL DEFAULT FALSE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run_at TIMESTAMPTZ,
+    next_run_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_backup_schedules_next_run ON backup_schedules(next_run_at) WHERE enabled = TRUE;
+
+SELECT diesel_manage_updated_at('backup_schedules');
+
+ALTER TABLE backup_jobs ADD COLUMN schedule_id INT REFERENCES backup_schedules(id) ON DELETE SET NULL;
+-- Object key of the copy uploaded to the off-site target
+ALTER TABLE backup_jobs ADD COLUMN remote_path TEXT;
+
+CREATE INDEX idx_backup_jobs_schedule ON backup_jobs(schedule_id) WHERE schedule_id IS NOT NULL;
@@ -0,0 +1,3 @@
+ALTER TABLE backup_jobs DROP COLUMN IF EXISTS progress_total;
+ALTER TABLE backup_jobs DROP COLUMN IF EXISTS progress_current;
+ALTER TABLE backup_jobs DROP COLUMN IF EXISTS progress_stage;
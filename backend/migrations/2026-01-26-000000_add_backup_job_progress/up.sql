@@ -0,0 +1,4 @@
+-- Progress of running backup and restore jobs, updated as they go
+ALTER TABLE backup_jobs ADD COLUMN progress_stage TEXT;
+ALTER TABLE backup_jobs ADD COLUMN progress_current BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE backup_jobs ADD COLUMN progress_total BIGINT;
@@ -0,0 +1,19 @@
+DROP INDEX IF EXISTS idx_documentation_pages_deleted_at;
+DROP INDEX IF EXISTS idx_projects_deleted_at;
+DROP INDEX IF EXISTS idx_devices_deleted_at;
+DROP INDEX IF EXISTS idx_attachments_deleted_at;
+DROP INDEX IF EXISTS idx_comments_deleted_at;
+DROP INDEX IF EXISTS idx_tickets_deleted_at;
+
+ALTER TABLE documentation_pages DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE documentation_pages DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE projects DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE projects DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE devices DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE devices DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE attachments DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE attachments DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE comments DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE comments DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE tickets DROP COLUMN IF EXISTS deleted_by;
+ALTER TABLE tickets DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,20 @@
+-- Soft delete: deleted rows stay in the recycle bin until the purge job removes them
+ALTER TABLE tickets ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE tickets ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+ALTER TABLE comments ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE comments ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+ALTER TABLE attachments ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE attachments ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+ALTER TABLE devices ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE devices ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+ALTER TABLE projects ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE projects ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+ALTER TABLE documentation_pages ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE documentation_pages ADD COLUMN deleted_by UUID REFERENCES users(uuid) ON DELETE SET NULL;
+
+CREATE INDEX idx_tickets_deleted_at ON tickets(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_comments_deleted_at ON comments(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_attachments_deleted_at ON attachments(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_devices_deleted_at ON devices(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_projects_deleted_at ON projects(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_documentation_pages_deleted_at ON documentation_pages(deleted_at) WHERE deleted_at IS NOT NULL;
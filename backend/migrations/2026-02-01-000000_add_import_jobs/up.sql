@@ -0,0 +1,22 @@
+-- Bulk CSV/XLSX imports of tickets, users and devices, run as background jobs
+CREATE TABLE import_jobs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+    entity_type VARCHAR(20) NOT NULL, -- 'tickets', 'users' or 'devices'
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, processing, completed, failed
+    dry_run BOOLEAN NOT NULL DEFAULT FALSE,
+    file_id TEXT NOT NULL, -- upload returned by the preview step
+    mapping JSONB NOT NULL DEFAULT '{}', -- target field -> source column header
+    progress_current BIGINT NOT NULL DEFAULT 0,
+    progress_total BIGINT,
+    created_count INTEGER NOT NULL DEFAULT 0,
+    updated_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    errors JSONB NOT NULL DEFAULT '[]', -- [{ "row": n, "field": "...", "message": "..." }]
+    error_message TEXT,
+    created_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_import_jobs_created_at ON import_jobs (created_at DESC);
+
@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Import from the parent crate
+extern crate backend;
+use backend::db;
+use backend::models::DocumentationStatus;
+use backend::repository;
+use backend::services::doc_import::{DocImportService, ImportOptions};
+use backend::utils::storage::{create_storage, get_storage_config};
+
+#[tokio::main]
+async fn main() {
+    // Get the zip path, author and optional parent page from command line arguments
+    let args: Vec<String> = env::args().collect();
+    let publish = args.iter().any(|arg| arg == "--publish");
+    let positional: Vec<&String> = args.iter().skip(1).filter(|arg| !arg.starts_with("--")).collect();
+    if positional.len() < 2 {
+        println!("Usage: {} <path_to_zip> <author_email> [parent_page_id] [--publish]", args[0]);
+        return;
+    }
+
+    let zip_path = Path::new(positional[0]);
+    let parent_id = match positional.get(2).map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => {
+            println!("Invalid parent page id: {}", positional[2]);
+            return;
+        }
+        None => None,
+    };
+
+    // Read the archive
+    let data = match fs::read(zip_path) {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Failed to read zip file: {}", e);
+            return;
+        }
+    };
+
+    // Establish database connection
+    let pool = db::establish_connection_pool();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => {
+            println!("Database connection error: {}", e);
+            return;
+        }
+    };
+
+    let author = match repository::get_user_by_email(positional[1], &mut conn) {
+        Ok(user) => user.uuid,
+        Err(e) => {
+            println!("Author {} not found: {}", positional[1], e);
+            return;
+        }
+    };
+
+    let options = ImportOptions {
+        parent_id,
+        status: if publish { DocumentationStatus::Published } else { DocumentationStatus::Draft },
+        author,
+    };
+
+    let storage = create_storage(get_storage_config());
+    let report = match DocImportService::import_archive(&mut conn, storage.as_ref(), &data, &options).await {
+        Ok(report) => report,
+        Err(e) => {
+            println!("Import failed: {}", e);
+            return;
+        }
+    };
+
+    for page in &report.pages {
+        println!("Created page {} ({}): {}", page.id, page.slug.as_deref().unwrap_or("-"), page.title);
+    }
+    for warning in &report.warnings {
+        println!("Warning: {}", warning);
+    }
+
+    println!(
+        "Import complete. Pages: {}, Images: {}, Warnings: {}",
+        report.pages.len(),
+        report.images_uploaded,
+        report.warnings.len()
+    );
+}
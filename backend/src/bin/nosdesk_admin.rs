@@ -0,0 +1,408 @@
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use actix_web::web;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel_migrations::MigrationHarness;
+use tracing_subscriber::EnvFilter;
+
+// Import from the parent crate
+extern crate backend;
+use backend::db::{self, DbConnection, Pool, MIGRATIONS};
+use backend::handlers::msgraph_integration;
+use backend::models::{NewBackupJob, SignatureStatus, User};
+use backend::repository;
+use backend::services::admin::{self, AdminError, CheckStatus};
+use backend::services::backup::{self as backup_service, BackupError};
+use backend::services::backup_integrity;
+use backend::utils::storage::{create_storage, get_storage_config};
+
+const USAGE: &str = "\
+Usage: nosdesk-admin <command> [arguments]
+
+Commands:
+  create-admin <name> <email>          Create an admin account (password read from stdin)
+  reset-mfa <email>                    Turn off a user's authenticator app MFA
+  revoke-sessions <email>              Sign a user out of every session
+  backup [--include-sensitive]         Create a backup archive (password read from stdin)
+  verify-backup <archive>              Check an archive's checksums and signature
+  restore <archive> --yes              Restore the database and files from an archive
+  graph-sync [entities] [--delta]      Sync from Microsoft Graph (default: users,devices,groups)
+  migrate                              Apply pending database migrations
+  purge-temp [--max-age-hours <n>]     Remove stale temp uploads (default: 24 hours)
+  diagnostics                          Check configuration and database status";
+
+/// Operator tasks for a Nosdesk installation, run against the same database and storage as the server
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv::dotenv().ok();
+
+    // Library logging goes to stderr, quiet unless RUST_LOG asks for more
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")))
+        .with_writer(io::stderr)
+        .try_init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let rest = &args[1..];
+
+    let result = match command.as_str() {
+        "create-admin" => create_admin(rest),
+        "reset-mfa" => reset_mfa(rest),
+        "revoke-sessions" => revoke_sessions(rest),
+        "backup" => backup(rest).await,
+        "verify-backup" => verify_backup(rest),
+        "restore" => restore(rest).await,
+        "graph-sync" => graph_sync(rest).await,
+        "migrate" => migrate(),
+        "purge-temp" => purge_temp(rest),
+        "diagnostics" => return diagnostics(),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        other => Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Positional arguments, with flags (`--name`) removed
+fn positional(args: &[String]) -> Vec<&str> {
+    args.iter().filter(|arg| !arg.starts_with("--")).map(String::as_str).collect()
+}
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| arg == flag)
+}
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+/// Read a secret from stdin so it doesn't end up in shell history or the process list
+fn read_secret(prompt: &str) -> Result<String, String> {
+    eprint!("{}: ", prompt);
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read {}: {}", prompt.to_lowercase(), e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn connect(pool: &Pool) -> Result<DbConnection, String> {
+    pool.get().map_err(|e| format!("Database connection error: {}", e))
+}
+
+fn find_user(conn: &mut DbConnection, email: &str) -> Result<User, String> {
+    repository::get_user_by_email(email, conn).map_err(|e| match e {
+        diesel::result::Error::NotFound => format!("No user with primary email {}", email),
+        e => format!("Failed to look up user: {}", e),
+    })
+}
+
+fn create_admin(args: &[String]) -> Result<(), String> {
+    let [name, email] = positional(args)[..] else {
+        return Err("Usage: nosdesk-admin create-admin <name> <email>".to_string());
+    };
+    let password = read_secret("Password")?;
+
+    let pool = db::establish_connection_pool();
+    let mut conn = connect(&pool)?;
+    let user = admin::create_admin(&mut conn, name, email, &password).map_err(|e| match e {
+        AdminError::Validation(errors) => errors.join("\n"),
+        e => e.to_string(),
+    })?;
+
+    println!("Created admin {} ({})", user.name, user.uuid);
+    Ok(())
+}
+
+fn reset_mfa(args: &[String]) -> Result<(), String> {
+    let [email] = positional(args)[..] else {
+        return Err("Usage: nosdesk-admin reset-mfa <email>".to_string());
+    };
+
+    let pool = db::establish_connection_pool();
+    let mut conn = connect(&pool)?;
+    let user = find_user(&mut conn, email)?;
+    if !user.mfa_enabled {
+        println!("MFA is not enabled for {}", email);
+        return Ok(());
+    }
+
+    admin::reset_mfa(&mut conn, &user.uuid).map_err(|e| format!("Failed to reset MFA: {}", e))?;
+    println!("MFA disabled for {}; they can set it up again after signing in", email);
+    Ok(())
+}
+
+fn revoke_sessions(args: &[String]) -> Result<(), String> {
+    let [email] = positional(args)[..] else {
+        return Err("Usage: nosdesk-admin revoke-sessions <email>".to_string());
+    };
+
+    let pool = db::establish_connection_pool();
+    let mut conn = connect(&pool)?;
+    let user = find_user(&mut conn, email)?;
+    let (sessions, refresh_tokens) = admin::revoke_all_sessions(&mut conn, &user.uuid)
+        .map_err(|e| format!("Failed to revoke sessions: {}", e))?;
+
+    println!("Revoked {} session(s) and {} refresh token(s) for {}", sessions, refresh_tokens, email);
+    Ok(())
+}
+
+async fn backup(args: &[String]) -> Result<(), String> {
+    let include_sensitive = has_flag(args, "--include-sensitive");
+    let password = if include_sensitive {
+        let password = read_secret("Backup password")?;
+        if password.is_empty() {
+            return Err("A password is required when including sensitive data".to_string());
+        }
+        Some(password)
+    } else {
+        None
+    };
+
+    let pool = db::establish_connection_pool();
+    let storage = create_storage(get_storage_config());
+    let job = {
+        let mut conn = connect(&pool)?;
+        repository::backup::create_backup_job(&mut conn, NewBackupJob {
+            job_type: "export".to_string(),
+            status: "processing".to_string(),
+            include_sensitive,
+            created_by: None,
+            schedule_id: None,
+        })
+        .map_err(|e| format!("Failed to create job: {}", e))?
+    };
+
+    // Storage is driven from a blocking thread, as in the HTTP handler
+    let path = tokio::task::spawn_blocking(move || {
+        let mut conn = connect(&pool)?;
+        backup_service::run_export_job(&mut conn, storage.as_ref(), job.id, include_sensitive, password.as_deref())
+            .map_err(|e| format!("Backup failed: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Backup task panicked: {:?}", e))??;
+
+    println!("Backup written to {}", path.display());
+    Ok(())
+}
+
+fn verify_backup(args: &[String]) -> Result<(), String> {
+    let [archive] = positional(args)[..] else {
+        return Err("Usage: nosdesk-admin verify-backup <archive>".to_string());
+    };
+
+    let verification = backup_integrity::verify_backup(Path::new(archive))
+        .map_err(|e| format!("Could not read backup: {}", e))?;
+
+    println!("Entries checked: {}", verification.entries_checked);
+    println!("Signature: {}", match verification.signature {
+        SignatureStatus::Valid => "valid",
+        SignatureStatus::Invalid => "INVALID",
+        SignatureStatus::Unsigned => "none",
+        SignatureStatus::Unverifiable => "present, but no key configured to check it",
+    });
+    for warning in &verification.warnings {
+        println!("Warning: {}", warning);
+    }
+    for problem in &verification.problems {
+        println!("Problem: {}", problem);
+    }
+
+    if verification.valid {
+        println!("Backup OK");
+        Ok(())
+    } else {
+        Err("Backup FAILED verification".to_string())
+    }
+}
+
+async fn restore(args: &[String]) -> Result<(), String> {
+    let [archive] = positional(args)[..] else {
+        return Err("Usage: nosdesk-admin restore <archive> --yes".to_string());
+    };
+    if !has_flag(args, "--yes") {
+        return Err("Restoring writes the archive's records and files over the current data; pass --yes to continue".to_string());
+    }
+    let backup_path = PathBuf::from(archive);
+
+    // Previewing checks the archive against its manifest first
+    let preview = backup_service::preview_restore(&backup_path).map_err(|e| match e {
+        BackupError::VerificationFailed(problems) => format!("Backup failed verification:\n{}", problems.join("\n")),
+        e => format!("Invalid backup file: {}", e),
+    })?;
+    for warning in &preview.warnings {
+        println!("Warning: {}", warning);
+    }
+
+    let password = if preview.has_encrypted_sensitive {
+        let password = read_secret("Backup password")?;
+        match backup_service::verify_backup_password(&backup_path, &password) {
+            Ok(true) => Some(password),
+            Ok(false) => return Err("Invalid password".to_string()),
+            Err(e) => return Err(format!("Password verification failed: {}", e)),
+        }
+    } else {
+        None
+    };
+
+    let pool = db::establish_connection_pool();
+    let storage = create_storage(get_storage_config());
+    let mut conn = connect(&pool)?;
+
+    let job_id = backup_service::create_restore_job(&mut conn, preview.has_encrypted_sensitive, None)
+        .map_err(|e| format!("Failed to create restore job: {}", e))?;
+    let stats = backup_service::run_restore_job(&mut conn, &backup_path, password.as_deref(), job_id)
+        .map_err(|e| format!("Restore failed: {}", e))?;
+    println!("Restored {} record(s) across {} table(s)", stats.records_restored, stats.tables_restored);
+
+    let files_restored = backup_service::restore_backup_files(&backup_path, storage.as_ref(), |restored, total| {
+        if restored % 100 == 0 || restored == total {
+            eprint!("\rRestoring files: {}/{}", restored, total);
+        }
+    })
+    .await
+    .map_err(|e| {
+        backup_service::fail_restore_job(&mut conn, job_id, &e.to_string());
+        format!("File restore failed: {}", e)
+    })?;
+    eprintln!();
+    println!("Restored {} file(s)", files_restored);
+
+    let thumbnails = backup_service::regenerate_user_thumbnails(&mut conn).await;
+    println!("Regenerated {} user thumbnail(s)", thumbnails);
+    backup_service::complete_restore_job(&mut conn, job_id);
+    Ok(())
+}
+
+async fn graph_sync(args: &[String]) -> Result<(), String> {
+    let entities: Vec<String> = match positional(args).first() {
+        Some(list) => list.split(',').map(|entity| entity.trim().to_string()).filter(|e| !e.is_empty()).collect(),
+        None => vec!["users".to_string(), "devices".to_string(), "groups".to_string()],
+    };
+    let use_delta = has_flag(args, "--delta");
+
+    let pool = db::establish_connection_pool();
+    let session = {
+        let mut conn = connect(&pool)?;
+        msgraph_integration::begin_sync(&mut conn, entities, use_delta).map_err(|e| e.to_string())?
+    };
+    println!("Started sync {}", session.id);
+
+    let result = msgraph_integration::run_sync(web::Data::new(pool), session).await?;
+    println!("{}", result.message);
+    println!("Processed {} item(s) with {} error(s)", result.total_processed, result.total_errors);
+    if result.success {
+        Ok(())
+    } else {
+        Err("Sync did not complete successfully".to_string())
+    }
+}
+
+fn migrate() -> Result<(), String> {
+    let pool = db::establish_connection_pool();
+    let mut conn = connect(&pool)?;
+    let applied = conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+    if applied.is_empty() {
+        println!("Database is up to date");
+    } else {
+        for version in &applied {
+            println!("Applied {}", version);
+        }
+        println!("Applied {} migration(s)", applied.len());
+    }
+    Ok(())
+}
+
+fn purge_temp(args: &[String]) -> Result<(), String> {
+    let max_age = match flag_value(args, "--max-age-hours") {
+        Some(hours) => {
+            let hours: u64 = hours.parse().map_err(|_| format!("Invalid number of hours: {}", hours))?;
+            Duration::from_secs(hours * 60 * 60)
+        }
+        None => admin::TEMP_FILE_MAX_AGE,
+    };
+
+    let temp_dir = admin::temp_dir();
+    let stats = admin::cleanup_temp_files(&temp_dir, max_age);
+    println!(
+        "Checked {} file(s) in {}, removed {} ({} MB freed)",
+        stats.files_checked,
+        temp_dir.display(),
+        stats.files_removed,
+        stats.bytes_freed_mb
+    );
+    for error in &stats.errors {
+        eprintln!("{}", error);
+    }
+    if stats.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} file(s) could not be removed", stats.errors.len()))
+    }
+}
+
+fn diagnostics() -> ExitCode {
+    let mut checks = admin::configuration_checks();
+
+    // Not `establish_connection_pool`, which exits when the database is unreachable
+    if let Ok(database_url) = env::var("DATABASE_URL") {
+        let manager = ConnectionManager::<PgConnection>::new(database_url);
+        match Pool::builder().max_size(1).connection_timeout(Duration::from_secs(5)).build(manager) {
+            Ok(pool) => match pool.get() {
+                Ok(mut conn) => checks.extend(admin::database_checks(&mut conn)),
+                Err(e) => checks.push(database_unreachable(e.to_string())),
+            },
+            Err(e) => checks.push(database_unreachable(e.to_string())),
+        }
+    }
+
+    let width = checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+    for check in &checks {
+        let status = match check.status {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warn",
+            CheckStatus::Error => "FAIL",
+        };
+        println!("[{:<4}] {:<width$}  {}", status, check.name, check.detail, width = width);
+    }
+
+    if checks.iter().any(|check| check.status == CheckStatus::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn database_unreachable(detail: String) -> admin::ConfigCheck {
+    admin::ConfigCheck {
+        name: "Database",
+        status: CheckStatus::Error,
+        detail,
+    }
+}
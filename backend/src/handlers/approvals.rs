@@ -0,0 +1,512 @@
+use actix_web::{web, HttpResponse, Responder, HttpRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::models::{ApproverType, NewApprovalChain, NewApprovalChainStep, TicketApproval, TicketApprovalSummary};
+use crate::repository;
+use crate::services::approvals::{ApprovalChannel, ApprovalDecision, ApprovalError, ApprovalService, DecisionOutcome};
+use crate::utils::rbac::{is_admin, require_admin, require_auth};
+use crate::utils::reset_tokens::TokenType;
+use crate::utils::sse::SseBroadcaster;
+
+// ============================================================================
+// Approval Chain Configuration (admin)
+// ============================================================================
+
+/// Get the approval chain of a category
+pub async fn get_approval_chain(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::approvals::get_chain_for_category(&mut conn, path.into_inner()) {
+        Ok(Some(chain)) => HttpResponse::Ok().json(chain),
+        Ok(None) => HttpResponse::NotFound().json("No approval chain configured for this category"),
+        Err(e) => {
+            error!(error = ?e, "Failed to get approval chain");
+            HttpResponse::InternalServerError().json("Failed to get approval chain")
+        }
+    }
+}
+
+/// A step in an approval chain request
+#[derive(Debug, Deserialize)]
+pub struct ApprovalStepRequest {
+    pub approver_type: String,
+    pub approver_user_uuid: Option<Uuid>,
+    pub approver_group_id: Option<i32>,
+}
+
+/// Request body for creating or replacing an approval chain
+#[derive(Debug, Deserialize)]
+pub struct SaveApprovalChainRequest {
+    pub name: String,
+    pub is_active: Option<bool>,
+    /// Steps in the order they must approve
+    pub steps: Vec<ApprovalStepRequest>,
+}
+
+/// Create or replace the approval chain of a category
+pub async fn save_approval_chain(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: web::Json<SaveApprovalChainRequest>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+
+    let category_id = path.into_inner();
+    let body = body.into_inner();
+
+    if body.name.trim().is_empty() || body.steps.is_empty() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": "A chain needs a name and at least one step"
+        }));
+    }
+
+    let mut steps = Vec::with_capacity(body.steps.len());
+    for (index, step) in body.steps.into_iter().enumerate() {
+        let approver_type = match step.approver_type.parse::<ApproverType>() {
+            Ok(approver_type) => approver_type,
+            Err(e) => return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid request",
+                "message": e
+            })),
+        };
+
+        // Only keep the target that matches the approver type
+        let (approver_user_uuid, approver_group_id) = match approver_type {
+            ApproverType::User => (step.approver_user_uuid, None),
+            ApproverType::Group => (None, step.approver_group_id),
+            ApproverType::Manager => (None, None),
+        };
+        if (approver_type == ApproverType::User && approver_user_uuid.is_none())
+            || (approver_type == ApproverType::Group && approver_group_id.is_none())
+        {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid request",
+                "message": format!("Step {} is missing its approver", index + 1)
+            }));
+        }
+
+        steps.push(NewApprovalChainStep {
+            chain_id: 0, // Set by the repository
+            step_order: 0,
+            approver_type: approver_type.as_str().to_string(),
+            approver_user_uuid,
+            approver_group_id,
+        });
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if repository::categories::get_category_by_id(&mut conn, category_id).is_err() {
+        return HttpResponse::NotFound().json("Category not found");
+    }
+
+    let new_chain = NewApprovalChain {
+        category_id,
+        name: body.name.trim().to_string(),
+        is_active: body.is_active.unwrap_or(true),
+        created_by: Uuid::parse_str(&claims.sub).ok(),
+    };
+
+    match repository::approvals::save_chain(&mut conn, new_chain, steps) {
+        Ok(chain) => HttpResponse::Ok().json(chain),
+        Err(e) => {
+            error!(category_id, error = ?e, "Failed to save approval chain");
+            HttpResponse::InternalServerError().json("Failed to save approval chain")
+        }
+    }
+}
+
+/// Remove the approval chain of a category
+/// Tickets already awaiting approval keep their steps.
+pub async fn delete_approval_chain(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::approvals::delete_chain_for_category(&mut conn, path.into_inner()) {
+        Ok(0) => HttpResponse::NotFound().json("No approval chain configured for this category"),
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            error!(error = ?e, "Failed to delete approval chain");
+            HttpResponse::InternalServerError().json("Failed to delete approval chain")
+        }
+    }
+}
+
+// ============================================================================
+// Ticket Approvals
+// ============================================================================
+
+/// Get the approval steps and audit trail of a ticket
+pub async fn get_ticket_approvals(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_auth(&req) {
+        return e;
+    }
+
+    let ticket_id = path.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let ticket = match repository::tickets::get_ticket_by_id(&mut conn, ticket_id) {
+        Ok(ticket) => ticket,
+        Err(_) => return HttpResponse::NotFound().json("Ticket not found"),
+    };
+
+    let approvals = repository::approvals::get_ticket_approvals(&mut conn, ticket_id);
+    let events = repository::approvals::get_events_for_ticket(&mut conn, ticket_id);
+    match (approvals, events) {
+        (Ok(approvals), Ok(events)) => HttpResponse::Ok().json(TicketApprovalSummary {
+            ticket_id,
+            approval_status: ticket.approval_status,
+            approvals,
+            events,
+        }),
+        (Err(e), _) | (_, Err(e)) => {
+            error!(ticket_id, error = ?e, "Failed to get ticket approvals");
+            HttpResponse::InternalServerError().json("Failed to get ticket approvals")
+        }
+    }
+}
+
+/// Request body for approving or rejecting from the app
+#[derive(Debug, Default, Deserialize)]
+pub struct DecisionRequest {
+    pub comment: Option<String>,
+}
+
+/// Approve a pending step of a ticket
+pub async fn approve_ticket_step(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    path: web::Path<(i32, i32)>,
+    body: Option<web::Json<DecisionRequest>>,
+) -> impl Responder {
+    decide_from_app(req, pool, sse_state, path.into_inner(), body, true).await
+}
+
+/// Reject a pending step of a ticket
+pub async fn reject_ticket_step(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    path: web::Path<(i32, i32)>,
+    body: Option<web::Json<DecisionRequest>>,
+) -> impl Responder {
+    decide_from_app(req, pool, sse_state, path.into_inner(), body, false).await
+}
+
+async fn decide_from_app(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    (ticket_id, approval_id): (i32, i32),
+    body: Option<web::Json<DecisionRequest>>,
+    approve: bool,
+) -> HttpResponse {
+    let claims = match require_auth(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let actor = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    // The step must belong to the ticket in the URL
+    match repository::approvals::get_ticket_approval(&mut conn, approval_id) {
+        Ok(approval) if approval.ticket_id == ticket_id => {}
+        Ok(_) | Err(diesel::result::Error::NotFound) => return HttpResponse::NotFound().json("Approval not found"),
+        Err(_) => return HttpResponse::InternalServerError().json("Failed to get approval"),
+    }
+
+    let decision = ApprovalDecision {
+        approve,
+        comment: body.and_then(|b| b.into_inner().comment),
+        channel: ApprovalChannel::Web,
+        token_hash: None,
+    };
+
+    match ApprovalService::decide(&mut conn, approval_id, actor, is_admin(&claims), decision).await {
+        Ok(outcome) => {
+            broadcast_outcome(&sse_state, &outcome, &claims.sub).await;
+            HttpResponse::Ok().json(decision_response(&outcome))
+        }
+        Err(e) => approval_error_response(e),
+    }
+}
+
+/// Pending approval steps the current user can decide
+/// Admins also see steps whose approver could not be resolved.
+pub async fn get_my_pending_approvals(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let claims = match require_auth(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let mut approvals = match repository::approvals::get_pending_approvals_for_user(&mut conn, &user_uuid) {
+        Ok(approvals) => approvals,
+        Err(e) => {
+            error!(error = ?e, "Failed to get pending approvals");
+            return HttpResponse::InternalServerError().json("Failed to get pending approvals");
+        }
+    };
+
+    if is_admin(&claims) {
+        if let Ok(unassigned) = repository::approvals::get_unassigned_pending_approvals(&mut conn) {
+            approvals.extend(unassigned);
+        }
+    }
+
+    HttpResponse::Ok().json(approvals)
+}
+
+// ============================================================================
+// Email Link Decisions (public, token-authenticated)
+// ============================================================================
+
+/// Request body for checking an approval link
+#[derive(Debug, Deserialize)]
+pub struct ValidateApprovalTokenRequest {
+    pub token: String,
+}
+
+/// What an approval link is for, shown before the approver decides
+#[derive(Debug, Serialize)]
+pub struct ValidateApprovalTokenResponse {
+    pub valid: bool,
+    pub ticket_id: Option<i32>,
+    pub ticket_title: Option<String>,
+    pub approval: Option<TicketApproval>,
+    pub message: Option<String>,
+}
+
+impl ValidateApprovalTokenResponse {
+    fn invalid(message: &str) -> Self {
+        Self {
+            valid: false,
+            ticket_id: None,
+            ticket_title: None,
+            approval: None,
+            message: Some(message.to_string()),
+        }
+    }
+}
+
+/// Validate an approval link without consuming it
+pub async fn validate_approval_token(
+    pool: web::Data<Pool>,
+    body: web::Json<ValidateApprovalTokenRequest>,
+) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let token = match repository::reset_tokens::find_valid_token(
+        &mut conn,
+        &body.token,
+        TokenType::ApprovalDecision.as_str(),
+    ) {
+        Ok(token) => token,
+        Err(message) => return HttpResponse::Ok().json(ValidateApprovalTokenResponse::invalid(&message)),
+    };
+
+    let Some(approval_id) = token_approval_id(&token.metadata) else {
+        return HttpResponse::Ok().json(ValidateApprovalTokenResponse::invalid("Invalid approval link"));
+    };
+
+    let approval = match repository::approvals::get_ticket_approval(&mut conn, approval_id) {
+        Ok(approval) => approval,
+        Err(_) => return HttpResponse::Ok().json(ValidateApprovalTokenResponse::invalid("This approval no longer exists")),
+    };
+    if approval.status != crate::models::ApprovalStepStatus::Pending.as_str() {
+        return HttpResponse::Ok().json(ValidateApprovalTokenResponse::invalid("This approval has already been decided"));
+    }
+
+    let ticket_title = repository::tickets::get_ticket_by_id(&mut conn, approval.ticket_id)
+        .ok()
+        .map(|ticket| ticket.title);
+
+    HttpResponse::Ok().json(ValidateApprovalTokenResponse {
+        valid: true,
+        ticket_id: Some(approval.ticket_id),
+        ticket_title,
+        approval: Some(approval),
+        message: None,
+    })
+}
+
+/// Request body for deciding from an email link
+#[derive(Debug, Deserialize)]
+pub struct TokenDecisionRequest {
+    pub token: String,
+    /// "approve" or "reject"
+    pub decision: String,
+    pub comment: Option<String>,
+}
+
+/// Approve or reject using an emailed link (the token is consumed with the decision)
+pub async fn decide_with_token(
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    body: web::Json<TokenDecisionRequest>,
+) -> impl Responder {
+    let body = body.into_inner();
+    let approve = match body.decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        _ => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": "Decision must be 'approve' or 'reject'"
+        })),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let token = match repository::reset_tokens::find_valid_token(
+        &mut conn,
+        &body.token,
+        TokenType::ApprovalDecision.as_str(),
+    ) {
+        Ok(token) => token,
+        Err(message) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid token",
+            "message": message
+        })),
+    };
+
+    let Some(approval_id) = token_approval_id(&token.metadata) else {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid token",
+            "message": "Invalid approval link"
+        }));
+    };
+
+    let decision = ApprovalDecision {
+        approve,
+        comment: body.comment,
+        channel: ApprovalChannel::Email,
+        token_hash: Some(token.token_hash.clone()),
+    };
+
+    // The link acts as its recipient; admin rights are not carried over to email links
+    match ApprovalService::decide(&mut conn, approval_id, token.user_uuid, false, decision).await {
+        Ok(outcome) => {
+            broadcast_outcome(&sse_state, &outcome, &token.user_uuid.to_string()).await;
+            HttpResponse::Ok().json(decision_response(&outcome))
+        }
+        Err(e) => approval_error_response(e),
+    }
+}
+
+fn token_approval_id(metadata: &Option<serde_json::Value>) -> Option<i32> {
+    metadata
+        .as_ref()?
+        .get("approval_id")?
+        .as_i64()
+        .and_then(|id| i32::try_from(id).ok())
+}
+
+fn decision_response(outcome: &DecisionOutcome) -> serde_json::Value {
+    json!({
+        "success": true,
+        "approval": outcome.approval,
+        "ticket_approval_status": outcome.ticket_status.as_str(),
+        "next_step": outcome.next_step,
+    })
+}
+
+async fn broadcast_outcome(
+    sse_state: &web::Data<crate::handlers::sse::SseState>,
+    outcome: &DecisionOutcome,
+    updated_by: &str,
+) {
+    SseBroadcaster::broadcast_ticket_updated(
+        sse_state,
+        outcome.approval.ticket_id,
+        "approval_status",
+        json!(outcome.ticket_status.as_str()),
+        updated_by,
+    ).await;
+}
+
+fn approval_error_response(e: ApprovalError) -> HttpResponse {
+    match e {
+        ApprovalError::NotFound => HttpResponse::NotFound().json("Approval not found"),
+        ApprovalError::NotPending => HttpResponse::Conflict().json(json!({
+            "error": "Conflict",
+            "message": e.to_string()
+        })),
+        ApprovalError::NotAuthorized => HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": e.to_string()
+        })),
+        ApprovalError::LinkUsed => HttpResponse::BadRequest().json(json!({
+            "error": "Invalid token",
+            "message": e.to_string()
+        })),
+        ApprovalError::DatabaseError(ref err) => {
+            error!(error = ?err, "Approval decision failed");
+            HttpResponse::InternalServerError().json("Failed to record decision")
+        }
+    }
+}
@@ -12,6 +12,7 @@ use crate::models::{
     UserRegistration, UserResponse
 };
 use crate::repository;
+use crate::services::admin::AdminError;
 use crate::utils::{self, ValidationError, parse_uuid};
 use crate::utils::auth::{hash_password, validate_password};
 use crate::utils::mfa;
@@ -1085,101 +1086,8 @@ pub async fn setup_initial_admin(
         }
     }
 
-    // Comprehensive input validation using our validation utilities
-    let mut validation_errors = Vec::new();
-
-    // Validate name
-    let trimmed_name = admin_data.name.trim();
-    if trimmed_name.is_empty() {
-        validation_errors.push("name: Name is required".to_string());
-    } else if trimmed_name.len() > 255 {
-        validation_errors.push("name: Name must be less than 255 characters".to_string());
-    }
-
-    // Validate email
-    let trimmed_email = admin_data.email.trim();
-    if trimmed_email.is_empty() {
-        validation_errors.push("email: Email is required".to_string());
-    } else if trimmed_email.len() > 255 {
-        validation_errors.push("email: Email must be less than 255 characters".to_string());
-    } else if !trimmed_email.contains('@') || !trimmed_email.contains('.') {
-        validation_errors.push("email: Invalid email format".to_string());
-    }
-
-    // Validate password
-    if admin_data.password.len() < 8 {
-        validation_errors.push("password: Password must be at least 8 characters long".to_string());
-    } else if admin_data.password.len() > 128 {
-        validation_errors.push("password: Password must be less than 128 characters".to_string());
-    }
-
-    // If there are validation errors, return them
-    if !validation_errors.is_empty() {
-        return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "Validation failed",
-            "errors": validation_errors
-        }));
-    }
-
-    // Hash the password
-    let password_hash = match hash_password(&admin_data.password) {
-        Ok(hash) => hash,
-        Err(e) => {
-            error!(error = ?e, "Error hashing password");
-            return HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error processing password"
-            }));
-        }
-    };
-
-    // Generate UUID for the admin user
-    let _user_uuid = Uuid::now_v7();
-
-    // Create the admin user using convenience function with email
-    let (normalized_name, normalized_email) = utils::normalization::normalize_user_data(&admin_data.name, &admin_data.email);
-    let (new_user, email) = utils::NewUserBuilder::admin_user(
-        normalized_name,
-        normalized_email.clone()
-    ).build_with_email();
-
-    match repository::user_helpers::create_user_with_email(new_user, email, true, Some("manual".to_string()), &mut conn) {
-        Ok((created_user, _email_entry)) => {
-            // Create local auth identity with password hash
-            use diesel::prelude::*;
-            use crate::schema::user_auth_identities;
-
-            #[derive(diesel::Insertable)]
-            #[diesel(table_name = user_auth_identities)]
-            struct NewLocalAuthIdentity {
-                user_uuid: Uuid,
-                provider_type: String,
-                external_id: String,
-                email: Option<String>,
-                password_hash: Option<String>,
-            }
-
-            let auth_identity = NewLocalAuthIdentity {
-                user_uuid: created_user.uuid,
-                provider_type: "local".to_string(),
-                external_id: normalized_email.clone(),
-                email: Some(normalized_email.clone()),
-                password_hash: Some(password_hash),
-            };
-
-            if let Err(e) = diesel::insert_into(user_auth_identities::table)
-                .values(&auth_identity)
-                .execute(&mut conn) {
-                error!(error = ?e, "Error creating auth identity");
-                // Rollback by deleting the user
-                let _ = repository::users::delete_user(&created_user.uuid, &mut conn);
-                return HttpResponse::InternalServerError().json(json!({
-                    "status": "error",
-                    "message": "Error creating user authentication"
-                }));
-            }
-
+    match crate::services::admin::create_admin(&mut conn, &admin_data.name, &admin_data.email, &admin_data.password) {
+        Ok(created_user) => {
             info!(user_name = %created_user.name, "Initial admin user created successfully");
 
             let response = crate::models::AdminSetupResponse {
@@ -1189,16 +1097,23 @@ pub async fn setup_initial_admin(
             };
             HttpResponse::Created().json(response)
         },
+        Err(AdminError::Validation(validation_errors)) => {
+            HttpResponse::BadRequest().json(json!({
+                "status": "error",
+                "message": "Validation failed",
+                "errors": validation_errors
+            }))
+        },
         Err(e) => {
             error!(error = ?e, "Error creating admin user");
-            
+
             // Provide more specific error messages for common issues
-            let error_message = if format!("{:?}", e).contains("duplicate") || format!("{:?}", e).contains("unique") {
-                "Email address already exists in the system"
-            } else {
-                "Error creating admin user"
+            let error_message = match e {
+                AdminError::EmailExists => "Email address already exists in the system",
+                AdminError::PasswordHash => "Error processing password",
+                _ => "Error creating admin user",
             };
-            
+
             HttpResponse::InternalServerError().json(json!({
                 "status": "error",
                 "message": error_message
@@ -1502,14 +1417,7 @@ pub async fn mfa_disable(
     }
 
     // Disable MFA
-    let mfa_update = crate::models::UserMfaUpdate {
-        mfa_enabled: Some(false),
-        mfa_secret: None, // Clear the secret
-        mfa_backup_codes: Some(serde_json::Value::Null), // Clear backup codes
-        updated_at: Some(chrono::Utc::now().naive_utc()),
-    };
-
-    match repository::update_user_mfa(&user_uuid, mfa_update, &mut conn) {
+    match crate::services::admin::reset_mfa(&mut conn, &user_uuid) {
         Ok(_) => {
             tracing::info!("MFA disabled for user: {} (scope: {})", user_uuid, claims.scope);
             HttpResponse::Ok().json(json!({
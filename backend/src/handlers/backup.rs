@@ -4,21 +4,28 @@ use diesel::prelude::*;
 use futures::StreamExt;
 use serde_json::json;
 use std::io::Write;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::db::Pool;
 use crate::models::{
     Claims, StartBackupExportRequest, ExecuteRestoreRequest, BackupJobResponse,
-    NewBackupJob, BackupJobUpdate,
+    NewBackupJob, BackupJobUpdate, BackupScheduleRequest, NewBackupSchedule, BackupScheduleUpdate,
+    SelectiveRestoreRequest, RestorePreview,
 };
 use crate::repository::backup as backup_repo;
 use crate::services::backup as backup_service;
-use crate::utils::image::generate_user_avatar_thumbnail;
+use crate::services::backup::BackupError;
+use crate::services::backup_integrity;
+use crate::services::backup_schedule;
+use crate::services::selective_restore::{self, RestoreSelection};
+use crate::utils::storage::Storage;
 
 /// Start a backup export job
 /// POST /api/admin/backup/export
 pub async fn start_export(
     pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
     req: actix_web::HttpRequest,
     body: web::Json<StartBackupExportRequest>,
 ) -> impl Responder {
@@ -56,6 +63,7 @@ pub async fn start_export(
         status: "processing".to_string(),
         include_sensitive: body.include_sensitive,
         created_by: Some(user_uuid),
+        schedule_id: None,
     };
 
     let job = match backup_repo::create_backup_job(&mut conn, new_job) {
@@ -67,9 +75,10 @@ pub async fn start_export(
     let include_sensitive = body.include_sensitive;
     let password = body.password.clone();
 
-    // Run backup in background
+    // Run backup in background (on a blocking thread: it reads files through the storage backend)
     let pool_clone = pool.clone();
-    tokio::spawn(async move {
+    let storage = storage.get_ref().clone();
+    tokio::task::spawn_blocking(move || {
         let mut conn = match pool_clone.get() {
             Ok(conn) => conn,
             Err(e) => {
@@ -78,22 +87,8 @@ pub async fn start_export(
             }
         };
 
-        match backup_service::create_backup(&mut conn, job_id, include_sensitive, password.as_deref()) {
-            Ok(path) => {
-                log::info!("Backup completed successfully: {:?}", path);
-            }
-            Err(e) => {
-                log::error!("Backup failed: {}", e);
-                // Update job with error
-                let _ = backup_repo::update_backup_job(&mut conn, job_id, BackupJobUpdate {
-                    status: Some("failed".to_string()),
-                    file_path: None,
-                    file_size: None,
-                    error_message: Some(e.to_string()),
-                    completed_at: Some(chrono::Utc::now().naive_utc()),
-                });
-            }
-        }
+        // Failures are recorded on the job
+        let _ = backup_service::run_export_job(&mut conn, storage.as_ref(), job_id, include_sensitive, password.as_deref());
     });
 
     HttpResponse::Accepted().json(BackupJobResponse::from(job))
@@ -165,6 +160,80 @@ pub async fn get_job(
     }
 }
 
+/// Response for an archive that doesn't match its manifest
+fn verification_failed(problems: Vec<String>) -> HttpResponse {
+    HttpResponse::UnprocessableEntity().json(json!({
+        "error": "Backup failed verification",
+        "problems": problems,
+    }))
+}
+
+/// Preview an archive off the async runtime; previewing hashes every entry
+async fn preview_archive(file_path: &std::path::Path) -> Result<RestorePreview, BackupError> {
+    let file_path = file_path.to_path_buf();
+    tokio::task::spawn_blocking(move || backup_service::preview_restore(&file_path))
+        .await
+        .unwrap_or_else(|e| Err(BackupError::IoError(std::io::Error::other(e))))
+}
+
+/// Check a backup password off the async runtime; deriving the key is deliberately slow
+async fn check_archive_password(file_path: &std::path::Path, password: &str) -> Result<bool, BackupError> {
+    let (file_path, password) = (file_path.to_path_buf(), password.to_string());
+    tokio::task::spawn_blocking(move || backup_service::verify_backup_password(&file_path, &password))
+        .await
+        .unwrap_or_else(|e| Err(BackupError::IoError(std::io::Error::other(e))))
+}
+
+/// Check a backup archive against its manifest without restoring it
+/// GET /api/admin/backup/jobs/{id}/verify
+pub async fn verify_backup(
+    pool: web::Data<Pool>,
+    path: web::Path<String>,
+    req: actix_web::HttpRequest,
+) -> impl Responder {
+    // Get authenticated admin user
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+
+    // Check if user is admin
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let job_id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json(json!({"error": "Invalid job ID"})),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    let job = match backup_repo::get_backup_job(&mut conn, job_id) {
+        Ok(job) => job,
+        Err(diesel::result::Error::NotFound) => return HttpResponse::NotFound().json(json!({"error": "Job not found"})),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Failed to get job: {}", e)})),
+    };
+
+    let file_path = match job.file_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => return HttpResponse::BadRequest().json(json!({"error": "No backup file available"})),
+    };
+
+    // Hashing reads the whole archive
+    match tokio::task::spawn_blocking(move || backup_integrity::verify_backup(&file_path)).await {
+        Ok(Ok(verification)) => HttpResponse::Ok().json(verification),
+        Ok(Err(e)) => HttpResponse::UnprocessableEntity().json(json!({
+            "error": "Backup could not be read",
+            "problems": [e.to_string()],
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Verification failed: {}", e)})),
+    }
+}
+
 /// Download a completed backup
 /// GET /api/admin/backup/download/{id}
 pub async fn download_backup(
@@ -318,6 +387,7 @@ pub async fn upload_restore(
         status: "pending".to_string(),
         include_sensitive: false, // Will be updated after preview
         created_by: Some(user_uuid),
+        schedule_id: None,
     };
 
     let job = match backup_repo::create_backup_job(&mut conn, new_job) {
@@ -379,8 +449,14 @@ pub async fn preview_restore(
         None => return HttpResponse::BadRequest().json(json!({"error": "No backup file available"})),
     };
 
-    match backup_service::preview_restore(&file_path) {
-        Ok(preview) => HttpResponse::Ok().json(preview),
+    match preview_archive(&file_path).await {
+        Ok(mut preview) => {
+            if let Err(e) = selective_restore::mark_ids_in_use(&mut conn, &mut preview.entities) {
+                log::warn!("Failed to check restorable ids against the database: {}", e);
+            }
+            HttpResponse::Ok().json(preview)
+        }
+        Err(BackupError::VerificationFailed(problems)) => verification_failed(problems),
         Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Failed to preview: {}", e)})),
     }
 }
@@ -389,6 +465,7 @@ pub async fn preview_restore(
 /// POST /api/admin/backup/restore/{id}/execute
 pub async fn execute_restore(
     pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
     path: web::Path<String>,
     req: actix_web::HttpRequest,
     body: web::Json<ExecuteRestoreRequest>,
@@ -430,15 +507,16 @@ pub async fn execute_restore(
     };
 
     // Verify password if backup has encrypted sensitive data
-    let preview = match backup_service::preview_restore(&file_path) {
+    let preview = match preview_archive(&file_path).await {
         Ok(p) => p,
+        Err(BackupError::VerificationFailed(problems)) => return verification_failed(problems),
         Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Failed to preview: {}", e)})),
     };
 
     if preview.has_encrypted_sensitive {
         match &body.password {
             Some(password) => {
-                match backup_service::verify_backup_password(&file_path, password) {
+                match check_archive_password(&file_path, password).await {
                     Ok(true) => {}
                     Ok(false) => return HttpResponse::BadRequest().json(json!({"error": "Invalid password"})),
                     Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Password verification failed: {}", e)})),
@@ -448,46 +526,151 @@ pub async fn execute_restore(
         }
     }
 
-    // Update job status
-    let _ = backup_repo::update_backup_job(&mut conn, job_id, BackupJobUpdate {
-        status: Some("processing".to_string()),
-        file_path: None,
-        file_size: None,
-        error_message: None,
-        completed_at: None,
-    });
+    // Restore the database, recording progress on the job
+    let mut restore_conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+    let stats = {
+        let file_path = file_path.clone();
+        let password = body.password.clone();
+        tokio::task::spawn_blocking(move || backup_service::run_restore_job(&mut restore_conn, &file_path, password.as_deref(), job_id)).await
+    };
+    let stats = match stats {
+        Ok(Ok(stats)) => stats,
+        Ok(Err(e)) => return HttpResponse::InternalServerError().json(json!({"error": format!("Restore failed: {}", e)})),
+        Err(e) => {
+            backup_service::fail_restore_job(&mut conn, job_id, &e.to_string());
+            return HttpResponse::InternalServerError().json(json!({"error": format!("Restore failed: {}", e)}));
+        }
+    };
+
+    let restored = backup_service::restore_backup_files(&file_path, storage.get_ref().as_ref(), |restored, total| {
+        let _ = backup_repo::set_backup_job_progress(&mut conn, job_id, "Restoring files", restored as i64, Some(total as i64));
+    })
+    .await;
+    match restored {
+        Ok(files_restored) => {
+            // Regenerate thumbnails for all users with avatars
+            let thumbnails_regenerated = backup_service::regenerate_user_thumbnails(&mut conn).await;
+            log::info!("Regenerated {} user thumbnails after restore", thumbnails_regenerated);
 
-    // Restore files (database restore would need more work)
-    match backup_service::restore_backup_files(&file_path) {
-        Ok(count) => {
-            let _ = backup_repo::update_backup_job(&mut conn, job_id, BackupJobUpdate {
-                status: Some("completed".to_string()),
-                file_path: None,
-                file_size: None,
-                error_message: None,
-                completed_at: Some(chrono::Utc::now().naive_utc()),
-            });
+            backup_service::complete_restore_job(&mut conn, job_id);
 
             HttpResponse::Ok().json(json!({
                 "success": true,
-                "files_restored": count,
-                "message": "Files restored successfully. Database restore is not yet implemented."
+                "message": "System restored successfully",
+                "tables_restored": stats.tables_restored,
+                "records_restored": stats.records_restored,
+                "files_restored": files_restored,
+                "thumbnails_regenerated": thumbnails_regenerated
             }))
         }
         Err(e) => {
-            let _ = backup_repo::update_backup_job(&mut conn, job_id, BackupJobUpdate {
-                status: Some("failed".to_string()),
-                file_path: None,
-                file_size: None,
-                error_message: Some(e.to_string()),
-                completed_at: Some(chrono::Utc::now().naive_utc()),
-            });
-
+            backup_service::fail_restore_job(&mut conn, job_id, &e.to_string());
             HttpResponse::InternalServerError().json(json!({"error": format!("Restore failed: {}", e)}))
         }
     }
 }
 
+/// Restore individual tickets and documentation pages from a backup
+/// POST /api/admin/backup/restore/{id}/selective
+pub async fn selective_restore(
+    pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<String>,
+    req: actix_web::HttpRequest,
+    body: web::Json<SelectiveRestoreRequest>,
+) -> impl Responder {
+    // Get authenticated admin user
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+
+    // Check if user is admin
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json(json!({"error": "Invalid user UUID"})),
+    };
+
+    let job_id = match Uuid::parse_str(&path.into_inner()) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json(json!({"error": "Invalid job ID"})),
+    };
+
+    let request = body.into_inner();
+    if request.tickets.is_empty() && request.documentation_pages.is_empty() {
+        return HttpResponse::BadRequest().json(json!({"error": "Select at least one ticket or documentation page"}));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    // Any backup with an archive will do: uploaded restores and local exports alike
+    let job = match backup_repo::get_backup_job(&mut conn, job_id) {
+        Ok(job) => job,
+        Err(diesel::result::Error::NotFound) => return HttpResponse::NotFound().json(json!({"error": "Job not found"})),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Failed to get job: {}", e)})),
+    };
+
+    let file_path = match job.file_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => return HttpResponse::BadRequest().json(json!({"error": "No backup file available"})),
+    };
+
+    let verify_path = file_path.clone();
+    match tokio::task::spawn_blocking(move || backup_integrity::verify_backup(&verify_path)).await {
+        Ok(Ok(verification)) if verification.valid => {}
+        Ok(Ok(verification)) => return verification_failed(verification.problems),
+        Ok(Err(e)) => return HttpResponse::BadRequest().json(json!({"error": format!("Invalid backup file: {}", e)})),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Verification failed: {}", e)})),
+    }
+
+    let selection = RestoreSelection {
+        tickets: request.tickets,
+        documentation_pages: request.documentation_pages,
+        include_subpages: request.include_subpages.unwrap_or(true),
+        acting_user: user_uuid,
+    };
+
+    let archive_path = file_path.clone();
+    let restored = tokio::task::spawn_blocking(move || {
+        selective_restore::restore_selection(&mut conn, &archive_path, &selection)
+    })
+    .await;
+
+    let mut report = match restored {
+        Ok(Ok(report)) => report,
+        Ok(Err(e)) => {
+            log::error!("Selective restore from backup {} failed: {}", job_id, e);
+            return HttpResponse::InternalServerError().json(json!({"error": format!("Restore failed: {}", e)}));
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Restore failed: {}", e)})),
+    };
+
+    // Records are committed by now; a file that fails to copy is reported, not rolled back
+    match selective_restore::restore_selected_files(&file_path, storage.get_ref().as_ref(), &report.tickets).await {
+        Ok(count) => report.files_restored = count,
+        Err(e) => report.warnings.push(format!("Ticket files could not be restored: {}", e)),
+    }
+
+    log::info!(
+        "Selective restore from backup {}: {} tickets, {} pages, {} files",
+        job_id,
+        report.tickets.len(),
+        report.documentation_pages.len(),
+        report.files_restored
+    );
+    HttpResponse::Ok().json(report)
+}
+
 /// Delete a backup job and its associated file
 /// DELETE /api/admin/backup/jobs/{id}
 pub async fn delete_job(
@@ -537,6 +720,228 @@ pub async fn delete_job(
     }
 }
 
+// ============================================================================
+// BACKUP SCHEDULES
+// ============================================================================
+
+/// Check a schedule request; returns the next run time for an enabled schedule
+fn validate_schedule(body: &BackupScheduleRequest) -> Result<Option<chrono::NaiveDateTime>, String> {
+    if body.name.trim().is_empty() {
+        return Err("Name is required".to_string());
+    }
+    let next_run = backup_schedule::next_run(&body.cron_expression, chrono::Utc::now().naive_utc())
+        .map_err(|e| format!("Invalid cron expression: {}", e))?;
+    if next_run.is_none() {
+        return Err("Cron expression never matches".to_string());
+    }
+
+    let has_password_source = [&body.password_env, &body.password_file]
+        .iter()
+        .any(|source| source.as_deref().is_some_and(|value| !value.trim().is_empty()));
+    if body.include_sensitive && !has_password_source {
+        return Err("A password environment variable or file is required when including sensitive data".to_string());
+    }
+    if body.keep_daily.is_some_and(|keep| keep < 0) || body.keep_weekly.is_some_and(|keep| keep < 0) {
+        return Err("Retention counts cannot be negative".to_string());
+    }
+    if body.upload_remote && backup_schedule::remote_storage().is_none() {
+        return Err("Off-site upload requires BACKUP_S3_BUCKET to be configured".to_string());
+    }
+
+    Ok(if body.enabled.unwrap_or(true) { next_run } else { None })
+}
+
+fn optional_setting(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// List backup schedules
+/// GET /api/admin/backup/schedules
+pub async fn get_schedules(
+    pool: web::Data<Pool>,
+    req: actix_web::HttpRequest,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    match backup_repo::get_backup_schedules(&mut conn) {
+        Ok(schedules) => HttpResponse::Ok().json(schedules),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Failed to get schedules: {}", e)})),
+    }
+}
+
+/// Create a backup schedule
+/// POST /api/admin/backup/schedules
+pub async fn create_schedule(
+    pool: web::Data<Pool>,
+    req: actix_web::HttpRequest,
+    body: web::Json<BackupScheduleRequest>,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json(json!({"error": "Invalid user UUID"})),
+    };
+
+    let next_run_at = match validate_schedule(&body) {
+        Ok(next_run_at) => next_run_at,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e})),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    let new_schedule = NewBackupSchedule {
+        name: body.name.trim().to_string(),
+        cron_expression: body.cron_expression.trim().to_string(),
+        include_sensitive: body.include_sensitive,
+        password_env: optional_setting(&body.password_env),
+        password_file: optional_setting(&body.password_file),
+        keep_daily: body.keep_daily.unwrap_or(7),
+        keep_weekly: body.keep_weekly.unwrap_or(4),
+        upload_remote: body.upload_remote,
+        enabled: body.enabled.unwrap_or(true),
+        next_run_at,
+        created_by: Some(user_uuid),
+    };
+
+    match backup_repo::create_backup_schedule(&mut conn, new_schedule) {
+        Ok(schedule) => HttpResponse::Created().json(schedule),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Failed to create schedule: {}", e)})),
+    }
+}
+
+/// Replace a backup schedule's settings
+/// PUT /api/admin/backup/schedules/{id}
+pub async fn update_schedule(
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    req: actix_web::HttpRequest,
+    body: web::Json<BackupScheduleRequest>,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let next_run_at = match validate_schedule(&body) {
+        Ok(next_run_at) => next_run_at,
+        Err(e) => return HttpResponse::BadRequest().json(json!({"error": e})),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    let update = BackupScheduleUpdate {
+        name: Some(body.name.trim().to_string()),
+        cron_expression: Some(body.cron_expression.trim().to_string()),
+        include_sensitive: Some(body.include_sensitive),
+        password_env: Some(optional_setting(&body.password_env)),
+        password_file: Some(optional_setting(&body.password_file)),
+        keep_daily: body.keep_daily,
+        keep_weekly: body.keep_weekly,
+        upload_remote: Some(body.upload_remote),
+        enabled: Some(body.enabled.unwrap_or(true)),
+        next_run_at: Some(next_run_at),
+    };
+
+    match backup_repo::update_backup_schedule(&mut conn, path.into_inner(), update) {
+        Ok(schedule) => HttpResponse::Ok().json(schedule),
+        Err(diesel::result::Error::NotFound) => HttpResponse::NotFound().json(json!({"error": "Schedule not found"})),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Failed to update schedule: {}", e)})),
+    }
+}
+
+/// Delete a backup schedule (its backups are kept)
+/// DELETE /api/admin/backup/schedules/{id}
+pub async fn delete_schedule(
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    req: actix_web::HttpRequest,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    match backup_repo::delete_backup_schedule(&mut conn, path.into_inner()) {
+        Ok(0) => HttpResponse::NotFound().json(json!({"error": "Schedule not found"})),
+        Ok(_) => HttpResponse::Ok().json(json!({"success": true, "message": "Schedule deleted"})),
+        Err(e) => HttpResponse::InternalServerError().json(json!({"error": format!("Failed to delete schedule: {}", e)})),
+    }
+}
+
+/// Run a backup schedule now, outside its cron timing
+/// POST /api/admin/backup/schedules/{id}/run
+pub async fn run_schedule(
+    pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<i32>,
+    req: actix_web::HttpRequest,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({"error": "Authentication required"})),
+    };
+    if claims.role != "admin" {
+        return HttpResponse::Forbidden().json(json!({"error": "Admin access required"}));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Database error: {}", e)})),
+    };
+
+    let schedule = match backup_repo::get_backup_schedule(&mut conn, path.into_inner()) {
+        Ok(schedule) => schedule,
+        Err(diesel::result::Error::NotFound) => return HttpResponse::NotFound().json(json!({"error": "Schedule not found"})),
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Failed to get schedule: {}", e)})),
+    };
+    let _ = backup_repo::set_backup_schedule_last_run(&mut conn, schedule.id, chrono::Utc::now().naive_utc());
+
+    let pool = pool.get_ref().clone();
+    let storage = storage.get_ref().clone();
+    tokio::spawn(async move {
+        if let Err(e) = backup_schedule::run_schedule(pool, storage, schedule).await {
+            log::error!("Scheduled backup failed: {}", e);
+        }
+    });
+
+    HttpResponse::Accepted().json(json!({"success": true, "message": "Backup started"}))
+}
+
 // ============================================================================
 // ONBOARDING RESTORE ENDPOINTS (Unauthenticated - only work during setup)
 // ============================================================================
@@ -620,8 +1025,12 @@ pub async fn onboarding_upload_restore(
     };
 
     // Validate and get preview
-    let preview = match backup_service::preview_restore(&filepath) {
+    let preview = match preview_archive(&filepath).await {
         Ok(p) => p,
+        Err(BackupError::VerificationFailed(problems)) => {
+            let _ = std::fs::remove_file(&filepath);
+            return verification_failed(problems);
+        }
         Err(e) => {
             let _ = std::fs::remove_file(&filepath);
             return HttpResponse::BadRequest().json(json!({"error": format!("Invalid backup file: {}", e)}));
@@ -639,6 +1048,7 @@ pub async fn onboarding_upload_restore(
 /// POST /api/setup/restore/execute
 pub async fn onboarding_execute_restore(
     pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
     body: web::Json<OnboardingRestoreRequest>,
 ) -> impl Responder {
     let mut conn = match pool.get() {
@@ -661,15 +1071,16 @@ pub async fn onboarding_execute_restore(
     }
 
     // Verify password if backup has encrypted sensitive data
-    let preview = match backup_service::preview_restore(&file_path) {
+    let preview = match preview_archive(&file_path).await {
         Ok(p) => p,
+        Err(BackupError::VerificationFailed(problems)) => return verification_failed(problems),
         Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("Invalid backup file: {}", e)})),
     };
 
     if preview.has_encrypted_sensitive {
         match &body.password {
             Some(password) => {
-                match backup_service::verify_backup_password(&file_path, password) {
+                match check_archive_password(&file_path, password).await {
                     Ok(true) => {}
                     Ok(false) => return HttpResponse::BadRequest().json(json!({"error": "Invalid password"})),
                     Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Password verification failed: {}", e)})),
@@ -679,11 +1090,16 @@ pub async fn onboarding_execute_restore(
         }
     }
 
+    let job_id = match backup_service::create_restore_job(&mut conn, preview.has_encrypted_sensitive, None) {
+        Ok(job_id) => job_id,
+        Err(e) => return HttpResponse::InternalServerError().json(json!({"error": format!("Failed to create restore job: {}", e)})),
+    };
+
     // Restore database data using the shared service function
-    match backup_service::restore_database(&mut conn, &file_path, body.password.as_deref()) {
+    match backup_service::run_restore_job(&mut conn, &file_path, body.password.as_deref(), job_id) {
         Ok(stats) => {
             // Restore files
-            let files_restored = match backup_service::restore_backup_files(&file_path) {
+            let files_restored = match backup_service::restore_backup_files(&file_path, storage.get_ref().as_ref(), |_, _| {}).await {
                 Ok(count) => count,
                 Err(e) => {
                     log::warn!("File restore had issues: {}", e);
@@ -692,9 +1108,11 @@ pub async fn onboarding_execute_restore(
             };
 
             // Regenerate thumbnails for all users with avatars
-            let thumbnails_regenerated = regenerate_user_thumbnails(&mut conn).await;
+            let thumbnails_regenerated = backup_service::regenerate_user_thumbnails(&mut conn).await;
             log::info!("Regenerated {} user thumbnails after restore", thumbnails_regenerated);
 
+            backup_service::complete_restore_job(&mut conn, job_id);
+
             // Clean up the uploaded backup file
             let _ = std::fs::remove_file(&file_path);
 
@@ -721,47 +1139,3 @@ pub struct OnboardingRestoreRequest {
     pub file_path: String,
     pub password: Option<String>,
 }
-
-/// Regenerate thumbnails for all users with avatars
-/// Returns the count of successfully regenerated thumbnails
-async fn regenerate_user_thumbnails(conn: &mut crate::db::DbConnection) -> u64 {
-    use diesel::RunQueryDsl;
-
-    // Query all users with avatar URLs
-    #[derive(diesel::QueryableByName)]
-    struct UserAvatar {
-        #[diesel(sql_type = diesel::sql_types::Text)]
-        uuid_str: String,
-        #[diesel(sql_type = diesel::sql_types::Text)]
-        avatar: String,
-    }
-
-    let user_avatars: Vec<UserAvatar> = match diesel::sql_query(
-        "SELECT uuid::text as uuid_str, avatar_url as avatar FROM users WHERE avatar_url IS NOT NULL"
-    ).load(conn) {
-        Ok(avatars) => avatars,
-        Err(e) => {
-            log::error!("Failed to query users for thumbnail regeneration: {}", e);
-            return 0;
-        }
-    };
-
-    let mut regenerated = 0u64;
-
-    for user_avatar in user_avatars {
-        match generate_user_avatar_thumbnail(&user_avatar.avatar, &user_avatar.uuid_str).await {
-            Ok(Some(_)) => {
-                regenerated += 1;
-                log::debug!("Regenerated thumbnail for user {}", user_avatar.uuid_str);
-            }
-            Ok(None) => {
-                log::warn!("Could not generate thumbnail for user {} - avatar may be missing", user_avatar.uuid_str);
-            }
-            Err(e) => {
-                log::warn!("Failed to regenerate thumbnail for user {}: {}", user_avatar.uuid_str, e);
-            }
-        }
-    }
-
-    regenerated
-}
@@ -0,0 +1,238 @@
+//! Bulk CSV/XLSX import and CSV export of tickets, users and devices (admin only)
+
+use actix_multipart::Multipart;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
+use serde_json::json;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::handlers::tickets::PaginationParams;
+use crate::models::{ImportEntityType, StartImportRequest};
+use crate::repository::import_jobs as import_repo;
+use crate::services::bulk_import::{self, ImportError, MAX_UPLOAD_BYTES};
+use crate::utils::rbac::require_admin;
+
+/// Jobs returned by the job list
+const JOB_LIST_LIMIT: i64 = 50;
+
+fn parse_entity(entity: &str) -> Result<ImportEntityType, HttpResponse> {
+    ImportEntityType::parse(entity).ok_or_else(|| {
+        HttpResponse::BadRequest().json(json!({
+            "error": "Bad Request",
+            "message": format!("Unknown entity type: {} (expected tickets, users or devices)", entity)
+        }))
+    })
+}
+
+fn import_error_response(e: ImportError) -> HttpResponse {
+    match e {
+        ImportError::Mapping(errors) => HttpResponse::BadRequest().json(json!({
+            "error": "Invalid column mapping",
+            "errors": errors
+        })),
+        ImportError::UnsupportedFile(_) | ImportError::Parse(_) | ImportError::Csv(_) => {
+            HttpResponse::BadRequest().json(json!({
+                "error": "Bad Request",
+                "message": e.to_string()
+            }))
+        }
+        ImportError::FileNotFound => HttpResponse::NotFound().json(json!({
+            "error": "Not Found",
+            "message": e.to_string()
+        })),
+        ImportError::Database(_) | ImportError::Io(_) => {
+            error!(error = %e, "Bulk import failed");
+            HttpResponse::InternalServerError().json("Import failed")
+        }
+    }
+}
+
+// POST /api/admin/import/{entity}/upload - Store a CSV/XLSX file and preview its columns
+pub async fn upload_import_file(
+    req: HttpRequest,
+    path: web::Path<String>,
+    mut payload: Multipart,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+    let entity = match parse_entity(&path) {
+        Ok(entity) => entity,
+        Err(e) => return e,
+    };
+
+    let mut upload: Option<(String, Vec<u8>)> = None;
+    while let Some(item) = payload.next().await {
+        let mut field = match item {
+            Ok(field) => field,
+            Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("Upload error: {}", e)})),
+        };
+        let Some(file_name) = field.content_disposition().get_filename().map(String::from) else {
+            continue;
+        };
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("Upload error: {}", e)})),
+            };
+            if data.len() + chunk.len() > MAX_UPLOAD_BYTES {
+                return HttpResponse::PayloadTooLarge().json(json!({
+                    "error": "File too large",
+                    "message": format!("Import files are limited to {} MB", MAX_UPLOAD_BYTES / 1024 / 1024)
+                }));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        upload = Some((file_name, data));
+    }
+
+    let Some((file_name, data)) = upload else {
+        return HttpResponse::BadRequest().json(json!({"error": "No file uploaded"}));
+    };
+
+    match web::block(move || bulk_import::save_upload(entity, &file_name, &data)).await {
+        Ok(Ok(preview)) => HttpResponse::Ok().json(preview),
+        Ok(Err(e)) => import_error_response(e),
+        Err(e) => {
+            error!(error = %e, "Import upload task failed");
+            HttpResponse::InternalServerError().json("Import failed")
+        }
+    }
+}
+
+// POST /api/admin/import/{entity}/jobs - Import (or dry-run) an uploaded file in the background
+pub async fn start_import(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<String>,
+    body: web::Json<StartImportRequest>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let entity = match parse_entity(&path) {
+        Ok(entity) => entity,
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let body = body.into_inner();
+    let created_by = Uuid::parse_str(&claims.sub).ok();
+    let job = match bulk_import::create_job(
+        &mut conn, entity, &body.file_id, body.mapping, body.dry_run, created_by,
+    ) {
+        Ok(job) => job,
+        Err(e) => return import_error_response(e),
+    };
+
+    bulk_import::spawn_import_job(pool.get_ref().clone(), job.id);
+
+    HttpResponse::Accepted().json(job)
+}
+
+// GET /api/admin/import/jobs - Recent import jobs
+pub async fn get_import_jobs(req: HttpRequest, pool: web::Data<Pool>) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match import_repo::get_import_jobs(&mut conn, JOB_LIST_LIMIT) {
+        Ok(jobs) => HttpResponse::Ok().json(jobs),
+        Err(e) => {
+            error!(error = ?e, "Failed to load import jobs");
+            HttpResponse::InternalServerError().json("Failed to load import jobs")
+        }
+    }
+}
+
+// GET /api/admin/import/jobs/{id} - Progress and row errors of one import job
+pub async fn get_import_job(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match import_repo::get_import_job(&mut conn, path.into_inner()) {
+        Ok(job) => HttpResponse::Ok().json(job),
+        Err(diesel::result::Error::NotFound) => HttpResponse::NotFound().json("Import job not found"),
+        Err(e) => {
+            error!(error = ?e, "Failed to load import job");
+            HttpResponse::InternalServerError().json("Failed to load import job")
+        }
+    }
+}
+
+// GET /api/admin/export/{entity} - CSV export; tickets take the same filters as the ticket list
+pub async fn export_csv(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<String>,
+    query: web::Query<PaginationParams>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+    let entity = match parse_entity(&path) {
+        Ok(entity) => entity,
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let result = web::block(move || match entity {
+        ImportEntityType::Tickets => {
+            let (sort_field, sort_direction) = query.sort();
+            bulk_import::export_tickets_csv(&mut conn, &query.filters(), sort_field, sort_direction)
+        }
+        ImportEntityType::Users => bulk_import::export_users_csv(&mut conn),
+        ImportEntityType::Devices => bulk_import::export_devices_csv(&mut conn),
+    })
+    .await;
+
+    match result {
+        Ok(Ok(data)) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"{}-{}.csv\"",
+                    entity.as_str(),
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                ),
+            ))
+            .body(data),
+        Ok(Err(e)) => {
+            error!(entity = entity.as_str(), error = %e, "CSV export failed");
+            HttpResponse::InternalServerError().json("Export failed")
+        }
+        Err(e) => {
+            error!(error = %e, "CSV export task failed");
+            HttpResponse::InternalServerError().json("Export failed")
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use actix_web::{web, HttpResponse, Responder, HttpRequest};
+use diesel::result::Error;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::db::{DbConnection, Pool};
+use crate::models::{AssignmentTrigger, CatalogItem, CatalogItemUpdate, FormSchema, NewCatalogItem, TicketPriority, TicketUpdate};
+use crate::repository;
+use crate::services::assignment::AssignmentEngine;
+use crate::services::catalog::{validate_schema, CatalogError, CatalogService};
+use crate::utils::rbac::{is_admin, is_technician_or_admin, require_admin, require_auth};
+use crate::utils::sse::SseBroadcaster;
+
+// ============================================================================
+// Catalog Endpoints for Regular Users
+// ============================================================================
+
+/// List catalog items the current user can request
+pub async fn get_catalog(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let claims = match require_auth(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::catalog::get_items_for_user(&mut conn, &user_uuid, is_admin(&claims)) {
+        Ok(items) => HttpResponse::Ok().json(items),
+        Err(e) => {
+            error!(error = ?e, "Failed to get catalog items");
+            HttpResponse::InternalServerError().json("Failed to get catalog items")
+        }
+    }
+}
+
+/// Load an active item the user is allowed to see
+fn get_visible_item(
+    conn: &mut DbConnection,
+    item_id: i32,
+    user_uuid: &Uuid,
+    is_admin: bool,
+) -> Result<CatalogItem, HttpResponse> {
+    let item = match repository::catalog::get_item_by_id(conn, item_id) {
+        Ok(item) => item,
+        Err(Error::NotFound) => return Err(HttpResponse::NotFound().json("Catalog item not found")),
+        Err(_) => return Err(HttpResponse::InternalServerError().json("Failed to get catalog item")),
+    };
+
+    let category_active = repository::categories::get_category_by_id(conn, item.category_id)
+        .map(|category| category.is_active)
+        .unwrap_or(false);
+    let visible = repository::categories::can_user_see_category(conn, user_uuid, item.category_id, is_admin)
+        .unwrap_or(false);
+
+    // Hidden items are reported as missing rather than forbidden
+    if !item.is_active || !category_active || !visible {
+        return Err(HttpResponse::NotFound().json("Catalog item not found"));
+    }
+
+    Ok(item)
+}
+
+/// Get a catalog item with its form
+pub async fn get_catalog_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let claims = match require_auth(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match get_visible_item(&mut conn, path.into_inner(), &user_uuid, is_admin(&claims)) {
+        Ok(item) => HttpResponse::Ok().json(item),
+        Err(response) => response,
+    }
+}
+
+/// Request body for submitting a catalog form
+#[derive(Debug, Deserialize)]
+pub struct SubmitCatalogRequest {
+    pub answers: Map<String, Value>,
+}
+
+/// Submit a catalog form, creating a service request ticket
+pub async fn submit_catalog_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    path: web::Path<i32>,
+    body: web::Json<SubmitCatalogRequest>,
+) -> impl Responder {
+    let claims = match require_auth(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let user_uuid = match Uuid::parse_str(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let item = match get_visible_item(&mut conn, path.into_inner(), &user_uuid, is_admin(&claims)) {
+        Ok(item) => item,
+        Err(response) => return response,
+    };
+
+    let (mut ticket, submission) = match CatalogService::submit(
+        &mut conn,
+        &item,
+        &body.answers,
+        user_uuid,
+        is_technician_or_admin(&claims),
+    ) {
+        Ok(created) => created,
+        Err(CatalogError::InvalidAnswers(errors)) => return HttpResponse::UnprocessableEntity().json(json!({
+            "error": "Invalid answers",
+            "message": "Some fields need attention",
+            "fields": errors
+        })),
+        Err(CatalogError::DatabaseError(e)) => {
+            error!(item_id = item.id, error = ?e, "Failed to submit catalog item");
+            return HttpResponse::InternalServerError().json("Failed to submit request");
+        }
+        Err(e) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": e.to_string()
+        })),
+    };
+
+    // Run automatic assignment rules, as for any new ticket
+    if let Some(result) = AssignmentEngine::evaluate_rules(&mut conn, &ticket, AssignmentTrigger::TicketCreated) {
+        if let Some(assigned_uuid) = result.assigned_user_uuid {
+            let assign_update = TicketUpdate {
+                assignee_uuid: Some(Some(assigned_uuid)),
+                updated_at: Some(chrono::Utc::now().naive_utc()),
+                ..Default::default()
+            };
+            if let Ok(updated) = repository::update_ticket_partial(&mut conn, ticket.id, assign_update) {
+                ticket = updated;
+                info!(ticket_id = ticket.id, rule = %result.rule_name, "Auto-assigned catalog request");
+            }
+        }
+    }
+
+    {
+        use crate::services::approvals::ApprovalService;
+
+        match ApprovalService::start(&mut conn, &ticket, Some(user_uuid)).await {
+            Ok(approvals) if !approvals.is_empty() => {
+                ticket.approval_status = Some(crate::models::TicketApprovalStatus::Pending.as_str().to_string());
+            }
+            Ok(_) => {}
+            Err(e) => error!(ticket_id = ticket.id, error = %e, "Failed to start approval chain"),
+        }
+    }
+
+    SseBroadcaster::broadcast_ticket_created(
+        &sse_state,
+        ticket.id,
+        serde_json::to_value(&ticket).unwrap_or_default(),
+    ).await;
+
+    HttpResponse::Created().json(json!({
+        "ticket": ticket,
+        "submission": submission
+    }))
+}
+
+/// Get the catalog form answers a ticket was created from
+pub async fn get_ticket_catalog_submission(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_auth(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::catalog::get_submission_for_ticket(&mut conn, path.into_inner()) {
+        Ok(Some(submission)) => HttpResponse::Ok().json(submission),
+        Ok(None) => HttpResponse::NotFound().json("Ticket was not created from the catalog"),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get catalog submission"),
+    }
+}
+
+// ============================================================================
+// Admin Catalog Endpoints
+// ============================================================================
+
+/// Get all catalog items, including inactive ones (admin only)
+pub async fn get_all_catalog_items_admin(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::catalog::get_all_items(&mut conn) {
+        Ok(items) => HttpResponse::Ok().json(items),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get catalog items"),
+    }
+}
+
+/// Parse and check a form schema from a request body
+fn parse_schema(value: &Value) -> Result<FormSchema, HttpResponse> {
+    let schema: FormSchema = serde_json::from_value(value.clone()).map_err(|e| {
+        HttpResponse::BadRequest().json(json!({
+            "error": "Invalid form schema",
+            "message": e.to_string()
+        }))
+    })?;
+
+    validate_schema(&schema).map_err(|message| {
+        HttpResponse::BadRequest().json(json!({
+            "error": "Invalid form schema",
+            "message": message
+        }))
+    })?;
+
+    Ok(schema)
+}
+
+/// Request body for creating a catalog item
+#[derive(Debug, Deserialize)]
+pub struct CreateCatalogItemRequest {
+    pub category_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub form_schema: Option<Value>,
+    pub title_template: Option<String>,
+    pub default_priority: Option<TicketPriority>,
+    pub link_requester_devices: Option<bool>,
+}
+
+/// Create a catalog item (admin only)
+pub async fn create_catalog_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    body: web::Json<CreateCatalogItemRequest>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let body = body.into_inner();
+
+    if body.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": "Name is required"
+        }));
+    }
+
+    let schema = match body.form_schema.as_ref().map(parse_schema).transpose() {
+        Ok(schema) => schema.unwrap_or_default(),
+        Err(response) => return response,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if repository::categories::get_category_by_id(&mut conn, body.category_id).is_err() {
+        return HttpResponse::NotFound().json("Category not found");
+    }
+
+    let display_order = repository::catalog::get_next_display_order(&mut conn, body.category_id).unwrap_or(0);
+
+    let new_item = NewCatalogItem {
+        category_id: body.category_id,
+        name: body.name.trim().to_string(),
+        description: body.description,
+        icon: body.icon,
+        form_schema: serde_json::to_value(&schema).unwrap_or_else(|_| json!({"fields": []})),
+        title_template: body.title_template.filter(|t| !t.trim().is_empty()),
+        default_priority: body.default_priority.unwrap_or(TicketPriority::Medium),
+        link_requester_devices: body.link_requester_devices.unwrap_or(false),
+        display_order,
+        is_active: true,
+        created_by: Uuid::parse_str(&claims.sub).ok(),
+    };
+
+    match repository::catalog::create_item(&mut conn, new_item) {
+        Ok(item) => HttpResponse::Created().json(item),
+        Err(e) => {
+            error!(error = ?e, "Failed to create catalog item");
+            HttpResponse::InternalServerError().json("Failed to create catalog item")
+        }
+    }
+}
+
+/// Request body for updating a catalog item
+/// Empty strings clear the optional text fields.
+#[derive(Debug, Deserialize)]
+pub struct UpdateCatalogItemRequest {
+    pub category_id: Option<i32>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub form_schema: Option<Value>,
+    pub title_template: Option<String>,
+    pub default_priority: Option<TicketPriority>,
+    pub link_requester_devices: Option<bool>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+/// Update a catalog item (admin only)
+pub async fn update_catalog_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: web::Json<UpdateCatalogItemRequest>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let item_id = path.into_inner();
+    let body = body.into_inner();
+
+    let form_schema = match body.form_schema.as_ref().map(parse_schema).transpose() {
+        Ok(schema) => schema.and_then(|s| serde_json::to_value(&s).ok()),
+        Err(response) => return response,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Some(category_id) = body.category_id {
+        if repository::categories::get_category_by_id(&mut conn, category_id).is_err() {
+            return HttpResponse::NotFound().json("Category not found");
+        }
+    }
+
+    let clearable = |value: Option<String>| value.map(|v| Some(v).filter(|v| !v.trim().is_empty()));
+    let update = CatalogItemUpdate {
+        category_id: body.category_id,
+        name: body.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+        description: clearable(body.description),
+        icon: clearable(body.icon),
+        form_schema,
+        title_template: clearable(body.title_template),
+        default_priority: body.default_priority,
+        link_requester_devices: body.link_requester_devices,
+        display_order: body.display_order,
+        is_active: body.is_active,
+        updated_at: None,
+    };
+
+    match repository::catalog::update_item(&mut conn, item_id, update) {
+        Ok(item) => HttpResponse::Ok().json(item),
+        Err(Error::NotFound) => HttpResponse::NotFound().json("Catalog item not found"),
+        Err(e) => {
+            error!(item_id, error = ?e, "Failed to update catalog item");
+            HttpResponse::InternalServerError().json("Failed to update catalog item")
+        }
+    }
+}
+
+/// Deactivate a catalog item (admin only)
+pub async fn delete_catalog_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::catalog::deactivate_item(&mut conn, path.into_inner()) {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(Error::NotFound) => HttpResponse::NotFound().json("Catalog item not found"),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to delete catalog item"),
+    }
+}
@@ -6,6 +6,7 @@ use uuid::Uuid;
 use crate::db::Pool;
 use crate::models::{NewTicketCategory, TicketCategoryUpdate, Claims};
 use crate::repository;
+use crate::services::doc_templates::{DocTemplateError, DocTemplateService};
 use crate::utils::rbac::require_admin;
 
 // ============================================================================
@@ -97,6 +98,24 @@ pub struct CreateCategoryRequest {
     pub color: Option<String>,
     pub icon: Option<String>,
     pub visible_to_group_ids: Option<Vec<i32>>, // If None or empty, category is public
+    pub documentation_template_id: Option<i32>,
+}
+
+/// Check that a category's documentation template refers to a template page
+fn validate_documentation_template(
+    conn: &mut crate::db::DbConnection,
+    template_id: Option<i32>,
+) -> Result<(), HttpResponse> {
+    let Some(template_id) = template_id else {
+        return Ok(());
+    };
+    match DocTemplateService::get_template(conn, template_id) {
+        Ok(_) => Ok(()),
+        Err(DocTemplateError::DatabaseError(_)) => {
+            Err(HttpResponse::InternalServerError().json("Failed to load documentation template"))
+        }
+        Err(e) => Err(HttpResponse::BadRequest().json(e.to_string())),
+    }
 }
 
 /// Create a new category (admin only)
@@ -127,6 +146,10 @@ pub async fn create_category(
         Err(_) => 0,
     };
 
+    if let Err(e) = validate_documentation_template(&mut conn, body.documentation_template_id) {
+        return e;
+    }
+
     let new_category = NewTicketCategory {
         name: body.name.clone(),
         description: body.description.clone(),
@@ -135,6 +158,7 @@ pub async fn create_category(
         display_order,
         is_active: true,
         created_by,
+        documentation_template_id: body.documentation_template_id,
     };
 
     match repository::categories::create_category(&mut conn, new_category) {
@@ -173,6 +197,7 @@ pub struct UpdateCategoryRequest {
     pub icon: Option<String>,
     pub is_active: Option<bool>,
     pub visible_to_group_ids: Option<Vec<i32>>, // If provided, replaces existing visibility
+    pub documentation_template_id: Option<Option<i32>>,
 }
 
 /// Update an existing category (admin only)
@@ -199,6 +224,10 @@ pub async fn update_category(
         Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
     };
 
+    if let Err(e) = validate_documentation_template(&mut conn, body.documentation_template_id.flatten()) {
+        return e;
+    }
+
     let category_update = TicketCategoryUpdate {
         name: body.name.clone(),
         description: body.description.clone(),
@@ -207,6 +236,7 @@ pub async fn update_category(
         display_order: None,
         is_active: body.is_active,
         updated_at: None,
+        documentation_template_id: body.documentation_template_id,
     };
 
     match repository::categories::update_category(&mut conn, category_id, category_update) {
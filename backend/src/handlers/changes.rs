@@ -0,0 +1,458 @@
+use actix_web::{web, HttpResponse, Responder, HttpRequest};
+use chrono::{DateTime, Utc};
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel::Connection;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::models::{
+    ChangeApprovalStatus, ChangeDetailsUpdate, ChangeRiskLevel, NewChangeDetails, NewTicket,
+    TicketLinkType, TicketPriority, TicketStatus,
+};
+use crate::repository;
+use crate::services::ticket_relationships::{RelationshipError, TicketRelationshipService};
+use crate::utils::rbac::{require_admin, require_technician_or_admin};
+use crate::utils::sse::SseBroadcaster;
+
+// ============================================================================
+// Change Requests
+// ============================================================================
+// A change is a ticket with ticket_type = 'change' and a change_details row.
+// Affected devices use ticket_devices, so the regular ticket device endpoints apply.
+// Approval flow: draft -> pending -> approved | rejected (rejected changes can be resubmitted).
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeListQuery {
+    pub approval_status: Option<String>,
+}
+
+/// List changes (technician/admin)
+pub async fn list_changes(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    query: web::Query<ChangeListQuery>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let approval_status = match query.approval_status.as_deref() {
+        None | Some("all") => None,
+        Some(value) => match value.parse::<ChangeApprovalStatus>() {
+            Ok(status) => Some(status),
+            Err(message) => return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid request",
+                "message": message
+            })),
+        },
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::changes::list_changes(&mut conn, approval_status) {
+        Ok(changes) => HttpResponse::Ok().json(changes),
+        Err(e) => {
+            error!(error = ?e, "Failed to list changes");
+            HttpResponse::InternalServerError().json("Failed to list changes")
+        }
+    }
+}
+
+/// Request body for creating a change
+#[derive(Debug, Deserialize)]
+pub struct CreateChangeRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<TicketPriority>,
+    pub assignee_uuid: Option<Uuid>,
+    pub category_id: Option<i32>,
+    pub risk_level: Option<String>,
+    pub planned_start: Option<DateTime<Utc>>,
+    pub planned_end: Option<DateTime<Utc>>,
+    pub implementation_plan: Option<String>,
+    pub backout_plan: Option<String>,
+    /// Devices the change touches
+    pub device_ids: Option<Vec<i32>>,
+    /// Problem this change addresses
+    pub problem_id: Option<i32>,
+}
+
+fn validate_window(start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> Result<(), HttpResponse> {
+    if let (Some(start), Some(end)) = (start, end) {
+        if end <= start {
+            return Err(HttpResponse::BadRequest().json(json!({
+                "error": "Invalid change window",
+                "message": "planned_end must be after planned_start"
+            })));
+        }
+    }
+    Ok(())
+}
+
+fn parse_risk_level(value: &str) -> Result<ChangeRiskLevel, HttpResponse> {
+    value.parse::<ChangeRiskLevel>().map_err(|message| {
+        HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": message
+        }))
+    })
+}
+
+/// Create a change request in draft state (technician/admin)
+pub async fn create_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    body: web::Json<CreateChangeRequest>,
+) -> impl Responder {
+    let claims = match require_technician_or_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let created_by = Uuid::parse_str(&claims.sub).ok();
+    let body = body.into_inner();
+
+    if body.title.trim().is_empty() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": "Title is required"
+        }));
+    }
+    if let Err(e) = validate_window(body.planned_start, body.planned_end) {
+        return e;
+    }
+    let risk_level = match body.risk_level.as_deref().map(parse_risk_level).transpose() {
+        Ok(level) => level.unwrap_or(ChangeRiskLevel::Low),
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let new_ticket = NewTicket {
+        title: body.title.trim().to_string(),
+        description: body.description,
+        status: TicketStatus::Open,
+        priority: body.priority.unwrap_or(TicketPriority::Medium),
+        requester_uuid: created_by,
+        assignee_uuid: body.assignee_uuid,
+        category_id: body.category_id,
+    };
+    let details = NewChangeDetails {
+        ticket_id: 0, // Set by the repository once the ticket exists
+        risk_level: risk_level.as_str().to_string(),
+        planned_start: body.planned_start.map(|d| d.naive_utc()),
+        planned_end: body.planned_end.map(|d| d.naive_utc()),
+        implementation_plan: body.implementation_plan,
+        backout_plan: body.backout_plan,
+    };
+
+    // The change, its devices and its problem link are created together or not at all
+    let device_ids = body.device_ids.unwrap_or_default();
+    let created = conn.transaction::<_, RelationshipError, _>(|conn| {
+        let (ticket, _details) = repository::changes::create_change(conn, new_ticket, details, created_by)?;
+        for &device_id in &device_ids {
+            repository::add_device_to_ticket(conn, ticket.id, device_id)?;
+        }
+        if let Some(problem_id) = body.problem_id {
+            TicketRelationshipService::link(conn, ticket.id, problem_id, TicketLinkType::RelatesTo, created_by)?;
+        }
+        Ok(ticket)
+    });
+    let ticket = match created {
+        Ok(ticket) => ticket,
+        Err(RelationshipError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _))) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid request",
+                "message": "A referenced device, category or user does not exist"
+            }));
+        }
+        Err(RelationshipError::DatabaseError(e)) => {
+            error!(error = ?e, "Failed to create change");
+            return HttpResponse::InternalServerError().json("Failed to create change");
+        }
+        Err(e) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid relationship",
+            "message": e.to_string()
+        })),
+    };
+
+    SseBroadcaster::broadcast_ticket_created(
+        &sse_state,
+        ticket.id,
+        serde_json::to_value(&ticket).unwrap_or_default(),
+    ).await;
+
+    match repository::changes::get_change_record(&mut conn, ticket.id) {
+        Ok(record) => HttpResponse::Created().json(record),
+        Err(_) => HttpResponse::Created().json(ticket),
+    }
+}
+
+/// Get a change with its devices, projects and problems (technician/admin)
+pub async fn get_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::changes::get_change_record(&mut conn, path.into_inner()) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(Error::NotFound) => HttpResponse::NotFound().json("Change not found"),
+        Err(e) => {
+            error!(error = ?e, "Failed to get change");
+            HttpResponse::InternalServerError().json("Failed to get change")
+        }
+    }
+}
+
+/// Request body for updating change details
+#[derive(Debug, Deserialize)]
+pub struct UpdateChangeRequest {
+    pub risk_level: Option<String>,
+    pub planned_start: Option<DateTime<Utc>>,
+    pub planned_end: Option<DateTime<Utc>>,
+    pub implementation_plan: Option<String>,
+    pub backout_plan: Option<String>,
+}
+
+/// Update the plan, window or risk of a change (technician/admin)
+/// Editing an approved or pending change sends it back to draft for re-approval
+pub async fn update_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: web::Json<UpdateChangeRequest>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let change_id = path.into_inner();
+    let body = body.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let current = match repository::changes::get_change_details(&mut conn, change_id) {
+        Ok(details) => details,
+        Err(Error::NotFound) => return HttpResponse::NotFound().json("Change not found"),
+        Err(_) => return HttpResponse::InternalServerError().json("Failed to get change"),
+    };
+
+    // Validate the resulting window, not just the fields that were sent
+    let start = body.planned_start.or(current.planned_start.map(|d| d.and_utc()));
+    let end = body.planned_end.or(current.planned_end.map(|d| d.and_utc()));
+    if let Err(e) = validate_window(start, end) {
+        return e;
+    }
+    let risk_level = match body.risk_level.as_deref().map(parse_risk_level).transpose() {
+        Ok(level) => level,
+        Err(e) => return e,
+    };
+
+    let mut update = ChangeDetailsUpdate {
+        risk_level: risk_level.map(|level| level.as_str().to_string()),
+        planned_start: body.planned_start.map(|d| Some(d.naive_utc())),
+        planned_end: body.planned_end.map(|d| Some(d.naive_utc())),
+        implementation_plan: body.implementation_plan.map(Some),
+        backout_plan: body.backout_plan.map(Some),
+        updated_at: Some(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    let approval_status = current.approval_status.parse().unwrap_or(ChangeApprovalStatus::Draft);
+    if matches!(approval_status, ChangeApprovalStatus::Pending | ChangeApprovalStatus::Approved) {
+        update.approval_status = Some(ChangeApprovalStatus::Draft.as_str().to_string());
+        update.approved_by = Some(None);
+        update.approved_at = Some(None);
+    }
+
+    if let Err(e) = repository::changes::update_change_details(&mut conn, change_id, update) {
+        error!(change_id, error = ?e, "Failed to update change");
+        return HttpResponse::InternalServerError().json("Failed to update change");
+    }
+
+    match repository::changes::get_change_record(&mut conn, change_id) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get change"),
+    }
+}
+
+/// Move a change to a new approval status, checking the allowed transitions
+fn transition_change(
+    conn: &mut crate::db::DbConnection,
+    change_id: i32,
+    to: ChangeApprovalStatus,
+    approver: Option<Uuid>,
+    note: Option<String>,
+) -> Result<(), HttpResponse> {
+    let current = match repository::changes::get_change_details(conn, change_id) {
+        Ok(details) => details,
+        Err(Error::NotFound) => return Err(HttpResponse::NotFound().json("Change not found")),
+        Err(_) => return Err(HttpResponse::InternalServerError().json("Failed to get change")),
+    };
+    let from = current.approval_status.parse().unwrap_or(ChangeApprovalStatus::Draft);
+
+    let allowed = matches!(
+        (from, to),
+        (ChangeApprovalStatus::Draft | ChangeApprovalStatus::Rejected, ChangeApprovalStatus::Pending)
+            | (ChangeApprovalStatus::Pending, ChangeApprovalStatus::Approved | ChangeApprovalStatus::Rejected)
+    );
+    if !allowed {
+        return Err(HttpResponse::Conflict().json(json!({
+            "error": "Invalid transition",
+            "message": format!("Change is {} and cannot become {}", from.as_str(), to.as_str())
+        })));
+    }
+
+    if to == ChangeApprovalStatus::Pending
+        && (current.implementation_plan.is_none() || current.backout_plan.is_none() || current.planned_start.is_none())
+    {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": "Incomplete change",
+            "message": "A planned window, implementation plan and backout plan are required before submitting"
+        })));
+    }
+
+    let decided = matches!(to, ChangeApprovalStatus::Approved | ChangeApprovalStatus::Rejected);
+    let update = ChangeDetailsUpdate {
+        approval_status: Some(to.as_str().to_string()),
+        approved_by: Some(if decided { approver } else { None }),
+        approved_at: Some(decided.then(|| Utc::now().naive_utc())),
+        approval_note: Some(note),
+        updated_at: Some(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+
+    repository::changes::update_change_details(conn, change_id, update)
+        .map(|_| ())
+        .map_err(|e| {
+            error!(change_id, error = ?e, "Failed to update change approval");
+            HttpResponse::InternalServerError().json("Failed to update change")
+        })
+}
+
+/// Submit a draft or rejected change for approval (technician/admin)
+pub async fn submit_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let change_id = path.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(e) = transition_change(&mut conn, change_id, ChangeApprovalStatus::Pending, None, None) {
+        return e;
+    }
+
+    match repository::changes::get_change_record(&mut conn, change_id) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get change"),
+    }
+}
+
+/// Request body for approving or rejecting a change
+#[derive(Debug, Deserialize)]
+pub struct ChangeDecisionRequest {
+    pub note: Option<String>,
+}
+
+/// Approve a pending change (admin only)
+/// The response lists other approved changes that touch the same devices in an overlapping window
+pub async fn approve_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: Option<web::Json<ChangeDecisionRequest>>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+
+    let change_id = path.into_inner();
+    let note = body.and_then(|b| b.into_inner().note);
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(e) = transition_change(&mut conn, change_id, ChangeApprovalStatus::Approved, Uuid::parse_str(&claims.sub).ok(), note) {
+        return e;
+    }
+
+    let record = match repository::changes::get_change_record(&mut conn, change_id) {
+        Ok(record) => record,
+        Err(_) => return HttpResponse::InternalServerError().json("Failed to get change"),
+    };
+
+    let mut conflicts = Vec::new();
+    if let (Some(start), Some(end)) = (record.details.planned_start, record.details.planned_end) {
+        for device in &record.devices {
+            if let Ok(overlapping) = repository::changes::get_overlapping_changes_for_device(&mut conn, device.id, start, end, change_id) {
+                for ticket in overlapping {
+                    conflicts.push(json!({ "device_id": device.id, "change_id": ticket.id, "title": ticket.title }));
+                }
+            }
+        }
+    }
+
+    HttpResponse::Ok().json(json!({
+        "change": record,
+        "conflicts": conflicts,
+    }))
+}
+
+/// Reject a pending change (admin only)
+pub async fn reject_change(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: Option<web::Json<ChangeDecisionRequest>>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+
+    let change_id = path.into_inner();
+    let note = body.and_then(|b| b.into_inner().note);
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(e) = transition_change(&mut conn, change_id, ChangeApprovalStatus::Rejected, Uuid::parse_str(&claims.sub).ok(), note) {
+        return e;
+    }
+
+    match repository::changes::get_change_record(&mut conn, change_id) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get change"),
+    }
+}
@@ -95,6 +95,8 @@ fn log_document_root_types(awareness: &Awareness, doc_id: &str) {
     trace!(doc_id = %doc_id, state_vector = ?sv, "Document state vector");
 }
 use crate::models::{NewArticleContent, NewArticleContentRevision};
+use crate::utils::cluster_relay::{self, ClusterRelay, DocumentFrame, FrameKind};
+use crate::utils::presence::PresenceRegistry;
 use crate::utils::redis_yjs_cache::RedisYjsCache;
 
 // How often heartbeat checks are performed (server-side connection health monitoring)
@@ -111,6 +113,43 @@ const MAX_PENDING_DURATION: Duration = Duration::from_secs(120);
 const EMPTY_ROOM_FINAL_SAVE_DELAY: Duration = Duration::from_secs(2);
 // How long to keep document state after room becomes empty
 const EMPTY_ROOM_CLEANUP_DELAY: Duration = Duration::from_secs(300); // 5 minutes
+// Run revision retention every 120 maintenance ticks (hourly at a 30s tick)
+const RETENTION_EVERY_TICKS: u32 = 120;
+
+/// When to snapshot a document into its revision history
+///
+/// Loaded from the environment:
+/// - `REVISION_SNAPSHOT_UPDATES` - content updates since the last snapshot (default 200, 0 disables)
+/// - `REVISION_SNAPSHOT_MINUTES` - minutes of editing since the last snapshot (default 10, 0 disables)
+/// - `REVISION_SNAPSHOT_ON_EMPTY` - snapshot when the last editor leaves (default true)
+#[derive(Debug, Clone, Copy)]
+pub struct SnapshotPolicy {
+    pub every_updates: u32,
+    pub every_active: Option<Duration>,
+    pub on_room_empty: bool,
+}
+
+impl SnapshotPolicy {
+    pub fn from_env() -> Self {
+        let every_updates = std::env::var("REVISION_SNAPSHOT_UPDATES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(200);
+        let minutes = std::env::var("REVISION_SNAPSHOT_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(10);
+        let on_room_empty = std::env::var("REVISION_SNAPSHOT_ON_EMPTY")
+            .map(|v| !matches!(v.to_lowercase().as_str(), "false" | "0" | "no"))
+            .unwrap_or(true);
+
+        Self {
+            every_updates,
+            every_active: (minutes > 0).then(|| Duration::from_secs(minutes * 60)),
+            on_room_empty,
+        }
+    }
+}
 
 // Document type enum to distinguish between tickets and documentation
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -238,9 +277,12 @@ struct DocumentState {
     room_empty_since: Option<Instant>, // Track when room became empty
     final_save_completed: bool, // Track if final save was done
     // Snapshot tracking (for version history)
-    update_counter: u32,                    // Total updates since document creation
+    update_counter: u32,                    // Total content updates since document creation
     last_snapshot_at: u32,                  // Update count when last snapshot created
     contributors: std::collections::HashSet<Uuid>, // Contributors since last snapshot (only added on actual content changes)
+    first_change_at: Option<Instant>,       // First content change since last snapshot
+    // Cluster mode: only the instance holding the lease persists the document
+    is_owner: bool,
 }
 
 impl DocumentState {
@@ -257,6 +299,8 @@ impl DocumentState {
             update_counter: 0,
             last_snapshot_at: 0,
             contributors: std::collections::HashSet::new(),
+            first_change_at: None,
+            is_owner: true,
         }
     }
     
@@ -266,8 +310,7 @@ impl DocumentState {
             self.pending_since = Some(Instant::now());
         }
         self.sync_message_count += 1;
-        self.update_counter += 1; // Track total updates for snapshot scheduling
-        // Note: has_changes_since_last_revision is set separately only when content actually changes
+        // Note: update_counter is bumped in add_contributor, only when content actually changes
 
         // Reset room empty tracking since there's activity
         self.room_empty_since = None;
@@ -346,20 +389,34 @@ impl DocumentState {
     }
 
     // Snapshot management methods
-    fn should_create_snapshot(&self) -> bool {
-        // Session-based revisions: snapshots are only created when editing sessions end
-        // (when room becomes empty), not based on update count thresholds.
-        // This provides more meaningful revision history based on actual editing sessions.
-        false
+    fn should_create_snapshot(&self, policy: &SnapshotPolicy) -> bool {
+        // Nothing to attribute means no content changed since the last snapshot
+        if self.contributors.is_empty() {
+            return false;
+        }
+
+        if policy.every_updates > 0 && self.update_counter - self.last_snapshot_at >= policy.every_updates {
+            return true;
+        }
+
+        match (policy.every_active, self.first_change_at) {
+            (Some(every), Some(first_change)) => first_change.elapsed() >= every,
+            _ => false,
+        }
     }
 
     fn add_contributor(&mut self, user_uuid: Uuid) {
         self.contributors.insert(user_uuid);
+        self.update_counter += 1;
+        if self.first_change_at.is_none() {
+            self.first_change_at = Some(Instant::now());
+        }
     }
 
     fn reset_snapshot_tracking(&mut self) {
         self.last_snapshot_at = self.update_counter;
         self.contributors.clear();
+        self.first_change_at = None;
     }
 }
 
@@ -379,26 +436,64 @@ pub struct YjsAppState {
     pool: web::Data<crate::db::Pool>,
     redis_cache: Arc<RedisYjsCache>,
     sse_state: web::Data<crate::handlers::sse::SseState>,
+    snapshot_policy: SnapshotPolicy,
+    relay: Option<Arc<ClusterRelay>>,
+    presence: Arc<PresenceRegistry>,
 }
 
 impl YjsAppState {
-    pub fn new(pool: web::Data<crate::db::Pool>, redis_cache: Arc<RedisYjsCache>, sse_state: web::Data<crate::handlers::sse::SseState>) -> Self {
+    pub fn new(
+        pool: web::Data<crate::db::Pool>,
+        redis_cache: Arc<RedisYjsCache>,
+        sse_state: web::Data<crate::handlers::sse::SseState>,
+        relay: Option<Arc<ClusterRelay>>,
+        presence: Arc<PresenceRegistry>,
+    ) -> Self {
         let state = YjsAppState {
             documents: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
             pool,
             redis_cache,
             sse_state,
+            snapshot_policy: SnapshotPolicy::from_env(),
+            relay,
+            presence,
         };
+
+        // Apply Yjs traffic from other instances
+        if let Some(relay) = &state.relay {
+            let mut frames = relay.document_frames();
+            let state_clone = state.clone();
+            actix::spawn(async move {
+                loop {
+                    match frames.recv().await {
+                        Ok(frame) => state_clone.apply_relay_frame(frame).await,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(count)) => {
+                            // Missed updates are recovered by the next sync step exchange
+                            warn!(count, "Cluster relay listener lagged");
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+
         // Start the periodic cleanup and save task
         let state_clone = state.clone();
         actix::spawn(async move {
             use actix::clock::interval;
             let mut interval = interval(Duration::from_secs(30)); // Check every 30 seconds (was 10)
+            let mut ticks: u32 = 0;
             loop {
                 interval.tick().await;
                 state_clone.cleanup_stale_sessions().await;
+                state_clone.refresh_document_ownership().await;
                 state_clone.save_all_active_documents().await;
+
+                ticks = ticks.wrapping_add(1);
+                if ticks.is_multiple_of(RETENTION_EVERY_TICKS) {
+                    state_clone.prune_old_revisions();
+                }
             }
         });
         state
@@ -412,6 +507,12 @@ impl YjsAppState {
         let mut snapshot_count = 0;
 
         for (doc_id, doc_state) in documents.iter_mut() {
+            // Another instance persists this document. Pending changes stay flagged so they are
+            // saved here if this instance takes over the lease.
+            if !doc_state.is_owner {
+                continue;
+            }
+
             // Regular saves for active documents
             if doc_state.should_save() {
                 debug!(doc_id = %doc_id, "Saving document with pending changes");
@@ -420,8 +521,8 @@ impl YjsAppState {
                 saved_count += 1;
             }
 
-            // Check for snapshot creation (every 500 updates)
-            if doc_state.should_create_snapshot() {
+            // Periodic snapshot during long editing sessions (update count or active time)
+            if doc_state.should_create_snapshot(&self.snapshot_policy) {
                 debug!(doc_id = %doc_id, updates_since_snapshot = doc_state.update_counter - doc_state.last_snapshot_at,
                     "Snapshot threshold reached");
 
@@ -441,7 +542,7 @@ impl YjsAppState {
                 final_saved_count += 1;
 
                 // Create revision at end of editing session if there were content changes
-                if !doc_state.contributors.is_empty() {
+                if self.snapshot_policy.on_room_empty && !doc_state.contributors.is_empty() {
                     debug!(doc_id = %doc_id, "Creating session-end revision");
                     let contributors = doc_state.contributors.clone();
                     self.create_snapshot_revision(doc_id, &doc_state.awareness, contributors);
@@ -463,6 +564,24 @@ impl YjsAppState {
         }
     }
 
+    // Thin old revision snapshots according to the retention policy (runs off the async runtime)
+    fn prune_old_revisions(&self) {
+        let pool = self.pool.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = match pool.get() {
+                Ok(conn) => conn,
+                Err(e) => {
+                    error!(error = ?e, "Database connection error during revision retention");
+                    return;
+                }
+            };
+            let policy = crate::services::revision_retention::RetentionPolicy::from_env();
+            if let Err(e) = crate::services::revision_retention::RevisionRetentionService::prune_all(&mut conn, &policy) {
+                error!(error = ?e, "Revision retention failed");
+            }
+        });
+    }
+
     // Get or create awareness for a document
     async fn get_or_create_awareness(&self, doc_id: &str) -> Arc<Awareness> {
         let mut documents = self.documents.write().await;
@@ -667,7 +786,22 @@ impl YjsAppState {
             }
 
             let awareness_arc = Arc::new(awareness);
-            let doc_state = DocumentState::new(Arc::clone(&awareness_arc));
+            let mut doc_state = DocumentState::new(Arc::clone(&awareness_arc));
+
+            if let Some(relay) = &self.relay {
+                doc_state.is_owner = relay.claim_document(doc_id).await;
+
+                // Other instances may hold edits newer than the cached snapshot - ask them for
+                // the difference; their sync step 2 replies arrive through the relay
+                let state_vector = awareness_arc.doc().transact().state_vector();
+                let request = yrs::sync::Message::Sync(yrs::sync::SyncMessage::SyncStep1(state_vector)).encode_v1();
+                let relay = Arc::clone(relay);
+                let doc_id = doc_id.to_string();
+                actix::spawn(async move {
+                    relay.publish_document(&doc_id, FrameKind::Message, None, &request).await;
+                });
+            }
+
             documents.insert(doc_id.to_string(), doc_state);
             awareness_arc
         }
@@ -716,9 +850,173 @@ impl YjsAppState {
         }
     }
 
+    // ============= Cluster relay =============
+
+    /// Publish a message from a local client (or server-side edit) to the other instances
+    async fn relay_message(&self, doc_id: &str, msg: &[u8], contributor: Option<Uuid>) {
+        if let Some(relay) = &self.relay {
+            if cluster_relay::is_relayable(msg) {
+                relay.publish_document(doc_id, FrameKind::Message, contributor, msg).await;
+            }
+        }
+    }
+
+    /// Publish a full document state that replaces the document on the other instances
+    async fn relay_reset(&self, doc_id: &str, full_state: &[u8]) {
+        if let Some(relay) = &self.relay {
+            relay.publish_document(doc_id, FrameKind::Reset, None, full_state).await;
+        }
+    }
+
+    /// Apply a frame published by another instance
+    /// Documents not loaded here are skipped - they are read from Redis/PostgreSQL when opened.
+    async fn apply_relay_frame(&self, frame: DocumentFrame) {
+        match frame.kind {
+            FrameKind::Message => {
+                let awareness = {
+                    let documents = self.documents.read().await;
+                    match documents.get(&frame.doc_id) {
+                        Some(doc_state) => Arc::clone(&doc_state.awareness),
+                        None => return,
+                    }
+                };
+
+                let state_before = awareness.doc().transact().state_vector();
+                let responses = match DefaultProtocol.handle(&awareness, &frame.payload) {
+                    Ok(responses) => responses,
+                    Err(e) => {
+                        warn!(doc_id = %frame.doc_id, error = ?e, "Error applying relayed message");
+                        return;
+                    }
+                };
+
+                if cluster_relay::is_sync_step1(&frame.payload) {
+                    // Another instance asked for missing state - answer everyone through the relay
+                    if let Some(relay) = &self.relay {
+                        for response in responses {
+                            let encoded = response.encode_v1();
+                            relay.publish_document(&frame.doc_id, FrameKind::Message, None, &encoded).await;
+                        }
+                    }
+                    return;
+                }
+
+                self.broadcast(&frame.doc_id, "", &frame.payload).await;
+
+                let state_after = awareness.doc().transact().state_vector();
+                if state_before != state_after {
+                    self.mark_document_changed(&frame.doc_id).await;
+                    if let Some(contributor) = frame.contributor {
+                        self.add_contributor(&frame.doc_id, contributor).await;
+                    }
+                }
+            }
+            FrameKind::Reset => {
+                let Some(new_doc) = crate::utils::yjs::decode_document(&frame.payload) else {
+                    warn!(doc_id = %frame.doc_id, "Ignoring undecodable relayed document reset");
+                    return;
+                };
+                if !self.documents.read().await.contains_key(&frame.doc_id) {
+                    return;
+                }
+
+                self.replace_document(&frame.doc_id, new_doc).await;
+                self.mark_document_changed(&frame.doc_id).await;
+
+                use yrs::sync::Message;
+                let encoded = Message::Sync(yrs::sync::SyncMessage::Update(frame.payload)).encode_v1();
+                self.broadcast(&frame.doc_id, "", &encoded).await;
+                info!(doc_id = %frame.doc_id, "Applied relayed document reset");
+            }
+        }
+    }
+
+    /// Acquire or renew the persistence lease for every document held in memory
+    async fn refresh_document_ownership(&self) {
+        let Some(relay) = &self.relay else {
+            return;
+        };
+
+        let doc_ids: Vec<DocumentId> = self.documents.read().await.keys().cloned().collect();
+        let mut ownership = Vec::with_capacity(doc_ids.len());
+        for doc_id in doc_ids {
+            let owned = relay.claim_document(&doc_id).await;
+            ownership.push((doc_id, owned));
+        }
+
+        let mut documents = self.documents.write().await;
+        for (doc_id, owned) in ownership {
+            if let Some(doc_state) = documents.get_mut(&doc_id) {
+                if owned && !doc_state.is_owner {
+                    info!(doc_id = %doc_id, "Took over persistence of document");
+                }
+                doc_state.is_owner = owned;
+            }
+        }
+    }
+
+    /// Encode the current live state of a document (memory → Redis → database)
+    pub async fn encode_document_state(&self, doc_id: &str) -> Vec<u8> {
+        let awareness = self.get_or_create_awareness(doc_id).await;
+        let txn = awareness.doc().transact();
+        txn.encode_state_as_update_v1(&StateVector::default())
+    }
+
+    /// Append the content of another document to the end of a live document
+    ///
+    /// Used when merging tickets: the source notes are copied below a heading and a
+    /// separator, connected clients receive the change as a regular Yjs update.
+    pub async fn append_document_content(
+        &self,
+        doc_id: &str,
+        source_document: &[u8],
+        heading: &str,
+        contributor: Uuid,
+    ) -> bool {
+        let Some(source) = crate::utils::yjs::decode_document(source_document) else {
+            return false;
+        };
+        if crate::utils::yjs::block_count(&source) == 0 {
+            return false;
+        }
+
+        let awareness = self.get_or_create_awareness(doc_id).await;
+        let update = {
+            let doc = awareness.doc();
+            let state_before = doc.transact().state_vector();
+
+            {
+                let source_txn = source.transact();
+                let Some(source_fragment) = source_txn.get_xml_fragment(crate::utils::yjs::PROSEMIRROR_FRAGMENT) else {
+                    return false;
+                };
+
+                let mut txn = doc.transact_mut();
+                let fragment = txn.get_or_insert_xml_fragment(crate::utils::yjs::PROSEMIRROR_FRAGMENT);
+                crate::utils::yjs::push_horizontal_rule(&fragment, &mut txn);
+                crate::utils::yjs::push_heading(&fragment, &mut txn, 2, heading);
+                crate::utils::yjs::copy_children(&source_fragment, &source_txn, &fragment, &mut txn);
+            }
+
+            let txn = doc.transact();
+            txn.encode_state_as_update_v1(&state_before)
+        };
+
+        use yrs::sync::Message;
+        let encoded = Message::Sync(yrs::sync::SyncMessage::Update(update)).encode_v1();
+        self.broadcast(doc_id, "", &encoded).await;
+        self.relay_message(doc_id, &encoded, Some(contributor)).await;
+
+        self.mark_document_changed(doc_id).await;
+        self.add_contributor(doc_id, contributor).await;
+        self.save_document_by_id(doc_id).await;
+
+        true
+    }
+
 
     // Register session
-    async fn register_session(&self, doc_id: &str, session_id: &str, addr: Addr<YjsWebSocket>) {
+    async fn register_session(&self, doc_id: &str, session_id: &str, user_uuid: Uuid, addr: Addr<YjsWebSocket>) {
         let mut sessions = self.sessions.write().await;
 
         // Get or create the room for this document
@@ -741,14 +1039,10 @@ impl YjsAppState {
 
         debug!(session_id = %session_id, doc_id = %doc_id, room_size, "Session joined document");
 
-        // Broadcast viewer count change via SSE for tickets
+        // Broadcast viewer change via SSE for tickets
         if let Some(DocumentType::Ticket(ticket_id)) = DocumentType::from_doc_id(doc_id) {
-            let sse_state = self.sse_state.clone();
-            crate::utils::sse::SseBroadcaster::broadcast_viewer_count(
-                &sse_state,
-                ticket_id,
-                room_size,
-            ).await;
+            self.presence.join(ticket_id, session_id, user_uuid);
+            self.presence.announce(&self.sse_state, ticket_id).await;
         }
     }
 
@@ -777,14 +1071,10 @@ impl YjsAppState {
             // Release the sessions lock before any async operations
             drop(sessions);
 
-            // Broadcast viewer count change via SSE for tickets
+            // Broadcast viewer change via SSE for tickets
             if let Some(DocumentType::Ticket(ticket_id)) = DocumentType::from_doc_id(doc_id) {
-                let sse_state = self.sse_state.clone();
-                crate::utils::sse::SseBroadcaster::broadcast_viewer_count(
-                    &sse_state,
-                    ticket_id,
-                    room_size,
-                ).await;
+                self.presence.leave(ticket_id, session_id);
+                self.presence.announce(&self.sse_state, ticket_id).await;
             }
 
             // If room is empty, mark it as empty but don't save immediately
@@ -806,6 +1096,7 @@ impl YjsAppState {
         let now = Instant::now();
         let mut stale_session_count = 0;
         let mut newly_empty_rooms = Vec::new();
+        let mut presence_changed = HashSet::new();
 
         // First pass: collect stale sessions
         for (doc_id, room) in sessions.iter_mut() {
@@ -824,6 +1115,11 @@ impl YjsAppState {
             for session_id in stale_sessions.iter() {
                 debug!(session_id = %session_id, doc_id = %doc_id, "Removing stale session");
                 room.remove(session_id);
+                if let Some(DocumentType::Ticket(ticket_id)) = DocumentType::from_doc_id(doc_id) {
+                    if self.presence.leave(ticket_id, session_id) {
+                        presence_changed.insert(ticket_id);
+                    }
+                }
             }
 
             // If room just became empty, mark it
@@ -840,6 +1136,10 @@ impl YjsAppState {
         // Release the sessions lock before updating document states
         drop(sessions);
 
+        for ticket_id in presence_changed {
+            self.presence.announce(&self.sse_state, ticket_id).await;
+        }
+
         // Mark newly empty rooms
         if !newly_empty_rooms.is_empty() {
             let mut documents = self.documents.write().await;
@@ -856,20 +1156,26 @@ impl YjsAppState {
     async fn force_save_document(&self, doc_id: &str) {
         let mut documents = self.documents.write().await;
         if let Some(doc_state) = documents.get_mut(doc_id) {
+            if !doc_state.is_owner {
+                // Pending changes stay flagged in case this instance takes over the lease
+                debug!(doc_id = %doc_id, "Skipping final save - persisted by another instance");
+                return;
+            }
+
             debug!(doc_id = %doc_id, "Force saving document on disconnect");
             self.save_document_internal(doc_id, &doc_state.awareness);
             doc_state.mark_saved();
 
             // Create revision at end of editing session if there were actual content changes
             // Contributors are only added when content actually changes, so this is sufficient
-            if !doc_state.contributors.is_empty() {
+            if self.snapshot_policy.on_room_empty && !doc_state.contributors.is_empty() {
                 info!(doc_id = %doc_id, contributors = doc_state.contributors.len(),
                     "Creating session-end revision");
                 let contributors = doc_state.contributors.clone();
                 self.create_snapshot_revision(doc_id, &doc_state.awareness, contributors);
                 doc_state.reset_snapshot_tracking();
             } else {
-                debug!(doc_id = %doc_id, "Skipping revision - no content changes in session");
+                debug!(doc_id = %doc_id, "Skipping session-end revision");
             }
 
             // Mark final save completed so periodic task doesn't duplicate
@@ -882,7 +1188,7 @@ impl YjsAppState {
         let mut documents = self.documents.write().await;
         if let Some(doc_state) = documents.get_mut(doc_id) {
             // Force save regardless of timing constraints when explicitly called
-            if doc_state.has_pending_changes {
+            if doc_state.has_pending_changes && doc_state.is_owner {
                 self.save_document_internal(doc_id, &doc_state.awareness);
                 doc_state.mark_saved();
             }
@@ -1322,8 +1628,9 @@ impl YjsWebSocket {
                         addr.do_send(YjsMessage(Bytes::from(encoded)));
                     }
 
-                    // Broadcast the entire message to other clients
+                    // Broadcast the entire message to other clients, on this and other instances
                     app_state.broadcast(&doc_id, &session_id, &msg_vec).await;
+                    app_state.relay_message(&doc_id, &msg_vec, content_changed.then_some(user_uuid)).await;
 
                     // Mark document as changed after sync updates (even if failed)
                     // This ensures the backend saves whatever state it has
@@ -1360,9 +1667,10 @@ impl Actor for YjsWebSocket {
         let app_state = self.app_state.clone();
         let doc_id = self.doc_id.clone();
         let session_id = self.id.clone();
+        let user_uuid = self.user_uuid;
         let addr = ctx.address();
         actix::spawn(async move {
-            app_state.register_session(&doc_id, &session_id, addr).await;
+            app_state.register_session(&doc_id, &session_id, user_uuid, addr).await;
         });
 
         debug!(doc_id = %self.doc_id, "Waiting for client sync request");
@@ -1461,17 +1769,9 @@ impl Handler<YjsMessage> for YjsWebSocket {
     }
 }
 
-// WebSocket connection handler - entry point for WebSocket requests
-pub async fn ws_handler(
-    req: HttpRequest,
-    stream: web::Payload,
-    app_state: web::Data<YjsAppState>,
-    path: web::Path<String>,
-) -> Result<HttpResponse, Error> {
-    let doc_id = path.into_inner();
-    debug!(doc_id = %doc_id, "WebSocket connection request");
-
-    // Validate Origin header to prevent WebSocket hijacking (CSWSH)
+/// Reject websocket upgrades from other origins to prevent WebSocket hijacking (CSWSH)
+/// Shared with the event websocket.
+pub(crate) fn validate_ws_origin(req: &HttpRequest) -> Result<(), Error> {
     let frontend_url = std::env::var("FRONTEND_URL")
         .unwrap_or_else(|_| "http://localhost:5173".to_string());
     let allowed_origin = frontend_url.trim_end_matches('/');
@@ -1499,6 +1799,22 @@ pub async fn ws_handler(
         }
     }
 
+    Ok(())
+}
+
+// WebSocket connection handler - entry point for WebSocket requests
+pub async fn ws_handler(
+    req: HttpRequest,
+    stream: web::Payload,
+    app_state: web::Data<YjsAppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let doc_id = path.into_inner();
+    debug!(doc_id = %doc_id, "WebSocket connection request");
+
+    // Validate Origin header to prevent WebSocket hijacking (CSWSH)
+    validate_ws_origin(&req)?;
+
     // Extract and validate JWT token from httpOnly cookie
     let token = req.cookie(crate::utils::cookies::ACCESS_TOKEN_COOKIE)
         .ok_or_else(|| actix_web::error::ErrorUnauthorized("No authentication cookie"))?;
@@ -1531,6 +1847,74 @@ pub async fn ws_handler(
 
 // ============= Revision History API Endpoints =============
 
+/// Query parameters for revision diffs
+/// `to` defaults to the latest revision and `from` to the revision before `to`.
+#[derive(serde::Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: Option<i32>,
+    pub to: Option<i32>,
+}
+
+/// Validate the access token cookie for revision endpoints that expose document content
+async fn authenticate_cookie(req: &HttpRequest, conn: &mut crate::db::DbConnection) -> Result<Uuid, HttpResponse> {
+    let unauthorized = || HttpResponse::Unauthorized().json(json!({
+        "error": "Unauthorized",
+        "message": "Authentication required"
+    }));
+
+    let token = req.cookie(crate::utils::cookies::ACCESS_TOKEN_COOKIE).ok_or_else(unauthorized)?;
+    match crate::utils::jwt::JwtUtils::validate_token_with_user_check(token.value(), conn).await {
+        Ok((_claims, user)) => Ok(user.uuid),
+        Err(_) => Err(unauthorized()),
+    }
+}
+
+/// Resolve the diff range from the query, given the latest revision and a lookup for the previous one
+fn resolve_diff_range(
+    query: &RevisionDiffQuery,
+    latest: i32,
+    previous: impl FnOnce(i32) -> Option<i32>,
+) -> Result<(i32, i32), HttpResponse> {
+    let to = query.to.unwrap_or(latest);
+    let from = match query.from {
+        Some(from) => from,
+        None => previous(to).unwrap_or(to),
+    };
+
+    if from > to {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": "Invalid range",
+            "message": "'from' must not be newer than 'to'"
+        })));
+    }
+    Ok((from, to))
+}
+
+/// Run the diff and map the result to a response
+fn revision_diff_response(
+    revisions: Vec<crate::services::revision_diff::RevisionSnapshot>,
+    from: i32,
+    to: i32,
+) -> HttpResponse {
+    use crate::services::revision_diff::{DiffError, RevisionDiffService};
+
+    // Both endpoints must exist; revisions in between may have been thinned by retention
+    let has_endpoints = revisions.first().is_some_and(|r| r.revision_number == from)
+        && revisions.last().is_some_and(|r| r.revision_number == to);
+    if !has_endpoints {
+        return HttpResponse::NotFound().json("Revision not found");
+    }
+
+    match RevisionDiffService::diff(&revisions) {
+        Ok(diff) => HttpResponse::Ok().json(diff),
+        Err(e @ DiffError::Undecodable(_)) => {
+            error!(error = %e, "Error decoding revision for diff");
+            HttpResponse::InternalServerError().json("Error decoding revision")
+        }
+        Err(e) => HttpResponse::NotFound().json(e.to_string()),
+    }
+}
+
 /// GET /tickets/:id/revisions - List all revisions for a ticket
 pub async fn get_ticket_revisions(
     ticket_id: web::Path<i32>,
@@ -1599,6 +1983,61 @@ pub async fn get_ticket_revision(
     }
 }
 
+/// GET /tickets/:id/revisions/diff?from=&to= - Diff two revisions of a ticket
+pub async fn get_ticket_revision_diff(
+    req: HttpRequest,
+    ticket_id: web::Path<i32>,
+    query: web::Query<RevisionDiffQuery>,
+    pool: web::Data<crate::db::Pool>,
+) -> HttpResponse {
+    let ticket_id = ticket_id.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(response) = authenticate_cookie(&req, &mut conn).await {
+        return response;
+    }
+
+    let article_content = match crate::repository::article_content::get_article_content_by_ticket_id(&mut conn, ticket_id) {
+        Ok(content) => content,
+        Err(_) => return HttpResponse::NotFound().json("No article content found for this ticket"),
+    };
+
+    let latest = match crate::repository::article_content::get_latest_article_content_revision(&mut conn, article_content.id) {
+        Ok(revision) => revision.revision_number,
+        Err(_) => return HttpResponse::NotFound().json("No revisions found for this ticket"),
+    };
+
+    let (from, to) = match resolve_diff_range(&query, latest, |to| {
+        crate::repository::article_content::get_previous_article_content_revision_number(&mut conn, article_content.id, to)
+            .ok()
+            .flatten()
+    }) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+
+    let revisions = match crate::repository::article_content::get_article_content_revisions_between(&mut conn, article_content.id, from, to) {
+        Ok(revisions) => revisions,
+        Err(_) => return HttpResponse::InternalServerError().json("Error retrieving revisions"),
+    };
+
+    let snapshots = revisions
+        .into_iter()
+        .map(|revision| crate::services::revision_diff::RevisionSnapshot {
+            revision_number: revision.revision_number,
+            created_at: revision.created_at,
+            contributors: revision.contributed_by.into_iter().flatten().collect(),
+            content: revision.yjs_document_content,
+        })
+        .collect();
+
+    revision_diff_response(snapshots, from, to)
+}
+
 /// POST /tickets/:id/restore/:revision_number - Restore ticket to a specific revision
 pub async fn restore_ticket_revision(
     path: web::Path<(i32, i32)>,
@@ -1677,6 +2116,7 @@ pub async fn restore_ticket_revision(
     // Replace the document in app_state with the new one
     // This creates a new Awareness with the restored document
     app_state.replace_document(&doc_id, new_doc).await;
+    app_state.relay_reset(&doc_id, &full_state).await;
 
     // Mark document as changed to trigger save
     app_state.mark_document_changed(&doc_id).await;
@@ -1743,6 +2183,7 @@ pub async fn get_doc_revision(
                 "title": revision.title,
                 "yjs_document_content": content_base64,
                 "created_by": revision.created_by,
+                "contributed_by": revision.contributed_by,
                 "created_at": revision.created_at,
                 "change_summary": revision.change_summary,
             }))
@@ -1751,6 +2192,56 @@ pub async fn get_doc_revision(
     }
 }
 
+/// GET /docs/:id/revisions/diff?from=&to= - Diff two revisions of a documentation page
+pub async fn get_doc_revision_diff(
+    req: HttpRequest,
+    doc_id: web::Path<i32>,
+    query: web::Query<RevisionDiffQuery>,
+    pool: web::Data<crate::db::Pool>,
+) -> HttpResponse {
+    let doc_id = doc_id.into_inner();
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(response) = authenticate_cookie(&req, &mut conn).await {
+        return response;
+    }
+
+    let latest = match crate::repository::documentation::get_latest_documentation_revision(&mut conn, doc_id) {
+        Ok(revision) => revision.revision_number,
+        Err(_) => return HttpResponse::NotFound().json("No revisions found for this page"),
+    };
+
+    let (from, to) = match resolve_diff_range(&query, latest, |to| {
+        crate::repository::documentation::get_previous_documentation_revision_number(&mut conn, doc_id, to)
+            .ok()
+            .flatten()
+    }) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+
+    let revisions = match crate::repository::documentation::get_documentation_revisions_between(&mut conn, doc_id, from, to) {
+        Ok(revisions) => revisions,
+        Err(_) => return HttpResponse::InternalServerError().json("Error retrieving revisions"),
+    };
+
+    let snapshots = revisions
+        .into_iter()
+        .map(|revision| crate::services::revision_diff::RevisionSnapshot {
+            revision_number: revision.revision_number,
+            created_at: revision.created_at,
+            contributors: revision.contributed_by.into_iter().flatten().collect(),
+            content: revision.yjs_document_snapshot,
+        })
+        .collect();
+
+    revision_diff_response(snapshots, from, to)
+}
+
 /// POST /docs/:id/restore/:revision_number - Restore documentation page to a specific revision
 pub async fn restore_doc_revision(
     path: web::Path<(i32, i32)>,
@@ -1822,6 +2313,7 @@ pub async fn restore_doc_revision(
 
     // Replace the document in app_state with the new one
     app_state.replace_document(&doc_id_str, new_doc).await;
+    app_state.relay_reset(&doc_id_str, &full_state).await;
 
     // Mark document as changed to trigger save
     app_state.mark_document_changed(&doc_id_str).await;
@@ -1847,9 +2339,11 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/article/{doc_id}", web::get().to(get_article_content))
             .route("/ws/{doc_id}", web::get().to(ws_handler))
             .route("/tickets/{ticket_id}/revisions", web::get().to(get_ticket_revisions))
+            .route("/tickets/{ticket_id}/revisions/diff", web::get().to(get_ticket_revision_diff))
             .route("/tickets/{ticket_id}/revisions/{revision_number}", web::get().to(get_ticket_revision))
             .route("/tickets/{ticket_id}/restore/{revision_number}", web::post().to(restore_ticket_revision))
             .route("/docs/{doc_id}/revisions", web::get().to(get_doc_revisions))
+            .route("/docs/{doc_id}/revisions/diff", web::get().to(get_doc_revision_diff))
             .route("/docs/{doc_id}/revisions/{revision_number}", web::get().to(get_doc_revision))
             .route("/docs/{doc_id}/restore/{revision_number}", web::post().to(restore_doc_revision))
     );
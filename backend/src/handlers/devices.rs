@@ -458,7 +458,7 @@ pub async fn delete_device(
         Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
     };
 
-    match repository::delete_device(&mut conn, device_id) {
+    match repository::delete_device(&mut conn, device_id, Uuid::parse_str(&claims.sub).ok()) {
         Ok(rows_affected) => {
             if rows_affected > 0 {
                 HttpResponse::Ok().json(json!({
@@ -671,9 +671,10 @@ pub async fn bulk_devices(
 
     match action {
         "delete" => {
+            let deleted_by = Uuid::parse_str(&claims.sub).ok();
             let mut deleted = 0;
             for id in ids {
-                match repository::delete_device(&mut conn, *id) {
+                match repository::delete_device(&mut conn, *id, deleted_by) {
                     Ok(rows) => deleted += rows,
                     Err(e) => {
                         error!(device_id = *id, error = ?e, "Error deleting device in bulk operation");
@@ -1,62 +1,21 @@
 use actix_web::{web, HttpResponse, HttpRequest, HttpMessage, Responder};
+use actix_multipart::Multipart;
+use futures::StreamExt;
 use chrono::Utc;
 use serde::Deserialize;
 use serde_json::json;
 use tracing::{debug, error, info};
 use uuid::Uuid;
-use yrs::{Doc, Transact, ReadTxn, WriteTxn, GetString, Options, updates::decoder::Decode, Update, XmlFragment, XmlOut};
-use std::panic;
+use yrs::{Doc, Transact, ReadTxn, WriteTxn, Options, updates::decoder::Decode, Update, XmlFragment};
 use regex::Regex;
 
 use crate::db::{Pool, DbConnection};
 use crate::models::{Claims, NewDocumentationPage, DocumentationPageWithChildren, DocumentationStatus, DocumentationPage, DocumentationPageResponse, UserInfoWithAvatar};
 use crate::repository;
+use crate::services::doc_templates::{DocTemplateError, DocTemplateService, InstantiateOptions};
 use crate::utils;
 use crate::utils::rbac::{is_admin, is_technician_or_admin};
 
-/// Recursively extract plain text from an XmlOut node
-fn extract_text_from_xml_node(node: &XmlOut, txn: &yrs::Transaction) -> String {
-    match node {
-        XmlOut::Text(text_ref) => {
-            // XmlTextRef::get_string returns the text content
-            match panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                text_ref.get_string(txn)
-            })) {
-                Ok(s) => s,
-                Err(_) => String::new(),
-            }
-        }
-        XmlOut::Element(elem_ref) => {
-            // Recursively extract text from element's children
-            let mut text = String::new();
-            for child in elem_ref.children(txn) {
-                let child_text = extract_text_from_xml_node(&child, txn);
-                if !child_text.is_empty() {
-                    if !text.is_empty() {
-                        text.push(' ');
-                    }
-                    text.push_str(&child_text);
-                }
-            }
-            text
-        }
-        XmlOut::Fragment(frag_ref) => {
-            // Recursively extract text from fragment's children
-            let mut text = String::new();
-            for child in frag_ref.children(txn) {
-                let child_text = extract_text_from_xml_node(&child, txn);
-                if !child_text.is_empty() {
-                    if !text.is_empty() {
-                        text.push(' ');
-                    }
-                    text.push_str(&child_text);
-                }
-            }
-            text
-        }
-    }
-}
-
 /// Extract text content from a Yjs document binary blob
 /// Returns the plain text content extracted from the ProseMirror XmlFragment
 fn extract_yjs_content(yjs_document: &[u8]) -> Option<String> {
@@ -93,7 +52,7 @@ fn extract_yjs_content(yjs_document: &[u8]) -> Option<String> {
 
         // Iterate through top-level children (paragraphs, headings, etc.)
         for child in fragment.children(&txn) {
-            let child_text = extract_text_from_xml_node(&child, &txn);
+            let child_text = utils::yjs::node_text(&child, &txn);
             if !child_text.is_empty() {
                 text_parts.push(child_text);
             }
@@ -508,8 +467,8 @@ pub async fn delete_documentation_page(
     // Check if the page exists
     match repository::get_documentation_page(page_id, &mut conn) {
         Ok(_) => {
-            // Delete the page
-            match repository::delete_documentation_page(page_id, &mut conn) {
+            // Move the page and its subpages to the recycle bin
+            match repository::delete_documentation_page(page_id, Uuid::parse_str(&claims.sub).ok(), &mut conn) {
                 Ok(_) => {
                     info!(page_id = page_id, deleted_by = %claims.name, "Documentation page deleted");
                     HttpResponse::NoContent().finish()
@@ -829,6 +788,17 @@ pub struct CreateDocPageFromTicket {
     pub description: Option<String>,
     pub icon: Option<String>,
     pub parent_id: Option<i32>,
+    /// Template to start from; defaults to the ticket category's documentation template
+    pub template_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct InstantiateTemplateRequest {
+    /// Title for the new page; placeholders are filled in. Defaults to the template's title
+    pub title: Option<String>,
+    pub icon: Option<String>,
+    pub parent_id: Option<i32>,
+    pub ticket_id: Option<i32>,
 }
 
 // Response struct for documentation export (minimal fields needed for markdown export)
@@ -896,6 +866,361 @@ pub async fn export_documentation_pages(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DocumentationExportQuery {
+    /// `markdown` (default) or `html`
+    pub format: Option<String>,
+    /// Include child pages (exports a zip)
+    pub subtree: Option<bool>,
+}
+
+fn parse_export_format(format: Option<&str>) -> Result<crate::services::doc_export::ExportFormat, HttpResponse> {
+    use crate::services::doc_export::ExportFormat;
+
+    match format {
+        None => Ok(ExportFormat::Markdown),
+        Some(value) => ExportFormat::parse(value).ok_or_else(|| {
+            HttpResponse::BadRequest().json(json!({
+                "error": "Invalid format",
+                "message": "Format must be 'markdown' or 'html'"
+            }))
+        }),
+    }
+}
+
+fn export_response(
+    result: Result<crate::services::doc_export::ExportedFile, crate::services::doc_export::DocExportError>,
+) -> HttpResponse {
+    use crate::services::doc_export::DocExportError;
+
+    match result {
+        Ok(file) => HttpResponse::Ok()
+            .content_type(file.content_type)
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", file.filename),
+            ))
+            .body(file.data),
+        Err(DocExportError::PageNotFound(_)) => HttpResponse::NotFound().json("Page not found"),
+        Err(e) => {
+            error!(error = %e, "Documentation export failed");
+            HttpResponse::InternalServerError().json("Failed to export documentation")
+        }
+    }
+}
+
+// Export a page as Markdown or standalone HTML, or the page with its children as a zip
+pub async fn export_documentation_page(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+    path: web::Path<i32>,
+    query: web::Query<DocumentationExportQuery>,
+) -> impl Responder {
+    use crate::services::doc_export::DocExportService;
+
+    let page_id = path.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can export documentation"
+        }));
+    }
+
+    let format = match parse_export_format(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(response) => return response,
+    };
+
+    let result = if query.subtree.unwrap_or(false) {
+        DocExportService::export_tree(&mut conn, storage.get_ref().as_ref(), Some(page_id), format).await
+    } else {
+        DocExportService::export_page(&mut conn, storage.get_ref().as_ref(), page_id, format).await
+    };
+    export_response(result)
+}
+
+// Export the whole knowledge base as a zip of Markdown or HTML files
+pub async fn export_knowledge_base(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+    query: web::Query<DocumentationExportQuery>,
+) -> impl Responder {
+    use crate::services::doc_export::DocExportService;
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can export documentation"
+        }));
+    }
+
+    let format = match parse_export_format(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(response) => return response,
+    };
+
+    export_response(DocExportService::export_tree(&mut conn, storage.get_ref().as_ref(), None, format).await)
+}
+
+#[derive(Deserialize)]
+pub struct DocumentationImportQuery {
+    /// Page to import under (top level when omitted)
+    pub parent_id: Option<i32>,
+    /// Status for pages whose front-matter doesn't set one: draft (default) or published
+    pub status: Option<String>,
+}
+
+// Import a zip of Markdown/HTML files as documentation pages
+pub async fn import_documentation(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+    query: web::Query<DocumentationImportQuery>,
+    mut payload: Multipart,
+) -> impl Responder {
+    use crate::services::doc_import::{DocImportError, DocImportService, ImportOptions, MAX_ARCHIVE_BYTES};
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can import documentation"
+        }));
+    }
+
+    let author = match utils::parse_uuid(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID in token"),
+    };
+
+    let status = match query.status.as_deref() {
+        None | Some("draft") => DocumentationStatus::Draft,
+        Some("published") => DocumentationStatus::Published,
+        Some(other) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid status",
+            "message": format!("Unsupported status '{}', expected draft or published", other)
+        })),
+    };
+
+    // Read the uploaded archive (the first file field)
+    let mut archive: Option<Vec<u8>> = None;
+    while let Some(item) = payload.next().await {
+        let mut field = match item {
+            Ok(field) => field,
+            Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("Upload error: {}", e)})),
+        };
+        if archive.is_some() || field.content_disposition().get_filename().is_none() {
+            continue;
+        }
+
+        let mut data = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => return HttpResponse::BadRequest().json(json!({"error": format!("Upload error: {}", e)})),
+            };
+            if data.len() + chunk.len() > MAX_ARCHIVE_BYTES {
+                return HttpResponse::PayloadTooLarge().json(json!({
+                    "error": "File too large",
+                    "message": format!("Documentation archives are limited to {} MB", MAX_ARCHIVE_BYTES / 1024 / 1024)
+                }));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        archive = Some(data);
+    }
+
+    let Some(archive) = archive else {
+        return HttpResponse::BadRequest().json(json!({"error": "No file uploaded"}));
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let options = ImportOptions {
+        parent_id: query.parent_id,
+        status,
+        author,
+    };
+
+    match DocImportService::import_archive(&mut conn, storage.get_ref().as_ref(), &archive, &options).await {
+        Ok(report) => {
+            info!(pages = report.pages.len(), images = report.images_uploaded, "Imported documentation");
+            HttpResponse::Created().json(report)
+        }
+        Err(DocImportError::ParentNotFound(_)) => HttpResponse::NotFound().json("Parent page not found"),
+        Err(e @ (DocImportError::InvalidArchive(_) | DocImportError::NoDocuments | DocImportError::ZipError(_))) => {
+            HttpResponse::BadRequest().json(json!({
+                "error": "Invalid archive",
+                "message": e.to_string()
+            }))
+        }
+        Err(e) => {
+            error!(error = %e, "Documentation import failed");
+            HttpResponse::InternalServerError().json("Failed to import documentation")
+        }
+    }
+}
+
+// Serve images stored for documentation pages
+pub async fn serve_documentation_file(
+    path: web::Path<String>,
+    req: HttpRequest,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+) -> impl Responder {
+    let file_path = format!("documentation/{}", path.into_inner());
+    match crate::utils::storage::serve_file_from_storage(storage.get_ref().clone(), &file_path, &req).await {
+        Ok(response) => response,
+        Err(_) => HttpResponse::NotFound().finish(),
+    }
+}
+
+// List documentation templates
+pub async fn get_documentation_templates(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can use documentation templates"
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match DocTemplateService::list_templates(&mut conn) {
+        Ok(templates) => match to_page_responses(templates, &mut conn) {
+            Ok(responses) => HttpResponse::Ok().json(responses),
+            Err(err) => HttpResponse::InternalServerError().json(err),
+        },
+        Err(_) => HttpResponse::InternalServerError().json("Failed to fetch templates"),
+    }
+}
+
+// Create a new page from a template, filling in its placeholders
+pub async fn instantiate_documentation_template(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: web::Json<InstantiateTemplateRequest>,
+) -> impl Responder {
+    let template_id = path.into_inner();
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can create documentation pages"
+        }));
+    }
+
+    let user_uuid = match utils::parse_uuid(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID in token"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let template = match DocTemplateService::get_template(&mut conn, template_id) {
+        Ok(template) => template,
+        Err(DocTemplateError::TemplateNotFound(_)) => return HttpResponse::NotFound().json("Template not found"),
+        Err(DocTemplateError::NotATemplate(_)) => return HttpResponse::BadRequest().json(json!({
+            "error": "Not a template",
+            "message": "The page is not marked as a template"
+        })),
+        Err(_) => return HttpResponse::InternalServerError().json("Failed to load template"),
+    };
+
+    let request = body.into_inner();
+    if let Some(ticket_id) = request.ticket_id {
+        if repository::get_ticket_by_id(&mut conn, ticket_id).is_err() {
+            return HttpResponse::NotFound().json("Ticket not found");
+        }
+    }
+
+    let options = InstantiateOptions {
+        title: request.title,
+        slug: None,
+        icon: request.icon,
+        parent_id: request.parent_id,
+        ticket_id: request.ticket_id,
+        author: user_uuid,
+    };
+
+    match DocTemplateService::instantiate(&mut conn, &template, options) {
+        Ok(page) => {
+            info!(template_id = template_id, page_id = page.id, "Created documentation page from template");
+            match to_page_response(page, &mut conn) {
+                Ok(response) => HttpResponse::Created().json(response),
+                Err(err) => HttpResponse::InternalServerError().json(err),
+            }
+        }
+        Err(e) => {
+            error!(template_id = template_id, error = %e, "Error instantiating documentation template");
+            HttpResponse::InternalServerError().json("Failed to create page from template")
+        }
+    }
+}
+
 // Create a documentation page from a ticket's article content
 pub async fn create_documentation_page_from_ticket(
     req: HttpRequest,
@@ -955,6 +1280,40 @@ pub async fn create_documentation_page_from_ticket(
         Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID in token"),
     };
 
+    // Start from the requested template, or the one configured for the ticket's category
+    let template = match page_data.template_id {
+        Some(template_id) => match DocTemplateService::get_template(&mut conn, template_id) {
+            Ok(template) => Some(template),
+            Err(DocTemplateError::DatabaseError(e)) => {
+                error!(template_id = template_id, error = ?e, "Error loading documentation template");
+                return HttpResponse::InternalServerError().json("Failed to load template");
+            }
+            Err(e) => return HttpResponse::BadRequest().json(e.to_string()),
+        },
+        None => repository::get_ticket_by_id(&mut conn, ticket_id)
+            .ok()
+            .and_then(|ticket| ticket.category_id)
+            .and_then(|category_id| DocTemplateService::template_for_category(&mut conn, category_id).ok().flatten()),
+    };
+
+    if let Some(template) = template {
+        let options = InstantiateOptions {
+            title: Some(page_data.title.clone()),
+            slug: Some(slug),
+            icon: page_data.icon.clone(),
+            parent_id: page_data.parent_id,
+            ticket_id: Some(ticket_id),
+            author: user_uuid,
+        };
+        return match DocTemplateService::instantiate(&mut conn, &template, options) {
+            Ok(page) => HttpResponse::Created().json(page),
+            Err(e) => {
+                error!(ticket_id = ticket_id, template_id = template.id, error = %e, "Error creating documentation page from template");
+                HttpResponse::InternalServerError().json("Failed to create documentation page")
+            }
+        };
+    }
+
     let new_page = NewDocumentationPage {
         uuid: Uuid::now_v7(),
         title: page_data.title.clone(),
@@ -0,0 +1,415 @@
+//! Multiplexed event websocket
+//!
+//! One connection per client carrying what otherwise takes an SSE stream plus REST polling:
+//! - `TicketEvent` notifications, with the same filters, permissions and replay as the SSE stream
+//! - request/response RPC for common ticket operations
+//! - ticket presence (who is viewing which ticket)
+//!
+//! Messages are JSON text frames tagged by `type`. Authentication uses the short-lived token
+//! from `POST /api/events/token`, passed as the `sse_token` query parameter.
+
+use actix::{Actor, ActorContext, AsyncContext, Handler, Message, Running, StreamHandler};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::handlers::sse::{SseEnvelope, SseState, TicketEvent};
+use crate::models::Claims;
+use crate::repository;
+use crate::services::event_filter::{EventAccess, EventFilter, TicketParties};
+use crate::utils::presence::PresenceRegistry;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Messages sent by the client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    /// Replace the event filters (same meaning as the SSE stream query parameters)
+    Subscribe {
+        ticket_ids: Option<Vec<i32>>,
+        assigned: Option<String>,
+        categories: Option<Vec<String>>,
+    },
+    /// Call a method; the reply carries the same `id`
+    Rpc {
+        id: Value,
+        method: String,
+        #[serde(default)]
+        params: Value,
+    },
+    /// Start viewing a ticket
+    View { ticket_id: i32 },
+    /// Stop viewing a ticket
+    Leave { ticket_id: i32 },
+    Ping,
+}
+
+/// Messages sent to the client
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Event { id: Uuid, event: TicketEvent },
+    /// Events were missed and cannot be replayed - refetch state
+    Resync,
+    Subscribed,
+    RpcResult { id: Value, result: Value },
+    RpcError { id: Value, status: u16, error: Value },
+    Pong,
+    Error { message: String },
+}
+
+/// Reply produced by background work, delivered through the actor
+#[derive(Message)]
+#[rtype(result = "()")]
+struct SocketReply(ServerMessage);
+
+type RpcError = (u16, Value);
+
+struct EventSocket {
+    id: String,
+    claims: Claims,
+    user_uuid: Uuid,
+    is_staff: bool,
+    pool: web::Data<crate::db::Pool>,
+    sse_state: web::Data<SseState>,
+    presence: web::Data<PresenceRegistry>,
+    access: EventAccess,
+    last_event_id: Option<Uuid>,
+    // Ids already sent from the replay buffer, skipped if they also arrive live
+    replayed: HashSet<Uuid>,
+    viewing: HashSet<i32>,
+    hb: Instant,
+}
+
+impl EventSocket {
+    fn send(&self, message: &ServerMessage, ctx: &mut ws::WebsocketContext<Self>) {
+        match serde_json::to_string(message) {
+            Ok(text) => ctx.text(text),
+            Err(e) => warn!(session_id = %self.id, error = %e, "Failed to serialize websocket message"),
+        }
+    }
+
+    fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+            if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+                warn!(session_id = %act.id, "Event websocket heartbeat timeout, disconnecting");
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+    }
+
+    fn handle_text(&mut self, text: &str, ctx: &mut ws::WebsocketContext<Self>) {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(e) => {
+                self.send(&ServerMessage::Error { message: format!("Invalid message: {}", e) }, ctx);
+                return;
+            }
+        };
+
+        match message {
+            ClientMessage::Subscribe { ticket_ids, assigned, categories } => {
+                match EventFilter::from_parts(ticket_ids, assigned.as_deref(), categories.as_deref()) {
+                    Ok(filter) => {
+                        self.access.set_filter(filter);
+                        self.send(&ServerMessage::Subscribed, ctx);
+                    }
+                    Err(message) => self.send(&ServerMessage::Error { message }, ctx),
+                }
+            }
+            ClientMessage::Rpc { id, method, params } => {
+                let pool = self.pool.clone();
+                let sse_state = self.sse_state.clone();
+                let presence = self.presence.clone();
+                let claims = self.claims.clone();
+                let addr = ctx.address();
+                actix::spawn(async move {
+                    let reply = match dispatch_rpc(&pool, &sse_state, &presence, &claims, &method, params).await {
+                        Ok(result) => ServerMessage::RpcResult { id, result },
+                        Err((status, error)) => ServerMessage::RpcError { id, status, error },
+                    };
+                    addr.do_send(SocketReply(reply));
+                });
+            }
+            ClientMessage::View { ticket_id } => {
+                let visible = match self.pool.get() {
+                    Ok(mut conn) => can_view_ticket(&mut conn, ticket_id, self.user_uuid, self.is_staff).is_ok(),
+                    Err(_) => false,
+                };
+                if !visible {
+                    self.send(&ServerMessage::Error { message: format!("Ticket {} not found", ticket_id) }, ctx);
+                    return;
+                }
+                if self.presence.join(ticket_id, &self.id, self.user_uuid) {
+                    self.viewing.insert(ticket_id);
+                    self.announce(ticket_id);
+                }
+            }
+            ClientMessage::Leave { ticket_id } => {
+                if self.viewing.remove(&ticket_id) && self.presence.leave(ticket_id, &self.id) {
+                    self.announce(ticket_id);
+                }
+            }
+            ClientMessage::Ping => self.send(&ServerMessage::Pong, ctx),
+        }
+    }
+
+    fn announce(&self, ticket_id: i32) {
+        let presence = self.presence.clone();
+        let sse_state = self.sse_state.clone();
+        actix::spawn(async move {
+            presence.announce(&sse_state, ticket_id).await;
+        });
+    }
+}
+
+impl Actor for EventSocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.hb(ctx);
+        self.sse_state.add_client(self.id.clone(), self.claims.sub.clone());
+
+        // Subscribe before reading the replay buffer so nothing falls in between
+        let receiver = self.sse_state.sender.subscribe();
+        if let Some(last_event_id) = self.last_event_id {
+            match self.sse_state.events_after(last_event_id) {
+                Some(missed) => {
+                    for envelope in missed {
+                        self.replayed.insert(envelope.id);
+                        if self.access.allows(&envelope.event, &envelope.subject) {
+                            self.send(&ServerMessage::Event { id: envelope.id, event: envelope.event }, ctx);
+                        }
+                    }
+                }
+                None => self.send(&ServerMessage::Resync, ctx),
+            }
+        }
+        ctx.add_stream(BroadcastStream::new(receiver));
+
+        debug!(session_id = %self.id, user_uuid = %self.user_uuid, "Event websocket started");
+    }
+
+    fn stopping(&mut self, _: &mut Self::Context) -> Running {
+        self.sse_state.remove_client(&self.id);
+
+        let ticket_ids: Vec<i32> = self.viewing.drain().collect();
+        for ticket_id in ticket_ids {
+            if self.presence.leave(ticket_id, &self.id) {
+                self.announce(ticket_id);
+            }
+        }
+
+        Running::Stop
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for EventSocket {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => {
+                self.hb = Instant::now();
+                ctx.pong(&msg);
+            }
+            Ok(ws::Message::Pong(_)) => {
+                self.hb = Instant::now();
+            }
+            Ok(ws::Message::Text(text)) => {
+                self.hb = Instant::now();
+                self.handle_text(&text, ctx);
+            }
+            Ok(ws::Message::Binary(_)) => {
+                self.send(&ServerMessage::Error { message: "Binary messages are not supported".to_string() }, ctx);
+            }
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(ws::Message::Continuation(_)) | Ok(ws::Message::Nop) => {}
+            Err(e) => {
+                warn!(session_id = %self.id, error = ?e, "Event websocket protocol error");
+                ctx.stop();
+            }
+        }
+    }
+}
+
+impl StreamHandler<Result<SseEnvelope, BroadcastStreamRecvError>> for EventSocket {
+    fn handle(&mut self, item: Result<SseEnvelope, BroadcastStreamRecvError>, ctx: &mut Self::Context) {
+        match item {
+            Ok(envelope) => {
+                if self.replayed.remove(&envelope.id) || !self.access.allows(&envelope.event, &envelope.subject) {
+                    return;
+                }
+                self.send(&ServerMessage::Event { id: envelope.id, event: envelope.event }, ctx);
+            }
+            Err(BroadcastStreamRecvError::Lagged(count)) => {
+                // Unlike SSE the socket stays open; the client refetches instead of reconnecting
+                warn!(session_id = %self.id, count, "Event websocket lagged, requesting resync");
+                self.send(&ServerMessage::Resync, ctx);
+            }
+        }
+    }
+}
+
+impl Handler<SocketReply> for EventSocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: SocketReply, ctx: &mut Self::Context) {
+        self.send(&msg.0, ctx);
+    }
+}
+
+/// Non-staff users may only act on tickets they requested, are assigned to or created
+fn can_view_ticket(
+    conn: &mut crate::db::DbConnection,
+    ticket_id: i32,
+    user: Uuid,
+    is_staff: bool,
+) -> Result<(), RpcError> {
+    let ticket = repository::get_ticket_by_id(conn, ticket_id)
+        .map_err(|_| (404, json!("Ticket not found")))?;
+    if is_staff || TicketParties::from(&ticket).includes(user) {
+        Ok(())
+    } else {
+        // Same answer as a missing ticket, so ids can't be probed
+        Err((404, json!("Ticket not found")))
+    }
+}
+
+fn ticket_id_param(params: &Value) -> Result<i32, RpcError> {
+    params
+        .get("ticket_id")
+        .and_then(Value::as_i64)
+        .map(|id| id as i32)
+        .ok_or((400, json!("ticket_id is required")))
+}
+
+/// Turn an error response from a shared handler helper into an RPC error
+async fn rpc_error_from_response(response: HttpResponse) -> RpcError {
+    let status = response.status().as_u16();
+    let body = actix_web::body::to_bytes(response.into_body())
+        .await
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+        .unwrap_or(Value::Null);
+    (status, body)
+}
+
+/// Supported methods:
+/// - `ticket.get` `{ticket_id}` - the complete ticket
+/// - `ticket.update` `{ticket_id, changes}` - same fields as `PATCH /api/tickets/{id}`
+/// - `ticket.viewers` `{ticket_id}` - users currently viewing the ticket
+async fn dispatch_rpc(
+    pool: &web::Data<crate::db::Pool>,
+    sse_state: &web::Data<SseState>,
+    presence: &web::Data<PresenceRegistry>,
+    claims: &Claims,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    let mut conn = pool.get().map_err(|_| (500, json!("Database connection error")))?;
+    let user = Uuid::parse_str(&claims.sub).map_err(|_| (401, json!("Invalid user in token")))?;
+    let is_staff = claims.role == "admin" || claims.role == "technician";
+
+    match method {
+        "ticket.get" => {
+            let ticket_id = ticket_id_param(&params)?;
+            can_view_ticket(&mut conn, ticket_id, user, is_staff)?;
+            repository::get_complete_ticket(&mut conn, ticket_id)
+                .map(|ticket| json!(ticket))
+                .map_err(|_| (404, json!("Ticket not found")))
+        }
+        "ticket.update" => {
+            let ticket_id = ticket_id_param(&params)?;
+            can_view_ticket(&mut conn, ticket_id, user, is_staff)?;
+            let changes = params
+                .get("changes")
+                .filter(|changes| changes.is_object())
+                .cloned()
+                .ok_or((400, json!("changes must be an object")))?;
+
+            match crate::handlers::tickets::apply_ticket_update(&mut conn, sse_state, claims, ticket_id, changes).await {
+                Ok(ticket) => Ok(json!(ticket)),
+                Err(response) => Err(rpc_error_from_response(response).await),
+            }
+        }
+        "ticket.viewers" => {
+            let ticket_id = ticket_id_param(&params)?;
+            can_view_ticket(&mut conn, ticket_id, user, is_staff)?;
+            Ok(json!({ "ticket_id": ticket_id, "viewers": presence.viewers(ticket_id).await }))
+        }
+        _ => Err((404, json!(format!("Unknown method '{}'", method)))),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EventSocketQuery {
+    sse_token: Option<String>,
+    ticket_ids: Option<String>,
+    assigned: Option<String>,
+    categories: Option<String>,
+    last_event_id: Option<String>,
+}
+
+// Event websocket endpoint (auth via SSE token, like the SSE stream)
+pub async fn events_socket(
+    req: HttpRequest,
+    stream: web::Payload,
+    pool: web::Data<crate::db::Pool>,
+    sse_state: web::Data<SseState>,
+    presence: web::Data<PresenceRegistry>,
+    query: web::Query<EventSocketQuery>,
+) -> Result<HttpResponse, Error> {
+    crate::handlers::collaboration::validate_ws_origin(&req)?;
+
+    let token = query
+        .sse_token
+        .as_deref()
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Missing SSE token"))?;
+
+    let mut conn = pool
+        .get()
+        .map_err(|_| actix_web::error::ErrorInternalServerError("Database connection failed"))?;
+
+    use crate::utils::jwt::JwtUtils;
+    let (claims, user) = JwtUtils::validate_token_with_user_check(token, &mut conn)
+        .await
+        .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid or expired token"))?;
+
+    let filter = EventFilter::from_query(
+        query.ticket_ids.as_deref(),
+        query.assigned.as_deref(),
+        query.categories.as_deref(),
+    )
+    .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let is_staff = claims.role == "admin" || claims.role == "technician";
+    let actor = EventSocket {
+        id: Uuid::now_v7().to_string(),
+        user_uuid: user.uuid,
+        is_staff,
+        access: EventAccess::new(user.uuid, is_staff, filter),
+        claims,
+        pool: pool.clone(),
+        sse_state: sse_state.clone(),
+        presence: presence.clone(),
+        last_event_id: query.last_event_id.as_deref().and_then(|id| Uuid::parse_str(id.trim()).ok()),
+        replayed: HashSet::new(),
+        viewing: HashSet::new(),
+        hb: Instant::now(),
+    };
+
+    ws::start(actor, &req, stream)
+}
@@ -436,59 +436,14 @@ pub async fn cleanup_temp_files(
         })));
     }
 
-    let storage_path = std::env::var("STORAGE_PATH").unwrap_or_else(|_| "uploads".to_string());
-    let temp_dir = format!("{}/temp", storage_path);
-    let max_age = std::time::Duration::from_secs(24 * 60 * 60); // 24 hours
-
-    let mut files_removed = 0;
-    let mut files_checked = 0;
-    let mut bytes_freed: u64 = 0;
-    let mut errors: Vec<String> = Vec::new();
-
-    if let Ok(entries) = std::fs::read_dir(&temp_dir) {
-        for entry in entries.flatten() {
-            files_checked += 1;
-            let path = entry.path();
-
-            if path.is_file() {
-                if let Ok(metadata) = entry.metadata() {
-                    if let Ok(modified) = metadata.modified() {
-                        if let Ok(age) = std::time::SystemTime::now().duration_since(modified) {
-                            if age > max_age {
-                                let size = metadata.len();
-                                if let Err(e) = std::fs::remove_file(&path) {
-                                    errors.push(format!("Failed to delete {:?}: {}", path, e));
-                                } else {
-                                    files_removed += 1;
-                                    bytes_freed += size;
-                                    debug!(path = ?path, age_hours = age.as_secs() / 3600, "Removed stale temp file");
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    } else {
-        info!(temp_dir = %temp_dir, "Temp directory does not exist or is not accessible");
-    }
-
-    info!(
-        files_checked,
-        files_removed,
-        bytes_freed_mb = bytes_freed / (1024 * 1024),
-        "Temp file cleanup completed"
+    let stats = crate::services::admin::cleanup_temp_files(
+        &crate::services::admin::temp_dir(),
+        crate::services::admin::TEMP_FILE_MAX_AGE,
     );
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "status": "success",
         "message": "Temp file cleanup completed",
-        "stats": {
-            "files_checked": files_checked,
-            "files_removed": files_removed,
-            "bytes_freed": bytes_freed,
-            "bytes_freed_mb": bytes_freed / (1024 * 1024),
-            "errors": errors
-        }
+        "stats": stats
     })))
 } 
\ No newline at end of file
@@ -0,0 +1,226 @@
+//! Knowledge base article suggestions for tickets
+//!
+//! Suggestions for an existing ticket (ticket detail) and for a ticket that is still being
+//! written (pre-submit), plus click tracking and deflection statistics.
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::db::Pool;
+use crate::models::{Claims, KbSuggestionSource, NewKbSuggestionClick};
+use crate::repository;
+use crate::services::event_filter::TicketParties;
+use crate::services::kb_suggestions::{self, KbSuggestionIndex};
+use crate::utils;
+use crate::utils::rbac::is_technician_or_admin;
+
+const DEFAULT_LIMIT: usize = 5;
+const MAX_LIMIT: usize = 20;
+/// Longest title/description considered for suggestions or stored with a click
+const MAX_QUERY_LENGTH: usize = 4000;
+const DEFAULT_STATS_DAYS: i64 = 30;
+
+fn truncate(text: &str) -> &str {
+    match text.char_indices().nth(MAX_QUERY_LENGTH) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SuggestionRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct SuggestionLimitQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct SuggestionClickRequest {
+    pub page_id: i32,
+    pub source: KbSuggestionSource,
+    pub ticket_id: Option<i32>,
+    /// Title and description the suggestion was made for (pre-submit)
+    pub query: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SuggestionStatsQuery {
+    pub days: Option<i64>,
+}
+
+// POST /api/kb/suggestions - Suggestions for a ticket that hasn't been submitted yet
+pub async fn suggest_for_draft(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    index: web::Data<KbSuggestionIndex>,
+    body: web::Json<SuggestionRequest>,
+) -> impl Responder {
+    if req.extensions().get::<Claims>().is_none() {
+        return HttpResponse::Unauthorized().json("Authentication required");
+    }
+
+    let title = truncate(body.title.as_deref().unwrap_or(""));
+    let description = truncate(body.description.as_deref().unwrap_or(""));
+    if title.trim().is_empty() && description.trim().is_empty() {
+        return HttpResponse::Ok().json(Vec::<kb_suggestions::Suggestion>::new());
+    }
+    let limit = body.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match index.get(&mut conn) {
+        Ok(index) => HttpResponse::Ok().json(index.suggest(title, description, limit, &[])),
+        Err(e) => {
+            error!(error = %e, "Failed to build knowledge base suggestion index");
+            HttpResponse::InternalServerError().json("Failed to load suggestions")
+        }
+    }
+}
+
+// GET /api/tickets/{id}/kb-suggestions - Suggestions for an existing ticket
+pub async fn suggest_for_ticket(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    index: web::Data<KbSuggestionIndex>,
+    path: web::Path<i32>,
+    query: web::Query<SuggestionLimitQuery>,
+) -> impl Responder {
+    let ticket_id = path.into_inner();
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json("Authentication required"),
+    };
+    let user_uuid = match utils::parse_uuid(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    // Non-staff users only see suggestions for their own tickets
+    let ticket = match repository::get_ticket_by_id(&mut conn, ticket_id) {
+        Ok(ticket) if is_technician_or_admin(&claims) || TicketParties::from(&ticket).includes(user_uuid) => ticket,
+        _ => return HttpResponse::NotFound().json("Ticket not found"),
+    };
+
+    // Documentation written from this ticket isn't a suggestion for it
+    let exclude: Vec<i32> = repository::get_documentation_pages_by_ticket_id(&mut conn, ticket_id)
+        .map(|pages| pages.into_iter().map(|page| page.id).collect())
+        .unwrap_or_default();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    match index.get(&mut conn) {
+        Ok(index) => {
+            let description = truncate(ticket.description.as_deref().unwrap_or(""));
+            HttpResponse::Ok().json(index.suggest(&ticket.title, description, limit, &exclude))
+        }
+        Err(e) => {
+            error!(ticket_id = ticket_id, error = %e, "Failed to build knowledge base suggestion index");
+            HttpResponse::InternalServerError().json("Failed to load suggestions")
+        }
+    }
+}
+
+// POST /api/kb/suggestions/clicks - Record a click on a suggested article
+pub async fn record_suggestion_click(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    body: web::Json<SuggestionClickRequest>,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json("Authentication required"),
+    };
+    let user_uuid = match utils::parse_uuid(&claims.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => return HttpResponse::BadRequest().json("Invalid user UUID"),
+    };
+
+    let request = body.into_inner();
+    if request.source == KbSuggestionSource::Ticket && request.ticket_id.is_none() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Missing ticket",
+            "message": "ticket_id is required for clicks on ticket suggestions"
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if repository::get_documentation_page(request.page_id, &mut conn).is_err() {
+        return HttpResponse::NotFound().json("Page not found");
+    }
+    if let Some(ticket_id) = request.ticket_id {
+        match repository::get_ticket_by_id(&mut conn, ticket_id) {
+            Ok(ticket) if is_technician_or_admin(&claims) || TicketParties::from(&ticket).includes(user_uuid) => {}
+            _ => return HttpResponse::NotFound().json("Ticket not found"),
+        }
+    }
+
+    let click = NewKbSuggestionClick {
+        page_id: request.page_id,
+        user_uuid: Some(user_uuid),
+        ticket_id: request.ticket_id,
+        source: request.source.as_str().to_string(),
+        query: request
+            .query
+            .as_deref()
+            .map(|query| truncate(query.trim()).to_string())
+            .filter(|query| !query.is_empty()),
+    };
+
+    match repository::kb_suggestions::record_click(&mut conn, click) {
+        Ok(click) => HttpResponse::Created().json(click),
+        Err(e) => {
+            error!(page_id = request.page_id, error = ?e, "Failed to record suggestion click");
+            HttpResponse::InternalServerError().json("Failed to record click")
+        }
+    }
+}
+
+// GET /api/kb/suggestions/stats?days= - Click and deflection statistics
+pub async fn get_suggestion_stats(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    query: web::Query<SuggestionStatsQuery>,
+) -> impl Responder {
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json("Authentication required"),
+    };
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can view suggestion statistics"
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let days = query.days.unwrap_or(DEFAULT_STATS_DAYS).clamp(1, 365);
+    match kb_suggestions::click_stats(&mut conn, days) {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(e) => {
+            error!(error = ?e, "Failed to load suggestion statistics");
+            HttpResponse::InternalServerError().json("Failed to load statistics")
+        }
+    }
+}
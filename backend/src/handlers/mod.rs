@@ -8,11 +8,14 @@ pub mod tickets;
 pub mod projects;
 pub mod devices;
 pub mod documentation;
+pub mod public_kb;
+pub mod kb_suggestions;
 pub mod auth_providers;
 pub mod email;
 pub mod microsoft_graph;
 pub mod msgraph_integration;
 pub mod sse;
+pub mod events_socket;
 pub mod password_reset;
 pub mod mfa_reset;
 pub mod invitation;
@@ -20,8 +23,15 @@ pub mod system;
 pub mod debug;
 pub mod branding;
 pub mod backup;
+pub mod bulk_import;
+pub mod recycle_bin;
+pub mod privacy;
 pub mod groups;
 pub mod categories;
+pub mod problems;
+pub mod changes;
+pub mod approvals;
+pub mod catalog;
 
 // Import all handlers from modules
 pub use auth::*;
@@ -42,7 +52,8 @@ pub use tickets::{
     create_empty_ticket, get_ticket, update_ticket, update_ticket_partial,
     delete_ticket, record_ticket_view, import_tickets_from_json,
     import_tickets_from_json_string, link_tickets, unlink_tickets,
-    add_device_to_ticket, remove_device_from_ticket, bulk_tickets
+    add_device_to_ticket, remove_device_from_ticket, bulk_tickets,
+    merge_ticket, split_ticket
 };
 pub use projects::*;
 // Export specific items from devices to avoid conflicts
@@ -328,6 +339,7 @@ pub async fn add_comment_to_ticket(
 }
 
 pub async fn delete_comment(
+    req: actix_web::HttpRequest,
     path: web::Path<i32>,
     pool: web::Data<crate::db::Pool>,
     sse_state: web::Data<crate::handlers::sse::SseState>,
@@ -351,7 +363,8 @@ pub async fn delete_comment(
         }
     };
     
-    match crate::repository::comments::delete_comment(&mut conn, comment_id) {
+    let deleted_by = req.extensions().get::<crate::models::Claims>().and_then(|claims| uuid::Uuid::parse_str(&claims.sub).ok());
+    match crate::repository::comments::delete_comment(&mut conn, comment_id, deleted_by) {
         Ok(deleted) => {
             if deleted > 0 {
                 // Broadcast SSE event for the deleted comment using centralized utility
@@ -377,9 +390,9 @@ pub async fn add_attachment_to_comment(_: web::Path<i32>, _: web::Data<crate::db
 }
 
 pub async fn delete_attachment(
+    req: actix_web::HttpRequest,
     path: web::Path<i32>,
     pool: web::Data<crate::db::Pool>,
-    storage: Arc<dyn crate::utils::storage::Storage>
 ) -> impl Responder {
     let attachment_id = path.into_inner();
     debug!(attachment_id, "Deleting attachment");
@@ -391,49 +404,22 @@ pub async fn delete_attachment(
             return HttpResponse::InternalServerError().json(json!({"error": "Database connection error"}));
         }
     };
-    
-    // First, get the attachment to find the file path
-    match crate::repository::comments::get_attachment_by_id(&mut conn, attachment_id) {
-        Ok(attachment) => {
-            debug!(attachment = ?attachment, "Found attachment");
-
-            // Extract the storage path from the URL
-            let storage_path = if attachment.url.starts_with("/uploads/temp/") {
-                attachment.url.trim_start_matches("/uploads/").to_string()
-            } else if attachment.url.starts_with("/uploads/tickets/") {
-                attachment.url.trim_start_matches("/uploads/").to_string()
-            } else {
-                attachment.url.trim_start_matches("/uploads/").to_string()
-            };
-
-            debug!(storage_path = %storage_path, "Attempting to delete file from storage");
-
-            // Delete the file using the storage abstraction
-            match storage.delete_file(&storage_path).await {
-                Ok(_) => debug!(storage_path = %storage_path, "Successfully deleted file from storage"),
-                Err(e) => warn!(error = ?e, storage_path = %storage_path, "Failed to delete file from storage"),
-            }
 
-            // Delete the database record
-            match crate::repository::comments::delete_attachment(&mut conn, attachment_id) {
-                Ok(deleted) => {
-                    if deleted > 0 {
-                        info!(attachment_id, "Successfully deleted attachment from database");
-                        HttpResponse::Ok().json(json!({"success": true, "message": "Attachment deleted"}))
-                    } else {
-                        warn!(attachment_id, "Attachment not found in database");
-                        HttpResponse::NotFound().json(json!({"error": "Attachment not found"}))
-                    }
-                },
-                Err(e) => {
-                    error!(attachment_id, error = %e, "Error deleting attachment from database");
-                    HttpResponse::InternalServerError().json(json!({"error": format!("Failed to delete attachment: {}", e)}))
-                }
+    // The file stays in storage until the recycle bin is purged
+    let deleted_by = req.extensions().get::<crate::models::Claims>().and_then(|claims| uuid::Uuid::parse_str(&claims.sub).ok());
+    match crate::repository::comments::delete_attachment(&mut conn, attachment_id, deleted_by) {
+        Ok(deleted) => {
+            if deleted > 0 {
+                info!(attachment_id, "Successfully deleted attachment");
+                HttpResponse::Ok().json(json!({"success": true, "message": "Attachment deleted"}))
+            } else {
+                warn!(attachment_id, "Attachment not found in database");
+                HttpResponse::NotFound().json(json!({"error": "Attachment not found"}))
             }
         },
         Err(e) => {
-            error!(attachment_id, error = %e, "Error finding attachment");
-            HttpResponse::NotFound().json(json!({"error": "Attachment not found"}))
+            error!(attachment_id, error = %e, "Error deleting attachment from database");
+            HttpResponse::InternalServerError().json(json!({"error": format!("Failed to delete attachment: {}", e)}))
         }
     }
 }
@@ -937,18 +937,68 @@ pub async fn sync_data(
         })),
     };
 
-    // Get Microsoft provider
-    let provider = match get_default_microsoft_provider() {
-        Ok(provider) => provider,
-        Err(_) => return HttpResponse::BadRequest().json(json!({
+    let session = match begin_sync(&mut conn, request.entities.clone(), request.use_delta) {
+        Ok(session) => session,
+        Err(SyncStartError::NotConfigured) => return HttpResponse::BadRequest().json(json!({
             "status": "error",
             "message": "Microsoft auth provider not found"
         })),
+        Err(SyncStartError::Database(e)) => {
+            error!("Failed to create sync history record: {:?}", e);
+            return HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": "Failed to create sync history record"
+            }));
+        }
     };
+    let session_id = session.id.clone();
 
-    // Determine the primary sync type based on entities
-    let entities = request.entities.clone();
-    let use_delta = request.use_delta;
+    // Start the sync process in the background
+    tokio::spawn(async move {
+        let _ = run_sync(db_pool, session).await;
+    });
+
+    // Return the session ID immediately
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "message": "Sync started successfully",
+        "session_id": session_id
+    }))
+}
+
+/// Why a sync could not be started
+#[derive(Debug)]
+pub enum SyncStartError {
+    /// The Microsoft client credentials are not configured
+    NotConfigured,
+    Database(diesel::result::Error),
+}
+
+impl std::fmt::Display for SyncStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncStartError::NotConfigured => write!(f, "Microsoft auth provider not found"),
+            SyncStartError::Database(e) => write!(f, "Failed to create sync history record: {}", e),
+        }
+    }
+}
+
+/// A sync that has been recorded in the sync history but not run yet
+pub struct SyncSession {
+    pub id: String,
+    provider_id: i32,
+    sync_type: String,
+    entities: Vec<String>,
+    use_delta: bool,
+}
+
+/// Record a new sync in the sync history and start tracking its progress
+pub fn begin_sync(
+    conn: &mut DbConnection,
+    entities: Vec<String>,
+    use_delta: bool,
+) -> Result<SyncSession, SyncStartError> {
+    let provider = get_default_microsoft_provider().map_err(|_| SyncStartError::NotConfigured)?;
 
     // Log delta sync request (full implementation pending)
     if use_delta {
@@ -957,6 +1007,7 @@ pub async fn sync_data(
         info!("Full sync requested");
     }
 
+    // Determine the primary sync type based on entities
     let sync_type = if entities.len() > 1 {
         "multiple".to_string()
     } else if entities.contains(&"devices".to_string()) {
@@ -984,16 +1035,7 @@ pub async fn sync_data(
         is_delta: use_delta,
     };
 
-    let sync_history = match sync_history_repo::create_sync_history(&mut conn, new_sync) {
-        Ok(history) => history,
-        Err(e) => {
-            error!("Failed to create sync history record: {:?}", e);
-            return HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Failed to create sync history record"
-            }));
-        }
-    };
+    let sync_history = sync_history_repo::create_sync_history(conn, new_sync).map_err(SyncStartError::Database)?;
 
     let session_id = sync_history.id.to_string();
     info!("Created sync history record with ID: {}", session_id);
@@ -1003,160 +1045,158 @@ pub async fn sync_data(
 
     update_sync_progress_with_type(&session_id, "initializing", 0, 0, "starting", "Initializing sync process", &sync_type, Some(use_delta));
 
-    // Start the sync process in the background
-    let provider_id = provider.id;
-    let session_id_clone = session_id.clone();
+    Ok(SyncSession {
+        id: session_id,
+        provider_id: provider.id,
+        sync_type,
+        entities,
+        use_delta,
+    })
+}
 
-    tokio::spawn(async move {
-        let mut conn = match db_pool.get() {
-            Ok(conn) => conn,
-            Err(_) => {
-                update_sync_progress(&session_id_clone, "error", 0, 0, "error", "Database connection failed");
-                return;
-            }
-        };
+/// Run a sync started with `begin_sync` and record how it ended in the sync history
+pub async fn run_sync(db_pool: web::Data<Pool>, session: SyncSession) -> Result<SyncResult, String> {
+    let SyncSession { id: session_id_clone, provider_id, sync_type, entities, use_delta } = session;
 
-        match perform_sync(&mut conn, provider_id, &entities, &session_id_clone, use_delta).await {
-            Ok(sync_result) => {
-                // Check if sync was cancelled by looking at the result
-                if !sync_result.success && sync_result.message.contains("cancelled") {
-                    // Update database with cancellation details
-                    let update = SyncHistoryUpdate {
-                        status: Some("cancelled".to_string()),
-                        error_message: Some(sync_result.message),
-                        records_processed: Some(sync_result.total_processed as i32),
-                        records_created: Some(0),
-                        records_updated: Some(sync_result.total_processed as i32),
-                        records_failed: Some(sync_result.total_errors as i32),
-                        completed_at: Some(Some(Utc::now().naive_utc())),
-                    };
-                    
-                    if let Ok(sync_id) = session_id_clone.parse::<i32>() {
-                        let _ = sync_history_repo::update_sync_history(&mut conn, sync_id, update);
-                    }
-                } else {
-                    // Normal completion - update with comprehensive results
-                    let status = if sync_result.total_errors > 0 {
-                        "completed_with_errors"
-                    } else {
-                        "completed"
-                    };
+    let mut conn = match db_pool.get() {
+        Ok(conn) => conn,
+        Err(_) => {
+            update_sync_progress(&session_id_clone, "error", 0, 0, "error", "Database connection failed");
+            return Err("Database connection failed".to_string());
+        }
+    };
 
-                    let completion_message = if sync_result.total_errors > 0 {
-                        format!(
-                            "Sync completed with {} errors: {} items processed ({})", 
-                            sync_result.total_errors,
-                            sync_result.total_processed,
-                            entities.join(", ")
-                        )
-                    } else {
-                        format!(
-                            "Sync completed successfully: {} items processed ({})", 
-                            sync_result.total_processed,
-                            entities.join(", ")
-                        )
-                    };
-                    
-                    let update = SyncHistoryUpdate {
-                        status: Some(status.to_string()),
-                        error_message: if sync_result.total_errors > 0 { 
-                            Some(completion_message.clone()) 
-                        } else { 
-                            None 
-                        },
-                        records_processed: Some(sync_result.total_processed as i32),
-                        records_created: Some(0), // Could track this separately in the future
-                        records_updated: Some(sync_result.total_processed as i32),
-                        records_failed: Some(sync_result.total_errors as i32),
-                        completed_at: Some(Some(Utc::now().naive_utc())),
-                    };
-                    
-                    if let Ok(sync_id) = session_id_clone.parse::<i32>() {
-                        match sync_history_repo::update_sync_history(&mut conn, sync_id, update) {
-                            Ok(_) => info!("Successfully updated sync history for session {}", sync_id),
-                            Err(e) => error!("Failed to update sync history: {:?}", e),
-                        }
-                    }
+    match perform_sync(&mut conn, provider_id, &entities, &session_id_clone, use_delta).await {
+        Ok(sync_result) => {
+            // Check if sync was cancelled by looking at the result
+            if !sync_result.success && sync_result.message.contains("cancelled") {
+                // Update database with cancellation details
+                let update = SyncHistoryUpdate {
+                    status: Some("cancelled".to_string()),
+                    error_message: Some(sync_result.message.clone()),
+                    records_processed: Some(sync_result.total_processed as i32),
+                    records_created: Some(0),
+                    records_updated: Some(sync_result.total_processed as i32),
+                    records_failed: Some(sync_result.total_errors as i32),
+                    completed_at: Some(Some(Utc::now().naive_utc())),
+                };
+
+                if let Ok(sync_id) = session_id_clone.parse::<i32>() {
+                    let _ = sync_history_repo::update_sync_history(&mut conn, sync_id, update);
+                }
+            } else {
+                // Normal completion - update with comprehensive results
+                let status = if sync_result.total_errors > 0 {
+                    "completed_with_errors"
+                } else {
+                    "completed"
+                };
 
-                    // Update in-memory progress with completion message
-                    update_sync_progress_with_type(
-                        &session_id_clone,
-                        &sync_type,
+                let completion_message = if sync_result.total_errors > 0 {
+                    format!(
+                        "Sync completed with {} errors: {} items processed ({})", 
+                        sync_result.total_errors,
                         sync_result.total_processed,
+                        entities.join(", ")
+                    )
+                } else {
+                    format!(
+                        "Sync completed successfully: {} items processed ({})", 
                         sync_result.total_processed,
-                        status,
-                        &completion_message,
-                        &sync_type,
-                        None
-                    );
-
-                    // Check if background photo sync should start after user sync completes
-                    if entities.contains(&"users".to_string()) && sync_result.total_processed > 0 {
-                        let background_photo_sync = std::env::var("MSGRAPH_BACKGROUND_PHOTOS")
-                            .unwrap_or("true".to_string())
-                            .parse::<bool>()
-                            .unwrap_or(true);
-                        
-                        if background_photo_sync {
-                            info!("Starting background photo sync for {} processed users", sync_result.total_processed);
-                            let db_pool_bg = db_pool.clone();
-                            let session_id_bg = session_id_clone.clone();
-                            
-                            tokio::spawn(async move {
-                                // Give the main sync a moment to finish database operations
-                                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                
-                                // Get access token for photo sync
-                                match fetch_microsoft_graph_users_optimized(provider_id).await {
-                                    Ok((_, access_token)) => {
-                                        if let Err(e) = background_photo_sync_task(db_pool_bg, provider_id, session_id_bg, access_token).await {
-                                            error!("Background photo sync failed: {}", e);
-                                        }
-                                    },
-                                    Err(e) => {
-                                        error!("Failed to get access token for background photo sync: {}", e);
-                                    }
-                                }
-                            });
-                        }
-                    }
-                }
-            },
-            Err(error) => {
-                let error_message = format!("Sync failed: {}", error);
-                error!("Sync failed for session {}: {}", session_id_clone, error);
-                
-                update_sync_progress_with_type(&session_id_clone, &sync_type, 0, 0, "error", &error_message, &sync_type, None);
+                        entities.join(", ")
+                    )
+                };
                 
-                // Update database with error
                 let update = SyncHistoryUpdate {
-                    status: Some("error".to_string()),
-                    error_message: Some(error_message),
-                    records_processed: Some(0),
-                    records_created: Some(0),
-                    records_updated: Some(0),
-                    records_failed: Some(1),
+                    status: Some(status.to_string()),
+                    error_message: if sync_result.total_errors > 0 { 
+                        Some(completion_message.clone()) 
+                    } else { 
+                        None 
+                    },
+                    records_processed: Some(sync_result.total_processed as i32),
+                    records_created: Some(0), // Could track this separately in the future
+                    records_updated: Some(sync_result.total_processed as i32),
+                    records_failed: Some(sync_result.total_errors as i32),
                     completed_at: Some(Some(Utc::now().naive_utc())),
                 };
                 
                 if let Ok(sync_id) = session_id_clone.parse::<i32>() {
-                    let _ = sync_history_repo::update_sync_history(&mut conn, sync_id, update);
+                    match sync_history_repo::update_sync_history(&mut conn, sync_id, update) {
+                        Ok(_) => info!("Successfully updated sync history for session {}", sync_id),
+                        Err(e) => error!("Failed to update sync history: {:?}", e),
+                    }
                 }
-            }
-        }
-    });
-
-    // Return the session ID immediately
-    HttpResponse::Ok().json(json!({
-        "success": true,
-        "message": "Sync started successfully",
-        "session_id": session_id
-    }))
-}
-
 
+                // Update in-memory progress with completion message
+                update_sync_progress_with_type(
+                    &session_id_clone,
+                    &sync_type,
+                    sync_result.total_processed,
+                    sync_result.total_processed,
+                    status,
+                    &completion_message,
+                    &sync_type,
+                    None
+                );
 
+                // Check if background photo sync should start after user sync completes
+                if entities.contains(&"users".to_string()) && sync_result.total_processed > 0 {
+                    let background_photo_sync = std::env::var("MSGRAPH_BACKGROUND_PHOTOS")
+                        .unwrap_or("true".to_string())
+                        .parse::<bool>()
+                        .unwrap_or(true);
+                    
+                    if background_photo_sync {
+                        info!("Starting background photo sync for {} processed users", sync_result.total_processed);
+                        let db_pool_bg = db_pool.clone();
+                        let session_id_bg = session_id_clone.clone();
+                        
+                        tokio::spawn(async move {
+                            // Give the main sync a moment to finish database operations
+                            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                            
+                            // Get access token for photo sync
+                            match fetch_microsoft_graph_users_optimized(provider_id).await {
+                                Ok((_, access_token)) => {
+                                    if let Err(e) = background_photo_sync_task(db_pool_bg, provider_id, session_id_bg, access_token).await {
+                                        error!("Background photo sync failed: {}", e);
+                                    }
+                                },
+                                Err(e) => {
+                                    error!("Failed to get access token for background photo sync: {}", e);
+                                }
+                            }
+                        });
+                    }
+                }
+            }
+            Ok(sync_result)
+        },
+        Err(error) => {
+            let error_message = format!("Sync failed: {}", error);
+            error!("Sync failed for session {}: {}", session_id_clone, error);
+
+            update_sync_progress_with_type(&session_id_clone, &sync_type, 0, 0, "error", &error_message, &sync_type, None);
+
+            // Update database with error
+            let update = SyncHistoryUpdate {
+                status: Some("error".to_string()),
+                error_message: Some(error_message.clone()),
+                records_processed: Some(0),
+                records_created: Some(0),
+                records_updated: Some(0),
+                records_failed: Some(1),
+                completed_at: Some(Some(Utc::now().naive_utc())),
+            };
 
+            if let Ok(sync_id) = session_id_clone.parse::<i32>() {
+                let _ = sync_history_repo::update_sync_history(&mut conn, sync_id, update);
+            }
+            Err(error_message)
+        }
+    }
+}
 
 /// Check Microsoft configuration
 fn check_microsoft_config() -> Result<(), String> {
@@ -4522,6 +4562,48 @@ async fn fetch_entra_object_id_from_graph(_provider_id: i32, azure_ad_device_id:
     Ok(object_id.to_string())
 }
 
+/// Fetch the Entra object ID of a user's manager from Microsoft Graph
+/// Returns Ok(None) when the user has no manager assigned
+pub async fn fetch_user_manager_id(microsoft_uuid: &Uuid) -> Result<Option<Uuid>, String> {
+    let (client, access_token) = get_msgraph_client_and_token().await?;
+
+    let url = format!(
+        "https://graph.microsoft.com/v1.0/users/{}/manager?$select=id",
+        microsoft_uuid
+    );
+
+    let graph_response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send Microsoft Graph request: {}", e))?;
+
+    let status = graph_response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let response_data: serde_json::Value = graph_response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Microsoft Graph response: {}", e))?;
+
+    if !status.is_success() {
+        let error_msg = response_data
+            .get("error")
+            .and_then(|err| err.get("message"))
+            .and_then(|msg| msg.as_str())
+            .unwrap_or("Unknown Microsoft Graph error");
+        return Err(format!("Microsoft Graph API error ({}): {}", status, error_msg));
+    }
+
+    Ok(response_data
+        .get("id")
+        .and_then(|id| id.as_str())
+        .and_then(|id| Uuid::parse_str(id).ok()))
+}
+
 /// Extract all email addresses from Microsoft Graph user data
 fn extract_user_emails(ms_user: &MicrosoftGraphUser) -> Vec<(String, String, bool)> {
     let mut emails = Vec::new();
@@ -0,0 +1,99 @@
+//! Data subject requests: export or erase everything held about a user (admin only)
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::services::privacy::{self, PrivacyError};
+use crate::utils::rbac::require_admin;
+use crate::utils::storage::Storage;
+
+fn parse_user_uuid(value: &str) -> Result<Uuid, HttpResponse> {
+    Uuid::parse_str(value).map_err(|_| {
+        HttpResponse::BadRequest().json(json!({
+            "error": "Bad Request",
+            "message": "Invalid user UUID"
+        }))
+    })
+}
+
+// GET /api/admin/privacy/users/{uuid}/export - Zip of all data tied to a user
+pub async fn export_user_data(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+    let user_uuid = match parse_user_uuid(&path) {
+        Ok(uuid) => uuid,
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match privacy::export_user_data(&mut conn, storage.get_ref().as_ref(), user_uuid).await {
+        Ok(file) => HttpResponse::Ok()
+            .content_type(file.content_type)
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", file.filename),
+            ))
+            .body(file.data),
+        Err(PrivacyError::UserNotFound) => HttpResponse::NotFound().json("User not found"),
+        Err(e) => {
+            error!(user_uuid = %user_uuid, error = %e, "User data export failed");
+            HttpResponse::InternalServerError().json("Failed to export user data")
+        }
+    }
+}
+
+// POST /api/admin/privacy/users/{uuid}/erase - Anonymise a user, keeping their tickets
+pub async fn erase_user(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let user_uuid = match parse_user_uuid(&path) {
+        Ok(uuid) => uuid,
+        Err(e) => return e,
+    };
+
+    if claims.sub == user_uuid.to_string() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Bad Request",
+            "message": "You cannot erase your own account"
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match privacy::erase_user(&mut conn, storage.get_ref().as_ref(), user_uuid).await {
+        Ok(_) => HttpResponse::Ok().json(json!({
+            "success": true,
+            "user_uuid": user_uuid,
+            "name": crate::repository::privacy::ERASED_USER_NAME,
+        })),
+        Err(PrivacyError::UserNotFound) => HttpResponse::NotFound().json("User not found"),
+        Err(e) => {
+            error!(user_uuid = %user_uuid, error = %e, "User erasure failed");
+            HttpResponse::InternalServerError().json("Failed to erase user")
+        }
+    }
+}
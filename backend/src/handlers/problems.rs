@@ -0,0 +1,307 @@
+use actix_web::{web, HttpResponse, Responder, HttpRequest};
+use chrono::Utc;
+use diesel::result::Error;
+use diesel::Connection;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::models::{NewProblemDetails, NewTicket, ProblemDetailsUpdate, TicketLinkType, TicketPriority, TicketStatus};
+use crate::repository;
+use crate::services::ticket_relationships::{RelationshipError, TicketRelationshipService};
+use crate::utils::rbac::require_technician_or_admin;
+use crate::utils::sse::SseBroadcaster;
+
+// ============================================================================
+// Problem Records
+// ============================================================================
+// A problem is a ticket with ticket_type = 'problem' and a problem_details row.
+// Incidents are linked to it through linked_tickets as "incident caused_by problem".
+
+#[derive(Debug, Deserialize)]
+pub struct ProblemListQuery {
+    pub known_error: Option<bool>,
+}
+
+/// List problems (technician/admin)
+pub async fn list_problems(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    query: web::Query<ProblemListQuery>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::problems::list_problems(&mut conn, query.known_error.unwrap_or(false)) {
+        Ok(problems) => HttpResponse::Ok().json(problems),
+        Err(e) => {
+            error!(error = ?e, "Failed to list problems");
+            HttpResponse::InternalServerError().json("Failed to list problems")
+        }
+    }
+}
+
+/// Request body for creating a problem
+#[derive(Debug, Deserialize)]
+pub struct CreateProblemRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<TicketPriority>,
+    pub assignee_uuid: Option<Uuid>,
+    pub category_id: Option<i32>,
+    pub root_cause: Option<String>,
+    pub workaround: Option<String>,
+    pub is_known_error: Option<bool>,
+    /// Incident tickets to link to the new problem
+    pub incident_ids: Option<Vec<i32>>,
+}
+
+/// Create a problem record (technician/admin)
+pub async fn create_problem(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    body: web::Json<CreateProblemRequest>,
+) -> impl Responder {
+    let claims = match require_technician_or_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+    let created_by = Uuid::parse_str(&claims.sub).ok();
+    let body = body.into_inner();
+
+    if body.title.trim().is_empty() {
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid request",
+            "message": "Title is required"
+        }));
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let is_known_error = body.is_known_error.unwrap_or(false);
+    let new_ticket = NewTicket {
+        title: body.title.trim().to_string(),
+        description: body.description,
+        status: TicketStatus::Open,
+        priority: body.priority.unwrap_or(TicketPriority::Medium),
+        requester_uuid: created_by,
+        assignee_uuid: body.assignee_uuid,
+        category_id: body.category_id,
+    };
+    let details = NewProblemDetails {
+        ticket_id: 0, // Set by the repository once the ticket exists
+        root_cause: body.root_cause,
+        workaround: body.workaround,
+        is_known_error,
+        known_error_at: is_known_error.then(|| Utc::now().naive_utc()),
+    };
+
+    // The problem and its incident links are created together or not at all
+    let incident_ids = body.incident_ids.unwrap_or_default();
+    let created = conn.transaction::<_, RelationshipError, _>(|conn| {
+        let (ticket, _details) = repository::problems::create_problem(conn, new_ticket, details, created_by)?;
+        for &incident_id in &incident_ids {
+            TicketRelationshipService::link(conn, incident_id, ticket.id, TicketLinkType::CausedBy, created_by)?;
+        }
+        Ok(ticket)
+    });
+    let ticket = match created {
+        Ok(ticket) => ticket,
+        Err(RelationshipError::DatabaseError(e)) => {
+            error!(error = ?e, "Failed to create problem");
+            return HttpResponse::InternalServerError().json("Failed to create problem");
+        }
+        Err(e) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid relationship",
+            "message": e.to_string()
+        })),
+    };
+
+    SseBroadcaster::broadcast_ticket_created(
+        &sse_state,
+        ticket.id,
+        serde_json::to_value(&ticket).unwrap_or_default(),
+    ).await;
+
+    match repository::problems::get_problem_record(&mut conn, ticket.id) {
+        Ok(record) => HttpResponse::Created().json(record),
+        Err(_) => HttpResponse::Created().json(ticket),
+    }
+}
+
+/// Get a problem with its linked incidents (technician/admin)
+pub async fn get_problem(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match repository::problems::get_problem_record(&mut conn, path.into_inner()) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(Error::NotFound) => HttpResponse::NotFound().json("Problem not found"),
+        Err(e) => {
+            error!(error = ?e, "Failed to get problem");
+            HttpResponse::InternalServerError().json("Failed to get problem")
+        }
+    }
+}
+
+/// Request body for updating problem details
+#[derive(Debug, Deserialize)]
+pub struct UpdateProblemRequest {
+    pub root_cause: Option<String>,
+    pub workaround: Option<String>,
+    pub is_known_error: Option<bool>,
+}
+
+/// Update root cause, workaround and known-error flag (technician/admin)
+pub async fn update_problem(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<i32>,
+    body: web::Json<UpdateProblemRequest>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let problem_id = path.into_inner();
+    let body = body.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let current = match repository::problems::get_problem_details(&mut conn, problem_id) {
+        Ok(details) => details,
+        Err(Error::NotFound) => return HttpResponse::NotFound().json("Problem not found"),
+        Err(_) => return HttpResponse::InternalServerError().json("Failed to get problem"),
+    };
+
+    let now = Utc::now().naive_utc();
+    // Empty strings clear a field
+    let mut update = ProblemDetailsUpdate {
+        root_cause: body.root_cause.map(|v| Some(v).filter(|v| !v.trim().is_empty())),
+        workaround: body.workaround.map(|v| Some(v).filter(|v| !v.trim().is_empty())),
+        updated_at: Some(now),
+        ..Default::default()
+    };
+
+    if let Some(is_known_error) = body.is_known_error {
+        if is_known_error != current.is_known_error {
+            update.is_known_error = Some(is_known_error);
+            update.known_error_at = Some(is_known_error.then_some(now));
+        }
+    }
+
+    if let Err(e) = repository::problems::update_problem_details(&mut conn, problem_id, update) {
+        error!(problem_id, error = ?e, "Failed to update problem");
+        return HttpResponse::InternalServerError().json("Failed to update problem");
+    }
+
+    match repository::problems::get_problem_record(&mut conn, problem_id) {
+        Ok(record) => HttpResponse::Ok().json(record),
+        Err(_) => HttpResponse::InternalServerError().json("Failed to get problem"),
+    }
+}
+
+/// Link an incident ticket to a problem (technician/admin)
+pub async fn link_problem_incident(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    let claims = match require_technician_or_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
+
+    let (problem_id, incident_id) = path.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    if let Err(e) = repository::problems::get_problem_details(&mut conn, problem_id) {
+        return match e {
+            Error::NotFound => HttpResponse::NotFound().json("Problem not found"),
+            _ => HttpResponse::InternalServerError().json("Failed to get problem"),
+        };
+    }
+
+    match TicketRelationshipService::link(
+        &mut conn,
+        incident_id,
+        problem_id,
+        TicketLinkType::CausedBy,
+        Uuid::parse_str(&claims.sub).ok(),
+    ) {
+        Ok(_) => {
+            SseBroadcaster::broadcast_ticket_linked(&sse_state, incident_id, problem_id, TicketLinkType::CausedBy.as_str()).await;
+            SseBroadcaster::broadcast_ticket_linked(&sse_state, problem_id, incident_id, TicketLinkType::Causes.as_str()).await;
+            HttpResponse::Ok().json(json!({"success": true}))
+        }
+        Err(RelationshipError::TicketNotFound(_)) => HttpResponse::NotFound().json("Incident not found"),
+        Err(RelationshipError::DatabaseError(e)) => {
+            error!(problem_id, incident_id, error = ?e, "Failed to link incident to problem");
+            HttpResponse::InternalServerError().json("Failed to link incident")
+        }
+        Err(e) => HttpResponse::BadRequest().json(json!({
+            "error": "Invalid relationship",
+            "message": e.to_string()
+        })),
+    }
+}
+
+/// Remove an incident from a problem (technician/admin)
+pub async fn unlink_problem_incident(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    path: web::Path<(i32, i32)>,
+) -> impl Responder {
+    if let Err(e) = require_technician_or_admin(&req) {
+        return e;
+    }
+
+    let (problem_id, incident_id) = path.into_inner();
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    // Only the incident's caused-by link to this problem; other relationships stay
+    match repository::problems::unlink_incident(&mut conn, problem_id, incident_id) {
+        Ok(true) => {
+            SseBroadcaster::broadcast_ticket_unlinked(&sse_state, incident_id, problem_id).await;
+            SseBroadcaster::broadcast_ticket_unlinked(&sse_state, problem_id, incident_id).await;
+            HttpResponse::Ok().json(json!({"success": true}))
+        }
+        Ok(false) => HttpResponse::NotFound().json("Incident is not linked to this problem"),
+        Err(e) => {
+            error!(problem_id, incident_id, error = ?e, "Failed to unlink incident from problem");
+            HttpResponse::InternalServerError().json("Failed to unlink incident")
+        }
+    }
+}
@@ -99,9 +99,10 @@ pub async fn delete_project(
     pool: web::Data<Pool>,
     path: web::Path<i32>,
 ) -> impl Responder {
-    if let Err(e) = require_admin(&req) {
-        return e;
-    }
+    let claims = match require_admin(&req) {
+        Ok(claims) => claims,
+        Err(e) => return e,
+    };
 
     let project_id = path.into_inner();
     let mut conn = match pool.get() {
@@ -109,7 +110,7 @@ pub async fn delete_project(
         Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
     };
 
-    match repository::delete_project(&mut conn, project_id) {
+    match repository::delete_project(&mut conn, project_id, uuid::Uuid::parse_str(&claims.sub).ok()) {
         Ok(0) => HttpResponse::NotFound().json("Project not found"),
         Ok(_) => HttpResponse::NoContent().finish(),
         Err(_) => HttpResponse::InternalServerError().json("Failed to delete project"),
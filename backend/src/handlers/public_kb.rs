@@ -0,0 +1,483 @@
+//! Public knowledge base (no authentication)
+//!
+//! Server-rendered pages under `/kb` and a JSON API under `/api/public/kb`. Only pages
+//! that `services::public_kb` considers visible are ever listed or rendered; anything else
+//! answers 404 exactly like a page that doesn't exist.
+
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::error;
+
+use crate::db::{DbConnection, Pool};
+use crate::models::PublicDocumentationPage;
+use crate::repository;
+use crate::repository::site_settings;
+use crate::services::doc_export::page_document;
+use crate::services::public_kb::{self, NavNode, PublicKbIndex};
+use crate::utils::yjs_render::{self, escape_html, Markup};
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+const CACHE_CONTROL: &str = "public, max-age=300";
+/// Pages carry no scripts, so only inline styles, images and the search form are allowed
+const CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; style-src 'unsafe-inline'; img-src 'self' data: https:; form-action 'self'; base-uri 'none'; frame-ancestors 'none'";
+
+#[derive(Deserialize)]
+pub struct PublicSearchQuery {
+    pub q: Option<String>,
+    pub limit: Option<usize>,
+}
+
+fn load_pages(conn: &mut DbConnection) -> Result<Vec<PublicDocumentationPage>, diesel::result::Error> {
+    Ok(public_kb::visible_pages(repository::get_public_documentation_pages(conn)?))
+}
+
+/// Rendered HTML body of a page
+fn page_body(conn: &mut DbConnection, page: &PublicDocumentationPage) -> String {
+    repository::get_documentation_page(page.id, conn)
+        .ok()
+        .and_then(|full_page| page_document(conn, &full_page))
+        .map(|doc| yjs_render::render(&doc, Markup::Html, &HashMap::new()))
+        .unwrap_or_default()
+}
+
+/// Percent-encode a page key for use in a URL path
+fn kb_href(key: &str) -> String {
+    let mut href = String::from("/kb/");
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            href.push(byte as char);
+        } else {
+            href.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    href
+}
+
+// ============================================================================
+// Branding and layout
+// ============================================================================
+
+struct Branding {
+    app_name: String,
+    logo_url: Option<String>,
+    favicon_url: Option<String>,
+    primary_color: String,
+}
+
+/// `#rgb`, `#rrggbb` or `#rrggbbaa`; anything else could break out of the stylesheet
+fn is_hex_color(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .map(|hex| matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false)
+}
+
+fn load_branding(conn: &mut DbConnection) -> Branding {
+    let settings = site_settings::get_site_settings(conn).ok();
+    Branding {
+        app_name: settings
+            .as_ref()
+            .map(|settings| settings.app_name.clone())
+            .unwrap_or_else(|| "Nosdesk".to_string()),
+        logo_url: settings.as_ref().and_then(|settings| settings.logo_url.clone()),
+        favicon_url: settings.as_ref().and_then(|settings| settings.favicon_url.clone()),
+        primary_color: settings
+            .and_then(|settings| settings.primary_color)
+            .filter(|color| is_hex_color(color))
+            .unwrap_or_else(|| "#2563eb".to_string()),
+    }
+}
+
+fn render_nav(nodes: &[NavNode], current: Option<i32>) -> String {
+    if nodes.is_empty() {
+        return String::new();
+    }
+    let mut html = String::from("<ul>");
+    for node in nodes {
+        let class = if Some(node.id) == current { " class=\"current\"" } else { "" };
+        let icon = node.icon.as_deref().map(|icon| format!("{} ", escape_html(icon))).unwrap_or_default();
+        html.push_str(&format!(
+            "<li><a href=\"{}\"{}>{}{}</a>{}</li>",
+            escape_html(&kb_href(&node.key)),
+            class,
+            icon,
+            escape_html(&node.title),
+            render_nav(&node.children, current)
+        ));
+    }
+    html.push_str("</ul>");
+    html
+}
+
+fn layout(branding: &Branding, title: &str, nav: &[NavNode], current: Option<i32>, query: &str, main: &str) -> String {
+    let favicon = branding
+        .favicon_url
+        .as_deref()
+        .map(|url| format!("<link rel=\"icon\" href=\"{}\">\n", escape_html(url)))
+        .unwrap_or_default();
+    let logo = branding
+        .logo_url
+        .as_deref()
+        .map(|url| format!("<img src=\"{}\" alt=\"\">", escape_html(url)))
+        .unwrap_or_default();
+    let page_title = if title.is_empty() {
+        format!("{} Knowledge Base", branding.app_name)
+    } else {
+        format!("{} · {}", title, branding.app_name)
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{page_title}</title>
+{favicon}<style>
+:root {{ --primary: {primary}; }}
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif; line-height: 1.6; margin: 0; color: #1f2937; }}
+header {{ display: flex; align-items: center; gap: 1rem; padding: 0.75rem 1.5rem; border-bottom: 3px solid var(--primary); }}
+header a.brand {{ display: flex; align-items: center; gap: 0.5rem; font-weight: 600; color: inherit; text-decoration: none; }}
+header img {{ height: 2rem; }}
+header form {{ margin-left: auto; }}
+header input {{ padding: 0.35rem 0.6rem; border: 1px solid #d1d5db; border-radius: 4px; min-width: 16rem; }}
+.layout {{ display: flex; max-width: 72rem; margin: 0 auto; }}
+nav {{ width: 16rem; flex-shrink: 0; padding: 1.5rem 1rem; border-right: 1px solid #e5e7eb; font-size: 0.95rem; }}
+nav ul {{ list-style: none; padding-left: 0.9rem; margin: 0; }}
+nav > ul {{ padding-left: 0; }}
+nav a {{ color: #374151; text-decoration: none; }}
+nav a.current {{ color: var(--primary); font-weight: 600; }}
+main {{ flex: 1; min-width: 0; padding: 1.5rem 2rem; }}
+main a {{ color: var(--primary); }}
+.breadcrumbs {{ font-size: 0.9rem; color: #6b7280; }}
+.meta {{ color: #6b7280; font-size: 0.85rem; }}
+pre {{ background: #f3f4f6; padding: 0.75rem 1rem; overflow-x: auto; border-radius: 4px; }}
+code {{ font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }}
+blockquote {{ border-left: 3px solid #d1d5db; margin-left: 0; padding-left: 1rem; color: #4b5563; }}
+img {{ max-width: 100%; }}
+@media (max-width: 48rem) {{ .layout {{ flex-direction: column; }} nav {{ width: auto; border-right: none; border-bottom: 1px solid #e5e7eb; }} }}
+</style>
+</head>
+<body>
+<header>
+<a class="brand" href="/kb">{logo}<span>{app_name}</span></a>
+<form action="/kb/search" method="get"><input type="search" name="q" value="{query}" placeholder="Search the knowledge base" aria-label="Search"></form>
+</header>
+<div class="layout">
+<nav>{nav}</nav>
+<main>
+{main}</main>
+</div>
+</body>
+</html>
+"#,
+        page_title = escape_html(&page_title),
+        favicon = favicon,
+        primary = branding.primary_color,
+        logo = logo,
+        app_name = escape_html(&branding.app_name),
+        query = escape_html(query),
+        nav = render_nav(nav, current),
+        main = main,
+    )
+}
+
+fn html_response(html: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", CACHE_CONTROL))
+        .insert_header(("Content-Security-Policy", CONTENT_SECURITY_POLICY))
+        .body(html)
+}
+
+fn not_found_page(branding: &Branding, nav: &[NavNode]) -> HttpResponse {
+    HttpResponse::NotFound()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Content-Security-Policy", CONTENT_SECURITY_POLICY))
+        .body(layout(
+            branding,
+            "Page not found",
+            nav,
+            None,
+            "",
+            "<h1>Page not found</h1>\n<p>This page doesn't exist or isn't public. <a href=\"/kb\">Back to the knowledge base</a>.</p>\n",
+        ))
+}
+
+fn error_page() -> HttpResponse {
+    HttpResponse::InternalServerError()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Content-Security-Policy", CONTENT_SECURITY_POLICY))
+        .body("<!DOCTYPE html><title>Error</title><p>The knowledge base is unavailable right now.</p>")
+}
+
+fn page_links(pages: &[&PublicDocumentationPage]) -> String {
+    let mut html = String::from("<ul>\n");
+    for page in pages {
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n",
+            escape_html(&kb_href(&public_kb::page_key(page))),
+            escape_html(&page.title)
+        ));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+// ============================================================================
+// Server-rendered site
+// ============================================================================
+
+// GET /kb - Knowledge base home with the top-level pages
+pub async fn kb_home(pool: web::Data<Pool>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return error_page(),
+    };
+    let pages = match load_pages(&mut conn) {
+        Ok(pages) => pages,
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            return error_page();
+        }
+    };
+    let branding = load_branding(&mut conn);
+    let nav = public_kb::nav_tree(&pages);
+
+    let top_level = public_kb::children_of(&pages, None);
+    let main = if top_level.is_empty() {
+        format!("<h1>{}</h1>\n<p>No articles have been published yet.</p>\n", escape_html(&branding.app_name))
+    } else {
+        format!("<h1>{} Knowledge Base</h1>\n{}", escape_html(&branding.app_name), page_links(&top_level))
+    };
+
+    html_response(layout(&branding, "", &nav, None, "", &main))
+}
+
+// GET /kb/{key} - A public page by slug or id
+pub async fn kb_page(pool: web::Data<Pool>, path: web::Path<String>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return error_page(),
+    };
+    let pages = match load_pages(&mut conn) {
+        Ok(pages) => pages,
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            return error_page();
+        }
+    };
+    let branding = load_branding(&mut conn);
+    let nav = public_kb::nav_tree(&pages);
+
+    let Some(page) = public_kb::find_page(&pages, &path.into_inner()) else {
+        return not_found_page(&branding, &nav);
+    };
+
+    let mut main = String::new();
+    let trail = public_kb::breadcrumbs(&pages, page);
+    if !trail.is_empty() {
+        let links: Vec<String> = trail
+            .iter()
+            .map(|parent| {
+                format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(&kb_href(&public_kb::page_key(parent))),
+                    escape_html(&parent.title)
+                )
+            })
+            .collect();
+        main.push_str(&format!("<div class=\"breadcrumbs\">{}</div>\n", links.join(" / ")));
+    }
+    main.push_str(&format!("<h1>{}</h1>\n", escape_html(&page.title)));
+    main.push_str(&page_body(&mut conn, page));
+
+    let children = public_kb::children_of(&pages, Some(page.id));
+    if !children.is_empty() {
+        main.push_str("<h2>In this section</h2>\n");
+        main.push_str(&page_links(&children));
+    }
+    main.push_str(&format!(
+        "<p class=\"meta\">Last updated {}</p>\n",
+        page.updated_at.format("%B %-d, %Y")
+    ));
+
+    html_response(layout(&branding, &page.title, &nav, Some(page.id), "", &main))
+}
+
+// GET /kb/search?q= - Search results page
+pub async fn kb_search(
+    pool: web::Data<Pool>,
+    index: web::Data<PublicKbIndex>,
+    query: web::Query<PublicSearchQuery>,
+) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return error_page(),
+    };
+    let pages = match load_pages(&mut conn) {
+        Ok(pages) => pages,
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            return error_page();
+        }
+    };
+    let branding = load_branding(&mut conn);
+    let nav = public_kb::nav_tree(&pages);
+    let q = query.q.as_deref().unwrap_or("").trim().to_string();
+
+    let search_index = match index.get(&mut conn, &pages) {
+        Ok(search_index) => search_index,
+        Err(e) => {
+            error!(error = %e, "Failed to build public search index");
+            return error_page();
+        }
+    };
+    let hits = public_kb::search(&search_index, &q, DEFAULT_SEARCH_LIMIT);
+
+    let mut main = format!("<h1>Search results for “{}”</h1>\n", escape_html(&q));
+    if hits.is_empty() {
+        main.push_str("<p>No articles matched your search.</p>\n");
+    } else {
+        main.push_str("<ol>\n");
+        for hit in &hits {
+            let path = if hit.path.is_empty() {
+                String::new()
+            } else {
+                format!("<div class=\"breadcrumbs\">{}</div>", escape_html(&hit.path.join(" / ")))
+            };
+            main.push_str(&format!(
+                "<li><a href=\"{}\">{}</a>{}<p>{}</p></li>\n",
+                escape_html(&kb_href(&hit.key)),
+                escape_html(&hit.title),
+                path,
+                escape_html(&hit.snippet)
+            ));
+        }
+        main.push_str("</ol>\n");
+    }
+
+    html_response(layout(&branding, "Search", &nav, None, &q, &main))
+}
+
+// GET /kb/sitemap.xml
+pub async fn kb_sitemap(pool: web::Data<Pool>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().finish(),
+    };
+    match load_pages(&mut conn) {
+        Ok(pages) => HttpResponse::Ok()
+            .content_type("application/xml; charset=utf-8")
+            .insert_header(("Cache-Control", CACHE_CONTROL))
+            .body(public_kb::sitemap(&public_kb::public_base_url(), &pages)),
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// ============================================================================
+// JSON API
+// ============================================================================
+
+// GET /api/public/kb/tree - Navigation tree
+pub async fn get_public_tree(pool: web::Data<Pool>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+    match load_pages(&mut conn) {
+        Ok(pages) => HttpResponse::Ok().json(public_kb::nav_tree(&pages)),
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            HttpResponse::InternalServerError().json("Failed to load knowledge base")
+        }
+    }
+}
+
+// GET /api/public/kb/pages/{key} - A page with its rendered HTML, breadcrumbs and children
+pub async fn get_public_page(pool: web::Data<Pool>, path: web::Path<String>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+    let pages = match load_pages(&mut conn) {
+        Ok(pages) => pages,
+        Err(e) => {
+            error!(error = %e, "Failed to load public documentation");
+            return HttpResponse::InternalServerError().json("Failed to load knowledge base");
+        }
+    };
+
+    let Some(page) = public_kb::find_page(&pages, &path.into_inner()) else {
+        return HttpResponse::NotFound().json("Page not found");
+    };
+
+    let summary = |page: &PublicDocumentationPage| {
+        json!({
+            "id": page.id,
+            "key": public_kb::page_key(page),
+            "title": page.title,
+            "icon": page.icon,
+        })
+    };
+
+    HttpResponse::Ok().json(json!({
+        "id": page.id,
+        "key": public_kb::page_key(page),
+        "title": page.title,
+        "slug": page.slug,
+        "icon": page.icon,
+        "updated_at": page.updated_at,
+        "breadcrumbs": public_kb::breadcrumbs(&pages, page).into_iter().map(summary).collect::<Vec<_>>(),
+        "children": public_kb::children_of(&pages, Some(page.id)).into_iter().map(summary).collect::<Vec<_>>(),
+        "html": page_body(&mut conn, page),
+    }))
+}
+
+// GET /api/public/kb/search?q=&limit= - Ranked search results
+pub async fn search_public_pages(
+    pool: web::Data<Pool>,
+    index: web::Data<PublicKbIndex>,
+    query: web::Query<PublicSearchQuery>,
+) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let result = load_pages(&mut conn).and_then(|pages| index.get(&mut conn, &pages));
+    match result {
+        Ok(search_index) => HttpResponse::Ok().json(public_kb::search(&search_index, query.q.as_deref().unwrap_or(""), limit)),
+        Err(e) => {
+            error!(error = %e, "Failed to search public documentation");
+            HttpResponse::InternalServerError().json("Failed to search knowledge base")
+        }
+    }
+}
+
+// GET /api/public/kb/search-index - Full index for client-side search
+pub async fn get_public_search_index(pool: web::Data<Pool>, index: web::Data<PublicKbIndex>) -> impl Responder {
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    let result = load_pages(&mut conn).and_then(|pages| index.get(&mut conn, &pages));
+    match result {
+        Ok(search_index) => HttpResponse::Ok()
+            .insert_header(("Cache-Control", CACHE_CONTROL))
+            .json(search_index.as_ref()),
+        Err(e) => {
+            error!(error = %e, "Failed to build public search index");
+            HttpResponse::InternalServerError().json("Failed to load search index")
+        }
+    }
+}
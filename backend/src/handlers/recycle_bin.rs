@@ -0,0 +1,121 @@
+//! Recycle bin: list, restore and permanently delete soft-deleted records (admin only)
+
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::error;
+
+use crate::db::Pool;
+use crate::models::RecycleBinItemType;
+use crate::services::recycle_bin::{self, RecycleBinError};
+use crate::utils::rbac::require_admin;
+use crate::utils::storage::Storage;
+
+fn parse_item(path: (String, i32)) -> Result<(RecycleBinItemType, i32), HttpResponse> {
+    let (item_type, id) = path;
+    match RecycleBinItemType::parse(&item_type) {
+        Some(item_type) => Ok((item_type, id)),
+        None => Err(HttpResponse::BadRequest().json(json!({
+            "error": "Bad Request",
+            "message": format!("Unknown item type: {}", item_type)
+        }))),
+    }
+}
+
+// GET /api/admin/recycle-bin - Everything in the recycle bin
+pub async fn get_recycle_bin(req: HttpRequest, pool: web::Data<Pool>) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match recycle_bin::list_items(&mut conn) {
+        Ok(items) => HttpResponse::Ok().json(json!({
+            "items": items,
+            "retention_days": recycle_bin::retention().num_days(),
+        })),
+        Err(e) => {
+            error!(error = ?e, "Failed to load recycle bin");
+            HttpResponse::InternalServerError().json("Failed to load recycle bin")
+        }
+    }
+}
+
+// POST /api/admin/recycle-bin/{type}/{id}/restore - Take a record out of the recycle bin
+pub async fn restore_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    path: web::Path<(String, i32)>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+    let (item_type, id) = match parse_item(path.into_inner()) {
+        Ok(item) => item,
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    match recycle_bin::restore(&mut conn, item_type, id) {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(RecycleBinError::NotFound) => HttpResponse::NotFound().json("Item not found in the recycle bin"),
+        Err(e @ RecycleBinError::ParentDeleted(..)) => HttpResponse::Conflict().json(json!({
+            "error": "Conflict",
+            "message": e.to_string()
+        })),
+        Err(e) => {
+            error!(id, error = %e, "Failed to restore from recycle bin");
+            HttpResponse::InternalServerError().json("Failed to restore item")
+        }
+    }
+}
+
+// DELETE /api/admin/recycle-bin/{type}/{id} - Permanently delete a record before its retention ends
+pub async fn purge_item(
+    req: HttpRequest,
+    pool: web::Data<Pool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<(String, i32)>,
+) -> impl Responder {
+    if let Err(e) = require_admin(&req) {
+        return e;
+    }
+    let (item_type, id) = match parse_item(path.into_inner()) {
+        Ok(item) => item,
+        Err(e) => return e,
+    };
+
+    let mut conn = match pool.get() {
+        Ok(conn) => conn,
+        Err(_) => return HttpResponse::InternalServerError().json("Database connection error"),
+    };
+
+    // Only records already in the recycle bin can be purged
+    match crate::repository::recycle_bin::get_deleted_at(&mut conn, item_type, id) {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().json("Item not found in the recycle bin"),
+        Err(e) => {
+            error!(id, error = ?e, "Failed to look up recycle bin item");
+            return HttpResponse::InternalServerError().json("Failed to delete item");
+        }
+    }
+
+    match recycle_bin::purge(&mut conn, item_type, id) {
+        Ok(paths) => {
+            recycle_bin::delete_files(storage.get_ref().as_ref(), paths).await;
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => {
+            error!(id, error = ?e, "Failed to purge from recycle bin");
+            HttpResponse::InternalServerError().json("Failed to delete item")
+        }
+    }
+}
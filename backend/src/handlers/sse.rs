@@ -2,7 +2,7 @@ use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
 use futures::Stream;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
@@ -12,6 +12,9 @@ use tokio::time::interval;
 use tokio_stream::wrappers::BroadcastStream;
 use uuid::Uuid;
 
+use crate::services::event_filter::{self, event_ticket_id, EventAccess, EventFilter, Subject};
+use crate::utils::cluster_relay::ClusterRelay;
+
 // Event types for SSE
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -84,6 +87,7 @@ pub enum TicketEvent {
     TicketLinked {
         ticket_id: i32,
         linked_ticket_id: i32,
+        link_type: String,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
     TicketUnlinked {
@@ -101,6 +105,9 @@ pub enum TicketEvent {
     ViewerCountChanged {
         ticket_id: i32,
         count: usize,
+        /// UUIDs of the users viewing the ticket
+        #[serde(default)]
+        viewers: Vec<String>,
         timestamp: chrono::DateTime<chrono::Utc>,
     },
     UserUpdated {
@@ -124,6 +131,17 @@ pub enum TicketEvent {
     },
 }
 
+/// An event with the id sent to clients, used for `Last-Event-ID` replay
+/// Ids are assigned by the instance that raised the event so they match across the cluster.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SseEnvelope {
+    pub id: Uuid,
+    pub event: TicketEvent,
+    /// Ticket parties or device owner, resolved once when the event was raised
+    #[serde(default)]
+    pub subject: Subject,
+}
+
 // Client connection info
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -134,30 +152,94 @@ pub struct ClientInfo {
 }
 
 // Global event broadcaster
-type EventSender = broadcast::Sender<TicketEvent>;
-type EventReceiver = broadcast::Receiver<TicketEvent>;
+type EventSender = broadcast::Sender<SseEnvelope>;
+type EventReceiver = broadcast::Receiver<SseEnvelope>;
+
+const DEFAULT_REPLAY_BUFFER: usize = 500;
 
 // Global state for managing SSE connections
 pub struct SseState {
     pub sender: EventSender,
     pub clients: Arc<Mutex<HashMap<String, ClientInfo>>>,
+    // Recent events for clients reconnecting with Last-Event-ID
+    history: Arc<Mutex<VecDeque<SseEnvelope>>>,
+    history_capacity: usize,
+    // Cluster relay - events are also published to other backend instances
+    relay: Option<Arc<ClusterRelay>>,
+    // Used to resolve each event's subject for access checks
+    pool: crate::db::Pool,
 }
 
 impl SseState {
-    pub fn new() -> Self {
+    pub fn new(pool: crate::db::Pool) -> Self {
         // Optimized buffer: 1000 events is sufficient for most use cases
         // Larger buffers use more memory and can cause lag detection issues
         let (sender, _) = broadcast::channel(1000);
+        let history_capacity = std::env::var("SSE_REPLAY_BUFFER")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_REPLAY_BUFFER);
         Self {
             sender,
             clients: Arc::new(Mutex::new(HashMap::new())),
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(history_capacity))),
+            history_capacity,
+            relay: None,
+            pool,
         }
     }
 
+    /// SSE state for multi-instance deployments
+    /// Local events are published through the relay, and events from other instances are
+    /// delivered to this instance's clients.
+    pub fn with_relay(relay: Arc<ClusterRelay>, pool: crate::db::Pool) -> Self {
+        let mut state = Self::new(pool);
+        let sender = state.sender.clone();
+        let history = state.history.clone();
+        let history_capacity = state.history_capacity;
+        let mut remote_events = relay.event_payloads();
+        tokio::spawn(async move {
+            loop {
+                match remote_events.recv().await {
+                    Ok(payload) => match serde_json::from_slice::<SseEnvelope>(&payload) {
+                        Ok(envelope) => {
+                            Self::record(&history, history_capacity, &envelope);
+                            let _ = sender.send(envelope);
+                        }
+                        Err(e) => tracing::warn!("SSE: Ignoring undecodable relayed event: {}", e),
+                    },
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        tracing::warn!("SSE: Relay listener lagged by {} events", count);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        state.relay = Some(relay);
+        state
+    }
+
     pub async fn broadcast_event(&self, event: TicketEvent) {
+        let subject = self.resolve_subject(&event).await;
+        let envelope = SseEnvelope {
+            id: Uuid::now_v7(),
+            event,
+            subject,
+        };
+
+        // Publish to the other instances first (their clients are not subscribed here)
+        if let Some(relay) = &self.relay {
+            match serde_json::to_vec(&envelope) {
+                Ok(payload) => relay.publish_event(&payload).await,
+                Err(e) => tracing::warn!("SSE: Failed to serialize event for relay: {}", e),
+            }
+        }
+
+        Self::record(&self.history, self.history_capacity, &envelope);
+
         // Fast, non-blocking broadcast - just send once
         // Log when events are dropped for tracking issues
-        match self.sender.send(event.clone()) {
+        match self.sender.send(envelope.clone()) {
             Ok(receiver_count) => {
                 #[cfg(debug_assertions)]
                 tracing::debug!("SSE: Event sent to {} receivers", receiver_count);
@@ -165,11 +247,60 @@ impl SseState {
             Err(_) => {
                 // No active receivers - log in debug mode to track dropped events
                 #[cfg(debug_assertions)]
-                tracing::warn!("SSE: Event dropped - no active receivers: {:?}", event);
+                tracing::warn!("SSE: Event dropped - no active receivers: {:?}", envelope.event);
+            }
+        }
+    }
+
+    /// Look up who an event concerns once, instead of once per connected client
+    /// An event whose subject cannot be loaded is only delivered to staff.
+    async fn resolve_subject(&self, event: &TicketEvent) -> Subject {
+        let unresolved = match event {
+            _ if event_ticket_id(event).is_some() => Subject::Ticket(None),
+            TicketEvent::DeviceUpdated { .. } => Subject::Device(None),
+            _ => return Subject::None,
+        };
+
+        let pool = self.pool.clone();
+        let event = event.clone();
+        let result = web::block(move || {
+            let mut conn = pool.get().map_err(|e| e.to_string())?;
+            event_filter::resolve_subject(&mut conn, &event).map_err(|e| e.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(subject)) => subject,
+            Ok(Err(e)) => {
+                tracing::warn!("SSE: Failed to resolve event subject, limiting event to staff: {}", e);
+                unresolved
+            }
+            Err(e) => {
+                tracing::warn!("SSE: Event subject lookup task failed, limiting event to staff: {}", e);
+                unresolved
             }
         }
     }
 
+    fn record(history: &Mutex<VecDeque<SseEnvelope>>, capacity: usize, envelope: &SseEnvelope) {
+        if capacity == 0 {
+            return;
+        }
+        let mut history = history.lock().unwrap();
+        while history.len() >= capacity {
+            history.pop_front();
+        }
+        history.push_back(envelope.clone());
+    }
+
+    /// Events recorded after `last_event_id`, oldest first
+    /// Returns `None` when the id is no longer (or never was) in the replay buffer.
+    pub fn events_after(&self, last_event_id: Uuid) -> Option<Vec<SseEnvelope>> {
+        let history = self.history.lock().unwrap();
+        let position = history.iter().position(|envelope| envelope.id == last_event_id)?;
+        Some(history.iter().skip(position + 1).cloned().collect())
+    }
+
     pub fn add_client(&self, client_id: String, user_id: String) {
         let mut clients = self.clients.lock().unwrap();
         clients.insert(
@@ -206,16 +337,61 @@ impl SseState {
     }
 }
 
+fn event_name(event: &TicketEvent) -> &'static str {
+    match event {
+        TicketEvent::TicketUpdated { .. } => "ticket-updated",
+        TicketEvent::TicketCreated { .. } => "ticket-created",
+        TicketEvent::TicketDeleted { .. } => "ticket-deleted",
+        TicketEvent::CommentAdded { .. } => "comment-added",
+        TicketEvent::CommentDeleted { .. } => "comment-deleted",
+        TicketEvent::AttachmentAdded { .. } => "attachment-added",
+        TicketEvent::AttachmentDeleted { .. } => "attachment-deleted",
+        TicketEvent::DeviceLinked { .. } => "device-linked",
+        TicketEvent::DeviceUnlinked { .. } => "device-unlinked",
+        TicketEvent::DeviceUpdated { .. } => "device-updated",
+        TicketEvent::ProjectAssigned { .. } => "project-assigned",
+        TicketEvent::ProjectUnassigned { .. } => "project-unassigned",
+        TicketEvent::TicketLinked { .. } => "ticket-linked",
+        TicketEvent::TicketUnlinked { .. } => "ticket-unlinked",
+        TicketEvent::DocumentationUpdated { .. } => "documentation-updated",
+        TicketEvent::ViewerCountChanged { .. } => "viewer-count-changed",
+        TicketEvent::UserUpdated { .. } => "user-updated",
+        TicketEvent::UserCreated { .. } => "user-created",
+        TicketEvent::UserDeleted { .. } => "user-deleted",
+        TicketEvent::Heartbeat { .. } => "heartbeat",
+    }
+}
+
+fn format_envelope(envelope: &SseEnvelope) -> actix_web::web::Bytes {
+    let event_data = serde_json::to_string(&envelope.event).unwrap_or_default();
+    actix_web::web::Bytes::from(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        envelope.id,
+        event_name(&envelope.event),
+        event_data
+    ))
+}
+
 // SSE stream implementation
 pub struct SseStream {
-    event_stream: BroadcastStream<TicketEvent>,
+    event_stream: BroadcastStream<SseEnvelope>,
     heartbeat_interval: tokio::time::Interval,
     client_id: String,
     state: web::Data<SseState>,
+    access: EventAccess,
+    // Replayed events and resync notices, sent before live events
+    pending: VecDeque<actix_web::web::Bytes>,
+    // Ids already sent from the replay buffer, skipped if they also arrive live
+    replayed: HashSet<Uuid>,
 }
 
 impl SseStream {
-    pub fn new(receiver: EventReceiver, client_id: String, state: web::Data<SseState>) -> Self {
+    pub fn new(
+        receiver: EventReceiver,
+        client_id: String,
+        state: web::Data<SseState>,
+        access: EventAccess,
+    ) -> Self {
         // 15 second heartbeat for better connection detection
         let mut heartbeat_interval = interval(Duration::from_secs(15));
         heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
@@ -228,6 +404,29 @@ impl SseStream {
             heartbeat_interval,
             client_id,
             state,
+            access,
+            pending: VecDeque::new(),
+            replayed: HashSet::new(),
+        }
+    }
+
+    /// Queue missed events for a client resuming from `last_event_id`
+    /// Must be called after subscribing so nothing falls between the replay and live events.
+    fn replay_from(&mut self, last_event_id: Uuid) {
+        match self.state.events_after(last_event_id) {
+            Some(missed) => {
+                for envelope in missed {
+                    self.replayed.insert(envelope.id);
+                    if self.access.allows(&envelope.event, &envelope.subject) {
+                        self.pending.push_back(format_envelope(&envelope));
+                    }
+                }
+            }
+            None => {
+                // Too far behind - tell the client to refetch its state
+                self.pending
+                    .push_back(actix_web::web::Bytes::from_static(b"event: resync\ndata: {}\n\n"));
+            }
         }
     }
 }
@@ -239,51 +438,37 @@ impl Stream for SseStream {
         let this = self.get_mut();
         let client_id = this.client_id.clone();
 
+        if let Some(bytes) = this.pending.pop_front() {
+            return Poll::Ready(Some(Ok(bytes)));
+        }
+
         // Poll the BroadcastStream - this properly maintains waker registration across polls
-        match Pin::new(&mut this.event_stream).poll_next(cx) {
-            Poll::Ready(Some(Ok(event))) => {
-                // Got event - determine event type and serialize
-                let event_type = match &event {
-                    TicketEvent::TicketUpdated { .. } => "ticket-updated",
-                    TicketEvent::TicketCreated { .. } => "ticket-created",
-                    TicketEvent::TicketDeleted { .. } => "ticket-deleted",
-                    TicketEvent::CommentAdded { .. } => "comment-added",
-                    TicketEvent::CommentDeleted { .. } => "comment-deleted",
-                    TicketEvent::AttachmentAdded { .. } => "attachment-added",
-                    TicketEvent::AttachmentDeleted { .. } => "attachment-deleted",
-                    TicketEvent::DeviceLinked { .. } => "device-linked",
-                    TicketEvent::DeviceUnlinked { .. } => "device-unlinked",
-                    TicketEvent::DeviceUpdated { .. } => "device-updated",
-                    TicketEvent::ProjectAssigned { .. } => "project-assigned",
-                    TicketEvent::ProjectUnassigned { .. } => "project-unassigned",
-                    TicketEvent::TicketLinked { .. } => "ticket-linked",
-                    TicketEvent::TicketUnlinked { .. } => "ticket-unlinked",
-                    TicketEvent::DocumentationUpdated { .. } => "documentation-updated",
-                    TicketEvent::ViewerCountChanged { .. } => "viewer-count-changed",
-                    TicketEvent::UserUpdated { .. } => "user-updated",
-                    TicketEvent::UserCreated { .. } => "user-created",
-                    TicketEvent::UserDeleted { .. } => "user-deleted",
-                    TicketEvent::Heartbeat { .. } => "heartbeat",
-                };
-
-                // Serialize event data
-                let event_data = serde_json::to_string(&event).unwrap_or_default();
-                let sse_data = format!("event: {}\ndata: {}\n\n", event_type, event_data);
-
-                return Poll::Ready(Some(Ok(actix_web::web::Bytes::from(sse_data))));
-            }
-            Poll::Ready(Some(Err(tokio_stream::wrappers::errors::BroadcastStreamRecvError::Lagged(count)))) => {
-                // Client is lagging - close connection so they can reconnect with fresh buffer
-                tracing::warn!("SSE: Client {} lagged by {} events, closing connection", client_id, count);
-                return Poll::Ready(None);
-            }
-            Poll::Ready(None) => {
-                // Stream ended (channel closed)
-                tracing::info!("SSE: Channel closed for client {}", client_id);
-                return Poll::Ready(None);
-            }
-            Poll::Pending => {
-                // No event yet - check heartbeat before returning Pending
+        loop {
+            match Pin::new(&mut this.event_stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(envelope))) => {
+                    if this.replayed.remove(&envelope.id) {
+                        continue;
+                    }
+                    // Skip events filtered out or not visible to this user
+                    if !this.access.allows(&envelope.event, &envelope.subject) {
+                        continue;
+                    }
+                    return Poll::Ready(Some(Ok(format_envelope(&envelope))));
+                }
+                Poll::Ready(Some(Err(tokio_stream::wrappers::errors::BroadcastStreamRecvError::Lagged(count)))) => {
+                    // Client is lagging - close connection so they can reconnect and replay
+                    tracing::warn!("SSE: Client {} lagged by {} events, closing connection", client_id, count);
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(None) => {
+                    // Stream ended (channel closed)
+                    tracing::info!("SSE: Channel closed for client {}", client_id);
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => {
+                    // No event yet - check heartbeat before returning Pending
+                    break;
+                }
             }
         }
 
@@ -343,11 +528,49 @@ pub async fn ticket_events_stream(
         }
     };
 
+    let filter = match EventFilter::from_query(
+        query.ticket_ids.as_deref(),
+        query.assigned.as_deref(),
+        query.categories.as_deref(),
+    ) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "status": "error",
+                "message": message
+            })));
+        }
+    };
+
+    let user_uuid = match crate::utils::parse_uuid(&user_info.sub) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Ok(HttpResponse::Unauthorized().json(json!({
+                "status": "error",
+                "message": "Invalid user in SSE token"
+            })));
+        }
+    };
+    let is_staff = user_info.role == "admin" || user_info.role == "technician";
+
+    // EventSource sends Last-Event-ID on reconnect; the query parameter covers fresh connections
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| query.last_event_id.clone())
+        .and_then(|id| Uuid::parse_str(id.trim()).ok());
+
     // Generate client ID and create stream
     let client_id = Uuid::now_v7().to_string();
     state.add_client(client_id.clone(), user_info.sub.clone());
     let receiver = state.sender.subscribe();
-    let stream = SseStream::new(receiver, client_id.clone(), state.clone());
+    let access = EventAccess::new(user_uuid, is_staff, filter);
+    let mut stream = SseStream::new(receiver, client_id.clone(), state.clone(), access);
+    if let Some(last_event_id) = last_event_id {
+        stream.replay_from(last_event_id);
+    }
 
     // Return SSE response with optimized headers
     Ok(HttpResponse::Ok()
@@ -361,6 +584,13 @@ pub async fn ticket_events_stream(
 #[derive(Deserialize)]
 pub struct TicketEventsQuery {
     sse_token: Option<String>,
+    /// Comma-separated ticket ids to limit the stream to
+    ticket_ids: Option<String>,
+    /// `me` to limit the stream to tickets assigned to the caller
+    assigned: Option<String>,
+    /// Comma-separated event categories (tickets, comments, devices, ...)
+    categories: Option<String>,
+    last_event_id: Option<String>,
 }
 
 // SSE status endpoint
@@ -6,9 +6,10 @@ use std::path::Path;
 use tracing::{debug, error, warn};
 use uuid::Uuid;
 
-use crate::models::{AssignmentTrigger, Claims, NewTicket, TicketPriority, TicketStatus, TicketUpdate, TicketsJson, UserRole};
+use crate::models::{AssignmentTrigger, Claims, NewTicket, TicketLinkType, TicketPriority, TicketStatus, TicketUpdate, TicketsJson, UserRole};
 use crate::repository;
 use crate::services::assignment::AssignmentEngine;
+use crate::services::ticket_status::{check_status_change, StatusChangeBlocked};
 use crate::utils::rbac::{is_admin, is_technician_or_admin};
 use crate::utils::sse::SseBroadcaster;
 
@@ -54,6 +55,21 @@ fn validate_assignee_role(
     }
 }
 
+/// 409 response for a status change refused by the shared status rules
+fn status_blocked_response(blocked: StatusChangeBlocked) -> HttpResponse {
+    match &blocked {
+        StatusChangeBlocked::ApprovalPending => HttpResponse::Conflict().json(json!({
+            "error": "Approval pending",
+            "message": blocked.to_string()
+        })),
+        StatusChangeBlocked::OpenChildren(open_children) => HttpResponse::Conflict().json(json!({
+            "error": "Open child tickets",
+            "message": format!("Ticket has {} open child ticket(s); set cascade_children to close them or leave them open", open_children.len()),
+            "open_children": open_children
+        })),
+    }
+}
+
 // Helper function to parse and validate assignee from string (for update operations)
 fn parse_and_validate_assignee_string(
     assignee_str: &str,
@@ -128,10 +144,12 @@ async fn broadcast_sse_simple(
             }
             "ticket_linked" => {
                 if let Some(linked_id) = data.get("linked_ticket_id").and_then(|v| v.as_u64()) {
+                    let link_type = data.get("link_type").and_then(|v| v.as_str()).unwrap_or("relates_to");
                     SseBroadcaster::broadcast_ticket_linked(
                         &sse_state,
                         ticket_id,
                         linked_id as i32,
+                        link_type,
                     )
                     .await;
                 }
@@ -208,6 +226,34 @@ pub struct PaginationParams {
     closed_on: Option<String>,
 }
 
+impl PaginationParams {
+    /// The list filters, for endpoints that share them (such as the CSV export)
+    pub fn filters(&self) -> crate::repository::tickets::TicketFilters {
+        crate::repository::tickets::TicketFilters {
+            search: self.search.clone(),
+            status: self.status.clone(),
+            priority: self.priority.clone(),
+            category: self.category.clone(),
+            assignee: self.assignee.clone(),
+            requester: self.requester.clone(),
+            created_after: self.created_after.clone(),
+            created_before: self.created_before.clone(),
+            created_on: self.created_on.clone(),
+            modified_after: self.modified_after.clone(),
+            modified_before: self.modified_before.clone(),
+            modified_on: self.modified_on.clone(),
+            closed_after: self.closed_after.clone(),
+            closed_before: self.closed_before.clone(),
+            closed_on: self.closed_on.clone(),
+        }
+    }
+
+    /// Sort field and direction
+    pub fn sort(&self) -> (Option<&str>, Option<&str>) {
+        (self.sort_field.as_deref(), self.sort_direction.as_deref())
+    }
+}
+
 // Paginated response
 #[derive(Serialize)]
 pub struct PaginatedResponse<T> {
@@ -351,7 +397,19 @@ pub async fn create_ticket(
     }
 
     match repository::create_ticket(&mut conn, new_ticket) {
-        Ok(ticket) => {
+        Ok(mut ticket) => {
+            if ticket.category_id.is_some() {
+                use crate::services::approvals::ApprovalService;
+
+                match ApprovalService::start(&mut conn, &ticket, ticket.requester_uuid).await {
+                    Ok(approvals) if !approvals.is_empty() => {
+                        ticket.approval_status = Some(crate::models::TicketApprovalStatus::Pending.as_str().to_string());
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!(ticket_id = ticket.id, error = %e, "Failed to start approval chain"),
+                }
+            }
+
             // Broadcast ticket creation via SSE
             crate::utils::sse::SseBroadcaster::broadcast_ticket_created(
                 &sse_state,
@@ -385,19 +443,28 @@ pub async fn update_ticket(
         }
     }
 
+    let current = match repository::get_ticket_by_id(&mut conn, ticket_id) {
+        Ok(ticket) => ticket,
+        Err(diesel::result::Error::NotFound) => return HttpResponse::NotFound().json("Ticket not found"),
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Failed to update ticket: {}", e)),
+    };
+    if let Err(blocked) = check_status_change(&mut conn, &current, new_ticket.status, None) {
+        return status_blocked_response(blocked);
+    }
+
     match repository::update_ticket(&mut conn, ticket_id, new_ticket) {
         Ok(ticket) => HttpResponse::Ok().json(ticket),
+        Err(diesel::result::Error::NotFound) => HttpResponse::NotFound().json("Ticket not found"),
         Err(e) => {
             HttpResponse::InternalServerError().json(format!("Failed to update ticket: {}", e))
         }
     }
 }
 
-// Delete a ticket with comprehensive cleanup
+// Move a ticket to the recycle bin
 pub async fn delete_ticket(
     req: HttpRequest,
     pool: web::Data<crate::db::Pool>,
-    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
     path: web::Path<i32>,
 ) -> impl Responder {
     // Extract claims and check role
@@ -422,10 +489,8 @@ pub async fn delete_ticket(
         Err(e) => return e,
     };
 
-    // Use the comprehensive deletion function that cleans up files
-    match repository::delete_ticket_with_cleanup(&mut conn, ticket_id, storage.as_ref().clone())
-        .await
-    {
+    // Files and related records are removed when the recycle bin is purged
+    match repository::delete_ticket(&mut conn, ticket_id, Uuid::parse_str(&claims.sub).ok()) {
         Ok(rows_affected) => {
             if rows_affected > 0 {
                 HttpResponse::NoContent().finish()
@@ -651,6 +716,32 @@ pub async fn update_ticket_partial(
         None => return HttpResponse::Unauthorized().json("Authentication required"),
     };
 
+    match apply_ticket_update(&mut conn, &sse_state, &user_info, ticket_id, body.into_inner()).await {
+        Ok(updated_ticket) => HttpResponse::Ok().json(updated_ticket),
+        Err(response) => response,
+    }
+}
+
+/// Apply a partial ticket update and broadcast the resulting SSE events
+/// Shared by the REST endpoint and the websocket RPC; errors are returned as ready responses.
+pub(crate) async fn apply_ticket_update(
+    conn: &mut crate::db::DbConnection,
+    sse_state: &web::Data<crate::handlers::sse::SseState>,
+    user_info: &Claims,
+    ticket_id: i32,
+    body: Value,
+) -> Result<crate::models::CompleteTicket, HttpResponse> {
+    let current = match repository::get_ticket_by_id(conn, ticket_id) {
+        Ok(ticket) => ticket,
+        Err(diesel::result::Error::NotFound) => {
+            return Err(HttpResponse::NotFound().json("Ticket not found"))
+        }
+        Err(e) => {
+            error!(ticket_id, error = ?e, "Failed to load ticket for update");
+            return Err(HttpResponse::InternalServerError().json("Failed to update ticket"));
+        }
+    };
+
     // Parse JSON and build TicketUpdate with user lookups
     let mut ticket_update = TicketUpdate {
         title: None,
@@ -702,7 +793,7 @@ pub async fn update_ticket_partial(
             ticket_update.requester_uuid = Some(Some(uuid));
         } else {
             // Try to look up by name
-            match crate::repository::users::get_user_by_name(requester_str, &mut conn) {
+            match crate::repository::users::get_user_by_name(requester_str, conn) {
                 Ok(user) => ticket_update.requester_uuid = Some(Some(user.uuid)),
                 Err(_) => {
                     warn!(name = %requester_str, "Could not find user by name");
@@ -718,9 +809,9 @@ pub async fn update_ticket_partial(
             ticket_update.assignee_uuid = Some(None);
         } else {
             // Parse and validate assignee
-            match parse_and_validate_assignee_string(assignee_str, &mut conn) {
+            match parse_and_validate_assignee_string(assignee_str, conn) {
                 Ok(uuid) => ticket_update.assignee_uuid = Some(Some(uuid)),
-                Err(response) => return response,
+                Err(response) => return Err(response),
             }
         }
     }
@@ -741,16 +832,27 @@ pub async fn update_ticket_partial(
         }
     }
 
-    // Track if category was changed for auto-assignment
-    let category_changed = body.get("category_id").is_some();
+    // Closing a parent ticket with open children requires an explicit choice:
+    // "cascade_children": true closes them too, false closes only the parent
+    let cascade_children = body.get("cascade_children").and_then(|v| v.as_bool());
+    if let Some(status) = ticket_update.status {
+        check_status_change(conn, &current, status, cascade_children)
+            .map_err(status_blocked_response)?;
+    }
+
+    // Track if category was changed for auto-assignment and approvals; clients often re-send
+    // the current category with the rest of the ticket, which is not a change
+    let category_changed = ticket_update
+        .category_id
+        .is_some_and(|category_id| category_id != current.category_id);
 
     // Update the ticket
-    match repository::update_ticket_partial(&mut conn, ticket_id, ticket_update) {
+    match repository::update_ticket_partial(conn, ticket_id, ticket_update) {
         Ok(updated_ticket) => {
             // Run automatic assignment rules if category changed and no assignee
             if category_changed && updated_ticket.assignee_uuid.is_none() {
                 if let Some(result) = AssignmentEngine::evaluate_rules(
-                    &mut conn,
+                    conn,
                     &updated_ticket,
                     AssignmentTrigger::CategoryChanged,
                 ) {
@@ -761,7 +863,7 @@ pub async fn update_ticket_partial(
                             updated_at: Some(chrono::Utc::now().naive_utc()),
                             ..Default::default()
                         };
-                        if repository::update_ticket_partial(&mut conn, ticket_id, assign_update).is_ok() {
+                        if repository::update_ticket_partial(conn, ticket_id, assign_update).is_ok() {
                             log::info!(
                                 "Auto-assigned ticket {} to user {} via rule '{}' ({}) on category change",
                                 ticket_id,
@@ -771,7 +873,7 @@ pub async fn update_ticket_partial(
                             );
 
                             // Get user info for the SSE event
-                            let user_info = repository::get_user_by_uuid(&assigned_uuid, &mut conn)
+                            let user_info = repository::get_user_by_uuid(&assigned_uuid, conn)
                                 .ok()
                                 .map(|u| crate::models::UserInfoWithAvatar::from(u));
 
@@ -797,9 +899,60 @@ pub async fn update_ticket_partial(
                 }
             }
 
+            // Entering a category with an approval chain (re)starts approvals
+            if category_changed {
+                use crate::services::approvals::ApprovalService;
+
+                let requested_by = Uuid::parse_str(&user_info.sub).ok();
+                match ApprovalService::start(conn, &updated_ticket, requested_by).await {
+                    Ok(approvals) if !approvals.is_empty() => {
+                        broadcast_sse_simple(
+                            sse_state.clone(),
+                            ticket_id,
+                            "ticket_updated".to_string(),
+                            json!({
+                                "key": "approval_status",
+                                "value": crate::models::TicketApprovalStatus::Pending.as_str(),
+                                "user_sub": "system"
+                            }),
+                        )
+                        .await;
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!(ticket_id, error = %e, "Failed to start approval chain"),
+                }
+            }
+
+            if updated_ticket.status == TicketStatus::Closed && cascade_children == Some(true) {
+                use crate::services::ticket_relationships::TicketRelationshipService;
+
+                let closed_by = Uuid::parse_str(&user_info.sub).ok();
+                match TicketRelationshipService::close_children(conn, ticket_id, closed_by) {
+                    Ok(children) => {
+                        for child in children {
+                            broadcast_sse_simple(
+                                sse_state.clone(),
+                                child.id,
+                                "ticket_updated".to_string(),
+                                json!({
+                                    "key": "status",
+                                    "value": child.status,
+                                    "user_sub": user_info.sub
+                                }),
+                            )
+                            .await;
+                        }
+                    }
+                    Err(e) => error!(ticket_id, error = ?e, "Failed to close child tickets"),
+                }
+            }
+
             // Broadcast SSE events IMMEDIATELY after DB update for low latency
             // Don't wait for fetching complete ticket data
-            for (key, value) in body.0.as_object().unwrap_or(&serde_json::Map::new()) {
+            for (key, value) in body.as_object().unwrap_or(&serde_json::Map::new()) {
+                if key == "cascade_children" {
+                    continue;
+                }
                 debug!(ticket_id = ticket_id, key = %key, value = ?value, "Broadcasting SSE event");
                 broadcast_sse_simple(
                     sse_state.clone(),
@@ -816,31 +969,35 @@ pub async fn update_ticket_partial(
 
             // Now fetch the complete ticket for the response
             // This happens after SSE broadcast so it doesn't delay real-time updates
-            let updated_ticket = match repository::get_complete_ticket(&mut conn, ticket_id) {
-                Ok(ticket) => ticket,
-                Err(_) => {
-                    return HttpResponse::InternalServerError()
-                        .json("Failed to fetch updated ticket")
-                }
-            };
-
-            // Return the updated complete ticket
-            HttpResponse::Ok().json(updated_ticket)
+            repository::get_complete_ticket(conn, ticket_id).map_err(|_| {
+                HttpResponse::InternalServerError().json("Failed to fetch updated ticket")
+            })
         }
+        Err(diesel::result::Error::NotFound) => Err(HttpResponse::NotFound().json("Ticket not found")),
         Err(e) => {
             error!(error = ?e, "Failed to update ticket");
-            HttpResponse::InternalServerError().json("Failed to update ticket")
+            Err(HttpResponse::InternalServerError().json("Failed to update ticket"))
         }
     }
 }
 
+#[derive(Deserialize)]
+pub struct LinkTicketsRequest {
+    pub link_type: Option<String>,
+}
+
 // Link tickets
+// The optional body sets the relationship type, read from the first ticket's side
+// ("ticket_id blocks linked_ticket_id"); without a body a plain relates_to link is created
 pub async fn link_tickets(
     req: HttpRequest,
     pool: web::Data<crate::db::Pool>,
     path: web::Path<(i32, i32)>,
+    body: Option<web::Json<LinkTicketsRequest>>,
     sse_state: web::Data<crate::handlers::sse::SseState>,
 ) -> impl Responder {
+    use crate::services::ticket_relationships::{RelationshipError, TicketRelationshipService};
+
     // Extract claims and check role
     let claims = match req.extensions().get::<Claims>() {
         Some(claims) => claims.clone(),
@@ -857,28 +1014,61 @@ pub async fn link_tickets(
         }));
     }
 
+    let link_type = match body.and_then(|b| b.into_inner().link_type) {
+        Some(value) => match value.parse::<TicketLinkType>() {
+            Ok(link_type) => link_type,
+            Err(message) => return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid link type",
+                "message": message
+            })),
+        },
+        None => TicketLinkType::RelatesTo,
+    };
+
+    let user_uuid = Uuid::parse_str(&claims.sub).ok();
     let (ticket_id, linked_ticket_id) = path.into_inner();
     let mut conn = match get_db_conn(&pool).await {
         Ok(conn) => conn,
         Err(e) => return e,
     };
 
-    match repository::link_tickets(&mut conn, ticket_id, linked_ticket_id) {
+    match TicketRelationshipService::link(&mut conn, ticket_id, linked_ticket_id, link_type, user_uuid) {
         Ok(_) => {
-            debug!(ticket_id = ticket_id, linked_ticket_id = linked_ticket_id, "Broadcasting SSE event for ticket linking");
+            debug!(ticket_id = ticket_id, linked_ticket_id = linked_ticket_id, link_type = %link_type, "Broadcasting SSE event for ticket linking");
 
-            // Broadcast SSE event for ticket linking
+            // Broadcast the link from both sides so each ticket view gets its own relationship type
             broadcast_sse_simple(
                 sse_state.clone(),
                 ticket_id,
                 "ticket_linked".to_string(),
                 json!({
-                    "linked_ticket_id": linked_ticket_id
+                    "linked_ticket_id": linked_ticket_id,
+                    "link_type": link_type.as_str()
+                }),
+            )
+            .await;
+            broadcast_sse_simple(
+                sse_state.clone(),
+                linked_ticket_id,
+                "ticket_linked".to_string(),
+                json!({
+                    "linked_ticket_id": ticket_id,
+                    "link_type": link_type.inverse().as_str()
                 }),
             )
             .await;
 
-            HttpResponse::Ok().json(json!({"success": true}))
+            HttpResponse::Ok().json(json!({"success": true, "link_type": link_type}))
+        }
+        Err(RelationshipError::TicketNotFound(id)) => HttpResponse::NotFound().json(json!({
+            "error": "Not found",
+            "message": format!("Ticket #{} not found", id)
+        })),
+        Err(e @ (RelationshipError::SelfLink | RelationshipError::Cycle { .. } | RelationshipError::ParentAlreadySet { .. })) => {
+            HttpResponse::BadRequest().json(json!({
+                "error": "Invalid relationship",
+                "message": e.to_string()
+            }))
         }
         Err(e) => {
             error!(error = ?e, "Failed to link tickets");
@@ -940,6 +1130,203 @@ pub async fn unlink_tickets(
     }
 }
 
+// Merge a ticket into another ticket
+pub async fn merge_ticket(
+    req: HttpRequest,
+    pool: web::Data<crate::db::Pool>,
+    path: web::Path<(i32, i32)>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+    yjs_app_state: web::Data<crate::handlers::collaboration::YjsAppState>,
+) -> impl Responder {
+    use crate::services::ticket_merge::{MergeError, TicketMergeService};
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can merge tickets"
+        }));
+    }
+
+    let user_uuid = match get_user_uuid_from_claims(&claims) {
+        Ok(uuid) => uuid,
+        Err(e) => return e,
+    };
+
+    let (source_id, target_id) = path.into_inner();
+    let mut conn = match get_db_conn(&pool).await {
+        Ok(conn) => conn,
+        Err(e) => return e,
+    };
+
+    // Capture the source notes before the merge so they can be appended to the target
+    let source_notes = yjs_app_state.encode_document_state(&format!("ticket-{}", source_id)).await;
+
+    let outcome = match TicketMergeService::merge(&mut conn, source_id, target_id, user_uuid) {
+        Ok(outcome) => outcome,
+        Err(MergeError::TicketNotFound(id)) => return HttpResponse::NotFound().json(json!({
+            "error": "Not found",
+            "message": format!("Ticket #{} not found", id)
+        })),
+        Err(e @ (MergeError::SameTicket | MergeError::AlreadyMerged { .. } | MergeError::InvalidComments(_))) => {
+            return HttpResponse::BadRequest().json(json!({
+                "error": "Invalid merge",
+                "message": e.to_string()
+            }));
+        }
+        Err(e) => {
+            error!(source_id, target_id, error = %e, "Failed to merge tickets");
+            return HttpResponse::InternalServerError().json("Failed to merge tickets");
+        }
+    };
+
+    let relocated = TicketMergeService::relocate_attachments(
+        &mut conn,
+        storage.get_ref().clone(),
+        &outcome.attachment_moves,
+    ).await;
+
+    let notes_appended = yjs_app_state.append_document_content(
+        &format!("ticket-{}", target_id),
+        &source_notes,
+        &format!("Merged from #{}: {}", outcome.source.id, outcome.source.title),
+        user_uuid,
+    ).await;
+
+    // Source ticket is now closed and redirects to the target
+    SseBroadcaster::broadcast_ticket_updated(&sse_state, source_id, "status", json!(outcome.source.status), &claims.sub).await;
+    SseBroadcaster::broadcast_ticket_updated(&sse_state, source_id, "merged_into_id", json!(target_id), &claims.sub).await;
+
+    for comment_id in &outcome.moved_comment_ids {
+        SseBroadcaster::broadcast_comment_deleted(&sse_state, source_id, *comment_id).await;
+    }
+    for device_id in &outcome.moved_device_ids {
+        SseBroadcaster::broadcast_device_unlinked(&sse_state, source_id, *device_id).await;
+        SseBroadcaster::broadcast_device_linked(&sse_state, target_id, *device_id).await;
+    }
+    for linked_id in &outcome.moved_linked_ticket_ids {
+        SseBroadcaster::broadcast_ticket_unlinked(&sse_state, source_id, *linked_id).await;
+        let link_type = repository::linked_tickets::get_link_type(&mut conn, target_id, *linked_id)
+            .ok()
+            .flatten()
+            .unwrap_or(TicketLinkType::RelatesTo);
+        SseBroadcaster::broadcast_ticket_linked(&sse_state, target_id, *linked_id, link_type.as_str()).await;
+    }
+    SseBroadcaster::broadcast_ticket_linked(&sse_state, target_id, source_id, TicketLinkType::DuplicatedBy.as_str()).await;
+
+    // Clients reload the target's comments from the complete ticket
+    if let Ok(complete) = repository::get_complete_ticket(&mut conn, target_id) {
+        for comment in complete.comments.iter().filter(|c| outcome.moved_comment_ids.contains(&c.comment.id)) {
+            SseBroadcaster::broadcast_comment_added(&sse_state, target_id, serde_json::to_value(comment).unwrap_or_default()).await;
+        }
+    }
+
+    debug!(source_id, target_id, relocated_attachments = relocated, notes_appended, "Ticket merge completed");
+
+    HttpResponse::Ok().json(json!({
+        "success": true,
+        "source": outcome.source,
+        "target": outcome.target,
+        "moved_comments": outcome.moved_comment_ids.len(),
+        "moved_devices": outcome.moved_device_ids.len(),
+        "moved_projects": outcome.moved_project_ids.len(),
+        "moved_links": outcome.moved_linked_ticket_ids.len(),
+        "notes_appended": notes_appended,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct SplitTicketRequest {
+    pub comment_ids: Vec<i32>,
+    pub title: Option<String>,
+}
+
+// Split selected comments out of a ticket into a new linked ticket
+pub async fn split_ticket(
+    req: HttpRequest,
+    pool: web::Data<crate::db::Pool>,
+    path: web::Path<i32>,
+    body: web::Json<SplitTicketRequest>,
+    sse_state: web::Data<crate::handlers::sse::SseState>,
+    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
+) -> impl Responder {
+    use crate::services::ticket_merge::{MergeError, TicketMergeService};
+
+    let claims = match req.extensions().get::<Claims>() {
+        Some(claims) => claims.clone(),
+        None => return HttpResponse::Unauthorized().json(json!({
+            "error": "Unauthorized",
+            "message": "Authentication required"
+        })),
+    };
+
+    if !is_technician_or_admin(&claims) {
+        return HttpResponse::Forbidden().json(json!({
+            "error": "Forbidden",
+            "message": "Only technicians and administrators can split tickets"
+        }));
+    }
+
+    let user_uuid = match get_user_uuid_from_claims(&claims) {
+        Ok(uuid) => uuid,
+        Err(e) => return e,
+    };
+
+    let source_id = path.into_inner();
+    let body = body.into_inner();
+    let mut conn = match get_db_conn(&pool).await {
+        Ok(conn) => conn,
+        Err(e) => return e,
+    };
+
+    let outcome = match TicketMergeService::split(&mut conn, source_id, &body.comment_ids, body.title, user_uuid) {
+        Ok(outcome) => outcome,
+        Err(MergeError::TicketNotFound(id)) => return HttpResponse::NotFound().json(json!({
+            "error": "Not found",
+            "message": format!("Ticket #{} not found", id)
+        })),
+        Err(MergeError::InvalidComments(message)) => return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid comment selection",
+            "message": message
+        })),
+        Err(e) => {
+            error!(source_id, error = %e, "Failed to split ticket");
+            return HttpResponse::InternalServerError().json("Failed to split ticket");
+        }
+    };
+
+    TicketMergeService::relocate_attachments(
+        &mut conn,
+        storage.get_ref().clone(),
+        &outcome.attachment_moves,
+    ).await;
+
+    let new_ticket_id = outcome.ticket.id;
+    SseBroadcaster::broadcast_ticket_created(
+        &sse_state,
+        new_ticket_id,
+        serde_json::to_value(&outcome.ticket).unwrap_or_default(),
+    ).await;
+    for comment_id in &outcome.moved_comment_ids {
+        SseBroadcaster::broadcast_comment_deleted(&sse_state, source_id, *comment_id).await;
+    }
+    SseBroadcaster::broadcast_ticket_linked(&sse_state, source_id, new_ticket_id, TicketLinkType::RelatesTo.as_str()).await;
+
+    HttpResponse::Created().json(json!({
+        "success": true,
+        "ticket": outcome.ticket,
+        "moved_comments": outcome.moved_comment_ids.len(),
+    }))
+}
+
 // Add device to ticket
 pub async fn add_device_to_ticket(
     req: HttpRequest,
@@ -1112,7 +1499,6 @@ pub struct BulkActionRequest {
 pub async fn bulk_tickets(
     req: HttpRequest,
     pool: web::Data<crate::db::Pool>,
-    storage: web::Data<std::sync::Arc<dyn crate::utils::storage::Storage>>,
     sse_state: web::Data<crate::handlers::sse::SseState>,
     body: web::Json<BulkActionRequest>,
 ) -> impl Responder {
@@ -1150,9 +1536,10 @@ pub async fn bulk_tickets(
                 }));
             }
 
+            let deleted_by = Uuid::parse_str(&claims.sub).ok();
             let mut deleted = 0;
             for id in ids {
-                match repository::delete_ticket_with_cleanup(&mut conn, *id, storage.as_ref().clone()).await {
+                match repository::delete_ticket(&mut conn, *id, deleted_by) {
                     Ok(rows) => deleted += rows,
                     Err(e) => {
                         error!(ticket_id = id, error = ?e, "Failed to delete ticket");
@@ -1183,7 +1570,18 @@ pub async fn bulk_tickets(
             };
 
             let mut updated = 0;
+            let mut skipped = 0;
             for id in ids {
+                let current = match repository::get_ticket_by_id(&mut conn, *id) {
+                    Ok(ticket) => ticket,
+                    Err(_) => continue,
+                };
+                // Parents with open children are left for a per-ticket close
+                if check_status_change(&mut conn, &current, status, None).is_err() {
+                    skipped += 1;
+                    continue;
+                }
+
                 let update = TicketUpdate {
                     title: None,
                     description: None,
@@ -1213,7 +1611,7 @@ pub async fn bulk_tickets(
                 }
             }
 
-            HttpResponse::Ok().json(json!({ "affected": updated }))
+            HttpResponse::Ok().json(json!({ "affected": updated, "skipped": skipped }))
         }
 
         "set-priority" => {
@@ -460,7 +460,7 @@ async fn main() -> std::io::Result<()> {
 
     // Create uploads directory structure if it doesn't exist
     let uploads_dir = "/app/uploads";
-    let directories = ["", "temp", "tickets", "users", "users/avatars", "users/banners", "users/thumbs"];
+    let directories = ["", "temp", "tickets", "users", "users/avatars", "users/banners", "users/thumbs", "documentation"];
     for dir in directories.iter() {
         let full_path = format!("{}/{}", uploads_dir, dir);
         match std::fs::create_dir_all(&full_path) {
@@ -494,11 +494,30 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Redis pub/sub relay for multi-instance deployments (CLUSTER_ENABLED=true)
+    let cluster_relay = utils::cluster_relay::ClusterRelay::from_env(&redis_cache);
+    if let Some(relay) = &cluster_relay {
+        relay.spawn_subscriber();
+        info!(instance_id = %relay.instance_id(), "Cluster relay enabled for collaboration and SSE");
+    }
+
     // Initialize SSE state for real-time ticket updates (must be created before YjsAppState)
-    let sse_state = web::Data::new(handlers::sse::SseState::new());
+    let sse_state = web::Data::new(match &cluster_relay {
+        Some(relay) => handlers::sse::SseState::with_relay(relay.clone(), pool.clone()),
+        None => handlers::sse::SseState::new(pool.clone()),
+    });
+
+    // Ticket presence, shared by collaboration sessions and the event websocket
+    let presence = web::Data::new(utils::presence::PresenceRegistry::new(cluster_relay.clone()));
 
     // Initialize WebSocket app state for collaborative editing (includes SseState for broadcasting)
-    let yjs_app_state = web::Data::new(handlers::collaboration::YjsAppState::new(web::Data::new(pool.clone()), redis_cache, sse_state.clone()));
+    let yjs_app_state = web::Data::new(handlers::collaboration::YjsAppState::new(
+        web::Data::new(pool.clone()),
+        redis_cache,
+        sse_state.clone(),
+        cluster_relay,
+        presence.clone().into_inner(),
+    ));
 
     // Initialize system state for tracking uptime
     let system_state = web::Data::new(handlers::system::SystemState::new());
@@ -516,6 +535,19 @@ async fn main() -> std::io::Result<()> {
     let storage = create_storage(storage_config);
     let storage_data = web::Data::new(storage.clone());
 
+    // Scheduled backups (backup_schedules); runs are claimed atomically across instances
+    services::backup_schedule::spawn_scheduler(pool.clone(), storage.clone());
+
+    // Permanently delete records that have been in the recycle bin past their retention
+    services::recycle_bin::spawn_purge_job(pool.clone(), storage.clone());
+
+    // Purge closed tickets and security events past RETENTION_* windows (off unless configured)
+    services::privacy::spawn_retention_job(pool.clone(), storage.clone());
+
+    // Search index for the public knowledge base, shared by all workers
+    let public_kb_index = web::Data::new(services::public_kb::PublicKbIndex::new());
+    let kb_suggestion_index = web::Data::new(services::kb_suggestions::KbSuggestionIndex::new());
+
     info!(host = %host, port = %port, environment = %environment, "Server starting");
     
     let server_result = HttpServer::new(move || {
@@ -557,8 +589,11 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(pool.clone()))
             .app_data(yjs_app_state.clone())
             .app_data(sse_state.clone())
+            .app_data(presence.clone())
             .app_data(system_state.clone())
             .app_data(storage_data.clone())
+            .app_data(public_kb_index.clone())
+            .app_data(kb_suggestion_index.clone())
             .app_data(json_config)
             .app_data(multipart_config)
             
@@ -576,6 +611,16 @@ async fn main() -> std::io::Result<()> {
 
             // Public branding config (needed for favicon/logo before login)
             .route("/api/branding", web::get().to(handlers::branding::get_public_branding))
+
+            // Public knowledge base: published public pages only (server-rendered site + JSON API)
+            .route("/kb", web::get().to(handlers::public_kb::kb_home))
+            .route("/kb/sitemap.xml", web::get().to(handlers::public_kb::kb_sitemap))
+            .route("/kb/search", web::get().to(handlers::public_kb::kb_search))
+            .route("/kb/{key}", web::get().to(handlers::public_kb::kb_page))
+            .route("/api/public/kb/tree", web::get().to(handlers::public_kb::get_public_tree))
+            .route("/api/public/kb/pages/{key}", web::get().to(handlers::public_kb::get_public_page))
+            .route("/api/public/kb/search", web::get().to(handlers::public_kb::search_public_pages))
+            .route("/api/public/kb/search-index", web::get().to(handlers::public_kb::get_public_search_index))
             
             // Public WebSocket for collaboration (auth handled in WebSocket handler)
             .service(
@@ -592,6 +637,8 @@ async fn main() -> std::io::Result<()> {
             // Main event stream for all real-time updates (tickets, documentation, devices, etc.)
             .route("/api/events/stream", web::get().to(handlers::sse::ticket_events_stream))
             .route("/api/events/status", web::get().to(handlers::sse::sse_status))
+            // Multiplexed websocket: events, ticket RPC and presence (same token auth as SSE)
+            .route("/api/events/ws", web::get().to(handlers::events_socket::events_socket))
             
             // Authentication routes (public by design)
             .service(
@@ -620,6 +667,9 @@ async fn main() -> std::io::Result<()> {
                     // Invitation routes (public)
                     .route("/invitation/validate", web::post().to(handlers::invitation::validate_invitation))
                     .route("/invitation/accept", web::post().to(handlers::invitation::accept_invitation))
+                    // Approval email link routes (public, token-authenticated)
+                    .route("/approval/validate", web::post().to(handlers::approvals::validate_approval_token))
+                    .route("/approval/decide", web::post().to(handlers::approvals::decide_with_token))
                     .route("/providers", web::get().to(handlers::get_enabled_auth_providers))
                     .route("/oauth/authorize", web::post().to(handlers::oauth_authorize))
                     .route("/oauth/callback", web::get().to(handlers::oauth_callback))
@@ -676,10 +726,33 @@ async fn main() -> std::io::Result<()> {
                     .route("/admin/backup/jobs", web::get().to(handlers::backup::get_jobs))
                     .route("/admin/backup/jobs/{id}", web::get().to(handlers::backup::get_job))
                     .route("/admin/backup/jobs/{id}", web::delete().to(handlers::backup::delete_job))
+                    .route("/admin/backup/jobs/{id}/verify", web::get().to(handlers::backup::verify_backup))
                     .route("/admin/backup/download/{id}", web::get().to(handlers::backup::download_backup))
                     .route("/admin/backup/restore/upload", web::post().to(handlers::backup::upload_restore))
                     .route("/admin/backup/restore/{id}/preview", web::get().to(handlers::backup::preview_restore))
                     .route("/admin/backup/restore/{id}/execute", web::post().to(handlers::backup::execute_restore))
+                    .route("/admin/backup/restore/{id}/selective", web::post().to(handlers::backup::selective_restore))
+                    .route("/admin/backup/schedules", web::get().to(handlers::backup::get_schedules))
+                    .route("/admin/backup/schedules", web::post().to(handlers::backup::create_schedule))
+                    .route("/admin/backup/schedules/{id}", web::put().to(handlers::backup::update_schedule))
+                    .route("/admin/backup/schedules/{id}", web::delete().to(handlers::backup::delete_schedule))
+                    .route("/admin/backup/schedules/{id}/run", web::post().to(handlers::backup::run_schedule))
+
+                    // Bulk import/export (admin only)
+                    .route("/admin/import/jobs", web::get().to(handlers::bulk_import::get_import_jobs))
+                    .route("/admin/import/jobs/{id}", web::get().to(handlers::bulk_import::get_import_job))
+                    .route("/admin/import/{entity}/upload", web::post().to(handlers::bulk_import::upload_import_file))
+                    .route("/admin/import/{entity}/jobs", web::post().to(handlers::bulk_import::start_import))
+                    .route("/admin/export/{entity}", web::get().to(handlers::bulk_import::export_csv))
+
+                    // Recycle bin (admin only)
+                    .route("/admin/recycle-bin", web::get().to(handlers::recycle_bin::get_recycle_bin))
+                    .route("/admin/recycle-bin/{item_type}/{id}/restore", web::post().to(handlers::recycle_bin::restore_item))
+                    .route("/admin/recycle-bin/{item_type}/{id}", web::delete().to(handlers::recycle_bin::purge_item))
+
+                    // Data subject requests (admin only)
+                    .route("/admin/privacy/users/{uuid}/export", web::get().to(handlers::privacy::export_user_data))
+                    .route("/admin/privacy/users/{uuid}/erase", web::post().to(handlers::privacy::erase_user))
 
                     // Microsoft Graph API endpoints
                     .route("/auth/microsoft/graph", web::post().to(handlers::process_graph_request))
@@ -729,6 +802,8 @@ async fn main() -> std::io::Result<()> {
                     .route("/import/json", web::post().to(handlers::import_tickets_from_json_string))
                     .route("/tickets/{ticket_id}/link/{linked_ticket_id}", web::post().to(handlers::link_tickets))
                     .route("/tickets/{ticket_id}/unlink/{linked_ticket_id}", web::delete().to(handlers::unlink_tickets))
+                    .route("/tickets/{ticket_id}/merge/{target_ticket_id}", web::post().to(handlers::merge_ticket))
+                    .route("/tickets/{ticket_id}/split", web::post().to(handlers::split_ticket))
                     .route("/tickets/{ticket_id}/devices/{device_id}", web::post().to(handlers::add_device_to_ticket))
                     .route("/tickets/{ticket_id}/devices/{device_id}", web::delete().to(handlers::remove_device_from_ticket))
                     .route("/tickets/{ticket_id}/comments", web::get().to(handlers::get_comments_by_ticket_id))
@@ -736,12 +811,7 @@ async fn main() -> std::io::Result<()> {
                     .route("/tickets/{ticket_id}/notes/images", web::post().to(handlers::upload_ticket_note_image))
                     .route("/comments/{id}", web::delete().to(handlers::delete_comment))
                     .route("/comments/{comment_id}/attachments", web::post().to(handlers::add_attachment_to_comment))
-                    .route("/attachments/{id}", web::delete().to({
-                        let storage = storage.clone();
-                        move |path, pool| {
-                            handlers::delete_attachment(path, pool, storage.clone())
-                        }
-                    }))
+                    .route("/attachments/{id}", web::delete().to(handlers::delete_attachment))
                     
                     // ===== PROJECT MANAGEMENT =====
                     .route("/projects", web::get().to(handlers::get_all_projects))
@@ -780,6 +850,42 @@ async fn main() -> std::io::Result<()> {
                     .route("/admin/categories/{id}", web::delete().to(handlers::categories::delete_category))
                     .route("/admin/categories/{id}/visibility", web::put().to(handlers::categories::set_category_visibility))
 
+                    // ===== PROBLEM MANAGEMENT =====
+                    .route("/problems", web::get().to(handlers::problems::list_problems))
+                    .route("/problems", web::post().to(handlers::problems::create_problem))
+                    .route("/problems/{id}", web::get().to(handlers::problems::get_problem))
+                    .route("/problems/{id}", web::put().to(handlers::problems::update_problem))
+                    .route("/problems/{id}/incidents/{ticket_id}", web::post().to(handlers::problems::link_problem_incident))
+                    .route("/problems/{id}/incidents/{ticket_id}", web::delete().to(handlers::problems::unlink_problem_incident))
+
+                    // ===== CHANGE MANAGEMENT =====
+                    .route("/changes", web::get().to(handlers::changes::list_changes))
+                    .route("/changes", web::post().to(handlers::changes::create_change))
+                    .route("/changes/{id}", web::get().to(handlers::changes::get_change))
+                    .route("/changes/{id}", web::put().to(handlers::changes::update_change))
+                    .route("/changes/{id}/submit", web::post().to(handlers::changes::submit_change))
+                    .route("/changes/{id}/approve", web::post().to(handlers::changes::approve_change))
+                    .route("/changes/{id}/reject", web::post().to(handlers::changes::reject_change))
+
+                    // ===== APPROVAL WORKFLOWS =====
+                    .route("/admin/categories/{id}/approval-chain", web::get().to(handlers::approvals::get_approval_chain))
+                    .route("/admin/categories/{id}/approval-chain", web::put().to(handlers::approvals::save_approval_chain))
+                    .route("/admin/categories/{id}/approval-chain", web::delete().to(handlers::approvals::delete_approval_chain))
+                    .route("/tickets/{id}/approvals", web::get().to(handlers::approvals::get_ticket_approvals))
+                    .route("/tickets/{id}/approvals/{approval_id}/approve", web::post().to(handlers::approvals::approve_ticket_step))
+                    .route("/tickets/{id}/approvals/{approval_id}/reject", web::post().to(handlers::approvals::reject_ticket_step))
+                    .route("/approvals/pending", web::get().to(handlers::approvals::get_my_pending_approvals))
+
+                    // ===== SERVICE CATALOG =====
+                    .route("/catalog", web::get().to(handlers::catalog::get_catalog))
+                    .route("/catalog/{id}", web::get().to(handlers::catalog::get_catalog_item))
+                    .route("/catalog/{id}/submit", web::post().to(handlers::catalog::submit_catalog_item))
+                    .route("/tickets/{id}/catalog-submission", web::get().to(handlers::catalog::get_ticket_catalog_submission))
+                    .route("/admin/catalog", web::get().to(handlers::catalog::get_all_catalog_items_admin))
+                    .route("/admin/catalog", web::post().to(handlers::catalog::create_catalog_item))
+                    .route("/admin/catalog/{id}", web::put().to(handlers::catalog::update_catalog_item))
+                    .route("/admin/catalog/{id}", web::delete().to(handlers::catalog::delete_catalog_item))
+
                     // ===== ASSIGNMENT RULES MANAGEMENT =====
                     .route("/admin/assignment-rules", web::get().to(handlers::assignment_rules::get_all_rules))
                     .route("/admin/assignment-rules", web::post().to(handlers::assignment_rules::create_rule))
@@ -829,6 +935,11 @@ async fn main() -> std::io::Result<()> {
                     // ===== DOCUMENTATION SYSTEM =====
                     .route("/documentation/pages", web::get().to(handlers::get_documentation_pages))
                     .route("/documentation/pages/export", web::get().to(handlers::export_documentation_pages))
+                    .route("/documentation/export", web::get().to(handlers::export_knowledge_base))
+                    .route("/documentation/import", web::post().to(handlers::import_documentation))
+                    .route("/documentation/templates", web::get().to(handlers::get_documentation_templates))
+                    .route("/documentation/templates/{id}/instantiate", web::post().to(handlers::instantiate_documentation_template))
+                    .route("/documentation/pages/{id}/export", web::get().to(handlers::export_documentation_page))
                     .route("/documentation/pages", web::post().to(handlers::create_documentation_page))
                     .route("/documentation/pages/{id}", web::get().to(handlers::get_documentation_page))
                     .route("/documentation/pages/{id}", web::put().to(handlers::update_documentation_page))
@@ -844,6 +955,10 @@ async fn main() -> std::io::Result<()> {
                     .route("/documentation/pages/reorder", web::post().to(handlers::reorder_pages))
                     .route("/documentation/pages/move", web::post().to(handlers::move_page_to_parent))
                     .route("/tickets/{ticket_id}/documentation", web::get().to(handlers::get_documentation_pages_by_ticket_id))
+                    .route("/tickets/{id}/kb-suggestions", web::get().to(handlers::kb_suggestions::suggest_for_ticket))
+                    .route("/kb/suggestions", web::post().to(handlers::kb_suggestions::suggest_for_draft))
+                    .route("/kb/suggestions/clicks", web::post().to(handlers::kb_suggestions::record_suggestion_click))
+                    .route("/kb/suggestions/stats", web::get().to(handlers::kb_suggestions::get_suggestion_stats))
                     .route("/tickets/{ticket_id}/documentation/create", web::post().to(handlers::create_documentation_page_from_ticket))
                     .route("/documentation/{id}", web::put().to(handlers::update_documentation_page))
                     .route("/documentation/{id}", web::delete().to(handlers::delete_documentation_page))
@@ -852,6 +967,7 @@ async fn main() -> std::io::Result<()> {
             // Unified file serving using storage abstraction (protected routes)
             .route("/uploads/tickets/{path:.*}", web::get().to(handlers::serve_protected_file))
             .route("/uploads/temp/{path:.*}", web::get().to(handlers::serve_protected_file))
+            .route("/uploads/documentation/{path:.*}", web::get().to(handlers::serve_documentation_file))
             
             // === FRONTEND STATIC FILES ===
             // Serve static frontend files with SPA fallback using default_handler
@@ -117,6 +117,11 @@ pub struct Ticket {
     pub closed_at: Option<NaiveDateTime>,
     pub closed_by: Option<Uuid>,
     pub category_id: Option<i32>,
+    pub merged_into_id: Option<i32>,
+    pub ticket_type: String,
+    pub approval_status: Option<String>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
 }
 
 // Ticket implementation removed - serialization now handled by serde attributes
@@ -174,6 +179,8 @@ pub struct Device {
     pub os_version: Option<String>,
     pub is_managed: Option<bool>,
     pub enrollment_date: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable, AsChangeset)]
@@ -200,7 +207,7 @@ pub struct NewDevice {
     pub enrollment_date: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize, AsChangeset)]
+#[derive(Debug, Default, Serialize, Deserialize, AsChangeset)]
 #[diesel(table_name = crate::schema::devices)]
 pub struct DeviceUpdate {
     pub name: Option<String>,
@@ -257,6 +264,8 @@ pub struct Comment {
     pub updated_at: NaiveDateTime,
     pub is_edited: bool,
     pub edit_count: i32,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -281,6 +290,8 @@ pub struct Attachment {
     pub uploaded_by: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub transcription: Option<String>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable, AsChangeset)]
@@ -378,6 +389,7 @@ pub struct CompleteTicket {
     pub comments: Vec<CommentWithAttachments>,
     pub article_content: Option<String>,
     pub linked_tickets: Vec<i32>,
+    pub relationships: TicketRelationships,
     pub projects: Vec<Project>,
 }
 
@@ -508,6 +520,20 @@ pub struct DocumentationPage {
     pub yjs_document: Option<Vec<u8>>,
     pub yjs_client_id: Option<i64>,
     pub has_unsaved_changes: bool,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
+}
+
+// Documentation page as listed on the public knowledge base (no content or internal fields)
+#[derive(Debug, Serialize, Clone, Queryable)]
+pub struct PublicDocumentationPage {
+    pub id: i32,
+    pub title: String,
+    pub slug: Option<String>,
+    pub icon: Option<String>,
+    pub parent_id: Option<i32>,
+    pub display_order: Option<i32>,
+    pub updated_at: chrono::NaiveDateTime,
 }
 
 // Documentation Page with Children
@@ -830,6 +856,8 @@ pub struct Project {
     pub updated_at: NaiveDateTime,
     pub created_by: Option<Uuid>,
     pub owner_uuid: Option<Uuid>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub deleted_by: Option<Uuid>,
 }
 
 // New Project for creating projects
@@ -909,6 +937,97 @@ pub struct LinkedTicket {
 pub struct NewLinkedTicket {
     pub ticket_id: i32,
     pub linked_ticket_id: i32,
+    pub link_type: String,
+    pub created_by: Option<Uuid>,
+}
+
+/// Relationship types stored in `linked_tickets.link_type`
+///
+/// Every relationship is stored as two rows, one per direction; the row owned by the
+/// other ticket carries the inverse type (e.g. `parent_of` on the parent, `child_of` on the child).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketLinkType {
+    RelatesTo,
+    ParentOf,
+    ChildOf,
+    Blocks,
+    BlockedBy,
+    DuplicateOf,
+    DuplicatedBy,
+    CausedBy,
+    Causes,
+}
+
+impl TicketLinkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RelatesTo => "relates_to",
+            Self::ParentOf => "parent_of",
+            Self::ChildOf => "child_of",
+            Self::Blocks => "blocks",
+            Self::BlockedBy => "blocked_by",
+            Self::DuplicateOf => "duplicate_of",
+            Self::DuplicatedBy => "duplicated_by",
+            Self::CausedBy => "caused_by",
+            Self::Causes => "causes",
+        }
+    }
+
+    /// The type stored on the row seen from the other ticket
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::RelatesTo => Self::RelatesTo,
+            Self::ParentOf => Self::ChildOf,
+            Self::ChildOf => Self::ParentOf,
+            Self::Blocks => Self::BlockedBy,
+            Self::BlockedBy => Self::Blocks,
+            Self::DuplicateOf => Self::DuplicatedBy,
+            Self::DuplicatedBy => Self::DuplicateOf,
+            Self::CausedBy => Self::Causes,
+            Self::Causes => Self::CausedBy,
+        }
+    }
+}
+
+impl std::fmt::Display for TicketLinkType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for TicketLinkType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            // "duplicates" was listed in the original schema comment
+            "relates_to" => Ok(Self::RelatesTo),
+            "parent_of" => Ok(Self::ParentOf),
+            "child_of" => Ok(Self::ChildOf),
+            "blocks" => Ok(Self::Blocks),
+            "blocked_by" => Ok(Self::BlockedBy),
+            "duplicate_of" | "duplicates" => Ok(Self::DuplicateOf),
+            "duplicated_by" => Ok(Self::DuplicatedBy),
+            "caused_by" => Ok(Self::CausedBy),
+            "causes" => Ok(Self::Causes),
+            _ => Err(format!("Invalid ticket link type: {}", s)),
+        }
+    }
+}
+
+/// Relationships of a ticket grouped by type, as seen from that ticket
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TicketRelationships {
+    pub related: Vec<i32>,
+    pub parents: Vec<i32>,
+    pub children: Vec<i32>,
+    pub blocks: Vec<i32>,
+    pub blocked_by: Vec<i32>,
+    pub duplicate_of: Vec<i32>,
+    pub duplicates: Vec<i32>,
+    pub caused_by: Vec<i32>,
+    pub causes: Vec<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1243,6 +1362,7 @@ pub struct DocumentationRevision {
     pub created_at: chrono::NaiveDateTime,
     pub created_by: Uuid,
     pub change_summary: Option<String>,
+    pub contributed_by: Vec<Option<Uuid>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -2017,6 +2137,11 @@ pub struct BackupJob {
     pub created_by: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub completed_at: Option<NaiveDateTime>,
+    pub schedule_id: Option<i32>,
+    pub remote_path: Option<String>,
+    pub progress_stage: Option<String>,
+    pub progress_current: i64,
+    pub progress_total: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -2026,6 +2151,7 @@ pub struct NewBackupJob {
     pub status: String,
     pub include_sensitive: bool,
     pub created_by: Option<Uuid>,
+    pub schedule_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, AsChangeset)]
@@ -2051,6 +2177,11 @@ pub struct BackupJobResponse {
     pub created_by: Option<String>,
     pub created_at: NaiveDateTime,
     pub completed_at: Option<NaiveDateTime>,
+    pub schedule_id: Option<i32>,
+    pub remote_path: Option<String>,
+    pub progress_stage: Option<String>,
+    pub progress_current: i64,
+    pub progress_total: Option<i64>,
 }
 
 impl From<BackupJob> for BackupJobResponse {
@@ -2066,6 +2197,11 @@ impl From<BackupJob> for BackupJobResponse {
             created_by: job.created_by.map(|u| u.to_string()),
             created_at: job.created_at,
             completed_at: job.completed_at,
+            schedule_id: job.schedule_id,
+            remote_path: job.remote_path,
+            progress_stage: job.progress_stage,
+            progress_current: job.progress_current,
+            progress_total: job.progress_total,
         }
     }
 }
@@ -2077,6 +2213,78 @@ pub struct StartBackupExportRequest {
     pub password: Option<String>,
 }
 
+// Scheduled backup policy
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::backup_schedules)]
+pub struct BackupSchedule {
+    pub id: i32,
+    pub name: String,
+    /// Five-field cron expression, evaluated in UTC
+    pub cron_expression: String,
+    pub include_sensitive: bool,
+    /// Environment variable holding the archive password
+    pub password_env: Option<String>,
+    /// File holding the archive password (e.g. a Docker secret)
+    pub password_file: Option<String>,
+    pub keep_daily: i32,
+    pub keep_weekly: i32,
+    /// Copy finished archives to the BACKUP_S3_* target
+    pub upload_remote: bool,
+    pub enabled: bool,
+    pub last_run_at: Option<NaiveDateTime>,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::backup_schedules)]
+pub struct NewBackupSchedule {
+    pub name: String,
+    pub cron_expression: String,
+    pub include_sensitive: bool,
+    pub password_env: Option<String>,
+    pub password_file: Option<String>,
+    pub keep_daily: i32,
+    pub keep_weekly: i32,
+    pub upload_remote: bool,
+    pub enabled: bool,
+    pub next_run_at: Option<NaiveDateTime>,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = crate::schema::backup_schedules)]
+pub struct BackupScheduleUpdate {
+    pub name: Option<String>,
+    pub cron_expression: Option<String>,
+    pub include_sensitive: Option<bool>,
+    pub password_env: Option<Option<String>>,
+    pub password_file: Option<Option<String>>,
+    pub keep_daily: Option<i32>,
+    pub keep_weekly: Option<i32>,
+    pub upload_remote: Option<bool>,
+    pub enabled: Option<bool>,
+    pub next_run_at: Option<Option<NaiveDateTime>>,
+}
+
+// Request to create or replace a backup schedule
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupScheduleRequest {
+    pub name: String,
+    pub cron_expression: String,
+    #[serde(default)]
+    pub include_sensitive: bool,
+    pub password_env: Option<String>,
+    pub password_file: Option<String>,
+    pub keep_daily: Option<i32>,
+    pub keep_weekly: Option<i32>,
+    #[serde(default)]
+    pub upload_remote: bool,
+    pub enabled: Option<bool>,
+}
+
 // Request to execute a restore
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExecuteRestoreRequest {
@@ -2093,6 +2301,15 @@ pub struct BackupManifest {
     pub tables: std::collections::HashMap<String, TableManifest>,
     pub files: FilesManifest,
     pub encryption: Option<EncryptionManifest>,
+    /// Newest schema migration applied when the backup was taken
+    #[serde(default)]
+    pub schema_version: Option<String>,
+    /// SHA-256 (hex) of each data and file entry, keyed by archive path (format 2.1+)
+    #[serde(default)]
+    pub checksums: std::collections::BTreeMap<String, String>,
+    /// Signature over the rest of the manifest, when a signing key is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ManifestSignature>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -2114,12 +2331,101 @@ pub struct EncryptionManifest {
     pub nonce: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestSignature {
+    /// `HMAC-SHA256` or `Ed25519`
+    pub algorithm: String,
+    /// Hex-encoded signature or tag
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Valid,
+    Invalid,
+    #[default]
+    Unsigned,
+    /// Signed, but no key to check the signature with is configured
+    Unverifiable,
+}
+
+// Result of checking a backup archive against its manifest
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub valid: bool,
+    pub entries_checked: usize,
+    pub signature: SignatureStatus,
+    /// Reasons the archive failed verification
+    pub problems: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
 // Restore preview response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RestorePreview {
     pub manifest: BackupManifest,
     pub has_encrypted_sensitive: bool,
     pub warnings: Vec<String>,
+    #[serde(default)]
+    pub entities: RestorableEntities,
+    #[serde(default)]
+    pub verification: BackupVerification,
+}
+
+// Tickets and documentation pages that can be restored individually
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RestorableEntities {
+    pub tickets: Vec<RestorableTicket>,
+    pub documentation_pages: Vec<RestorablePage>,
+    /// The lists are capped (newest first); true if entries were left out
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestorableTicket {
+    pub id: i32,
+    pub title: String,
+    pub status: Option<String>,
+    pub updated_at: Option<String>,
+    /// The id is taken in the current database, so a restore would get a new id
+    pub id_in_use: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestorablePage {
+    pub id: i32,
+    pub title: String,
+    pub parent_id: Option<i32>,
+    pub id_in_use: bool,
+}
+
+// Request to restore individual records from a backup
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelectiveRestoreRequest {
+    #[serde(default)]
+    pub tickets: Vec<i32>,
+    #[serde(default)]
+    pub documentation_pages: Vec<i32>,
+    /// Restore the pages below each selected page too (default true)
+    pub include_subpages: Option<bool>,
+}
+
+// An entity restored by a selective restore; `restored_id` differs when the id was taken
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoredEntity {
+    pub original_id: i32,
+    pub restored_id: i32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SelectiveRestoreReport {
+    pub tickets: Vec<RestoredEntity>,
+    pub documentation_pages: Vec<RestoredEntity>,
+    /// Rows restored per table
+    pub rows: std::collections::BTreeMap<String, usize>,
+    pub files_restored: u64,
+    pub warnings: Vec<String>,
 }
 
 // ============================================================================
@@ -2275,6 +2581,8 @@ pub struct TicketCategory {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub created_by: Option<Uuid>,
+    /// Template page used for documentation created from this category's tickets
+    pub documentation_template_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -2287,6 +2595,7 @@ pub struct NewTicketCategory {
     pub display_order: i32,
     pub is_active: bool,
     pub created_by: Option<Uuid>,
+    pub documentation_template_id: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, AsChangeset)]
@@ -2299,6 +2608,7 @@ pub struct TicketCategoryUpdate {
     pub display_order: Option<i32>,
     pub is_active: Option<bool>,
     pub updated_at: Option<NaiveDateTime>,
+    pub documentation_template_id: Option<Option<i32>>,
 }
 
 // Category with visibility information for admin views
@@ -2536,4 +2846,763 @@ pub struct AssignmentResult {
     pub rule_name: String,
     pub assigned_user_uuid: Option<Uuid>,
     pub method: AssignmentMethod,
-}
\ No newline at end of file
+}
+// ============================================================================
+// Problem & Change Management
+// ============================================================================
+
+/// Kind of work item stored in `tickets.ticket_type`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketType {
+    Incident,
+    Problem,
+    Change,
+    /// Submitted through a service catalog form
+    ServiceRequest,
+}
+
+impl TicketType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Incident => "incident",
+            Self::Problem => "problem",
+            Self::Change => "change",
+            Self::ServiceRequest => "service_request",
+        }
+    }
+}
+
+impl std::fmt::Display for TicketType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for TicketType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "incident" => Ok(Self::Incident),
+            "problem" => Ok(Self::Problem),
+            "change" => Ok(Self::Change),
+            "service_request" => Ok(Self::ServiceRequest),
+            _ => Err(format!("Invalid ticket type: {}", s)),
+        }
+    }
+}
+
+/// Risk level of a change request
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeRiskLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl ChangeRiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for ChangeRiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(format!("Invalid risk level: {}", s)),
+        }
+    }
+}
+
+/// Approval state of a change request
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeApprovalStatus {
+    Draft,
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ChangeApprovalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::str::FromStr for ChangeApprovalStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(Self::Draft),
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            _ => Err(format!("Invalid approval status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::problem_details)]
+#[diesel(primary_key(ticket_id))]
+pub struct ProblemDetails {
+    pub ticket_id: i32,
+    pub root_cause: Option<String>,
+    pub workaround: Option<String>,
+    pub is_known_error: bool,
+    pub known_error_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::problem_details)]
+pub struct NewProblemDetails {
+    pub ticket_id: i32,
+    pub root_cause: Option<String>,
+    pub workaround: Option<String>,
+    pub is_known_error: bool,
+    pub known_error_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = crate::schema::problem_details)]
+pub struct ProblemDetailsUpdate {
+    pub root_cause: Option<Option<String>>,
+    pub workaround: Option<Option<String>>,
+    pub is_known_error: Option<bool>,
+    pub known_error_at: Option<Option<NaiveDateTime>>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::change_details)]
+#[diesel(primary_key(ticket_id))]
+pub struct ChangeDetails {
+    pub ticket_id: i32,
+    pub risk_level: String,
+    pub planned_start: Option<NaiveDateTime>,
+    pub planned_end: Option<NaiveDateTime>,
+    pub implementation_plan: Option<String>,
+    pub backout_plan: Option<String>,
+    pub approval_status: String,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<NaiveDateTime>,
+    pub approval_note: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::change_details)]
+pub struct NewChangeDetails {
+    pub ticket_id: i32,
+    pub risk_level: String,
+    pub planned_start: Option<NaiveDateTime>,
+    pub planned_end: Option<NaiveDateTime>,
+    pub implementation_plan: Option<String>,
+    pub backout_plan: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = crate::schema::change_details)]
+pub struct ChangeDetailsUpdate {
+    pub risk_level: Option<String>,
+    pub planned_start: Option<Option<NaiveDateTime>>,
+    pub planned_end: Option<Option<NaiveDateTime>>,
+    pub implementation_plan: Option<Option<String>>,
+    pub backout_plan: Option<Option<String>>,
+    pub approval_status: Option<String>,
+    pub approved_by: Option<Option<Uuid>>,
+    pub approved_at: Option<Option<NaiveDateTime>>,
+    pub approval_note: Option<Option<String>>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// Problem ticket with its details and the incidents linked to it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProblemRecord {
+    pub ticket: Ticket,
+    pub details: ProblemDetails,
+    pub incidents: Vec<Ticket>,
+    pub changes: Vec<Ticket>,
+}
+
+/// Problem list entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProblemListItem {
+    pub ticket: Ticket,
+    pub details: ProblemDetails,
+    pub incident_count: i64,
+}
+
+/// Change ticket with its details, the devices it touches and related records
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub ticket: Ticket,
+    pub details: ChangeDetails,
+    pub devices: Vec<Device>,
+    pub projects: Vec<Project>,
+    pub problems: Vec<Ticket>,
+}
+
+/// Change list entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeListItem {
+    pub ticket: Ticket,
+    pub details: ChangeDetails,
+    pub device_count: i64,
+}
+
+// ============================================================================
+// Approval Workflows
+// ============================================================================
+
+/// Who approves a chain step
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApproverType {
+    /// A specific user
+    User,
+    /// The requester's manager from Microsoft Graph
+    Manager,
+    /// Any member of a group
+    Group,
+}
+
+impl ApproverType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Manager => "manager",
+            Self::Group => "group",
+        }
+    }
+}
+
+impl std::str::FromStr for ApproverType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Self::User),
+            "manager" => Ok(Self::Manager),
+            "group" => Ok(Self::Group),
+            _ => Err(format!("Invalid approver type: {}", s)),
+        }
+    }
+}
+
+/// State of a single approval step on a ticket
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStepStatus {
+    /// Earlier steps have not been approved yet
+    Waiting,
+    /// Awaiting a decision from this step's approvers
+    Pending,
+    Approved,
+    Rejected,
+    /// Not needed because an earlier step rejected
+    Skipped,
+    /// Withdrawn because the ticket left the category
+    Cancelled,
+}
+
+impl ApprovalStepStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Skipped => "skipped",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Approval state stored on `tickets.approval_status`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl TicketApprovalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::approval_chains)]
+pub struct ApprovalChain {
+    pub id: i32,
+    pub category_id: i32,
+    pub name: String,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::approval_chains)]
+pub struct NewApprovalChain {
+    pub category_id: i32,
+    pub name: String,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Associations, Clone)]
+#[diesel(table_name = crate::schema::approval_chain_steps)]
+#[diesel(belongs_to(ApprovalChain, foreign_key = chain_id))]
+pub struct ApprovalChainStep {
+    pub id: i32,
+    pub chain_id: i32,
+    pub step_order: i32,
+    pub approver_type: String,
+    pub approver_user_uuid: Option<Uuid>,
+    pub approver_group_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::approval_chain_steps)]
+pub struct NewApprovalChainStep {
+    pub chain_id: i32,
+    pub step_order: i32,
+    pub approver_type: String,
+    pub approver_user_uuid: Option<Uuid>,
+    pub approver_group_id: Option<i32>,
+}
+
+/// Approval chain with its ordered steps
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalChainWithSteps {
+    #[serde(flatten)]
+    pub chain: ApprovalChain,
+    pub steps: Vec<ApprovalChainStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::ticket_approvals)]
+pub struct TicketApproval {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub step_order: i32,
+    pub approver_type: String,
+    pub approver_user_uuid: Option<Uuid>,
+    pub approver_group_id: Option<i32>,
+    pub status: String,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub comment: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::ticket_approvals)]
+pub struct NewTicketApproval {
+    pub ticket_id: i32,
+    pub step_order: i32,
+    pub approver_type: String,
+    pub approver_user_uuid: Option<Uuid>,
+    pub approver_group_id: Option<i32>,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::ticket_approval_events)]
+pub struct TicketApprovalEvent {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub approval_id: Option<i32>,
+    pub action: String,
+    pub actor_uuid: Option<Uuid>,
+    pub channel: String,
+    pub comment: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::ticket_approval_events)]
+pub struct NewTicketApprovalEvent {
+    pub ticket_id: i32,
+    pub approval_id: Option<i32>,
+    pub action: String,
+    pub actor_uuid: Option<Uuid>,
+    pub channel: String,
+    pub comment: Option<String>,
+}
+
+/// Approvals and audit trail of a ticket
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TicketApprovalSummary {
+    pub ticket_id: i32,
+    pub approval_status: Option<String>,
+    pub approvals: Vec<TicketApproval>,
+    pub events: Vec<TicketApprovalEvent>,
+}
+
+// ============================================================================
+// Service Catalog
+// ============================================================================
+
+/// Input type of a catalog form field
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FormFieldType {
+    Text,
+    Textarea,
+    Number,
+    Checkbox,
+    Date,
+    Select,
+    MultiSelect,
+    /// One or more device ids; the devices are linked to the created ticket
+    Device,
+    /// A user UUID
+    User,
+}
+
+/// Show a field only when another field's answer matches
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldCondition {
+    /// Key of the controlling field
+    pub field: String,
+    /// Visible when the answer equals this value
+    #[serde(default)]
+    pub equals: Option<serde_json::Value>,
+    /// Visible when the answer is any of these values
+    #[serde(default)]
+    pub one_of: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FormField {
+    /// Answer key, unique within the form
+    pub key: String,
+    pub label: String,
+    #[serde(rename = "type")]
+    pub field_type: FormFieldType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub help_text: Option<String>,
+    /// Choices for select and multi_select fields
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub visible_when: Option<FieldCondition>,
+}
+
+/// Form definition stored in `catalog_items.form_schema`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct FormSchema {
+    #[serde(default)]
+    pub fields: Vec<FormField>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::catalog_items)]
+pub struct CatalogItem {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub category_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub form_schema: serde_json::Value,
+    pub title_template: Option<String>,
+    pub default_priority: TicketPriority,
+    pub link_requester_devices: bool,
+    pub display_order: i32,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub created_by: Option<Uuid>,
+}
+
+impl CatalogItem {
+    /// Parse the stored form schema
+    pub fn schema(&self) -> Result<FormSchema, serde_json::Error> {
+        serde_json::from_value(self.form_schema.clone())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::catalog_items)]
+pub struct NewCatalogItem {
+    pub category_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub form_schema: serde_json::Value,
+    pub title_template: Option<String>,
+    pub default_priority: TicketPriority,
+    pub link_requester_devices: bool,
+    pub display_order: i32,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, AsChangeset, Default)]
+#[diesel(table_name = crate::schema::catalog_items)]
+pub struct CatalogItemUpdate {
+    pub category_id: Option<i32>,
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub icon: Option<Option<String>>,
+    pub form_schema: Option<serde_json::Value>,
+    pub title_template: Option<Option<String>>,
+    pub default_priority: Option<TicketPriority>,
+    pub link_requester_devices: Option<bool>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::catalog_submissions)]
+pub struct CatalogSubmission {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub catalog_item_id: Option<i32>,
+    pub form_schema: serde_json::Value,
+    pub answers: serde_json::Value,
+    pub submitted_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::catalog_submissions)]
+pub struct NewCatalogSubmission {
+    pub ticket_id: i32,
+    pub catalog_item_id: Option<i32>,
+    pub form_schema: serde_json::Value,
+    pub answers: serde_json::Value,
+    pub submitted_by: Option<Uuid>,
+}
+
+// ============================================================================
+// Knowledge Base Suggestions
+// ============================================================================
+
+/// Where a suggested article was shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KbSuggestionSource {
+    /// Suggested while the requester was filling in a new ticket
+    PreSubmit,
+    /// Suggested on an existing ticket
+    Ticket,
+}
+
+impl KbSuggestionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KbSuggestionSource::PreSubmit => "pre_submit",
+            KbSuggestionSource::Ticket => "ticket",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable, Clone)]
+#[diesel(table_name = crate::schema::kb_suggestion_clicks)]
+pub struct KbSuggestionClick {
+    pub id: i32,
+    pub page_id: i32,
+    pub user_uuid: Option<Uuid>,
+    pub ticket_id: Option<i32>,
+    pub source: String,
+    pub query: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = crate::schema::kb_suggestion_clicks)]
+pub struct NewKbSuggestionClick {
+    pub page_id: i32,
+    pub user_uuid: Option<Uuid>,
+    pub ticket_id: Option<i32>,
+    pub source: String,
+    pub query: Option<String>,
+}
+
+// ============================================================================
+// Recycle Bin
+// ============================================================================
+
+/// Kind of soft-deleted record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecycleBinItemType {
+    Ticket,
+    Comment,
+    Attachment,
+    Device,
+    Project,
+    DocumentationPage,
+}
+
+impl RecycleBinItemType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ticket" => Some(RecycleBinItemType::Ticket),
+            "comment" => Some(RecycleBinItemType::Comment),
+            "attachment" => Some(RecycleBinItemType::Attachment),
+            "device" => Some(RecycleBinItemType::Device),
+            "project" => Some(RecycleBinItemType::Project),
+            "documentation_page" => Some(RecycleBinItemType::DocumentationPage),
+            _ => None,
+        }
+    }
+}
+
+/// A soft-deleted record as listed in the recycle bin
+#[derive(Debug, Serialize)]
+pub struct RecycleBinItem {
+    pub item_type: RecycleBinItemType,
+    pub id: i32,
+    /// Title, name or an excerpt of the content
+    pub title: String,
+    /// Ticket a comment or attachment belongs to
+    pub ticket_id: Option<i32>,
+    pub deleted_at: NaiveDateTime,
+    #[serde(serialize_with = "serialize_optional_uuid_as_string")]
+    pub deleted_by: Option<Uuid>,
+    /// When the purge job will remove it for good
+    pub purge_at: NaiveDateTime,
+}
+
+// ============================================================================
+// Bulk Import Jobs
+// ============================================================================
+
+/// Kind of record a bulk import or export works on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportEntityType {
+    Tickets,
+    Users,
+    Devices,
+}
+
+impl ImportEntityType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tickets" => Some(ImportEntityType::Tickets),
+            "users" => Some(ImportEntityType::Users),
+            "devices" => Some(ImportEntityType::Devices),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportEntityType::Tickets => "tickets",
+            ImportEntityType::Users => "users",
+            ImportEntityType::Devices => "devices",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Identifiable, Queryable)]
+#[diesel(table_name = crate::schema::import_jobs)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub entity_type: String,
+    pub status: String,
+    pub dry_run: bool,
+    /// Upload returned by the preview step
+    pub file_id: String,
+    /// Target field -> source column header
+    pub mapping: serde_json::Value,
+    pub progress_current: i64,
+    pub progress_total: Option<i64>,
+    pub created_count: i32,
+    pub updated_count: i32,
+    pub failed_count: i32,
+    /// Row-level errors (`ImportRowError`)
+    pub errors: serde_json::Value,
+    pub error_message: Option<String>,
+    #[serde(serialize_with = "serialize_optional_uuid_as_string")]
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crate::schema::import_jobs)]
+pub struct NewImportJob {
+    pub entity_type: String,
+    pub status: String,
+    pub dry_run: bool,
+    pub file_id: String,
+    pub mapping: serde_json::Value,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Default, AsChangeset)]
+#[diesel(table_name = crate::schema::import_jobs)]
+pub struct ImportJobUpdate {
+    pub status: Option<String>,
+    pub progress_current: Option<i64>,
+    pub progress_total: Option<i64>,
+    pub created_count: Option<i32>,
+    pub updated_count: Option<i32>,
+    pub failed_count: Option<i32>,
+    pub errors: Option<serde_json::Value>,
+    pub error_message: Option<String>,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+/// A problem with one row of an import file; `row` is 1-based and counts the header row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+// Request to start an import from a previously uploaded file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StartImportRequest {
+    /// Returned by the preview upload
+    pub file_id: String,
+    /// Target field -> source column header
+    pub mapping: std::collections::HashMap<String, String>,
+    /// Validate every row without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
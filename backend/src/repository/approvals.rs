@@ -0,0 +1,213 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+// ============================================================================
+// Approval Chains
+// ============================================================================
+
+/// Get the approval chain configured for a category, with its ordered steps
+pub fn get_chain_for_category(
+    conn: &mut DbConnection,
+    category_id: i32,
+) -> QueryResult<Option<ApprovalChainWithSteps>> {
+    let chain = approval_chains::table
+        .filter(approval_chains::category_id.eq(category_id))
+        .first::<ApprovalChain>(conn)
+        .optional()?;
+
+    let Some(chain) = chain else {
+        return Ok(None);
+    };
+
+    let steps = ApprovalChainStep::belonging_to(&chain)
+        .order(approval_chain_steps::step_order.asc())
+        .load::<ApprovalChainStep>(conn)?;
+
+    Ok(Some(ApprovalChainWithSteps { chain, steps }))
+}
+
+/// Create or replace the approval chain of a category
+/// Steps are renumbered in the given order.
+pub fn save_chain(
+    conn: &mut DbConnection,
+    new_chain: NewApprovalChain,
+    steps: Vec<NewApprovalChainStep>,
+) -> QueryResult<ApprovalChainWithSteps> {
+    conn.transaction(|conn| {
+        let chain = diesel::insert_into(approval_chains::table)
+            .values(&new_chain)
+            .on_conflict(approval_chains::category_id)
+            .do_update()
+            .set((
+                approval_chains::name.eq(&new_chain.name),
+                approval_chains::is_active.eq(new_chain.is_active),
+                approval_chains::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .get_result::<ApprovalChain>(conn)?;
+
+        diesel::delete(approval_chain_steps::table.filter(approval_chain_steps::chain_id.eq(chain.id)))
+            .execute(conn)?;
+
+        let steps: Vec<NewApprovalChainStep> = steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| NewApprovalChainStep {
+                chain_id: chain.id,
+                step_order: index as i32 + 1,
+                ..step
+            })
+            .collect();
+
+        let steps = diesel::insert_into(approval_chain_steps::table)
+            .values(&steps)
+            .get_results::<ApprovalChainStep>(conn)?;
+
+        Ok(ApprovalChainWithSteps { chain, steps })
+    })
+}
+
+/// Remove the approval chain of a category
+pub fn delete_chain_for_category(conn: &mut DbConnection, category_id: i32) -> QueryResult<usize> {
+    diesel::delete(approval_chains::table.filter(approval_chains::category_id.eq(category_id)))
+        .execute(conn)
+}
+
+// ============================================================================
+// Ticket Approvals
+// ============================================================================
+
+/// Create the approval steps of a ticket
+pub fn create_ticket_approvals(
+    conn: &mut DbConnection,
+    approvals: Vec<NewTicketApproval>,
+) -> QueryResult<Vec<TicketApproval>> {
+    diesel::insert_into(ticket_approvals::table)
+        .values(&approvals)
+        .get_results(conn)
+}
+
+/// Get a single approval step
+pub fn get_ticket_approval(conn: &mut DbConnection, approval_id: i32) -> QueryResult<TicketApproval> {
+    ticket_approvals::table.find(approval_id).first(conn)
+}
+
+/// Get all approval steps of a ticket in order, oldest rounds first
+pub fn get_ticket_approvals(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<TicketApproval>> {
+    ticket_approvals::table
+        .filter(ticket_approvals::ticket_id.eq(ticket_id))
+        .order((ticket_approvals::created_at.asc(), ticket_approvals::step_order.asc(), ticket_approvals::id.asc()))
+        .load(conn)
+}
+
+/// Steps of a ticket that are still waiting or pending
+pub fn get_open_ticket_approvals(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<TicketApproval>> {
+    ticket_approvals::table
+        .filter(ticket_approvals::ticket_id.eq(ticket_id))
+        .filter(ticket_approvals::status.eq_any([
+            ApprovalStepStatus::Waiting.as_str(),
+            ApprovalStepStatus::Pending.as_str(),
+        ]))
+        .order((ticket_approvals::step_order.asc(), ticket_approvals::id.asc()))
+        .load(conn)
+}
+
+/// Record a decision on an approval step
+/// Only a step that is still pending is updated; otherwise this returns `NotFound`, so two
+/// concurrent decisions on the same step cannot both succeed.
+pub fn decide_ticket_approval(
+    conn: &mut DbConnection,
+    approval_id: i32,
+    status: ApprovalStepStatus,
+    decided_by: Option<Uuid>,
+    comment: Option<String>,
+) -> QueryResult<TicketApproval> {
+    let pending = ticket_approvals::table
+        .find(approval_id)
+        .filter(ticket_approvals::status.eq(ApprovalStepStatus::Pending.as_str()));
+    diesel::update(pending)
+        .set((
+            ticket_approvals::status.eq(status.as_str()),
+            ticket_approvals::decided_by.eq(decided_by),
+            ticket_approvals::decided_at.eq(Some(Utc::now().naive_utc())),
+            ticket_approvals::comment.eq(comment),
+        ))
+        .get_result(conn)
+}
+
+/// Move an approval step to a new status without a decision (activate, skip, cancel)
+pub fn set_ticket_approval_status(
+    conn: &mut DbConnection,
+    approval_id: i32,
+    status: ApprovalStepStatus,
+) -> QueryResult<TicketApproval> {
+    diesel::update(ticket_approvals::table.find(approval_id))
+        .set(ticket_approvals::status.eq(status.as_str()))
+        .get_result(conn)
+}
+
+/// Set or clear the approval status stored on the ticket
+pub fn set_ticket_approval_state(
+    conn: &mut DbConnection,
+    ticket_id: i32,
+    status: Option<TicketApprovalStatus>,
+) -> QueryResult<Ticket> {
+    diesel::update(tickets::table.find(ticket_id))
+        .set((
+            tickets::approval_status.eq(status.map(|s| s.as_str())),
+            tickets::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+}
+
+/// Pending approval steps a user can decide: assigned to them directly or to one of their groups
+pub fn get_pending_approvals_for_user(
+    conn: &mut DbConnection,
+    user_uuid: &Uuid,
+) -> QueryResult<Vec<TicketApproval>> {
+    let group_ids = crate::repository::groups::get_group_ids_for_user(conn, user_uuid)?;
+
+    ticket_approvals::table
+        .filter(ticket_approvals::status.eq(ApprovalStepStatus::Pending.as_str()))
+        .filter(
+            ticket_approvals::approver_user_uuid
+                .eq(user_uuid)
+                .or(ticket_approvals::approver_group_id.eq_any(group_ids)),
+        )
+        .order(ticket_approvals::created_at.asc())
+        .load(conn)
+}
+
+/// Pending steps with no resolvable approver (e.g. a manager that could not be looked up)
+pub fn get_unassigned_pending_approvals(conn: &mut DbConnection) -> QueryResult<Vec<TicketApproval>> {
+    ticket_approvals::table
+        .filter(ticket_approvals::status.eq(ApprovalStepStatus::Pending.as_str()))
+        .filter(ticket_approvals::approver_user_uuid.is_null())
+        .filter(ticket_approvals::approver_group_id.is_null())
+        .order(ticket_approvals::created_at.asc())
+        .load(conn)
+}
+
+// ============================================================================
+// Audit Trail
+// ============================================================================
+
+/// Append an entry to a ticket's approval audit trail
+pub fn record_event(conn: &mut DbConnection, event: NewTicketApprovalEvent) -> QueryResult<TicketApprovalEvent> {
+    diesel::insert_into(ticket_approval_events::table)
+        .values(&event)
+        .get_result(conn)
+}
+
+/// Get the approval audit trail of a ticket, oldest first
+pub fn get_events_for_ticket(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<TicketApprovalEvent>> {
+    ticket_approval_events::table
+        .filter(ticket_approval_events::ticket_id.eq(ticket_id))
+        .order(ticket_approval_events::created_at.asc())
+        .load(conn)
+}
@@ -59,6 +59,33 @@ pub fn get_article_content_revision(
         .first(conn)
 }
 
+/// Revisions numbered `from..=to`, oldest first (used for revision diffs)
+pub fn get_article_content_revisions_between(
+    conn: &mut DbConnection,
+    article_content_id: i32,
+    from: i32,
+    to: i32,
+) -> QueryResult<Vec<ArticleContentRevision>> {
+    article_content_revisions::table
+        .filter(article_content_revisions::article_content_id.eq(article_content_id))
+        .filter(article_content_revisions::revision_number.between(from, to))
+        .order(article_content_revisions::revision_number.asc())
+        .load(conn)
+}
+
+/// Number of the newest revision older than `before`, if any
+pub fn get_previous_article_content_revision_number(
+    conn: &mut DbConnection,
+    article_content_id: i32,
+    before: i32,
+) -> QueryResult<Option<i32>> {
+    article_content_revisions::table
+        .filter(article_content_revisions::article_content_id.eq(article_content_id))
+        .filter(article_content_revisions::revision_number.lt(before))
+        .select(diesel::dsl::max(article_content_revisions::revision_number))
+        .first(conn)
+}
+
 pub fn get_latest_article_content_revision(
     conn: &mut DbConnection,
     article_content_id: i32
@@ -69,6 +96,33 @@ pub fn get_latest_article_content_revision(
         .first(conn)
 }
 
+/// Article contents with more than one revision (candidates for retention thinning)
+pub fn get_article_content_ids_with_revision_history(conn: &mut DbConnection) -> QueryResult<Vec<i32>> {
+    article_content_revisions::table
+        .group_by(article_content_revisions::article_content_id)
+        .having(diesel::dsl::count_star().gt(1))
+        .select(article_content_revisions::article_content_id)
+        .load(conn)
+}
+
+/// Revision ids and creation times of an article content, newest first
+pub fn get_article_content_revision_times(
+    conn: &mut DbConnection,
+    article_content_id: i32,
+) -> QueryResult<Vec<(i32, chrono::NaiveDateTime)>> {
+    article_content_revisions::table
+        .filter(article_content_revisions::article_content_id.eq(article_content_id))
+        .order(article_content_revisions::created_at.desc())
+        .select((article_content_revisions::id, article_content_revisions::created_at))
+        .load(conn)
+}
+
+/// Delete article content revisions by id
+pub fn delete_article_content_revisions(conn: &mut DbConnection, revision_ids: &[i32]) -> QueryResult<usize> {
+    diesel::delete(article_content_revisions::table.filter(article_content_revisions::id.eq_any(revision_ids)))
+        .execute(conn)
+}
+
 // Update Yjs state fields for ticket article content (snapshot-based persistence)
 // Note: Does NOT update the parent ticket's updated_at - that should only happen
 // when there are actual content changes, not on every sync/save
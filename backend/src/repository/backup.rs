@@ -1,8 +1,11 @@
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use uuid::Uuid;
 use crate::db::DbConnection;
-use crate::models::{BackupJob, NewBackupJob, BackupJobUpdate};
-use crate::schema::backup_jobs;
+use crate::models::{
+    BackupJob, NewBackupJob, BackupJobUpdate, BackupSchedule, NewBackupSchedule, BackupScheduleUpdate,
+};
+use crate::schema::{backup_jobs, backup_schedules};
 
 /// Create a new backup job record
 pub fn create_backup_job(
@@ -86,3 +89,133 @@ pub fn get_completed_exports(
         .order(backup_jobs::created_at.desc())
         .load(conn)
 }
+
+/// Record where a backup was uploaded off-site
+pub fn set_backup_job_remote_path(
+    conn: &mut DbConnection,
+    job_id: Uuid,
+    remote_path: &str,
+) -> QueryResult<BackupJob> {
+    diesel::update(backup_jobs::table.find(job_id))
+        .set(backup_jobs::remote_path.eq(remote_path))
+        .get_result(conn)
+}
+
+/// Record how far a running job has got
+pub fn set_backup_job_progress(
+    conn: &mut DbConnection,
+    job_id: Uuid,
+    stage: &str,
+    current: i64,
+    total: Option<i64>,
+) -> QueryResult<usize> {
+    diesel::update(backup_jobs::table.find(job_id))
+        .set((
+            backup_jobs::progress_stage.eq(stage),
+            backup_jobs::progress_current.eq(current),
+            backup_jobs::progress_total.eq(total),
+        ))
+        .execute(conn)
+}
+
+/// Get completed exports made by a schedule (most recent first)
+pub fn get_completed_schedule_exports(
+    conn: &mut DbConnection,
+    schedule_id: i32,
+) -> QueryResult<Vec<BackupJob>> {
+    backup_jobs::table
+        .filter(backup_jobs::schedule_id.eq(schedule_id))
+        .filter(backup_jobs::job_type.eq("export"))
+        .filter(backup_jobs::status.eq("completed"))
+        .order(backup_jobs::created_at.desc())
+        .load(conn)
+}
+
+// ============================================================================
+// Backup Schedules
+// ============================================================================
+
+/// Get all backup schedules
+pub fn get_backup_schedules(conn: &mut DbConnection) -> QueryResult<Vec<BackupSchedule>> {
+    backup_schedules::table
+        .order(backup_schedules::name.asc())
+        .load(conn)
+}
+
+/// Get a backup schedule by ID
+pub fn get_backup_schedule(conn: &mut DbConnection, schedule_id: i32) -> QueryResult<BackupSchedule> {
+    backup_schedules::table.find(schedule_id).first(conn)
+}
+
+/// Create a backup schedule
+pub fn create_backup_schedule(
+    conn: &mut DbConnection,
+    new_schedule: NewBackupSchedule,
+) -> QueryResult<BackupSchedule> {
+    diesel::insert_into(backup_schedules::table)
+        .values(&new_schedule)
+        .get_result(conn)
+}
+
+/// Update a backup schedule
+pub fn update_backup_schedule(
+    conn: &mut DbConnection,
+    schedule_id: i32,
+    update: BackupScheduleUpdate,
+) -> QueryResult<BackupSchedule> {
+    diesel::update(backup_schedules::table.find(schedule_id))
+        .set(&update)
+        .get_result(conn)
+}
+
+/// Delete a backup schedule (its jobs are kept)
+pub fn delete_backup_schedule(conn: &mut DbConnection, schedule_id: i32) -> QueryResult<usize> {
+    diesel::delete(backup_schedules::table.find(schedule_id)).execute(conn)
+}
+
+/// Enabled schedules whose next run is due
+pub fn get_due_backup_schedules(
+    conn: &mut DbConnection,
+    now: NaiveDateTime,
+) -> QueryResult<Vec<BackupSchedule>> {
+    backup_schedules::table
+        .filter(backup_schedules::enabled.eq(true))
+        .filter(backup_schedules::next_run_at.le(now))
+        .order(backup_schedules::next_run_at.asc())
+        .load(conn)
+}
+
+/// Claim a due run by moving the schedule on to its next run
+///
+/// Only succeeds if `next_run_at` still holds the value the caller saw, so when several
+/// instances poll the same database only one of them runs the backup.
+pub fn claim_backup_schedule_run(
+    conn: &mut DbConnection,
+    schedule_id: i32,
+    expected_next_run: NaiveDateTime,
+    next_run: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+) -> QueryResult<bool> {
+    let updated = diesel::update(
+        backup_schedules::table
+            .find(schedule_id)
+            .filter(backup_schedules::next_run_at.eq(expected_next_run)),
+    )
+    .set((
+        backup_schedules::next_run_at.eq(next_run),
+        backup_schedules::last_run_at.eq(now),
+    ))
+    .execute(conn)?;
+    Ok(updated == 1)
+}
+
+/// Record a manual run of a schedule
+pub fn set_backup_schedule_last_run(
+    conn: &mut DbConnection,
+    schedule_id: i32,
+    now: NaiveDateTime,
+) -> QueryResult<usize> {
+    diesel::update(backup_schedules::table.find(schedule_id))
+        .set(backup_schedules::last_run_at.eq(now))
+        .execute(conn)
+}
@@ -0,0 +1,108 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+// ============================================================================
+// Catalog Items
+// ============================================================================
+
+/// Get all catalog items, including inactive ones (admin)
+pub fn get_all_items(conn: &mut DbConnection) -> QueryResult<Vec<CatalogItem>> {
+    catalog_items::table
+        .order((catalog_items::display_order.asc(), catalog_items::name.asc()))
+        .load(conn)
+}
+
+/// Get active catalog items whose category the user can see
+/// Visibility follows the category's group visibility rules.
+pub fn get_items_for_user(
+    conn: &mut DbConnection,
+    user_uuid: &Uuid,
+    is_admin: bool,
+) -> QueryResult<Vec<CatalogItem>> {
+    let category_ids: Vec<i32> = crate::repository::categories::get_categories_for_user(conn, user_uuid, is_admin)?
+        .into_iter()
+        .map(|category| category.id)
+        .collect();
+
+    catalog_items::table
+        .filter(catalog_items::is_active.eq(true))
+        .filter(catalog_items::category_id.eq_any(category_ids))
+        .order((catalog_items::display_order.asc(), catalog_items::name.asc()))
+        .load(conn)
+}
+
+/// Get a catalog item by ID
+pub fn get_item_by_id(conn: &mut DbConnection, item_id: i32) -> QueryResult<CatalogItem> {
+    catalog_items::table.find(item_id).first(conn)
+}
+
+/// Create a catalog item
+pub fn create_item(conn: &mut DbConnection, new_item: NewCatalogItem) -> QueryResult<CatalogItem> {
+    diesel::insert_into(catalog_items::table)
+        .values(&new_item)
+        .get_result(conn)
+}
+
+/// Update a catalog item
+pub fn update_item(
+    conn: &mut DbConnection,
+    item_id: i32,
+    mut update: CatalogItemUpdate,
+) -> QueryResult<CatalogItem> {
+    if update.updated_at.is_none() {
+        update.updated_at = Some(Utc::now().naive_utc());
+    }
+
+    diesel::update(catalog_items::table.find(item_id))
+        .set(&update)
+        .get_result(conn)
+}
+
+/// Soft delete a catalog item (set is_active to false)
+/// Submissions keep pointing at the item.
+pub fn deactivate_item(conn: &mut DbConnection, item_id: i32) -> QueryResult<CatalogItem> {
+    diesel::update(catalog_items::table.find(item_id))
+        .set((
+            catalog_items::is_active.eq(false),
+            catalog_items::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+}
+
+/// Get the next display order value within a category
+pub fn get_next_display_order(conn: &mut DbConnection, category_id: i32) -> QueryResult<i32> {
+    let max_order: Option<i32> = catalog_items::table
+        .filter(catalog_items::category_id.eq(category_id))
+        .select(diesel::dsl::max(catalog_items::display_order))
+        .first(conn)?;
+
+    Ok(max_order.unwrap_or(0) + 1)
+}
+
+// ============================================================================
+// Submissions
+// ============================================================================
+
+/// Store the answers of a catalog form submission
+pub fn create_submission(
+    conn: &mut DbConnection,
+    submission: NewCatalogSubmission,
+) -> QueryResult<CatalogSubmission> {
+    diesel::insert_into(catalog_submissions::table)
+        .values(&submission)
+        .get_result(conn)
+}
+
+/// Get the catalog submission that created a ticket, if any
+pub fn get_submission_for_ticket(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Option<CatalogSubmission>> {
+    catalog_submissions::table
+        .filter(catalog_submissions::ticket_id.eq(ticket_id))
+        .first(conn)
+        .optional()
+}
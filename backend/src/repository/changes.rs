@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+// ============================================================================
+// Change Requests
+// ============================================================================
+
+/// Create a change ticket together with its detail row and empty notes
+pub fn create_change(
+    conn: &mut DbConnection,
+    new_ticket: NewTicket,
+    details: NewChangeDetails,
+    created_by: Option<Uuid>,
+) -> QueryResult<(Ticket, ChangeDetails)> {
+    conn.transaction(|conn| {
+        let ticket = crate::repository::tickets::create_typed_ticket(conn, new_ticket, TicketType::Change, created_by)?;
+
+        let details = diesel::insert_into(change_details::table)
+            .values(&NewChangeDetails { ticket_id: ticket.id, ..details })
+            .get_result::<ChangeDetails>(conn)?;
+
+        Ok((ticket, details))
+    })
+}
+
+/// Get the detail row of a change
+pub fn get_change_details(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<ChangeDetails> {
+    change_details::table.find(ticket_id).first(conn)
+}
+
+/// Update the detail row of a change
+pub fn update_change_details(
+    conn: &mut DbConnection,
+    ticket_id: i32,
+    update: ChangeDetailsUpdate,
+) -> QueryResult<ChangeDetails> {
+    diesel::update(change_details::table.find(ticket_id))
+        .set(&update)
+        .get_result(conn)
+}
+
+/// List changes, optionally filtered by approval status, ordered by planned start
+pub fn list_changes(conn: &mut DbConnection, approval_status: Option<ChangeApprovalStatus>) -> QueryResult<Vec<ChangeListItem>> {
+    let mut query = tickets::table
+        .inner_join(change_details::table)
+        .filter(tickets::ticket_type.eq(TicketType::Change.as_str()))
+        .filter(tickets::deleted_at.is_null())
+        .into_boxed();
+
+    if let Some(status) = approval_status {
+        query = query.filter(change_details::approval_status.eq(status.as_str()));
+    }
+
+    let rows = query
+        .order((change_details::planned_start.asc().nulls_last(), tickets::id.desc()))
+        .select((tickets::all_columns, change_details::all_columns))
+        .load::<(Ticket, ChangeDetails)>(conn)?;
+
+    let ids: Vec<i32> = rows.iter().map(|(ticket, _)| ticket.id).collect();
+    let device_counts: HashMap<i32, i64> = ticket_devices::table
+        .filter(ticket_devices::ticket_id.eq_any(&ids))
+        .group_by(ticket_devices::ticket_id)
+        .select((ticket_devices::ticket_id, diesel::dsl::count_star()))
+        .load::<(i32, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    Ok(rows
+        .into_iter()
+        .map(|(ticket, details)| {
+            let device_count = device_counts.get(&ticket.id).copied().unwrap_or(0);
+            ChangeListItem { ticket, details, device_count }
+        })
+        .collect())
+}
+
+/// Get a change with its details, affected devices, projects and related problems
+pub fn get_change_record(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<ChangeRecord> {
+    let ticket = tickets::table
+        .find(ticket_id)
+        .filter(tickets::ticket_type.eq(TicketType::Change.as_str()))
+        .filter(tickets::deleted_at.is_null())
+        .first::<Ticket>(conn)?;
+    let details = get_change_details(conn, ticket_id)?;
+
+    let devices = crate::repository::tickets::get_devices_for_ticket(conn, ticket_id)?;
+    let projects = crate::repository::projects::get_projects_for_ticket(conn, ticket_id)?;
+
+    let related = crate::repository::linked_tickets::get_ticket_relationships(conn, ticket_id)?.related;
+    let problems = tickets::table
+        .filter(tickets::id.eq_any(related))
+        .filter(tickets::ticket_type.eq(TicketType::Problem.as_str()))
+        .load::<Ticket>(conn)?;
+
+    Ok(ChangeRecord { ticket, details, devices, projects, problems })
+}
+
+/// Approved changes whose planned window overlaps the given device's window
+/// Used to flag conflicting changes on the same device
+pub fn get_overlapping_changes_for_device(
+    conn: &mut DbConnection,
+    device_id: i32,
+    start: chrono::NaiveDateTime,
+    end: chrono::NaiveDateTime,
+    exclude_ticket_id: i32,
+) -> QueryResult<Vec<Ticket>> {
+    tickets::table
+        .inner_join(change_details::table)
+        .inner_join(ticket_devices::table)
+        .filter(ticket_devices::device_id.eq(device_id))
+        .filter(tickets::id.ne(exclude_ticket_id))
+        .filter(tickets::deleted_at.is_null())
+        .filter(change_details::approval_status.eq(ChangeApprovalStatus::Approved.as_str()))
+        .filter(change_details::planned_start.lt(end))
+        .filter(change_details::planned_end.gt(start))
+        .select(tickets::all_columns)
+        .load::<Ticket>(conn)
+}
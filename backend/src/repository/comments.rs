@@ -1,5 +1,6 @@
 use diesel::prelude::*;
 use diesel::QueryResult;
+use uuid::Uuid;
 
 use crate::db::DbConnection;
 use crate::models::*;
@@ -9,6 +10,7 @@ use crate::schema::*;
 pub fn get_comments_by_ticket_id(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<Comment>> {
     comments::table
         .filter(comments::ticket_id.eq(ticket_id))
+        .filter(comments::deleted_at.is_null())
         .order(comments::created_at.desc())
         .load(conn)
 }
@@ -32,6 +34,7 @@ pub fn create_comment(conn: &mut DbConnection, new_comment: NewComment) -> Query
 pub fn get_attachments_by_comment_id(conn: &mut DbConnection, comment_id: i32) -> QueryResult<Vec<Attachment>> {
     attachments::table
         .filter(attachments::comment_id.eq(comment_id))
+        .filter(attachments::deleted_at.is_null())
         .load(conn)
 }
 
@@ -42,7 +45,10 @@ pub fn create_attachment(conn: &mut DbConnection, new_attachment: NewAttachment)
 }
 
 pub fn get_comment_by_id(conn: &mut DbConnection, comment_id: i32) -> QueryResult<Comment> {
-    comments::table.find(comment_id).first(conn)
+    comments::table
+        .find(comment_id)
+        .filter(comments::deleted_at.is_null())
+        .first(conn)
 }
 
 pub fn get_comments_with_attachments_by_ticket_id(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<CommentWithAttachments>> {
@@ -68,21 +74,38 @@ pub fn get_comments_with_attachments_by_ticket_id(conn: &mut DbConnection, ticke
     Ok(comments_with_attachments)
 }
 
-pub fn delete_comment(conn: &mut DbConnection, comment_id: i32) -> QueryResult<usize> {
-    // First delete all attachments associated with this comment
-    diesel::delete(attachments::table.filter(attachments::comment_id.eq(comment_id))).execute(conn)?;
-    
-    // Then delete the comment itself
-    diesel::delete(comments::table.find(comment_id)).execute(conn)
+/// Move a comment and its attachments to the recycle bin
+pub fn delete_comment(conn: &mut DbConnection, comment_id: i32, deleted_by: Option<Uuid>) -> QueryResult<usize> {
+    // The same timestamp on both lets a restore of the comment bring back its attachments
+    let now = chrono::Utc::now().naive_utc();
+    conn.transaction(|conn| {
+        diesel::update(
+            attachments::table
+                .filter(attachments::comment_id.eq(comment_id))
+                .filter(attachments::deleted_at.is_null()),
+        )
+        .set((attachments::deleted_at.eq(now), attachments::deleted_by.eq(deleted_by)))
+        .execute(conn)?;
+
+        diesel::update(comments::table.find(comment_id).filter(comments::deleted_at.is_null()))
+            .set((comments::deleted_at.eq(now), comments::deleted_by.eq(deleted_by)))
+            .execute(conn)
+    })
 }
 
 pub fn get_attachment_by_id(conn: &mut DbConnection, attachment_id: i32) -> QueryResult<Attachment> {
     attachments::table
         .find(attachment_id)
+        .filter(attachments::deleted_at.is_null())
         .first(conn)
 }
 
-pub fn delete_attachment(conn: &mut DbConnection, attachment_id: i32) -> QueryResult<usize> {
-    diesel::delete(attachments::table.find(attachment_id))
+/// Move an attachment to the recycle bin; its file is removed when it is purged
+pub fn delete_attachment(conn: &mut DbConnection, attachment_id: i32, deleted_by: Option<Uuid>) -> QueryResult<usize> {
+    diesel::update(attachments::table.find(attachment_id).filter(attachments::deleted_at.is_null()))
+        .set((
+            attachments::deleted_at.eq(chrono::Utc::now().naive_utc()),
+            attachments::deleted_by.eq(deleted_by),
+        ))
         .execute(conn)
 } 
\ No newline at end of file
@@ -11,6 +11,7 @@ use crate::schema::*;
 // Device operations
 pub fn get_all_devices(conn: &mut DbConnection) -> QueryResult<Vec<Device>> {
     devices::table
+        .filter(devices::deleted_at.is_null())
         .order_by(devices::id.asc())
         .load::<Device>(conn)
 }
@@ -27,7 +28,7 @@ pub fn get_paginated_devices(
     warranty: Option<String>,
 ) -> Result<(Vec<Device>, i64), Error> {
     // Build the main query
-    let mut query = devices::table.into_boxed();
+    let mut query = devices::table.filter(devices::deleted_at.is_null()).into_boxed();
     
     // Apply filters if provided
     if let Some(search_term) = search.clone() {
@@ -61,7 +62,7 @@ pub fn get_paginated_devices(
     }
     
     // Build a separate count query with the same filters
-    let mut count_query = devices::table.into_boxed();
+    let mut count_query = devices::table.filter(devices::deleted_at.is_null()).into_boxed();
     
     if let Some(search_term) = search {
         if !search_term.is_empty() {
@@ -130,6 +131,7 @@ pub fn get_paginated_devices(
 pub fn get_device_by_id(conn: &mut DbConnection, device_id: i32) -> QueryResult<Device> {
     devices::table
         .find(device_id)
+        .filter(devices::deleted_at.is_null())
         .first(conn)
 }
 
@@ -151,10 +153,18 @@ pub fn get_device_by_microsoft_id(conn: &mut DbConnection, microsoft_device_id:
         .first(conn)
 }
 
+/// Look up a device by serial number, including one in the recycle bin
+pub fn get_device_by_serial_number(conn: &mut DbConnection, serial_number: &str) -> QueryResult<Device> {
+    devices::table
+        .filter(devices::serial_number.eq(serial_number))
+        .first(conn)
+}
+
 #[allow(dead_code)]
 pub fn get_devices_by_user(conn: &mut DbConnection, user_uuid: &Uuid) -> QueryResult<Vec<Device>> {
     devices::table
         .filter(devices::primary_user_uuid.eq(user_uuid))
+        .filter(devices::deleted_at.is_null())
         .order_by(devices::name.asc())
         .load::<Device>(conn)
 }
@@ -174,8 +184,13 @@ pub fn update_device(conn: &mut DbConnection, device_id: i32, device_update: Dev
         .get_result(conn)
 }
 
-pub fn delete_device(conn: &mut DbConnection, device_id: i32) -> QueryResult<usize> {
-    diesel::delete(devices::table.find(device_id))
+/// Move a device to the recycle bin
+pub fn delete_device(conn: &mut DbConnection, device_id: i32, deleted_by: Option<Uuid>) -> QueryResult<usize> {
+    diesel::update(devices::table.find(device_id).filter(devices::deleted_at.is_null()))
+        .set((
+            devices::deleted_at.eq(Utc::now().naive_utc()),
+            devices::deleted_by.eq(deleted_by),
+        ))
         .execute(conn)
 }
 
@@ -237,6 +252,7 @@ pub fn get_devices_for_user(conn: &mut DbConnection, user_uuid: &Uuid) -> QueryR
     
     devices
         .filter(primary_user_uuid.eq(user_uuid))
+        .filter(deleted_at.is_null())
         .order(name.asc())
         .load(conn)
 }
@@ -251,7 +267,7 @@ pub fn get_paginated_devices_excluding_ids(
     use crate::schema::devices::dsl::*;
     
     // Create count query
-    let mut count_query = devices.into_boxed();
+    let mut count_query = devices.filter(deleted_at.is_null()).into_boxed();
     
     // Exclude specific device IDs
     if !exclude_ids.is_empty() {
@@ -275,7 +291,7 @@ pub fn get_paginated_devices_excluding_ids(
     let total_count = count_query.count().get_result::<i64>(conn)?;
     
     // Create data query
-    let mut data_query = devices.into_boxed();
+    let mut data_query = devices.filter(deleted_at.is_null()).into_boxed();
     
     // Apply the same filters
     if !exclude_ids.is_empty() {
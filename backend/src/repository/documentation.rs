@@ -4,22 +4,86 @@ use diesel::sql_types::{Integer, Nullable};
 
 use crate::db::DbConnection;
 use crate::models::{
-    DocumentationPage, DocumentationPageWithChildren,
-    NewDocumentationPage, DocumentationPageUpdate, PageOrder
+    DocumentationPage, DocumentationPageWithChildren, DocumentationStatus,
+    NewDocumentationPage, DocumentationPageUpdate, PageOrder, PublicDocumentationPage
 };
 use crate::schema::documentation_pages;
 
 // Get all documentation pages
 pub fn get_documentation_pages(conn: &mut DbConnection) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(documentation_pages::title.asc())
         .load::<DocumentationPage>(conn)
 }
 
+// Get published, public pages (not archived, not templates) without their content
+pub fn get_public_documentation_pages(conn: &mut DbConnection) -> Result<Vec<PublicDocumentationPage>, Error> {
+    documentation_pages::table
+        .into_boxed()
+        .filter(documentation_pages::status.eq(DocumentationStatus::Published))
+        .filter(documentation_pages::is_public.eq(true))
+        .filter(documentation_pages::archived_at.is_null())
+        .filter(documentation_pages::deleted_at.is_null())
+        .filter(documentation_pages::is_template.eq(false))
+        .order_by((documentation_pages::display_order.asc(), documentation_pages::title.asc()))
+        .select((
+            documentation_pages::id,
+            documentation_pages::title,
+            documentation_pages::slug,
+            documentation_pages::icon,
+            documentation_pages::parent_id,
+            documentation_pages::display_order,
+            documentation_pages::updated_at,
+        ))
+        .load::<PublicDocumentationPage>(conn)
+}
+
+// Get published pages (not archived, not templates) without their content,
+// public or not, in the same shape as the public listing
+pub fn get_published_documentation_pages(conn: &mut DbConnection) -> Result<Vec<PublicDocumentationPage>, Error> {
+    documentation_pages::table
+        .into_boxed()
+        .filter(documentation_pages::status.eq(DocumentationStatus::Published))
+        .filter(documentation_pages::archived_at.is_null())
+        .filter(documentation_pages::deleted_at.is_null())
+        .filter(documentation_pages::is_template.eq(false))
+        .order_by((documentation_pages::display_order.asc(), documentation_pages::title.asc()))
+        .select((
+            documentation_pages::id,
+            documentation_pages::title,
+            documentation_pages::slug,
+            documentation_pages::icon,
+            documentation_pages::parent_id,
+            documentation_pages::display_order,
+            documentation_pages::updated_at,
+        ))
+        .load::<PublicDocumentationPage>(conn)
+}
+
+// Get template pages (not archived), by title
+pub fn get_template_pages(conn: &mut DbConnection) -> Result<Vec<DocumentationPage>, Error> {
+    documentation_pages::table
+        .filter(documentation_pages::is_template.eq(true))
+        .filter(documentation_pages::archived_at.is_null())
+        .filter(documentation_pages::deleted_at.is_null())
+        .order_by(documentation_pages::title.asc())
+        .load::<DocumentationPage>(conn)
+}
+
+// Get documentation pages by their ids
+pub fn get_documentation_pages_by_ids(conn: &mut DbConnection, ids: &[i32]) -> Result<Vec<DocumentationPage>, Error> {
+    documentation_pages::table
+        .filter(documentation_pages::id.eq_any(ids))
+        .filter(documentation_pages::deleted_at.is_null())
+        .load::<DocumentationPage>(conn)
+}
+
 // Get a specific documentation page by ID
 pub fn get_documentation_page(id: i32, conn: &mut DbConnection) -> Result<DocumentationPage, Error> {
     documentation_pages::table
         .find(id)
+        .filter(documentation_pages::deleted_at.is_null())
         .first::<DocumentationPage>(conn)
 }
 
@@ -27,6 +91,7 @@ pub fn get_documentation_page(id: i32, conn: &mut DbConnection) -> Result<Docume
 pub fn get_documentation_page_by_slug(slug: &str, conn: &mut DbConnection) -> Result<DocumentationPage, Error> {
     documentation_pages::table
         .filter(documentation_pages::slug.eq(slug))
+        .filter(documentation_pages::deleted_at.is_null())
         .first::<DocumentationPage>(conn)
 }
 
@@ -51,15 +116,33 @@ pub fn update_documentation_page(
         .get_result(conn)
 }
 
-// Delete a documentation page
-pub fn delete_documentation_page(id: i32, conn: &mut DbConnection) -> Result<usize, Error> {
-    diesel::delete(documentation_pages::table.find(id)).execute(conn)
+// Move a documentation page and its subpages to the recycle bin
+//
+// The whole subtree gets the same timestamp, so restoring the page brings its subpages back.
+pub fn delete_documentation_page(id: i32, deleted_by: Option<uuid::Uuid>, conn: &mut DbConnection) -> Result<usize, Error> {
+    let now = chrono::Utc::now().naive_utc();
+    conn.transaction(|conn| {
+        let mut page_ids = get_all_descendant_ids(conn, id)?;
+        page_ids.push(id);
+
+        diesel::update(
+            documentation_pages::table
+                .filter(documentation_pages::id.eq_any(&page_ids))
+                .filter(documentation_pages::deleted_at.is_null()),
+        )
+        .set((
+            documentation_pages::deleted_at.eq(now),
+            documentation_pages::deleted_by.eq(deleted_by),
+        ))
+        .execute(conn)
+    })
 }
 
 // Get top-level documentation pages
 pub fn get_top_level_pages(conn: &mut DbConnection) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
         .filter(documentation_pages::parent_id.is_null())
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(documentation_pages::title.asc())
         .load::<DocumentationPage>(conn)
 }
@@ -68,6 +151,7 @@ pub fn get_top_level_pages(conn: &mut DbConnection) -> Result<Vec<DocumentationP
 pub fn get_pages_by_parent_id(parent_id: i32, conn: &mut DbConnection) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
         .filter(documentation_pages::parent_id.eq(parent_id))
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(documentation_pages::title.asc())
         .load::<DocumentationPage>(conn)
 }
@@ -76,6 +160,7 @@ pub fn get_pages_by_parent_id(parent_id: i32, conn: &mut DbConnection) -> Result
 pub fn get_documentation_pages_by_ticket_id(conn: &mut DbConnection, ticket_id: i32) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
         .filter(documentation_pages::ticket_id.eq(ticket_id))
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(documentation_pages::title.asc())
         .load::<DocumentationPage>(conn)
 }
@@ -91,6 +176,7 @@ pub fn get_ordered_top_level_pages(
 ) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
         .filter(documentation_pages::parent_id.is_null())
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(coalesce(documentation_pages::display_order, 0).asc())
         .load::<DocumentationPage>(conn)
 }
@@ -102,6 +188,7 @@ pub fn get_ordered_pages_by_parent_id(
 ) -> Result<Vec<DocumentationPage>, Error> {
     documentation_pages::table
         .filter(documentation_pages::parent_id.eq(parent_id))
+        .filter(documentation_pages::deleted_at.is_null())
         .order_by(coalesce(documentation_pages::display_order, 0).asc())
         .load::<DocumentationPage>(conn)
 }
@@ -276,6 +363,7 @@ pub fn create_documentation_revision(
                 documentation_revisions::yjs_document_snapshot.eq(yjs_document_content),
                 documentation_revisions::yjs_state_vector.eq(yjs_state_vector),
                 documentation_revisions::created_by.eq(created_by),
+                documentation_revisions::contributed_by.eq(&contributed_by),
             ))
             .execute(conn)?;
 
@@ -310,6 +398,37 @@ pub fn get_documentation_revision(
         .first(conn)
 }
 
+// Get revisions numbered from..=to for a page, oldest first (used for revision diffs)
+pub fn get_documentation_revisions_between(
+    conn: &mut DbConnection,
+    page_id: i32,
+    from: i32,
+    to: i32,
+) -> Result<Vec<crate::models::DocumentationRevision>, Error> {
+    use crate::schema::documentation_revisions::dsl;
+
+    dsl::documentation_revisions
+        .filter(dsl::page_id.eq(page_id))
+        .filter(dsl::revision_number.between(from, to))
+        .order_by(dsl::revision_number.asc())
+        .load(conn)
+}
+
+// Get the number of the newest revision older than `before`, if any
+pub fn get_previous_documentation_revision_number(
+    conn: &mut DbConnection,
+    page_id: i32,
+    before: i32,
+) -> Result<Option<i32>, Error> {
+    use crate::schema::documentation_revisions::dsl;
+
+    dsl::documentation_revisions
+        .filter(dsl::page_id.eq(page_id))
+        .filter(dsl::revision_number.lt(before))
+        .select(diesel::dsl::max(dsl::revision_number))
+        .first(conn)
+}
+
 // Get the latest revision for a documentation page
 pub fn get_latest_documentation_revision(
     conn: &mut DbConnection,
@@ -322,3 +441,36 @@ pub fn get_latest_documentation_revision(
         .order_by(dsl::revision_number.desc())
         .first(conn)
 }
+
+// Get pages with more than one revision (candidates for retention thinning)
+pub fn get_page_ids_with_revision_history(conn: &mut DbConnection) -> Result<Vec<i32>, Error> {
+    use crate::schema::documentation_revisions::dsl;
+
+    dsl::documentation_revisions
+        .group_by(dsl::page_id)
+        .having(diesel::dsl::count_star().gt(1))
+        .select(dsl::page_id)
+        .load(conn)
+}
+
+// Get revision ids and creation times for a page, newest first
+pub fn get_documentation_revision_times(
+    conn: &mut DbConnection,
+    page_id: i32,
+) -> Result<Vec<(i32, chrono::NaiveDateTime)>, Error> {
+    use crate::schema::documentation_revisions::dsl;
+
+    dsl::documentation_revisions
+        .filter(dsl::page_id.eq(page_id))
+        .order_by(dsl::created_at.desc())
+        .select((dsl::id, dsl::created_at))
+        .load(conn)
+}
+
+// Delete documentation revisions by id
+pub fn delete_documentation_revisions(conn: &mut DbConnection, revision_ids: &[i32]) -> Result<usize, Error> {
+    use crate::schema::documentation_revisions::dsl;
+
+    diesel::delete(dsl::documentation_revisions.filter(dsl::id.eq_any(revision_ids)))
+        .execute(conn)
+}
@@ -442,6 +442,7 @@ pub fn get_devices_in_group(conn: &mut DbConnection, group_id: i32) -> QueryResu
     device_groups::table
         .filter(device_groups::group_id.eq(group_id))
         .inner_join(devices::table.on(devices::id.eq(device_groups::device_id)))
+        .filter(devices::deleted_at.is_null())
         .select(devices::all_columns)
         .load(conn)
 }
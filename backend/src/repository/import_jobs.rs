@@ -0,0 +1,64 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+use crate::db::DbConnection;
+use crate::models::{ImportJob, ImportJobUpdate, NewImportJob};
+use crate::schema::import_jobs;
+
+/// Create a new import job record
+pub fn create_import_job(
+    conn: &mut DbConnection,
+    new_job: NewImportJob,
+) -> QueryResult<ImportJob> {
+    diesel::insert_into(import_jobs::table)
+        .values(&new_job)
+        .get_result(conn)
+}
+
+/// Get an import job by ID
+pub fn get_import_job(
+    conn: &mut DbConnection,
+    job_id: Uuid,
+) -> QueryResult<ImportJob> {
+    import_jobs::table.find(job_id).first(conn)
+}
+
+/// Get recent import jobs (most recent first)
+pub fn get_import_jobs(
+    conn: &mut DbConnection,
+    limit: i64,
+) -> QueryResult<Vec<ImportJob>> {
+    import_jobs::table
+        .order(import_jobs::created_at.desc())
+        .limit(limit)
+        .load(conn)
+}
+
+/// Update an import job
+pub fn update_import_job(
+    conn: &mut DbConnection,
+    job_id: Uuid,
+    update: ImportJobUpdate,
+) -> QueryResult<ImportJob> {
+    diesel::update(import_jobs::table.find(job_id))
+        .set(&update)
+        .get_result(conn)
+}
+
+/// Record how many rows a running import has processed
+pub fn set_import_job_progress(
+    conn: &mut DbConnection,
+    job_id: Uuid,
+    current: i64,
+    created: i32,
+    updated: i32,
+    failed: i32,
+) -> QueryResult<usize> {
+    diesel::update(import_jobs::table.find(job_id))
+        .set((
+            import_jobs::progress_current.eq(current),
+            import_jobs::created_count.eq(created),
+            import_jobs::updated_count.eq(updated),
+            import_jobs::failed_count.eq(failed),
+        ))
+        .execute(conn)
+}
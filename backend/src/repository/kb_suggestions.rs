@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+/// Record a click on a suggested article
+pub fn record_click(conn: &mut DbConnection, click: NewKbSuggestionClick) -> QueryResult<KbSuggestionClick> {
+    diesel::insert_into(kb_suggestion_clicks::table)
+        .values(&click)
+        .get_result(conn)
+}
+
+/// Clicks recorded since `since`, oldest first
+pub fn get_clicks_since(conn: &mut DbConnection, since: NaiveDateTime) -> QueryResult<Vec<KbSuggestionClick>> {
+    kb_suggestion_clicks::table
+        .filter(kb_suggestion_clicks::created_at.ge(since))
+        .order(kb_suggestion_clicks::created_at.asc())
+        .load(conn)
+}
+
+/// A ticket's (requester, creator, created_at)
+pub type TicketCreation = (Option<Uuid>, Option<Uuid>, NaiveDateTime);
+
+/// Creation times of tickets requested or created by any of `users` since `since`
+pub fn get_ticket_creations_by_users(
+    conn: &mut DbConnection,
+    users: &[Uuid],
+    since: NaiveDateTime,
+) -> QueryResult<Vec<TicketCreation>> {
+    tickets::table
+        .filter(tickets::created_at.ge(since))
+        .filter(
+            tickets::requester_uuid
+                .eq_any(users)
+                .or(tickets::created_by.eq_any(users)),
+        )
+        .select((tickets::requester_uuid, tickets::created_by, tickets::created_at))
+        .load(conn)
+}
@@ -5,6 +5,16 @@ use tracing::debug;
 use crate::db::DbConnection;
 use crate::models::*;
 
+/// Ids of tickets in the recycle bin; links to them are hidden until they're restored
+fn deleted_ticket_ids() -> crate::schema::tickets::BoxedQuery<'static, diesel::pg::Pg, diesel::sql_types::Integer> {
+    use crate::schema::tickets;
+
+    tickets::table
+        .filter(tickets::deleted_at.is_not_null())
+        .select(tickets::id)
+        .into_boxed()
+}
+
 // Linked Tickets
 pub fn get_linked_tickets(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<i32>> {
     use crate::schema::linked_tickets;
@@ -15,6 +25,7 @@ pub fn get_linked_tickets(conn: &mut DbConnection, ticket_id: i32) -> QueryResul
     // Use explicit table and column references to avoid ambiguity
     let linked_ids = linked_tickets::table
         .filter(linked_tickets::ticket_id.eq(ticket_id))
+        .filter(diesel::dsl::not(linked_tickets::linked_ticket_id.eq_any(deleted_ticket_ids())))
         .select(linked_tickets::linked_ticket_id)
         .load::<i32>(conn)?;
 
@@ -23,10 +34,80 @@ pub fn get_linked_tickets(conn: &mut DbConnection, ticket_id: i32) -> QueryResul
     Ok(linked_ids)
 }
 
-pub fn link_tickets(conn: &mut DbConnection, ticket1_id: i32, ticket2_id: i32) -> QueryResult<()> {
+/// Get every link owned by a ticket (one row per related ticket, typed from this ticket's side)
+pub fn get_ticket_links(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<LinkedTicket>> {
+    use crate::schema::linked_tickets;
+
+    linked_tickets::table
+        .filter(linked_tickets::ticket_id.eq(ticket_id))
+        .filter(diesel::dsl::not(linked_tickets::linked_ticket_id.eq_any(deleted_ticket_ids())))
+        .order(linked_tickets::created_at.asc())
+        .load::<LinkedTicket>(conn)
+}
+
+/// Get a ticket's relationships grouped by type
+pub fn get_ticket_relationships(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<TicketRelationships> {
+    let mut relationships = TicketRelationships::default();
+
+    for link in get_ticket_links(conn, ticket_id)? {
+        // Unknown legacy values are treated as plain related links
+        let link_type = link.link_type.parse().unwrap_or(TicketLinkType::RelatesTo);
+        let group = match link_type {
+            TicketLinkType::RelatesTo => &mut relationships.related,
+            TicketLinkType::ParentOf => &mut relationships.children,
+            TicketLinkType::ChildOf => &mut relationships.parents,
+            TicketLinkType::Blocks => &mut relationships.blocks,
+            TicketLinkType::BlockedBy => &mut relationships.blocked_by,
+            TicketLinkType::DuplicateOf => &mut relationships.duplicate_of,
+            TicketLinkType::DuplicatedBy => &mut relationships.duplicates,
+            TicketLinkType::CausedBy => &mut relationships.caused_by,
+            TicketLinkType::Causes => &mut relationships.causes,
+        };
+        group.push(link.linked_ticket_id);
+    }
+
+    Ok(relationships)
+}
+
+/// Get all (from, to) pairs stored with the given type, across all tickets
+pub fn get_link_edges(conn: &mut DbConnection, link_type: TicketLinkType) -> QueryResult<Vec<(i32, i32)>> {
+    use crate::schema::linked_tickets;
+
+    linked_tickets::table
+        .filter(linked_tickets::link_type.eq(link_type.as_str()))
+        .select((linked_tickets::ticket_id, linked_tickets::linked_ticket_id))
+        .load::<(i32, i32)>(conn)
+}
+
+/// Get the type of the link from `ticket_id` to `linked_ticket_id`, if any
+pub fn get_link_type(conn: &mut DbConnection, ticket_id: i32, linked_ticket_id: i32) -> QueryResult<Option<TicketLinkType>> {
+    use crate::schema::linked_tickets;
+
+    let link_type = linked_tickets::table
+        .filter(linked_tickets::ticket_id.eq(ticket_id))
+        .filter(linked_tickets::linked_ticket_id.eq(linked_ticket_id))
+        .select(linked_tickets::link_type)
+        .first::<String>(conn)
+        .optional()?;
+
+    Ok(link_type.map(|t| t.parse().unwrap_or(TicketLinkType::RelatesTo)))
+}
+
+/// Link two tickets with a typed relationship
+///
+/// `link_type` is read from `ticket1_id`'s side ("ticket1 blocks ticket2"); the row owned by
+/// `ticket2_id` stores the inverse type. An existing link between the pair is retyped.
+pub fn link_tickets(
+    conn: &mut DbConnection,
+    ticket1_id: i32,
+    ticket2_id: i32,
+    link_type: TicketLinkType,
+    created_by: Option<uuid::Uuid>,
+) -> QueryResult<()> {
     use crate::schema::linked_tickets;
+    use diesel::upsert::excluded;
 
-    debug!(ticket1_id, ticket2_id, "Linking tickets");
+    debug!(ticket1_id, ticket2_id, link_type = %link_type, "Linking tickets");
 
     // First, check if the tickets exist
     let ticket1 = crate::repository::tickets::get_ticket_by_id(conn, ticket1_id)?;
@@ -35,47 +116,32 @@ pub fn link_tickets(conn: &mut DbConnection, ticket1_id: i32, ticket2_id: i32) -
     debug!(id = ticket1.id, title = %ticket1.title, "Found ticket1");
     debug!(id = ticket2.id, title = %ticket2.title, "Found ticket2");
 
-    // Check if the links already exist
-    let existing_links_1_to_2 = linked_tickets::table
-        .filter(linked_tickets::ticket_id.eq(ticket1_id))
-        .filter(linked_tickets::linked_ticket_id.eq(ticket2_id))
-        .count()
-        .get_result::<i64>(conn)?;
-
-    let existing_links_2_to_1 = linked_tickets::table
-        .filter(linked_tickets::ticket_id.eq(ticket2_id))
-        .filter(linked_tickets::linked_ticket_id.eq(ticket1_id))
-        .count()
-        .get_result::<i64>(conn)?;
-
-    debug!(from = ticket1_id, to = ticket2_id, count = existing_links_1_to_2, "Existing links");
-    debug!(from = ticket2_id, to = ticket1_id, count = existing_links_2_to_1, "Existing links");
-
     // Create bidirectional links
-    let new_link1 = NewLinkedTicket {
-        ticket_id: ticket1.id,
-        linked_ticket_id: ticket2.id,
-    };
-
-    let new_link2 = NewLinkedTicket {
-        ticket_id: ticket2.id,
-        linked_ticket_id: ticket1.id,
-    };
-
-    // Insert both links in a transaction
+    let new_links = vec![
+        NewLinkedTicket {
+            ticket_id: ticket1.id,
+            linked_ticket_id: ticket2.id,
+            link_type: link_type.as_str().to_string(),
+            created_by,
+        },
+        NewLinkedTicket {
+            ticket_id: ticket2.id,
+            linked_ticket_id: ticket1.id,
+            link_type: link_type.inverse().as_str().to_string(),
+            created_by,
+        },
+    ];
+
+    // Insert both links in a transaction, retyping the pair if it is already linked
     conn.transaction(|conn| {
-        let inserted_1_to_2 = diesel::insert_into(linked_tickets::table)
-            .values(&new_link1)
-            .on_conflict_do_nothing()
-            .execute(conn)?;
-
-        let inserted_2_to_1 = diesel::insert_into(linked_tickets::table)
-            .values(&new_link2)
-            .on_conflict_do_nothing()
+        let upserted = diesel::insert_into(linked_tickets::table)
+            .values(&new_links)
+            .on_conflict((linked_tickets::ticket_id, linked_tickets::linked_ticket_id))
+            .do_update()
+            .set(linked_tickets::link_type.eq(excluded(linked_tickets::link_type)))
             .execute(conn)?;
 
-        debug!(from = ticket1_id, to = ticket2_id, inserted = inserted_1_to_2, "Inserted links");
-        debug!(from = ticket2_id, to = ticket1_id, inserted = inserted_2_to_1, "Inserted links");
+        debug!(ticket1_id, ticket2_id, upserted, "Upserted links");
 
         Ok(())
     })
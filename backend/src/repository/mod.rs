@@ -1,12 +1,17 @@
 // Domain-specific modules
+pub mod approvals;
 pub mod article_content;
 pub mod assignment_rules;
+pub mod catalog;
 pub mod categories;
+pub mod changes;
 pub mod comments;
 pub mod devices;
 pub mod documentation;
 pub mod groups;
+pub mod kb_suggestions;
 pub mod linked_tickets;
+pub mod problems;
 pub mod projects;
 pub mod sync_history;
 pub mod tickets;
@@ -27,6 +32,15 @@ pub mod site_settings;
 // Backup and restore
 pub mod backup;
 
+// Bulk CSV/XLSX imports
+pub mod import_jobs;
+
+// Soft-deleted records
+pub mod recycle_bin;
+
+// Data subject requests and retention
+pub mod privacy;
+
 // Re-export all functions
 pub use article_content::*;
 pub use comments::*;
@@ -0,0 +1,135 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::{
+    ActiveSession, Attachment, Comment, SecurityEvent, Ticket, TicketStatus, User, UserRole,
+};
+use crate::schema::{
+    active_sessions, attachments, comments, refresh_tokens, reset_tokens, security_events, tickets,
+    user_auth_identities, user_emails, user_groups, user_ticket_views, users,
+};
+
+/// Name given to a user once their personal data has been erased
+pub const ERASED_USER_NAME: &str = "Deleted user";
+
+// ============================================================================
+// Subject access
+// ============================================================================
+
+/// Tickets the user requested or is assigned to, including ones in the recycle bin
+pub fn get_subject_tickets(conn: &mut DbConnection, user_uuid: Uuid) -> QueryResult<Vec<Ticket>> {
+    tickets::table
+        .filter(
+            tickets::requester_uuid.eq(user_uuid)
+                .or(tickets::assignee_uuid.eq(user_uuid)),
+        )
+        .order(tickets::id.asc())
+        .load(conn)
+}
+
+/// Comments written by the user
+pub fn get_subject_comments(conn: &mut DbConnection, user_uuid: Uuid) -> QueryResult<Vec<Comment>> {
+    comments::table
+        .filter(comments::user_uuid.eq(user_uuid))
+        .order(comments::id.asc())
+        .load(conn)
+}
+
+/// Attachments the user uploaded or that belong to their comments
+pub fn get_subject_attachments(conn: &mut DbConnection, user_uuid: Uuid) -> QueryResult<Vec<Attachment>> {
+    let comment_ids = comments::table
+        .filter(comments::user_uuid.eq(user_uuid))
+        .select(comments::id);
+
+    attachments::table
+        .filter(
+            attachments::uploaded_by.eq(user_uuid)
+                .or(attachments::comment_id.eq_any(comment_ids.nullable())),
+        )
+        .order(attachments::id.asc())
+        .load(conn)
+}
+
+/// All of the user's sessions, expired ones included
+pub fn get_subject_sessions(conn: &mut DbConnection, user_uuid: Uuid) -> QueryResult<Vec<ActiveSession>> {
+    active_sessions::table
+        .filter(active_sessions::user_uuid.eq(user_uuid))
+        .order(active_sessions::created_at.asc())
+        .load(conn)
+}
+
+/// Security events recorded for the user
+pub fn get_subject_security_events(conn: &mut DbConnection, user_uuid: Uuid) -> QueryResult<Vec<SecurityEvent>> {
+    security_events::table
+        .filter(security_events::user_uuid.eq(user_uuid))
+        .order(security_events::created_at.asc())
+        .load(conn)
+}
+
+// ============================================================================
+// Erasure
+// ============================================================================
+
+/// Strip a user's personal data while keeping the account row, so tickets and comments that
+/// reference it stay intact
+///
+/// Clears the profile and MFA secrets, demotes the account to a regular user and removes its
+/// email addresses, sign-in identities, sessions, tokens, security events, ticket views and
+/// group memberships. Returns the user as it was before erasure so the caller can remove its
+/// avatar and banner files once the transaction has committed.
+pub fn anonymise_user(conn: &mut DbConnection, user_uuid: Uuid, now: NaiveDateTime) -> QueryResult<User> {
+    conn.transaction(|conn| {
+        let user: User = users::table.find(user_uuid).first(conn)?;
+
+        diesel::update(users::table.find(user_uuid))
+            .set((
+                users::name.eq(ERASED_USER_NAME),
+                users::role.eq(UserRole::User),
+                users::updated_at.eq(now),
+                users::pronouns.eq(None::<String>),
+                users::avatar_url.eq(None::<String>),
+                users::banner_url.eq(None::<String>),
+                users::avatar_thumb.eq(None::<String>),
+                users::theme.eq(None::<String>),
+                users::microsoft_uuid.eq(None::<Uuid>),
+                users::mfa_secret.eq(None::<String>),
+                users::mfa_enabled.eq(false),
+                users::mfa_backup_codes.eq(None::<serde_json::Value>),
+                users::passkey_credentials.eq(None::<serde_json::Value>),
+            ))
+            .execute(conn)?;
+
+        diesel::delete(user_emails::table.filter(user_emails::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(user_auth_identities::table.filter(user_auth_identities::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(security_events::table.filter(security_events::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(active_sessions::table.filter(active_sessions::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(refresh_tokens::table.filter(refresh_tokens::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(reset_tokens::table.filter(reset_tokens::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(user_ticket_views::table.filter(user_ticket_views::user_uuid.eq(user_uuid))).execute(conn)?;
+        diesel::delete(user_groups::table.filter(user_groups::user_uuid.eq(user_uuid))).execute(conn)?;
+
+        Ok(user)
+    })
+}
+
+// ============================================================================
+// Retention
+// ============================================================================
+
+/// Tickets closed before `cutoff`
+pub fn get_tickets_closed_before(conn: &mut DbConnection, cutoff: NaiveDateTime) -> QueryResult<Vec<i32>> {
+    tickets::table
+        .into_boxed()
+        .filter(tickets::status.eq(TicketStatus::Closed))
+        .filter(tickets::closed_at.lt(cutoff))
+        .select(tickets::id)
+        .order(tickets::id.asc())
+        .load(conn)
+}
+
+/// Delete security events recorded before `cutoff`
+pub fn delete_security_events_before(conn: &mut DbConnection, cutoff: NaiveDateTime) -> QueryResult<usize> {
+    diesel::delete(security_events::table.filter(security_events::created_at.lt(cutoff))).execute(conn)
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+// ============================================================================
+// Problem Records
+// ============================================================================
+
+/// Create a problem ticket together with its detail row and empty notes
+pub fn create_problem(
+    conn: &mut DbConnection,
+    new_ticket: NewTicket,
+    details: NewProblemDetails,
+    created_by: Option<Uuid>,
+) -> QueryResult<(Ticket, ProblemDetails)> {
+    conn.transaction(|conn| {
+        let ticket = crate::repository::tickets::create_typed_ticket(conn, new_ticket, TicketType::Problem, created_by)?;
+
+        let details = diesel::insert_into(problem_details::table)
+            .values(&NewProblemDetails { ticket_id: ticket.id, ..details })
+            .get_result::<ProblemDetails>(conn)?;
+
+        Ok((ticket, details))
+    })
+}
+
+/// Get the detail row of a problem
+pub fn get_problem_details(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<ProblemDetails> {
+    problem_details::table.find(ticket_id).first(conn)
+}
+
+/// Update the detail row of a problem
+pub fn update_problem_details(
+    conn: &mut DbConnection,
+    ticket_id: i32,
+    update: ProblemDetailsUpdate,
+) -> QueryResult<ProblemDetails> {
+    diesel::update(problem_details::table.find(ticket_id))
+        .set(&update)
+        .get_result(conn)
+}
+
+/// List problems, optionally only known errors, newest first
+pub fn list_problems(conn: &mut DbConnection, known_errors_only: bool) -> QueryResult<Vec<ProblemListItem>> {
+    let mut query = tickets::table
+        .inner_join(problem_details::table)
+        .filter(tickets::ticket_type.eq(TicketType::Problem.as_str()))
+        .filter(tickets::deleted_at.is_null())
+        .into_boxed();
+
+    if known_errors_only {
+        query = query.filter(problem_details::is_known_error.eq(true));
+    }
+
+    let rows = query
+        .order(tickets::updated_at.desc())
+        .select((tickets::all_columns, problem_details::all_columns))
+        .load::<(Ticket, ProblemDetails)>(conn)?;
+
+    // Incidents are linked as "incident caused_by problem", stored on the problem as "causes"
+    let ids: Vec<i32> = rows.iter().map(|(ticket, _)| ticket.id).collect();
+    let incident_counts: HashMap<i32, i64> = linked_tickets::table
+        .filter(linked_tickets::ticket_id.eq_any(&ids))
+        .filter(linked_tickets::link_type.eq(TicketLinkType::Causes.as_str()))
+        .group_by(linked_tickets::ticket_id)
+        .select((linked_tickets::ticket_id, diesel::dsl::count_star()))
+        .load::<(i32, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    Ok(rows
+        .into_iter()
+        .map(|(ticket, details)| {
+            let incident_count = incident_counts.get(&ticket.id).copied().unwrap_or(0);
+            ProblemListItem { ticket, details, incident_count }
+        })
+        .collect())
+}
+
+/// Get a problem with its details, linked incidents and related changes
+pub fn get_problem_record(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<ProblemRecord> {
+    let ticket = tickets::table
+        .find(ticket_id)
+        .filter(tickets::ticket_type.eq(TicketType::Problem.as_str()))
+        .filter(tickets::deleted_at.is_null())
+        .first::<Ticket>(conn)?;
+    let details = get_problem_details(conn, ticket_id)?;
+
+    let relationships = crate::repository::linked_tickets::get_ticket_relationships(conn, ticket_id)?;
+
+    let incidents = tickets::table
+        .filter(tickets::id.eq_any(relationships.causes))
+        .order(tickets::created_at.desc())
+        .load::<Ticket>(conn)?;
+
+    let changes = tickets::table
+        .filter(tickets::id.eq_any(relationships.related))
+        .filter(tickets::ticket_type.eq(TicketType::Change.as_str()))
+        .order(tickets::created_at.desc())
+        .load::<Ticket>(conn)?;
+
+    Ok(ProblemRecord { ticket, details, incidents, changes })
+}
+
+/// Remove the caused-by link from an incident to a problem, and its inverse row
+/// Returns `false` when the incident is not linked to the problem that way.
+pub fn unlink_incident(conn: &mut DbConnection, problem_id: i32, incident_id: i32) -> QueryResult<bool> {
+    conn.transaction(|conn| {
+        let deleted = diesel::delete(
+            linked_tickets::table
+                .filter(linked_tickets::ticket_id.eq(incident_id))
+                .filter(linked_tickets::linked_ticket_id.eq(problem_id))
+                .filter(linked_tickets::link_type.eq(TicketLinkType::CausedBy.as_str())),
+        )
+        .execute(conn)?;
+        if deleted == 0 {
+            return Ok(false);
+        }
+
+        diesel::delete(
+            linked_tickets::table
+                .filter(linked_tickets::ticket_id.eq(problem_id))
+                .filter(linked_tickets::linked_ticket_id.eq(incident_id))
+                .filter(linked_tickets::link_type.eq(TicketLinkType::Causes.as_str())),
+        )
+        .execute(conn)?;
+        Ok(true)
+    })
+}
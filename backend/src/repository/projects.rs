@@ -10,19 +10,23 @@ use crate::schema::*;
 // Project operations
 #[allow(dead_code)]
 pub fn get_all_projects(conn: &mut DbConnection) -> QueryResult<Vec<Project>> {
-    projects::table.load(conn)
+    projects::table.filter(projects::deleted_at.is_null()).load(conn)
 }
 
 pub fn get_projects_with_ticket_count(conn: &mut DbConnection) -> Result<Vec<ProjectWithTicketCount>, Error> {
     // Get all projects
-    let all_projects = projects::table.load::<Project>(conn)?;
+    let all_projects = projects::table
+        .filter(projects::deleted_at.is_null())
+        .load::<Project>(conn)?;
     
     // For each project, count the tickets
     let mut projects_with_count = Vec::new();
     
     for project in all_projects {
         let count = project_tickets::table
+            .inner_join(tickets::table)
             .filter(project_tickets::project_id.eq(project.id))
+            .filter(tickets::deleted_at.is_null())
             .count()
             .get_result::<i64>(conn)?;
         
@@ -46,16 +50,20 @@ pub fn get_projects_with_ticket_count(conn: &mut DbConnection) -> Result<Vec<Pro
 pub fn get_project_by_id(conn: &mut DbConnection, project_id: i32) -> QueryResult<Project> {
     projects::table
         .find(project_id)
+        .filter(projects::deleted_at.is_null())
         .first(conn)
 }
 
 pub fn get_project_with_ticket_count(conn: &mut DbConnection, project_id: i32) -> Result<ProjectWithTicketCount, Error> {
     let project = projects::table
         .find(project_id)
+        .filter(projects::deleted_at.is_null())
         .first::<Project>(conn)?;
     
     let count = project_tickets::table
+        .inner_join(tickets::table)
         .filter(project_tickets::project_id.eq(project_id))
+        .filter(tickets::deleted_at.is_null())
         .count()
         .get_result::<i64>(conn)?;
     
@@ -93,9 +101,14 @@ pub fn update_project(conn: &mut DbConnection, project_id: i32, project_update:
         .get_result(conn)
 }
 
-pub fn delete_project(conn: &mut DbConnection, project_id: i32) -> QueryResult<usize> {
-    // This will also delete all project_tickets entries due to ON DELETE CASCADE
-    diesel::delete(projects::table.find(project_id)).execute(conn)
+/// Move a project to the recycle bin; its ticket associations are kept for a restore
+pub fn delete_project(conn: &mut DbConnection, project_id: i32, deleted_by: Option<uuid::Uuid>) -> QueryResult<usize> {
+    diesel::update(projects::table.find(project_id).filter(projects::deleted_at.is_null()))
+        .set((
+            projects::deleted_at.eq(chrono::Utc::now().naive_utc()),
+            projects::deleted_by.eq(deleted_by),
+        ))
+        .execute(conn)
 }
 
 // Project-Ticket association operations
@@ -110,7 +123,7 @@ pub fn add_ticket_to_project(conn: &mut DbConnection, project_id: i32, ticket_id
     }
 
     // Then check if the project exists
-    match projects::table.find(project_id).first::<Project>(conn) {
+    match projects::table.find(project_id).filter(projects::deleted_at.is_null()).first::<Project>(conn) {
         Ok(_) => debug!(project_id, "Project exists"),
         Err(e) => {
             warn!(project_id, error = ?e, "Project does not exist");
@@ -152,6 +165,7 @@ pub fn get_project_tickets(conn: &mut DbConnection, project_id: i32) -> QueryRes
     let raw_tickets: Vec<Ticket> = project_tickets::table
         .filter(project_tickets::project_id.eq(project_id))
         .inner_join(tickets::table)
+        .filter(tickets::deleted_at.is_null())
         .select(tickets::all_columns)
         .load::<Ticket>(conn)?;
 
@@ -183,6 +197,7 @@ pub fn get_projects_for_ticket(conn: &mut DbConnection, ticket_id: i32) -> Query
     project_tickets::table
         .filter(project_tickets::ticket_id.eq(ticket_id))
         .inner_join(projects::table)
+        .filter(projects::deleted_at.is_null())
         .select(projects::all_columns)
         .load::<Project>(conn)
 } 
\ No newline at end of file
@@ -0,0 +1,296 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::QueryResult;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+
+// ============= Listing =============
+
+pub fn get_deleted_tickets(conn: &mut DbConnection) -> QueryResult<Vec<Ticket>> {
+    tickets::table
+        .filter(tickets::deleted_at.is_not_null())
+        .order(tickets::deleted_at.desc())
+        .load(conn)
+}
+
+/// Deleted comments of tickets that aren't deleted themselves
+pub fn get_deleted_comments(conn: &mut DbConnection) -> QueryResult<Vec<Comment>> {
+    comments::table
+        .inner_join(tickets::table)
+        .filter(comments::deleted_at.is_not_null())
+        .filter(tickets::deleted_at.is_null())
+        .order(comments::deleted_at.desc())
+        .select(comments::all_columns)
+        .load(conn)
+}
+
+/// Attachments deleted on their own (not along with their comment), with their ticket
+pub fn get_deleted_attachments(conn: &mut DbConnection) -> QueryResult<Vec<(Attachment, Option<i32>)>> {
+    attachments::table
+        .left_join(comments::table)
+        .filter(attachments::deleted_at.is_not_null())
+        .filter(
+            comments::deleted_at
+                .is_null()
+                .or(comments::deleted_at.ne(attachments::deleted_at)),
+        )
+        .order(attachments::deleted_at.desc())
+        .select((attachments::all_columns, comments::ticket_id.nullable()))
+        .load(conn)
+}
+
+pub fn get_deleted_devices(conn: &mut DbConnection) -> QueryResult<Vec<Device>> {
+    devices::table
+        .filter(devices::deleted_at.is_not_null())
+        .order(devices::deleted_at.desc())
+        .load(conn)
+}
+
+pub fn get_deleted_projects(conn: &mut DbConnection) -> QueryResult<Vec<Project>> {
+    projects::table
+        .filter(projects::deleted_at.is_not_null())
+        .order(projects::deleted_at.desc())
+        .load(conn)
+}
+
+/// Every deleted page, subpages included
+pub fn get_deleted_documentation_pages(conn: &mut DbConnection) -> QueryResult<Vec<DocumentationPage>> {
+    documentation_pages::table
+        .filter(documentation_pages::deleted_at.is_not_null())
+        .order(documentation_pages::deleted_at.desc())
+        .load(conn)
+}
+
+/// When a record was deleted, or `None` if it's missing or not deleted
+pub fn get_deleted_at(
+    conn: &mut DbConnection,
+    item_type: RecycleBinItemType,
+    id: i32,
+) -> QueryResult<Option<NaiveDateTime>> {
+    let deleted_at: QueryResult<Option<NaiveDateTime>> = match item_type {
+        RecycleBinItemType::Ticket => tickets::table.find(id).select(tickets::deleted_at).first(conn),
+        RecycleBinItemType::Comment => comments::table.find(id).select(comments::deleted_at).first(conn),
+        RecycleBinItemType::Attachment => attachments::table.find(id).select(attachments::deleted_at).first(conn),
+        RecycleBinItemType::Device => devices::table.find(id).select(devices::deleted_at).first(conn),
+        RecycleBinItemType::Project => projects::table.find(id).select(projects::deleted_at).first(conn),
+        RecycleBinItemType::DocumentationPage => documentation_pages::table
+            .find(id)
+            .select(documentation_pages::deleted_at)
+            .first(conn),
+    };
+    Ok(deleted_at.optional()?.flatten())
+}
+
+/// The record a comment, attachment or subpage belongs to
+pub fn get_parent(
+    conn: &mut DbConnection,
+    item_type: RecycleBinItemType,
+    id: i32,
+) -> QueryResult<Option<(RecycleBinItemType, i32)>> {
+    let parent = match item_type {
+        RecycleBinItemType::Comment => comments::table
+            .find(id)
+            .select(comments::ticket_id)
+            .first::<i32>(conn)
+            .optional()?
+            .map(|ticket_id| (RecycleBinItemType::Ticket, ticket_id)),
+        RecycleBinItemType::Attachment => attachments::table
+            .find(id)
+            .select(attachments::comment_id)
+            .first::<Option<i32>>(conn)
+            .optional()?
+            .flatten()
+            .map(|comment_id| (RecycleBinItemType::Comment, comment_id)),
+        RecycleBinItemType::DocumentationPage => documentation_pages::table
+            .find(id)
+            .select(documentation_pages::parent_id)
+            .first::<Option<i32>>(conn)
+            .optional()?
+            .flatten()
+            .map(|parent_id| (RecycleBinItemType::DocumentationPage, parent_id)),
+        RecycleBinItemType::Ticket | RecycleBinItemType::Device | RecycleBinItemType::Project => None,
+    };
+    Ok(parent)
+}
+
+// ============= Restore =============
+
+/// Take a record out of the recycle bin
+///
+/// Attachments deleted along with a comment, and subpages deleted along with a page, come
+/// back with it; ones deleted separately stay in the bin.
+pub fn restore_item(conn: &mut DbConnection, item_type: RecycleBinItemType, id: i32) -> QueryResult<usize> {
+    let Some(deleted_at) = get_deleted_at(conn, item_type, id)? else {
+        return Ok(0);
+    };
+
+    conn.transaction(|conn| match item_type {
+        RecycleBinItemType::Ticket => diesel::update(tickets::table.find(id))
+            .set((tickets::deleted_at.eq(None::<NaiveDateTime>), tickets::deleted_by.eq(None::<Uuid>)))
+            .execute(conn),
+        RecycleBinItemType::Comment => {
+            diesel::update(
+                attachments::table
+                    .filter(attachments::comment_id.eq(id))
+                    .filter(attachments::deleted_at.eq(deleted_at)),
+            )
+            .set((attachments::deleted_at.eq(None::<NaiveDateTime>), attachments::deleted_by.eq(None::<Uuid>)))
+            .execute(conn)?;
+            diesel::update(comments::table.find(id))
+                .set((comments::deleted_at.eq(None::<NaiveDateTime>), comments::deleted_by.eq(None::<Uuid>)))
+                .execute(conn)
+        }
+        RecycleBinItemType::Attachment => diesel::update(attachments::table.find(id))
+            .set((attachments::deleted_at.eq(None::<NaiveDateTime>), attachments::deleted_by.eq(None::<Uuid>)))
+            .execute(conn),
+        RecycleBinItemType::Device => diesel::update(devices::table.find(id))
+            .set((devices::deleted_at.eq(None::<NaiveDateTime>), devices::deleted_by.eq(None::<Uuid>)))
+            .execute(conn),
+        RecycleBinItemType::Project => diesel::update(projects::table.find(id))
+            .set((projects::deleted_at.eq(None::<NaiveDateTime>), projects::deleted_by.eq(None::<Uuid>)))
+            .execute(conn),
+        RecycleBinItemType::DocumentationPage => {
+            let mut page_ids = vec![id];
+            let mut pending = vec![id];
+            while !pending.is_empty() {
+                let children: Vec<i32> = documentation_pages::table
+                    .filter(documentation_pages::parent_id.eq_any(&pending))
+                    .filter(documentation_pages::deleted_at.eq(deleted_at))
+                    .select(documentation_pages::id)
+                    .load(conn)?;
+                page_ids.extend(&children);
+                pending = children;
+            }
+
+            diesel::update(documentation_pages::table.filter(documentation_pages::id.eq_any(&page_ids)))
+                .set((
+                    documentation_pages::deleted_at.eq(None::<NaiveDateTime>),
+                    documentation_pages::deleted_by.eq(None::<Uuid>),
+                ))
+                .execute(conn)
+        }
+    })
+}
+
+// ============= Purge =============
+
+/// Ids of records deleted before `cutoff`
+pub fn get_expired_ids(
+    conn: &mut DbConnection,
+    item_type: RecycleBinItemType,
+    cutoff: NaiveDateTime,
+) -> QueryResult<Vec<i32>> {
+    match item_type {
+        RecycleBinItemType::Ticket => tickets::table
+            .filter(tickets::deleted_at.lt(cutoff))
+            .select(tickets::id)
+            .load(conn),
+        RecycleBinItemType::Comment => comments::table
+            .filter(comments::deleted_at.lt(cutoff))
+            .select(comments::id)
+            .load(conn),
+        RecycleBinItemType::Attachment => attachments::table
+            .filter(attachments::deleted_at.lt(cutoff))
+            .select(attachments::id)
+            .load(conn),
+        RecycleBinItemType::Device => devices::table
+            .filter(devices::deleted_at.lt(cutoff))
+            .select(devices::id)
+            .load(conn),
+        RecycleBinItemType::Project => projects::table
+            .filter(projects::deleted_at.lt(cutoff))
+            .select(projects::id)
+            .load(conn),
+        RecycleBinItemType::DocumentationPage => documentation_pages::table
+            .filter(documentation_pages::deleted_at.lt(cutoff))
+            .select(documentation_pages::id)
+            .load(conn),
+    }
+}
+
+/// Extract storage path from attachment URL
+/// Converts /uploads/tickets/123/filename.ext to tickets/123/filename.ext
+fn extract_storage_path_from_url(url: &str) -> Option<String> {
+    if url.starts_with("/uploads/tickets/") || url.starts_with("/uploads/temp/") {
+        Some(url.trim_start_matches("/uploads/").to_string())
+    } else {
+        None
+    }
+}
+
+/// Permanently delete attachment records, returning the storage paths of their files
+fn purge_attachments(conn: &mut DbConnection, attachment_ids: &[i32]) -> QueryResult<Vec<String>> {
+    let urls: Vec<String> = attachments::table
+        .filter(attachments::id.eq_any(attachment_ids))
+        .select(attachments::url)
+        .load(conn)?;
+    diesel::delete(attachments::table.filter(attachments::id.eq_any(attachment_ids))).execute(conn)?;
+
+    Ok(urls.iter().filter_map(|url| extract_storage_path_from_url(url)).collect())
+}
+
+/// Permanently delete a ticket with its comments, attachments, links and collaborative content
+///
+/// Returns the storage paths of the ticket's files; the caller deletes them once the
+/// transaction has committed.
+pub fn purge_ticket(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Vec<String>> {
+    conn.transaction(|conn| {
+        let comment_ids: Vec<i32> = comments::table
+            .filter(comments::ticket_id.eq(ticket_id))
+            .select(comments::id)
+            .load(conn)?;
+        let attachment_ids: Vec<i32> = attachments::table
+            .filter(attachments::comment_id.eq_any(&comment_ids))
+            .select(attachments::id)
+            .load(conn)?;
+        let paths = purge_attachments(conn, &attachment_ids)?;
+
+        diesel::delete(comments::table.filter(comments::ticket_id.eq(ticket_id))).execute(conn)?;
+        diesel::delete(linked_tickets::table.filter(
+            linked_tickets::ticket_id.eq(ticket_id)
+                .or(linked_tickets::linked_ticket_id.eq(ticket_id))
+        )).execute(conn)?;
+        diesel::delete(ticket_devices::table.filter(ticket_devices::ticket_id.eq(ticket_id))).execute(conn)?;
+        diesel::delete(project_tickets::table.filter(project_tickets::ticket_id.eq(ticket_id))).execute(conn)?;
+        diesel::delete(article_contents::table.filter(article_contents::ticket_id.eq(ticket_id))).execute(conn)?;
+        diesel::delete(tickets::table.find(ticket_id)).execute(conn)?;
+
+        Ok(paths)
+    })
+}
+
+/// Permanently delete a comment and its attachments, returning the attachments' storage paths
+pub fn purge_comment(conn: &mut DbConnection, comment_id: i32) -> QueryResult<Vec<String>> {
+    conn.transaction(|conn| {
+        let attachment_ids: Vec<i32> = attachments::table
+            .filter(attachments::comment_id.eq(comment_id))
+            .select(attachments::id)
+            .load(conn)?;
+        let paths = purge_attachments(conn, &attachment_ids)?;
+        diesel::delete(comments::table.find(comment_id)).execute(conn)?;
+        Ok(paths)
+    })
+}
+
+/// Permanently delete an attachment, returning its storage path
+pub fn purge_attachment(conn: &mut DbConnection, attachment_id: i32) -> QueryResult<Vec<String>> {
+    purge_attachments(conn, &[attachment_id])
+}
+
+/// Permanently delete a device (its ticket and group links go with it)
+pub fn purge_device(conn: &mut DbConnection, device_id: i32) -> QueryResult<usize> {
+    diesel::delete(devices::table.find(device_id)).execute(conn)
+}
+
+/// Permanently delete a project (its ticket associations go with it)
+pub fn purge_project(conn: &mut DbConnection, project_id: i32) -> QueryResult<usize> {
+    diesel::delete(projects::table.find(project_id)).execute(conn)
+}
+
+/// Permanently delete a page (its subpages and revisions go with it)
+pub fn purge_documentation_page(conn: &mut DbConnection, page_id: i32) -> QueryResult<usize> {
+    diesel::delete(documentation_pages::table.find(page_id)).execute(conn)
+}
@@ -1,5 +1,6 @@
 use diesel::prelude::*;
 use chrono::Utc;
+use uuid::Uuid;
 
 use crate::db::DbConnection;
 use crate::models::{RefreshToken, NewRefreshToken};
@@ -39,3 +40,17 @@ pub fn revoke_refresh_token(
     .execute(conn)
 }
 
+
+/// Revoke every outstanding refresh token for a user
+pub fn revoke_user_refresh_tokens(
+    conn: &mut DbConnection,
+    user_uuid: &Uuid,
+) -> Result<usize, diesel::result::Error> {
+    diesel::update(
+        refresh_tokens::table
+            .filter(refresh_tokens::user_uuid.eq(user_uuid))
+            .filter(refresh_tokens::revoked_at.is_null())
+    )
+    .set(refresh_tokens::revoked_at.eq(Utc::now().naive_utc()))
+    .execute(conn)
+}
@@ -58,6 +58,24 @@ pub fn mark_token_as_used(
         .get_result(conn)
 }
 
+/// Mark a token as used unless it already is; returns the number of rows claimed (0 or 1)
+/// so that two concurrent requests cannot both act on a single-use token
+pub fn claim_unused_token(
+    conn: &mut DbConnection,
+    token_hash_value: &str,
+) -> QueryResult<usize> {
+    diesel::update(
+        reset_tokens::table
+            .filter(reset_tokens::token_hash.eq(token_hash_value))
+            .filter(reset_tokens::is_used.eq(false)),
+    )
+    .set((
+        reset_tokens::used_at.eq(Some(Utc::now())),
+        reset_tokens::is_used.eq(true),
+    ))
+    .execute(conn)
+}
+
 /// Count tokens for a user created within a time window (for rate limiting)
 pub fn count_recent_tokens(
     conn: &mut DbConnection,
@@ -93,13 +111,13 @@ pub fn invalidate_tokens_by_type(
     .execute(conn)
 }
 
-/// Validate and consume a reset token
-/// Returns Ok(user_uuid) if token is valid, unused, and not expired
-pub fn validate_and_consume_token(
+/// Look up a token and check that it is of the expected type, unused and not expired
+/// Does not consume the token (used to preview what a link will do before acting on it)
+pub fn find_valid_token(
     conn: &mut DbConnection,
     raw_token: &str,
     expected_token_type: &str,
-) -> Result<Uuid, String> {
+) -> Result<ResetToken, String> {
     // Hash the raw token to look it up
     let token_hash_value = ResetTokenUtils::hash_token(raw_token);
 
@@ -123,11 +141,32 @@ pub fn validate_and_consume_token(
         return Err("Token has expired".to_string());
     }
 
+    Ok(token)
+}
+
+/// Validate and consume a token, returning the full row (including metadata)
+pub fn consume_token(
+    conn: &mut DbConnection,
+    raw_token: &str,
+    expected_token_type: &str,
+) -> Result<ResetToken, String> {
+    let token = find_valid_token(conn, raw_token, expected_token_type)?;
+
     // Mark as used
-    mark_token_as_used(conn, &token_hash_value)
+    mark_token_as_used(conn, &token.token_hash)
         .map_err(|_| "Failed to mark token as used".to_string())?;
 
-    Ok(token.user_uuid)
+    Ok(token)
+}
+
+/// Validate and consume a reset token
+/// Returns Ok(user_uuid) if token is valid, unused, and not expired
+pub fn validate_and_consume_token(
+    conn: &mut DbConnection,
+    raw_token: &str,
+    expected_token_type: &str,
+) -> Result<Uuid, String> {
+    consume_token(conn, raw_token, expected_token_type).map(|token| token.user_uuid)
 }
 
 #[cfg(test)]
@@ -2,13 +2,11 @@ use diesel::prelude::*;
 use diesel::result::Error;
 use diesel::QueryResult;
 use uuid::Uuid;
-use std::sync::Arc;
-use tracing::{debug, warn};
+use tracing::debug;
 
 use crate::db::DbConnection;
 use crate::models::*;
 use crate::schema::*;
-use crate::utils::storage::Storage;
 
 // ============= Helper Functions for Enum Parsing =============
 
@@ -47,69 +45,71 @@ fn parse_status_filter(status_filter: &str) -> Vec<TicketStatus> {
 
 // Get all tickets
 pub fn get_all_tickets(conn: &mut DbConnection) -> QueryResult<Vec<Ticket>> {
-    tickets::table.load(conn)
+    tickets::table.filter(tickets::deleted_at.is_null()).load(conn)
 }
 
-// Get paginated tickets with filtering and sorting
-pub fn get_paginated_tickets(
-    conn: &mut DbConnection,
-    page: i64,
-    page_size: i64,
-    sort_field: Option<String>,
-    sort_direction: Option<String>,
-    search: Option<String>,
-    status: Option<String>,
-    priority: Option<String>,
-    category: Option<String>,
-    assignee: Option<String>,
-    requester: Option<String>,
-    created_after: Option<String>,
-    created_before: Option<String>,
-    created_on: Option<String>,
-    modified_after: Option<String>,
-    modified_before: Option<String>,
-    modified_on: Option<String>,
-    closed_after: Option<String>,
-    closed_before: Option<String>,
-    closed_on: Option<String>,
-) -> Result<(Vec<Ticket>, i64), Error> {
-    // Build the main query
-    let mut query = tickets::table.into_boxed();
-    
-    // Apply filters if provided
-    if let Some(search_term) = search.clone() {
+/// Filters shared by the ticket list and the ticket CSV export
+#[derive(Debug, Clone, Default)]
+pub struct TicketFilters {
+    pub search: Option<String>,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub category: Option<String>,
+    pub assignee: Option<String>,
+    pub requester: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub created_on: Option<String>,
+    pub modified_after: Option<String>,
+    pub modified_before: Option<String>,
+    pub modified_on: Option<String>,
+    pub closed_after: Option<String>,
+    pub closed_before: Option<String>,
+    pub closed_on: Option<String>,
+}
+
+/// Parse a YYYY-MM-DD filter value into a datetime at the given time of day
+fn parse_filter_date(value: &Option<String>, h: u32, m: u32, s: u32) -> Option<chrono::NaiveDateTime> {
+    value
+        .as_deref()
+        .and_then(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+        .and_then(|date| date.and_hms_opt(h, m, s))
+}
+
+/// Build a boxed query over non-deleted tickets with the given filters applied
+fn filtered_tickets_query(filters: &TicketFilters) -> tickets::BoxedQuery<'static, diesel::pg::Pg> {
+    let mut query = tickets::table.filter(tickets::deleted_at.is_null()).into_boxed();
+
+    if let Some(search_term) = &filters.search {
         if !search_term.is_empty() {
             let search_pattern = format!("%{}%", search_term.to_lowercase());
             query = query.filter(
                 tickets::title.ilike(search_pattern.clone())
-                    .or(tickets::description.ilike(search_pattern.clone()))
+                    .or(tickets::description.ilike(search_pattern))
                     .or(tickets::id.eq_any(
                         search_term.parse::<i32>().ok().map(|id| vec![id]).unwrap_or_default()
                     ))
             );
         }
     }
-    
+
     // Handle enum status filter (supports comma-separated values for multi-select)
-    if let Some(status_filter) = status.clone() {
+    if let Some(status_filter) = &filters.status {
         if status_filter != "all" {
-            let status_enums = parse_status_filter(&status_filter);
+            let status_enums = parse_status_filter(status_filter);
             if !status_enums.is_empty() {
                 query = query.filter(tickets::status.eq_any(status_enums));
             }
         }
     }
 
-    // Handle enum priority filter
-    if let Some(priority_filter) = priority.clone() {
+    if let Some(priority_filter) = &filters.priority {
         if priority_filter != "all" {
-            let priority_enum = parse_ticket_priority(&priority_filter);
-            query = query.filter(tickets::priority.eq(priority_enum));
+            query = query.filter(tickets::priority.eq(parse_ticket_priority(priority_filter)));
         }
     }
 
-    // Handle category filter
-    if let Some(category_filter) = category.clone() {
+    if let Some(category_filter) = &filters.category {
         if category_filter != "all" {
             if let Ok(category_id) = category_filter.parse::<i32>() {
                 query = query.filter(tickets::category_id.eq(Some(category_id)));
@@ -117,187 +117,161 @@ pub fn get_paginated_tickets(
         }
     }
 
-    // Build a separate count query with the same filters
-    let mut count_query = tickets::table.into_boxed();
-    
-    // Apply the same filters to the count query
-    if let Some(search_term) = search {
-        if !search_term.is_empty() {
-            let search_pattern = format!("%{}%", search_term.to_lowercase());
-            count_query = count_query.filter(
-                tickets::title.ilike(search_pattern.clone())
-                    .or(tickets::description.ilike(search_pattern.clone()))
-                    .or(tickets::id.eq_any(
-                        search_term.parse::<i32>().ok().map(|id| vec![id]).unwrap_or_default()
-                    ))
-            );
-        }
-    }
-    
-    // Handle enum status filter for count query (supports comma-separated values)
-    if let Some(status_filter) = status {
-        if status_filter != "all" {
-            let status_enums = parse_status_filter(&status_filter);
-            if !status_enums.is_empty() {
-                count_query = count_query.filter(tickets::status.eq_any(status_enums));
-            }
-        }
-    }
-
-    // Handle enum priority filter for count query
-    if let Some(priority_filter) = priority {
-        if priority_filter != "all" {
-            let priority_enum = parse_ticket_priority(&priority_filter);
-            count_query = count_query.filter(tickets::priority.eq(priority_enum));
-        }
-    }
-
-    // Handle category filter for count query
-    if let Some(category_filter) = category {
-        if category_filter != "all" {
-            if let Ok(category_id) = category_filter.parse::<i32>() {
-                count_query = count_query.filter(tickets::category_id.eq(Some(category_id)));
-            }
-        }
-    }
-
-    // Handle assignee filter for count query
-    if let Some(assignee_filter) = &assignee {
+    if let Some(assignee_filter) = &filters.assignee {
         if assignee_filter == "unassigned" {
-            // Filter for tickets with no assignee
-            count_query = count_query.filter(tickets::assignee_uuid.is_null());
             query = query.filter(tickets::assignee_uuid.is_null());
         } else if assignee_filter != "all" {
-            // Parse the UUID string
-            if let Ok(assignee_uuid) = uuid::Uuid::parse_str(assignee_filter) {
-                count_query = count_query.filter(tickets::assignee_uuid.eq(Some(assignee_uuid)));
+            if let Ok(assignee_uuid) = Uuid::parse_str(assignee_filter) {
                 query = query.filter(tickets::assignee_uuid.eq(Some(assignee_uuid)));
             }
         }
     }
 
-    // Handle requester filter
-    if let Some(requester_filter) = &requester {
+    if let Some(requester_filter) = &filters.requester {
         if requester_filter != "all" {
-            // Parse the UUID string
-            if let Ok(requester_uuid) = uuid::Uuid::parse_str(requester_filter) {
-                count_query = count_query.filter(tickets::requester_uuid.eq(Some(requester_uuid)));
+            if let Ok(requester_uuid) = Uuid::parse_str(requester_filter) {
                 query = query.filter(tickets::requester_uuid.eq(Some(requester_uuid)));
             }
         }
     }
 
-    // Handle date filtering
     // Created date filters
-    if let Some(created_after_str) = &created_after {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(created_after_str, "%Y-%m-%d") {
-            let datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            query = query.filter(tickets::created_at.ge(datetime));
-            count_query = count_query.filter(tickets::created_at.ge(datetime));
-        }
+    if let Some(datetime) = parse_filter_date(&filters.created_after, 0, 0, 0) {
+        query = query.filter(tickets::created_at.ge(datetime));
     }
-    
-    if let Some(created_before_str) = &created_before {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(created_before_str, "%Y-%m-%d") {
-            let datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::created_at.le(datetime));
-            count_query = count_query.filter(tickets::created_at.le(datetime));
-        }
+    if let Some(datetime) = parse_filter_date(&filters.created_before, 23, 59, 59) {
+        query = query.filter(tickets::created_at.le(datetime));
     }
-    
-    if let Some(created_on_str) = &created_on {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(created_on_str, "%Y-%m-%d") {
-            let start_datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            let end_datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::created_at.between(start_datetime, end_datetime));
-            count_query = count_query.filter(tickets::created_at.between(start_datetime, end_datetime));
-        }
+    if let (Some(start), Some(end)) = (
+        parse_filter_date(&filters.created_on, 0, 0, 0),
+        parse_filter_date(&filters.created_on, 23, 59, 59),
+    ) {
+        query = query.filter(tickets::created_at.between(start, end));
     }
-    
+
     // Modified date filters (using updated_at column)
-    if let Some(modified_after_str) = &modified_after {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(modified_after_str, "%Y-%m-%d") {
-            let datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            query = query.filter(tickets::updated_at.ge(datetime));
-            count_query = count_query.filter(tickets::updated_at.ge(datetime));
-        }
+    if let Some(datetime) = parse_filter_date(&filters.modified_after, 0, 0, 0) {
+        query = query.filter(tickets::updated_at.ge(datetime));
     }
-    
-    if let Some(modified_before_str) = &modified_before {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(modified_before_str, "%Y-%m-%d") {
-            let datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::updated_at.le(datetime));
-            count_query = count_query.filter(tickets::updated_at.le(datetime));
-        }
+    if let Some(datetime) = parse_filter_date(&filters.modified_before, 23, 59, 59) {
+        query = query.filter(tickets::updated_at.le(datetime));
     }
-    
-    if let Some(modified_on_str) = &modified_on {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(modified_on_str, "%Y-%m-%d") {
-            let start_datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            let end_datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::updated_at.between(start_datetime, end_datetime));
-            count_query = count_query.filter(tickets::updated_at.between(start_datetime, end_datetime));
-        }
+    if let (Some(start), Some(end)) = (
+        parse_filter_date(&filters.modified_on, 0, 0, 0),
+        parse_filter_date(&filters.modified_on, 23, 59, 59),
+    ) {
+        query = query.filter(tickets::updated_at.between(start, end));
     }
-    
-    // Closed date filtering
-    if let Some(closed_after_str) = &closed_after {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(closed_after_str, "%Y-%m-%d") {
-            let start_datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            query = query.filter(tickets::closed_at.gt(start_datetime));
-            count_query = count_query.filter(tickets::closed_at.gt(start_datetime));
-        }
+
+    // Closed date filters
+    if let Some(datetime) = parse_filter_date(&filters.closed_after, 0, 0, 0) {
+        query = query.filter(tickets::closed_at.gt(datetime));
     }
-    
-    if let Some(closed_before_str) = &closed_before {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(closed_before_str, "%Y-%m-%d") {
-            let end_datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::closed_at.lt(end_datetime));
-            count_query = count_query.filter(tickets::closed_at.lt(end_datetime));
-        }
+    if let Some(datetime) = parse_filter_date(&filters.closed_before, 23, 59, 59) {
+        query = query.filter(tickets::closed_at.lt(datetime));
     }
-    
-    if let Some(closed_on_str) = &closed_on {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(closed_on_str, "%Y-%m-%d") {
-            let start_datetime = date.and_hms_opt(0, 0, 0).unwrap();
-            let end_datetime = date.and_hms_opt(23, 59, 59).unwrap();
-            query = query.filter(tickets::closed_at.between(start_datetime, end_datetime));
-            count_query = count_query.filter(tickets::closed_at.between(start_datetime, end_datetime));
-        }
+    if let (Some(start), Some(end)) = (
+        parse_filter_date(&filters.closed_on, 0, 0, 0),
+        parse_filter_date(&filters.closed_on, 23, 59, 59),
+    ) {
+        query = query.filter(tickets::closed_at.between(start, end));
     }
-    
-    // Count total matching records (before pagination)
-    let total: i64 = count_query.count().get_result(conn)?;
-    
-    // Apply sorting to the main query
-    match (sort_field.as_deref(), sort_direction.as_deref()) {
-        (Some("id"), Some("asc")) => query = query.order(tickets::id.asc()),
-        (Some("id"), _) => query = query.order(tickets::id.desc()),
-        (Some("title"), Some("asc")) => query = query.order(tickets::title.asc()),
-        (Some("title"), _) => query = query.order(tickets::title.desc()),
-        (Some("status"), Some("asc")) => query = query.order(tickets::status.asc()),
-        (Some("status"), _) => query = query.order(tickets::status.desc()),
-        (Some("priority"), Some("asc")) => query = query.order(tickets::priority.asc()),
-        (Some("priority"), _) => query = query.order(tickets::priority.desc()),
-        (Some("created_at"), Some("asc")) => query = query.order(tickets::created_at.asc()),
-        (Some("created_at"), _) => query = query.order(tickets::created_at.desc()),
-        (Some("requester_uuid"), Some("asc")) => query = query.order(tickets::requester_uuid.asc()),
-        (Some("requester_uuid"), _) => query = query.order(tickets::requester_uuid.desc()),
-        (Some("assignee_uuid"), Some("asc")) => query = query.order(tickets::assignee_uuid.asc()),
-        (Some("assignee_uuid"), _) => query = query.order(tickets::assignee_uuid.desc()),
-        _ => query = query.order(tickets::id.desc()), // Default sort
+
+    query
+}
+
+/// Apply the list view's sort options to a filtered ticket query
+fn sort_tickets_query(
+    query: tickets::BoxedQuery<'static, diesel::pg::Pg>,
+    sort_field: Option<&str>,
+    sort_direction: Option<&str>,
+) -> tickets::BoxedQuery<'static, diesel::pg::Pg> {
+    match (sort_field, sort_direction) {
+        (Some("id"), Some("asc")) => query.order(tickets::id.asc()),
+        (Some("id"), _) => query.order(tickets::id.desc()),
+        (Some("title"), Some("asc")) => query.order(tickets::title.asc()),
+        (Some("title"), _) => query.order(tickets::title.desc()),
+        (Some("status"), Some("asc")) => query.order(tickets::status.asc()),
+        (Some("status"), _) => query.order(tickets::status.desc()),
+        (Some("priority"), Some("asc")) => query.order(tickets::priority.asc()),
+        (Some("priority"), _) => query.order(tickets::priority.desc()),
+        (Some("created_at"), Some("asc")) => query.order(tickets::created_at.asc()),
+        (Some("created_at"), _) => query.order(tickets::created_at.desc()),
+        (Some("requester_uuid"), Some("asc")) => query.order(tickets::requester_uuid.asc()),
+        (Some("requester_uuid"), _) => query.order(tickets::requester_uuid.desc()),
+        (Some("assignee_uuid"), Some("asc")) => query.order(tickets::assignee_uuid.asc()),
+        (Some("assignee_uuid"), _) => query.order(tickets::assignee_uuid.desc()),
+        _ => query.order(tickets::id.desc()), // Default sort
     }
-    
-    // Apply pagination
+}
+
+// Get paginated tickets with filtering and sorting
+pub fn get_paginated_tickets(
+    conn: &mut DbConnection,
+    page: i64,
+    page_size: i64,
+    sort_field: Option<String>,
+    sort_direction: Option<String>,
+    search: Option<String>,
+    status: Option<String>,
+    priority: Option<String>,
+    category: Option<String>,
+    assignee: Option<String>,
+    requester: Option<String>,
+    created_after: Option<String>,
+    created_before: Option<String>,
+    created_on: Option<String>,
+    modified_after: Option<String>,
+    modified_before: Option<String>,
+    modified_on: Option<String>,
+    closed_after: Option<String>,
+    closed_before: Option<String>,
+    closed_on: Option<String>,
+) -> Result<(Vec<Ticket>, i64), Error> {
+    let filters = TicketFilters {
+        search,
+        status,
+        priority,
+        category,
+        assignee,
+        requester,
+        created_after,
+        created_before,
+        created_on,
+        modified_after,
+        modified_before,
+        modified_on,
+        closed_after,
+        closed_before,
+        closed_on,
+    };
+
+    // Count total matching records (before pagination)
+    let total: i64 = filtered_tickets_query(&filters).count().get_result(conn)?;
+
     let offset = (page - 1) * page_size;
-    query = query.offset(offset).limit(page_size);
-    
-    // Execute the query
-    let results = query.load::<Ticket>(conn)?;
-    
+    let results = sort_tickets_query(
+        filtered_tickets_query(&filters),
+        sort_field.as_deref(),
+        sort_direction.as_deref(),
+    )
+    .offset(offset)
+    .limit(page_size)
+    .load::<Ticket>(conn)?;
+
     Ok((results, total))
 }
 
+/// Get every ticket matching the list filters, unpaginated (used by CSV export)
+pub fn get_filtered_tickets(
+    conn: &mut DbConnection,
+    filters: &TicketFilters,
+    sort_field: Option<&str>,
+    sort_direction: Option<&str>,
+) -> QueryResult<Vec<Ticket>> {
+    sort_tickets_query(filtered_tickets_query(filters), sort_field, sort_direction).load(conn)
+}
+
 // Get paginated tickets with user information for list views
 pub fn get_paginated_tickets_with_users(
     conn: &mut DbConnection,
@@ -362,6 +336,7 @@ pub fn get_paginated_tickets_with_users(
 pub fn get_ticket_by_id(conn: &mut DbConnection, ticket_id: i32) -> QueryResult<Ticket> {
     tickets::table
         .find(ticket_id)
+        .filter(tickets::deleted_at.is_null())
         .first(conn)
 }
 
@@ -371,8 +346,37 @@ pub fn create_ticket(conn: &mut DbConnection, new_ticket: NewTicket) -> QueryRes
         .get_result(conn)
 }
 
+/// Create a ticket of a specific type (problem, change) with empty notes
+pub fn create_typed_ticket(
+    conn: &mut DbConnection,
+    new_ticket: NewTicket,
+    ticket_type: TicketType,
+    created_by: Option<Uuid>,
+) -> QueryResult<Ticket> {
+    conn.transaction(|conn| {
+        let ticket = create_ticket(conn, new_ticket)?;
+
+        let ticket = diesel::update(tickets::table.find(ticket.id))
+            .set((
+                tickets::ticket_type.eq(ticket_type.as_str()),
+                tickets::created_by.eq(created_by),
+            ))
+            .get_result::<Ticket>(conn)?;
+
+        crate::repository::article_content::create_article_content(conn, NewArticleContent {
+            ticket_id: ticket.id,
+            yjs_state_vector: None,
+            yjs_document: None,
+            yjs_client_id: None,
+        })?;
+
+        Ok(ticket)
+    })
+}
+
+// Tickets in the recycle bin are read-only until restored; updating one is NotFound
 pub fn update_ticket(conn: &mut DbConnection, ticket_id: i32, ticket: NewTicket) -> QueryResult<Ticket> {
-    diesel::update(tickets::table.find(ticket_id))
+    diesel::update(tickets::table.find(ticket_id).filter(tickets::deleted_at.is_null()))
         .set(&ticket)
         .get_result(conn)
 }
@@ -381,89 +385,22 @@ pub fn update_ticket(conn: &mut DbConnection, ticket_id: i32, ticket: NewTicket)
 pub fn update_ticket_partial(conn: &mut DbConnection, ticket_id: i32, ticket_update: crate::models::TicketUpdate) -> QueryResult<Ticket> {
     debug!(ticket_id, update = ?ticket_update, "Updating ticket");
     
-    diesel::update(tickets::table.find(ticket_id))
+    diesel::update(tickets::table.find(ticket_id).filter(tickets::deleted_at.is_null()))
         .set(&ticket_update)
         .get_result(conn)
 }
 
-/// Comprehensive ticket deletion that cleans up all associated data and files
-pub async fn delete_ticket_with_cleanup(
-    conn: &mut DbConnection, 
-    ticket_id: i32,
-    storage: Arc<dyn Storage>
-) -> Result<usize, Error> {
-    // Start a transaction to ensure all operations succeed or fail together
-    conn.transaction(|conn| {
-        // 1. First, get all comments for this ticket to find attachments
-        let comments = crate::repository::comments::get_comments_by_ticket_id(conn, ticket_id)?;
-        
-        // 2. Collect all attachment paths for file cleanup
-        let mut attachment_paths = Vec::new();
-        for comment in &comments {
-            let attachments = crate::repository::comments::get_attachments_by_comment_id(conn, comment.id)?;
-            for attachment in &attachments {
-                // Extract the storage path from the URL
-                if let Some(storage_path) = extract_storage_path_from_url(&attachment.url) {
-                    attachment_paths.push(storage_path);
-                }
-                // Delete the attachment record
-                diesel::delete(crate::schema::attachments::table.find(attachment.id)).execute(conn)?;
-            }
-        }
-        
-        // 3. Delete all comments for this ticket
-        diesel::delete(crate::schema::comments::table.filter(crate::schema::comments::ticket_id.eq(ticket_id))).execute(conn)?;
-        
-        // 4. Delete linked tickets relationships
-        diesel::delete(crate::schema::linked_tickets::table.filter(
-            crate::schema::linked_tickets::ticket_id.eq(ticket_id)
-                .or(crate::schema::linked_tickets::linked_ticket_id.eq(ticket_id))
-        )).execute(conn)?;
-        
-        // 5. Delete ticket-device relationships
-        diesel::delete(crate::schema::ticket_devices::table.filter(
-            crate::schema::ticket_devices::ticket_id.eq(ticket_id)
-        )).execute(conn)?;
-        
-        // 6. Delete ticket-project relationships
-        diesel::delete(crate::schema::project_tickets::table.filter(
-            crate::schema::project_tickets::ticket_id.eq(ticket_id)
-        )).execute(conn)?;
-        
-        // 7. Delete article content
-        diesel::delete(crate::schema::article_contents::table.filter(
-            crate::schema::article_contents::ticket_id.eq(ticket_id)
-        )).execute(conn)?;
-        
-        // 8. Finally, delete the ticket itself
-        let result = diesel::delete(tickets::table.find(ticket_id)).execute(conn)?;
-        
-        // Return the attachment paths for file cleanup (outside transaction)
-        Ok((result, attachment_paths))
-    }).map(|(result, attachment_paths)| {
-        // Clean up files after successful database transaction
-        // This is done outside the transaction to avoid blocking the database
-        tokio::spawn(async move {
-            for path in attachment_paths {
-                if let Err(e) = storage.delete_file(&path).await {
-                    warn!(path, error = ?e, "Failed to delete file during ticket cleanup");
-                }
-            }
-        });
-        result
-    })
-}
-
-/// Extract storage path from attachment URL
-/// Converts /uploads/tickets/123/filename.ext to tickets/123/filename.ext
-fn extract_storage_path_from_url(url: &str) -> Option<String> {
-    if url.starts_with("/uploads/tickets/") {
-        Some(url.trim_start_matches("/uploads/").to_string())
-    } else if url.starts_with("/uploads/temp/") {
-        Some(url.trim_start_matches("/uploads/").to_string())
-    } else {
-        None
-    }
+/// Move a ticket to the recycle bin
+///
+/// Comments, attachments and links stay in place; the purge job removes them, along with the
+/// ticket's files, once the ticket has been in the recycle bin for the retention period.
+pub fn delete_ticket(conn: &mut DbConnection, ticket_id: i32, deleted_by: Option<Uuid>) -> QueryResult<usize> {
+    diesel::update(tickets::table.find(ticket_id).filter(tickets::deleted_at.is_null()))
+        .set((
+            tickets::deleted_at.eq(chrono::Utc::now().naive_utc()),
+            tickets::deleted_by.eq(deleted_by),
+        ))
+        .execute(conn)
 }
 
 // Composite operations for tickets
@@ -516,6 +453,9 @@ pub fn get_complete_ticket(conn: &mut DbConnection, ticket_id: i32) -> Result<Co
     // Get linked tickets
     let linked_tickets = crate::repository::linked_tickets::get_linked_tickets(conn, ticket_id).unwrap_or_default();
     debug!(ticket_id, count = linked_tickets.len(), "Found linked tickets");
+
+    // Group the same links by relationship type
+    let relationships = crate::repository::linked_tickets::get_ticket_relationships(conn, ticket_id).unwrap_or_default();
     
     // Get projects for this ticket
     let projects = crate::repository::projects::get_projects_for_ticket(conn, ticket_id).unwrap_or_default();
@@ -529,6 +469,7 @@ pub fn get_complete_ticket(conn: &mut DbConnection, ticket_id: i32) -> Result<Co
         comments: comments_with_attachments,
         article_content,
         linked_tickets,
+        relationships,
         projects,
     })
 }
@@ -661,6 +602,7 @@ pub fn get_devices_for_ticket(conn: &mut DbConnection, ticket_id: i32) -> QueryR
     ticket_devices::table
         .inner_join(devices::table)
         .filter(ticket_devices::ticket_id.eq(ticket_id))
+        .filter(devices::deleted_at.is_null())
         .select(devices::all_columns)
         .load(conn)
 }
@@ -670,6 +612,7 @@ pub fn get_tickets_for_device(conn: &mut DbConnection, device_id: i32) -> QueryR
     ticket_devices::table
         .inner_join(tickets::table)
         .filter(ticket_devices::device_id.eq(device_id))
+        .filter(tickets::deleted_at.is_null())
         .select(tickets::all_columns)
         .load(conn)
 } 
\ No newline at end of file
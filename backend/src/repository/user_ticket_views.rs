@@ -67,6 +67,7 @@ impl UserTicketViewsRepository {
         user_ticket_views::table
             .inner_join(tickets::table.on(user_ticket_views::ticket_id.eq(tickets::id)))
             .filter(user_ticket_views::user_uuid.eq(user_uuid_param))
+            .filter(tickets::deleted_at.is_null())
             .order(user_ticket_views::last_viewed_at.desc())
             .limit(limit)
             .select((
@@ -331,6 +331,15 @@ pub fn get_users_by_uuids(uuids: &[Uuid], conn: &mut DbConnection) -> Result<Vec
         .load::<User>(conn)
 }
 
+// Get all administrators (for system notifications)
+pub fn get_admin_users(conn: &mut DbConnection) -> Result<Vec<User>, Error> {
+    users::table
+        .into_boxed()
+        .filter(users::role.eq(UserRole::Admin))
+        .order_by(users::name.asc())
+        .load::<User>(conn)
+}
+
 // Count total users in the database (for onboarding check)
 pub fn count_users(conn: &mut DbConnection) -> Result<i64, Error> {
     users::table.count().get_result(conn)
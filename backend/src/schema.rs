@@ -45,6 +45,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    approval_chain_steps (id) {
+        id -> Int4,
+        chain_id -> Int4,
+        step_order -> Int4,
+        #[max_length = 20]
+        approver_type -> Varchar,
+        approver_user_uuid -> Nullable<Uuid>,
+        approver_group_id -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    approval_chains (id) {
+        id -> Int4,
+        category_id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        is_active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        created_by -> Nullable<Uuid>,
+    }
+}
+
 diesel::table! {
     article_content_revisions (id) {
         id -> Int4,
@@ -141,6 +167,8 @@ diesel::table! {
         uploaded_by -> Nullable<Uuid>,
         created_at -> Timestamptz,
         transcription -> Nullable<Text>,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -158,6 +186,72 @@ diesel::table! {
         created_by -> Nullable<Uuid>,
         created_at -> Timestamptz,
         completed_at -> Nullable<Timestamptz>,
+        schedule_id -> Nullable<Int4>,
+        remote_path -> Nullable<Text>,
+        progress_stage -> Nullable<Text>,
+        progress_current -> Int8,
+        progress_total -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    backup_schedules (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 100]
+        cron_expression -> Varchar,
+        include_sensitive -> Bool,
+        #[max_length = 255]
+        password_env -> Nullable<Varchar>,
+        password_file -> Nullable<Text>,
+        keep_daily -> Int4,
+        keep_weekly -> Int4,
+        upload_remote -> Bool,
+        enabled -> Bool,
+        last_run_at -> Nullable<Timestamptz>,
+        next_run_at -> Nullable<Timestamptz>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TicketPriority;
+
+    catalog_items (id) {
+        id -> Int4,
+        uuid -> Uuid,
+        category_id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        #[max_length = 50]
+        icon -> Nullable<Varchar>,
+        form_schema -> Jsonb,
+        #[max_length = 255]
+        title_template -> Nullable<Varchar>,
+        default_priority -> TicketPriority,
+        link_requester_devices -> Bool,
+        display_order -> Int4,
+        is_active -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        created_by -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    catalog_submissions (id) {
+        id -> Int4,
+        ticket_id -> Int4,
+        catalog_item_id -> Nullable<Int4>,
+        form_schema -> Jsonb,
+        answers -> Jsonb,
+        submitted_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -170,6 +264,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    change_details (ticket_id) {
+        ticket_id -> Int4,
+        #[max_length = 20]
+        risk_level -> Varchar,
+        planned_start -> Nullable<Timestamptz>,
+        planned_end -> Nullable<Timestamptz>,
+        implementation_plan -> Nullable<Text>,
+        backout_plan -> Nullable<Text>,
+        #[max_length = 20]
+        approval_status -> Varchar,
+        approved_by -> Nullable<Uuid>,
+        approved_at -> Nullable<Timestamptz>,
+        approval_note -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     comments (id) {
         id -> Int4,
@@ -180,6 +293,8 @@ diesel::table! {
         updated_at -> Timestamptz,
         is_edited -> Bool,
         edit_count -> Int4,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -222,6 +337,8 @@ diesel::table! {
         os_version -> Nullable<Varchar>,
         is_managed -> Nullable<Bool>,
         enrollment_date -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -266,6 +383,8 @@ diesel::table! {
         yjs_document -> Nullable<Bytea>,
         yjs_client_id -> Nullable<Int8>,
         has_unsaved_changes -> Bool,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -281,6 +400,7 @@ diesel::table! {
         created_at -> Timestamptz,
         created_by -> Uuid,
         change_summary -> Nullable<Text>,
+        contributed_by -> Array<Nullable<Uuid>>,
     }
 }
 
@@ -309,6 +429,42 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    import_jobs (id) {
+        id -> Uuid,
+        #[max_length = 20]
+        entity_type -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        dry_run -> Bool,
+        file_id -> Text,
+        mapping -> Jsonb,
+        progress_current -> Int8,
+        progress_total -> Nullable<Int8>,
+        created_count -> Int4,
+        updated_count -> Int4,
+        failed_count -> Int4,
+        errors -> Jsonb,
+        error_message -> Nullable<Text>,
+        created_by -> Nullable<Uuid>,
+        created_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    kb_suggestion_clicks (id) {
+        id -> Int4,
+        page_id -> Int4,
+        user_uuid -> Nullable<Uuid>,
+        ticket_id -> Nullable<Int4>,
+        #[max_length = 20]
+        source -> Varchar,
+        query -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     linked_tickets (ticket_id, linked_ticket_id) {
         ticket_id -> Int4,
@@ -321,6 +477,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    problem_details (ticket_id) {
+        ticket_id -> Int4,
+        root_cause -> Nullable<Text>,
+        workaround -> Nullable<Text>,
+        is_known_error -> Bool,
+        known_error_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     project_tickets (project_id, ticket_id) {
         project_id -> Int4,
@@ -346,6 +514,8 @@ diesel::table! {
         updated_at -> Timestamptz,
         created_by -> Nullable<Uuid>,
         owner_uuid -> Nullable<Uuid>,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -449,6 +619,39 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    ticket_approval_events (id) {
+        id -> Int4,
+        ticket_id -> Int4,
+        approval_id -> Nullable<Int4>,
+        #[max_length = 20]
+        action -> Varchar,
+        actor_uuid -> Nullable<Uuid>,
+        #[max_length = 20]
+        channel -> Varchar,
+        comment -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    ticket_approvals (id) {
+        id -> Int4,
+        ticket_id -> Int4,
+        step_order -> Int4,
+        #[max_length = 20]
+        approver_type -> Varchar,
+        approver_user_uuid -> Nullable<Uuid>,
+        approver_group_id -> Nullable<Int4>,
+        #[max_length = 20]
+        status -> Varchar,
+        decided_by -> Nullable<Uuid>,
+        decided_at -> Nullable<Timestamptz>,
+        comment -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     ticket_categories (id) {
         id -> Int4,
@@ -465,6 +668,7 @@ diesel::table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         created_by -> Nullable<Uuid>,
+        documentation_template_id -> Nullable<Int4>,
     }
 }
 
@@ -497,6 +701,13 @@ diesel::table! {
         closed_at -> Nullable<Timestamptz>,
         closed_by -> Nullable<Uuid>,
         category_id -> Nullable<Int4>,
+        merged_into_id -> Nullable<Int4>,
+        #[max_length = 20]
+        ticket_type -> Varchar,
+        #[max_length = 20]
+        approval_status -> Nullable<Varchar>,
+        deleted_at -> Nullable<Timestamptz>,
+        deleted_by -> Nullable<Uuid>,
     }
 }
 
@@ -589,6 +800,9 @@ diesel::table! {
 }
 
 diesel::joinable!(active_sessions -> users (user_uuid));
+diesel::joinable!(approval_chain_steps -> approval_chains (chain_id));
+diesel::joinable!(approval_chain_steps -> groups (approver_group_id));
+diesel::joinable!(approval_chains -> ticket_categories (category_id));
 diesel::joinable!(article_content_revisions -> article_contents (article_content_id));
 diesel::joinable!(article_contents -> tickets (ticket_id));
 diesel::joinable!(assignment_log -> assignment_rules (rule_id));
@@ -599,10 +813,17 @@ diesel::joinable!(assignment_rules -> groups (target_group_id));
 diesel::joinable!(assignment_rules -> ticket_categories (category_id));
 diesel::joinable!(attachments -> comments (comment_id));
 diesel::joinable!(attachments -> users (uploaded_by));
+diesel::joinable!(backup_jobs -> backup_schedules (schedule_id));
 diesel::joinable!(backup_jobs -> users (created_by));
+diesel::joinable!(backup_schedules -> users (created_by));
+diesel::joinable!(catalog_items -> ticket_categories (category_id));
+diesel::joinable!(catalog_submissions -> catalog_items (catalog_item_id));
+diesel::joinable!(catalog_submissions -> tickets (ticket_id));
 diesel::joinable!(category_group_visibility -> groups (group_id));
 diesel::joinable!(category_group_visibility -> ticket_categories (category_id));
 diesel::joinable!(category_group_visibility -> users (created_by));
+diesel::joinable!(change_details -> tickets (ticket_id));
+diesel::joinable!(change_details -> users (approved_by));
 diesel::joinable!(comments -> tickets (ticket_id));
 diesel::joinable!(comments -> users (user_uuid));
 diesel::joinable!(device_groups -> devices (device_id));
@@ -612,7 +833,12 @@ diesel::joinable!(documentation_pages -> tickets (ticket_id));
 diesel::joinable!(documentation_revisions -> documentation_pages (page_id));
 diesel::joinable!(documentation_revisions -> users (created_by));
 diesel::joinable!(groups -> users (created_by));
+diesel::joinable!(import_jobs -> users (created_by));
+diesel::joinable!(kb_suggestion_clicks -> documentation_pages (page_id));
+diesel::joinable!(kb_suggestion_clicks -> tickets (ticket_id));
+diesel::joinable!(kb_suggestion_clicks -> users (user_uuid));
 diesel::joinable!(linked_tickets -> users (created_by));
+diesel::joinable!(problem_details -> tickets (ticket_id));
 diesel::joinable!(project_tickets -> projects (project_id));
 diesel::joinable!(project_tickets -> tickets (ticket_id));
 diesel::joinable!(project_tickets -> users (created_by));
@@ -622,6 +848,11 @@ diesel::joinable!(security_events -> active_sessions (session_id));
 diesel::joinable!(security_events -> users (user_uuid));
 diesel::joinable!(site_settings -> users (updated_by));
 diesel::joinable!(sync_history -> users (initiated_by));
+diesel::joinable!(ticket_approval_events -> ticket_approvals (approval_id));
+diesel::joinable!(ticket_approval_events -> tickets (ticket_id));
+diesel::joinable!(ticket_approvals -> groups (approver_group_id));
+diesel::joinable!(ticket_approvals -> tickets (ticket_id));
+diesel::joinable!(ticket_categories -> documentation_pages (documentation_template_id));
 diesel::joinable!(ticket_categories -> users (created_by));
 diesel::joinable!(ticket_devices -> devices (device_id));
 diesel::joinable!(ticket_devices -> tickets (ticket_id));
@@ -633,4 +864,4 @@ diesel::joinable!(user_ticket_views -> tickets (ticket_id));
 diesel::joinable!(user_ticket_views -> users (user_uuid));
 
 diesel::allow_tables_to_appear_in_same_query!(
-    active_sessions,article_content_revisions,article_contents,assignment_log,assignment_rule_state,assignment_rules,attachments,backup_jobs,category_group_visibility,comments,device_groups,devices,documentation_pages,documentation_revisions,groups,linked_tickets,project_tickets,projects,refresh_tokens,reset_tokens,security_events,site_settings,sync_delta_tokens,sync_history,ticket_categories,ticket_devices,tickets,user_auth_identities,user_emails,user_groups,user_ticket_views,users,);
+    active_sessions,approval_chain_steps,approval_chains,article_content_revisions,article_contents,assignment_log,assignment_rule_state,assignment_rules,attachments,backup_jobs,backup_schedules,catalog_items,catalog_submissions,category_group_visibility,change_details,comments,device_groups,devices,documentation_pages,documentation_revisions,groups,import_jobs,kb_suggestion_clicks,linked_tickets,problem_details,project_tickets,projects,refresh_tokens,reset_tokens,security_events,site_settings,sync_delta_tokens,sync_history,ticket_approval_events,ticket_approvals,ticket_categories,ticket_devices,tickets,user_auth_identities,user_emails,user_groups,user_ticket_views,users,);
@@ -0,0 +1,416 @@
+//! Operator Tasks
+//!
+//! Account and maintenance operations that both the admin HTTP handlers and the
+//! `nosdesk-admin` command-line tool perform, so the two can't drift apart.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::{User, UserMfaUpdate};
+use crate::repository;
+use crate::utils::{self, auth::hash_password};
+
+/// Temp uploads older than this are removed by `cleanup_temp_files`
+pub const TEMP_FILE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug)]
+pub enum AdminError {
+    /// The submitted details were rejected; holds one message per problem
+    Validation(Vec<String>),
+    EmailExists,
+    PasswordHash,
+    Database(diesel::result::Error),
+}
+
+impl std::fmt::Display for AdminError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminError::Validation(errors) => write!(f, "Validation failed: {}", errors.join("; ")),
+            AdminError::EmailExists => write!(f, "Email address already exists in the system"),
+            AdminError::PasswordHash => write!(f, "Error processing password"),
+            AdminError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {}
+
+impl From<diesel::result::Error> for AdminError {
+    fn from(e: diesel::result::Error) -> Self {
+        AdminError::Database(e)
+    }
+}
+
+/// Check the name, email and password for a new admin account
+pub fn validate_admin_details(name: &str, email: &str, password: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let name = name.trim();
+    if name.is_empty() {
+        errors.push("name: Name is required".to_string());
+    } else if name.len() > 255 {
+        errors.push("name: Name must be less than 255 characters".to_string());
+    }
+
+    let email = email.trim();
+    if email.is_empty() {
+        errors.push("email: Email is required".to_string());
+    } else if email.len() > 255 {
+        errors.push("email: Email must be less than 255 characters".to_string());
+    } else if !email.contains('@') || !email.contains('.') {
+        errors.push("email: Invalid email format".to_string());
+    }
+
+    if password.len() < 8 {
+        errors.push("password: Password must be at least 8 characters long".to_string());
+    } else if password.len() > 128 {
+        errors.push("password: Password must be less than 128 characters".to_string());
+    }
+
+    errors
+}
+
+/// Create an admin account that signs in with a local password
+pub fn create_admin(conn: &mut DbConnection, name: &str, email: &str, password: &str) -> Result<User, AdminError> {
+    let errors = validate_admin_details(name, email, password);
+    if !errors.is_empty() {
+        return Err(AdminError::Validation(errors));
+    }
+
+    let password_hash = hash_password(password).map_err(|_| AdminError::PasswordHash)?;
+
+    let (normalized_name, normalized_email) = utils::normalization::normalize_user_data(name, email);
+    if repository::get_user_by_email(&normalized_email, conn).is_ok() {
+        return Err(AdminError::EmailExists);
+    }
+    let (new_user, primary_email) = utils::NewUserBuilder::admin_user(
+        normalized_name,
+        normalized_email.clone(),
+    ).build_with_email();
+
+    let (user, _) = repository::user_helpers::create_user_with_email(
+        new_user,
+        primary_email,
+        true,
+        Some("manual".to_string()),
+        conn,
+    )
+    .map_err(|e| {
+        let message = format!("{:?}", e);
+        if message.contains("duplicate") || message.contains("unique") {
+            AdminError::EmailExists
+        } else {
+            AdminError::Database(e)
+        }
+    })?;
+
+    // Local auth identity holding the password hash
+    use crate::schema::user_auth_identities;
+    use diesel::prelude::*;
+
+    #[derive(diesel::Insertable)]
+    #[diesel(table_name = user_auth_identities)]
+    struct NewLocalAuthIdentity {
+        user_uuid: Uuid,
+        provider_type: String,
+        external_id: String,
+        email: Option<String>,
+        password_hash: Option<String>,
+    }
+
+    let auth_identity = NewLocalAuthIdentity {
+        user_uuid: user.uuid,
+        provider_type: "local".to_string(),
+        external_id: normalized_email.clone(),
+        email: Some(normalized_email),
+        password_hash: Some(password_hash),
+    };
+
+    if let Err(e) = diesel::insert_into(user_auth_identities::table)
+        .values(&auth_identity)
+        .execute(conn)
+    {
+        // Don't leave an account nobody can sign in to
+        let _ = repository::users::delete_user(&user.uuid, conn);
+        return Err(AdminError::Database(e));
+    }
+
+    info!(user_name = %user.name, "Admin user created");
+    Ok(user)
+}
+
+/// Turn off a user's TOTP MFA, clearing the secret and backup codes
+pub fn reset_mfa(conn: &mut DbConnection, user_uuid: &Uuid) -> Result<User, diesel::result::Error> {
+    let mfa_update = UserMfaUpdate {
+        mfa_enabled: Some(false),
+        mfa_secret: None, // Clear the secret
+        mfa_backup_codes: Some(serde_json::Value::Null), // Clear backup codes
+        updated_at: Some(chrono::Utc::now().naive_utc()),
+    };
+    repository::update_user_mfa(user_uuid, mfa_update, conn)
+}
+
+/// Sign a user out everywhere: delete their sessions and revoke their refresh tokens
+///
+/// Returns the number of sessions and refresh tokens revoked.
+pub fn revoke_all_sessions(conn: &mut DbConnection, user_uuid: &Uuid) -> Result<(usize, usize), diesel::result::Error> {
+    let sessions = repository::active_sessions::revoke_other_sessions(conn, user_uuid, None)?;
+    let refresh_tokens = repository::refresh_tokens::revoke_user_refresh_tokens(conn, user_uuid)?;
+    info!(user_uuid = %user_uuid, sessions, refresh_tokens, "Revoked all sessions");
+    Ok((sessions, refresh_tokens))
+}
+
+/// Directory chunked and in-progress uploads are written to
+pub fn temp_dir() -> PathBuf {
+    let storage_path = std::env::var("STORAGE_PATH").unwrap_or_else(|_| "uploads".to_string());
+    Path::new(&storage_path).join("temp")
+}
+
+/// Outcome of a temp file cleanup
+#[derive(Debug, Default, Serialize)]
+pub struct TempCleanupStats {
+    pub files_checked: usize,
+    pub files_removed: usize,
+    pub bytes_freed: u64,
+    pub bytes_freed_mb: u64,
+    pub errors: Vec<String>,
+}
+
+/// Remove files in `temp_dir` last modified more than `max_age` ago
+pub fn cleanup_temp_files(temp_dir: &Path, max_age: Duration) -> TempCleanupStats {
+    let mut stats = TempCleanupStats::default();
+
+    if let Ok(entries) = std::fs::read_dir(temp_dir) {
+        for entry in entries.flatten() {
+            stats.files_checked += 1;
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+
+            let Ok(metadata) = entry.metadata() else { continue };
+            let Ok(modified) = metadata.modified() else { continue };
+            let Ok(age) = SystemTime::now().duration_since(modified) else { continue };
+            if age <= max_age {
+                continue;
+            }
+
+            let size = metadata.len();
+            if let Err(e) = std::fs::remove_file(&path) {
+                stats.errors.push(format!("Failed to delete {:?}: {}", path, e));
+            } else {
+                stats.files_removed += 1;
+                stats.bytes_freed += size;
+                debug!(path = ?path, age_hours = age.as_secs() / 3600, "Removed stale temp file");
+            }
+        }
+    } else {
+        info!(temp_dir = %temp_dir.display(), "Temp directory does not exist or is not accessible");
+    }
+
+    stats.bytes_freed_mb = stats.bytes_freed / (1024 * 1024);
+    info!(
+        files_checked = stats.files_checked,
+        files_removed = stats.files_removed,
+        bytes_freed_mb = stats.bytes_freed_mb,
+        "Temp file cleanup completed"
+    );
+    stats
+}
+
+/// Outcome of a configuration check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One line of the configuration diagnostics
+#[derive(Debug, Serialize)]
+pub struct ConfigCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl ConfigCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+fn env_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| !value.trim().is_empty())
+}
+
+/// Database URL with the password removed
+fn redact_database_url(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme_end), Some(at)) if at > scheme_end => {
+            let credentials = &url[scheme_end + 3..at];
+            let user = credentials.split(':').next().unwrap_or_default();
+            format!("{}{}:***{}", &url[..scheme_end + 3], user, &url[at..])
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Check the environment the server reads its configuration from
+///
+/// Applies the same rules the server enforces at startup, without exiting on the first failure.
+pub fn configuration_checks() -> Vec<ConfigCheck> {
+    use crate::config_utils;
+
+    let production = std::env::var("ENVIRONMENT").is_ok_and(|env| env == "production");
+    let strict = if production { CheckStatus::Error } else { CheckStatus::Warning };
+    let mut checks = vec![ConfigCheck::new(
+        "ENVIRONMENT",
+        CheckStatus::Ok,
+        std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()),
+    )];
+
+    checks.push(match std::env::var("DATABASE_URL") {
+        Ok(url) => ConfigCheck::new("DATABASE_URL", CheckStatus::Ok, redact_database_url(&url)),
+        Err(_) => ConfigCheck::new("DATABASE_URL", CheckStatus::Error, "not set"),
+    });
+
+    checks.push(match std::env::var("JWT_SECRET") {
+        Ok(secret) if secret.len() >= 32 => ConfigCheck::new("JWT_SECRET", CheckStatus::Ok, "set"),
+        Ok(_) => ConfigCheck::new("JWT_SECRET", strict, "shorter than 32 characters"),
+        Err(_) => ConfigCheck::new("JWT_SECRET", CheckStatus::Error, "not set"),
+    });
+
+    checks.push(match std::env::var("MFA_ENCRYPTION_KEY") {
+        Ok(key) if key.len() == 64 && hex::decode(&key).is_ok() => {
+            ConfigCheck::new("MFA_ENCRYPTION_KEY", CheckStatus::Ok, "set")
+        }
+        Ok(_) => ConfigCheck::new("MFA_ENCRYPTION_KEY", CheckStatus::Error, "must be 64 hex characters"),
+        Err(_) => ConfigCheck::new("MFA_ENCRYPTION_KEY", strict, "not set; MFA is disabled"),
+    });
+
+    checks.push(match crate::utils::storage::get_storage_config() {
+        crate::utils::storage::StorageConfig::Local { base_path } => {
+            ConfigCheck::new("Storage", CheckStatus::Ok, format!("local ({})", base_path))
+        }
+        crate::utils::storage::StorageConfig::S3 { bucket, .. } => {
+            ConfigCheck::new("Storage", CheckStatus::Ok, format!("S3 bucket {}", bucket))
+        }
+    });
+
+    let microsoft_missing: Vec<&str> = [
+        ("MICROSOFT_CLIENT_ID", config_utils::get_microsoft_client_id().is_ok()),
+        ("MICROSOFT_TENANT_ID", config_utils::get_microsoft_tenant_id().is_ok()),
+        ("MICROSOFT_CLIENT_SECRET", config_utils::get_microsoft_client_secret().is_ok()),
+        ("MICROSOFT_REDIRECT_URI", config_utils::get_microsoft_redirect_uri().is_ok()),
+    ]
+    .into_iter()
+    .filter(|(_, present)| !present)
+    .map(|(name, _)| name)
+    .collect();
+    checks.push(match microsoft_missing.len() {
+        0 => ConfigCheck::new("Microsoft Graph", CheckStatus::Ok, "configured"),
+        4 => ConfigCheck::new("Microsoft Graph", CheckStatus::Ok, "not configured"),
+        _ => ConfigCheck::new("Microsoft Graph", CheckStatus::Warning, format!("missing {}", microsoft_missing.join(", "))),
+    });
+
+    checks.push(if !config_utils::is_oidc_enabled() {
+        ConfigCheck::new("OIDC", CheckStatus::Ok, "disabled")
+    } else if config_utils::get_oidc_client_id().is_err()
+        || config_utils::get_oidc_client_secret().is_err()
+        || config_utils::get_oidc_issuer_url().is_err()
+    {
+        ConfigCheck::new("OIDC", CheckStatus::Error, "enabled, but the client ID, secret or issuer URL is missing")
+    } else {
+        ConfigCheck::new("OIDC", CheckStatus::Ok, format!("enabled ({})", config_utils::get_oidc_display_name()))
+    });
+
+    checks.push(ConfigCheck::new(
+        "REDIS_URL",
+        if env_set("REDIS_URL") { CheckStatus::Ok } else { CheckStatus::Warning },
+        redact_database_url(&crate::utils::rate_limit::get_redis_url()),
+    ));
+
+    checks.push(match crate::services::backup_integrity::signing_algorithm() {
+        Some(algorithm) => ConfigCheck::new("Backup signing", CheckStatus::Ok, algorithm),
+        None => ConfigCheck::new("Backup signing", CheckStatus::Warning, "no key configured; backups are unsigned"),
+    });
+
+    checks.push(ConfigCheck::new(
+        "Off-site backups",
+        CheckStatus::Ok,
+        if env_set("BACKUP_S3_BUCKET") { "S3 configured" } else { "not configured" },
+    ));
+
+    let retention = crate::services::privacy::RetentionWindows::from_env();
+    let describe = |window: Option<chrono::Duration>| {
+        window.map_or_else(|| "kept".to_string(), |w| format!("{} days", w.num_days()))
+    };
+    checks.push(ConfigCheck::new(
+        "Data retention",
+        CheckStatus::Ok,
+        format!(
+            "recycle bin {} days, closed tickets {}, security events {}",
+            crate::services::recycle_bin::retention().num_days(),
+            describe(retention.closed_tickets),
+            describe(retention.security_events),
+        ),
+    ));
+
+    checks
+}
+
+/// Check the database is reachable and its schema is current
+pub fn database_checks(conn: &mut DbConnection) -> Vec<ConfigCheck> {
+    use crate::services::backup_migration;
+
+    let mut checks = Vec::new();
+
+    checks.push(match repository::count_users(conn) {
+        Ok(0) => ConfigCheck::new("Users", CheckStatus::Warning, "none; initial setup required"),
+        Ok(count) => ConfigCheck::new("Users", CheckStatus::Ok, count.to_string()),
+        Err(e) => ConfigCheck::new("Users", CheckStatus::Error, e.to_string()),
+    });
+
+    checks.push(match (
+        backup_migration::applied_schema_version(conn),
+        backup_migration::latest_schema_version(),
+    ) {
+        (Ok(applied), Ok(latest)) if applied == latest => {
+            ConfigCheck::new("Schema", CheckStatus::Ok, applied.unwrap_or_else(|| "empty".to_string()))
+        }
+        (Ok(applied), Ok(latest)) => ConfigCheck::new(
+            "Schema",
+            CheckStatus::Warning,
+            format!(
+                "at {}, this build expects {}; run migrations",
+                applied.unwrap_or_else(|| "empty".to_string()),
+                latest.unwrap_or_default()
+            ),
+        ),
+        (Err(e), _) | (_, Err(e)) => ConfigCheck::new("Schema", CheckStatus::Error, e.to_string()),
+    });
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_admin_details() {
+        assert!(validate_admin_details("Ada", "ada@example.com", "correct horse").is_empty());
+
+        let errors = validate_admin_details("  ", "not-an-email", "short");
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("name:"));
+        assert!(errors[1].starts_with("email:"));
+        assert!(errors[2].starts_with("password:"));
+    }
+}
@@ -0,0 +1,369 @@
+//! Approval Service
+//!
+//! Runs the approval chain configured for a ticket's category.
+//!
+//! When a ticket enters a category with an active chain, each chain step is copied onto the
+//! ticket with its approver resolved at that moment (a `manager` step looks up the requester's
+//! manager in Microsoft Graph). Steps are decided in order; the first rejection ends the
+//! chain. Approvers are emailed a single-use link backed by the hashed reset token table.
+
+use diesel::prelude::*;
+use serde_json::json;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::repository;
+use crate::repository::approvals as approval_repo;
+use crate::utils::reset_tokens::{ResetTokenUtils, TokenType};
+
+/// Error type for approval operations
+#[derive(Debug)]
+pub enum ApprovalError {
+    NotFound,
+    NotPending,
+    NotAuthorized,
+    /// The emailed link was used by another request in the meantime
+    LinkUsed,
+    DatabaseError(diesel::result::Error),
+}
+
+impl std::fmt::Display for ApprovalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApprovalError::NotFound => write!(f, "Approval not found"),
+            ApprovalError::NotPending => write!(f, "This approval step is not awaiting a decision"),
+            ApprovalError::NotAuthorized => write!(f, "You are not an approver for this step"),
+            ApprovalError::LinkUsed => write!(f, "Token has already been used"),
+            ApprovalError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for ApprovalError {
+    fn from(e: diesel::result::Error) -> Self {
+        ApprovalError::DatabaseError(e)
+    }
+}
+
+/// Where a decision was made, stored on the audit trail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalChannel {
+    Web,
+    Email,
+    System,
+}
+
+impl ApprovalChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalChannel::Web => "web",
+            ApprovalChannel::Email => "email",
+            ApprovalChannel::System => "system",
+        }
+    }
+}
+
+/// A decision submitted for one step
+#[derive(Debug)]
+pub struct ApprovalDecision {
+    pub approve: bool,
+    pub comment: Option<String>,
+    pub channel: ApprovalChannel,
+    /// Hash of the emailed link's token, consumed together with the decision
+    pub token_hash: Option<String>,
+}
+
+/// Result of a decision on one step
+#[derive(Debug)]
+pub struct DecisionOutcome {
+    pub approval: TicketApproval,
+    /// Ticket approval status after the decision
+    pub ticket_status: TicketApprovalStatus,
+    /// The step that became pending, if the chain continues
+    pub next_step: Option<TicketApproval>,
+}
+
+/// Service for ticket approval chains
+pub struct ApprovalService;
+
+impl ApprovalService {
+    /// Start (or restart) the approval chain for a ticket's current category.
+    /// Open approvals from a previous category are cancelled first.
+    /// Returns the created steps, or an empty list when the category needs no approval.
+    pub async fn start(
+        conn: &mut DbConnection,
+        ticket: &Ticket,
+        requested_by: Option<Uuid>,
+    ) -> Result<Vec<TicketApproval>, ApprovalError> {
+        Self::cancel(conn, ticket.id, requested_by)?;
+
+        let chain = match ticket.category_id {
+            Some(category_id) => approval_repo::get_chain_for_category(conn, category_id)?,
+            None => None,
+        };
+        let Some(chain) = chain.filter(|c| c.chain.is_active && !c.steps.is_empty()) else {
+            // An outcome from the previous category no longer applies
+            if ticket.approval_status.is_some() {
+                approval_repo::set_ticket_approval_state(conn, ticket.id, None)?;
+            }
+            return Ok(Vec::new());
+        };
+
+        // Resolve approvers before writing anything; the manager lookup calls Graph
+        let mut new_approvals = Vec::with_capacity(chain.steps.len());
+        for (index, step) in chain.steps.iter().enumerate() {
+            let approver_type = step.approver_type.parse::<ApproverType>().unwrap_or(ApproverType::User);
+            let approver_user_uuid = match approver_type {
+                ApproverType::Manager => Self::resolve_manager(conn, ticket.requester_uuid).await,
+                _ => step.approver_user_uuid,
+            };
+
+            let status = if index == 0 { ApprovalStepStatus::Pending } else { ApprovalStepStatus::Waiting };
+            new_approvals.push(NewTicketApproval {
+                ticket_id: ticket.id,
+                step_order: step.step_order,
+                approver_type: step.approver_type.clone(),
+                approver_user_uuid,
+                approver_group_id: step.approver_group_id,
+                status: status.as_str().to_string(),
+            });
+        }
+
+        let approvals = conn.transaction(|conn| {
+            let approvals = approval_repo::create_ticket_approvals(conn, new_approvals)?;
+            approval_repo::set_ticket_approval_state(conn, ticket.id, Some(TicketApprovalStatus::Pending))?;
+            approval_repo::record_event(conn, NewTicketApprovalEvent {
+                ticket_id: ticket.id,
+                approval_id: None,
+                action: "requested".to_string(),
+                actor_uuid: requested_by,
+                channel: ApprovalChannel::System.as_str().to_string(),
+                comment: Some(format!("Approval chain '{}' started", chain.chain.name)),
+            })?;
+            Ok::<_, diesel::result::Error>(approvals)
+        })?;
+
+        debug!(ticket_id = ticket.id, steps = approvals.len(), "Started approval chain");
+
+        if let Some(first) = approvals.first() {
+            Self::notify(conn, first, ticket).await;
+        }
+
+        Ok(approvals)
+    }
+
+    /// Cancel every open step of a ticket and clear its approval status.
+    /// Decided steps stay on record for the audit trail.
+    pub fn cancel(conn: &mut DbConnection, ticket_id: i32, actor: Option<Uuid>) -> Result<(), ApprovalError> {
+        conn.transaction(|conn| {
+            let open = approval_repo::get_open_ticket_approvals(conn, ticket_id)?;
+            if open.is_empty() {
+                return Ok(());
+            }
+
+            for approval in &open {
+                approval_repo::set_ticket_approval_status(conn, approval.id, ApprovalStepStatus::Cancelled)?;
+            }
+            approval_repo::set_ticket_approval_state(conn, ticket_id, None)?;
+            approval_repo::record_event(conn, NewTicketApprovalEvent {
+                ticket_id,
+                approval_id: None,
+                action: "cancelled".to_string(),
+                actor_uuid: actor,
+                channel: ApprovalChannel::System.as_str().to_string(),
+                comment: Some("Ticket category changed".to_string()),
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Whether a user may decide a step: its named approver, a member of its group,
+    /// or an admin (admins also decide steps whose approver could not be resolved)
+    pub fn can_decide(
+        conn: &mut DbConnection,
+        approval: &TicketApproval,
+        user_uuid: &Uuid,
+        is_admin: bool,
+    ) -> QueryResult<bool> {
+        if is_admin || approval.approver_user_uuid.as_ref() == Some(user_uuid) {
+            return Ok(true);
+        }
+        match approval.approver_group_id {
+            Some(group_id) => repository::groups::is_user_in_group(conn, user_uuid, group_id),
+            None => Ok(false),
+        }
+    }
+
+    /// Approve or reject a pending step and advance the chain
+    pub async fn decide(
+        conn: &mut DbConnection,
+        approval_id: i32,
+        actor: Uuid,
+        is_admin: bool,
+        decision: ApprovalDecision,
+    ) -> Result<DecisionOutcome, ApprovalError> {
+        let ApprovalDecision { approve, comment, channel, token_hash } = decision;
+        let comment = comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+        let outcome = conn.transaction(|conn| {
+            let approval = match approval_repo::get_ticket_approval(conn, approval_id) {
+                Ok(approval) => approval,
+                Err(diesel::result::Error::NotFound) => return Err(ApprovalError::NotFound),
+                Err(e) => return Err(e.into()),
+            };
+            if approval.status != ApprovalStepStatus::Pending.as_str() {
+                return Err(ApprovalError::NotPending);
+            }
+            if !Self::can_decide(conn, &approval, &actor, is_admin)? {
+                return Err(ApprovalError::NotAuthorized);
+            }
+            // A link is only spent once the decision is recorded; a failed decision leaves it usable
+            if let Some(token_hash) = &token_hash {
+                if repository::reset_tokens::claim_unused_token(conn, token_hash)? == 0 {
+                    return Err(ApprovalError::LinkUsed);
+                }
+            }
+
+            let (step_status, action) = if approve {
+                (ApprovalStepStatus::Approved, "approved")
+            } else {
+                (ApprovalStepStatus::Rejected, "rejected")
+            };
+            // Another decision may have landed since the read above
+            let approval = match approval_repo::decide_ticket_approval(conn, approval.id, step_status, Some(actor), comment.clone()) {
+                Ok(approval) => approval,
+                Err(diesel::result::Error::NotFound) => return Err(ApprovalError::NotPending),
+                Err(e) => return Err(e.into()),
+            };
+
+            approval_repo::record_event(conn, NewTicketApprovalEvent {
+                ticket_id: approval.ticket_id,
+                approval_id: Some(approval.id),
+                action: action.to_string(),
+                actor_uuid: Some(actor),
+                channel: channel.as_str().to_string(),
+                comment,
+            })?;
+
+            let remaining = approval_repo::get_open_ticket_approvals(conn, approval.ticket_id)?;
+            let (ticket_status, next_step) = if !approve {
+                for step in &remaining {
+                    approval_repo::set_ticket_approval_status(conn, step.id, ApprovalStepStatus::Skipped)?;
+                }
+                (TicketApprovalStatus::Rejected, None)
+            } else if let Some(next) = remaining.first() {
+                let next = approval_repo::set_ticket_approval_status(conn, next.id, ApprovalStepStatus::Pending)?;
+                (TicketApprovalStatus::Pending, Some(next))
+            } else {
+                (TicketApprovalStatus::Approved, None)
+            };
+
+            if ticket_status != TicketApprovalStatus::Pending {
+                approval_repo::set_ticket_approval_state(conn, approval.ticket_id, Some(ticket_status))?;
+            }
+
+            Ok(DecisionOutcome { approval, ticket_status, next_step })
+        })?;
+
+        if let Some(next) = &outcome.next_step {
+            match repository::tickets::get_ticket_by_id(conn, next.ticket_id) {
+                Ok(ticket) => Self::notify(conn, next, &ticket).await,
+                Err(e) => error!(ticket_id = next.ticket_id, error = ?e, "Failed to load ticket for approval notification"),
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Look up the local user who is the requester's manager in Microsoft Graph
+    async fn resolve_manager(conn: &mut DbConnection, requester_uuid: Option<Uuid>) -> Option<Uuid> {
+        let requester = repository::users::get_user_by_uuid(&requester_uuid?, conn).ok()?;
+        let microsoft_uuid = requester.microsoft_uuid?;
+
+        match crate::handlers::msgraph_integration::fetch_user_manager_id(&microsoft_uuid).await {
+            Ok(Some(manager_id)) => match repository::users::get_user_by_microsoft_uuid(conn, &manager_id) {
+                Ok(manager) => Some(manager.uuid),
+                Err(_) => {
+                    warn!(requester = %requester.uuid, "Requester's manager has no local account; step left for admins");
+                    None
+                }
+            },
+            Ok(None) => {
+                warn!(requester = %requester.uuid, "Requester has no manager in Microsoft Graph; step left for admins");
+                None
+            }
+            Err(e) => {
+                warn!(requester = %requester.uuid, error = %e, "Failed to look up manager; step left for admins");
+                None
+            }
+        }
+    }
+
+    /// Email every approver of a step a single-use decision link
+    async fn notify(conn: &mut DbConnection, approval: &TicketApproval, ticket: &Ticket) {
+        let recipients = match (approval.approver_user_uuid, approval.approver_group_id) {
+            (Some(user_uuid), _) => vec![user_uuid],
+            (None, Some(group_id)) => repository::groups::get_member_uuids_for_group(conn, group_id).unwrap_or_default(),
+            (None, None) => Vec::new(),
+        };
+        if recipients.is_empty() {
+            return;
+        }
+
+        let requester_name = ticket
+            .requester_uuid
+            .and_then(|uuid| repository::users::get_user_by_uuid(&uuid, conn).ok())
+            .map(|user| user.name)
+            .unwrap_or_else(|| "A user".to_string());
+        let base_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let branding = crate::utils::email_branding::get_email_branding(conn, &base_url);
+
+        let mut messages = Vec::new();
+        for user_uuid in recipients {
+            let Ok(user) = repository::users::get_user_by_uuid(&user_uuid, conn) else { continue };
+            let Some(email) = repository::user_helpers::get_primary_email(&user_uuid, conn) else { continue };
+
+            let token = ResetTokenUtils::create_reset_token(user_uuid, TokenType::ApprovalDecision);
+            if let Err(e) = repository::reset_tokens::create_reset_token(
+                conn,
+                &token.token_hash,
+                user_uuid,
+                token.token_type.as_str(),
+                None,
+                None,
+                token.expires_at,
+                Some(json!({ "approval_id": approval.id, "ticket_id": ticket.id })),
+            ) {
+                error!(approval_id = approval.id, error = ?e, "Failed to store approval token");
+                continue;
+            }
+            messages.push((email, user.name, token.raw_token));
+        }
+
+        let ticket_id = ticket.id;
+        let ticket_label = format!("#{}: {}", ticket.id, ticket.title);
+        tokio::spawn(async move {
+            let email_service = match crate::utils::email::EmailService::from_env() {
+                Ok(service) => service,
+                Err(e) => {
+                    warn!(error = ?e, "Email service unavailable; approval request not sent");
+                    return;
+                }
+            };
+            for (email, name, raw_token) in messages {
+                if let Err(e) = email_service.send_approval_request_email(
+                    &email,
+                    &name,
+                    &ticket_label,
+                    &requester_name,
+                    &raw_token,
+                    &branding,
+                ).await {
+                    warn!(ticket_id, error = %e, "Failed to send approval request email");
+                }
+            }
+        });
+    }
+}
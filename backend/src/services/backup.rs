@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{BufRead, BufReader, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use chrono::Utc;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Text;
+use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
-use walkdir::WalkDir;
 use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
 use ring::rand::{SecureRandom, SystemRandom};
 use ring::pbkdf2;
@@ -15,14 +18,30 @@ use ring::pbkdf2;
 use crate::db::DbConnection;
 use crate::models::{
     BackupManifest, TableManifest, FilesManifest, EncryptionManifest, RestorePreview,
-    BackupJobUpdate,
+    BackupJobUpdate, NewBackupJob,
 };
 use crate::repository::backup as backup_repo;
+use crate::services::backup_integrity::{self, HashingWriter};
+use crate::services::backup_migration::{self, StagingSchema};
+use crate::utils::image::generate_user_avatar_thumbnail;
+use crate::utils::storage::{get_content_type, Storage, StorageError};
 
 // Encryption constants
 const SALT_LENGTH: usize = 32;
 const PBKDF2_ITERATIONS: u32 = 100_000;
 
+/// Archive format version; 2.0 stores tables as NDJSON (`data/{table}.ndjson`), 2.1 adds
+/// per-entry checksums and an optional manifest signature
+const BACKUP_FORMAT_VERSION: &str = "2.1";
+/// Rows fetched per cursor batch when exporting a table
+const EXPORT_BATCH_SIZE: usize = 1000;
+/// Rows inserted per statement when restoring a table
+const RESTORE_BATCH_SIZE: usize = 500;
+/// Files copied between job progress updates
+const FILE_PROGRESS_INTERVAL: u64 = 50;
+/// Buffer for streaming file contents into the archive
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
 /// Sensitive fields to exclude or encrypt per table
 const SENSITIVE_FIELDS: &[(&str, &[&str])] = &[
     ("users", &["mfa_secret", "mfa_backup_codes"]),
@@ -48,6 +67,8 @@ const BACKUP_TABLES: &[&str] = &[
     "article_contents",
     "article_content_revisions",
     "linked_tickets",
+    "problem_details",
+    "change_details",
     "site_settings",
     "sync_history",
     "active_sessions",
@@ -67,6 +88,11 @@ pub enum BackupError {
     EncryptionError(String),
     InvalidPassword,
     CorruptedBackup(String),
+    StorageError(String),
+    /// The archive doesn't match its manifest; holds the problems found
+    VerificationFailed(Vec<String>),
+    /// Bringing an older archive up to the current schema failed
+    SchemaMigration(String),
 }
 
 impl std::fmt::Display for BackupError {
@@ -79,6 +105,11 @@ impl std::fmt::Display for BackupError {
             BackupError::EncryptionError(e) => write!(f, "Encryption error: {}", e),
             BackupError::InvalidPassword => write!(f, "Invalid password"),
             BackupError::CorruptedBackup(e) => write!(f, "Corrupted backup: {}", e),
+            BackupError::StorageError(e) => write!(f, "Storage error: {}", e),
+            BackupError::SchemaMigration(e) => write!(f, "Schema migration error: {}", e),
+            BackupError::VerificationFailed(problems) => {
+                write!(f, "Backup failed verification: {}", problems.join("; "))
+            }
         }
     }
 }
@@ -107,6 +138,12 @@ impl From<diesel::result::Error> for BackupError {
     }
 }
 
+impl From<StorageError> for BackupError {
+    fn from(e: StorageError) -> Self {
+        BackupError::StorageError(format!("{:?}", e))
+    }
+}
+
 /// Derive encryption key from password using PBKDF2
 fn derive_key(password: &str, salt: &[u8]) -> [u8; 32] {
     let mut key = [0u8; 32];
@@ -158,56 +195,119 @@ fn decrypt_data(encrypted_data: &[u8], key: &[u8; 32], nonce_bytes: &[u8; NONCE_
     Ok(plaintext.to_vec())
 }
 
-/// Export table data as JSON using raw SQL
-fn export_table_data(
-    conn: &mut DbConnection,
-    table_name: &str,
-    include_sensitive: bool,
-) -> Result<(serde_json::Value, i64), BackupError> {
-    use diesel::sql_query;
-    use diesel::sql_types::Text;
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[diesel(sql_type = Text)]
+    row_to_json: String,
+}
 
-    // Get all rows as JSON
-    let query = format!("SELECT row_to_json(t) FROM {} t", table_name);
+/// Sensitive fields of a table, if it has any
+fn sensitive_fields(table_name: &str) -> Option<&'static [&'static str]> {
+    SENSITIVE_FIELDS.iter()
+        .find(|(t, _)| *t == table_name)
+        .map(|(_, fields)| *fields)
+}
 
-    #[derive(QueryableByName)]
-    struct JsonRow {
-        #[diesel(sql_type = Text)]
-        row_to_json: String,
+/// Column identifying a row when sensitive fields are restored
+fn sensitive_key_column(table_name: &str) -> &'static str {
+    match table_name {
+        "users" => "uuid",
+        _ => "id",
     }
+}
 
-    let results: Vec<JsonRow> = sql_query(&query).load(conn)?;
+/// Double-quote an SQL identifier
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
 
-    let mut rows: Vec<serde_json::Value> = Vec::new();
-    for row in results {
-        let mut json_value: serde_json::Value = serde_json::from_str(&row.row_to_json)?;
+/// Record how far a job has got; failures are logged rather than failing the job
+fn report_progress(conn: &mut DbConnection, job_id: Option<Uuid>, stage: &str, current: i64, total: Option<i64>) {
+    if let Some(job_id) = job_id {
+        if let Err(e) = backup_repo::set_backup_job_progress(conn, job_id, stage, current, total) {
+            log::warn!("Failed to record progress for backup job {}: {}", job_id, e);
+        }
+    }
+}
 
-        // If not including sensitive data, remove sensitive fields
-        if !include_sensitive {
-            if let Some(fields) = SENSITIVE_FIELDS.iter()
-                .find(|(t, _)| *t == table_name)
-                .map(|(_, fields)| *fields)
-            {
-                if let serde_json::Value::Object(ref mut map) = json_value {
+/// Stream a table into the archive as NDJSON (one `row_to_json` object per line)
+///
+/// Rows are read through a server-side cursor in batches, so memory use doesn't grow with the
+/// table. Sensitive fields are always stripped from the archive entry; when `sensitive` is
+/// given, each row's key and sensitive fields are collected there for encryption. Returns the
+/// row count and the entry's SHA-256.
+fn export_table<W: Write + Seek>(
+    conn: &mut DbConnection,
+    zip: &mut ZipWriter<W>,
+    options: FileOptions,
+    table_name: &str,
+    mut sensitive: Option<&mut Vec<serde_json::Value>>,
+) -> Result<(i64, String), BackupError> {
+    zip.start_file(format!("data/{}.ndjson", table_name), options)?;
+    let mut entry = HashingWriter::new(zip);
+    let fields = sensitive_fields(table_name).unwrap_or(&[]);
+    let key_column = sensitive_key_column(table_name);
+
+    // Cursors only live inside a transaction
+    let count = conn.transaction::<_, BackupError, _>(|conn| {
+        sql_query(format!(
+            "DECLARE backup_export NO SCROLL CURSOR FOR SELECT row_to_json(t)::text AS row_to_json FROM {} t",
+            table_name
+        ))
+        .execute(conn)?;
+
+        let fetch = format!("FETCH {} FROM backup_export", EXPORT_BATCH_SIZE);
+        let mut count = 0i64;
+        loop {
+            let rows: Vec<JsonRow> = sql_query(&fetch).load(conn)?;
+            if rows.is_empty() {
+                break;
+            }
+
+            for row in rows {
+                let mut value: serde_json::Value = serde_json::from_str(&row.row_to_json)?;
+                if let serde_json::Value::Object(ref mut map) = value {
+                    if let Some(collected) = sensitive.as_deref_mut() {
+                        let subset: serde_json::Map<String, serde_json::Value> = map.iter()
+                            .filter(|(column, _)| *column == key_column || fields.contains(&column.as_str()))
+                            .map(|(column, value)| (column.clone(), value.clone()))
+                            .collect();
+                        collected.push(serde_json::Value::Object(subset));
+                    }
                     for field in fields {
                         map.remove(*field);
                     }
                 }
+
+                serde_json::to_writer(&mut entry, &value)?;
+                entry.write_all(b"\n")?;
+                count += 1;
             }
         }
 
-        rows.push(json_value);
-    }
+        sql_query("CLOSE backup_export").execute(conn)?;
+        Ok(count)
+    })?;
 
-    let count = rows.len() as i64;
-    Ok((serde_json::Value::Array(rows), count))
+    Ok((count, entry.finish()))
 }
 
-/// Get the uploads directory path
-fn get_uploads_dir() -> PathBuf {
-    std::env::var("UPLOAD_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/app/uploads"))
+/// Storage folders left out of backups: local backup archives and thumbnails (regenerated)
+const EXCLUDED_FILE_PREFIXES: &[&str] = &["backups/", "users/thumbs/"];
+
+/// Whether a stored file belongs in a backup
+fn is_backed_up_file(path: &str) -> bool {
+    !EXCLUDED_FILE_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// Storage path for a `files/` archive entry, rejecting absolute or parent-relative paths
+fn archive_file_path(entry_name: &str) -> Option<&str> {
+    let relative = entry_name.strip_prefix("files/")?;
+    let safe = !relative.is_empty()
+        && !relative.ends_with('/')
+        && !relative.starts_with('/')
+        && relative.split(['/', '\\']).all(|part| part != ".." && !part.is_empty());
+    safe.then_some(relative)
 }
 
 /// Get the backups directory path
@@ -219,8 +319,13 @@ fn get_backups_dir() -> PathBuf {
 }
 
 /// Create a backup export
+///
+/// Files are read through `storage`, so uploads kept in S3 are included. Storage calls are
+/// driven with the current Tokio handle, so this must run on a blocking thread
+/// (`spawn_blocking`), not directly on an async task.
 pub fn create_backup(
     conn: &mut DbConnection,
+    storage: &dyn Storage,
     job_id: Uuid,
     include_sensitive: bool,
     password: Option<&str>,
@@ -239,65 +344,67 @@ pub fn create_backup(
         .unix_permissions(0o644);
 
     let mut table_manifests = HashMap::new();
-    let mut sensitive_data: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut checksums = BTreeMap::new();
+    let mut sensitive_data: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+    let encrypt_sensitive = include_sensitive && password.is_some();
+    let table_total = BACKUP_TABLES.len() as i64;
 
     // Export each table
-    for table_name in BACKUP_TABLES {
-        // Always export without sensitive data first
-        let (data, count) = export_table_data(conn, table_name, false)?;
-
-        let json_content = serde_json::to_string_pretty(&data)?;
-        let path = format!("data/{}.json", table_name);
+    for (index, table_name) in BACKUP_TABLES.iter().enumerate() {
+        report_progress(conn, Some(job_id), &format!("Exporting {}", table_name), index as i64, Some(table_total));
 
-        zip.start_file(&path, options)?;
-        zip.write_all(json_content.as_bytes())?;
+        // If including sensitive data, also collect the sensitive fields for encryption
+        let mut sensitive_rows = Vec::new();
+        let collect_sensitive = encrypt_sensitive && sensitive_fields(table_name).is_some();
+        let (count, checksum) = export_table(conn, &mut zip, options, table_name, collect_sensitive.then_some(&mut sensitive_rows))?;
 
         table_manifests.insert(table_name.to_string(), TableManifest { count });
-
-        // If including sensitive data, also export the sensitive fields separately
-        if include_sensitive && password.is_some() {
-            if SENSITIVE_FIELDS.iter().any(|(t, _)| *t == *table_name) {
-                let (full_data, _) = export_table_data(conn, table_name, true)?;
-                sensitive_data.insert(table_name.to_string(), full_data);
-            }
+        checksums.insert(format!("data/{}.ndjson", table_name), checksum);
+        if collect_sensitive {
+            sensitive_data.insert(table_name.to_string(), sensitive_rows);
         }
     }
 
-    // Export files
-    let uploads_dir = get_uploads_dir();
+    // Export files from the storage backend (local uploads directory or S3 bucket),
+    // streaming each one into the archive
+    let runtime = tokio::runtime::Handle::current();
+    let stored_files: Vec<_> = runtime.block_on(storage.list_files(""))?
+        .into_iter()
+        .filter(|stored| is_backed_up_file(&stored.path))
+        .collect();
+    let files_total = stored_files.len() as i64;
     let mut file_count = 0i64;
     let mut total_size = 0i64;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
 
-    if uploads_dir.exists() {
-        let thumbs_dir = uploads_dir.join("users").join("thumbs");
-        for entry in WalkDir::new(&uploads_dir)
-            .into_iter()
-            .filter_entry(|e| {
-                let path = e.path();
-                // Skip the backups directory and thumbnails (can be regenerated)
-                !path.starts_with(get_backups_dir()) && !path.starts_with(&thumbs_dir)
-            })
-            .filter_map(|e| e.ok())
-        {
-            if entry.file_type().is_file() {
-                let file_path = entry.path();
-                let relative_path = file_path.strip_prefix(&uploads_dir)
-                    .map_err(|e| BackupError::IoError(std::io::Error::new(
-                        std::io::ErrorKind::Other, e.to_string()
-                    )))?;
-
-                let archive_path = format!("files/{}", relative_path.display());
-
-                zip.start_file(&archive_path, options)?;
-                let mut file = File::open(file_path)?;
-                let mut buffer = Vec::new();
-                let size = file.read_to_end(&mut buffer)?;
-                zip.write_all(&buffer)?;
-
-                file_count += 1;
-                total_size += size as i64;
+    for stored in &stored_files {
+        if (file_count as u64).is_multiple_of(FILE_PROGRESS_INTERVAL) {
+            report_progress(conn, Some(job_id), "Copying files", file_count, Some(files_total));
+        }
+
+        // Files deleted since the listing are skipped
+        let mut reader = match runtime.block_on(storage.open_file(&stored.path)) {
+            Ok(reader) => reader,
+            Err(StorageError::NotFound(_)) => {
+                log::warn!("Skipping file removed during backup: {}", stored.path);
+                continue;
             }
+            Err(e) => return Err(e.into()),
+        };
+
+        let entry_name = format!("files/{}", stored.path);
+        zip.start_file(entry_name.as_str(), options)?;
+        let mut entry = HashingWriter::new(&mut zip);
+        loop {
+            let read = runtime.block_on(reader.read(&mut buffer))?;
+            if read == 0 {
+                break;
+            }
+            entry.write_all(&buffer[..read])?;
+            total_size += read as i64;
         }
+        checksums.insert(entry_name, entry.finish());
+        file_count += 1;
     }
 
     // Handle sensitive data encryption
@@ -318,6 +425,7 @@ pub fn create_backup(
         // Write encrypted sensitive data
         zip.start_file("data/sensitive.json.enc", options)?;
         zip.write_all(&encrypted)?;
+        checksums.insert("data/sensitive.json.enc".to_string(), backup_integrity::sha256_hex(&encrypted));
 
         Some(EncryptionManifest {
             algorithm: "AES-256-GCM".to_string(),
@@ -330,8 +438,8 @@ pub fn create_backup(
     };
 
     // Create manifest
-    let manifest = BackupManifest {
-        version: "1.0".to_string(),
+    let mut manifest = BackupManifest {
+        version: BACKUP_FORMAT_VERSION.to_string(),
         created_at: Utc::now().to_rfc3339(),
         nosdesk_version: env!("CARGO_PKG_VERSION").to_string(),
         include_sensitive,
@@ -341,8 +449,13 @@ pub fn create_backup(
             total_size_bytes: total_size,
         },
         encryption: encryption_manifest,
+        schema_version: backup_migration::applied_schema_version(conn)?,
+        checksums,
+        signature: None,
     };
+    backup_integrity::sign_manifest(&mut manifest)?;
 
+    report_progress(conn, Some(job_id), "Finishing", file_count, Some(files_total));
     let manifest_json = serde_json::to_string_pretty(&manifest)?;
     zip.start_file("manifest.json", options)?;
     zip.write_all(manifest_json.as_bytes())?;
@@ -362,6 +475,98 @@ pub fn create_backup(
     Ok(backup_path)
 }
 
+/// Create a backup for an existing export job, marking the job failed if it doesn't complete
+///
+/// Same threading requirement as `create_backup`.
+pub fn run_export_job(
+    conn: &mut DbConnection,
+    storage: &dyn Storage,
+    job_id: Uuid,
+    include_sensitive: bool,
+    password: Option<&str>,
+) -> Result<PathBuf, BackupError> {
+    let result = create_backup(conn, storage, job_id, include_sensitive, password);
+    match &result {
+        Ok(path) => log::info!("Backup completed successfully: {:?}", path),
+        Err(e) => {
+            log::error!("Backup failed: {}", e);
+            let _ = backup_repo::update_backup_job(conn, job_id, BackupJobUpdate {
+                status: Some("failed".to_string()),
+                file_path: None,
+                file_size: None,
+                error_message: Some(e.to_string()),
+                completed_at: Some(Utc::now().naive_utc()),
+            });
+        }
+    }
+    result
+}
+
+/// Create a job to record a restore that didn't start from an uploaded restore job
+/// (the setup wizard and the admin CLI)
+pub fn create_restore_job(
+    conn: &mut DbConnection,
+    include_sensitive: bool,
+    created_by: Option<Uuid>,
+) -> Result<Uuid, BackupError> {
+    let job = backup_repo::create_backup_job(conn, NewBackupJob {
+        job_type: "restore".to_string(),
+        status: "pending".to_string(),
+        include_sensitive,
+        created_by,
+        schedule_id: None,
+    })?;
+    Ok(job.id)
+}
+
+/// Restore the database for a restore job, recording progress on the job and marking it
+/// failed if the restore doesn't complete
+///
+/// Restoring files follows; the caller marks the job completed with `complete_restore_job`.
+pub fn run_restore_job(
+    conn: &mut DbConnection,
+    backup_path: &Path,
+    password: Option<&str>,
+    job_id: Uuid,
+) -> Result<RestoreStats, BackupError> {
+    let _ = backup_repo::update_backup_job(conn, job_id, BackupJobUpdate {
+        status: Some("processing".to_string()),
+        file_path: None,
+        file_size: None,
+        error_message: None,
+        completed_at: None,
+    });
+
+    let result = restore_database(conn, backup_path, password, Some(job_id));
+    if let Err(e) = &result {
+        log::error!("Restore failed: {}", e);
+        fail_restore_job(conn, job_id, &e.to_string());
+    }
+    result
+}
+
+/// Mark a restore job completed
+pub fn complete_restore_job(conn: &mut DbConnection, job_id: Uuid) {
+    let _ = backup_repo::update_backup_job(conn, job_id, BackupJobUpdate {
+        status: Some("completed".to_string()),
+        file_path: None,
+        file_size: None,
+        error_message: None,
+        completed_at: Some(Utc::now().naive_utc()),
+    });
+}
+
+/// Mark a restore job failed
+pub fn fail_restore_job(conn: &mut DbConnection, job_id: Uuid, error: &str) {
+    let _ = backup_repo::update_backup_job(conn, job_id, BackupJobUpdate {
+        status: Some("failed".to_string()),
+        file_path: None,
+        file_size: None,
+        error_message: Some(error.to_string()),
+        completed_at: Some(Utc::now().naive_utc()),
+    });
+}
+
 /// Read and parse a backup archive
 pub fn read_backup_manifest(backup_path: &Path) -> Result<BackupManifest, BackupError> {
     let file = File::open(backup_path)?;
@@ -376,13 +581,20 @@ pub fn read_backup_manifest(backup_path: &Path) -> Result<BackupManifest, Backup
 }
 
 /// Preview what a restore would do
+///
+/// Every entry is checked against the manifest first; archives that fail verification are
+/// refused with `BackupError::VerificationFailed`.
 pub fn preview_restore(backup_path: &Path) -> Result<RestorePreview, BackupError> {
+    let verification = backup_integrity::verify_backup(backup_path)?;
+    if !verification.valid {
+        return Err(BackupError::VerificationFailed(verification.problems));
+    }
     let manifest = read_backup_manifest(backup_path)?;
 
     let has_encrypted_sensitive = manifest.encryption.is_some();
 
     // Generate warnings
-    let mut warnings = Vec::new();
+    let mut warnings = verification.warnings.clone();
 
     // Version mismatch warning
     let current_version = env!("CARGO_PKG_VERSION");
@@ -393,6 +605,23 @@ pub fn preview_restore(backup_path: &Path) -> Result<RestorePreview, BackupError
         ));
     }
 
+    // Schema warnings: older archives are migrated, newer ones lose unknown columns
+    let current_schema = backup_migration::latest_schema_version()?;
+    match (manifest.schema_version.as_deref(), current_schema.as_deref()) {
+        (Some(archive), Some(current)) if archive < current => warnings.push(format!(
+            "Backup was taken at schema version {}; it will be migrated to {} during restore",
+            archive, current
+        )),
+        (Some(archive), Some(current)) if archive > current => warnings.push(format!(
+            "Backup is from a newer schema version ({}) than this server ({}); data this version doesn't know about will be skipped",
+            archive, current
+        )),
+        (None, _) => warnings.push(
+            "Backup doesn't record its schema version; columns that no longer exist will be skipped".to_string(),
+        ),
+        _ => {}
+    }
+
     // Large file warning
     if manifest.files.total_size_bytes > 1024 * 1024 * 1024 {
         warnings.push(format!(
@@ -401,44 +630,122 @@ pub fn preview_restore(backup_path: &Path) -> Result<RestorePreview, BackupError
         ));
     }
 
+    let entities = crate::services::selective_restore::list_restorable_entities(backup_path)?;
+
     Ok(RestorePreview {
         manifest,
         has_encrypted_sensitive,
         warnings,
+        entities,
+        verification,
     })
 }
 
-/// Restore files from a backup archive
-pub fn restore_backup_files(
+/// Restore files from a backup archive into the storage backend
+///
+/// Each file is extracted to a staging file and streamed to storage from there, so memory
+/// use doesn't depend on file sizes. `on_progress` receives (restored, total) as it goes.
+pub async fn restore_backup_files(
     backup_path: &Path,
+    storage: &dyn Storage,
+    on_progress: impl FnMut(u64, u64),
+) -> Result<u64, BackupError> {
+    restore_backup_files_matching(backup_path, storage, |path| Some(path.to_string()), true, on_progress).await
+}
+
+/// Restore the archived files `destination` maps to a storage path (`None` skips a file)
+///
+/// Without `overwrite`, files that already exist in storage are left alone.
+pub async fn restore_backup_files_matching(
+    backup_path: &Path,
+    storage: &dyn Storage,
+    destination: impl Fn(&str) -> Option<String>,
+    overwrite: bool,
+    mut on_progress: impl FnMut(u64, u64),
 ) -> Result<u64, BackupError> {
     let file = File::open(backup_path)?;
     let mut archive = ZipArchive::new(file)?;
 
-    let uploads_dir = get_uploads_dir();
-    let mut restored_count = 0u64;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let name = file.name().to_string();
+    let total = archive.file_names()
+        .filter_map(archive_file_path)
+        .filter(|path| destination(path).is_some())
+        .count() as u64;
+    let staging_dir = get_backups_dir().join("tmp");
+    fs::create_dir_all(&staging_dir)?;
+    let staging_path = staging_dir.join(format!("restore-{}", Uuid::now_v7()));
 
-        if name.starts_with("files/") && !name.ends_with('/') {
-            let relative_path = name.strip_prefix("files/").unwrap();
-            let dest_path = uploads_dir.join(relative_path);
+    let mut restored_count = 0u64;
+    let result: Result<(), BackupError> = async {
+        for i in 0..archive.len() {
+            let name = archive.by_index(i)?.name().to_string();
+            let Some(relative_path) = archive_file_path(&name) else {
+                if name.starts_with("files/") && !name.ends_with('/') {
+                    log::warn!("Skipping backup entry with unsafe path: {}", name);
+                }
+                continue;
+            };
+            let Some(dest_path) = destination(relative_path) else {
+                continue;
+            };
+            if !overwrite && storage.file_exists(&dest_path).await? {
+                continue;
+            }
 
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
+            {
+                let mut file = archive.by_index(i)?;
+                let mut staged = File::create(&staging_path)?;
+                std::io::copy(&mut file, &mut staged)?;
             }
 
-            let mut dest_file = File::create(&dest_path)?;
-            std::io::copy(&mut file, &mut dest_file)?;
+            storage
+                .write_file_from_path(&dest_path, &staging_path, get_content_type(&dest_path))
+                .await?;
             restored_count += 1;
+            if restored_count.is_multiple_of(FILE_PROGRESS_INTERVAL) {
+                on_progress(restored_count, total);
+            }
         }
+        Ok(())
     }
+    .await;
+
+    let _ = fs::remove_file(&staging_path);
+    result?;
+    on_progress(restored_count, total);
 
     Ok(restored_count)
 }
 
+/// Visit every row of a table in an archive, NDJSON or 1.x JSON array
+///
+/// Returns false if the table isn't in the archive.
+pub(crate) fn scan_table<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    table_name: &str,
+    mut visit: impl FnMut(serde_json::Value) -> Result<(), BackupError>,
+) -> Result<bool, BackupError> {
+    let ndjson_path = format!("data/{}.ndjson", table_name);
+    let legacy_path = format!("data/{}.json", table_name);
+
+    if archive.file_names().any(|name| name == ndjson_path) {
+        let entry = archive.by_name(&ndjson_path)?;
+        for line in BufReader::new(entry).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                visit(serde_json::from_str(&line)?)?;
+            }
+        }
+        Ok(true)
+    } else if archive.file_names().any(|name| name == legacy_path) {
+        let entry = archive.by_name(&legacy_path)?;
+        let rows: Vec<serde_json::Value> = serde_json::from_reader(BufReader::new(entry))?;
+        rows.into_iter().try_for_each(visit)?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
 /// Verify if a password can decrypt the sensitive data
 pub fn verify_backup_password(backup_path: &Path, password: &str) -> Result<bool, BackupError> {
     let manifest = read_backup_manifest(backup_path)?;
@@ -498,11 +805,97 @@ pub struct RestoreStats {
     pub records_restored: usize,
 }
 
+/// Regenerate thumbnails for all users with avatars
+/// Returns the count of successfully regenerated thumbnails
+pub async fn regenerate_user_thumbnails(conn: &mut DbConnection) -> u64 {
+    // Query all users with avatar URLs
+    #[derive(diesel::QueryableByName)]
+    struct UserAvatar {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        uuid_str: String,
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        avatar: String,
+    }
+
+    let user_avatars: Vec<UserAvatar> = match diesel::sql_query(
+        "SELECT uuid::text as uuid_str, avatar_url as avatar FROM users WHERE avatar_url IS NOT NULL"
+    ).load(conn) {
+        Ok(avatars) => avatars,
+        Err(e) => {
+            log::error!("Failed to query users for thumbnail regeneration: {}", e);
+            return 0;
+        }
+    };
+
+    let mut regenerated = 0u64;
+
+    for user_avatar in user_avatars {
+        match generate_user_avatar_thumbnail(&user_avatar.avatar, &user_avatar.uuid_str).await {
+            Ok(Some(_)) => {
+                regenerated += 1;
+                log::debug!("Regenerated thumbnail for user {}", user_avatar.uuid_str);
+            }
+            Ok(None) => {
+                log::warn!("Could not generate thumbnail for user {} - avatar may be missing", user_avatar.uuid_str);
+            }
+            Err(e) => {
+                log::warn!("Failed to regenerate thumbnail for user {}: {}", user_avatar.uuid_str, e);
+            }
+        }
+    }
+
+    regenerated
+}
+
+/// Rows processed so far by a database restore, recorded on its job
+struct RestoreProgress {
+    job_id: Option<Uuid>,
+    processed: i64,
+    total: i64,
+}
+
+impl RestoreProgress {
+    fn advance(&mut self, conn: &mut DbConnection, table_name: &str, rows: usize) {
+        self.processed += rows as i64;
+        report_progress(conn, self.job_id, &format!("Restoring {}", table_name), self.processed, Some(self.total));
+    }
+}
+
+/// Table restore order (respecting foreign key dependencies)
+const RESTORE_ORDER: &[&str] = &[
+    "users",
+    "user_emails",
+    "user_auth_identities",
+    "devices",
+    "tickets",
+    "ticket_devices",
+    "comments",
+    "attachments",
+    "projects",
+    "project_tickets",
+    "documentation_pages",
+    "documentation_revisions",
+    "article_contents",
+    "article_content_revisions",
+    "linked_tickets",
+    "problem_details",
+    "change_details",
+    "site_settings",
+    "user_ticket_views",
+];
+
 /// Restore database tables from backup archive
+///
+/// Tables are read row by row (NDJSON, or the JSON arrays of 1.x archives) and inserted in
+/// parameterised batches. With a `job_id`, progress is recorded on the job after each batch.
+///
+/// Archives taken at an older schema migration are loaded into a staging schema and migrated
+/// first (see `backup_migration`).
 pub fn restore_database(
     conn: &mut DbConnection,
     backup_path: &Path,
     password: Option<&str>,
+    job_id: Option<Uuid>,
 ) -> Result<RestoreStats, BackupError> {
     let file = File::open(backup_path)?;
     let mut archive = ZipArchive::new(file)?;
@@ -515,121 +908,163 @@ pub fn restore_database(
         serde_json::from_str(&content)?
     };
 
-    // Table restore order (respecting foreign key dependencies)
-    let restore_order = [
-        "users",
-        "user_emails",
-        "user_auth_identities",
-        "devices",
-        "tickets",
-        "ticket_devices",
-        "comments",
-        "attachments",
-        "projects",
-        "project_tickets",
-        "documentation_pages",
-        "documentation_revisions",
-        "article_contents",
-        "article_content_revisions",
-        "linked_tickets",
-        "site_settings",
-        "user_ticket_views",
-    ];
-
-    let mut tables_restored = 0;
-    let mut records_restored = 0;
+    let current_version = backup_migration::latest_schema_version()?;
+    let stats = match manifest.schema_version.as_deref() {
+        Some(version) if backup_migration::needs_migration(Some(version), current_version.as_deref()) => {
+            log::info!(
+                "Backup was taken at schema {}, migrating to {} during restore",
+                version,
+                current_version.as_deref().unwrap_or_default()
+            );
+            restore_through_staging(conn, &mut archive, backup_path, &manifest, version, password, job_id)?
+        }
+        _ => {
+            let stats = load_tables(conn, &mut archive, &manifest, job_id)?;
+            restore_sensitive_data(conn, backup_path, &manifest, password)?;
+            stats
+        }
+    };
 
-    // Restore each table
-    for table_name in &restore_order {
-        let data_path = format!("data/{}.json", table_name);
+    // Reset all sequences to avoid primary key conflicts
+    reset_sequences(conn)?;
 
-        // Try to read the file, skip if not in backup
-        let content = match archive.by_name(&data_path) {
-            Ok(mut data_file) => {
-                let mut content = String::new();
-                data_file.read_to_string(&mut content)?;
-                content
-            }
-            Err(_) => continue,
-        };
+    Ok(stats)
+}
 
-        let rows: Vec<serde_json::Value> = serde_json::from_str(&content)?;
+/// Insert every table in the archive into the tables `search_path` resolves to
+fn load_tables<R: Read + Seek>(
+    conn: &mut DbConnection,
+    archive: &mut ZipArchive<R>,
+    manifest: &BackupManifest,
+    job_id: Option<Uuid>,
+) -> Result<RestoreStats, BackupError> {
+    let mut tables_restored = 0;
+    let mut records_restored = 0;
+    let mut progress = RestoreProgress {
+        job_id,
+        processed: 0,
+        total: RESTORE_ORDER.iter()
+            .filter_map(|table| manifest.tables.get(*table))
+            .map(|table| table.count)
+            .sum(),
+    };
 
-        if rows.is_empty() {
+    // Restore each table, skipping tables that aren't in the backup
+    for table_name in RESTORE_ORDER {
+        let Some(count) = restore_table_data(conn, archive, table_name, &mut progress)? else {
             continue;
-        }
+        };
 
-        // Insert rows using raw SQL
-        let count = restore_table_data(conn, table_name, &rows)?;
         if count > 0 {
             tables_restored += 1;
             records_restored += count;
         }
     }
 
-    // Handle encrypted sensitive data if password provided
-    if manifest.encryption.is_some() && password.is_some() {
-        // Read encrypted data from archive
-        let encrypted_data: Option<Vec<u8>> = {
-            let file = File::open(backup_path)?;
-            let mut archive = ZipArchive::new(file)?;
+    Ok(RestoreStats {
+        tables_restored,
+        records_restored,
+    })
+}
+
+/// Load an older archive into a staging schema, migrate it and copy it into the live tables
+fn restore_through_staging<R: Read + Seek>(
+    conn: &mut DbConnection,
+    archive: &mut ZipArchive<R>,
+    backup_path: &Path,
+    manifest: &BackupManifest,
+    version: &str,
+    password: Option<&str>,
+    job_id: Option<Uuid>,
+) -> Result<RestoreStats, BackupError> {
+    report_progress(conn, job_id, &format!("Migrating backup from schema {}", version), 0, None);
+    let staging = StagingSchema::create(conn, version)?;
+    let result = migrate_staged(conn, &staging, archive, backup_path, manifest, password, job_id);
+    staging.remove(conn);
+    result
+}
+
+fn migrate_staged<R: Read + Seek>(
+    conn: &mut DbConnection,
+    staging: &StagingSchema,
+    archive: &mut ZipArchive<R>,
+    backup_path: &Path,
+    manifest: &BackupManifest,
+    password: Option<&str>,
+    job_id: Option<Uuid>,
+) -> Result<RestoreStats, BackupError> {
+    // No job progress while staged: `backup_jobs` could resolve to a staging table
+    load_tables(conn, archive, manifest, None)?;
+    restore_sensitive_data(conn, backup_path, manifest, password)?;
+    staging.migrate_after(conn, manifest.schema_version.as_deref().unwrap_or_default())?;
+    staging.leave(conn)?;
+
+    let mut stats = RestoreStats { tables_restored: 0, records_restored: 0 };
+    let total = RESTORE_ORDER.len() as i64;
+    for (index, table_name) in RESTORE_ORDER.iter().enumerate() {
+        report_progress(conn, job_id, &format!("Restoring {}", table_name), index as i64, Some(total));
+        let count = staging.copy_table(conn, table_name)?;
+        if count > 0 {
+            stats.tables_restored += 1;
+            stats.records_restored += count;
+        }
+    }
+    Ok(stats)
+}
+
+/// Decrypt the archive's sensitive fields with `password` and apply them to the restored rows
+fn restore_sensitive_data(
+    conn: &mut DbConnection,
+    backup_path: &Path,
+    manifest: &BackupManifest,
+    password: Option<&str>,
+) -> Result<(), BackupError> {
+    let (Some(enc_info), Some(password)) = (&manifest.encryption, password) else {
+        return Ok(());
+    };
 
-            // Check if file exists and read it
-            let result = archive.by_name("data/sensitive.json.enc");
-            if let Ok(mut enc_file) = result {
+    // Read encrypted data from archive
+    let encrypted_data = {
+        let file = File::open(backup_path)?;
+        let mut archive = ZipArchive::new(file)?;
+        let data = match archive.by_name("data/sensitive.json.enc") {
+            Ok(mut enc_file) => {
                 let mut data = Vec::new();
                 enc_file.read_to_end(&mut data)?;
-                drop(enc_file); // Explicitly drop before archive
-                Some(data)
-            } else {
-                None
+                data
             }
+            Err(_) => return Ok(()),
         };
+        data
+    };
 
-        // Process encrypted data after archive is dropped
-        if let Some(encrypted_data) = encrypted_data {
-            if let Some(enc_info) = &manifest.encryption {
-                let password = password.unwrap();
-                let salt = hex::decode(&enc_info.salt)
-                    .map_err(|e| BackupError::EncryptionError(format!("Invalid salt: {}", e)))?;
-                let nonce = hex::decode(&enc_info.nonce)
-                    .map_err(|e| BackupError::EncryptionError(format!("Invalid nonce: {}", e)))?;
-
-                let mut salt_arr = [0u8; SALT_LENGTH];
-                let mut nonce_arr = [0u8; NONCE_LEN];
-                salt_arr.copy_from_slice(&salt);
-                nonce_arr.copy_from_slice(&nonce);
-
-                // Derive key and decrypt
-                let key = derive_key(password, &salt_arr);
-                let decrypted = decrypt_data(&encrypted_data, &key, &nonce_arr)?;
-
-                let sensitive_tables: std::collections::HashMap<String, Vec<serde_json::Value>> =
-                    serde_json::from_slice(&decrypted)?;
-
-                // Update tables with sensitive fields
-                for (table_name, rows) in sensitive_tables {
-                    update_sensitive_fields(conn, &table_name, &rows)?;
-                }
-            }
-        }
-    }
+    let salt = hex::decode(&enc_info.salt)
+        .map_err(|e| BackupError::EncryptionError(format!("Invalid salt: {}", e)))?;
+    let nonce = hex::decode(&enc_info.nonce)
+        .map_err(|e| BackupError::EncryptionError(format!("Invalid nonce: {}", e)))?;
 
-    // Reset all sequences to avoid primary key conflicts
-    reset_sequences(conn)?;
+    let mut salt_arr = [0u8; SALT_LENGTH];
+    let mut nonce_arr = [0u8; NONCE_LEN];
+    salt_arr.copy_from_slice(&salt);
+    nonce_arr.copy_from_slice(&nonce);
 
-    Ok(RestoreStats {
-        tables_restored,
-        records_restored,
-    })
+    // Derive key and decrypt
+    let key = derive_key(password, &salt_arr);
+    let decrypted = decrypt_data(&encrypted_data, &key, &nonce_arr)?;
+
+    let sensitive_tables: std::collections::HashMap<String, Vec<serde_json::Value>> =
+        serde_json::from_slice(&decrypted)?;
+
+    // Update tables with sensitive fields
+    for (table_name, rows) in sensitive_tables {
+        update_sensitive_fields(conn, &table_name, &rows)?;
+    }
+    Ok(())
 }
 
 /// Reset all sequences to be higher than the max ID in each table
 /// This is necessary after restoring data with explicit IDs
-fn reset_sequences(conn: &mut DbConnection) -> Result<(), BackupError> {
-    use diesel::sql_query;
-    use diesel::RunQueryDsl;
-
+pub(crate) fn reset_sequences(conn: &mut DbConnection) -> Result<(), BackupError> {
     // Tables with serial/bigserial id columns that need sequence reset
     let tables_with_sequences = [
         "tickets",
@@ -668,123 +1103,169 @@ fn reset_sequences(conn: &mut DbConnection) -> Result<(), BackupError> {
     Ok(())
 }
 
-/// Restore data to a table using raw SQL
-fn restore_table_data(
+/// Columns of a table in the current schema
+pub(crate) fn table_columns(conn: &mut DbConnection, table_name: &str) -> Result<HashSet<String>, BackupError> {
+    #[derive(QueryableByName)]
+    struct ColumnName {
+        #[diesel(sql_type = Text)]
+        column_name: String,
+    }
+
+    let columns: Vec<ColumnName> = sql_query(
+        "SELECT column_name::text AS column_name FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = $1",
+    )
+    .bind::<Text, _>(table_name)
+    .load(conn)?;
+    Ok(columns.into_iter().map(|c| c.column_name).collect())
+}
+
+/// Backed-up columns that still exist, in a stable order
+fn batch_columns(rows: &[serde_json::Value], existing: &HashSet<String>) -> Vec<String> {
+    let columns: BTreeSet<&String> = rows.iter()
+        .filter_map(|row| row.as_object())
+        .flat_map(|map| map.keys())
+        .filter(|column| existing.contains(*column))
+        .collect();
+    columns.into_iter().cloned().collect()
+}
+
+/// Insert a batch of rows with one parameterised statement
+///
+/// `json_populate_recordset` converts the JSON to the table's column types, so values are
+/// never spliced into the SQL. Only the backed-up columns are inserted; the rest get their
+/// defaults. Rows that already exist are left alone.
+fn insert_batch(
     conn: &mut DbConnection,
     table_name: &str,
+    columns: &[String],
     rows: &[serde_json::Value],
 ) -> Result<usize, BackupError> {
-    use diesel::sql_query;
-    use diesel::RunQueryDsl;
-
-    let mut inserted = 0;
-
-    for row in rows {
-        if let serde_json::Value::Object(map) = row {
-            if map.is_empty() {
-                continue;
-            }
-
-            let columns: Vec<&str> = map.keys().map(|k| k.as_str()).collect();
-
-            // Build values string for the query
-            let values: Vec<String> = map.values()
-                .map(|v| json_to_sql_value(v))
-                .collect();
+    let column_list = columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", ");
+    let query = format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM json_populate_recordset(NULL::{table}, $1::json) \
+         ON CONFLICT DO NOTHING",
+        table = table_name,
+        columns = column_list,
+    );
+    Ok(sql_query(query).bind::<Text, _>(serde_json::to_string(rows)?).execute(conn)?)
+}
 
-            // Execute as raw SQL with formatted values
-            let full_query = format!(
-                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT DO NOTHING",
-                table_name,
-                columns.join(", "),
-                values.join(", ")
-            );
+/// Insert one batch, falling back to row-by-row inserts so a bad row only loses itself
+fn restore_batch(conn: &mut DbConnection, table_name: &str, existing: &HashSet<String>, rows: &[serde_json::Value]) -> usize {
+    let columns = batch_columns(rows, existing);
+    if columns.is_empty() {
+        return 0;
+    }
 
-            match sql_query(&full_query).execute(conn) {
-                Ok(count) => inserted += count,
-                Err(e) => {
-                    log::warn!("Failed to insert into {}: {}", table_name, e);
-                    // Continue with other rows
-                }
-            }
+    match insert_batch(conn, table_name, &columns, rows) {
+        Ok(count) => count,
+        Err(e) => {
+            log::warn!("Batch insert into {} failed, retrying row by row: {}", table_name, e);
+            rows.iter()
+                .map(|row| match insert_batch(conn, table_name, &columns, std::slice::from_ref(row)) {
+                    Ok(count) => count,
+                    Err(e) => {
+                        log::warn!("Failed to insert into {}: {}", table_name, e);
+                        0
+                    }
+                })
+                .sum()
         }
     }
-
-    Ok(inserted)
 }
 
-/// Convert JSON value to SQL literal
-fn json_to_sql_value(value: &serde_json::Value) -> String {
-    match value {
-        serde_json::Value::Null => "NULL".to_string(),
-        serde_json::Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
-        serde_json::Value::Number(n) => n.to_string(),
-        serde_json::Value::String(s) => format!("'{}'", s.replace('\'', "''")),
-        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-            // Handle JSONB for both arrays and objects
-            // This works for JSONB columns like mfa_backup_codes
-            format!("'{}'::jsonb", value.to_string().replace('\'', "''"))
+/// Restore a table's rows in batches of `RESTORE_BATCH_SIZE`; `None` if it isn't in the archive
+fn restore_table_data<R: Read + Seek>(
+    conn: &mut DbConnection,
+    archive: &mut ZipArchive<R>,
+    table_name: &str,
+    progress: &mut RestoreProgress,
+) -> Result<Option<usize>, BackupError> {
+    let existing = table_columns(conn, table_name)?;
+    let mut inserted = 0;
+    let mut batch = Vec::with_capacity(RESTORE_BATCH_SIZE);
+
+    let found = scan_table(archive, table_name, |row| {
+        if row.as_object().is_some_and(|map| !map.is_empty()) {
+            batch.push(row);
         }
+        if batch.len() == RESTORE_BATCH_SIZE {
+            inserted += restore_batch(conn, table_name, &existing, &batch);
+            progress.advance(conn, table_name, batch.len());
+            batch.clear();
+        }
+        Ok(())
+    })?;
+    if !found {
+        return Ok(None);
     }
+    if !batch.is_empty() {
+        inserted += restore_batch(conn, table_name, &existing, &batch);
+        progress.advance(conn, table_name, batch.len());
+    }
+
+    Ok(Some(inserted))
 }
 
-/// Update sensitive fields in existing rows
+/// Update sensitive fields in existing rows, a batch at a time
 fn update_sensitive_fields(
     conn: &mut DbConnection,
     table_name: &str,
     rows: &[serde_json::Value],
 ) -> Result<(), BackupError> {
-    use diesel::sql_query;
-    use diesel::RunQueryDsl;
-
-    // Determine primary key column based on table
-    let pk_column = match table_name {
-        "users" => "uuid",
-        _ => "id",
+    let Some(fields) = sensitive_fields(table_name) else {
+        return Ok(());
     };
+    let key_column = quote_ident(sensitive_key_column(table_name));
+    let assignments = fields.iter()
+        .map(|field| format!("{field} = source.{field}", field = quote_ident(field)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let query = format!(
+        "UPDATE {table} AS target SET {assignments} \
+         FROM json_populate_recordset(NULL::{table}, $1::json) AS source \
+         WHERE target.{key} = source.{key}",
+        table = table_name,
+        assignments = assignments,
+        key = key_column,
+    );
 
-    for row in rows {
-        if let serde_json::Value::Object(map) = row {
-            // Get primary key value
-            let pk_value = match map.get(pk_column) {
-                Some(v) => json_to_sql_value(v),
-                None => continue,
-            };
-
-            // Build UPDATE for sensitive fields only
-            let sensitive_fields: &[&str] = match table_name {
-                "users" => &["mfa_secret", "mfa_backup_codes"],
-                "user_auth_identities" => &["password_hash", "metadata"],
-                "refresh_tokens" => &["token_hash"],
-                "reset_tokens" => &["token_hash", "metadata"],
-                _ => &[],
-            };
-
-            let updates: Vec<String> = sensitive_fields.iter()
-                .filter_map(|field| {
-                    map.get(*field).map(|v| {
-                        format!("{} = {}", field, json_to_sql_value(v))
-                    })
-                })
-                .collect();
-
-            if updates.is_empty() {
-                continue;
-            }
-
-            let query = format!(
-                "UPDATE {} SET {} WHERE {} = {}",
-                table_name,
-                updates.join(", "),
-                pk_column,
-                pk_value
-            );
-
-            if let Err(e) = sql_query(&query).execute(conn) {
-                log::warn!("Failed to update sensitive fields in {}: {}", table_name, e);
-            }
+    for batch in rows.chunks(RESTORE_BATCH_SIZE) {
+        if let Err(e) = sql_query(&query).bind::<Text, _>(serde_json::to_string(batch)?).execute(conn) {
+            log::warn!("Failed to update sensitive fields in {}: {}", table_name, e);
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_file_path() {
+        assert_eq!(archive_file_path("files/tickets/12/report.pdf"), Some("tickets/12/report.pdf"));
+        assert_eq!(archive_file_path("files/users/"), None);
+        assert_eq!(archive_file_path("data/users.json"), None);
+        assert_eq!(archive_file_path("files/../etc/passwd"), None);
+        assert_eq!(archive_file_path("files//etc/passwd"), None);
+        assert_eq!(archive_file_path("files/tickets\\..\\..\\secret"), None);
+
+        assert!(is_backed_up_file("tickets/12/report.pdf"));
+        assert!(!is_backed_up_file("users/thumbs/avatar.webp"));
+        assert!(!is_backed_up_file("backups/backup-2026-01-01-000000.zip"));
+    }
+
+    #[test]
+    fn test_batch_columns_skip_dropped_columns() {
+        let existing: HashSet<String> = ["id", "title", "status"].iter().map(|c| c.to_string()).collect();
+        let rows = vec![
+            serde_json::json!({"id": 1, "title": "Printer", "legacy_flag": true}),
+            serde_json::json!({"id": 2, "status": "open"}),
+        ];
+        assert_eq!(batch_columns(&rows, &existing), vec!["id", "status", "title"]);
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+}
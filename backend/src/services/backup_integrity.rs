@@ -0,0 +1,364 @@
+//! Backup Integrity
+//!
+//! Every data and file entry of a backup archive is hashed with SHA-256 as it is written, and
+//! the hashes are stored in the manifest. When a signing key is configured the manifest is
+//! also signed, so an archive can be checked for truncation or tampering before anything is
+//! restored from it.
+//!
+//! - `BACKUP_HMAC_KEY` - shared secret; signs manifests with HMAC-SHA256 and checks them
+//! - `BACKUP_ED25519_PRIVATE_KEY` - hex Ed25519 seed (32 bytes); signs manifests with Ed25519
+//!   (preferred over HMAC when both are set)
+//! - `BACKUP_ED25519_PUBLIC_KEY` - hex public key for checking Ed25519 signatures (derived from
+//!   the private key when not set)
+//! - `BACKUP_REQUIRE_SIGNATURE` - when true, unsigned or uncheckable archives fail verification
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use ring::digest::{self, Context, SHA256};
+use ring::hmac;
+use ring::signature::{self, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::models::{BackupManifest, BackupVerification, ManifestSignature, SignatureStatus};
+use crate::services::backup::{read_backup_manifest, BackupError};
+
+pub const HMAC_ALGORITHM: &str = "HMAC-SHA256";
+pub const ED25519_ALGORITHM: &str = "Ed25519";
+
+/// Archive entry holding the manifest itself, which is signed rather than hashed
+const MANIFEST_ENTRY: &str = "manifest.json";
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Writer that hashes everything written through it
+pub struct HashingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    context: Context,
+}
+
+impl<'a, W: Write> HashingWriter<'a, W> {
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, context: Context::new(&SHA256) }
+    }
+
+    /// Hex SHA-256 of everything written
+    pub fn finish(self) -> String {
+        hex::encode(self.context.finish())
+    }
+}
+
+impl<W: Write> Write for HashingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.context.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Hex SHA-256 of a byte slice
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&SHA256, data))
+}
+
+/// Hex SHA-256 of everything a reader yields
+fn sha256_reader(reader: &mut impl Read) -> io::Result<String> {
+    let mut context = Context::new(&SHA256);
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(hex::encode(context.finish()))
+}
+
+/// JSON with object keys sorted at every level, so the same manifest always signs the same
+fn canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                canonical_json(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// The bytes a manifest signature covers: the canonical manifest without its signature
+fn signed_bytes(manifest: &BackupManifest) -> Result<Vec<u8>, BackupError> {
+    let mut value = serde_json::to_value(manifest)?;
+    if let Value::Object(map) = &mut value {
+        map.remove("signature");
+    }
+    let mut out = String::new();
+    canonical_json(&value, &mut out);
+    Ok(out.into_bytes())
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn ed25519_key_pair() -> Option<Ed25519KeyPair> {
+    let seed = env_value("BACKUP_ED25519_PRIVATE_KEY")?;
+    match hex::decode(&seed).ok().and_then(|seed| Ed25519KeyPair::from_seed_unchecked(&seed).ok()) {
+        Some(key_pair) => Some(key_pair),
+        None => {
+            log::error!("BACKUP_ED25519_PRIVATE_KEY is not a valid hex Ed25519 seed; ignoring it");
+            None
+        }
+    }
+}
+
+fn ed25519_public_key() -> Option<Vec<u8>> {
+    match env_value("BACKUP_ED25519_PUBLIC_KEY") {
+        Some(key) => match hex::decode(&key) {
+            Ok(key) => Some(key),
+            Err(_) => {
+                log::error!("BACKUP_ED25519_PUBLIC_KEY is not valid hex; ignoring it");
+                None
+            }
+        },
+        None => ed25519_key_pair().map(|key_pair| key_pair.public_key().as_ref().to_vec()),
+    }
+}
+
+fn hmac_key() -> Option<hmac::Key> {
+    env_value("BACKUP_HMAC_KEY").map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+}
+
+fn signature_required() -> bool {
+    env_value("BACKUP_REQUIRE_SIGNATURE").is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// Algorithm new manifests are signed with, if a signing key is configured
+pub fn signing_algorithm() -> Option<&'static str> {
+    if ed25519_key_pair().is_some() {
+        Some(ED25519_ALGORITHM)
+    } else if hmac_key().is_some() {
+        Some(HMAC_ALGORITHM)
+    } else {
+        None
+    }
+}
+
+/// Sign a manifest with the configured key, if any
+pub fn sign_manifest(manifest: &mut BackupManifest) -> Result<(), BackupError> {
+    manifest.signature = None;
+    let signature = if let Some(key_pair) = ed25519_key_pair() {
+        Some(ManifestSignature {
+            algorithm: ED25519_ALGORITHM.to_string(),
+            value: hex::encode(key_pair.sign(&signed_bytes(manifest)?)),
+        })
+    } else {
+        hmac_key()
+            .map(|key| -> Result<_, BackupError> {
+                Ok(ManifestSignature {
+                    algorithm: HMAC_ALGORITHM.to_string(),
+                    value: hex::encode(hmac::sign(&key, &signed_bytes(manifest)?)),
+                })
+            })
+            .transpose()?
+    };
+    manifest.signature = signature;
+    Ok(())
+}
+
+/// Check a manifest's signature against the configured keys
+pub fn check_signature(manifest: &BackupManifest) -> Result<SignatureStatus, BackupError> {
+    let Some(manifest_signature) = &manifest.signature else {
+        return Ok(SignatureStatus::Unsigned);
+    };
+    let Ok(value) = hex::decode(&manifest_signature.value) else {
+        return Ok(SignatureStatus::Invalid);
+    };
+    let message = signed_bytes(manifest)?;
+
+    let verified = match manifest_signature.algorithm.as_str() {
+        ED25519_ALGORITHM => ed25519_public_key()
+            .map(|key| UnparsedPublicKey::new(&signature::ED25519, key).verify(&message, &value).is_ok()),
+        HMAC_ALGORITHM => hmac_key().map(|key| hmac::verify(&key, &message, &value).is_ok()),
+        _ => None,
+    };
+
+    Ok(match verified {
+        Some(true) => SignatureStatus::Valid,
+        Some(false) => SignatureStatus::Invalid,
+        None => SignatureStatus::Unverifiable,
+    })
+}
+
+/// Check every entry of an archive against the checksums in its manifest
+fn check_entries<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    manifest: &BackupManifest,
+    verification: &mut BackupVerification,
+) -> Result<(), BackupError> {
+    let mut seen = HashSet::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() || name == MANIFEST_ENTRY {
+            continue;
+        }
+
+        let Some(expected) = manifest.checksums.get(&name) else {
+            verification.problems.push(format!("{}: not listed in the manifest", name));
+            continue;
+        };
+        seen.insert(name.clone());
+
+        // Reading the whole entry also checks its CRC, which catches truncated data
+        match sha256_reader(&mut entry) {
+            Ok(actual) if actual == *expected => verification.entries_checked += 1,
+            Ok(_) => verification.problems.push(format!("{}: checksum mismatch", name)),
+            Err(e) => verification.problems.push(format!("{}: unreadable ({})", name, e)),
+        }
+    }
+
+    for name in manifest.checksums.keys().filter(|name| !seen.contains(*name)) {
+        verification.problems.push(format!("{}: missing from the archive", name));
+    }
+    Ok(())
+}
+
+/// Check an archive without restoring anything from it
+pub fn verify_backup(backup_path: &Path) -> Result<BackupVerification, BackupError> {
+    let manifest = read_backup_manifest(backup_path)?;
+    let mut archive = ZipArchive::new(File::open(backup_path)?)?;
+    let mut verification = BackupVerification::default();
+
+    if manifest.checksums.is_empty() {
+        verification.warnings.push(format!(
+            "Backup format {} has no checksums; its contents can't be verified",
+            manifest.version
+        ));
+    } else {
+        check_entries(&mut archive, &manifest, &mut verification)?;
+    }
+
+    verification.signature = check_signature(&manifest)?;
+    match verification.signature {
+        SignatureStatus::Valid => {}
+        SignatureStatus::Invalid => verification.problems.push("Manifest signature does not match".to_string()),
+        SignatureStatus::Unsigned if signature_required() => {
+            verification.problems.push("Backup is not signed".to_string())
+        }
+        SignatureStatus::Unverifiable if signature_required() => {
+            verification.problems.push("No key is configured to check the backup's signature".to_string())
+        }
+        SignatureStatus::Unsigned => {}
+        SignatureStatus::Unverifiable => verification
+            .warnings
+            .push("Backup is signed, but no key is configured to check the signature".to_string()),
+    }
+
+    verification.valid = verification.problems.is_empty();
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn manifest(checksums: &[(&str, &str)]) -> BackupManifest {
+        serde_json::from_value(json!({
+            "version": "2.1",
+            "created_at": "2026-01-01T00:00:00Z",
+            "nosdesk_version": "0.1.0",
+            "include_sensitive": false,
+            "tables": {"tickets": {"count": 1}, "users": {"count": 2}},
+            "files": {"total_count": 0, "total_size_bytes": 0},
+            "encryption": null,
+            "checksums": checksums.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<std::collections::BTreeMap<_, _>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let mut out = String::new();
+        canonical_json(&json!({"b": [1, {"d": null, "c": "x"}], "a": true}), &mut out);
+        assert_eq!(out, r#"{"a":true,"b":[1,{"c":"x","d":null}]}"#);
+    }
+
+    #[test]
+    fn test_signed_bytes_ignore_signature() {
+        let mut signed = manifest(&[("data/tickets.ndjson", "abc")]);
+        let unsigned = signed_bytes(&signed).unwrap();
+        signed.signature = Some(ManifestSignature { algorithm: HMAC_ALGORITHM.to_string(), value: "00".to_string() });
+        assert_eq!(signed_bytes(&signed).unwrap(), unsigned);
+
+        // Any change to the manifest changes what is signed
+        signed.checksums.insert("data/tickets.ndjson".to_string(), "abd".to_string());
+        assert_ne!(signed_bytes(&signed).unwrap(), unsigned);
+    }
+
+    #[test]
+    fn test_hashing_writer() {
+        let mut buffer = Vec::new();
+        let mut writer = HashingWriter::new(&mut buffer);
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+        assert_eq!(writer.finish(), sha256_hex(b"hello world"));
+        assert_eq!(buffer, b"hello world");
+    }
+
+    #[test]
+    fn test_check_entries() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in [("data/tickets.ndjson", "{}\n"), ("files/a.png", "png"), ("files/extra.txt", "x")] {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(data.as_bytes()).unwrap();
+        }
+        let mut archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+        let good = sha256_hex(b"{}\n");
+        let manifest = manifest(&[
+            ("data/tickets.ndjson", good.as_str()),
+            ("files/a.png", "0000"),
+            ("files/gone.png", "0000"),
+        ]);
+        let mut verification = BackupVerification::default();
+        check_entries(&mut archive, &manifest, &mut verification).unwrap();
+
+        assert_eq!(verification.entries_checked, 1);
+        assert_eq!(verification.problems, vec![
+            "files/a.png: checksum mismatch".to_string(),
+            "files/extra.txt: not listed in the manifest".to_string(),
+            "files/gone.png: missing from the archive".to_string(),
+        ]);
+    }
+}
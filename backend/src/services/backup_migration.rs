@@ -0,0 +1,287 @@
+//! Backup Migration
+//!
+//! Archives record the last schema migration applied when they were taken
+//! (`BackupManifest::schema_version`). An archive from an older schema can't be inserted into
+//! the current tables directly: renamed columns would be dropped and new constraints could
+//! reject rows. Instead it is loaded into a staging schema built from the embedded migrations
+//! up to the archive's version, the migrations since are run there, and the migrated rows are
+//! copied into the live tables.
+
+use diesel::migration::{Migration, MigrationSource};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Nullable, Text};
+use diesel_migrations::MigrationHarness;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::{DbConnection, MIGRATIONS};
+use crate::services::backup::{quote_ident, BackupError};
+
+#[derive(QueryableByName)]
+struct Setting {
+    #[diesel(sql_type = Text)]
+    value: String,
+}
+
+#[derive(QueryableByName)]
+struct Version {
+    #[diesel(sql_type = Nullable<Text>)]
+    version: Option<String>,
+}
+
+fn embedded_migrations() -> Result<Vec<Box<dyn Migration<Pg>>>, BackupError> {
+    let mut migrations = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|e| BackupError::SchemaMigration(e.to_string()))?;
+    migrations.sort_by_key(|migration| migration.name().version().to_string());
+    Ok(migrations)
+}
+
+/// Version of the newest migration this build knows about
+pub fn latest_schema_version() -> Result<Option<String>, BackupError> {
+    Ok(embedded_migrations()?
+        .last()
+        .map(|migration| migration.name().version().to_string()))
+}
+
+/// Version of the newest migration applied to the database
+pub fn applied_schema_version(conn: &mut DbConnection) -> Result<Option<String>, BackupError> {
+    let row: Version = sql_query("SELECT MAX(version)::text AS version FROM __diesel_schema_migrations")
+        .get_result(conn)?;
+    Ok(row.version)
+}
+
+/// Whether an archive taken at `archive_version` has to be migrated to restore on `current`
+///
+/// Archives without a recorded version predate this check and are restored directly.
+pub fn needs_migration(archive_version: Option<&str>, current: Option<&str>) -> bool {
+    matches!((archive_version, current), (Some(archive), Some(current)) if archive < current)
+}
+
+/// A column of a table, as `information_schema.columns` describes it
+#[derive(QueryableByName)]
+struct ColumnInfo {
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    data_type: String,
+    #[diesel(sql_type = Text)]
+    udt_schema: String,
+    #[diesel(sql_type = Text)]
+    udt_name: String,
+}
+
+/// Columns a table has in a given schema
+fn schema_columns(conn: &mut DbConnection, schema: &str, table_name: &str) -> Result<Vec<ColumnInfo>, BackupError> {
+    Ok(sql_query(
+        "SELECT column_name::text AS column_name, data_type::text AS data_type, \
+         udt_schema::text AS udt_schema, udt_name::text AS udt_name \
+         FROM information_schema.columns \
+         WHERE table_schema = $1 AND table_name = $2 AND is_generated = 'NEVER' \
+         ORDER BY ordinal_position",
+    )
+    .bind::<Text, _>(schema)
+    .bind::<Text, _>(table_name)
+    .load(conn)?)
+}
+
+/// Expression selecting a staged column for insertion into a live column
+///
+/// Replaying the migrations in the staging schema creates its own copies of the enum types,
+/// and Postgres has no cast between two enum types, so enum values go through text.
+fn staged_column_expr(live: &ColumnInfo) -> String {
+    let column = quote_ident(&live.column_name);
+    if live.data_type == "USER-DEFINED" {
+        format!(
+            "{}::text::{}.{}",
+            column,
+            quote_ident(&live.udt_schema),
+            quote_ident(&live.udt_name)
+        )
+    } else {
+        column
+    }
+}
+
+/// A temporary schema an old archive is loaded and migrated in
+///
+/// While entered, the connection's `search_path` puts the staging schema first, so unqualified
+/// table names (the migrations and the regular restore code) resolve to the staging tables.
+/// Always `remove` it, whether the restore succeeded or not.
+pub struct StagingSchema {
+    name: String,
+    live_schema: String,
+    original_search_path: String,
+}
+
+impl StagingSchema {
+    /// Create the staging schema, enter it and run the migrations up to `version`
+    pub fn create(conn: &mut DbConnection, version: &str) -> Result<Self, BackupError> {
+        let live_schema: Setting = sql_query("SELECT current_schema()::text AS value").get_result(conn)?;
+        let search_path: Setting = sql_query("SELECT current_setting('search_path') AS value").get_result(conn)?;
+        let staging = Self {
+            name: format!("backup_staging_{}", Uuid::new_v4().simple()),
+            live_schema: live_schema.value,
+            original_search_path: search_path.value,
+        };
+
+        sql_query(format!("CREATE SCHEMA {}", staging.name)).execute(conn)?;
+        if let Err(e) = staging.enter(conn).and_then(|_| staging.migrate(conn, |v| v <= version)) {
+            staging.remove(conn);
+            return Err(e);
+        }
+        Ok(staging)
+    }
+
+    fn enter(&self, conn: &mut DbConnection) -> Result<(), BackupError> {
+        // The live schema stays on the path for extension functions (uuid-ossp, pgcrypto)
+        sql_query(format!("SET search_path TO {}, {}", self.name, quote_ident(&self.live_schema))).execute(conn)?;
+        sql_query(format!(
+            "CREATE TABLE IF NOT EXISTS {}.__diesel_schema_migrations (\
+             version VARCHAR(50) PRIMARY KEY NOT NULL, \
+             run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP)",
+            self.name
+        ))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// Run the embedded migrations whose version `select` accepts, in order
+    fn migrate(&self, conn: &mut DbConnection, select: impl Fn(&str) -> bool) -> Result<usize, BackupError> {
+        let mut applied = 0;
+        for migration in embedded_migrations()? {
+            let version = migration.name().version().to_string();
+            if !select(&version) {
+                continue;
+            }
+            conn.run_migration(migration.as_ref())
+                .map_err(|e| BackupError::SchemaMigration(format!("{}: {}", migration.name(), e)))?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// Run the migrations after `version`, bringing the staged data up to the current schema
+    pub fn migrate_after(&self, conn: &mut DbConnection, version: &str) -> Result<usize, BackupError> {
+        let applied = self.migrate(conn, |v| v > version)?;
+        log::info!("Migrated staged backup through {} migration(s)", applied);
+        Ok(applied)
+    }
+
+    /// Copy a staged table into the live schema, returning the rows inserted
+    ///
+    /// Must be called after `leave`. Rows that already exist are left alone.
+    pub fn copy_table(&self, conn: &mut DbConnection, table_name: &str) -> Result<usize, BackupError> {
+        let staged: HashSet<String> = schema_columns(conn, &self.name, table_name)?
+            .into_iter()
+            .map(|column| column.column_name)
+            .collect();
+        let columns: Vec<ColumnInfo> = schema_columns(conn, &self.live_schema, table_name)?
+            .into_iter()
+            .filter(|column| staged.contains(&column.column_name))
+            .collect();
+        if columns.is_empty() {
+            return Ok(0);
+        }
+
+        let column_list = columns.iter().map(|c| quote_ident(&c.column_name)).collect::<Vec<_>>().join(", ");
+        let select_list = columns.iter().map(staged_column_expr).collect::<Vec<_>>().join(", ");
+        let query = format!(
+            "INSERT INTO {live}.{table} ({columns}) SELECT {select} FROM {staging}.{table} ON CONFLICT DO NOTHING",
+            live = quote_ident(&self.live_schema),
+            staging = self.name,
+            table = quote_ident(table_name),
+            columns = column_list,
+            select = select_list,
+        );
+        Ok(sql_query(query).execute(conn)?)
+    }
+
+    /// Go back to the connection's original `search_path`
+    pub fn leave(&self, conn: &mut DbConnection) -> Result<(), BackupError> {
+        sql_query(format!("SET search_path TO {}", self.original_search_path)).execute(conn)?;
+        Ok(())
+    }
+
+    /// Leave and remove the staging schema; failures are logged since the restore is over
+    pub fn remove(self, conn: &mut DbConnection) {
+        if let Err(e) = self.leave(conn) {
+            log::error!("Failed to reset search_path after staged restore: {}", e);
+        }
+        if let Err(e) = sql_query(format!("DROP SCHEMA IF EXISTS {} CASCADE", self.name)).execute(conn) {
+            log::error!("Failed to drop staging schema {}: {}", self.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::r2d2::{self, ConnectionManager};
+
+    /// Version of the migration that created the enum types
+    const INITIAL_SCHEMA_VERSION: &str = "20250603015044";
+
+    #[test]
+    fn test_needs_migration() {
+        assert!(needs_migration(Some("20260104000001"), Some("20260128000000")));
+        assert!(!needs_migration(Some("20260128000000"), Some("20260128000000")));
+        // Newer archives are restored directly (with a warning in the preview)
+        assert!(!needs_migration(Some("20260130000000"), Some("20260128000000")));
+        // Archives from before versions were recorded
+        assert!(!needs_migration(None, Some("20260128000000")));
+    }
+
+    #[test]
+    fn test_staged_column_expr_casts_enums_through_text() {
+        let column = |data_type: &str, udt_name: &str| ColumnInfo {
+            column_name: "role".to_string(),
+            data_type: data_type.to_string(),
+            udt_schema: "public".to_string(),
+            udt_name: udt_name.to_string(),
+        };
+        assert_eq!(staged_column_expr(&column("USER-DEFINED", "user_role")), "\"role\"::text::\"public\".\"user_role\"");
+        assert_eq!(staged_column_expr(&column("character varying", "varchar")), "\"role\"");
+    }
+
+    /// Stages the initial schema (which defines its own enum types) and copies a user back
+    ///
+    /// Needs a migrated database in `TEST_DATABASE_URL`; skipped when it isn't set.
+    #[test]
+    fn test_copy_table_with_enum_columns() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL not set; skipping staged restore test");
+            return;
+        };
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<diesel::PgConnection>::new(url))
+            .expect("test database pool");
+        let mut conn = pool.get().expect("test database connection");
+        conn.run_pending_migrations(MIGRATIONS).expect("migrate test database");
+
+        let user_uuid = Uuid::now_v7();
+        let staging = StagingSchema::create(&mut conn, INITIAL_SCHEMA_VERSION).expect("create staging schema");
+        let result = (|| -> Result<usize, BackupError> {
+            sql_query(format!(
+                "INSERT INTO users (uuid, name, role) VALUES ('{}', 'Staged User', 'technician')",
+                user_uuid
+            ))
+            .execute(&mut conn)?;
+            staging.migrate_after(&mut conn, INITIAL_SCHEMA_VERSION)?;
+            staging.leave(&mut conn)?;
+            staging.copy_table(&mut conn, "users")
+        })();
+        staging.remove(&mut conn);
+
+        assert_eq!(result.expect("copy staged users"), 1);
+        let role: Setting = sql_query(format!("SELECT role::text AS value FROM users WHERE uuid = '{}'", user_uuid))
+            .get_result(&mut conn)
+            .expect("copied user");
+        assert_eq!(role.value, "technician");
+        sql_query(format!("DELETE FROM users WHERE uuid = '{}'", user_uuid))
+            .execute(&mut conn)
+            .expect("clean up copied user");
+    }
+}
@@ -0,0 +1,403 @@
+//! Scheduled Backup Service
+//!
+//! Runs the backup schedules stored in `backup_schedules`. A background task checks once a
+//! minute for due schedules; each run creates an export job like a manual backup, optionally
+//! uploads the archive to the off-site target, then prunes the schedule's older backups.
+//!
+//! - Passwords for sensitive exports come from the environment variable or secret file the
+//!   schedule names; they are never stored in the database.
+//! - The off-site target is an S3 bucket (or S3-compatible service such as MinIO) configured
+//!   with `BACKUP_S3_BUCKET`, `BACKUP_S3_REGION`, `BACKUP_S3_ACCESS_KEY`,
+//!   `BACKUP_S3_SECRET_KEY`, `BACKUP_S3_ENDPOINT` and `BACKUP_S3_PREFIX` (default `backups`).
+//! - Retention keeps the newest backup of each of the last `keep_daily` days and
+//!   `keep_weekly` ISO weeks that have backups; both 0 keeps everything.
+//! - Failed runs are recorded on the job and emailed to every administrator.
+
+use chrono::{Datelike, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::Pool;
+use crate::models::{BackupJob, BackupJobUpdate, BackupSchedule, NewBackupJob};
+use crate::repository;
+use crate::repository::backup as backup_repo;
+use crate::services::backup as backup_service;
+use crate::utils::cron::CronSchedule;
+use crate::utils::storage::{create_storage, Storage, StorageConfig};
+
+/// How often the scheduler looks for due schedules
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Storage for off-site copies, if `BACKUP_S3_BUCKET` is set
+pub fn remote_storage() -> Option<Arc<dyn Storage>> {
+    StorageConfig::s3_from_env("BACKUP_S3").map(create_storage)
+}
+
+fn remote_prefix() -> String {
+    std::env::var("BACKUP_S3_PREFIX")
+        .ok()
+        .map(|prefix| prefix.trim_matches('/').to_string())
+        .filter(|prefix| !prefix.is_empty())
+        .unwrap_or_else(|| "backups".to_string())
+}
+
+/// Next run of a cron expression after `after`
+pub fn next_run(cron_expression: &str, after: NaiveDateTime) -> Result<Option<NaiveDateTime>, String> {
+    Ok(CronSchedule::parse(cron_expression)?.next_after(after))
+}
+
+/// Read the archive password from the schedule's secret file or environment variable
+pub fn resolve_password(schedule: &BackupSchedule) -> Result<Option<String>, String> {
+    if !schedule.include_sensitive {
+        return Ok(None);
+    }
+
+    let password = if let Some(path) = schedule.password_file.as_deref().filter(|path| !path.is_empty()) {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read password file {}: {}", path, e))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string()
+    } else if let Some(name) = schedule.password_env.as_deref().filter(|name| !name.is_empty()) {
+        std::env::var(name).map_err(|_| format!("Password environment variable {} is not set", name))?
+    } else {
+        return Err("Sensitive backups need a password environment variable or file".to_string());
+    };
+
+    if password.is_empty() {
+        return Err("The backup password is empty".to_string());
+    }
+    Ok(Some(password))
+}
+
+/// Backups (id, creation time) that fall outside the retention windows
+pub fn backups_to_prune(backups: &[(Uuid, NaiveDateTime)], keep_daily: usize, keep_weekly: usize) -> Vec<Uuid> {
+    if keep_daily == 0 && keep_weekly == 0 {
+        return Vec::new();
+    }
+
+    let mut newest_first = backups.to_vec();
+    newest_first.sort_by_key(|backup| std::cmp::Reverse(backup.1));
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut prune = Vec::new();
+    for (id, created_at) in newest_first {
+        let date = created_at.date();
+        let week = (date.iso_week().year(), date.iso_week().week());
+
+        // The first backup seen for a day/week is that period's newest
+        let keep_for_day = days.len() < keep_daily && !days.contains(&date);
+        let keep_for_week = weeks.len() < keep_weekly && !weeks.contains(&week);
+        if keep_for_day {
+            days.insert(date);
+        }
+        if keep_for_week {
+            weeks.insert(week);
+        }
+        if !keep_for_day && !keep_for_week {
+            prune.push(id);
+        }
+    }
+    prune
+}
+
+/// Start the background task that runs due schedules
+pub fn spawn_scheduler(pool: Pool, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            run_due_schedules(&pool, &storage).await;
+        }
+    });
+}
+
+async fn run_due_schedules(pool: &Pool, storage: &Arc<dyn Storage>) {
+    let claim_pool = pool.clone();
+    let claimed = tokio::task::spawn_blocking(move || -> Result<Vec<BackupSchedule>, String> {
+        let mut conn = claim_pool.get().map_err(|e| e.to_string())?;
+        let now = Utc::now().naive_utc();
+        let mut claimed = Vec::new();
+        for schedule in backup_repo::get_due_backup_schedules(&mut conn, now).map_err(|e| e.to_string())? {
+            let Some(expected) = schedule.next_run_at else { continue };
+            let next = next_run(&schedule.cron_expression, now).unwrap_or_else(|e| {
+                warn!(schedule_id = schedule.id, error = %e, "Invalid cron expression; schedule paused");
+                None
+            });
+            if backup_repo::claim_backup_schedule_run(&mut conn, schedule.id, expected, next, now).map_err(|e| e.to_string())? {
+                claimed.push(schedule);
+            }
+        }
+        Ok(claimed)
+    })
+    .await;
+
+    let schedules = match claimed {
+        Ok(Ok(schedules)) => schedules,
+        Ok(Err(e)) => {
+            error!(error = %e, "Failed to load due backup schedules");
+            return;
+        }
+        Err(e) => {
+            error!(error = ?e, "Backup scheduler task panicked");
+            return;
+        }
+    };
+
+    for schedule in schedules {
+        if let Err(e) = run_schedule(pool.clone(), storage.clone(), schedule).await {
+            error!(error = %e, "Scheduled backup failed");
+        }
+    }
+}
+
+/// Run a schedule now: back up, upload, prune; failures are recorded and emailed
+pub async fn run_schedule(pool: Pool, storage: Arc<dyn Storage>, schedule: BackupSchedule) -> Result<BackupJob, String> {
+    let schedule_id = schedule.id;
+    let schedule_name = schedule.name.clone();
+    info!(schedule_id, name = %schedule_name, "Running scheduled backup");
+
+    let result = run_backup(&pool, storage, &schedule).await;
+    match result {
+        Ok(job) => {
+            if let Err(e) = apply_retention(&pool, &schedule).await {
+                warn!(schedule_id, error = %e, "Backup retention failed");
+            }
+            info!(schedule_id, job_id = %job.id, "Scheduled backup completed");
+            Ok(job)
+        }
+        Err((job_id, message)) => {
+            if let Some(job_id) = job_id {
+                let pool = pool.clone();
+                let message = message.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    if let Ok(mut conn) = pool.get() {
+                        let _ = backup_repo::update_backup_job(&mut conn, job_id, BackupJobUpdate {
+                            status: Some("failed".to_string()),
+                            file_path: None,
+                            file_size: None,
+                            error_message: Some(message),
+                            completed_at: Some(Utc::now().naive_utc()),
+                        });
+                    }
+                })
+                .await;
+            }
+            notify_admins(&pool, &schedule_name, &message).await;
+            Err(format!("{}: {}", schedule_name, message))
+        }
+    }
+}
+
+/// Create the archive and upload it; on failure returns the job (if one was created) and why
+async fn run_backup(
+    pool: &Pool,
+    storage: Arc<dyn Storage>,
+    schedule: &BackupSchedule,
+) -> Result<BackupJob, (Option<Uuid>, String)> {
+    let blocking_pool = pool.clone();
+    let blocking_schedule = schedule.clone();
+    let (job_id, backup_path) = tokio::task::spawn_blocking(move || {
+        let mut conn = blocking_pool.get().map_err(|e| (None, format!("Database error: {}", e)))?;
+        let job = backup_repo::create_backup_job(&mut conn, NewBackupJob {
+            job_type: "export".to_string(),
+            status: "processing".to_string(),
+            include_sensitive: blocking_schedule.include_sensitive,
+            created_by: None,
+            schedule_id: Some(blocking_schedule.id),
+        })
+        .map_err(|e| (None, format!("Failed to create job: {}", e)))?;
+
+        let password = resolve_password(&blocking_schedule).map_err(|e| (Some(job.id), e))?;
+        let path = backup_service::create_backup(
+            &mut conn,
+            storage.as_ref(),
+            job.id,
+            blocking_schedule.include_sensitive,
+            password.as_deref(),
+        )
+            .map_err(|e| (Some(job.id), e.to_string()))?;
+        Ok((job.id, path))
+    })
+    .await
+    .map_err(|e| (None, format!("Backup task panicked: {:?}", e)))??;
+
+    if schedule.upload_remote {
+        let storage = remote_storage()
+            .ok_or_else(|| (Some(job_id), "Off-site upload is enabled but BACKUP_S3_BUCKET is not set".to_string()))?;
+        let filename = backup_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("backup_{}.zip", job_id));
+        // Stream the archive from disk rather than holding it in memory
+        let remote_path = format!("{}/{}", remote_prefix().trim_end_matches('/'), filename);
+        storage
+            .write_file_from_path(&remote_path, &backup_path, "application/zip")
+            .await
+            .map_err(|e| (Some(job_id), format!("Upload failed: {:?}", e)))?;
+
+        let pool = pool.clone();
+        return tokio::task::spawn_blocking(move || {
+            let mut conn = pool.get().map_err(|e| (Some(job_id), format!("Database error: {}", e)))?;
+            backup_repo::set_backup_job_remote_path(&mut conn, job_id, &remote_path)
+                .map_err(|e| (Some(job_id), format!("Failed to record upload: {}", e)))
+        })
+        .await
+        .map_err(|e| (Some(job_id), format!("Backup task panicked: {:?}", e)))?;
+    }
+
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| (Some(job_id), format!("Database error: {}", e)))?;
+        backup_repo::get_backup_job(&mut conn, job_id).map_err(|e| (Some(job_id), e.to_string()))
+    })
+    .await
+    .map_err(|e| (Some(job_id), format!("Backup task panicked: {:?}", e)))?
+}
+
+/// Delete the schedule's backups that fall outside its retention windows
+async fn apply_retention(pool: &Pool, schedule: &BackupSchedule) -> Result<usize, String> {
+    let schedule_id = schedule.id;
+    let (keep_daily, keep_weekly) = (schedule.keep_daily.max(0) as usize, schedule.keep_weekly.max(0) as usize);
+
+    let lookup_pool = pool.clone();
+    let expired: Vec<BackupJob> = tokio::task::spawn_blocking(move || -> Result<Vec<BackupJob>, String> {
+        let mut conn = lookup_pool.get().map_err(|e| e.to_string())?;
+        let jobs = backup_repo::get_completed_schedule_exports(&mut conn, schedule_id).map_err(|e| e.to_string())?;
+        let backups: Vec<(Uuid, NaiveDateTime)> = jobs.iter().map(|job| (job.id, job.created_at)).collect();
+        let prune: HashSet<Uuid> = backups_to_prune(&backups, keep_daily, keep_weekly).into_iter().collect();
+        Ok(jobs.into_iter().filter(|job| prune.contains(&job.id)).collect())
+    })
+    .await
+    .map_err(|e| format!("Retention task panicked: {:?}", e))??;
+
+    if expired.is_empty() {
+        return Ok(0);
+    }
+
+    let storage = remote_storage();
+    for job in &expired {
+        if let (Some(storage), Some(remote_path)) = (&storage, &job.remote_path) {
+            if let Err(e) = storage.delete_file(remote_path).await {
+                warn!(job_id = %job.id, error = ?e, "Failed to delete off-site backup");
+            }
+        }
+    }
+
+    let pool = pool.clone();
+    let count = expired.len();
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        for job in expired {
+            if let Some(file_path) = &job.file_path {
+                backup_service::delete_backup_file(file_path).map_err(|e| e.to_string())?;
+            }
+            backup_repo::delete_backup_job(&mut conn, job.id).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Retention task panicked: {:?}", e))??;
+
+    info!(schedule_id, pruned = count, "Pruned old scheduled backups");
+    Ok(count)
+}
+
+/// Email every administrator about a failed run
+async fn notify_admins(pool: &Pool, schedule_name: &str, message: &str) {
+    let pool = pool.clone();
+    let recipients = tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().ok()?;
+        let base_url = std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let branding = crate::utils::email_branding::get_email_branding(&mut conn, &base_url);
+        let emails: Vec<String> = repository::users::get_admin_users(&mut conn)
+            .ok()?
+            .into_iter()
+            .filter_map(|admin| repository::user_helpers::get_primary_email(&admin.uuid, &mut conn))
+            .collect();
+        Some((emails, branding))
+    })
+    .await
+    .ok()
+    .flatten();
+
+    let Some((emails, branding)) = recipients else {
+        warn!("Could not load administrators to notify about a failed backup");
+        return;
+    };
+
+    let email_service = match crate::utils::email::EmailService::from_env() {
+        Ok(service) => service,
+        Err(e) => {
+            warn!(error = ?e, "Email service unavailable; backup failure not sent");
+            return;
+        }
+    };
+    for email in emails {
+        if let Err(e) = email_service.send_backup_failure_email(&email, schedule_name, message, &branding).await {
+            warn!(error = %e, "Failed to send backup failure email");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_backups_to_prune() {
+        // Two backups a day from Monday 5 Jan to Sunday 18 Jan 2026
+        let backups: Vec<(Uuid, NaiveDateTime)> = (5..=18)
+            .flat_map(|day| [at(1, day, 3), at(1, day, 15)])
+            .map(|time| (Uuid::now_v7(), time))
+            .collect();
+        let id_at = |time: NaiveDateTime| backups.iter().find(|(_, created)| *created == time).unwrap().0;
+
+        let prune = backups_to_prune(&backups, 3, 2);
+        let kept: Vec<Uuid> = backups.iter().map(|(id, _)| *id).filter(|id| !prune.contains(id)).collect();
+
+        // Newest of the last three days; the newest of the last week is among them, plus
+        // the newest of the week before
+        let expected = [at(1, 18, 15), at(1, 17, 15), at(1, 16, 15), at(1, 11, 15)].map(id_at);
+        assert_eq!(kept.len(), expected.len());
+        assert!(expected.iter().all(|id| kept.contains(id)));
+
+        assert!(backups_to_prune(&backups, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_password_requires_a_source() {
+        let mut schedule = BackupSchedule {
+            id: 1,
+            name: "Nightly".to_string(),
+            cron_expression: "0 3 * * *".to_string(),
+            include_sensitive: false,
+            password_env: None,
+            password_file: None,
+            keep_daily: 7,
+            keep_weekly: 4,
+            upload_remote: false,
+            enabled: true,
+            last_run_at: None,
+            next_run_at: None,
+            created_by: None,
+            created_at: at(1, 1, 0),
+            updated_at: at(1, 1, 0),
+        };
+        assert_eq!(resolve_password(&schedule), Ok(None));
+
+        schedule.include_sensitive = true;
+        assert!(resolve_password(&schedule).is_err());
+
+        schedule.password_env = Some("NOSDESK_TEST_BACKUP_PASSWORD_UNSET".to_string());
+        assert!(resolve_password(&schedule).is_err());
+    }
+}
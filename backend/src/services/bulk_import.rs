@@ -0,0 +1,1118 @@
+//! Bulk Import Service
+//!
+//! Imports tickets, users and devices from CSV or XLSX files, and exports them back to CSV.
+//!
+//! An import runs in three steps:
+//!
+//! - **Upload** stores the file in the temp directory and returns a preview: the column
+//!   headers, the first few rows and a suggested mapping from import fields to columns.
+//! - **Mapping** is chosen by the admin and checked against the file's headers.
+//! - **The job** processes every row in the background, recording progress and row-level
+//!   errors on its `import_jobs` record. A dry run performs the same validation and lookups
+//!   but writes nothing, so its counts show what a real run would create and update.
+//!
+//! Records are matched so that importing a file twice updates instead of duplicating:
+//!
+//! - Users by any of their email addresses (`user_emails`)
+//! - Devices by serial number
+//! - Tickets by `id` when that column is mapped; rows without one create new tickets
+//!
+//! Requester, assignee and primary user columns hold email addresses. Each row is written in
+//! its own transaction, so a bad row is reported and skipped without affecting the others.
+//! Imported tickets do not start approval chains or send notifications.
+//!
+//! Uploads live in the temp directory and are removed by the temp file cleanup, so a dry run
+//! can be followed by a real run of the same file.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::path::PathBuf;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::{DbConnection, Pool};
+use crate::models::{
+    DeviceUpdate, ImportEntityType, ImportJob, ImportJobUpdate, ImportRowError, NewDevice,
+    NewImportJob, NewTicket, TicketPriority, TicketStatus, TicketUpdate, User, UserRole,
+    UserUpdate,
+};
+use crate::repository;
+use crate::repository::import_jobs as import_repo;
+use crate::repository::tickets::TicketFilters;
+use crate::services::admin::temp_dir;
+use crate::services::ticket_status::check_status_change;
+use crate::utils::user::NewUserBuilder;
+
+/// Largest file accepted for import
+pub const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Largest number of data rows in one import
+const MAX_ROWS: usize = 50_000;
+
+/// Rows shown in the upload preview
+const PREVIEW_ROWS: usize = 10;
+
+/// Rows processed between progress updates
+const PROGRESS_INTERVAL: usize = 50;
+
+/// Row errors kept on the job record; the failed count still covers every row
+const MAX_ROW_ERRORS: usize = 1000;
+
+#[derive(Debug)]
+pub enum ImportError {
+    UnsupportedFile(String),
+    Parse(String),
+    Mapping(Vec<String>),
+    FileNotFound,
+    Database(diesel::result::Error),
+    Io(std::io::Error),
+    Csv(csv::Error),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::UnsupportedFile(name) => {
+                write!(f, "Unsupported file type: {} (expected .csv or .xlsx)", name)
+            }
+            ImportError::Parse(e) => write!(f, "Could not read file: {}", e),
+            ImportError::Mapping(errors) => write!(f, "Invalid column mapping: {}", errors.join("; ")),
+            ImportError::FileNotFound => write!(f, "Uploaded file not found; upload it again"),
+            ImportError::Database(e) => write!(f, "Database error: {}", e),
+            ImportError::Io(e) => write!(f, "IO error: {}", e),
+            ImportError::Csv(e) => write!(f, "CSV error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for ImportError {
+    fn from(e: diesel::result::Error) -> Self {
+        ImportError::Database(e)
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+impl From<csv::Error> for ImportError {
+    fn from(e: csv::Error) -> Self {
+        ImportError::Csv(e)
+    }
+}
+
+// ============= Reading files =============
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileFormat {
+    Csv,
+    Xlsx,
+}
+
+impl FileFormat {
+    fn from_file_name(name: &str) -> Option<Self> {
+        let extension = name.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(FileFormat::Csv),
+            "xlsx" => Some(FileFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Header row and data rows of an import file, with cells trimmed
+#[derive(Debug, Default, PartialEq)]
+struct Sheet {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+fn parse_csv(data: &[u8]) -> Result<Sheet, ImportError> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let headers = reader.headers()?.iter().map(String::from).collect();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        rows.push(record.iter().map(String::from).collect());
+        if rows.len() > MAX_ROWS {
+            return Err(ImportError::Parse(format!("more than {} rows", MAX_ROWS)));
+        }
+    }
+
+    Ok(Sheet { headers, rows })
+}
+
+/// Read the first worksheet of an XLSX workbook
+fn parse_xlsx(data: &[u8]) -> Result<Sheet, ImportError> {
+    use calamine::{Data, Reader, Xlsx};
+
+    let mut workbook: Xlsx<_> =
+        Xlsx::new(Cursor::new(data)).map_err(|e| ImportError::Parse(e.to_string()))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| ImportError::Parse("workbook has no worksheets".to_string()))?
+        .map_err(|e| ImportError::Parse(e.to_string()))?;
+
+    let mut rows = range.rows().map(|row| {
+        row.iter()
+            .map(|cell| match cell {
+                Data::Empty => String::new(),
+                cell => cell.to_string().trim().to_string(),
+            })
+            .collect::<Vec<String>>()
+    });
+
+    let headers = rows.next().unwrap_or_default();
+    let rows: Vec<Vec<String>> = rows.collect();
+    if rows.len() > MAX_ROWS {
+        return Err(ImportError::Parse(format!("more than {} rows", MAX_ROWS)));
+    }
+
+    Ok(Sheet { headers, rows })
+}
+
+fn parse_file(format: FileFormat, data: &[u8]) -> Result<Sheet, ImportError> {
+    let mut sheet = match format {
+        FileFormat::Csv => parse_csv(data)?,
+        FileFormat::Xlsx => parse_xlsx(data)?,
+    };
+
+    if sheet.headers.iter().all(|h| h.is_empty()) {
+        return Err(ImportError::Parse("the first row must contain column headers".to_string()));
+    }
+    // Blank lines at the end of spreadsheets are common; drop them
+    while sheet.rows.last().is_some_and(|row| row.iter().all(|v| v.is_empty())) {
+        sheet.rows.pop();
+    }
+
+    Ok(sheet)
+}
+
+// ============= Uploads =============
+
+/// Path of an uploaded import file; rejects ids that are not ones we handed out
+fn upload_path(file_id: &str) -> Result<(PathBuf, FileFormat), ImportError> {
+    let (stem, _) = file_id.rsplit_once('.').ok_or(ImportError::FileNotFound)?;
+    let valid = stem
+        .strip_prefix("import-")
+        .is_some_and(|uuid| Uuid::parse_str(uuid).is_ok());
+    let format = FileFormat::from_file_name(file_id).filter(|_| valid).ok_or(ImportError::FileNotFound)?;
+
+    Ok((temp_dir().join(file_id), format))
+}
+
+fn load_upload(file_id: &str) -> Result<Sheet, ImportError> {
+    let (path, format) = upload_path(file_id)?;
+    let data = match std::fs::read(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(ImportError::FileNotFound),
+        Err(e) => return Err(e.into()),
+    };
+    parse_file(format, &data)
+}
+
+/// Describes an import field for the mapping step
+#[derive(Debug, Serialize)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub required: bool,
+    /// Other header names the field is suggested for
+    #[serde(skip)]
+    aliases: &'static [&'static str],
+}
+
+const fn field(name: &'static str, required: bool, aliases: &'static [&'static str]) -> FieldSpec {
+    FieldSpec { name, required, aliases }
+}
+
+const USER_FIELDS: &[FieldSpec] = &[
+    field("email", true, &["email address", "mail", "e-mail"]),
+    field("name", false, &["full name", "display name"]),
+    field("role", false, &[]),
+    field("pronouns", false, &[]),
+];
+
+const DEVICE_FIELDS: &[FieldSpec] = &[
+    field("serial_number", true, &["serial", "serial no", "sn"]),
+    field("name", false, &["device name", "device"]),
+    field("hostname", false, &["host"]),
+    field("device_type", false, &["type"]),
+    field("manufacturer", false, &["make", "vendor"]),
+    field("model", false, &[]),
+    field("warranty_status", false, &["warranty"]),
+    field("location", false, &[]),
+    field("notes", false, &[]),
+    field("primary_user_email", false, &["primary user", "user email", "owner", "assigned to"]),
+];
+
+const TICKET_FIELDS: &[FieldSpec] = &[
+    field("id", false, &["ticket id", "ticket", "#"]),
+    field("title", false, &["subject", "summary"]),
+    field("description", false, &["details", "body"]),
+    field("status", false, &["state"]),
+    field("priority", false, &[]),
+    field("requester_email", false, &["requester", "requested by", "reporter"]),
+    field("assignee_email", false, &["assignee", "assigned to", "technician"]),
+];
+
+/// Fields that can be mapped for an entity type
+pub fn fields(entity: ImportEntityType) -> &'static [FieldSpec] {
+    match entity {
+        ImportEntityType::Users => USER_FIELDS,
+        ImportEntityType::Devices => DEVICE_FIELDS,
+        ImportEntityType::Tickets => TICKET_FIELDS,
+    }
+}
+
+/// Lowercase and drop punctuation so "Serial No." matches "serial_number"-style names
+fn normalize_header(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '_' || *c == '#')
+        .map(|c| if c == '_' { ' ' } else { c.to_ascii_lowercase() })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Map each field to the first header that looks like it
+pub fn suggest_mapping(entity: ImportEntityType, headers: &[String]) -> HashMap<String, String> {
+    let normalized: Vec<String> = headers.iter().map(|h| normalize_header(h)).collect();
+    let mut used = HashSet::new();
+    let mut mapping = HashMap::new();
+
+    for spec in fields(entity) {
+        let candidates: Vec<String> = std::iter::once(normalize_header(spec.name))
+            .chain(spec.aliases.iter().map(|a| normalize_header(a)))
+            .collect();
+        let found = candidates.iter().find_map(|candidate| {
+            normalized
+                .iter()
+                .position(|h| h == candidate)
+                .filter(|index| !used.contains(index))
+        });
+        if let Some(index) = found {
+            used.insert(index);
+            mapping.insert(spec.name.to_string(), headers[index].clone());
+        }
+    }
+
+    mapping
+}
+
+/// Resolve a field -> header mapping to field -> column index
+fn resolve_mapping(
+    entity: ImportEntityType,
+    headers: &[String],
+    mapping: &HashMap<String, String>,
+) -> Result<HashMap<&'static str, usize>, ImportError> {
+    let specs = fields(entity);
+    let mut errors = Vec::new();
+    let mut columns = HashMap::new();
+
+    for (field, header) in mapping {
+        let Some(spec) = specs.iter().find(|s| s.name == field) else {
+            errors.push(format!("unknown field '{}'", field));
+            continue;
+        };
+        match headers.iter().position(|h| h == header.trim()) {
+            Some(index) => {
+                columns.insert(spec.name, index);
+            }
+            None => errors.push(format!("{}: column '{}' is not in the file", field, header)),
+        }
+    }
+    for spec in specs.iter().filter(|s| s.required) {
+        if !mapping.contains_key(spec.name) {
+            errors.push(format!("{}: a column is required", spec.name));
+        }
+    }
+    // Without an id every ticket row is new, and new tickets need a title
+    if entity == ImportEntityType::Tickets && !columns.contains_key("id") && !columns.contains_key("title") {
+        errors.push("title: a column is required unless id is mapped".to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(columns)
+    } else {
+        errors.sort();
+        Err(ImportError::Mapping(errors))
+    }
+}
+
+/// What the mapping step shows for an uploaded file
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    pub file_id: String,
+    pub entity_type: ImportEntityType,
+    pub headers: Vec<String>,
+    pub sample_rows: Vec<Vec<String>>,
+    pub total_rows: usize,
+    pub fields: &'static [FieldSpec],
+    pub suggested_mapping: HashMap<String, String>,
+}
+
+/// Check an uploaded file can be read, keep it in the temp directory and preview it
+pub fn save_upload(
+    entity: ImportEntityType,
+    file_name: &str,
+    data: &[u8],
+) -> Result<ImportPreview, ImportError> {
+    let format = FileFormat::from_file_name(file_name)
+        .ok_or_else(|| ImportError::UnsupportedFile(file_name.to_string()))?;
+    let sheet = parse_file(format, data)?;
+
+    let file_id = format!("import-{}.{}", Uuid::new_v4(), format.extension());
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(&file_id), data)?;
+
+    Ok(ImportPreview {
+        suggested_mapping: suggest_mapping(entity, &sheet.headers),
+        sample_rows: sheet.rows.iter().take(PREVIEW_ROWS).cloned().collect(),
+        total_rows: sheet.rows.len(),
+        headers: sheet.headers,
+        fields: fields(entity),
+        entity_type: entity,
+        file_id,
+    })
+}
+
+/// Validate the mapping against the uploaded file and record a pending job for it
+pub fn create_job(
+    conn: &mut DbConnection,
+    entity: ImportEntityType,
+    file_id: &str,
+    mapping: HashMap<String, String>,
+    dry_run: bool,
+    created_by: Option<Uuid>,
+) -> Result<ImportJob, ImportError> {
+    let sheet = load_upload(file_id)?;
+    resolve_mapping(entity, &sheet.headers, &mapping)?;
+
+    let mapping = serde_json::to_value(&mapping).unwrap_or_default();
+    Ok(import_repo::create_import_job(conn, NewImportJob {
+        entity_type: entity.as_str().to_string(),
+        status: "pending".to_string(),
+        dry_run,
+        file_id: file_id.to_string(),
+        mapping,
+        created_by,
+    })?)
+}
+
+/// Run an import job on a blocking thread
+pub fn spawn_import_job(pool: Pool, job_id: Uuid) {
+    tokio::task::spawn_blocking(move || {
+        let mut conn = match pool.get() {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!(job_id = %job_id, error = %e, "No database connection for import job");
+                return;
+            }
+        };
+        if let Err(e) = run_import_job(&mut conn, job_id) {
+            error!(job_id = %job_id, error = %e, "Import job failed");
+            let _ = import_repo::update_import_job(&mut conn, job_id, ImportJobUpdate {
+                status: Some("failed".to_string()),
+                error_message: Some(e.to_string()),
+                completed_at: Some(Utc::now().naive_utc()),
+                ..Default::default()
+            });
+        }
+    });
+}
+
+// ============= Processing rows =============
+
+/// One data row with the job's column mapping applied
+struct Row<'a> {
+    values: &'a [String],
+    columns: &'a HashMap<&'static str, usize>,
+}
+
+impl Row<'_> {
+    /// Mapped, non-empty cell value
+    fn get(&self, field: &str) -> Option<&str> {
+        let index = *self.columns.get(field)?;
+        self.values.get(index).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+
+    fn get_owned(&self, field: &str) -> Option<String> {
+        self.get(field).map(String::from)
+    }
+}
+
+enum RowOutcome {
+    Created,
+    Updated,
+}
+
+/// Errors found in one row; all of a row's problems are reported together
+#[derive(Default)]
+struct RowErrors(Vec<(Option<&'static str>, String)>);
+
+impl RowErrors {
+    fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.0.push((Some(field), message.into()));
+    }
+
+    fn check(self) -> Result<(), RowErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<diesel::result::Error> for RowErrors {
+    fn from(e: diesel::result::Error) -> Self {
+        RowErrors(vec![(None, format!("Database error: {}", e))])
+    }
+}
+
+/// Rows in the file already handled by this run, so a dry run counts repeats as updates
+#[derive(Default)]
+struct SeenKeys(HashSet<String>);
+
+impl SeenKeys {
+    fn insert(&mut self, key: &str) -> bool {
+        self.0.insert(key.to_lowercase())
+    }
+}
+
+fn validate_email(errors: &mut RowErrors, field: &'static str, email: &str) -> bool {
+    if email.len() > 255 {
+        errors.add(field, "Email must be less than 255 characters");
+        false
+    } else if !email.contains('@') || !email.contains('.') || email.contains(char::is_whitespace) {
+        errors.add(field, format!("Invalid email address '{}'", email));
+        false
+    } else {
+        true
+    }
+}
+
+/// Look up the user an email column refers to
+fn resolve_user(
+    conn: &mut DbConnection,
+    errors: &mut RowErrors,
+    field: &'static str,
+    email: Option<&str>,
+) -> Option<User> {
+    let email = email?;
+    if !validate_email(errors, field, email) {
+        return None;
+    }
+    match repository::user_emails::find_user_by_any_email(conn, email) {
+        Ok(user) => Some(user),
+        Err(diesel::result::Error::NotFound) => {
+            errors.add(field, format!("No user with email '{}'", email));
+            None
+        }
+        Err(e) => {
+            errors.add(field, format!("Database error: {}", e));
+            None
+        }
+    }
+}
+
+fn import_user(
+    conn: &mut DbConnection,
+    row: &Row,
+    dry_run: bool,
+    seen: &mut SeenKeys,
+) -> Result<RowOutcome, RowErrors> {
+    let mut errors = RowErrors::default();
+
+    let email = row.get("email").map(crate::utils::normalize_email);
+    match &email {
+        Some(email) => {
+            validate_email(&mut errors, "email", email);
+        }
+        None => errors.add("email", "Email is required"),
+    }
+    let name = row.get_owned("name");
+    if name.as_ref().is_some_and(|n| n.len() > 255) {
+        errors.add("name", "Name must be less than 255 characters");
+    }
+    let role = match row.get("role").map(crate::utils::parse_role) {
+        Some(Ok(role)) => Some(role),
+        Some(Err(_)) => {
+            errors.add("role", "Role must be admin, technician or user");
+            None
+        }
+        None => None,
+    };
+    let pronouns = row.get_owned("pronouns");
+    errors.check()?;
+    let email = email.unwrap_or_default();
+
+    let existing = match repository::user_emails::find_user_by_any_email(conn, &email) {
+        Ok(user) => Some(user),
+        Err(diesel::result::Error::NotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let first_in_file = seen.insert(&email);
+
+    match existing {
+        Some(user) => {
+            if !dry_run {
+                repository::update_user(&user.uuid, UserUpdate {
+                    name,
+                    role,
+                    pronouns,
+                    avatar_url: None,
+                    banner_url: None,
+                    avatar_thumb: None,
+                    theme: None,
+                    microsoft_uuid: None,
+                    updated_at: Some(Utc::now().naive_utc()),
+                }, conn)?;
+            }
+            Ok(RowOutcome::Updated)
+        }
+        None if dry_run && !first_in_file => Ok(RowOutcome::Updated),
+        None => {
+            let Some(name) = name else {
+                let mut errors = RowErrors::default();
+                errors.add("name", "Name is required for new users");
+                return Err(errors);
+            };
+            if !dry_run {
+                let (new_user, email) = NewUserBuilder::new(name, email, role.unwrap_or(UserRole::User))
+                    .with_pronouns(pronouns)
+                    .build_with_email();
+                repository::user_helpers::create_user_with_email(
+                    new_user,
+                    email,
+                    false,
+                    Some("import".to_string()),
+                    conn,
+                )?;
+            }
+            Ok(RowOutcome::Created)
+        }
+    }
+}
+
+fn import_device(
+    conn: &mut DbConnection,
+    row: &Row,
+    dry_run: bool,
+    seen: &mut SeenKeys,
+) -> Result<RowOutcome, RowErrors> {
+    let mut errors = RowErrors::default();
+
+    let serial_number = row.get_owned("serial_number");
+    if serial_number.is_none() {
+        errors.add("serial_number", "Serial number is required");
+    }
+    let primary_user = resolve_user(conn, &mut errors, "primary_user_email", row.get("primary_user_email"));
+    errors.check()?;
+    let serial_number = serial_number.unwrap_or_default();
+
+    let existing = match repository::devices::get_device_by_serial_number(conn, &serial_number) {
+        Ok(device) => Some(device),
+        Err(diesel::result::Error::NotFound) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let first_in_file = seen.insert(&serial_number);
+
+    match existing {
+        Some(device) if device.deleted_at.is_some() => {
+            let mut errors = RowErrors::default();
+            errors.add("serial_number", "A device with this serial number is in the recycle bin");
+            Err(errors)
+        }
+        Some(device) => {
+            if !dry_run {
+                repository::devices::update_device(conn, device.id, DeviceUpdate {
+                    name: row.get_owned("name"),
+                    hostname: row.get_owned("hostname"),
+                    device_type: row.get_owned("device_type"),
+                    manufacturer: row.get_owned("manufacturer"),
+                    model: row.get_owned("model"),
+                    warranty_status: row.get_owned("warranty_status"),
+                    location: row.get_owned("location"),
+                    notes: row.get_owned("notes"),
+                    primary_user_uuid: primary_user.map(|u| u.uuid),
+                    ..Default::default()
+                })?;
+            }
+            Ok(RowOutcome::Updated)
+        }
+        None if dry_run && !first_in_file => Ok(RowOutcome::Updated),
+        None => {
+            let Some(name) = row.get_owned("name") else {
+                let mut errors = RowErrors::default();
+                errors.add("name", "Name is required for new devices");
+                return Err(errors);
+            };
+            if !dry_run {
+                repository::devices::create_device(conn, NewDevice {
+                    name,
+                    hostname: row.get_owned("hostname"),
+                    device_type: row.get_owned("device_type"),
+                    serial_number: Some(serial_number),
+                    manufacturer: row.get_owned("manufacturer"),
+                    model: row.get_owned("model"),
+                    warranty_status: row.get_owned("warranty_status"),
+                    location: row.get_owned("location"),
+                    notes: row.get_owned("notes"),
+                    primary_user_uuid: primary_user.map(|u| u.uuid),
+                    microsoft_device_id: None,
+                    intune_device_id: None,
+                    entra_device_id: None,
+                    compliance_state: None,
+                    last_sync_time: None,
+                    operating_system: None,
+                    os_version: None,
+                    is_managed: None,
+                    enrollment_date: None,
+                })?;
+            }
+            Ok(RowOutcome::Created)
+        }
+    }
+}
+
+/// Accepts the API values plus the spellings spreadsheets tend to use ("In Progress")
+fn parse_status(value: &str) -> Option<TicketStatus> {
+    match value.trim().to_lowercase().replace([' ', '_'], "-").as_str() {
+        "open" => Some(TicketStatus::Open),
+        "in-progress" => Some(TicketStatus::InProgress),
+        "closed" => Some(TicketStatus::Closed),
+        _ => None,
+    }
+}
+
+fn parse_priority(value: &str) -> Option<TicketPriority> {
+    match value.trim().to_lowercase().as_str() {
+        "low" => Some(TicketPriority::Low),
+        "medium" => Some(TicketPriority::Medium),
+        "high" => Some(TicketPriority::High),
+        _ => None,
+    }
+}
+
+fn import_ticket(
+    conn: &mut DbConnection,
+    row: &Row,
+    dry_run: bool,
+    _seen: &mut SeenKeys,
+) -> Result<RowOutcome, RowErrors> {
+    let mut errors = RowErrors::default();
+
+    let existing = match row.get("id").map(|id| id.trim_start_matches('#').parse::<i32>()) {
+        Some(Ok(id)) => match repository::tickets::get_ticket_by_id(conn, id) {
+            Ok(ticket) => Some(ticket),
+            Err(diesel::result::Error::NotFound) => {
+                errors.add("id", format!("Ticket #{} not found", id));
+                None
+            }
+            Err(e) => return Err(e.into()),
+        },
+        Some(Err(_)) => {
+            errors.add("id", "Ticket id must be a number");
+            None
+        }
+        None => None,
+    };
+    let title = row.get_owned("title");
+    if existing.is_none() && row.get("id").is_none() && title.is_none() {
+        errors.add("title", "Title is required for new tickets");
+    }
+    if title.as_ref().is_some_and(|t| t.len() > 255) {
+        errors.add("title", "Title must be less than 255 characters");
+    }
+    let status = row.get("status").and_then(|value| {
+        let status = parse_status(value);
+        if status.is_none() {
+            errors.add("status", "Status must be open, in-progress or closed");
+        }
+        status
+    });
+    let priority = row.get("priority").and_then(|value| {
+        let priority = parse_priority(value);
+        if priority.is_none() {
+            errors.add("priority", "Priority must be low, medium or high");
+        }
+        priority
+    });
+    let requester = resolve_user(conn, &mut errors, "requester_email", row.get("requester_email"));
+    let assignee = resolve_user(conn, &mut errors, "assignee_email", row.get("assignee_email"));
+    if assignee.as_ref().is_some_and(|u| u.role != UserRole::Technician && u.role != UserRole::Admin) {
+        errors.add("assignee_email", "Only technicians and administrators can be assigned to tickets");
+    }
+    // Imports follow the same status rules as the ticket endpoints
+    if let (Some(ticket), Some(status)) = (&existing, status) {
+        if let Err(blocked) = check_status_change(conn, ticket, status, None) {
+            errors.add("status", blocked.to_string());
+        }
+    }
+    errors.check()?;
+
+    let now = Utc::now().naive_utc();
+    match existing {
+        Some(ticket) => {
+            if !dry_run {
+                let closed_at = match status {
+                    Some(TicketStatus::Closed) if ticket.status != TicketStatus::Closed => Some(Some(now)),
+                    Some(TicketStatus::Open | TicketStatus::InProgress) => Some(None),
+                    _ => None,
+                };
+                repository::tickets::update_ticket_partial(conn, ticket.id, TicketUpdate {
+                    title,
+                    description: row.get_owned("description"),
+                    status,
+                    priority,
+                    requester_uuid: requester.map(|u| Some(u.uuid)),
+                    assignee_uuid: assignee.map(|u| Some(u.uuid)),
+                    updated_at: Some(now),
+                    closed_at,
+                    category_id: None,
+                })?;
+            }
+            Ok(RowOutcome::Updated)
+        }
+        None => {
+            if !dry_run {
+                let status = status.unwrap_or(TicketStatus::Open);
+                let ticket = repository::tickets::create_ticket(conn, NewTicket {
+                    title: title.unwrap_or_default(),
+                    description: row.get_owned("description"),
+                    status,
+                    priority: priority.unwrap_or(TicketPriority::Medium),
+                    requester_uuid: requester.map(|u| u.uuid),
+                    assignee_uuid: assignee.map(|u| u.uuid),
+                    category_id: None,
+                })?;
+                if status == TicketStatus::Closed {
+                    repository::tickets::update_ticket_partial(conn, ticket.id, TicketUpdate {
+                        closed_at: Some(Some(now)),
+                        ..Default::default()
+                    })?;
+                }
+            }
+            Ok(RowOutcome::Created)
+        }
+    }
+}
+
+/// Process every row of a pending job's file, recording progress and row errors on the job
+pub fn run_import_job(conn: &mut DbConnection, job_id: Uuid) -> Result<ImportJob, ImportError> {
+    let job = import_repo::get_import_job(conn, job_id)?;
+    let entity = ImportEntityType::parse(&job.entity_type)
+        .ok_or_else(|| ImportError::Parse(format!("unknown entity type '{}'", job.entity_type)))?;
+    let mapping: HashMap<String, String> = serde_json::from_value(job.mapping.clone())
+        .map_err(|e| ImportError::Mapping(vec![e.to_string()]))?;
+
+    let sheet = load_upload(&job.file_id)?;
+    let columns = resolve_mapping(entity, &sheet.headers, &mapping)?;
+    let total = sheet.rows.len();
+
+    import_repo::update_import_job(conn, job_id, ImportJobUpdate {
+        status: Some("processing".to_string()),
+        progress_total: Some(total as i64),
+        ..Default::default()
+    })?;
+    info!(job_id = %job_id, entity = entity.as_str(), rows = total, dry_run = job.dry_run, "Starting import");
+
+    let import_row = match entity {
+        ImportEntityType::Users => import_user,
+        ImportEntityType::Devices => import_device,
+        ImportEntityType::Tickets => import_ticket,
+    };
+
+    let (mut created, mut updated, mut failed) = (0i32, 0i32, 0i32);
+    let mut row_errors: Vec<ImportRowError> = Vec::new();
+    let mut seen = SeenKeys::default();
+
+    for (index, values) in sheet.rows.iter().enumerate() {
+        // Row 1 is the header row
+        let row_number = index + 2;
+        let row = Row { values, columns: &columns };
+
+        // A dry run writes nothing, so it does not need a transaction
+        let result = if values.iter().all(|v| v.is_empty()) {
+            None
+        } else if job.dry_run {
+            Some(import_row(conn, &row, true, &mut seen))
+        } else {
+            Some(conn.transaction(|conn| import_row(conn, &row, false, &mut seen)))
+        };
+
+        match result {
+            None => {}
+            Some(Ok(RowOutcome::Created)) => created += 1,
+            Some(Ok(RowOutcome::Updated)) => updated += 1,
+            Some(Err(RowErrors(errors))) => {
+                failed += 1;
+                for (field, message) in errors {
+                    if row_errors.len() < MAX_ROW_ERRORS {
+                        row_errors.push(ImportRowError {
+                            row: row_number,
+                            field: field.map(String::from),
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+
+        if (index + 1) % PROGRESS_INTERVAL == 0 {
+            if let Err(e) = import_repo::set_import_job_progress(
+                conn, job_id, (index + 1) as i64, created, updated, failed,
+            ) {
+                warn!(job_id = %job_id, error = %e, "Failed to record import progress");
+            }
+        }
+    }
+
+    info!(job_id = %job_id, created, updated, failed, "Import finished");
+
+    Ok(import_repo::update_import_job(conn, job_id, ImportJobUpdate {
+        status: Some("completed".to_string()),
+        progress_current: Some(total as i64),
+        created_count: Some(created),
+        updated_count: Some(updated),
+        failed_count: Some(failed),
+        errors: Some(serde_json::to_value(&row_errors).unwrap_or_default()),
+        completed_at: Some(Utc::now().naive_utc()),
+        ..Default::default()
+    })?)
+}
+
+// ============= Export =============
+
+/// Stop spreadsheet apps treating exported text as a formula
+fn sanitize_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv(headers: &[&str], rows: Vec<Vec<String>>) -> Result<Vec<u8>, ImportError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(headers)?;
+    for row in rows {
+        writer.write_record(row.iter().map(|v| sanitize_cell(v)))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| ImportError::Io(e.into_error()))
+}
+
+fn status_str(status: TicketStatus) -> &'static str {
+    match status {
+        TicketStatus::Open => "open",
+        TicketStatus::InProgress => "in-progress",
+        TicketStatus::Closed => "closed",
+    }
+}
+
+fn priority_str(priority: TicketPriority) -> &'static str {
+    match priority {
+        TicketPriority::Low => "low",
+        TicketPriority::Medium => "medium",
+        TicketPriority::High => "high",
+    }
+}
+
+fn format_datetime(value: Option<chrono::NaiveDateTime>) -> String {
+    value.map(|v| v.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default()
+}
+
+/// Tickets matching the list filters as CSV; the columns import back unchanged
+pub fn export_tickets_csv(
+    conn: &mut DbConnection,
+    filters: &TicketFilters,
+    sort_field: Option<&str>,
+    sort_direction: Option<&str>,
+) -> Result<Vec<u8>, ImportError> {
+    let tickets = repository::tickets::get_filtered_tickets(conn, filters, sort_field, sort_direction)?;
+
+    let user_uuids: Vec<Uuid> = tickets
+        .iter()
+        .flat_map(|t| [t.requester_uuid, t.assignee_uuid])
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let emails = repository::user_helpers::get_primary_emails_batch(&user_uuids, conn);
+    let email = |uuid: Option<Uuid>| uuid.and_then(|u| emails.get(&u).cloned()).unwrap_or_default();
+
+    let rows = tickets
+        .iter()
+        .map(|t| {
+            vec![
+                t.id.to_string(),
+                t.title.clone(),
+                t.description.clone().unwrap_or_default(),
+                status_str(t.status).to_string(),
+                priority_str(t.priority).to_string(),
+                email(t.requester_uuid),
+                email(t.assignee_uuid),
+                t.category_id.map(|c| c.to_string()).unwrap_or_default(),
+                format_datetime(Some(t.created_at)),
+                format_datetime(Some(t.updated_at)),
+                format_datetime(t.closed_at),
+            ]
+        })
+        .collect();
+
+    write_csv(
+        &[
+            "id", "title", "description", "status", "priority", "requester_email", "assignee_email",
+            "category_id", "created_at", "updated_at", "closed_at",
+        ],
+        rows,
+    )
+}
+
+/// All users as CSV, with their primary email
+pub fn export_users_csv(conn: &mut DbConnection) -> Result<Vec<u8>, ImportError> {
+    let users = repository::users::get_users(conn)?;
+    let uuids: Vec<Uuid> = users.iter().map(|u| u.uuid).collect();
+    let emails = repository::user_helpers::get_primary_emails_batch(&uuids, conn);
+
+    let rows = users
+        .iter()
+        .map(|u| {
+            vec![
+                emails.get(&u.uuid).cloned().unwrap_or_default(),
+                u.name.clone(),
+                crate::utils::role_to_string(&u.role),
+                u.pronouns.clone().unwrap_or_default(),
+                format_datetime(Some(u.created_at)),
+            ]
+        })
+        .collect();
+
+    write_csv(&["email", "name", "role", "pronouns", "created_at"], rows)
+}
+
+/// All devices outside the recycle bin as CSV
+pub fn export_devices_csv(conn: &mut DbConnection) -> Result<Vec<u8>, ImportError> {
+    let devices = repository::devices::get_all_devices(conn)?;
+    let uuids: Vec<Uuid> = devices.iter().filter_map(|d| d.primary_user_uuid).collect();
+    let emails = repository::user_helpers::get_primary_emails_batch(&uuids, conn);
+
+    let rows = devices
+        .into_iter()
+        .map(|d| {
+            vec![
+                d.serial_number.unwrap_or_default(),
+                d.name,
+                d.hostname.unwrap_or_default(),
+                d.device_type.unwrap_or_default(),
+                d.manufacturer.unwrap_or_default(),
+                d.model.unwrap_or_default(),
+                d.warranty_status.unwrap_or_default(),
+                d.location.unwrap_or_default(),
+                d.notes.unwrap_or_default(),
+                d.primary_user_uuid.and_then(|u| emails.get(&u).cloned()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    write_csv(
+        &[
+            "serial_number", "name", "hostname", "device_type", "manufacturer", "model",
+            "warranty_status", "location", "notes", "primary_user_email",
+        ],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_csv_with_bom_and_ragged_rows() {
+        let sheet = parse_file(FileFormat::Csv, b"\xEF\xBB\xBFEmail, Name\na@example.com , Ann\nb@example.com\n\n").unwrap();
+        assert_eq!(sheet.headers, headers(&["Email", "Name"]));
+        assert_eq!(sheet.rows, vec![headers(&["a@example.com", "Ann"]), headers(&["b@example.com"])]);
+    }
+
+    #[test]
+    fn suggests_mapping_from_header_names_and_aliases() {
+        let mapping = suggest_mapping(
+            ImportEntityType::Devices,
+            &headers(&["Serial No.", "Device Name", "Owner", "Colour"]),
+        );
+        assert_eq!(mapping.get("serial_number").map(String::as_str), Some("Serial No."));
+        assert_eq!(mapping.get("name").map(String::as_str), Some("Device Name"));
+        assert_eq!(mapping.get("primary_user_email").map(String::as_str), Some("Owner"));
+        assert_eq!(mapping.len(), 3);
+    }
+
+    #[test]
+    fn rejects_bad_mappings() {
+        let file_headers = headers(&["Subject", "State"]);
+        let mapping: HashMap<String, String> = [
+            ("status".to_string(), "State".to_string()),
+            ("colour".to_string(), "Subject".to_string()),
+            ("priority".to_string(), "Urgency".to_string()),
+        ]
+        .into();
+
+        match resolve_mapping(ImportEntityType::Tickets, &file_headers, &mapping) {
+            Err(ImportError::Mapping(errors)) => assert_eq!(errors, vec![
+                "priority: column 'Urgency' is not in the file".to_string(),
+                "title: a column is required unless id is mapped".to_string(),
+                "unknown field 'colour'".to_string(),
+            ]),
+            other => panic!("expected mapping errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn only_accepts_upload_ids_we_issue() {
+        let id = format!("import-{}.csv", Uuid::new_v4());
+        assert!(upload_path(&id).is_ok());
+        assert!(upload_path("../../etc/passwd").is_err());
+        assert!(upload_path("import-../../secret.csv").is_err());
+        assert!(upload_path(&id.replace(".csv", ".exe")).is_err());
+    }
+
+    #[test]
+    fn parses_spreadsheet_status_spellings() {
+        assert_eq!(parse_status("In Progress"), Some(TicketStatus::InProgress));
+        assert_eq!(parse_status("in_progress"), Some(TicketStatus::InProgress));
+        assert_eq!(parse_status("done"), None);
+    }
+
+    #[test]
+    fn escapes_formula_cells_on_export() {
+        let csv = write_csv(&["title"], vec![vec!["=HYPERLINK(\"x\")".to_string()], vec!["Printer".to_string()]]).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "title\n\"'=HYPERLINK(\"\"x\"\")\"\nPrinter\n");
+    }
+}
@@ -0,0 +1,418 @@
+//! Service Catalog
+//!
+//! Validates catalog form schemas and submissions, and turns a submission into a
+//! `service_request` ticket.
+//!
+//! Answers are checked against the item's schema: hidden fields (whose `visible_when`
+//! condition is not met) are dropped, required fields must be answered, and each answer
+//! must match its field type. A condition may only refer to a field earlier in the form,
+//! so visibility can be resolved in a single pass.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::repository;
+
+/// Error type for catalog submissions
+#[derive(Debug)]
+pub enum CatalogError {
+    ItemInactive,
+    InvalidSchema(String),
+    InvalidAnswers(Vec<FieldError>),
+    DeviceNotAllowed(i32),
+    DatabaseError(diesel::result::Error),
+}
+
+impl std::fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogError::ItemInactive => write!(f, "This catalog item is no longer available"),
+            CatalogError::InvalidSchema(e) => write!(f, "Invalid form schema: {}", e),
+            CatalogError::InvalidAnswers(errors) => write!(f, "{} field(s) failed validation", errors.len()),
+            CatalogError::DeviceNotAllowed(id) => write!(f, "Device #{} cannot be linked to this request", id),
+            CatalogError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for CatalogError {
+    fn from(e: diesel::result::Error) -> Self {
+        CatalogError::DatabaseError(e)
+    }
+}
+
+/// A validation failure for one form field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+/// Service for catalog submissions
+pub struct CatalogService;
+
+impl CatalogService {
+    /// Validate the answers and create the ticket, its submission record and device links.
+    /// `any_device` lets technicians link devices that are not assigned to the requester.
+    pub fn submit(
+        conn: &mut DbConnection,
+        item: &CatalogItem,
+        answers: &Map<String, Value>,
+        requester_uuid: Uuid,
+        any_device: bool,
+    ) -> Result<(Ticket, CatalogSubmission), CatalogError> {
+        if !item.is_active {
+            return Err(CatalogError::ItemInactive);
+        }
+
+        let schema = item.schema().map_err(|e| CatalogError::InvalidSchema(e.to_string()))?;
+        let answers = validate_answers(&schema, answers).map_err(CatalogError::InvalidAnswers)?;
+
+        let mut device_ids = answered_device_ids(&schema, &answers);
+        let own_devices: Vec<i32> = repository::devices::get_devices_for_user(conn, &requester_uuid)?
+            .into_iter()
+            .map(|device| device.id)
+            .collect();
+        if !any_device {
+            if let Some(id) = device_ids.iter().find(|id| !own_devices.contains(id)) {
+                return Err(CatalogError::DeviceNotAllowed(*id));
+            }
+        }
+        if item.link_requester_devices {
+            device_ids.extend(own_devices);
+        }
+        let mut seen = HashSet::new();
+        device_ids.retain(|id| seen.insert(*id));
+
+        conn.transaction(|conn| {
+            let new_ticket = NewTicket {
+                title: render_title(item.title_template.as_deref(), &item.name, &answers),
+                description: Some(render_description(&schema, &answers)),
+                status: TicketStatus::Open,
+                priority: item.default_priority,
+                requester_uuid: Some(requester_uuid),
+                assignee_uuid: None,
+                category_id: Some(item.category_id),
+            };
+            let ticket = repository::tickets::create_typed_ticket(
+                conn,
+                new_ticket,
+                TicketType::ServiceRequest,
+                Some(requester_uuid),
+            )?;
+
+            let submission = repository::catalog::create_submission(conn, NewCatalogSubmission {
+                ticket_id: ticket.id,
+                catalog_item_id: Some(item.id),
+                form_schema: item.form_schema.clone(),
+                answers: Value::Object(answers.clone()),
+                submitted_by: Some(requester_uuid),
+            })?;
+
+            for device_id in &device_ids {
+                match repository::tickets::add_device_to_ticket(conn, ticket.id, *device_id) {
+                    Ok(_) => {}
+                    // A device the technician picked may not exist
+                    Err(diesel::result::Error::DatabaseError(
+                        diesel::result::DatabaseErrorKind::ForeignKeyViolation,
+                        _,
+                    )) => return Err(CatalogError::DeviceNotAllowed(*device_id)),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            Ok((ticket, submission))
+        })
+    }
+}
+
+/// Check a schema before it is saved on a catalog item
+pub fn validate_schema(schema: &FormSchema) -> Result<(), String> {
+    let mut keys = HashSet::new();
+
+    for field in &schema.fields {
+        if field.key.trim().is_empty() || field.label.trim().is_empty() {
+            return Err("Every field needs a key and a label".to_string());
+        }
+        if field.key.contains(['{', '}']) {
+            return Err(format!("Field key '{}' cannot contain braces", field.key));
+        }
+        if matches!(field.field_type, FormFieldType::Select | FormFieldType::MultiSelect) && field.options.is_empty() {
+            return Err(format!("Field '{}' needs at least one option", field.key));
+        }
+        if let Some(condition) = &field.visible_when {
+            if !keys.contains(condition.field.as_str()) {
+                return Err(format!(
+                    "Field '{}' depends on '{}', which must be an earlier field",
+                    field.key, condition.field
+                ));
+            }
+            if condition.equals.is_none() && condition.one_of.is_none() {
+                return Err(format!("Field '{}' has a condition without a value", field.key));
+            }
+        }
+        if !keys.insert(field.key.as_str()) {
+            return Err(format!("Duplicate field key '{}'", field.key));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate answers against a schema, returning only the answers of visible fields
+pub fn validate_answers(schema: &FormSchema, answers: &Map<String, Value>) -> Result<Map<String, Value>, Vec<FieldError>> {
+    let mut cleaned = Map::new();
+    let mut errors = Vec::new();
+
+    for field in &schema.fields {
+        if !is_visible(field, &cleaned) {
+            continue;
+        }
+
+        let answer = answers.get(&field.key).filter(|value| !is_blank(value));
+        let Some(answer) = answer else {
+            if field.required {
+                errors.push(FieldError::new(&field.key, format!("{} is required", field.label)));
+            }
+            continue;
+        };
+
+        match normalize_answer(field, answer) {
+            Ok(Some(value)) => {
+                cleaned.insert(field.key.clone(), value);
+            }
+            Ok(None) if field.required => {
+                errors.push(FieldError::new(&field.key, format!("{} is required", field.label)));
+            }
+            Ok(None) => {}
+            Err(message) => errors.push(FieldError::new(&field.key, message)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(cleaned)
+    } else {
+        Err(errors)
+    }
+}
+
+fn is_visible(field: &FormField, answers: &Map<String, Value>) -> bool {
+    let Some(condition) = &field.visible_when else {
+        return true;
+    };
+    let Some(answer) = answers.get(&condition.field) else {
+        return false;
+    };
+
+    let matches = |expected: &Value| match answer {
+        // A multi-select matches when any selected option matches
+        Value::Array(items) => items.contains(expected),
+        other => other == expected,
+    };
+
+    condition.equals.as_ref().map(matches).unwrap_or(false)
+        || condition.one_of.as_ref().is_some_and(|values| values.iter().any(matches))
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// Check an answer's type, returning the value to store (None for an unticked checkbox)
+fn normalize_answer(field: &FormField, answer: &Value) -> Result<Option<Value>, String> {
+    let type_error = |expected: &str| format!("{} must be {}", field.label, expected);
+
+    match field.field_type {
+        FormFieldType::Text | FormFieldType::Textarea => answer
+            .as_str()
+            .map(|s| Some(Value::String(s.trim().to_string())))
+            .ok_or_else(|| type_error("text")),
+        FormFieldType::Number => match answer {
+            Value::Number(_) => Ok(Some(answer.clone())),
+            _ => Err(type_error("a number")),
+        },
+        FormFieldType::Checkbox => match answer {
+            // A required checkbox has to be ticked
+            Value::Bool(true) => Ok(Some(Value::Bool(true))),
+            Value::Bool(false) => Ok(None),
+            _ => Err(type_error("true or false")),
+        },
+        FormFieldType::Date => answer
+            .as_str()
+            .filter(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok())
+            .map(|s| Some(Value::String(s.to_string())))
+            .ok_or_else(|| type_error("a date (YYYY-MM-DD)")),
+        FormFieldType::Select => match answer.as_str() {
+            Some(choice) if field.options.iter().any(|o| o == choice) => Ok(Some(answer.clone())),
+            _ => Err(type_error("one of the listed options")),
+        },
+        FormFieldType::MultiSelect => {
+            let choices = answer.as_array().ok_or_else(|| type_error("a list of options"))?;
+            let valid = choices
+                .iter()
+                .all(|c| c.as_str().is_some_and(|c| field.options.iter().any(|o| o == c)));
+            if valid {
+                Ok(Some(answer.clone()))
+            } else {
+                Err(type_error("a list of the listed options"))
+            }
+        }
+        FormFieldType::Device => {
+            // Accept a single id or a list, always stored as a list
+            let ids: Option<Vec<i64>> = match answer {
+                Value::Number(n) => n.as_i64().map(|id| vec![id]),
+                Value::Array(items) => items.iter().map(|v| v.as_i64()).collect(),
+                _ => None,
+            };
+            match ids {
+                Some(ids) if ids.iter().all(|id| i32::try_from(*id).is_ok()) => {
+                    Ok(Some(Value::Array(ids.into_iter().map(Value::from).collect())))
+                }
+                _ => Err(type_error("a device")),
+            }
+        }
+        FormFieldType::User => answer
+            .as_str()
+            .filter(|s| Uuid::parse_str(s).is_ok())
+            .map(|s| Some(Value::String(s.to_string())))
+            .ok_or_else(|| type_error("a user")),
+    }
+}
+
+/// Device ids answered in device fields
+pub fn answered_device_ids(schema: &FormSchema, answers: &Map<String, Value>) -> Vec<i32> {
+    schema
+        .fields
+        .iter()
+        .filter(|field| field.field_type == FormFieldType::Device)
+        .filter_map(|field| answers.get(&field.key)?.as_array())
+        .flatten()
+        .filter_map(|id| id.as_i64().and_then(|id| i32::try_from(id).ok()))
+        .collect()
+}
+
+fn answer_to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Bool(true) => "Yes".to_string(),
+        Value::Bool(false) => "No".to_string(),
+        Value::Array(items) => items.iter().map(answer_to_text).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Ticket title from the item's template, e.g. "Access to {system}"
+pub fn render_title(template: Option<&str>, item_name: &str, answers: &Map<String, Value>) -> String {
+    let Some(template) = template.filter(|t| !t.trim().is_empty()) else {
+        return item_name.to_string();
+    };
+
+    let mut title = template.to_string();
+    for (key, value) in answers {
+        title = title.replace(&format!("{{{}}}", key), &answer_to_text(value));
+    }
+    title.trim().to_string()
+}
+
+/// Plain-text ticket description listing each answered field
+pub fn render_description(schema: &FormSchema, answers: &Map<String, Value>) -> String {
+    schema
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let value = answers.get(&field.key)?;
+            Some(format!("{}: {}", field.label, answer_to_text(value)))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> FormSchema {
+        serde_json::from_value(json!({
+            "fields": [
+                {"key": "software", "label": "Software", "type": "select", "required": true, "options": ["Office", "Other"]},
+                {"key": "other_name", "label": "Name", "type": "text", "required": true,
+                 "visible_when": {"field": "software", "equals": "Other"}},
+                {"key": "laptop", "label": "Laptop", "type": "device"},
+                {"key": "agree", "label": "Licence terms", "type": "checkbox", "required": true}
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn answers(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_hidden_field_is_not_required_and_dropped() {
+        let result = validate_answers(&schema(), &answers(json!({
+            "software": "Office", "other_name": "ignored", "agree": true
+        })))
+        .unwrap();
+        assert!(!result.contains_key("other_name"));
+    }
+
+    #[test]
+    fn test_conditional_field_required_when_visible() {
+        let errors = validate_answers(&schema(), &answers(json!({"software": "Other", "agree": true}))).unwrap_err();
+        assert_eq!(errors, vec![FieldError::new("other_name", "Name is required")]);
+    }
+
+    #[test]
+    fn test_required_checkbox_must_be_ticked() {
+        let errors = validate_answers(&schema(), &answers(json!({"software": "Office", "agree": false}))).unwrap_err();
+        assert_eq!(errors[0].field, "agree");
+    }
+
+    #[test]
+    fn test_select_rejects_unknown_option() {
+        let errors = validate_answers(&schema(), &answers(json!({"software": "Photoshop", "agree": true}))).unwrap_err();
+        assert_eq!(errors[0].field, "software");
+    }
+
+    #[test]
+    fn test_device_answer_normalized_to_list() {
+        let schema = schema();
+        let result = validate_answers(&schema, &answers(json!({"software": "Office", "laptop": 7, "agree": true}))).unwrap();
+        assert_eq!(result["laptop"], json!([7]));
+        assert_eq!(answered_device_ids(&schema, &result), vec![7]);
+    }
+
+    #[test]
+    fn test_schema_condition_must_reference_earlier_field() {
+        let mut schema = schema();
+        schema.fields.swap(0, 1);
+        assert!(validate_schema(&schema).is_err());
+        assert!(validate_schema(&self::schema()).is_ok());
+    }
+
+    #[test]
+    fn test_render_title_and_description() {
+        let answers = answers(json!({"software": "Office", "agree": true}));
+        assert_eq!(render_title(Some("Install {software}"), "Software", &answers), "Install Office");
+        assert_eq!(render_title(None, "Software", &answers), "Software");
+        assert_eq!(render_description(&schema(), &answers), "Software: Office\nLicence terms: Yes");
+    }
+}
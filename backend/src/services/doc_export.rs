@@ -0,0 +1,444 @@
+//! Documentation Export Service
+//!
+//! Converts documentation pages from their stored Yjs documents into Markdown or
+//! standalone HTML. A single page exports as one file; a subtree or the whole knowledge
+//! base exports as a zip whose folders follow the `parent_id` hierarchy (a page with
+//! children becomes a folder holding `index.md` / `index.html`).
+//!
+//! Images are read through the `Storage` trait: HTML exports inline them as data URIs,
+//! Markdown exports in a zip bundle them under `assets/` and link them relatively.
+
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Write};
+use tracing::warn;
+use yrs::Doc;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::db::DbConnection;
+use crate::models::DocumentationPage;
+use crate::repository;
+use crate::utils::storage::{get_content_type, Storage};
+use crate::utils::yjs_render::{self, Markup};
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "markdown" | "md" => Some(Self::Markdown),
+            "html" | "pdf" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    fn markup(self) -> Markup {
+        match self {
+            Self::Markdown => Markup::Markdown,
+            Self::Html => Markup::Html,
+        }
+    }
+}
+
+/// Error type for documentation exports
+#[derive(Debug)]
+pub enum DocExportError {
+    PageNotFound(i32),
+    DatabaseError(diesel::result::Error),
+    ZipError(zip::result::ZipError),
+    IoError(std::io::Error),
+}
+
+impl std::fmt::Display for DocExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocExportError::PageNotFound(id) => write!(f, "Documentation page {} not found", id),
+            DocExportError::DatabaseError(e) => write!(f, "Database error: {}", e),
+            DocExportError::ZipError(e) => write!(f, "ZIP error: {}", e),
+            DocExportError::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for DocExportError {
+    fn from(e: diesel::result::Error) -> Self {
+        DocExportError::DatabaseError(e)
+    }
+}
+
+impl From<zip::result::ZipError> for DocExportError {
+    fn from(e: zip::result::ZipError) -> Self {
+        DocExportError::ZipError(e)
+    }
+}
+
+impl From<std::io::Error> for DocExportError {
+    fn from(e: std::io::Error) -> Self {
+        DocExportError::IoError(e)
+    }
+}
+
+/// A finished export, ready to send as a download
+pub struct ExportedFile {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Service for exporting documentation
+pub struct DocExportService;
+
+impl DocExportService {
+    /// Export one page as a single Markdown or HTML file
+    /// Markdown keeps the original image URLs; HTML inlines the images.
+    pub async fn export_page(
+        conn: &mut DbConnection,
+        storage: &dyn Storage,
+        page_id: i32,
+        format: ExportFormat,
+    ) -> Result<ExportedFile, DocExportError> {
+        let page = repository::get_documentation_page(page_id, conn).map_err(|e| match e {
+            diesel::result::Error::NotFound => DocExportError::PageNotFound(page_id),
+            e => DocExportError::DatabaseError(e),
+        })?;
+        let doc = page_document(conn, &page);
+
+        let mut images = HashMap::new();
+        if format == ExportFormat::Html {
+            if let Some(doc) = &doc {
+                for src in yjs_render::image_sources(doc) {
+                    if let Some(data_uri) = image_data_uri(storage, &src).await {
+                        images.insert(src, data_uri);
+                    }
+                }
+            }
+        }
+
+        Ok(ExportedFile {
+            filename: format!("{}.{}", entry_name(&page), format.extension()),
+            content_type: format.content_type(),
+            data: render_page(&page, doc.as_ref(), format, &images).into_bytes(),
+        })
+    }
+
+    /// Export a page and its descendants, or the whole knowledge base when `root` is None,
+    /// as a zip archive
+    pub async fn export_tree(
+        conn: &mut DbConnection,
+        storage: &dyn Storage,
+        root: Option<i32>,
+        format: ExportFormat,
+    ) -> Result<ExportedFile, DocExportError> {
+        let pages: Vec<DocumentationPage> = repository::get_documentation_pages(conn)?
+            .into_iter()
+            .filter(|page| page.archived_at.is_none() || Some(page.id) == root)
+            .collect();
+
+        let entries = plan_entries(&pages, root, format.extension())
+            .ok_or_else(|| DocExportError::PageNotFound(root.unwrap_or_default()))?;
+        let pages_by_id: HashMap<i32, &DocumentationPage> = pages.iter().map(|page| (page.id, page)).collect();
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        // Bundled images for Markdown, shared between pages: source → archive path
+        let mut assets: HashMap<String, String> = HashMap::new();
+        let mut data_uris: HashMap<String, String> = HashMap::new();
+
+        for (page_id, path) in &entries {
+            let page = pages_by_id[page_id];
+            let doc = page_document(conn, page);
+
+            let mut images = HashMap::new();
+            if let Some(doc) = &doc {
+                let up = "../".repeat(path.matches('/').count());
+                for src in yjs_render::image_sources(doc) {
+                    match format {
+                        ExportFormat::Html => {
+                            if !data_uris.contains_key(&src) {
+                                if let Some(data_uri) = image_data_uri(storage, &src).await {
+                                    data_uris.insert(src.clone(), data_uri);
+                                }
+                            }
+                            if let Some(data_uri) = data_uris.get(&src) {
+                                images.insert(src, data_uri.clone());
+                            }
+                        }
+                        ExportFormat::Markdown => {
+                            if !assets.contains_key(&src) {
+                                if let Some(data) = load_image(storage, &src).await {
+                                    let filename = src.rsplit('/').next().unwrap_or("image");
+                                    let asset = format!("assets/{}-{}", assets.len() + 1, filename);
+                                    zip.start_file(asset.as_str(), options)?;
+                                    zip.write_all(&data)?;
+                                    assets.insert(src.clone(), asset);
+                                }
+                            }
+                            if let Some(asset) = assets.get(&src) {
+                                images.insert(src, format!("{}{}", up, asset));
+                            }
+                        }
+                    }
+                }
+            }
+
+            zip.start_file(path.as_str(), options)?;
+            zip.write_all(render_page(page, doc.as_ref(), format, &images).as_bytes())?;
+        }
+
+        let data = zip.finish()?.into_inner();
+        let filename = match root.and_then(|id| pages_by_id.get(&id)) {
+            Some(page) => format!("{}.zip", entry_name(page)),
+            None => "knowledge-base.zip".to_string(),
+        };
+
+        Ok(ExportedFile {
+            filename,
+            content_type: "application/zip",
+            data,
+        })
+    }
+}
+
+/// The page's Yjs document, falling back to the linked ticket's notes
+pub(crate) fn page_document(conn: &mut DbConnection, page: &DocumentationPage) -> Option<Doc> {
+    let bytes = match &page.yjs_document {
+        Some(bytes) if !bytes.is_empty() => Some(bytes.clone()),
+        _ => page.ticket_id.and_then(|ticket_id| {
+            repository::get_article_content_by_ticket_id(conn, ticket_id)
+                .ok()
+                .and_then(|article| article.yjs_document)
+        }),
+    };
+    bytes.and_then(|bytes| crate::utils::yjs::decode_document(&bytes))
+}
+
+fn render_page(page: &DocumentationPage, doc: Option<&Doc>, format: ExportFormat, images: &HashMap<String, String>) -> String {
+    let body = doc
+        .map(|doc| yjs_render::render(doc, format.markup(), images))
+        .unwrap_or_default();
+
+    match format {
+        ExportFormat::Markdown if body.trim().is_empty() => format!("# {}\n", page.title),
+        ExportFormat::Markdown => format!("# {}\n\n{}", page.title, body),
+        ExportFormat::Html => yjs_render::html_page(&page.title, &body),
+    }
+}
+
+/// Storage path of an uploaded image, for URLs served by this backend
+pub(crate) fn storage_path_for_src(src: &str) -> Option<&str> {
+    let path = src
+        .strip_prefix("/uploads/")
+        .or_else(|| src.strip_prefix("/api/files/"))?;
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    if path.is_empty() || path.split('/').any(|segment| segment == ".." || segment.is_empty()) {
+        return None;
+    }
+    Some(path)
+}
+
+async fn load_image(storage: &dyn Storage, src: &str) -> Option<Vec<u8>> {
+    // External URLs are left as links rather than fetched
+    let path = storage_path_for_src(src)?;
+    match storage.get_file(path).await {
+        Ok(data) => Some(data),
+        Err(e) => {
+            warn!(path = %path, error = ?e, "Image missing from storage, keeping original link");
+            None
+        }
+    }
+}
+
+async fn image_data_uri(storage: &dyn Storage, src: &str) -> Option<String> {
+    let data = load_image(storage, src).await?;
+    let content_type = get_content_type(src.rsplit('/').next().unwrap_or(src));
+    Some(format!("data:{};base64,{}", content_type, general_purpose::STANDARD.encode(data)))
+}
+
+/// File or folder name for a page: its slug, or a slug of its title
+fn entry_name(page: &DocumentationPage) -> String {
+    let name = page
+        .slug
+        .as_deref()
+        .filter(|slug| !slug.trim().is_empty())
+        .map(slugify)
+        .unwrap_or_else(|| slugify(&page.title));
+    if name.is_empty() {
+        format!("page-{}", page.id)
+    } else {
+        name
+    }
+}
+
+pub(crate) fn slugify(value: &str) -> String {
+    let mut slug = String::new();
+    for c in value.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Archive path of every exported page, parents before children
+///
+/// With a root, only that page and its descendants are included (None if the root is
+/// missing); otherwise every page whose parent is absent becomes a top-level entry.
+fn plan_entries(pages: &[DocumentationPage], root: Option<i32>, extension: &str) -> Option<Vec<(i32, String)>> {
+    let ids: HashSet<i32> = pages.iter().map(|page| page.id).collect();
+    let mut children: HashMap<Option<i32>, Vec<&DocumentationPage>> = HashMap::new();
+    for page in pages {
+        let parent = page.parent_id.filter(|parent| ids.contains(parent));
+        children.entry(parent).or_default().push(page);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| {
+            a.display_order
+                .unwrap_or(i32::MAX)
+                .cmp(&b.display_order.unwrap_or(i32::MAX))
+                .then_with(|| a.title.cmp(&b.title))
+        });
+    }
+
+    let top: Vec<&DocumentationPage> = match root {
+        Some(root) => vec![pages.iter().find(|page| page.id == root)?],
+        None => children.get(&None).cloned().unwrap_or_default(),
+    };
+
+    let mut entries = Vec::new();
+    let mut visited = HashSet::new();
+    add_entries(&top, "", &children, extension, &mut visited, &mut entries);
+    Some(entries)
+}
+
+fn add_entries(
+    siblings: &[&DocumentationPage],
+    prefix: &str,
+    children: &HashMap<Option<i32>, Vec<&DocumentationPage>>,
+    extension: &str,
+    visited: &mut HashSet<i32>,
+    entries: &mut Vec<(i32, String)>,
+) {
+    let mut used_names = HashSet::new();
+    for page in siblings {
+        // Guards against parent_id cycles
+        if !visited.insert(page.id) {
+            continue;
+        }
+        let mut name = entry_name(page);
+        if !used_names.insert(name.clone()) {
+            name = format!("{}-{}", name, page.id);
+            used_names.insert(name.clone());
+        }
+
+        match children.get(&Some(page.id)).filter(|kids| !kids.is_empty()) {
+            Some(kids) => {
+                let folder = format!("{}{}/", prefix, name);
+                entries.push((page.id, format!("{}index.{}", folder, extension)));
+                add_entries(kids, &folder, children, extension, visited, entries);
+            }
+            None => entries.push((page.id, format!("{}{}.{}", prefix, name, extension))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DocumentationStatus;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn page(id: i32, title: &str, parent_id: Option<i32>) -> DocumentationPage {
+        let now = Utc::now().naive_utc();
+        DocumentationPage {
+            id,
+            uuid: Uuid::new_v4(),
+            title: title.to_string(),
+            slug: None,
+            icon: None,
+            cover_image: None,
+            status: DocumentationStatus::Published,
+            created_at: now,
+            updated_at: now,
+            created_by: Uuid::nil(),
+            last_edited_by: Uuid::nil(),
+            parent_id,
+            ticket_id: None,
+            display_order: None,
+            is_public: false,
+            is_template: false,
+            archived_at: None,
+            yjs_state_vector: None,
+            yjs_document: None,
+            yjs_client_id: None,
+            has_unsaved_changes: false,
+            deleted_at: None,
+            deleted_by: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_entries_follows_hierarchy() {
+        let pages = vec![
+            page(1, "Getting Started", None),
+            page(2, "Install", Some(1)),
+            page(3, "Configure", Some(1)),
+            page(4, "FAQ", None),
+        ];
+
+        let entries = plan_entries(&pages, None, "md").unwrap();
+        let paths: Vec<&str> = entries.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["faq.md", "getting-started/index.md", "getting-started/configure.md", "getting-started/install.md"]
+        );
+
+        let subtree = plan_entries(&pages, Some(1), "html").unwrap();
+        assert_eq!(subtree.len(), 3);
+        assert!(plan_entries(&pages, Some(99), "md").is_none());
+    }
+
+    #[test]
+    fn test_duplicate_names_get_ids() {
+        let pages = vec![page(1, "Notes", None), page(2, "Notes", None)];
+        let entries = plan_entries(&pages, None, "md").unwrap();
+        assert_eq!(entries[0].1, "notes.md");
+        assert_eq!(entries[1].1, "notes-2.md");
+    }
+
+    #[test]
+    fn test_storage_path_for_src() {
+        assert_eq!(storage_path_for_src("/uploads/tickets/4/notes/a.png"), Some("tickets/4/notes/a.png"));
+        assert_eq!(storage_path_for_src("/api/files/tickets/4/notes/a.png?v=2"), Some("tickets/4/notes/a.png"));
+        assert_eq!(storage_path_for_src("/uploads/../secrets"), None);
+        assert_eq!(storage_path_for_src("https://example.com/a.png"), None);
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("  VPN: Setup & Access! "), "vpn-setup-access");
+    }
+}
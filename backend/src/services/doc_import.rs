@@ -0,0 +1,730 @@
+//! Documentation Import Service
+//!
+//! Builds documentation pages from a zip of Markdown and HTML files, such as another wiki's
+//! export or an archive produced by `doc_export`. Folders become parent pages: their content
+//! comes from an `index` or `README` file inside them (or a file next to the folder with the
+//! same name), otherwise the page is created empty. Slugs come from file names, and
+//! front-matter (`<meta>` tags for HTML) can set `title`, `slug`, `icon`, `status` and
+//! `display_order`.
+//!
+//! Content is converted into the editor's Yjs structure so pages open in the collaborative
+//! editor. Images referenced by a relative path or inlined as data URIs are uploaded through
+//! `Storage`; other image URLs are kept as they are.
+
+use base64::{engine::general_purpose, Engine as _};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{Cursor, Read};
+use std::path::Component;
+use tracing::warn;
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use crate::db::DbConnection;
+use crate::models::{DocumentationStatus, NewDocumentationPage};
+use crate::repository;
+use crate::services::doc_export::slugify;
+use crate::utils::file_validation::FileValidator;
+use crate::utils::storage::Storage;
+use crate::utils::yjs;
+use crate::utils::yjs_import::{self, Block};
+
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+const HTML_EXTENSIONS: &[&str] = &["html", "htm"];
+
+/// File names (without extension) holding the content of their folder's page
+const INDEX_NAMES: &[&str] = &["index", "_index", "readme"];
+
+/// Largest archive accepted for upload
+pub const MAX_ARCHIVE_BYTES: usize = 100 * 1024 * 1024;
+
+const MAX_ENTRIES: usize = 5_000;
+const MAX_UNCOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Storage folder for imported images
+const IMAGE_FOLDER: &str = "documentation/images";
+
+/// Error type for documentation imports
+#[derive(Debug)]
+pub enum DocImportError {
+    ParentNotFound(i32),
+    InvalidArchive(String),
+    NoDocuments,
+    DatabaseError(diesel::result::Error),
+    ZipError(zip::result::ZipError),
+    IoError(std::io::Error),
+}
+
+impl std::fmt::Display for DocImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocImportError::ParentNotFound(id) => write!(f, "Parent page {} not found", id),
+            DocImportError::InvalidArchive(msg) => write!(f, "Invalid archive: {}", msg),
+            DocImportError::NoDocuments => write!(f, "The archive contains no Markdown or HTML files"),
+            DocImportError::DatabaseError(e) => write!(f, "Database error: {}", e),
+            DocImportError::ZipError(e) => write!(f, "ZIP error: {}", e),
+            DocImportError::IoError(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for DocImportError {
+    fn from(e: diesel::result::Error) -> Self {
+        DocImportError::DatabaseError(e)
+    }
+}
+
+impl From<zip::result::ZipError> for DocImportError {
+    fn from(e: zip::result::ZipError) -> Self {
+        DocImportError::ZipError(e)
+    }
+}
+
+impl From<std::io::Error> for DocImportError {
+    fn from(e: std::io::Error) -> Self {
+        DocImportError::IoError(e)
+    }
+}
+
+/// Where and as whom to import
+pub struct ImportOptions {
+    /// Existing page to import under; top-level pages otherwise
+    pub parent_id: Option<i32>,
+    /// Status of pages whose front-matter doesn't set one
+    pub status: DocumentationStatus,
+    pub author: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedPage {
+    pub id: i32,
+    pub title: String,
+    pub slug: Option<String>,
+    pub parent_id: Option<i32>,
+    /// Archive entry the content came from (None for folders without an index file)
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub pages: Vec<ImportedPage>,
+    pub images_uploaded: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Service for importing documentation
+pub struct DocImportService;
+
+impl DocImportService {
+    /// Import every Markdown/HTML file in a zip archive
+    ///
+    /// Images are uploaded first; pages are then created in a single transaction, so a
+    /// failed import leaves no partial page tree behind.
+    pub async fn import_archive(
+        conn: &mut DbConnection,
+        storage: &dyn Storage,
+        data: &[u8],
+        options: &ImportOptions,
+    ) -> Result<ImportReport, DocImportError> {
+        if let Some(parent_id) = options.parent_id {
+            repository::get_documentation_page(parent_id, conn).map_err(|e| match e {
+                diesel::result::Error::NotFound => DocImportError::ParentNotFound(parent_id),
+                e => DocImportError::DatabaseError(e),
+            })?;
+        }
+
+        let archive = read_archive(data)?;
+        let entries: Vec<String> = archive.keys().cloned().collect();
+        let plan = plan_pages(&entries);
+        if plan.is_empty() {
+            return Err(DocImportError::NoDocuments);
+        }
+
+        let mut report = ImportReport::default();
+        let mut uploaded: HashMap<String, String> = HashMap::new();
+        let mut contents = Vec::with_capacity(plan.len());
+
+        for page in &plan {
+            let mut content = match &page.source {
+                Some(source) => parse_page(source, &archive[source]),
+                None => PageContent::default(),
+            };
+            if let Some(source) = &page.source {
+                upload_images(storage, &archive, source, &mut content.blocks, &mut uploaded, &mut report).await;
+            }
+            contents.push(content);
+        }
+
+        report.pages = conn.transaction(|conn| create_pages(conn, &plan, contents, options))?;
+        Ok(report)
+    }
+}
+
+/// Read every file in the archive, skipping hidden files and macOS metadata
+fn read_archive(data: &[u8]) -> Result<HashMap<String, Vec<u8>>, DocImportError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    if archive.len() > MAX_ENTRIES {
+        return Err(DocImportError::InvalidArchive(format!(
+            "more than {} entries",
+            MAX_ENTRIES
+        )));
+    }
+
+    let mut files = HashMap::new();
+    let mut remaining = MAX_UNCOMPRESSED_BYTES;
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+
+        // enclosed_name rejects absolute paths and `..` traversal
+        let Some(parts) = file.enclosed_name().and_then(|path| {
+            path.components()
+                .filter(|component| !matches!(component, Component::CurDir))
+                .map(|component| match component {
+                    Component::Normal(part) => part.to_str().map(str::to_string),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        }) else {
+            continue;
+        };
+        if parts.is_empty() || parts.iter().any(|part| part.starts_with('.') || part == "__MACOSX") {
+            continue;
+        }
+
+        // Don't trust the declared size: read at most the remaining budget
+        let mut bytes = Vec::new();
+        (&mut file).take(remaining + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > remaining {
+            return Err(DocImportError::InvalidArchive(format!(
+                "more than {} MB uncompressed",
+                MAX_UNCOMPRESSED_BYTES / (1024 * 1024)
+            )));
+        }
+        remaining -= bytes.len() as u64;
+        files.insert(parts.join("/"), bytes);
+    }
+    Ok(files)
+}
+
+/// A page to create, parents before children
+#[derive(Debug, Clone, PartialEq)]
+struct PlannedPage {
+    /// Folder or file path (without extension) relative to the import root
+    key: String,
+    /// Last path component, used for the slug and fallback title
+    name: String,
+    /// Archive entry holding the content
+    source: Option<String>,
+    /// Index of the parent page in the plan
+    parent: Option<usize>,
+    /// Position among siblings, by name
+    order: i32,
+}
+
+fn split_extension(file_name: &str) -> (&str, &str) {
+    file_name.rsplit_once('.').unwrap_or((file_name, ""))
+}
+
+fn is_document(entry: &str) -> bool {
+    let (_, extension) = split_extension(entry.rsplit('/').next().unwrap_or(entry));
+    let extension = extension.to_ascii_lowercase();
+    MARKDOWN_EXTENSIONS.contains(&extension.as_str()) || HTML_EXTENSIONS.contains(&extension.as_str())
+}
+
+fn is_index(stem: &str) -> bool {
+    INDEX_NAMES.contains(&stem.to_ascii_lowercase().as_str())
+}
+
+fn parent_folder(path: &str) -> &str {
+    path.rsplit_once('/').map(|(folder, _)| folder).unwrap_or("")
+}
+
+/// Lay out the page tree for the documents in an archive
+///
+/// A single top-level folder without an index file (the usual result of zipping a
+/// folder) is treated as the import root rather than becoming a page itself.
+fn plan_pages(entries: &[String]) -> Vec<PlannedPage> {
+    let mut documents: Vec<&String> = entries.iter().filter(|entry| is_document(entry)).collect();
+    documents.sort();
+
+    let wrapper = documents
+        .first()
+        .and_then(|first| first.split_once('/').map(|(root, _)| format!("{}/", root)))
+        .filter(|root| {
+            documents.iter().all(|entry| {
+                entry.starts_with(root.as_str())
+                    && !(entry[root.len()..].split('/').count() == 1
+                        && is_index(split_extension(&entry[root.len()..]).0))
+            })
+        })
+        .unwrap_or_default();
+
+    let mut folders: BTreeSet<String> = BTreeSet::new();
+    let mut folder_sources: HashMap<String, String> = HashMap::new();
+    let mut files: Vec<(String, String)> = Vec::new();
+
+    for entry in documents {
+        let relative = &entry[wrapper.len()..];
+        let folder = parent_folder(relative);
+        let (stem, _) = split_extension(relative.rsplit('/').next().unwrap_or(relative));
+
+        let mut ancestor = folder;
+        while !ancestor.is_empty() {
+            folders.insert(ancestor.to_string());
+            ancestor = parent_folder(ancestor);
+        }
+
+        if !folder.is_empty() && is_index(stem) {
+            folder_sources.entry(folder.to_string()).or_insert_with(|| entry.clone());
+        } else {
+            let key = if folder.is_empty() { stem.to_string() } else { format!("{}/{}", folder, stem) };
+            files.push((key, entry.clone()));
+        }
+    }
+
+    // `guide.md` next to a `guide/` folder holds that folder's content unless it has an index
+    files.retain(|(key, entry)| {
+        if folders.contains(key) && !folder_sources.contains_key(key) {
+            folder_sources.insert(key.clone(), entry.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    let mut ordered_folders: Vec<String> = folders.into_iter().collect();
+    ordered_folders.sort_by_key(|folder| folder.matches('/').count());
+
+    let mut plan: Vec<PlannedPage> = Vec::new();
+    let mut folder_index: HashMap<String, usize> = HashMap::new();
+    for folder in ordered_folders {
+        let parent = folder_index.get(parent_folder(&folder)).copied();
+        folder_index.insert(folder.clone(), plan.len());
+        plan.push(PlannedPage {
+            name: folder.rsplit('/').next().unwrap_or(&folder).to_string(),
+            source: folder_sources.remove(&folder),
+            key: folder,
+            parent,
+            order: 0,
+        });
+    }
+    for (key, entry) in files {
+        plan.push(PlannedPage {
+            name: key.rsplit('/').next().unwrap_or(&key).to_string(),
+            parent: folder_index.get(parent_folder(&key)).copied(),
+            source: Some(entry),
+            key,
+            order: 0,
+        });
+    }
+
+    // Default ordering follows file names, so numbered files ("01-intro.md") keep their order
+    let mut siblings: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (index, page) in plan.iter().enumerate() {
+        siblings.entry(page.parent).or_default().push(index);
+    }
+    for indexes in siblings.values_mut() {
+        indexes.sort_by_key(|&index| plan[index].name.to_lowercase());
+        for (position, &index) in indexes.iter().enumerate() {
+            plan[index].order = position as i32;
+        }
+    }
+
+    plan
+}
+
+/// Page settings from front-matter or `<meta>` tags
+#[derive(Debug, Default, PartialEq)]
+struct PageMeta {
+    title: Option<String>,
+    slug: Option<String>,
+    icon: Option<String>,
+    display_order: Option<i32>,
+    status: Option<DocumentationStatus>,
+}
+
+impl PageMeta {
+    fn from_fields(fields: &HashMap<String, String>) -> Self {
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| fields.get(*name))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            title: field(&["title"]),
+            slug: field(&["slug"]),
+            icon: field(&["icon"]),
+            display_order: field(&["display_order", "order", "weight", "position"])
+                .and_then(|value| value.parse().ok()),
+            status: field(&["status"]).and_then(|status| match status.to_lowercase().as_str() {
+                "published" => Some(DocumentationStatus::Published),
+                "draft" => Some(DocumentationStatus::Draft),
+                _ => None,
+            }),
+        }
+    }
+}
+
+/// Split a `---` delimited front-matter block of `key: value` lines from Markdown
+fn split_front_matter(text: &str) -> (HashMap<String, String>, &str) {
+    let text = text.trim_start_matches('\u{feff}');
+    let mut fields = HashMap::new();
+
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (fields, text);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            return (fields, &rest[offset..]);
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            fields.insert(key.trim().to_lowercase(), value.to_string());
+        }
+    }
+
+    // No closing delimiter: not front-matter after all
+    (HashMap::new(), text)
+}
+
+#[derive(Debug, Default)]
+struct PageContent {
+    meta: PageMeta,
+    blocks: Vec<Block>,
+}
+
+fn parse_page(entry: &str, data: &[u8]) -> PageContent {
+    let text = String::from_utf8_lossy(data);
+    let (_, extension) = split_extension(entry);
+
+    let (mut meta, mut blocks) = if HTML_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        let document = yjs_import::parse_html(&text);
+        let mut meta = PageMeta::from_fields(&document.meta);
+        meta.title = meta.title.or(document.title);
+        (meta, document.blocks)
+    } else {
+        let (fields, body) = split_front_matter(&text);
+        (PageMeta::from_fields(&fields), yjs_import::parse_markdown(body))
+    };
+
+    // A leading h1 is the page title; drop it from the body
+    let heading = match blocks.first() {
+        Some(Block::Heading(1, inlines)) => Some(yjs_import::inline_text(inlines)),
+        _ => None,
+    };
+    match (&meta.title, heading) {
+        (Some(title), Some(heading)) if *title == heading => {
+            blocks.remove(0);
+        }
+        (None, Some(_)) => meta.title = yjs_import::take_title(&mut blocks),
+        _ => {}
+    }
+
+    PageContent { meta, blocks }
+}
+
+/// Resolve an image reference relative to the folder of the page referencing it
+/// Returns None for references leaving the archive root.
+fn resolve_path(folder: &str, src: &str) -> Option<String> {
+    let src = src.split(['?', '#']).next().unwrap_or(src);
+    let src = percent_decode(src);
+    let mut parts: Vec<&str> = folder.split('/').filter(|part| !part.is_empty()).collect();
+    for part in src.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        out.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Decode a base64 `data:` URI into its MIME type and bytes
+fn decode_data_uri(src: &str) -> Option<(String, Vec<u8>)> {
+    let (header, payload) = src.strip_prefix("data:")?.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    let data = general_purpose::STANDARD.decode(payload.trim()).ok()?;
+    Some((mime.to_string(), data))
+}
+
+/// Upload the images a page references and point its blocks at the stored copies
+///
+/// `uploaded` caches archive path (or data URI) → URL so shared images are stored once.
+async fn upload_images(
+    storage: &dyn Storage,
+    archive: &HashMap<String, Vec<u8>>,
+    source: &str,
+    blocks: &mut [Block],
+    uploaded: &mut HashMap<String, String>,
+    report: &mut ImportReport,
+) {
+    let folder = parent_folder(source);
+    let mut images = HashMap::new();
+
+    for src in yjs_import::image_sources(blocks) {
+        let (key, filename, data) = if let Some((mime, data)) = decode_data_uri(&src) {
+            let extension = mime.rsplit('/').next().unwrap_or("bin").split('+').next().unwrap_or("bin");
+            (src.clone(), format!("image.{}", extension), data)
+        } else if src.starts_with('/') || src.contains("://") || src.starts_with("data:") {
+            continue;
+        } else {
+            let Some(path) = resolve_path(folder, &src) else {
+                report.warnings.push(format!("{}: image {} is outside the archive", source, src));
+                continue;
+            };
+            if uploaded.contains_key(&path) {
+                images.insert(src, uploaded[&path].clone());
+                continue;
+            }
+            let Some(data) = archive.get(&path) else {
+                report.warnings.push(format!("{}: image {} not found in the archive", source, src));
+                continue;
+            };
+            let filename = path.rsplit('/').next().unwrap_or("image").to_string();
+            (path, filename, data.clone())
+        };
+
+        if let Some(url) = uploaded.get(&key) {
+            images.insert(src, url.clone());
+            continue;
+        }
+        if data.len() > MAX_IMAGE_BYTES {
+            report.warnings.push(format!("{}: image {} is larger than 10MB", source, filename));
+            continue;
+        }
+
+        let mime = match FileValidator::validate_file(&data, Some(&filename)) {
+            Ok(mime) if mime.starts_with("image/") => mime,
+            _ => {
+                report.warnings.push(format!("{}: {} is not a supported image", source, filename));
+                continue;
+            }
+        };
+        let filename = FileValidator::sanitize_filename(&filename).unwrap_or_else(|_| "image".to_string());
+
+        match storage.store_file(&data, &filename, &mime, IMAGE_FOLDER).await {
+            Ok(stored) => {
+                report.images_uploaded += 1;
+                uploaded.insert(key, stored.url.clone());
+                images.insert(src, stored.url);
+            }
+            Err(e) => {
+                warn!(error = ?e, filename = %filename, "Failed to store imported image");
+                report.warnings.push(format!("{}: failed to store image {}", source, filename));
+            }
+        }
+    }
+
+    yjs_import::rewrite_images(blocks, &images);
+}
+
+/// "getting-started" → "Getting started"
+fn title_from_name(name: &str) -> String {
+    let words = name.replace(['-', '_'], " ");
+    let words = words.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Untitled".to_string(),
+    }
+}
+
+/// First free slug of `base`, `base-2`, `base-3`, ...
+fn unique_slug(
+    conn: &mut DbConnection,
+    base: &str,
+    used: &mut HashSet<String>,
+) -> Result<String, diesel::result::Error> {
+    let mut candidate = base.to_string();
+    let mut suffix = 1;
+    loop {
+        if !used.contains(&candidate) {
+            match repository::get_documentation_page_by_slug(&candidate, conn) {
+                Err(diesel::result::Error::NotFound) => {
+                    used.insert(candidate.clone());
+                    return Ok(candidate);
+                }
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+        }
+        suffix += 1;
+        candidate = format!("{}-{}", base, suffix);
+    }
+}
+
+fn create_pages(
+    conn: &mut DbConnection,
+    plan: &[PlannedPage],
+    contents: Vec<PageContent>,
+    options: &ImportOptions,
+) -> Result<Vec<ImportedPage>, diesel::result::Error> {
+    let mut ids: Vec<i32> = Vec::with_capacity(plan.len());
+    let mut used_slugs = HashSet::new();
+    let mut created = Vec::with_capacity(plan.len());
+
+    for (page, content) in plan.iter().zip(contents) {
+        let parent_id = match page.parent {
+            Some(index) => Some(ids[index]),
+            None => options.parent_id,
+        };
+        let title = content.meta.title.unwrap_or_else(|| title_from_name(&page.name));
+        let base = content
+            .meta
+            .slug
+            .as_deref()
+            .map(slugify)
+            .filter(|slug| !slug.is_empty())
+            .unwrap_or_else(|| slugify(&page.name));
+        let base = if base.is_empty() { "page".to_string() } else { base };
+        let slug = unique_slug(conn, &base, &mut used_slugs)?;
+
+        let doc = yjs_import::build_document(&content.blocks);
+        let new_page = NewDocumentationPage {
+            uuid: Uuid::now_v7(),
+            title,
+            slug: Some(slug),
+            icon: content.meta.icon,
+            cover_image: None,
+            status: content.meta.status.unwrap_or(options.status),
+            created_by: options.author,
+            last_edited_by: options.author,
+            parent_id,
+            ticket_id: None,
+            display_order: Some(content.meta.display_order.unwrap_or(page.order)),
+            is_public: false,
+            is_template: false,
+            yjs_state_vector: None,
+            yjs_document: Some(yjs::encode_document(&doc)),
+            yjs_client_id: None,
+            has_unsaved_changes: false,
+        };
+
+        let created_page = repository::create_documentation_page(new_page, conn)?;
+        ids.push(created_page.id);
+        created.push(ImportedPage {
+            id: created_page.id,
+            title: created_page.title,
+            slug: created_page.slug,
+            parent_id: created_page.parent_id,
+            source: page.source.clone(),
+        });
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plan_pages_builds_hierarchy() {
+        let plan = plan_pages(&entries(&[
+            "kb/guides/index.md",
+            "kb/guides/setup/install.md",
+            "kb/faq.md",
+            "kb/assets/logo.png",
+        ]));
+        let summary: Vec<(&str, Option<&str>, Option<&str>)> = plan
+            .iter()
+            .map(|page| {
+                (
+                    page.key.as_str(),
+                    page.source.as_deref(),
+                    page.parent.map(|index| plan[index].key.as_str()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("guides", Some("kb/guides/index.md"), None),
+                ("guides/setup", None, Some("guides")),
+                ("faq", Some("kb/faq.md"), None),
+                ("guides/setup/install", Some("kb/guides/setup/install.md"), Some("guides/setup")),
+            ]
+        );
+        assert_eq!(plan[2].order, 0);
+        assert_eq!(plan[0].order, 1);
+    }
+
+    #[test]
+    fn test_plan_pages_uses_sibling_file_for_folder() {
+        let plan = plan_pages(&entries(&["guide.md", "guide/step.md", "index.md"]));
+        assert_eq!(plan[0].key, "guide");
+        assert_eq!(plan[0].source.as_deref(), Some("guide.md"));
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[2].key, "index");
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let (fields, body) = split_front_matter("---\ntitle: \"Setup: step one\"\ndisplay_order: 4\n---\n# Body\n");
+        let meta = PageMeta::from_fields(&fields);
+        assert_eq!(meta.title.as_deref(), Some("Setup: step one"));
+        assert_eq!(meta.display_order, Some(4));
+        assert_eq!(body, "# Body\n");
+
+        let (fields, body) = split_front_matter("---\n\nNot front-matter");
+        assert!(fields.is_empty());
+        assert_eq!(body, "---\n\nNot front-matter");
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(resolve_path("guides/setup", "../../assets/1-a%20b.png").as_deref(), Some("assets/1-a b.png"));
+        assert_eq!(resolve_path("guides", "./img/a.png?v=2").as_deref(), Some("guides/img/a.png"));
+        assert_eq!(resolve_path("", "../a.png"), None);
+    }
+
+    #[test]
+    fn test_title_from_name() {
+        assert_eq!(title_from_name("getting-started"), "Getting started");
+        assert_eq!(title_from_name("--"), "Untitled");
+    }
+}
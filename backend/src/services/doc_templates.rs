@@ -0,0 +1,263 @@
+//! Documentation Template Service
+//!
+//! Pages flagged `is_template` are skeletons for new pages. Instantiating one deep-copies its
+//! Yjs document into a new page and fills in `{{placeholder}}` tokens in the title, the text
+//! and string attributes (e.g. link targets):
+//!
+//! - `{{date}}`, `{{time}}` – when the page is created (UTC)
+//! - `{{author}}`, `{{author.name}}` – the user creating the page
+//! - `{{ticket.id}}`, `{{ticket.title}}`, `{{ticket.category}}`, `{{ticket.status}}` – the
+//!   linked ticket, when there is one
+//!
+//! Unknown placeholders are kept as they are. A ticket category can name a template that is
+//! used when documentation is created from one of its tickets.
+
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+use yrs::{Doc, ReadTxn, Transact, WriteTxn, XmlFragment};
+
+use crate::db::DbConnection;
+use crate::models::{DocumentationPage, DocumentationStatus, NewDocumentationPage, Ticket};
+use crate::repository;
+use crate::services::doc_export::page_document;
+use crate::utils::yjs::{self, PROSEMIRROR_FRAGMENT};
+
+/// Error type for template instantiation
+#[derive(Debug)]
+pub enum DocTemplateError {
+    TemplateNotFound(i32),
+    NotATemplate(i32),
+    DatabaseError(diesel::result::Error),
+}
+
+impl std::fmt::Display for DocTemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocTemplateError::TemplateNotFound(id) => write!(f, "Template {} not found", id),
+            DocTemplateError::NotATemplate(id) => write!(f, "Page {} is not a template", id),
+            DocTemplateError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for DocTemplateError {
+    fn from(e: diesel::result::Error) -> Self {
+        DocTemplateError::DatabaseError(e)
+    }
+}
+
+/// Values for a template's placeholders
+#[derive(Debug, Default, Clone)]
+pub struct TemplateValues(HashMap<String, String>);
+
+impl TemplateValues {
+    /// Values for a page created now by `author`, optionally linked to a ticket
+    pub fn new(author_name: &str, ticket: Option<&Ticket>, category_name: Option<&str>) -> Self {
+        let now = Utc::now();
+        let mut values = HashMap::from([
+            ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+            ("time".to_string(), now.format("%H:%M").to_string()),
+            ("author".to_string(), author_name.to_string()),
+            ("author.name".to_string(), author_name.to_string()),
+        ]);
+        if let Some(ticket) = ticket {
+            values.insert("ticket.id".to_string(), ticket.id.to_string());
+            values.insert("ticket.title".to_string(), ticket.title.clone());
+            let status = serde_json::to_value(ticket.status)
+                .ok()
+                .and_then(|status| status.as_str().map(str::to_string))
+                .unwrap_or_default();
+            values.insert("ticket.status".to_string(), status);
+            values.insert("ticket.category".to_string(), category_name.unwrap_or_default().to_string());
+        }
+        Self(values)
+    }
+
+    /// Replace `{{name}}` tokens (whitespace inside the braces is ignored)
+    pub fn substitute(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find("}}") {
+                Some(end) => {
+                    let name = after[..end].trim();
+                    match self.0.get(name) {
+                        Some(value) => out.push_str(value),
+                        None => out.push_str(&rest[start..start + 2 + end + 2]),
+                    }
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// Deep-copy a template document with its placeholders filled in
+pub fn instantiate_document(template: &Doc, values: &TemplateValues) -> Doc {
+    let doc = Doc::new();
+    let source_txn = template.transact();
+    if let Some(source) = source_txn.get_xml_fragment(PROSEMIRROR_FRAGMENT) {
+        let mut target_txn = doc.transact_mut();
+        let target = target_txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+        for child in source.children(&source_txn) {
+            yjs::copy_node_with(&child, &source_txn, &target, &mut target_txn, &|text: &str| values.substitute(text));
+        }
+    }
+    doc
+}
+
+/// Where and for whom to create a page from a template
+pub struct InstantiateOptions {
+    /// Title for the new page; the template's title (with placeholders filled in) otherwise
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub icon: Option<String>,
+    pub parent_id: Option<i32>,
+    pub ticket_id: Option<i32>,
+    pub author: Uuid,
+}
+
+/// Service for documentation templates
+pub struct DocTemplateService;
+
+impl DocTemplateService {
+    /// All template pages, by title
+    pub fn list_templates(conn: &mut DbConnection) -> Result<Vec<DocumentationPage>, DocTemplateError> {
+        Ok(repository::get_template_pages(conn)?)
+    }
+
+    /// Load a page and check it is a template
+    pub fn get_template(conn: &mut DbConnection, template_id: i32) -> Result<DocumentationPage, DocTemplateError> {
+        let page = repository::get_documentation_page(template_id, conn).map_err(|e| match e {
+            diesel::result::Error::NotFound => DocTemplateError::TemplateNotFound(template_id),
+            e => DocTemplateError::DatabaseError(e),
+        })?;
+        if !page.is_template {
+            return Err(DocTemplateError::NotATemplate(template_id));
+        }
+        Ok(page)
+    }
+
+    /// Template configured for a ticket category, if any
+    pub fn template_for_category(
+        conn: &mut DbConnection,
+        category_id: i32,
+    ) -> Result<Option<DocumentationPage>, DocTemplateError> {
+        let category = repository::categories::get_category_by_id(conn, category_id)?;
+        match category.documentation_template_id {
+            Some(template_id) => match Self::get_template(conn, template_id) {
+                Ok(template) => Ok(Some(template)),
+                // The page was unflagged or removed since the category was configured
+                Err(DocTemplateError::TemplateNotFound(_) | DocTemplateError::NotATemplate(_)) => Ok(None),
+                Err(e) => Err(e),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Create a draft page from a template
+    pub fn instantiate(
+        conn: &mut DbConnection,
+        template: &DocumentationPage,
+        options: InstantiateOptions,
+    ) -> Result<DocumentationPage, DocTemplateError> {
+        let author_name = repository::get_user_by_uuid(&options.author, conn)
+            .map(|user| user.name)
+            .unwrap_or_default();
+        let ticket = match options.ticket_id {
+            Some(ticket_id) => Some(repository::get_ticket_by_id(conn, ticket_id)?),
+            None => None,
+        };
+        let category_name = ticket
+            .as_ref()
+            .and_then(|ticket| ticket.category_id)
+            .and_then(|category_id| repository::categories::get_category_by_id(conn, category_id).ok())
+            .map(|category| category.name);
+        let values = TemplateValues::new(&author_name, ticket.as_ref(), category_name.as_deref());
+
+        let title = values.substitute(
+            options
+                .title
+                .as_deref()
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or(&template.title),
+        );
+        let document = page_document(conn, template).map(|doc| yjs::encode_document(&instantiate_document(&doc, &values)));
+
+        let new_page = NewDocumentationPage {
+            uuid: Uuid::now_v7(),
+            title,
+            slug: options.slug,
+            icon: options.icon.or_else(|| template.icon.clone()),
+            cover_image: template.cover_image.clone(),
+            status: DocumentationStatus::Draft,
+            created_by: options.author,
+            last_edited_by: options.author,
+            parent_id: options.parent_id,
+            ticket_id: options.ticket_id,
+            display_order: Some(0),
+            is_public: false,
+            is_template: false,
+            yjs_state_vector: None,
+            yjs_document: document,
+            yjs_client_id: None,
+            has_unsaved_changes: false,
+        };
+
+        Ok(repository::create_documentation_page(new_page, conn)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::yjs::push_paragraph;
+    use crate::utils::yjs_render::{self, Markup};
+    use yrs::{Xml, XmlElementPrelim};
+
+    fn values() -> TemplateValues {
+        TemplateValues(HashMap::from([
+            ("ticket.title".to_string(), "VPN outage".to_string()),
+            ("ticket.id".to_string(), "42".to_string()),
+        ]))
+    }
+
+    #[test]
+    fn test_substitute() {
+        let values = values();
+        assert_eq!(values.substitute("Post-incident: {{ ticket.title }}"), "Post-incident: VPN outage");
+        assert_eq!(values.substitute("{{unknown}} and {{ticket.id}}"), "{{unknown}} and 42");
+        assert_eq!(values.substitute("open {{ticket.id"), "open {{ticket.id");
+    }
+
+    #[test]
+    fn test_instantiate_document_fills_text_and_attributes() {
+        let template = Doc::new();
+        {
+            let mut txn = template.transact_mut();
+            let fragment = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+            push_paragraph(&fragment, &mut txn, "Incident {{ticket.id}}: {{ticket.title}}");
+            let paragraph = fragment.push_back(&mut txn, XmlElementPrelim::empty("paragraph"));
+            let image = paragraph.push_back(&mut txn, XmlElementPrelim::empty("image"));
+            image.insert_attribute(&mut txn, "src", "/uploads/{{ticket.id}}.png");
+        }
+
+        let doc = instantiate_document(&template, &values());
+        let html = yjs_render::render(&doc, Markup::Html, &HashMap::new());
+        assert!(html.contains("<p>Incident 42: VPN outage</p>"));
+        assert!(html.contains("src=\"/uploads/42.png\""));
+
+        // The template itself is untouched
+        let original = yjs_render::render(&template, Markup::Html, &HashMap::new());
+        assert!(original.contains("{{ticket.title}}"));
+    }
+}
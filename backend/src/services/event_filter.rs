@@ -0,0 +1,356 @@
+//! Event Filter Service
+//!
+//! Decides which SSE events a connected client receives.
+//!
+//! Clients can narrow their stream with subscription filters (specific tickets, tickets
+//! assigned to them, event categories). Independently of filters, end users only receive
+//! events for tickets they are part of (requester, assignee or creator) and for their own
+//! devices and profile; technicians and admins receive everything.
+//!
+//! Ticket parties and device owners are looked up once per event when it is broadcast
+//! (see [`resolve_subject`]) and travel with the event, so checking a client is free.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::handlers::sse::TicketEvent;
+use crate::schema::{devices, tickets};
+
+/// Coarse grouping of events for subscription filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    Tickets,
+    Comments,
+    Devices,
+    Projects,
+    Documentation,
+    Users,
+    Presence,
+    System,
+}
+
+impl EventCategory {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "tickets" | "ticket" => Some(Self::Tickets),
+            "comments" | "comment" => Some(Self::Comments),
+            "devices" | "device" => Some(Self::Devices),
+            "projects" | "project" => Some(Self::Projects),
+            "documentation" | "docs" => Some(Self::Documentation),
+            "users" | "user" => Some(Self::Users),
+            "presence" => Some(Self::Presence),
+            _ => None,
+        }
+    }
+
+    pub fn of(event: &TicketEvent) -> Self {
+        match event {
+            TicketEvent::TicketUpdated { .. }
+            | TicketEvent::TicketCreated { .. }
+            | TicketEvent::TicketDeleted { .. }
+            | TicketEvent::TicketLinked { .. }
+            | TicketEvent::TicketUnlinked { .. } => Self::Tickets,
+            TicketEvent::CommentAdded { .. }
+            | TicketEvent::CommentDeleted { .. }
+            | TicketEvent::AttachmentAdded { .. }
+            | TicketEvent::AttachmentDeleted { .. } => Self::Comments,
+            TicketEvent::DeviceLinked { .. }
+            | TicketEvent::DeviceUnlinked { .. }
+            | TicketEvent::DeviceUpdated { .. } => Self::Devices,
+            TicketEvent::ProjectAssigned { .. } | TicketEvent::ProjectUnassigned { .. } => Self::Projects,
+            TicketEvent::DocumentationUpdated { .. } => Self::Documentation,
+            TicketEvent::UserUpdated { .. }
+            | TicketEvent::UserCreated { .. }
+            | TicketEvent::UserDeleted { .. } => Self::Users,
+            TicketEvent::ViewerCountChanged { .. } => Self::Presence,
+            TicketEvent::Heartbeat { .. } => Self::System,
+        }
+    }
+}
+
+/// Ticket an event is about, if any
+pub fn event_ticket_id(event: &TicketEvent) -> Option<i32> {
+    match event {
+        TicketEvent::TicketUpdated { ticket_id, .. }
+        | TicketEvent::TicketCreated { ticket_id, .. }
+        | TicketEvent::TicketDeleted { ticket_id, .. }
+        | TicketEvent::CommentAdded { ticket_id, .. }
+        | TicketEvent::CommentDeleted { ticket_id, .. }
+        | TicketEvent::AttachmentAdded { ticket_id, .. }
+        | TicketEvent::AttachmentDeleted { ticket_id, .. }
+        | TicketEvent::DeviceLinked { ticket_id, .. }
+        | TicketEvent::DeviceUnlinked { ticket_id, .. }
+        | TicketEvent::ProjectAssigned { ticket_id, .. }
+        | TicketEvent::ProjectUnassigned { ticket_id, .. }
+        | TicketEvent::TicketLinked { ticket_id, .. }
+        | TicketEvent::TicketUnlinked { ticket_id, .. }
+        | TicketEvent::ViewerCountChanged { ticket_id, .. } => Some(*ticket_id),
+        _ => None,
+    }
+}
+
+/// Subscription filters requested by the client (all optional, combined with AND)
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub ticket_ids: Option<HashSet<i32>>,
+    pub assigned_to_me: bool,
+    pub categories: Option<HashSet<EventCategory>>,
+}
+
+impl EventFilter {
+    /// Build a filter from the stream query parameters
+    /// `ticket_ids` and `categories` are comma-separated; `assigned=me` keeps only my tickets.
+    pub fn from_query(ticket_ids: Option<&str>, assigned: Option<&str>, categories: Option<&str>) -> Result<Self, String> {
+        let ticket_ids = match ticket_ids.filter(|v| !v.trim().is_empty()) {
+            Some(list) => Some(
+                list.split(',')
+                    .map(|id| id.trim().parse::<i32>().map_err(|_| format!("Invalid ticket id '{}'", id.trim())))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        let categories = categories
+            .filter(|v| !v.trim().is_empty())
+            .map(|list| list.split(',').collect::<Vec<_>>());
+
+        Self::from_parts(ticket_ids, assigned, categories.as_deref())
+    }
+
+    /// Build a filter from already split values (websocket subscribe messages)
+    pub fn from_parts<S: AsRef<str>>(ticket_ids: Option<Vec<i32>>, assigned: Option<&str>, categories: Option<&[S]>) -> Result<Self, String> {
+        let assigned_to_me = match assigned.map(|v| v.trim().to_lowercase()) {
+            None => false,
+            Some(value) if value.is_empty() => false,
+            Some(value) if value == "me" => true,
+            Some(value) => return Err(format!("Unsupported assigned filter '{}', expected 'me'", value)),
+        };
+
+        let categories = match categories {
+            Some(names) => Some(
+                names
+                    .iter()
+                    .map(|name| {
+                        EventCategory::parse(name.as_ref())
+                            .ok_or_else(|| format!("Unknown event category '{}'", name.as_ref().trim()))
+                    })
+                    .collect::<Result<HashSet<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            ticket_ids: ticket_ids.map(|ids| ids.into_iter().collect()),
+            assigned_to_me,
+            categories,
+        })
+    }
+}
+
+/// People attached to a ticket, for access checks and the assigned filter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TicketParties {
+    pub requester: Option<Uuid>,
+    pub assignee: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+}
+
+impl From<&crate::models::Ticket> for TicketParties {
+    fn from(ticket: &crate::models::Ticket) -> Self {
+        Self {
+            requester: ticket.requester_uuid,
+            assignee: ticket.assignee_uuid,
+            created_by: ticket.created_by,
+        }
+    }
+}
+
+impl TicketParties {
+    pub fn includes(&self, user: Uuid) -> bool {
+        [self.requester, self.assignee, self.created_by].contains(&Some(user))
+    }
+}
+
+/// What the deciding function needs to know about the event's subject
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Subject {
+    #[default]
+    None,
+    /// `None` when the ticket could not be found (for example, deleted)
+    Ticket(Option<TicketParties>),
+    /// Primary user of the device, if any
+    Device(Option<Uuid>),
+}
+
+/// Decide whether an event passes the client's filters and permissions
+pub fn is_visible(event: &TicketEvent, filter: &EventFilter, user: Uuid, is_staff: bool, subject: &Subject) -> bool {
+    let category = EventCategory::of(event);
+    if category == EventCategory::System {
+        return true;
+    }
+
+    if let Some(categories) = &filter.categories {
+        if !categories.contains(&category) {
+            return false;
+        }
+    }
+
+    let ticket_id = event_ticket_id(event);
+    if let Some(ticket_ids) = &filter.ticket_ids {
+        if !ticket_id.is_some_and(|id| ticket_ids.contains(&id)) {
+            return false;
+        }
+    }
+
+    if filter.assigned_to_me {
+        match subject {
+            Subject::Ticket(Some(parties)) if parties.assignee == Some(user) => {}
+            _ => return false,
+        }
+    }
+
+    if is_staff {
+        return true;
+    }
+
+    match (event, subject) {
+        (_, Subject::Ticket(parties)) => parties.is_some_and(|parties| parties.includes(user)),
+        (_, Subject::Device(owner)) => *owner == Some(user),
+        (TicketEvent::UserUpdated { user_uuid, .. }, _) => Uuid::parse_str(user_uuid).ok() == Some(user),
+        (TicketEvent::UserCreated { .. } | TicketEvent::UserDeleted { .. }, _) => false,
+        (TicketEvent::DocumentationUpdated { .. }, _) => true,
+        _ => false,
+    }
+}
+
+/// Look up the ticket parties or device owner an event is about
+/// Called once per event when it is broadcast; events about neither need no query.
+pub fn resolve_subject(conn: &mut DbConnection, event: &TicketEvent) -> QueryResult<Subject> {
+    match (event_ticket_id(event), event) {
+        (Some(ticket_id), _) => {
+            let parties = tickets::table
+                .find(ticket_id)
+                .select((tickets::requester_uuid, tickets::assignee_uuid, tickets::created_by))
+                .first::<(Option<Uuid>, Option<Uuid>, Option<Uuid>)>(conn)
+                .optional()?
+                .map(|(requester, assignee, created_by)| TicketParties { requester, assignee, created_by });
+            Ok(Subject::Ticket(parties))
+        }
+        (None, TicketEvent::DeviceUpdated { device_id, .. }) => {
+            let owner = devices::table
+                .find(*device_id)
+                .select(devices::primary_user_uuid)
+                .first::<Option<Uuid>>(conn)
+                .optional()?
+                .flatten();
+            Ok(Subject::Device(owner))
+        }
+        _ => Ok(Subject::None),
+    }
+}
+
+/// Per-connection access checker
+pub struct EventAccess {
+    user: Uuid,
+    is_staff: bool,
+    filter: EventFilter,
+}
+
+impl EventAccess {
+    pub fn new(user: Uuid, is_staff: bool, filter: EventFilter) -> Self {
+        Self { user, is_staff, filter }
+    }
+
+    /// Replace the subscription filters, keeping the permission checks
+    pub fn set_filter(&mut self, filter: EventFilter) {
+        self.filter = filter;
+    }
+
+    /// Whether the client may receive this event, given the subject resolved at broadcast time
+    pub fn allows(&self, event: &TicketEvent, subject: &Subject) -> bool {
+        is_visible(event, &self.filter, self.user, self.is_staff, subject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn comment_on(ticket_id: i32) -> TicketEvent {
+        TicketEvent::CommentAdded { ticket_id, comment: serde_json::Value::Null, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        let filter = EventFilter::from_query(Some("123, 7"), Some("me"), Some("devices,tickets")).unwrap();
+        assert_eq!(filter.ticket_ids, Some(HashSet::from([123, 7])));
+        assert!(filter.assigned_to_me);
+        assert!(filter.categories.unwrap().contains(&EventCategory::Devices));
+
+        assert!(EventFilter::from_query(Some("abc"), None, None).is_err());
+        assert!(EventFilter::from_query(None, Some("bob"), None).is_err());
+        assert!(EventFilter::from_query(None, None, Some("weather")).is_err());
+    }
+
+    #[test]
+    fn test_end_user_only_sees_own_tickets() {
+        let me = Uuid::new_v4();
+        let mine = Subject::Ticket(Some(TicketParties { requester: Some(me), ..Default::default() }));
+        let other = Subject::Ticket(Some(TicketParties { requester: Some(Uuid::new_v4()), ..Default::default() }));
+        let filter = EventFilter::default();
+
+        assert!(is_visible(&comment_on(1), &filter, me, false, &mine));
+        assert!(!is_visible(&comment_on(2), &filter, me, false, &other));
+        assert!(!is_visible(&comment_on(3), &filter, me, false, &Subject::Ticket(None)));
+        // Staff see everything
+        assert!(is_visible(&comment_on(2), &filter, me, true, &Subject::Ticket(None)));
+    }
+
+    #[test]
+    fn test_ticket_and_category_filters() {
+        let me = Uuid::new_v4();
+        let filter = EventFilter::from_query(Some("123"), None, None).unwrap();
+        assert!(is_visible(&comment_on(123), &filter, me, true, &Subject::Ticket(None)));
+        assert!(!is_visible(&comment_on(124), &filter, me, true, &Subject::Ticket(None)));
+
+        let devices_only = EventFilter::from_query(None, None, Some("devices")).unwrap();
+        assert!(!is_visible(&comment_on(123), &devices_only, me, true, &Subject::Ticket(None)));
+        let device_event = TicketEvent::DeviceUpdated {
+            device_id: 4,
+            field: "name".to_string(),
+            value: serde_json::Value::Null,
+            updated_by: String::new(),
+            timestamp: Utc::now(),
+        };
+        assert!(is_visible(&device_event, &devices_only, me, true, &Subject::None));
+        assert!(!is_visible(&device_event, &devices_only, me, false, &Subject::Device(None)));
+    }
+
+    #[test]
+    fn test_access_uses_subject_resolved_at_broadcast() {
+        let me = Uuid::new_v4();
+        let access = EventAccess::new(me, false, EventFilter::default());
+        let mine = Subject::Ticket(Some(TicketParties { assignee: Some(me), ..Default::default() }));
+
+        assert!(access.allows(&comment_on(1), &mine));
+        assert!(!access.allows(&comment_on(1), &Subject::default()));
+
+        // Relayed envelopes round-trip their subject
+        let json = serde_json::to_string(&mine).unwrap();
+        assert_eq!(serde_json::from_str::<Subject>(&json).unwrap(), mine);
+    }
+
+    #[test]
+    fn test_assigned_to_me_filter() {
+        let me = Uuid::new_v4();
+        let filter = EventFilter::from_query(None, Some("me"), None).unwrap();
+        let assigned = Subject::Ticket(Some(TicketParties { assignee: Some(me), ..Default::default() }));
+        let requested = Subject::Ticket(Some(TicketParties { requester: Some(me), ..Default::default() }));
+
+        assert!(is_visible(&comment_on(1), &filter, me, true, &assigned));
+        assert!(!is_visible(&comment_on(1), &filter, me, true, &requested));
+    }
+}
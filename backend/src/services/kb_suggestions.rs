@@ -0,0 +1,445 @@
+//! Knowledge Base Suggestion Service
+//!
+//! Suggests published documentation pages for a ticket's title and description, both on
+//! ticket detail and while a requester is still writing the ticket. Pages are ranked with
+//! Okapi BM25 over their title and text; query words that appear nowhere in the knowledge
+//! base (usually typos) are matched to similar indexed words by trigram similarity.
+//!
+//! Clicks on suggestions are recorded. A click made before submitting that isn't followed
+//! by a ticket from the same user within [`DEFLECTION_WINDOW_HOURS`] counts as a deflected
+//! ticket.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::{KbSuggestionClick, KbSuggestionSource};
+use crate::repository;
+use crate::services::public_kb::{self, IndexedPage, PublicKbIndex};
+
+/// BM25 term frequency saturation
+const K1: f32 = 1.2;
+/// BM25 document length normalisation
+const B: f32 = 0.75;
+/// Title words count this many times towards a page's term frequencies
+const TITLE_WEIGHT: u32 = 3;
+/// Query words only found in the description weigh less than title words
+const DESCRIPTION_QUERY_WEIGHT: f32 = 0.5;
+/// Long descriptions are cut to this many distinct words
+const MAX_QUERY_TERMS: usize = 64;
+/// Minimum trigram similarity for a misspelt word to match an indexed one
+const MIN_TRIGRAM_SIMILARITY: f32 = 0.45;
+/// Suggestions scoring below this fraction of the best one are dropped
+const MIN_RELATIVE_SCORE: f32 = 0.35;
+/// Suggestions scoring below this are dropped
+const MIN_SCORE: f32 = 0.5;
+
+/// Hours after a pre-submit click in which a new ticket means the article didn't help
+pub const DEFLECTION_WINDOW_HOURS: i64 = 24;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "am", "an", "and", "any", "are", "as", "at", "be",
+    "been", "but", "by", "can", "cannot", "cant", "could", "did", "do", "does", "doesnt", "dont",
+    "for", "from", "get", "getting", "had", "has", "have", "having", "hello", "help", "hi", "how",
+    "i", "if", "im", "in", "into", "is", "isnt", "it", "its", "ive", "just", "me", "my", "need",
+    "no", "not", "of", "on", "or", "our", "please", "so", "some", "still", "thanks", "that",
+    "the", "their", "them", "then", "there", "this", "to", "too", "up", "us", "was", "we",
+    "were", "what", "when", "where", "which", "while", "why", "will", "with", "wont", "would",
+    "you", "your",
+];
+
+/// Reduce a lowercase word to a crude stem so "printers", "printer" and "printing" match
+fn stem(word: &str) -> String {
+    let mut stem = word;
+    if let Some(base) = stem.strip_suffix("ies").filter(|base| base.len() >= 3) {
+        return format!("{}y", base);
+    }
+    for suffix in ["ing", "ed"] {
+        if let Some(base) = stem.strip_suffix(suffix).filter(|base| base.len() >= 3) {
+            stem = base;
+            break;
+        }
+    }
+    if !stem.ends_with("ss") {
+        if let Some(base) = stem.strip_suffix('s').filter(|base| base.len() >= 3) {
+            stem = base;
+        }
+    }
+    if let Some(base) = stem.strip_suffix('e').filter(|base| base.len() >= 3) {
+        stem = base;
+    }
+    stem.to_string()
+}
+
+/// Lowercase, stemmed words of `text` without stop words
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() >= 2 && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .collect()
+}
+
+fn trigrams(word: &str) -> HashSet<String> {
+    let padded: Vec<char> = format!("  {} ", word).chars().collect();
+    padded.windows(3).map(|window| window.iter().collect()).collect()
+}
+
+/// Jaccard similarity of two words' trigram sets
+fn trigram_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let shared = a.intersection(&b).count();
+    let total = a.union(&b).count();
+    if total == 0 { 0.0 } else { shared as f32 / total as f32 }
+}
+
+struct IndexedDocument {
+    page: IndexedPage,
+    term_frequencies: HashMap<String, u32>,
+    length: u32,
+}
+
+/// BM25 index over the published pages
+pub struct SuggestionIndex {
+    documents: Vec<IndexedDocument>,
+    document_frequencies: HashMap<String, u32>,
+    average_length: f32,
+}
+
+impl SuggestionIndex {
+    pub fn new(pages: Vec<IndexedPage>) -> Self {
+        let mut document_frequencies: HashMap<String, u32> = HashMap::new();
+        let documents: Vec<IndexedDocument> = pages
+            .into_iter()
+            .map(|page| {
+                let mut term_frequencies: HashMap<String, u32> = HashMap::new();
+                for term in tokenize(&page.title) {
+                    *term_frequencies.entry(term).or_default() += TITLE_WEIGHT;
+                }
+                for term in tokenize(&page.text) {
+                    *term_frequencies.entry(term).or_default() += 1;
+                }
+                for term in term_frequencies.keys() {
+                    *document_frequencies.entry(term.clone()).or_default() += 1;
+                }
+                let length = term_frequencies.values().sum();
+                IndexedDocument { page, term_frequencies, length }
+            })
+            .collect();
+
+        let total_length: u64 = documents.iter().map(|document| document.length as u64).sum();
+        let average_length = if documents.is_empty() {
+            0.0
+        } else {
+            (total_length as f32 / documents.len() as f32).max(1.0)
+        };
+
+        Self { documents, document_frequencies, average_length }
+    }
+
+    fn idf(&self, term: &str) -> f32 {
+        let total = self.documents.len() as f32;
+        let frequency = self.document_frequencies.get(term).copied().unwrap_or(0) as f32;
+        (1.0 + (total - frequency + 0.5) / (frequency + 0.5)).ln()
+    }
+
+    /// Weighted query terms; unknown words are replaced by their closest indexed word
+    fn query_terms(&self, title: &str, description: &str) -> HashMap<String, f32> {
+        let mut weights: HashMap<String, f32> = HashMap::new();
+        let title_terms = tokenize(title).into_iter().map(|term| (term, 1.0));
+        let description_terms = tokenize(description).into_iter().map(|term| (term, DESCRIPTION_QUERY_WEIGHT));
+
+        for (term, weight) in title_terms.chain(description_terms) {
+            if weights.len() >= MAX_QUERY_TERMS && !weights.contains_key(&term) {
+                continue;
+            }
+
+            let (term, weight) = if self.document_frequencies.contains_key(&term) {
+                (term, weight)
+            } else if term.chars().count() >= 4 {
+                let closest = self
+                    .document_frequencies
+                    .keys()
+                    .map(|known| (known, trigram_similarity(&term, known)))
+                    .filter(|(_, similarity)| *similarity >= MIN_TRIGRAM_SIMILARITY)
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(a.0)));
+                match closest {
+                    Some((known, similarity)) => (known.clone(), weight * similarity),
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+
+            let entry = weights.entry(term).or_default();
+            *entry = entry.max(weight);
+        }
+        weights
+    }
+
+    /// Best matching pages for a ticket's title and description, leaving out `exclude`
+    pub fn suggest(&self, title: &str, description: &str, limit: usize, exclude: &[i32]) -> Vec<Suggestion> {
+        let query = self.query_terms(title, description);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let idf: HashMap<&str, f32> = query.keys().map(|term| (term.as_str(), self.idf(term))).collect();
+
+        let mut scored: Vec<(&IndexedDocument, f32)> = self
+            .documents
+            .iter()
+            .filter(|document| !exclude.contains(&document.page.id))
+            .filter_map(|document| {
+                let normalisation = K1 * (1.0 - B + B * document.length as f32 / self.average_length);
+                let score: f32 = query
+                    .iter()
+                    .filter_map(|(term, weight)| {
+                        let frequency = *document.term_frequencies.get(term)? as f32;
+                        Some(weight * idf[term.as_str()] * frequency * (K1 + 1.0) / (frequency + normalisation))
+                    })
+                    .sum();
+                (score > 0.0).then_some((document, score))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.page.title.cmp(&b.0.page.title)));
+        let best = scored.first().map(|(_, score)| *score).unwrap_or(0.0);
+
+        let words: Vec<String> = format!("{} {}", title, description)
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|word| word.chars().count() >= 3 && !STOP_WORDS.contains(&word.as_str()))
+            .collect();
+
+        scored
+            .into_iter()
+            .take_while(|(_, score)| *score >= MIN_SCORE && *score >= best * MIN_RELATIVE_SCORE)
+            .take(limit)
+            .map(|(document, score)| Suggestion {
+                id: document.page.id,
+                key: document.page.key.clone(),
+                title: document.page.title.clone(),
+                path: document.page.path.clone(),
+                snippet: public_kb::snippet(&document.page.text, &words),
+                score: (score * 100.0).round() / 100.0,
+            })
+            .collect()
+    }
+}
+
+/// A suggested page
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub id: i32,
+    /// Slug, or id for pages without one
+    pub key: String,
+    pub title: String,
+    /// Titles of the page's ancestors, root first
+    pub path: Vec<String>,
+    pub snippet: String,
+    pub score: f32,
+}
+
+/// Cached suggestion index, rebuilt when a published page changes
+pub struct KbSuggestionIndex {
+    cached: RwLock<Option<(u64, Arc<SuggestionIndex>)>>,
+}
+
+impl Default for KbSuggestionIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KbSuggestionIndex {
+    pub fn new() -> Self {
+        Self { cached: RwLock::new(None) }
+    }
+
+    /// Index of the currently published pages, from cache when they haven't changed
+    pub fn get(&self, conn: &mut DbConnection) -> Result<Arc<SuggestionIndex>, diesel::result::Error> {
+        let pages = repository::get_published_documentation_pages(conn)?;
+        let fingerprint = PublicKbIndex::fingerprint(&pages);
+        if let Some((cached_fingerprint, index)) = self.cached.read().unwrap().as_ref() {
+            if *cached_fingerprint == fingerprint {
+                return Ok(index.clone());
+            }
+        }
+
+        let index = Arc::new(SuggestionIndex::new(public_kb::build_index(conn, &pages)?));
+        *self.cached.write().unwrap() = Some((fingerprint, index.clone()));
+        Ok(index)
+    }
+}
+
+/// Click counts for one page
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PageClickStats {
+    pub page_id: i32,
+    pub title: String,
+    pub clicks: u32,
+    pub pre_submit_clicks: u32,
+    pub deflected: u32,
+}
+
+/// Suggestion clicks over a period
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KbSuggestionStats {
+    pub since: Option<NaiveDateTime>,
+    pub clicks: u32,
+    pub pre_submit_clicks: u32,
+    pub ticket_clicks: u32,
+    /// Pre-submit clicks not followed by a ticket from the same user
+    pub deflected: u32,
+    /// Pages by number of clicks
+    pub pages: Vec<PageClickStats>,
+}
+
+/// Count clicks and deflections; `tickets` holds when each user created a ticket
+pub fn summarize_clicks(
+    clicks: &[KbSuggestionClick],
+    tickets: &HashMap<Uuid, Vec<NaiveDateTime>>,
+    titles: &HashMap<i32, String>,
+) -> KbSuggestionStats {
+    let window = Duration::hours(DEFLECTION_WINDOW_HOURS);
+    let mut stats = KbSuggestionStats::default();
+    let mut pages: HashMap<i32, PageClickStats> = HashMap::new();
+
+    for click in clicks {
+        let page = pages.entry(click.page_id).or_insert_with(|| PageClickStats {
+            page_id: click.page_id,
+            title: titles.get(&click.page_id).cloned().unwrap_or_default(),
+            ..Default::default()
+        });
+        page.clicks += 1;
+        stats.clicks += 1;
+
+        if click.source != KbSuggestionSource::PreSubmit.as_str() {
+            stats.ticket_clicks += 1;
+            continue;
+        }
+        page.pre_submit_clicks += 1;
+        stats.pre_submit_clicks += 1;
+
+        let submitted_anyway = click.user_uuid.is_some_and(|user| {
+            tickets.get(&user).is_some_and(|created| {
+                created.iter().any(|at| *at >= click.created_at && *at <= click.created_at + window)
+            })
+        });
+        if click.user_uuid.is_some() && !submitted_anyway {
+            page.deflected += 1;
+            stats.deflected += 1;
+        }
+    }
+
+    stats.pages = pages.into_values().collect();
+    stats.pages.sort_by(|a, b| b.clicks.cmp(&a.clicks).then_with(|| a.page_id.cmp(&b.page_id)));
+    stats
+}
+
+/// Click and deflection statistics for the last `days` days
+pub fn click_stats(conn: &mut DbConnection, days: i64) -> Result<KbSuggestionStats, diesel::result::Error> {
+    let since = Utc::now().naive_utc() - Duration::days(days);
+    let clicks = repository::kb_suggestions::get_clicks_since(conn, since)?;
+
+    let users: Vec<Uuid> = clicks
+        .iter()
+        .filter_map(|click| click.user_uuid)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let mut tickets: HashMap<Uuid, Vec<NaiveDateTime>> = HashMap::new();
+    for (requester, creator, created_at) in repository::kb_suggestions::get_ticket_creations_by_users(conn, &users, since)? {
+        for user in [requester, creator].into_iter().flatten() {
+            tickets.entry(user).or_default().push(created_at);
+        }
+    }
+
+    let page_ids: Vec<i32> = clicks.iter().map(|click| click.page_id).collect::<HashSet<_>>().into_iter().collect();
+    let titles: HashMap<i32, String> = repository::get_documentation_pages_by_ids(conn, &page_ids)?
+        .into_iter()
+        .map(|page| (page.id, page.title))
+        .collect();
+
+    let mut stats = summarize_clicks(&clicks, &tickets, &titles);
+    stats.since = Some(since);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn page(id: i32, title: &str, text: &str) -> IndexedPage {
+        IndexedPage {
+            id,
+            key: id.to_string(),
+            title: title.to_string(),
+            path: Vec::new(),
+            text: text.to_string(),
+            updated_at: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    fn index() -> SuggestionIndex {
+        SuggestionIndex::new(vec![
+            page(1, "Connecting to the VPN", "Install the VPN client and sign in with your account."),
+            page(2, "Printer troubleshooting", "If printing fails, restart the printer and check the queue."),
+            page(3, "Password reset", "Reset your password from the sign in page."),
+        ])
+    }
+
+    #[test]
+    fn test_tokenize_stems_and_drops_stop_words() {
+        assert_eq!(tokenize("My printers are not printing!"), vec!["printer", "print"]);
+        assert_eq!(tokenize("Configured the configure files"), vec!["configur", "configur", "fil"]);
+    }
+
+    #[test]
+    fn test_suggest_ranks_relevant_pages() {
+        let index = index();
+        let suggestions = index.suggest("Can't print", "The printer in room 4 isn't printing anything", 5, &[]);
+        assert_eq!(suggestions.first().map(|s| s.id), Some(2));
+        assert!(suggestions.iter().all(|s| s.id != 1));
+
+        // Misspelt words still match through trigrams
+        let suggestions = index.suggest("pasword resett", "", 5, &[]);
+        assert_eq!(suggestions.first().map(|s| s.id), Some(3));
+
+        // Excluded pages and stop-word-only queries give nothing
+        assert!(index.suggest("printer", "", 5, &[2]).is_empty());
+        assert!(index.suggest("please help", "it is not working for me", 5, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_summarize_clicks_counts_deflections() {
+        let at = |hour| NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap();
+        let (alice, bob) = (Uuid::now_v7(), Uuid::now_v7());
+        let click = |id, user, source: KbSuggestionSource, hour| KbSuggestionClick {
+            id,
+            page_id: 7,
+            user_uuid: Some(user),
+            ticket_id: None,
+            source: source.as_str().to_string(),
+            query: None,
+            created_at: at(hour),
+        };
+        let clicks = vec![
+            click(1, alice, KbSuggestionSource::PreSubmit, 1),
+            click(2, bob, KbSuggestionSource::PreSubmit, 1),
+            click(3, bob, KbSuggestionSource::Ticket, 3),
+        ];
+        // Bob submitted a ticket an hour after reading the article
+        let tickets = HashMap::from([(bob, vec![at(2)])]);
+
+        let stats = summarize_clicks(&clicks, &tickets, &HashMap::from([(7, "VPN".to_string())]));
+        assert_eq!((stats.clicks, stats.pre_submit_clicks, stats.ticket_clicks, stats.deflected), (3, 2, 1, 1));
+        assert_eq!(stats.pages.len(), 1);
+        assert_eq!(stats.pages[0].title, "VPN");
+        assert_eq!(stats.pages[0].deflected, 1);
+    }
+}
@@ -1,2 +1,23 @@
+pub mod admin;
+pub mod approvals;
 pub mod assignment;
 pub mod backup;
+pub mod backup_integrity;
+pub mod backup_migration;
+pub mod backup_schedule;
+pub mod bulk_import;
+pub mod catalog;
+pub mod doc_export;
+pub mod doc_import;
+pub mod doc_templates;
+pub mod event_filter;
+pub mod kb_suggestions;
+pub mod privacy;
+pub mod public_kb;
+pub mod recycle_bin;
+pub mod revision_diff;
+pub mod revision_retention;
+pub mod selective_restore;
+pub mod ticket_merge;
+pub mod ticket_relationships;
+pub mod ticket_status;
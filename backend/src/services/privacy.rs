@@ -0,0 +1,392 @@
+//! Privacy Service
+//!
+//! Answers data subject requests and enforces data retention:
+//!
+//! - **Export** gathers everything tied to a user into a zip: profile, email addresses, sign-in
+//!   identities, sessions, security events, tickets they requested or are assigned to, their
+//!   comments and attachments (with the files), and their avatar and banner. Secrets such as
+//!   password hashes, MFA secrets and session tokens are left out.
+//! - **Erasure** anonymises the user in place. Tickets and comments keep pointing at the same
+//!   account, which now shows as "Deleted user" and can no longer sign in.
+//! - **Retention** permanently deletes closed tickets and security events older than the
+//!   configured windows. Both are disabled unless set:
+//!   - `RETENTION_CLOSED_TICKETS_DAYS` - days after closing before a ticket is purged
+//!   - `RETENTION_SECURITY_EVENTS_DAYS` - days security events are kept
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::db::{DbConnection, Pool};
+use crate::models::{RecycleBinItemType, User};
+use crate::repository;
+use crate::repository::privacy as privacy_repo;
+use crate::services::doc_export::{slugify, storage_path_for_src, ExportedFile};
+use crate::services::recycle_bin;
+use crate::utils::storage::Storage;
+
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Fields never included in an export, per archive entry
+const SECRET_FIELDS: &[(&str, &[&str])] = &[
+    ("user.json", &["mfa_secret", "mfa_backup_codes", "passkey_credentials"]),
+    ("auth_identities.json", &["password_hash", "metadata"]),
+    ("sessions.json", &["session_token"]),
+];
+
+#[derive(Debug)]
+pub enum PrivacyError {
+    UserNotFound,
+    Database(diesel::result::Error),
+    Zip(zip::result::ZipError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for PrivacyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrivacyError::UserNotFound => write!(f, "User not found"),
+            PrivacyError::Database(e) => write!(f, "Database error: {}", e),
+            PrivacyError::Zip(e) => write!(f, "ZIP error: {}", e),
+            PrivacyError::Io(e) => write!(f, "IO error: {}", e),
+            PrivacyError::Json(e) => write!(f, "JSON error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for PrivacyError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => PrivacyError::UserNotFound,
+            e => PrivacyError::Database(e),
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for PrivacyError {
+    fn from(e: zip::result::ZipError) -> Self {
+        PrivacyError::Zip(e)
+    }
+}
+
+impl From<std::io::Error> for PrivacyError {
+    fn from(e: std::io::Error) -> Self {
+        PrivacyError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for PrivacyError {
+    fn from(e: serde_json::Error) -> Self {
+        PrivacyError::Json(e)
+    }
+}
+
+/// Serialize records for an archive entry, leaving out that entry's secret fields
+fn redacted<T: Serialize>(entry: &str, records: &T) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(records)?;
+    let fields = SECRET_FIELDS
+        .iter()
+        .find(|(name, _)| *name == entry)
+        .map(|(_, fields)| *fields)
+        .unwrap_or(&[]);
+
+    let objects: Vec<&mut serde_json::Map<String, Value>> = match &mut value {
+        Value::Object(map) => vec![map],
+        Value::Array(items) => items.iter_mut().filter_map(Value::as_object_mut).collect(),
+        _ => Vec::new(),
+    };
+    for object in objects {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+    Ok(value)
+}
+
+fn write_json<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    options: FileOptions,
+    entry: &str,
+    value: &Value,
+) -> Result<(), PrivacyError> {
+    zip.start_file(entry, options)?;
+    serde_json::to_writer_pretty(&mut *zip, value)?;
+    Ok(())
+}
+
+/// Copy a stored file into the archive; missing files are noted in the log and skipped
+async fn write_file<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    options: FileOptions,
+    storage: &dyn Storage,
+    url: &str,
+    entry: &str,
+) -> Result<bool, PrivacyError> {
+    let Some(path) = storage_path_for_src(url) else {
+        return Ok(false);
+    };
+    match storage.get_file(path).await {
+        Ok(data) => {
+            zip.start_file(entry, options)?;
+            zip.write_all(&data)?;
+            Ok(true)
+        }
+        Err(e) => {
+            warn!(path, error = ?e, "File missing from storage, left out of data export");
+            Ok(false)
+        }
+    }
+}
+
+/// File extension of a stored file's URL, including the dot
+fn extension(url: &str) -> &str {
+    let name = url.rsplit('/').next().unwrap_or(url);
+    name.rfind('.').map(|index| &name[index..]).unwrap_or("")
+}
+
+/// Export everything held about a user as a zip archive
+pub async fn export_user_data(
+    conn: &mut DbConnection,
+    storage: &dyn Storage,
+    user_uuid: Uuid,
+) -> Result<ExportedFile, PrivacyError> {
+    let user = repository::get_user_by_uuid(&user_uuid, conn)?;
+    let emails = repository::user_emails::get_user_emails_by_uuid(conn, &user_uuid)?;
+    let identities = repository::user_auth_identities::get_user_identities(&user_uuid, conn)?;
+    let sessions = privacy_repo::get_subject_sessions(conn, user_uuid)?;
+    let security_events = privacy_repo::get_subject_security_events(conn, user_uuid)?;
+    let tickets = privacy_repo::get_subject_tickets(conn, user_uuid)?;
+    let comments = privacy_repo::get_subject_comments(conn, user_uuid)?;
+    let attachments = privacy_repo::get_subject_attachments(conn, user_uuid)?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    write_json(&mut zip, options, "export.json", &json!({
+        "user_uuid": user_uuid,
+        "exported_at": Utc::now().naive_utc(),
+        "counts": {
+            "emails": emails.len(),
+            "auth_identities": identities.len(),
+            "sessions": sessions.len(),
+            "security_events": security_events.len(),
+            "tickets": tickets.len(),
+            "comments": comments.len(),
+            "attachments": attachments.len(),
+        },
+    }))?;
+    write_json(&mut zip, options, "user.json", &redacted("user.json", &user)?)?;
+    write_json(&mut zip, options, "emails.json", &redacted("emails.json", &emails)?)?;
+    write_json(&mut zip, options, "auth_identities.json", &redacted("auth_identities.json", &identities)?)?;
+    write_json(&mut zip, options, "sessions.json", &redacted("sessions.json", &sessions)?)?;
+    write_json(&mut zip, options, "security_events.json", &redacted("security_events.json", &security_events)?)?;
+    write_json(&mut zip, options, "tickets.json", &redacted("tickets.json", &tickets)?)?;
+    write_json(&mut zip, options, "comments.json", &redacted("comments.json", &comments)?)?;
+    write_json(&mut zip, options, "attachments.json", &redacted("attachments.json", &attachments)?)?;
+
+    for (url, name) in [(&user.avatar_url, "avatar"), (&user.banner_url, "banner")] {
+        if let Some(url) = url {
+            write_file(&mut zip, options, storage, url, &format!("files/profile/{}{}", name, extension(url))).await?;
+        }
+    }
+    for attachment in &attachments {
+        let entry = format!("files/attachments/{}-{}", attachment.id, attachment.name.replace(['/', '\\'], "_"));
+        write_file(&mut zip, options, storage, &attachment.url, &entry).await?;
+    }
+
+    let data = zip.finish()?.into_inner();
+    let name = match slugify(&user.name) {
+        name if name.is_empty() => user_uuid.to_string(),
+        name => name,
+    };
+
+    info!(user_uuid = %user_uuid, tickets = tickets.len(), attachments = attachments.len(), "Exported user data");
+    Ok(ExportedFile {
+        filename: format!("user-data-{}.zip", name),
+        content_type: "application/zip",
+        data,
+    })
+}
+
+/// Erase a user's personal data, keeping their tickets and comments
+///
+/// Returns the user as it was before erasure.
+pub async fn erase_user(
+    conn: &mut DbConnection,
+    storage: &dyn Storage,
+    user_uuid: Uuid,
+) -> Result<User, PrivacyError> {
+    let user = privacy_repo::anonymise_user(conn, user_uuid, Utc::now().naive_utc())?;
+
+    let files = [&user.avatar_url, &user.banner_url, &user.avatar_thumb];
+    let paths: Vec<String> = files
+        .into_iter()
+        .flatten()
+        .filter_map(|url| storage_path_for_src(url))
+        .map(str::to_string)
+        .collect();
+    recycle_bin::delete_files(storage, paths).await;
+
+    info!(user_uuid = %user_uuid, "Erased user personal data");
+    Ok(user)
+}
+
+// ============================================================================
+// Retention
+// ============================================================================
+
+/// How long closed tickets and security events are kept; `None` keeps them forever
+#[derive(Debug, Clone, Default)]
+pub struct RetentionWindows {
+    pub closed_tickets: Option<Duration>,
+    pub security_events: Option<Duration>,
+}
+
+fn window_days(value: Option<String>) -> Option<Duration> {
+    value
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .map(Duration::days)
+}
+
+impl RetentionWindows {
+    /// Load the windows from `RETENTION_CLOSED_TICKETS_DAYS` and `RETENTION_SECURITY_EVENTS_DAYS`
+    pub fn from_env() -> Self {
+        Self {
+            closed_tickets: window_days(std::env::var("RETENTION_CLOSED_TICKETS_DAYS").ok()),
+            security_events: window_days(std::env::var("RETENTION_SECURITY_EVENTS_DAYS").ok()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.closed_tickets.is_some() || self.security_events.is_some()
+    }
+}
+
+/// Outcome of a retention run
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub tickets_purged: usize,
+    pub security_events_deleted: usize,
+    /// Storage paths of purged tickets' files, to delete once the run is over
+    pub files: Vec<String>,
+}
+
+/// Permanently delete closed tickets and security events older than their windows
+pub fn apply_retention(
+    conn: &mut DbConnection,
+    windows: &RetentionWindows,
+    now: NaiveDateTime,
+) -> Result<RetentionReport, diesel::result::Error> {
+    let mut report = RetentionReport::default();
+
+    if let Some(window) = windows.closed_tickets {
+        for ticket_id in privacy_repo::get_tickets_closed_before(conn, now - window)? {
+            match recycle_bin::purge(conn, RecycleBinItemType::Ticket, ticket_id) {
+                Ok(files) => {
+                    report.tickets_purged += 1;
+                    report.files.extend(files);
+                }
+                Err(e) => error!(ticket_id, error = ?e, "Failed to purge closed ticket"),
+            }
+        }
+    }
+
+    if let Some(window) = windows.security_events {
+        report.security_events_deleted = privacy_repo::delete_security_events_before(conn, now - window)?;
+    }
+
+    Ok(report)
+}
+
+/// Start the background job that applies the retention windows once a day
+///
+/// Does nothing when no window is configured.
+pub fn spawn_retention_job(pool: Pool, storage: Arc<dyn Storage>) {
+    let windows = RetentionWindows::from_env();
+    if !windows.is_enabled() {
+        return;
+    }
+    info!(
+        closed_ticket_days = windows.closed_tickets.map(|w| w.num_days()),
+        security_event_days = windows.security_events.map(|w| w.num_days()),
+        "Data retention enabled"
+    );
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let retention_pool = pool.clone();
+            let run_windows = windows.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<RetentionReport, String> {
+                let mut conn = retention_pool.get().map_err(|e| e.to_string())?;
+                apply_retention(&mut conn, &run_windows, Utc::now().naive_utc()).map_err(|e| e.to_string())
+            })
+            .await;
+
+            match result {
+                Ok(Ok(report)) if report.tickets_purged == 0 && report.security_events_deleted == 0 => {
+                    debug!("Data retention: nothing to delete")
+                }
+                Ok(Ok(report)) => {
+                    info!(
+                        tickets = report.tickets_purged,
+                        security_events = report.security_events_deleted,
+                        "Deleted records past their retention window"
+                    );
+                    recycle_bin::delete_files(storage.as_ref(), report.files).await;
+                }
+                Ok(Err(e)) => error!(error = %e, "Data retention run failed"),
+                Err(e) => error!(error = ?e, "Data retention task panicked"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_strips_secrets() {
+        let sessions = json!([
+            {"id": 1, "session_token": "secret", "device_name": "Laptop"},
+            {"id": 2, "session_token": "secret", "device_name": null}
+        ]);
+        let value = redacted("sessions.json", &sessions).unwrap();
+        assert_eq!(value[0], json!({"id": 1, "device_name": "Laptop"}));
+        assert!(value[1].get("session_token").is_none());
+
+        let user = json!({"name": "Ada", "mfa_secret": "abc", "mfa_enabled": true});
+        assert_eq!(redacted("user.json", &user).unwrap(), json!({"name": "Ada", "mfa_enabled": true}));
+
+        // Entries without secret fields are unchanged
+        let tickets = json!([{"id": 1, "title": "Printer"}]);
+        assert_eq!(redacted("tickets.json", &tickets).unwrap(), tickets);
+    }
+
+    #[test]
+    fn test_window_days() {
+        assert_eq!(window_days(Some("90".to_string())), Some(Duration::days(90)));
+        assert_eq!(window_days(Some(" 7 ".to_string())), Some(Duration::days(7)));
+        assert_eq!(window_days(Some("0".to_string())), None);
+        assert_eq!(window_days(Some("-1".to_string())), None);
+        assert_eq!(window_days(Some("forever".to_string())), None);
+        assert_eq!(window_days(None), None);
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(extension("/uploads/users/avatars/abc.webp"), ".webp");
+        assert_eq!(extension("/uploads/users/banners/abc"), "");
+    }
+}
@@ -0,0 +1,420 @@
+//! Public Knowledge Base Service
+//!
+//! Read-only view of the documentation for unauthenticated visitors. A page is public when
+//! it is published, marked `is_public`, not archived and not a template, and every one of
+//! its ancestors is public too. A public page below a draft or private parent is hidden
+//! along with it, so the navigation tree, breadcrumbs, search and sitemap never reveal pages
+//! that aren't public.
+//!
+//! Pages are addressed by slug, or by id when they have none. The search index holds the
+//! plain text of every public page and is rebuilt whenever a public page changes.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+
+use crate::db::DbConnection;
+use crate::models::PublicDocumentationPage;
+use crate::repository;
+use crate::services::doc_export::page_document;
+use crate::utils::yjs;
+
+/// Public pages, with pages hidden by a non-public ancestor removed
+pub fn visible_pages(pages: Vec<PublicDocumentationPage>) -> Vec<PublicDocumentationPage> {
+    let parents: HashMap<i32, Option<i32>> = pages.iter().map(|page| (page.id, page.parent_id)).collect();
+
+    let is_visible = |id: i32| {
+        let mut seen = HashSet::new();
+        let mut current = id;
+        loop {
+            // Guards against parent_id cycles
+            if !seen.insert(current) {
+                return false;
+            }
+            match parents.get(&current) {
+                Some(Some(parent)) => current = *parent,
+                Some(None) => return true,
+                // A parent that isn't among the public pages
+                None => return false,
+            }
+        }
+    };
+
+    let visible: HashSet<i32> = pages.iter().map(|page| page.id).filter(|&id| is_visible(id)).collect();
+    pages.into_iter().filter(|page| visible.contains(&page.id)).collect()
+}
+
+/// URL key of a page: its slug, or its id when it has none
+pub fn page_key(page: &PublicDocumentationPage) -> String {
+    page.slug
+        .as_deref()
+        .filter(|slug| !slug.trim().is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| page.id.to_string())
+}
+
+/// Find a visible page by slug or id
+pub fn find_page<'a>(pages: &'a [PublicDocumentationPage], key: &str) -> Option<&'a PublicDocumentationPage> {
+    pages
+        .iter()
+        .find(|page| page.slug.as_deref() == Some(key))
+        .or_else(|| {
+            let id: i32 = key.parse().ok()?;
+            pages.iter().find(|page| page.id == id)
+        })
+}
+
+/// Ancestors of a page, root first
+pub fn breadcrumbs<'a>(pages: &'a [PublicDocumentationPage], page: &PublicDocumentationPage) -> Vec<&'a PublicDocumentationPage> {
+    let by_id: HashMap<i32, &PublicDocumentationPage> = pages.iter().map(|page| (page.id, page)).collect();
+    let mut trail = Vec::new();
+    let mut seen = HashSet::from([page.id]);
+    let mut parent = page.parent_id;
+    while let Some(parent_page) = parent.and_then(|id| by_id.get(&id)) {
+        if !seen.insert(parent_page.id) {
+            break;
+        }
+        trail.push(*parent_page);
+        parent = parent_page.parent_id;
+    }
+    trail.reverse();
+    trail
+}
+
+/// Direct children of a page (or top-level pages for None), in display order
+pub fn children_of(pages: &[PublicDocumentationPage], parent_id: Option<i32>) -> Vec<&PublicDocumentationPage> {
+    let mut children: Vec<&PublicDocumentationPage> =
+        pages.iter().filter(|page| page.parent_id == parent_id).collect();
+    children.sort_by(|a, b| {
+        a.display_order
+            .unwrap_or(i32::MAX)
+            .cmp(&b.display_order.unwrap_or(i32::MAX))
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    children
+}
+
+/// Navigation tree entry
+#[derive(Debug, Clone, Serialize)]
+pub struct NavNode {
+    pub id: i32,
+    pub key: String,
+    pub title: String,
+    pub icon: Option<String>,
+    pub children: Vec<NavNode>,
+}
+
+/// Navigation tree of the visible pages
+pub fn nav_tree(pages: &[PublicDocumentationPage]) -> Vec<NavNode> {
+    fn build(pages: &[PublicDocumentationPage], parent_id: Option<i32>, seen: &mut HashSet<i32>) -> Vec<NavNode> {
+        let mut nodes = Vec::new();
+        for page in children_of(pages, parent_id) {
+            if !seen.insert(page.id) {
+                continue;
+            }
+            nodes.push(NavNode {
+                id: page.id,
+                key: page_key(page),
+                title: page.title.clone(),
+                icon: page.icon.clone(),
+                children: build(pages, Some(page.id), seen),
+            });
+        }
+        nodes
+    }
+    build(pages, None, &mut HashSet::new())
+}
+
+/// Search index entry for one page
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedPage {
+    pub id: i32,
+    pub key: String,
+    pub title: String,
+    /// Titles of the page's ancestors, root first
+    pub path: Vec<String>,
+    pub text: String,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// Build index entries from the visible pages' content
+pub fn build_index(
+    conn: &mut DbConnection,
+    pages: &[PublicDocumentationPage],
+) -> Result<Vec<IndexedPage>, diesel::result::Error> {
+    let ids: Vec<i32> = pages.iter().map(|page| page.id).collect();
+    let full_pages: HashMap<i32, _> = repository::get_documentation_pages_by_ids(conn, &ids)?
+        .into_iter()
+        .map(|page| (page.id, page))
+        .collect();
+
+    let mut index = Vec::with_capacity(pages.len());
+    for page in pages {
+        let text = full_pages
+            .get(&page.id)
+            .and_then(|full_page| page_document(conn, full_page))
+            .map(|doc| {
+                yjs::document_blocks(&doc)
+                    .into_iter()
+                    .map(|block| block.text)
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+
+        index.push(IndexedPage {
+            id: page.id,
+            key: page_key(page),
+            title: page.title.clone(),
+            path: breadcrumbs(pages, page).into_iter().map(|parent| parent.title.clone()).collect(),
+            text,
+            updated_at: page.updated_at,
+        });
+    }
+    Ok(index)
+}
+
+/// Cached search index, rebuilt when the set of public pages or any of their
+/// modification times changes
+pub struct PublicKbIndex {
+    cached: RwLock<Option<(u64, Arc<Vec<IndexedPage>>)>>,
+}
+
+impl Default for PublicKbIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PublicKbIndex {
+    pub fn new() -> Self {
+        Self { cached: RwLock::new(None) }
+    }
+
+    pub(crate) fn fingerprint(pages: &[PublicDocumentationPage]) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for page in pages {
+            (page.id, page.parent_id, &page.title, &page.slug, page.updated_at).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Index for the given visible pages, from cache when they haven't changed
+    pub fn get(
+        &self,
+        conn: &mut DbConnection,
+        pages: &[PublicDocumentationPage],
+    ) -> Result<Arc<Vec<IndexedPage>>, diesel::result::Error> {
+        let fingerprint = Self::fingerprint(pages);
+        if let Some((cached_fingerprint, index)) = self.cached.read().unwrap().as_ref() {
+            if *cached_fingerprint == fingerprint {
+                return Ok(index.clone());
+            }
+        }
+
+        let index = Arc::new(build_index(conn, pages)?);
+        *self.cached.write().unwrap() = Some((fingerprint, index.clone()));
+        Ok(index)
+    }
+}
+
+/// A search result
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: i32,
+    pub key: String,
+    pub title: String,
+    pub path: Vec<String>,
+    pub snippet: String,
+    pub score: u32,
+}
+
+fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    terms.dedup();
+    terms
+}
+
+/// Search the index; every query term must appear in the title or text
+/// Title matches weigh more than body matches.
+pub fn search(index: &[IndexedPage], query: &str, limit: usize) -> Vec<SearchHit> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<SearchHit> = index
+        .iter()
+        .filter_map(|page| {
+            let title = page.title.to_lowercase();
+            let text = page.text.to_lowercase();
+            let mut score = 0u32;
+            for term in &terms {
+                let in_title = title.matches(term.as_str()).count() as u32;
+                let in_text = (text.matches(term.as_str()).count() as u32).min(20);
+                if in_title + in_text == 0 {
+                    return None;
+                }
+                score += in_title * 10 + in_text;
+            }
+            Some(SearchHit {
+                id: page.id,
+                key: page.key.clone(),
+                title: page.title.clone(),
+                path: page.path.clone(),
+                snippet: snippet(&page.text, &terms),
+                score,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    hits.truncate(limit);
+    hits
+}
+
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_LENGTH: usize = 200;
+
+/// Excerpt of the text around the first matching term
+pub(crate) fn snippet(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+
+    // Case folding can change lengths; only locate matches when it doesn't
+    let position = (lower.len() == chars.len())
+        .then(|| {
+            terms.iter().filter_map(|term| {
+                let term: Vec<char> = term.chars().collect();
+                lower.windows(term.len()).position(|window| window == term.as_slice())
+            }).min()
+        })
+        .flatten()
+        .unwrap_or(0);
+
+    let start = position.saturating_sub(SNIPPET_BEFORE);
+    let end = (start + SNIPPET_LENGTH).min(chars.len());
+    let mut excerpt: String = chars[start..end].iter().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        excerpt.insert_str(0, "… ");
+    }
+    if end < chars.len() {
+        excerpt.push_str(" …");
+    }
+    excerpt
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// sitemap.xml listing the knowledge base home and every visible page
+pub fn sitemap(base_url: &str, pages: &[PublicDocumentationPage]) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    xml.push_str(&format!("  <url><loc>{}</loc></url>\n", escape_xml(&format!("{}/kb", base_url))));
+    for page in pages {
+        xml.push_str(&format!(
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+            escape_xml(&format!("{}/kb/{}", base_url, page_key(page))),
+            page.updated_at.format("%Y-%m-%d")
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// Base URL used for absolute links (sitemap, canonical URLs)
+pub fn public_base_url() -> String {
+    std::env::var("PUBLIC_KB_URL")
+        .or_else(|_| std::env::var("FRONTEND_URL"))
+        .unwrap_or_else(|_| "http://localhost:3000".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn page(id: i32, title: &str, parent_id: Option<i32>) -> PublicDocumentationPage {
+        PublicDocumentationPage {
+            id,
+            title: title.to_string(),
+            slug: Some(title.to_lowercase()),
+            icon: None,
+            parent_id,
+            display_order: None,
+            updated_at: NaiveDate::from_ymd_opt(2026, 1, 2).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_pages_under_hidden_parents_are_hidden() {
+        // 2 is a draft (absent from the public pages), so 3 must not leak; 4 and 5 form a cycle
+        let pages = vec![
+            page(1, "Guides", None),
+            page(3, "Orphan", Some(2)),
+            page(4, "Loop", Some(5)),
+            page(5, "Back", Some(4)),
+            page(6, "Install", Some(1)),
+        ];
+        let visible: Vec<i32> = visible_pages(pages).iter().map(|page| page.id).collect();
+        assert_eq!(visible, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_nav_tree_and_breadcrumbs() {
+        let pages = vec![page(1, "Guides", None), page(2, "Install", Some(1)), page(3, "Faq", None)];
+        let tree = nav_tree(&pages);
+        assert_eq!(tree.iter().map(|node| node.title.as_str()).collect::<Vec<_>>(), vec!["Faq", "Guides"]);
+        assert_eq!(tree[1].children[0].key, "install");
+
+        let trail = breadcrumbs(&pages, &pages[1]);
+        assert_eq!(trail.iter().map(|page| page.id).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(find_page(&pages, "2").map(|page| page.id), Some(2));
+    }
+
+    #[test]
+    fn test_search_ranks_title_matches() {
+        let index = vec![
+            IndexedPage {
+                id: 1,
+                key: "vpn".to_string(),
+                title: "VPN setup".to_string(),
+                path: vec![],
+                text: "Install the client.".to_string(),
+                updated_at: page(1, "x", None).updated_at,
+            },
+            IndexedPage {
+                id: 2,
+                key: "printers".to_string(),
+                title: "Printers".to_string(),
+                path: vec![],
+                text: "Printing works over the VPN only when the VPN client is connected.".to_string(),
+                updated_at: page(2, "x", None).updated_at,
+            },
+        ];
+        let hits = search(&index, "vpn", 10);
+        assert_eq!(hits.iter().map(|hit| hit.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(hits[1].snippet.contains("VPN"));
+        assert!(search(&index, "vpn printer missing", 10).is_empty());
+    }
+
+    #[test]
+    fn test_sitemap_escapes_urls() {
+        let xml = sitemap("https://help.example.com/", &[page(1, "A&B", None)]);
+        assert!(xml.contains("<loc>https://help.example.com/kb</loc>"));
+        assert!(xml.contains("<loc>https://help.example.com/kb/a&amp;b</loc><lastmod>2026-01-02</lastmod>"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Recycle Bin Service
+//!
+//! Deleting a ticket, comment, attachment, device, project or documentation page only marks it
+//! with `deleted_at`: it disappears from the app but stays in the recycle bin, where an
+//! administrator can restore it. A background job permanently deletes anything that has been
+//! in the bin longer than the retention period and removes the files of purged tickets,
+//! comments and attachments from storage.
+//!
+//! - `RECYCLE_BIN_RETENTION_DAYS` - days before deleted records are purged (default 30)
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{DbConnection, Pool};
+use crate::models::{DocumentationPage, RecycleBinItem, RecycleBinItemType};
+use crate::repository::recycle_bin as recycle_bin_repo;
+use crate::utils::storage::Storage;
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Longest comment excerpt shown as the title of a deleted comment
+const EXCERPT_LENGTH: usize = 80;
+
+/// Purge order: a ticket's purge takes its comments and attachments with it
+const PURGE_ORDER: [RecycleBinItemType; 6] = [
+    RecycleBinItemType::Ticket,
+    RecycleBinItemType::Comment,
+    RecycleBinItemType::Attachment,
+    RecycleBinItemType::Device,
+    RecycleBinItemType::Project,
+    RecycleBinItemType::DocumentationPage,
+];
+
+#[derive(Debug)]
+pub enum RecycleBinError {
+    NotFound,
+    /// The record belongs to another record that is still in the recycle bin
+    ParentDeleted(RecycleBinItemType, i32),
+    Database(diesel::result::Error),
+}
+
+impl std::fmt::Display for RecycleBinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecycleBinError::NotFound => write!(f, "Item not found in the recycle bin"),
+            RecycleBinError::ParentDeleted(item_type, id) => {
+                write!(f, "Restore the {} {} it belongs to first", label(*item_type), id)
+            }
+            RecycleBinError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for RecycleBinError {
+    fn from(e: diesel::result::Error) -> Self {
+        RecycleBinError::Database(e)
+    }
+}
+
+fn label(item_type: RecycleBinItemType) -> &'static str {
+    match item_type {
+        RecycleBinItemType::Ticket => "ticket",
+        RecycleBinItemType::Comment => "comment",
+        RecycleBinItemType::Attachment => "attachment",
+        RecycleBinItemType::Device => "device",
+        RecycleBinItemType::Project => "project",
+        RecycleBinItemType::DocumentationPage => "page",
+    }
+}
+
+/// How long deleted records stay in the recycle bin
+pub fn retention() -> Duration {
+    let days = std::env::var("RECYCLE_BIN_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|days| *days >= 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    Duration::days(days)
+}
+
+fn excerpt(content: &str) -> String {
+    let text = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(EXCERPT_LENGTH) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text,
+    }
+}
+
+/// Deleted pages that aren't inside another page deleted at the same time
+fn deleted_page_roots(pages: Vec<DocumentationPage>) -> Vec<DocumentationPage> {
+    let deleted: HashMap<i32, Option<NaiveDateTime>> = pages.iter().map(|page| (page.id, page.deleted_at)).collect();
+    pages
+        .into_iter()
+        .filter(|page| {
+            page.parent_id
+                .and_then(|parent_id| deleted.get(&parent_id))
+                .is_none_or(|parent_deleted_at| *parent_deleted_at != page.deleted_at)
+        })
+        .collect()
+}
+
+/// Everything in the recycle bin, most recently deleted first
+///
+/// Records deleted along with another one (a comment's attachments, a page's subpages) are
+/// listed under it rather than on their own.
+pub fn list_items(conn: &mut DbConnection) -> Result<Vec<RecycleBinItem>, diesel::result::Error> {
+    let retention = retention();
+    let mut items = Vec::new();
+    let mut push = |item_type, id, title: String, ticket_id, deleted_at: Option<NaiveDateTime>, deleted_by| {
+        if let Some(deleted_at) = deleted_at {
+            items.push(RecycleBinItem {
+                item_type,
+                id,
+                title,
+                ticket_id,
+                deleted_at,
+                deleted_by,
+                purge_at: deleted_at + retention,
+            });
+        }
+    };
+
+    for ticket in recycle_bin_repo::get_deleted_tickets(conn)? {
+        push(RecycleBinItemType::Ticket, ticket.id, ticket.title, None, ticket.deleted_at, ticket.deleted_by);
+    }
+    for comment in recycle_bin_repo::get_deleted_comments(conn)? {
+        let title = excerpt(&comment.content);
+        push(RecycleBinItemType::Comment, comment.id, title, Some(comment.ticket_id), comment.deleted_at, comment.deleted_by);
+    }
+    for (attachment, ticket_id) in recycle_bin_repo::get_deleted_attachments(conn)? {
+        push(RecycleBinItemType::Attachment, attachment.id, attachment.name, ticket_id, attachment.deleted_at, attachment.deleted_by);
+    }
+    for device in recycle_bin_repo::get_deleted_devices(conn)? {
+        push(RecycleBinItemType::Device, device.id, device.name, None, device.deleted_at, device.deleted_by);
+    }
+    for project in recycle_bin_repo::get_deleted_projects(conn)? {
+        push(RecycleBinItemType::Project, project.id, project.name, None, project.deleted_at, project.deleted_by);
+    }
+    for page in deleted_page_roots(recycle_bin_repo::get_deleted_documentation_pages(conn)?) {
+        push(RecycleBinItemType::DocumentationPage, page.id, page.title, page.ticket_id, page.deleted_at, page.deleted_by);
+    }
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+    Ok(items)
+}
+
+/// Take a record out of the recycle bin
+///
+/// A comment, attachment or subpage can't come back while the record it belongs to is still
+/// deleted, since it would stay hidden.
+pub fn restore(conn: &mut DbConnection, item_type: RecycleBinItemType, id: i32) -> Result<(), RecycleBinError> {
+    if recycle_bin_repo::get_deleted_at(conn, item_type, id)?.is_none() {
+        return Err(RecycleBinError::NotFound);
+    }
+
+    let mut current = (item_type, id);
+    while let Some((parent_type, parent_id)) = recycle_bin_repo::get_parent(conn, current.0, current.1)? {
+        if recycle_bin_repo::get_deleted_at(conn, parent_type, parent_id)?.is_some() {
+            return Err(RecycleBinError::ParentDeleted(parent_type, parent_id));
+        }
+        current = (parent_type, parent_id);
+    }
+
+    recycle_bin_repo::restore_item(conn, item_type, id)?;
+    info!(item_type = label(item_type), id, "Restored from recycle bin");
+    Ok(())
+}
+
+/// Permanently delete one record, returning the storage paths of files to remove
+pub fn purge(conn: &mut DbConnection, item_type: RecycleBinItemType, id: i32) -> Result<Vec<String>, diesel::result::Error> {
+    match item_type {
+        RecycleBinItemType::Ticket => recycle_bin_repo::purge_ticket(conn, id),
+        RecycleBinItemType::Comment => recycle_bin_repo::purge_comment(conn, id),
+        RecycleBinItemType::Attachment => recycle_bin_repo::purge_attachment(conn, id),
+        RecycleBinItemType::Device => recycle_bin_repo::purge_device(conn, id).map(|_| Vec::new()),
+        RecycleBinItemType::Project => recycle_bin_repo::purge_project(conn, id).map(|_| Vec::new()),
+        RecycleBinItemType::DocumentationPage => recycle_bin_repo::purge_documentation_page(conn, id).map(|_| Vec::new()),
+    }
+}
+
+/// Permanently delete everything deleted before `cutoff`, returning the number of records
+/// purged and the storage paths of files to remove
+pub fn purge_expired(conn: &mut DbConnection, cutoff: NaiveDateTime) -> Result<(usize, Vec<String>), diesel::result::Error> {
+    let mut purged = 0;
+    let mut paths = Vec::new();
+
+    for item_type in PURGE_ORDER {
+        for id in recycle_bin_repo::get_expired_ids(conn, item_type, cutoff)? {
+            match purge(conn, item_type, id) {
+                Ok(files) => {
+                    purged += 1;
+                    paths.extend(files);
+                }
+                Err(e) => error!(item_type = label(item_type), id, error = ?e, "Failed to purge from recycle bin"),
+            }
+        }
+    }
+
+    Ok((purged, paths))
+}
+
+/// Remove purged records' files from storage
+pub async fn delete_files(storage: &dyn Storage, paths: Vec<String>) {
+    for path in paths {
+        if let Err(e) = storage.delete_file(&path).await {
+            warn!(path, error = ?e, "Failed to delete file of purged record");
+        }
+    }
+}
+
+/// Start the background job that empties expired records from the recycle bin every hour
+pub fn spawn_purge_job(pool: Pool, storage: Arc<dyn Storage>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let purge_pool = pool.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<(usize, Vec<String>), String> {
+                let mut conn = purge_pool.get().map_err(|e| e.to_string())?;
+                let cutoff = Utc::now().naive_utc() - retention();
+                purge_expired(&mut conn, cutoff).map_err(|e| e.to_string())
+            })
+            .await;
+
+            match result {
+                Ok(Ok((0, _))) => debug!("Recycle bin: nothing to purge"),
+                Ok(Ok((purged, paths))) => {
+                    info!(purged, files = paths.len(), "Purged expired records from the recycle bin");
+                    delete_files(storage.as_ref(), paths).await;
+                }
+                Ok(Err(e)) => error!(error = %e, "Recycle bin purge failed"),
+                Err(e) => error!(error = ?e, "Recycle bin purge task panicked"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DocumentationStatus;
+    use uuid::Uuid;
+
+    fn page(id: i32, parent_id: Option<i32>, deleted_at: NaiveDateTime) -> DocumentationPage {
+        DocumentationPage {
+            id,
+            uuid: Uuid::new_v4(),
+            title: format!("Page {}", id),
+            slug: None,
+            icon: None,
+            cover_image: None,
+            status: DocumentationStatus::Published,
+            created_at: deleted_at,
+            updated_at: deleted_at,
+            created_by: Uuid::nil(),
+            last_edited_by: Uuid::nil(),
+            parent_id,
+            ticket_id: None,
+            display_order: None,
+            is_public: false,
+            is_template: false,
+            archived_at: None,
+            yjs_state_vector: None,
+            yjs_document: None,
+            yjs_client_id: None,
+            has_unsaved_changes: false,
+            deleted_at: Some(deleted_at),
+            deleted_by: None,
+        }
+    }
+
+    #[test]
+    fn test_deleted_page_roots() {
+        let first = Utc::now().naive_utc();
+        let later = first + Duration::minutes(5);
+        // 2 was deleted with 1; 3 was deleted on its own before 1; 5's parent is live
+        let pages = vec![page(1, None, later), page(2, Some(1), later), page(3, Some(1), first), page(5, Some(4), first)];
+
+        let roots: Vec<i32> = deleted_page_roots(pages).iter().map(|page| page.id).collect();
+        assert_eq!(roots, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("  short\n comment "), "short comment");
+        let long = "word ".repeat(40);
+        let excerpt = excerpt(&long);
+        assert_eq!(excerpt.chars().count(), EXCERPT_LENGTH + 1);
+        assert!(excerpt.ends_with('…'));
+    }
+}
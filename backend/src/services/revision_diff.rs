@@ -0,0 +1,517 @@
+//! Revision Diff Service
+//!
+//! Compares two stored Yjs snapshots of a ticket note or documentation page.
+//!
+//! Both snapshots are flattened into blocks (paragraphs, headings, list items, ...) and
+//! diffed structurally. A deleted block followed by an inserted block of the same type with
+//! similar text is reported as one modified block with a word-level diff.
+//!
+//! Changes are attributed to contributors using the revisions stored between the two
+//! endpoints: a change belongs to the contributors of the revision that introduced it. When
+//! the range is too long to walk, only the combined contributor list is returned.
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::utils::yjs::{self, DocumentBlock};
+
+/// Longest revision range walked for per-change attribution
+const MAX_ATTRIBUTION_STEPS: usize = 50;
+
+/// Largest LCS table built before falling back to a plain replace
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Minimum word similarity for a delete/insert pair to count as a modification
+const MODIFIED_SIMILARITY: f64 = 0.3;
+
+/// Error type for revision diffs
+#[derive(Debug)]
+pub enum DiffError {
+    EmptyRange,
+    Undecodable(i32),
+}
+
+impl std::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffError::EmptyRange => write!(f, "No revisions in the requested range"),
+            DiffError::Undecodable(revision) => write!(f, "Revision {} could not be decoded", revision),
+        }
+    }
+}
+
+/// A stored revision as input to the diff
+pub struct RevisionSnapshot {
+    pub revision_number: i32,
+    pub created_at: NaiveDateTime,
+    pub contributors: Vec<Uuid>,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Unchanged,
+    Inserted,
+    Deleted,
+    Modified,
+}
+
+/// A run of text within a modified block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextSegment {
+    pub change: ChangeKind,
+    pub text: String,
+}
+
+/// One block of the diff, in document order
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockChange {
+    pub change: ChangeKind,
+    pub kind: String,
+    pub depth: u8,
+    pub old_index: Option<usize>,
+    pub new_index: Option<usize>,
+    /// Block text (the old text for deleted blocks, the new text otherwise)
+    pub text: String,
+    /// Word-level diff, only for modified blocks
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<TextSegment>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributed_to: Vec<Uuid>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DiffStats {
+    pub blocks_inserted: usize,
+    pub blocks_deleted: usize,
+    pub blocks_modified: usize,
+    pub words_inserted: usize,
+    pub words_deleted: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub from_created_at: NaiveDateTime,
+    pub to_created_at: NaiveDateTime,
+    pub blocks: Vec<BlockChange>,
+    pub stats: DiffStats,
+    /// Everyone who contributed to a revision after `from_revision`, up to `to_revision`
+    pub contributors: Vec<Uuid>,
+}
+
+/// Service for comparing revisions
+pub struct RevisionDiffService;
+
+impl RevisionDiffService {
+    /// Diff the first and last of `revisions` (oldest first, both endpoints included)
+    pub fn diff(revisions: &[RevisionSnapshot]) -> Result<RevisionDiff, DiffError> {
+        let (first, last) = match (revisions.first(), revisions.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(DiffError::EmptyRange),
+        };
+
+        let old_blocks = decode_blocks(first)?;
+        let new_blocks = decode_blocks(last)?;
+        let mut blocks = diff_blocks(&old_blocks, &new_blocks);
+
+        // Steps are the revisions after `from`; each carries its own contributors
+        let steps = &revisions[1..];
+        let mut contributors: Vec<Uuid> = Vec::new();
+        for step in steps {
+            for uuid in &step.contributors {
+                if !contributors.contains(uuid) {
+                    contributors.push(*uuid);
+                }
+            }
+        }
+
+        if !steps.is_empty() && steps.len() <= MAX_ATTRIBUTION_STEPS {
+            let mut snapshots = vec![old_blocks];
+            for step in &steps[..steps.len() - 1] {
+                snapshots.push(decode_blocks(step)?);
+            }
+            snapshots.push(new_blocks);
+
+            let step_contributors: Vec<Vec<Uuid>> = steps.iter().map(|step| step.contributors.clone()).collect();
+            attribute_changes(&mut blocks, &snapshots, &step_contributors);
+        }
+
+        Ok(RevisionDiff {
+            from_revision: first.revision_number,
+            to_revision: last.revision_number,
+            from_created_at: first.created_at,
+            to_created_at: last.created_at,
+            stats: diff_stats(&blocks),
+            blocks,
+            contributors,
+        })
+    }
+}
+
+fn decode_blocks(snapshot: &RevisionSnapshot) -> Result<Vec<DocumentBlock>, DiffError> {
+    if snapshot.content.is_empty() {
+        return Ok(Vec::new());
+    }
+    yjs::decode_document(&snapshot.content)
+        .map(|doc| yjs::document_blocks(&doc))
+        .ok_or(DiffError::Undecodable(snapshot.revision_number))
+}
+
+// ============================================================================
+// Sequence diff
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// LCS-based diff of two sequences; deletions come before insertions within a run
+fn diff_sequences<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+
+    if a.len().saturating_mul(b.len()) > MAX_LCS_CELLS {
+        ops.extend((0..a.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b.len()).map(|j| Op::Insert(prefix + j)));
+    } else {
+        let (n, m) = (a.len(), b.len());
+        let width = m + 1;
+        let mut table = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                table[i * width + j] = if a[i] == b[j] {
+                    table[(i + 1) * width + j + 1] + 1
+                } else {
+                    table[(i + 1) * width + j].max(table[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if a[i] == b[j] {
+                ops.push(Op::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+                ops.push(Op::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(prefix + j));
+                j += 1;
+            }
+        }
+        ops.extend((i..n).map(|i| Op::Delete(prefix + i)));
+        ops.extend((j..m).map(|j| Op::Insert(prefix + j)));
+    }
+
+    let old_tail = old.len() - suffix;
+    let new_tail = new.len() - suffix;
+    ops.extend((0..suffix).map(|k| Op::Equal(old_tail + k, new_tail + k)));
+    ops
+}
+
+/// Split text into alternating word and whitespace tokens so they join back losslessly
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_whitespace = None;
+    for (index, ch) in text.char_indices() {
+        let is_whitespace = ch.is_whitespace();
+        if in_whitespace.is_some_and(|previous| previous != is_whitespace) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        in_whitespace = Some(is_whitespace);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Word-level diff of two texts
+pub fn diff_words(old: &str, new: &str) -> Vec<TextSegment> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let mut segments: Vec<TextSegment> = Vec::new();
+
+    for op in diff_sequences(&old_tokens, &new_tokens) {
+        let (change, token) = match op {
+            Op::Equal(_, j) => (ChangeKind::Unchanged, new_tokens[j]),
+            Op::Delete(i) => (ChangeKind::Deleted, old_tokens[i]),
+            Op::Insert(j) => (ChangeKind::Inserted, new_tokens[j]),
+        };
+        match segments.last_mut() {
+            Some(last) if last.change == change => last.text.push_str(token),
+            _ => segments.push(TextSegment { change, text: token.to_string() }),
+        }
+    }
+
+    segments
+}
+
+/// Share of words two texts have in common (0.0 - 1.0)
+fn similarity(old: &str, new: &str) -> f64 {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    if old_words.is_empty() && new_words.is_empty() {
+        return 1.0;
+    }
+    let common = diff_sequences(&old_words, &new_words)
+        .iter()
+        .filter(|op| matches!(op, Op::Equal(..)))
+        .count();
+    (2 * common) as f64 / (old_words.len() + new_words.len()) as f64
+}
+
+// ============================================================================
+// Block diff
+// ============================================================================
+
+/// Structural diff of two block lists
+pub fn diff_blocks(old: &[DocumentBlock], new: &[DocumentBlock]) -> Vec<BlockChange> {
+    let mut changes = Vec::new();
+    let mut deleted: Vec<usize> = Vec::new();
+    let mut inserted: Vec<usize> = Vec::new();
+
+    for op in diff_sequences(old, new) {
+        match op {
+            Op::Delete(i) => deleted.push(i),
+            Op::Insert(j) => inserted.push(j),
+            Op::Equal(i, j) => {
+                flush_run(old, new, &mut deleted, &mut inserted, &mut changes);
+                changes.push(BlockChange {
+                    change: ChangeKind::Unchanged,
+                    kind: new[j].kind.clone(),
+                    depth: new[j].depth,
+                    old_index: Some(i),
+                    new_index: Some(j),
+                    text: new[j].text.clone(),
+                    segments: Vec::new(),
+                    attributed_to: Vec::new(),
+                });
+            }
+        }
+    }
+    flush_run(old, new, &mut deleted, &mut inserted, &mut changes);
+
+    changes
+}
+
+/// Emit a run of deletions and insertions, pairing similar blocks as modifications
+fn flush_run(
+    old: &[DocumentBlock],
+    new: &[DocumentBlock],
+    deleted: &mut Vec<usize>,
+    inserted: &mut Vec<usize>,
+    changes: &mut Vec<BlockChange>,
+) {
+    for k in 0..deleted.len().max(inserted.len()) {
+        let old_block = deleted.get(k).map(|&i| (i, &old[i]));
+        let new_block = inserted.get(k).map(|&j| (j, &new[j]));
+
+        if let (Some((i, before)), Some((j, after))) = (old_block, new_block) {
+            if before.kind == after.kind && similarity(&before.text, &after.text) >= MODIFIED_SIMILARITY {
+                changes.push(BlockChange {
+                    change: ChangeKind::Modified,
+                    kind: after.kind.clone(),
+                    depth: after.depth,
+                    old_index: Some(i),
+                    new_index: Some(j),
+                    text: after.text.clone(),
+                    segments: diff_words(&before.text, &after.text),
+                    attributed_to: Vec::new(),
+                });
+                continue;
+            }
+        }
+
+        if let Some((i, before)) = old_block {
+            changes.push(BlockChange {
+                change: ChangeKind::Deleted,
+                kind: before.kind.clone(),
+                depth: before.depth,
+                old_index: Some(i),
+                new_index: None,
+                text: before.text.clone(),
+                segments: Vec::new(),
+                attributed_to: Vec::new(),
+            });
+        }
+        if let Some((j, after)) = new_block {
+            changes.push(BlockChange {
+                change: ChangeKind::Inserted,
+                kind: after.kind.clone(),
+                depth: after.depth,
+                old_index: None,
+                new_index: Some(j),
+                text: after.text.clone(),
+                segments: Vec::new(),
+                attributed_to: Vec::new(),
+            });
+        }
+    }
+
+    deleted.clear();
+    inserted.clear();
+}
+
+fn diff_stats(changes: &[BlockChange]) -> DiffStats {
+    let mut stats = DiffStats::default();
+    for change in changes {
+        match change.change {
+            ChangeKind::Unchanged => {}
+            ChangeKind::Inserted => {
+                stats.blocks_inserted += 1;
+                stats.words_inserted += change.text.split_whitespace().count();
+            }
+            ChangeKind::Deleted => {
+                stats.blocks_deleted += 1;
+                stats.words_deleted += change.text.split_whitespace().count();
+            }
+            ChangeKind::Modified => {
+                stats.blocks_modified += 1;
+                for segment in &change.segments {
+                    let words = segment.text.split_whitespace().count();
+                    match segment.change {
+                        ChangeKind::Inserted => stats.words_inserted += words,
+                        ChangeKind::Deleted => stats.words_deleted += words,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    stats
+}
+
+// ============================================================================
+// Attribution
+// ============================================================================
+
+/// Attribute each change to the contributors of the revision step that introduced it
+///
+/// `snapshots` holds the blocks of every revision in the range (oldest first) and
+/// `step_contributors[i]` the contributors of the step from `snapshots[i]` to `snapshots[i + 1]`.
+/// An inserted or modified block belongs to the last step where its final text appeared; a
+/// deleted block to the last step where its text disappeared.
+pub fn attribute_changes(
+    changes: &mut [BlockChange],
+    snapshots: &[Vec<DocumentBlock>],
+    step_contributors: &[Vec<Uuid>],
+) {
+    let sets: Vec<HashSet<(&str, u8, &str)>> = snapshots
+        .iter()
+        .map(|blocks| blocks.iter().map(|b| (b.kind.as_str(), b.depth, b.text.as_str())).collect())
+        .collect();
+
+    for change in changes.iter_mut() {
+        let key = (change.kind.as_str(), change.depth, change.text.as_str());
+        let appears = |step: usize| !sets[step].contains(&key) && sets[step + 1].contains(&key);
+        let disappears = |step: usize| sets[step].contains(&key) && !sets[step + 1].contains(&key);
+
+        let step = match change.change {
+            ChangeKind::Unchanged => None,
+            ChangeKind::Inserted | ChangeKind::Modified => (0..step_contributors.len()).rev().find(|&s| appears(s)),
+            ChangeKind::Deleted => (0..step_contributors.len()).rev().find(|&s| disappears(s)),
+        };
+
+        if let Some(step) = step {
+            change.attributed_to = step_contributors[step].clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(kind: &str, text: &str) -> DocumentBlock {
+        DocumentBlock { kind: kind.to_string(), depth: 0, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_diff_words() {
+        let segments = diff_words("the quick fox", "the slow fox");
+        assert_eq!(
+            segments,
+            vec![
+                TextSegment { change: ChangeKind::Unchanged, text: "the ".to_string() },
+                TextSegment { change: ChangeKind::Deleted, text: "quick".to_string() },
+                TextSegment { change: ChangeKind::Inserted, text: "slow".to_string() },
+                TextSegment { change: ChangeKind::Unchanged, text: " fox".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_blocks_insert_and_delete() {
+        let old = vec![block("paragraph", "keep"), block("paragraph", "remove me")];
+        let new = vec![block("heading", "New title"), block("paragraph", "keep")];
+        let changes = diff_blocks(&old, &new);
+
+        let kinds: Vec<ChangeKind> = changes.iter().map(|c| c.change).collect();
+        assert_eq!(kinds, vec![ChangeKind::Inserted, ChangeKind::Unchanged, ChangeKind::Deleted]);
+
+        let stats = diff_stats(&changes);
+        assert_eq!(stats.blocks_inserted, 1);
+        assert_eq!(stats.blocks_deleted, 1);
+        assert_eq!(stats.words_inserted, 2);
+    }
+
+    #[test]
+    fn test_similar_blocks_are_modified() {
+        let old = vec![block("paragraph", "restart the print spooler service")];
+        let new = vec![block("paragraph", "restart the print spooler service twice")];
+        let changes = diff_blocks(&old, &new);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change, ChangeKind::Modified);
+        assert_eq!(diff_stats(&changes).words_inserted, 1);
+    }
+
+    #[test]
+    fn test_unrelated_replacement_is_not_modified() {
+        let old = vec![block("paragraph", "alpha beta gamma")];
+        let new = vec![block("paragraph", "something else entirely")];
+        let kinds: Vec<ChangeKind> = diff_blocks(&old, &new).iter().map(|c| c.change).collect();
+        assert_eq!(kinds, vec![ChangeKind::Deleted, ChangeKind::Inserted]);
+    }
+
+    #[test]
+    fn test_attribution_follows_introducing_step() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let snapshots = vec![
+            vec![block("paragraph", "intro")],
+            vec![block("paragraph", "intro"), block("paragraph", "from alice")],
+            vec![block("paragraph", "from alice"), block("paragraph", "from bob")],
+        ];
+        let mut changes = diff_blocks(&snapshots[0], &snapshots[2]);
+        attribute_changes(&mut changes, &snapshots, &[vec![alice], vec![bob]]);
+
+        let by_text = |text: &str| changes.iter().find(|c| c.text == text).unwrap().attributed_to.clone();
+        assert_eq!(by_text("from alice"), vec![alice]);
+        assert_eq!(by_text("from bob"), vec![bob]);
+        // "intro" was removed in bob's step
+        assert_eq!(by_text("intro"), vec![bob]);
+    }
+}
@@ -0,0 +1,256 @@
+//! Revision Retention Service
+//!
+//! Thins old snapshots in `article_content_revisions` and `documentation_revisions` so the
+//! Yjs snapshot tables don't grow without bound.
+//!
+//! Every revision from the last hour is kept. Older revisions are reduced to the newest one per
+//! hour for the first day, then the newest one per day for the daily window (30 days by default),
+//! then the newest one per week. Weekly revisions are kept indefinitely unless a weekly window
+//! is configured, past which they are removed. The newest revision of a document is never removed.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use tracing::{debug, error, info};
+
+use crate::db::DbConnection;
+use crate::repository;
+
+/// How long each thinning tier lasts
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Revisions younger than this are always kept
+    pub keep_all: Duration,
+    /// Up to this age, keep the newest revision per hour
+    pub hourly: Duration,
+    /// Up to this age, keep the newest revision per day; beyond it, per week
+    pub daily: Duration,
+    /// Up to this age, keep the newest revision per week; beyond it, none.
+    /// `None` keeps weekly revisions indefinitely.
+    pub weekly: Option<Duration>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_all: Duration::hours(1),
+            hourly: Duration::days(1),
+            daily: Duration::days(30),
+            weekly: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Load the policy from environment variables, falling back to the defaults
+    ///
+    /// - `REVISION_RETENTION_HOURLY_HOURS` - hourly tier length (default 24)
+    /// - `REVISION_RETENTION_DAILY_DAYS` - daily tier length (default 30)
+    /// - `REVISION_RETENTION_WEEKLY_DAYS` - weekly tier length (default unset: keep indefinitely)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let hourly = std::env::var("REVISION_RETENTION_HOURLY_HOURS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .map(Duration::hours)
+            .unwrap_or(defaults.hourly);
+        let daily = std::env::var("REVISION_RETENTION_DAILY_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .map(Duration::days)
+            .unwrap_or(defaults.daily);
+        let daily = daily.max(hourly);
+        let weekly = std::env::var("REVISION_RETENTION_WEEKLY_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .map(|days| Duration::days(days).max(daily));
+
+        Self {
+            keep_all: defaults.keep_all,
+            hourly: hourly.max(defaults.keep_all),
+            daily,
+            weekly,
+        }
+    }
+}
+
+/// Summary of a retention run
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub article_revisions_deleted: usize,
+    pub documentation_revisions_deleted: usize,
+}
+
+/// Service for thinning revision history
+pub struct RevisionRetentionService;
+
+impl RevisionRetentionService {
+    /// Apply the retention policy to every ticket article and documentation page
+    pub fn prune_all(conn: &mut DbConnection, policy: &RetentionPolicy) -> Result<RetentionReport, diesel::result::Error> {
+        let now = Utc::now().naive_utc();
+        let mut report = RetentionReport::default();
+
+        for article_content_id in repository::article_content::get_article_content_ids_with_revision_history(conn)? {
+            let revisions = repository::article_content::get_article_content_revision_times(conn, article_content_id)?;
+            let prune = revisions_to_prune(&revisions, now, policy);
+            if prune.is_empty() {
+                continue;
+            }
+            match repository::article_content::delete_article_content_revisions(conn, &prune) {
+                Ok(deleted) => report.article_revisions_deleted += deleted,
+                Err(e) => error!(article_content_id, error = ?e, "Failed to prune article revisions"),
+            }
+        }
+
+        for page_id in repository::documentation::get_page_ids_with_revision_history(conn)? {
+            let revisions = repository::documentation::get_documentation_revision_times(conn, page_id)?;
+            let prune = revisions_to_prune(&revisions, now, policy);
+            if prune.is_empty() {
+                continue;
+            }
+            match repository::documentation::delete_documentation_revisions(conn, &prune) {
+                Ok(deleted) => report.documentation_revisions_deleted += deleted,
+                Err(e) => error!(page_id, error = ?e, "Failed to prune documentation revisions"),
+            }
+        }
+
+        if report.article_revisions_deleted + report.documentation_revisions_deleted > 0 {
+            info!(
+                article = report.article_revisions_deleted,
+                documentation = report.documentation_revisions_deleted,
+                "Pruned old revisions"
+            );
+        } else {
+            debug!("Revision retention: nothing to prune");
+        }
+
+        Ok(report)
+    }
+}
+
+/// Pick the revisions to delete from one document's history
+///
+/// `revisions` holds `(id, created_at)` pairs in any order. Within each time bucket the
+/// newest revision survives; the newest revision overall always survives.
+pub fn revisions_to_prune(
+    revisions: &[(i32, NaiveDateTime)],
+    now: NaiveDateTime,
+    policy: &RetentionPolicy,
+) -> Vec<i32> {
+    let mut sorted: Vec<&(i32, NaiveDateTime)> = revisions.iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+
+    let mut seen_buckets: HashSet<(u8, i64)> = HashSet::new();
+    let mut prune = Vec::new();
+
+    for (index, (id, created_at)) in sorted.into_iter().enumerate() {
+        let age = now - *created_at;
+        if age < policy.keep_all {
+            continue;
+        }
+
+        // Past the weekly window only the newest revision survives
+        if policy.weekly.is_some_and(|weekly| age >= weekly) {
+            if index > 0 {
+                prune.push(*id);
+            }
+            continue;
+        }
+
+        let timestamp = created_at.and_utc().timestamp();
+        let bucket = if age < policy.hourly {
+            (0, timestamp.div_euclid(3600))
+        } else if age < policy.daily {
+            (1, timestamp.div_euclid(86_400))
+        } else {
+            (2, timestamp.div_euclid(7 * 86_400))
+        };
+
+        // Sorted newest first, so the first revision seen in a bucket is the one to keep
+        // (the newest revision overall always comes first)
+        if !seen_buckets.insert(bucket) {
+            prune.push(*id);
+        }
+    }
+
+    prune
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 15).unwrap().and_hms_opt(12, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn test_recent_revisions_are_kept() {
+        let revisions: Vec<_> = (0..10).map(|i| (i, now() - Duration::minutes(i as i64 * 5))).collect();
+        assert!(revisions_to_prune(&revisions, now(), &RetentionPolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_hourly_thinning_keeps_newest_per_hour() {
+        // Three revisions in the 09:00 hour, three hours ago
+        let revisions = vec![
+            (1, now() - Duration::minutes(200)), // 09:10
+            (2, now() - Duration::minutes(190)), // 09:20
+            (3, now() - Duration::minutes(180)), // 09:30
+            (4, now()),
+        ];
+        let mut pruned = revisions_to_prune(&revisions, now(), &RetentionPolicy::default());
+        pruned.sort();
+        assert_eq!(pruned, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_daily_thinning_keeps_newest_per_day() {
+        let day = now() - Duration::days(5);
+        let revisions = vec![
+            (1, day - Duration::hours(3)),
+            (2, day - Duration::hours(2)),
+            (3, day),
+            (4, now()),
+        ];
+        let mut pruned = revisions_to_prune(&revisions, now(), &RetentionPolicy::default());
+        pruned.sort();
+        assert_eq!(pruned, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_newest_revision_is_never_pruned() {
+        // A single old revision and a duplicate in the same bucket
+        let old = now() - Duration::days(90);
+        let revisions = vec![(1, old - Duration::minutes(5)), (2, old)];
+        assert_eq!(revisions_to_prune(&revisions, now(), &RetentionPolicy::default()), vec![1]);
+    }
+
+    #[test]
+    fn test_weekly_beyond_daily_window() {
+        let week_start = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let revisions = vec![
+            (1, week_start + Duration::hours(1)),
+            (2, week_start + Duration::days(2)),
+            (3, now()),
+        ];
+        // Both fall in the same weekly bucket, only the newer survives
+        assert_eq!(revisions_to_prune(&revisions, now(), &RetentionPolicy::default()), vec![1]);
+    }
+
+    #[test]
+    fn test_revisions_past_weekly_window_are_pruned() {
+        let policy = RetentionPolicy { weekly: Some(Duration::days(365)), ..RetentionPolicy::default() };
+        let revisions = vec![
+            (1, now() - Duration::days(400)),
+            (2, now() - Duration::days(200)),
+            (3, now()),
+        ];
+        assert_eq!(revisions_to_prune(&revisions, now(), &policy), vec![1]);
+
+        // Even past the window, a document's newest revision stays
+        let revisions = vec![(1, now() - Duration::days(500)), (2, now() - Duration::days(400))];
+        assert_eq!(revisions_to_prune(&revisions, now(), &policy), vec![1]);
+    }
+}
@@ -0,0 +1,711 @@
+//! Selective Restore Service
+//!
+//! Restores individual tickets or documentation subtrees from a backup archive, leaving the
+//! rest of the database alone.
+//!
+//! - A ticket comes back with its comments, attachments, device links, collaborative content
+//!   (article contents and revisions), ticket links, problem/change details, project
+//!   membership and files.
+//! - A documentation page comes back with its subpages and revision history.
+//!
+//! Records keep their original ids unless the id is taken in the current database, in which
+//! case they get a new one and everything restored alongside them is pointed at it.
+//! References to records that no longer exist are cleared; required user references fall
+//! back to the admin doing the restore. Each such change is reported as a warning.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{Array, Bool, Int4, Text};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use crate::db::DbConnection;
+use crate::models::{RestorableEntities, RestorablePage, RestorableTicket, RestoredEntity, SelectiveRestoreReport};
+use crate::services::backup::{
+    quote_ident, reset_sequences, restore_backup_files_matching, scan_table, table_columns, BackupError,
+};
+use crate::utils::storage::Storage;
+
+type Row = Map<String, Value>;
+
+/// Most tickets/pages listed by a restore preview
+const MAX_LISTED_ENTITIES: usize = 5000;
+
+/// User references per table: (column, required)
+const USER_COLUMNS: &[(&str, &[(&str, bool)])] = &[
+    ("tickets", &[("requester_uuid", false), ("assignee_uuid", false), ("created_by", false), ("closed_by", false)]),
+    ("comments", &[("user_uuid", true)]),
+    ("attachments", &[("uploaded_by", false)]),
+    ("devices", &[("created_by", false), ("primary_user_uuid", false)]),
+    ("ticket_devices", &[("created_by", false)]),
+    ("article_contents", &[("created_by", false), ("updated_by", false)]),
+    ("linked_tickets", &[("created_by", false)]),
+    ("change_details", &[("approved_by", false)]),
+    ("project_tickets", &[("created_by", false)]),
+    ("documentation_pages", &[("created_by", true), ("last_edited_by", true)]),
+    ("documentation_revisions", &[("created_by", true)]),
+];
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = Int4)]
+    id: i32,
+}
+
+#[derive(QueryableByName)]
+struct ExistsRow {
+    #[diesel(sql_type = Bool)]
+    exists: bool,
+}
+
+fn int(row: &Row, column: &str) -> Option<i32> {
+    row.get(column).and_then(Value::as_i64).map(|value| value as i32)
+}
+
+fn text(row: &Row, column: &str) -> Option<String> {
+    row.get(column).and_then(Value::as_str).map(str::to_string)
+}
+
+fn in_set(row: &Row, column: &str, set: &HashSet<i32>) -> bool {
+    int(row, column).is_some_and(|value| set.contains(&value))
+}
+
+fn ids(rows: &[Row]) -> HashSet<i32> {
+    rows.iter().filter_map(|row| int(row, "id")).collect()
+}
+
+/// Rows of an archived table that `keep` accepts
+fn collect_rows<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    table_name: &str,
+    keep: impl Fn(&Row) -> bool,
+) -> Result<Vec<Row>, BackupError> {
+    let mut rows = Vec::new();
+    scan_table(archive, table_name, |value| {
+        if let Value::Object(row) = value {
+            if keep(&row) {
+                rows.push(row);
+            }
+        }
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Which of `ids` exist in a table now
+fn existing_ids(conn: &mut DbConnection, table_name: &str, ids: &[i32]) -> QueryResult<HashSet<i32>> {
+    let rows: Vec<IdRow> = sql_query(format!("SELECT id FROM {} WHERE id = ANY($1)", table_name))
+        .bind::<Array<Int4>, _>(ids.to_vec())
+        .load(conn)?;
+    Ok(rows.into_iter().map(|row| row.id).collect())
+}
+
+/// Tickets and documentation pages in an archive, newest first
+pub fn list_restorable_entities(backup_path: &Path) -> Result<RestorableEntities, BackupError> {
+    let mut archive = ZipArchive::new(File::open(backup_path)?)?;
+
+    let mut tickets = Vec::new();
+    scan_table(&mut archive, "tickets", |value| {
+        if let Some((row, id)) = value.as_object().and_then(|row| Some((row, int(row, "id")?))) {
+            tickets.push(RestorableTicket {
+                id,
+                title: text(row, "title").unwrap_or_default(),
+                status: text(row, "status"),
+                updated_at: text(row, "updated_at"),
+                id_in_use: false,
+            });
+        }
+        Ok(())
+    })?;
+
+    let mut documentation_pages = Vec::new();
+    scan_table(&mut archive, "documentation_pages", |value| {
+        if let Some((row, id)) = value.as_object().and_then(|row| Some((row, int(row, "id")?))) {
+            documentation_pages.push(RestorablePage {
+                id,
+                title: text(row, "title").unwrap_or_default(),
+                parent_id: int(row, "parent_id"),
+                id_in_use: false,
+            });
+        }
+        Ok(())
+    })?;
+
+    tickets.sort_by_key(|ticket| std::cmp::Reverse(ticket.id));
+    documentation_pages.sort_by_key(|page| std::cmp::Reverse(page.id));
+    let truncated = tickets.len() > MAX_LISTED_ENTITIES || documentation_pages.len() > MAX_LISTED_ENTITIES;
+    tickets.truncate(MAX_LISTED_ENTITIES);
+    documentation_pages.truncate(MAX_LISTED_ENTITIES);
+
+    Ok(RestorableEntities {
+        tickets,
+        documentation_pages,
+        truncated,
+    })
+}
+
+/// Flag listed entities whose ids are taken in the current database
+pub fn mark_ids_in_use(conn: &mut DbConnection, entities: &mut RestorableEntities) -> QueryResult<()> {
+    let ticket_ids: Vec<i32> = entities.tickets.iter().map(|ticket| ticket.id).collect();
+    let taken = existing_ids(conn, "tickets", &ticket_ids)?;
+    for ticket in &mut entities.tickets {
+        ticket.id_in_use = taken.contains(&ticket.id);
+    }
+
+    let page_ids: Vec<i32> = entities.documentation_pages.iter().map(|page| page.id).collect();
+    let taken = existing_ids(conn, "documentation_pages", &page_ids)?;
+    for page in &mut entities.documentation_pages {
+        page.id_in_use = taken.contains(&page.id);
+    }
+    Ok(())
+}
+
+/// What to restore
+pub struct RestoreSelection {
+    pub tickets: Vec<i32>,
+    pub documentation_pages: Vec<i32>,
+    pub include_subpages: bool,
+    /// Stands in for required user references to users that no longer exist
+    pub acting_user: Uuid,
+}
+
+/// Rows read from the archive for a selection, per table
+#[derive(Default)]
+struct SelectedRows {
+    tickets: Vec<Row>,
+    comments: Vec<Row>,
+    attachments: Vec<Row>,
+    devices: Vec<Row>,
+    ticket_devices: Vec<Row>,
+    article_contents: Vec<Row>,
+    article_content_revisions: Vec<Row>,
+    linked_tickets: Vec<Row>,
+    problem_details: Vec<Row>,
+    change_details: Vec<Row>,
+    project_tickets: Vec<Row>,
+    documentation_pages: Vec<Row>,
+    documentation_revisions: Vec<Row>,
+}
+
+/// Ids of the selected pages and, with `include_subpages`, everything below them
+fn page_subtree<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    roots: &[i32],
+    include_subpages: bool,
+) -> Result<HashSet<i32>, BackupError> {
+    let mut known = HashSet::new();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    scan_table(archive, "documentation_pages", |value| {
+        if let Some(row) = value.as_object() {
+            if let Some(id) = int(row, "id") {
+                known.insert(id);
+                if let Some(parent_id) = int(row, "parent_id") {
+                    children.entry(parent_id).or_default().push(id);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut selected = HashSet::new();
+    let mut pending: Vec<i32> = roots.iter().copied().filter(|id| known.contains(id)).collect();
+    while let Some(id) = pending.pop() {
+        if selected.insert(id) && include_subpages {
+            pending.extend(children.get(&id).into_iter().flatten().copied());
+        }
+    }
+    Ok(selected)
+}
+
+fn read_selection<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    selection: &RestoreSelection,
+) -> Result<SelectedRows, BackupError> {
+    let mut rows = SelectedRows::default();
+
+    if !selection.tickets.is_empty() {
+        let ticket_ids: HashSet<i32> = selection.tickets.iter().copied().collect();
+        rows.tickets = collect_rows(archive, "tickets", |row| in_set(row, "id", &ticket_ids))?;
+        rows.comments = collect_rows(archive, "comments", |row| in_set(row, "ticket_id", &ticket_ids))?;
+        let comment_ids = ids(&rows.comments);
+        rows.attachments = collect_rows(archive, "attachments", |row| in_set(row, "comment_id", &comment_ids))?;
+        rows.ticket_devices = collect_rows(archive, "ticket_devices", |row| in_set(row, "ticket_id", &ticket_ids))?;
+        let device_ids: HashSet<i32> = rows.ticket_devices.iter().filter_map(|row| int(row, "device_id")).collect();
+        rows.devices = collect_rows(archive, "devices", |row| in_set(row, "id", &device_ids))?;
+        rows.article_contents = collect_rows(archive, "article_contents", |row| in_set(row, "ticket_id", &ticket_ids))?;
+        let content_ids = ids(&rows.article_contents);
+        rows.article_content_revisions = collect_rows(archive, "article_content_revisions", |row| {
+            in_set(row, "article_content_id", &content_ids)
+        })?;
+        rows.linked_tickets = collect_rows(archive, "linked_tickets", |row| {
+            in_set(row, "ticket_id", &ticket_ids) || in_set(row, "linked_ticket_id", &ticket_ids)
+        })?;
+        rows.problem_details = collect_rows(archive, "problem_details", |row| in_set(row, "ticket_id", &ticket_ids))?;
+        rows.change_details = collect_rows(archive, "change_details", |row| in_set(row, "ticket_id", &ticket_ids))?;
+        rows.project_tickets = collect_rows(archive, "project_tickets", |row| in_set(row, "ticket_id", &ticket_ids))?;
+    }
+
+    if !selection.documentation_pages.is_empty() {
+        let page_ids = page_subtree(archive, &selection.documentation_pages, selection.include_subpages)?;
+        rows.documentation_pages = collect_rows(archive, "documentation_pages", |row| in_set(row, "id", &page_ids))?;
+        rows.documentation_revisions = collect_rows(archive, "documentation_revisions", |row| {
+            in_set(row, "page_id", &page_ids)
+        })?;
+    }
+
+    Ok(rows)
+}
+
+/// Inserts archived rows, tracking which ids they were restored under
+struct Restorer<'a> {
+    conn: &'a mut DbConnection,
+    acting_user: Uuid,
+    columns: HashMap<&'static str, HashSet<String>>,
+    users: HashMap<Uuid, bool>,
+    /// (table, archived id) -> restored id
+    restored: HashMap<(&'static str, i32), i32>,
+    report: SelectiveRestoreReport,
+}
+
+impl Restorer<'_> {
+    fn exists(&mut self, table_name: &str, column: &str, value: i32) -> Result<bool, BackupError> {
+        let row: ExistsRow = sql_query(format!(
+            "SELECT EXISTS(SELECT 1 FROM {} WHERE {} = $1) AS exists",
+            table_name,
+            quote_ident(column)
+        ))
+        .bind::<Int4, _>(value)
+        .get_result(self.conn)?;
+        Ok(row.exists)
+    }
+
+    fn uuid_exists(&mut self, table_name: &str, column: &str, value: Uuid) -> Result<bool, BackupError> {
+        let row: ExistsRow = sql_query(format!(
+            "SELECT EXISTS(SELECT 1 FROM {} WHERE {} = $1::uuid) AS exists",
+            table_name,
+            quote_ident(column)
+        ))
+        .bind::<Text, _>(value.to_string())
+        .get_result(self.conn)?;
+        Ok(row.exists)
+    }
+
+    /// Id a reference should use: the restored id, the same id if it still exists, or none
+    fn reference(&mut self, table_name: &'static str, value: Option<i32>) -> Result<Option<i32>, BackupError> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        if let Some(restored) = self.restored.get(&(table_name, value)) {
+            return Ok(Some(*restored));
+        }
+        Ok(self.exists(table_name, "id", value)?.then_some(value))
+    }
+
+    fn warn(&mut self, message: String) {
+        log::warn!("Selective restore: {}", message);
+        self.report.warnings.push(message);
+    }
+
+    /// Clear references to users that no longer exist; required ones fall back to the acting user
+    fn fix_user_references(&mut self, table_name: &str, row: &mut Row) -> Result<(), BackupError> {
+        let Some((_, columns)) = USER_COLUMNS.iter().find(|(table, _)| *table == table_name) else {
+            return Ok(());
+        };
+
+        for (column, required) in columns.iter() {
+            let Some(user_uuid) = row.get(*column).and_then(Value::as_str).and_then(|v| Uuid::parse_str(v).ok()) else {
+                continue;
+            };
+            let exists = match self.users.get(&user_uuid) {
+                Some(exists) => *exists,
+                None => {
+                    let exists = self.uuid_exists("users", "uuid", user_uuid)?;
+                    self.users.insert(user_uuid, exists);
+                    exists
+                }
+            };
+            if exists {
+                continue;
+            }
+
+            if *required {
+                row.insert(column.to_string(), Value::String(self.acting_user.to_string()));
+                self.warn(format!(
+                    "{} {}: user {} no longer exists, {} set to you",
+                    table_name,
+                    int(row, "id").map(|id| id.to_string()).unwrap_or_default(),
+                    user_uuid,
+                    column
+                ));
+            } else {
+                row.insert(column.to_string(), Value::Null);
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert one row, returning whether it was inserted
+    ///
+    /// Rows of tables with serial ids keep the archived id unless it's taken; the id they end
+    /// up with is recorded in `restored`.
+    fn insert(&mut self, table_name: &'static str, mut row: Row, serial: bool) -> Result<bool, BackupError> {
+        self.fix_user_references(table_name, &mut row)?;
+        // Records restored from a backup come back live, even if they were in the recycle bin
+        row.remove("deleted_at");
+        row.remove("deleted_by");
+
+        let archived_id = if serial { int(&row, "id") } else { None };
+        if let Some(id) = archived_id {
+            if self.exists(table_name, "id", id)? {
+                row.remove("id");
+            }
+        }
+
+        if !self.columns.contains_key(table_name) {
+            let columns = table_columns(self.conn, table_name)?;
+            self.columns.insert(table_name, columns);
+        }
+        let existing = &self.columns[table_name];
+        let column_list = row.keys()
+            .filter(|column| existing.contains(*column))
+            .map(|column| quote_ident(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let query = format!(
+            "INSERT INTO {table} ({columns}) SELECT {columns} FROM json_populate_record(NULL::{table}, $1::json) \
+             ON CONFLICT DO NOTHING{returning}",
+            table = table_name,
+            columns = column_list,
+            returning = if serial { " RETURNING id" } else { "" },
+        );
+        let payload = serde_json::to_string(&row)?;
+
+        let inserted = if serial {
+            let inserted: Vec<IdRow> = sql_query(query).bind::<Text, _>(payload).load(self.conn)?;
+            if let (Some(archived), Some(row)) = (archived_id, inserted.first()) {
+                self.restored.insert((table_name, archived), row.id);
+            }
+            !inserted.is_empty()
+        } else {
+            sql_query(query).bind::<Text, _>(payload).execute(self.conn)? > 0
+        };
+
+        if inserted {
+            *self.report.rows.entry(table_name.to_string()).or_insert(0) += 1;
+        }
+        Ok(inserted)
+    }
+
+    fn restore_tickets(&mut self, rows: SelectedRows) -> Result<SelectedRows, BackupError> {
+        let SelectedRows {
+            tickets,
+            comments,
+            attachments,
+            devices,
+            ticket_devices,
+            article_contents,
+            article_content_revisions,
+            linked_tickets,
+            problem_details,
+            change_details,
+            project_tickets,
+            documentation_pages,
+            documentation_revisions,
+        } = rows;
+
+        // Devices are shared between tickets: link to the current device if it still exists
+        for row in devices {
+            let Some(id) = int(&row, "id") else { continue };
+            if self.exists("devices", "id", id)? {
+                self.restored.insert(("devices", id), id);
+            } else {
+                self.insert("devices", row, true)?;
+            }
+        }
+
+        for mut row in tickets {
+            let Some(archived_id) = int(&row, "id") else { continue };
+            let merged_into = self.reference("tickets", int(&row, "merged_into_id"))?;
+            row.insert("merged_into_id".to_string(), merged_into.into());
+            if let Some(category_id) = int(&row, "category_id") {
+                if !self.exists("ticket_categories", "id", category_id)? {
+                    row.insert("category_id".to_string(), Value::Null);
+                    self.warn(format!("Ticket #{}: category {} no longer exists", archived_id, category_id));
+                }
+            }
+
+            if self.insert("tickets", row, true)? {
+                let restored_id = self.restored[&("tickets", archived_id)];
+                self.report.tickets.push(RestoredEntity { original_id: archived_id, restored_id });
+            } else {
+                self.warn(format!("Ticket #{} could not be restored", archived_id));
+            }
+        }
+
+        // Ticket each archived comment belonged to, for attachment URLs
+        let mut comment_tickets = HashMap::new();
+        for mut row in comments {
+            let Some(ticket_id) = int(&row, "ticket_id") else { continue };
+            let Some(&restored_ticket) = self.restored.get(&("tickets", ticket_id)) else { continue };
+            if let Some(id) = int(&row, "id") {
+                comment_tickets.insert(id, ticket_id);
+            }
+            row.insert("ticket_id".to_string(), restored_ticket.into());
+            self.insert("comments", row, true)?;
+        }
+
+        for mut row in attachments {
+            let Some(comment_id) = int(&row, "comment_id") else { continue };
+            let Some(&restored_comment) = self.restored.get(&("comments", comment_id)) else { continue };
+            row.insert("comment_id".to_string(), restored_comment.into());
+
+            // Files move with a remapped ticket (see `restore_selected_files`)
+            if let Some(&ticket_id) = comment_tickets.get(&comment_id) {
+                let restored_ticket = self.restored[&("tickets", ticket_id)];
+                if let (true, Some(url)) = (restored_ticket != ticket_id, text(&row, "url")) {
+                    let from = format!("/uploads/tickets/{}/", ticket_id);
+                    if let Some(rest) = url.strip_prefix(&from) {
+                        row.insert("url".to_string(), format!("/uploads/tickets/{}/{}", restored_ticket, rest).into());
+                    }
+                }
+            }
+            self.insert("attachments", row, true)?;
+        }
+
+        for mut row in ticket_devices {
+            let Some(&ticket_id) = int(&row, "ticket_id").and_then(|id| self.restored.get(&("tickets", id))) else {
+                continue;
+            };
+            let Some(&device_id) = int(&row, "device_id").and_then(|id| self.restored.get(&("devices", id))) else {
+                continue;
+            };
+            row.insert("ticket_id".to_string(), ticket_id.into());
+            row.insert("device_id".to_string(), device_id.into());
+            self.insert("ticket_devices", row, false)?;
+        }
+
+        for mut row in article_contents {
+            let Some(&ticket_id) = int(&row, "ticket_id").and_then(|id| self.restored.get(&("tickets", id))) else {
+                continue;
+            };
+            row.insert("ticket_id".to_string(), ticket_id.into());
+            self.insert("article_contents", row, true)?;
+        }
+
+        for mut row in article_content_revisions {
+            let Some(&content_id) = int(&row, "article_content_id")
+                .and_then(|id| self.restored.get(&("article_contents", id)))
+            else {
+                continue;
+            };
+            row.insert("article_content_id".to_string(), content_id.into());
+            self.insert("article_content_revisions", row, true)?;
+        }
+
+        for mut row in linked_tickets {
+            let ticket_id = self.reference("tickets", int(&row, "ticket_id"))?;
+            let linked_ticket_id = self.reference("tickets", int(&row, "linked_ticket_id"))?;
+            let (Some(ticket_id), Some(linked_ticket_id)) = (ticket_id, linked_ticket_id) else {
+                self.warn(format!(
+                    "Link between tickets #{} and #{} skipped: the other ticket no longer exists",
+                    int(&row, "ticket_id").unwrap_or_default(),
+                    int(&row, "linked_ticket_id").unwrap_or_default()
+                ));
+                continue;
+            };
+            row.insert("ticket_id".to_string(), ticket_id.into());
+            row.insert("linked_ticket_id".to_string(), linked_ticket_id.into());
+            self.insert("linked_tickets", row, false)?;
+        }
+
+        for (table_name, rows) in [("problem_details", problem_details), ("change_details", change_details)] {
+            for mut row in rows {
+                let Some(&ticket_id) = int(&row, "ticket_id").and_then(|id| self.restored.get(&("tickets", id))) else {
+                    continue;
+                };
+                row.insert("ticket_id".to_string(), ticket_id.into());
+                self.insert(table_name, row, false)?;
+            }
+        }
+
+        for mut row in project_tickets {
+            let Some(&ticket_id) = int(&row, "ticket_id").and_then(|id| self.restored.get(&("tickets", id))) else {
+                continue;
+            };
+            let Some(project_id) = int(&row, "project_id") else { continue };
+            if !self.exists("projects", "id", project_id)? {
+                self.warn(format!("Ticket #{}: project {} no longer exists", ticket_id, project_id));
+                continue;
+            }
+            row.insert("ticket_id".to_string(), ticket_id.into());
+            self.insert("project_tickets", row, false)?;
+        }
+
+        Ok(SelectedRows {
+            documentation_pages,
+            documentation_revisions,
+            ..SelectedRows::default()
+        })
+    }
+
+    fn restore_pages(&mut self, pages: Vec<Row>, revisions: Vec<Row>) -> Result<(), BackupError> {
+        let selected = ids(&pages);
+        let mut pending = pages;
+
+        // Parents before children
+        while !pending.is_empty() {
+            let (ready, waiting): (Vec<Row>, Vec<Row>) = pending.into_iter().partition(|row| {
+                int(row, "parent_id").is_none_or(|parent| {
+                    !selected.contains(&parent) || self.restored.contains_key(&("documentation_pages", parent))
+                })
+            });
+            if ready.is_empty() {
+                // Only pages whose parent failed to restore are left
+                for row in &waiting {
+                    self.warn(format!(
+                        "Page {} skipped: its parent page could not be restored",
+                        int(row, "id").unwrap_or_default()
+                    ));
+                }
+                break;
+            }
+
+            for mut row in ready {
+                let Some(archived_id) = int(&row, "id") else { continue };
+                let parent_id = match int(&row, "parent_id") {
+                    Some(parent) if selected.contains(&parent) => self.restored.get(&("documentation_pages", parent)).copied(),
+                    parent => self.reference("documentation_pages", parent)?,
+                };
+                row.insert("parent_id".to_string(), parent_id.into());
+                let ticket_id = self.reference("tickets", int(&row, "ticket_id"))?;
+                row.insert("ticket_id".to_string(), ticket_id.into());
+
+                // A page with the same uuid still exists: restore as a copy
+                if let Some(page_uuid) = text(&row, "uuid").and_then(|v| Uuid::parse_str(&v).ok()) {
+                    if self.uuid_exists("documentation_pages", "uuid", page_uuid)? {
+                        row.insert("uuid".to_string(), Uuid::now_v7().to_string().into());
+                    }
+                }
+
+                if self.insert("documentation_pages", row, true)? {
+                    let restored_id = self.restored[&("documentation_pages", archived_id)];
+                    self.report.documentation_pages.push(RestoredEntity { original_id: archived_id, restored_id });
+                } else {
+                    self.warn(format!("Page {} conflicts with an existing page and was skipped", archived_id));
+                }
+            }
+            pending = waiting;
+        }
+
+        for mut row in revisions {
+            let Some(&page_id) = int(&row, "page_id").and_then(|id| self.restored.get(&("documentation_pages", id))) else {
+                continue;
+            };
+            row.insert("page_id".to_string(), page_id.into());
+            self.insert("documentation_revisions", row, true)?;
+        }
+        Ok(())
+    }
+}
+
+/// Restore the selected records from an archive in one transaction
+///
+/// Files are restored separately with `restore_selected_files`, after the transaction.
+pub fn restore_selection(
+    conn: &mut DbConnection,
+    backup_path: &Path,
+    selection: &RestoreSelection,
+) -> Result<SelectiveRestoreReport, BackupError> {
+    let mut archive = ZipArchive::new(File::open(backup_path)?)?;
+    let rows = read_selection(&mut archive, selection)?;
+
+    let mut report = SelectiveRestoreReport::default();
+    let found: HashSet<i32> = ids(&rows.tickets);
+    for id in selection.tickets.iter().filter(|id| !found.contains(id)) {
+        report.warnings.push(format!("Ticket #{} is not in this backup", id));
+    }
+    let found: HashSet<i32> = ids(&rows.documentation_pages);
+    for id in selection.documentation_pages.iter().filter(|id| !found.contains(id)) {
+        report.warnings.push(format!("Page {} is not in this backup", id));
+    }
+
+    let report = conn.transaction::<_, BackupError, _>(|conn| {
+        let mut restorer = Restorer {
+            conn,
+            acting_user: selection.acting_user,
+            columns: HashMap::new(),
+            users: HashMap::new(),
+            restored: HashMap::new(),
+            report,
+        };
+        let rows = restorer.restore_tickets(rows)?;
+        restorer.restore_pages(rows.documentation_pages, rows.documentation_revisions)?;
+        Ok(restorer.report)
+    })?;
+
+    // Records kept their archived ids, which may be above a sequence restored from elsewhere
+    reset_sequences(conn)?;
+    Ok(report)
+}
+
+/// Restore the files of restored tickets, following any id remapping; existing files are kept
+pub async fn restore_selected_files(
+    backup_path: &Path,
+    storage: &dyn Storage,
+    tickets: &[RestoredEntity],
+) -> Result<u64, BackupError> {
+    if tickets.is_empty() {
+        return Ok(0);
+    }
+    let folders: HashMap<String, i32> = tickets
+        .iter()
+        .map(|ticket| (format!("tickets/{}/", ticket.original_id), ticket.restored_id))
+        .collect();
+
+    restore_backup_files_matching(
+        backup_path,
+        storage,
+        |path| {
+            let (folder, rest) = path.match_indices('/').nth(1).map(|(index, _)| path.split_at(index + 1))?;
+            folders.get(folder).map(|restored_id| format!("tickets/{}/{}", restored_id, rest))
+        },
+        false,
+        |_, _| {},
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn archive(pages: &[(i32, Option<i32>)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("data/documentation_pages.ndjson", FileOptions::default()).unwrap();
+        for (id, parent_id) in pages {
+            writeln!(zip, "{}", serde_json::json!({"id": id, "parent_id": parent_id, "title": "Page"})).unwrap();
+        }
+        ZipArchive::new(zip.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_page_subtree() {
+        let mut archive = archive(&[(1, None), (2, Some(1)), (3, Some(2)), (4, Some(1)), (5, None)]);
+
+        let subtree = page_subtree(&mut archive, &[1], true).unwrap();
+        assert_eq!(subtree, HashSet::from([1, 2, 3, 4]));
+
+        let single = page_subtree(&mut archive, &[2, 99], false).unwrap();
+        assert_eq!(single, HashSet::from([2]));
+    }
+}
@@ -0,0 +1,415 @@
+//! Ticket Merge Service
+//!
+//! Merges duplicate tickets into a target ticket and splits comments out into new tickets.
+//! Database relations are moved inside a single transaction; attachment files and the
+//! collaborative note content are handled by the caller once the transaction has committed.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::*;
+use crate::schema::*;
+use crate::services::ticket_relationships::{RelationshipError, TicketRelationshipService};
+use crate::utils::storage::Storage;
+
+/// Error type for merge and split operations
+#[derive(Debug)]
+pub enum MergeError {
+    TicketNotFound(i32),
+    SameTicket,
+    AlreadyMerged { ticket_id: i32, merged_into_id: i32 },
+    InvalidComments(String),
+    DatabaseError(diesel::result::Error),
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::TicketNotFound(id) => write!(f, "Ticket #{} not found", id),
+            MergeError::SameTicket => write!(f, "A ticket cannot be merged into itself"),
+            MergeError::AlreadyMerged { ticket_id, merged_into_id } => write!(
+                f,
+                "Ticket #{} has already been merged into ticket #{}",
+                ticket_id, merged_into_id
+            ),
+            MergeError::InvalidComments(e) => write!(f, "Invalid comment selection: {}", e),
+            MergeError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for MergeError {
+    fn from(e: diesel::result::Error) -> Self {
+        MergeError::DatabaseError(e)
+    }
+}
+
+/// An attachment whose file has to move to the new ticket's storage folder
+#[derive(Debug, Clone)]
+pub struct AttachmentMove {
+    pub attachment_id: i32,
+    pub from_path: String,
+    pub to_path: String,
+}
+
+/// Result of merging a source ticket into a target ticket
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub source: Ticket,
+    pub target: Ticket,
+    pub moved_comment_ids: Vec<i32>,
+    pub moved_device_ids: Vec<i32>,
+    pub moved_project_ids: Vec<i32>,
+    pub moved_linked_ticket_ids: Vec<i32>,
+    pub attachment_moves: Vec<AttachmentMove>,
+}
+
+/// Result of splitting comments out of a ticket
+#[derive(Debug)]
+pub struct SplitOutcome {
+    pub ticket: Ticket,
+    pub moved_comment_ids: Vec<i32>,
+    pub attachment_moves: Vec<AttachmentMove>,
+}
+
+/// Service for merging and splitting tickets
+pub struct TicketMergeService;
+
+impl TicketMergeService {
+    /// Merge `source_id` into `target_id`
+    ///
+    /// Moves comments (with their attachments), devices, projects and linked tickets to the
+    /// target, links the two tickets and closes the source with `merged_into_id` set.
+    pub fn merge(
+        conn: &mut DbConnection,
+        source_id: i32,
+        target_id: i32,
+        merged_by: Uuid,
+    ) -> Result<MergeOutcome, MergeError> {
+        if source_id == target_id {
+            return Err(MergeError::SameTicket);
+        }
+
+        conn.transaction(|conn| {
+            let source = Self::load_ticket(conn, source_id)?;
+            let target = Self::load_ticket(conn, target_id)?;
+
+            if let Some(merged_into_id) = source.merged_into_id {
+                return Err(MergeError::AlreadyMerged { ticket_id: source.id, merged_into_id });
+            }
+            if let Some(merged_into_id) = target.merged_into_id {
+                return Err(MergeError::AlreadyMerged { ticket_id: target.id, merged_into_id });
+            }
+
+            // 1. Comments and their attachments
+            let moved_comment_ids: Vec<i32> = comments::table
+                .filter(comments::ticket_id.eq(source_id))
+                .select(comments::id)
+                .load(conn)?;
+            let attachment_moves = Self::move_comments(conn, source_id, target_id, &moved_comment_ids)?;
+
+            // 2. Devices
+            let moved_device_ids: Vec<i32> = ticket_devices::table
+                .filter(ticket_devices::ticket_id.eq(source_id))
+                .select(ticket_devices::device_id)
+                .load(conn)?;
+            for device_id in &moved_device_ids {
+                diesel::insert_into(ticket_devices::table)
+                    .values(&NewTicketDevice { ticket_id: target_id, device_id: *device_id })
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+            diesel::delete(ticket_devices::table.filter(ticket_devices::ticket_id.eq(source_id)))
+                .execute(conn)?;
+
+            // 3. Projects
+            let moved_project_ids: Vec<i32> = project_tickets::table
+                .filter(project_tickets::ticket_id.eq(source_id))
+                .select(project_tickets::project_id)
+                .load(conn)?;
+            for project_id in &moved_project_ids {
+                diesel::insert_into(project_tickets::table)
+                    .values(&NewProjectTicket { project_id: *project_id, ticket_id: target_id })
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+            }
+            diesel::delete(project_tickets::table.filter(project_tickets::ticket_id.eq(source_id)))
+                .execute(conn)?;
+
+            // 4. Linked tickets (links are stored in both directions)
+            // Links keep their type; where the target is already linked to the same ticket,
+            // the target's existing relationship wins. The source's links are removed first so
+            // that moved links are validated as if the source no longer existed.
+            let moved_links: Vec<(i32, String)> = linked_tickets::table
+                .filter(linked_tickets::ticket_id.eq(source_id))
+                .filter(linked_tickets::linked_ticket_id.ne(target_id))
+                .select((linked_tickets::linked_ticket_id, linked_tickets::link_type))
+                .load(conn)?;
+            diesel::delete(linked_tickets::table.filter(
+                linked_tickets::ticket_id.eq(source_id).or(linked_tickets::linked_ticket_id.eq(source_id))
+            )).execute(conn)?;
+            for (linked_id, link_type) in &moved_links {
+                let link_type = link_type.parse().unwrap_or(TicketLinkType::RelatesTo);
+                Self::move_link(conn, target_id, *linked_id, link_type, merged_by)?;
+            }
+            let moved_linked_ticket_ids: Vec<i32> = moved_links.into_iter().map(|(id, _)| id).collect();
+
+            // Keep a link between the merged ticket and its target for traceability
+            Self::insert_link_pair(conn, source_id, target_id, TicketLinkType::DuplicateOf, Some(merged_by))?;
+
+            // 5. Close the source with the redirect marker
+            let now = Utc::now().naive_utc();
+            let source = diesel::update(tickets::table.find(source_id))
+                .set((
+                    tickets::status.eq(TicketStatus::Closed),
+                    tickets::closed_at.eq(Some(now)),
+                    tickets::closed_by.eq(Some(merged_by)),
+                    tickets::merged_into_id.eq(Some(target_id)),
+                    tickets::updated_at.eq(now),
+                ))
+                .get_result::<Ticket>(conn)?;
+
+            let target = diesel::update(tickets::table.find(target.id))
+                .set(tickets::updated_at.eq(now))
+                .get_result::<Ticket>(conn)?;
+
+            debug!(
+                source_id,
+                target_id,
+                comments = moved_comment_ids.len(),
+                devices = moved_device_ids.len(),
+                projects = moved_project_ids.len(),
+                links = moved_linked_ticket_ids.len(),
+                "Merged ticket records"
+            );
+
+            Ok(MergeOutcome {
+                source,
+                target,
+                moved_comment_ids,
+                moved_device_ids,
+                moved_project_ids,
+                moved_linked_ticket_ids,
+                attachment_moves,
+            })
+        })
+    }
+
+    /// Split the given comments of `source_id` out into a new ticket
+    ///
+    /// The new ticket inherits the requester, assignee, priority and category of the
+    /// source, gets empty article content and is linked back to the source.
+    pub fn split(
+        conn: &mut DbConnection,
+        source_id: i32,
+        comment_ids: &[i32],
+        title: Option<String>,
+        split_by: Uuid,
+    ) -> Result<SplitOutcome, MergeError> {
+        // A comment selected twice is still one comment
+        let comment_ids: Vec<i32> = comment_ids.iter().copied().collect::<BTreeSet<_>>().into_iter().collect();
+        if comment_ids.is_empty() {
+            return Err(MergeError::InvalidComments("no comments selected".to_string()));
+        }
+
+        conn.transaction(|conn| {
+            let source = Self::load_ticket(conn, source_id)?;
+
+            // Every selected comment must belong to the source ticket
+            let owned_ids: Vec<i32> = comments::table
+                .filter(comments::ticket_id.eq(source_id))
+                .filter(comments::id.eq_any(&comment_ids))
+                .select(comments::id)
+                .load(conn)?;
+            if owned_ids.len() != comment_ids.len() {
+                let foreign: Vec<String> = comment_ids.iter()
+                    .filter(|id| !owned_ids.contains(id))
+                    .map(|id| id.to_string())
+                    .collect();
+                return Err(MergeError::InvalidComments(format!(
+                    "comments {} do not belong to ticket #{}",
+                    foreign.join(", "),
+                    source_id
+                )));
+            }
+
+            let title = title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| format!("Split from #{}: {}", source.id, source.title));
+
+            let ticket = diesel::insert_into(tickets::table)
+                .values(&NewTicket {
+                    title,
+                    description: None,
+                    status: TicketStatus::Open,
+                    priority: source.priority,
+                    requester_uuid: source.requester_uuid,
+                    assignee_uuid: source.assignee_uuid,
+                    category_id: source.category_id,
+                })
+                .get_result::<Ticket>(conn)?;
+
+            diesel::update(tickets::table.find(ticket.id))
+                .set(tickets::created_by.eq(Some(split_by)))
+                .execute(conn)?;
+
+            crate::repository::article_content::create_article_content(conn, NewArticleContent {
+                ticket_id: ticket.id,
+                yjs_state_vector: None,
+                yjs_document: None,
+                yjs_client_id: None,
+            })?;
+
+            let attachment_moves = Self::move_comments(conn, source_id, ticket.id, &owned_ids)?;
+            Self::insert_link_pair(conn, source_id, ticket.id, TicketLinkType::RelatesTo, Some(split_by))?;
+
+            diesel::update(tickets::table.find(source_id))
+                .set(tickets::updated_at.eq(Utc::now().naive_utc()))
+                .execute(conn)?;
+
+            debug!(source_id, new_ticket_id = ticket.id, comments = owned_ids.len(), "Split ticket comments");
+
+            Ok(SplitOutcome {
+                ticket,
+                moved_comment_ids: owned_ids,
+                attachment_moves,
+            })
+        })
+    }
+
+    /// Move attachment files to their new ticket folder and update the stored URLs
+    ///
+    /// Runs after the database transaction has committed. Failures are logged and the
+    /// attachment keeps its old URL, which still resolves because the file was not moved.
+    pub async fn relocate_attachments(
+        conn: &mut DbConnection,
+        storage: Arc<dyn Storage>,
+        moves: &[AttachmentMove],
+    ) -> usize {
+        let mut relocated = 0;
+
+        for file_move in moves {
+            if let Err(e) = storage.move_file(&file_move.from_path, &file_move.to_path).await {
+                warn!(attachment_id = file_move.attachment_id, from = %file_move.from_path,
+                    to = %file_move.to_path, error = ?e, "Failed to move attachment file");
+                continue;
+            }
+
+            let new_url = format!("/uploads/{}", file_move.to_path);
+            match diesel::update(attachments::table.find(file_move.attachment_id))
+                .set(attachments::url.eq(&new_url))
+                .execute(conn)
+            {
+                Ok(_) => relocated += 1,
+                Err(e) => warn!(attachment_id = file_move.attachment_id, error = ?e,
+                    "Moved attachment file but failed to update its URL"),
+            }
+        }
+
+        relocated
+    }
+
+    fn load_ticket(conn: &mut DbConnection, ticket_id: i32) -> Result<Ticket, MergeError> {
+        match crate::repository::tickets::get_ticket_by_id(conn, ticket_id) {
+            Ok(ticket) => Ok(ticket),
+            Err(diesel::result::Error::NotFound) => Err(MergeError::TicketNotFound(ticket_id)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Re-parent comments and collect the attachment files that need to follow them
+    fn move_comments(
+        conn: &mut DbConnection,
+        from_ticket_id: i32,
+        to_ticket_id: i32,
+        comment_ids: &[i32],
+    ) -> Result<Vec<AttachmentMove>, MergeError> {
+        if comment_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        diesel::update(comments::table.filter(comments::id.eq_any(comment_ids)))
+            .set(comments::ticket_id.eq(to_ticket_id))
+            .execute(conn)?;
+
+        let moved_attachments: Vec<Attachment> = attachments::table
+            .filter(attachments::comment_id.eq_any(comment_ids.iter().map(|id| Some(*id)).collect::<Vec<_>>()))
+            .load(conn)?;
+
+        let old_prefix = format!("/uploads/tickets/{}/", from_ticket_id);
+        Ok(moved_attachments
+            .into_iter()
+            .filter_map(|attachment| {
+                attachment.url.strip_prefix(&old_prefix).map(|file_name| AttachmentMove {
+                    attachment_id: attachment.id,
+                    from_path: format!("tickets/{}/{}", from_ticket_id, file_name),
+                    to_path: format!("tickets/{}/{}", to_ticket_id, file_name),
+                })
+            })
+            .collect())
+    }
+
+    /// Re-create one of the source's links on the target
+    ///
+    /// The link goes through the same cycle and single-parent checks as a manual link. A link
+    /// the target cannot take with its type (it would create a cycle, give a ticket a second
+    /// parent, or points at a ticket in the recycle bin) is kept as `relates_to` instead.
+    fn move_link(
+        conn: &mut DbConnection,
+        target_id: i32,
+        linked_id: i32,
+        link_type: TicketLinkType,
+        merged_by: Uuid,
+    ) -> Result<(), MergeError> {
+        if crate::repository::linked_tickets::get_link_type(conn, target_id, linked_id)?.is_some() {
+            return Ok(());
+        }
+
+        match TicketRelationshipService::link(conn, target_id, linked_id, link_type, Some(merged_by)) {
+            Ok(()) => Ok(()),
+            Err(
+                RelationshipError::Cycle { .. }
+                | RelationshipError::ParentAlreadySet { .. }
+                | RelationshipError::TicketNotFound(_),
+            ) => {
+                debug!(target_id, linked_id, link_type = %link_type, "Moved link conflicts with the target's links, keeping it as relates_to");
+                Self::insert_link_pair(conn, target_id, linked_id, TicketLinkType::RelatesTo, Some(merged_by))
+            }
+            Err(RelationshipError::SelfLink) => Ok(()),
+            Err(RelationshipError::DatabaseError(e)) => Err(e.into()),
+        }
+    }
+
+    fn insert_link_pair(
+        conn: &mut DbConnection,
+        ticket_a: i32,
+        ticket_b: i32,
+        link_type: TicketLinkType,
+        created_by: Option<Uuid>,
+    ) -> Result<(), MergeError> {
+        diesel::insert_into(linked_tickets::table)
+            .values(&vec![
+                NewLinkedTicket {
+                    ticket_id: ticket_a,
+                    linked_ticket_id: ticket_b,
+                    link_type: link_type.as_str().to_string(),
+                    created_by,
+                },
+                NewLinkedTicket {
+                    ticket_id: ticket_b,
+                    linked_ticket_id: ticket_a,
+                    link_type: link_type.inverse().as_str().to_string(),
+                    created_by,
+                },
+            ])
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! Ticket Relationship Service
+//!
+//! Validates typed ticket links before they are stored and handles closing parent tickets.
+//!
+//! Directional relationships (parent/child, blocks, duplicate-of, caused-by) must stay acyclic:
+//! a ticket cannot end up blocking itself through a chain of other tickets, or be its own
+//! ancestor. A child ticket has at most one parent.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::db::DbConnection;
+use crate::models::{Ticket, TicketLinkType, TicketStatus};
+use crate::repository::linked_tickets;
+use crate::schema::tickets;
+
+/// Error type for relationship operations
+#[derive(Debug)]
+pub enum RelationshipError {
+    TicketNotFound(i32),
+    SelfLink,
+    Cycle { link_type: TicketLinkType, ticket_id: i32, linked_ticket_id: i32 },
+    ParentAlreadySet { child_id: i32, parent_id: i32 },
+    DatabaseError(diesel::result::Error),
+}
+
+impl std::fmt::Display for RelationshipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationshipError::TicketNotFound(id) => write!(f, "Ticket #{} not found", id),
+            RelationshipError::SelfLink => write!(f, "A ticket cannot be linked to itself"),
+            RelationshipError::Cycle { link_type, ticket_id, linked_ticket_id } => write!(
+                f,
+                "Linking #{} {} #{} would create a cycle",
+                ticket_id, link_type, linked_ticket_id
+            ),
+            RelationshipError::ParentAlreadySet { child_id, parent_id } => write!(
+                f,
+                "Ticket #{} already has parent #{}",
+                child_id, parent_id
+            ),
+            RelationshipError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<diesel::result::Error> for RelationshipError {
+    fn from(e: diesel::result::Error) -> Self {
+        RelationshipError::DatabaseError(e)
+    }
+}
+
+/// Service for typed ticket relationships
+pub struct TicketRelationshipService;
+
+impl TicketRelationshipService {
+    /// Validate and store a relationship, read from `ticket_id`'s side
+    pub fn link(
+        conn: &mut DbConnection,
+        ticket_id: i32,
+        linked_ticket_id: i32,
+        link_type: TicketLinkType,
+        created_by: Option<Uuid>,
+    ) -> Result<(), RelationshipError> {
+        if ticket_id == linked_ticket_id {
+            return Err(RelationshipError::SelfLink);
+        }
+
+        conn.transaction(|conn| {
+            for id in [ticket_id, linked_ticket_id] {
+                match crate::repository::tickets::get_ticket_by_id(conn, id) {
+                    Ok(_) => {}
+                    Err(diesel::result::Error::NotFound) => return Err(RelationshipError::TicketNotFound(id)),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            if let Some((from, to)) = Self::canonical_edge(ticket_id, linked_ticket_id, link_type) {
+                let canonical_type = Self::canonical_type(link_type);
+
+                // The pair may already be linked the other way round; that edge is replaced
+                let edges: Vec<(i32, i32)> = linked_tickets::get_link_edges(conn, canonical_type)?
+                    .into_iter()
+                    .filter(|edge| *edge != (to, from))
+                    .collect();
+
+                if would_create_cycle(&edges, from, to) {
+                    return Err(RelationshipError::Cycle { link_type, ticket_id, linked_ticket_id });
+                }
+
+                if canonical_type == TicketLinkType::ParentOf {
+                    // `to` is the child: it may only have one parent
+                    if let Some(&(parent_id, _)) = edges.iter().find(|(parent, child)| *child == to && *parent != from) {
+                        return Err(RelationshipError::ParentAlreadySet { child_id: to, parent_id });
+                    }
+                }
+            }
+
+            linked_tickets::link_tickets(conn, ticket_id, linked_ticket_id, link_type, created_by)?;
+            Ok(())
+        })
+    }
+
+    /// Children of a ticket that are not closed yet
+    pub fn open_children(conn: &mut DbConnection, parent_id: i32) -> QueryResult<Vec<Ticket>> {
+        let child_ids = linked_tickets::get_ticket_relationships(conn, parent_id)?.children;
+        if child_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tickets::table
+            .into_boxed()
+            .filter(tickets::id.eq_any(child_ids))
+            .filter(tickets::status.ne(TicketStatus::Closed))
+            .order(tickets::id.asc())
+            .load::<Ticket>(conn)
+    }
+
+    /// Close every open child of a ticket, recursively, returning the closed tickets
+    pub fn close_children(
+        conn: &mut DbConnection,
+        parent_id: i32,
+        closed_by: Option<Uuid>,
+    ) -> QueryResult<Vec<Ticket>> {
+        conn.transaction(|conn| {
+            let mut closed = Vec::new();
+            let mut queue = VecDeque::from([parent_id]);
+            let mut visited = HashSet::from([parent_id]);
+            let now = Utc::now().naive_utc();
+
+            while let Some(current) = queue.pop_front() {
+                let child_ids = linked_tickets::get_ticket_relationships(conn, current)?.children;
+                for child_id in child_ids {
+                    if !visited.insert(child_id) {
+                        continue;
+                    }
+                    queue.push_back(child_id);
+
+                    let updated = diesel::update(
+                        tickets::table
+                            .find(child_id)
+                            .filter(tickets::status.ne(TicketStatus::Closed)),
+                    )
+                    .set((
+                        tickets::status.eq(TicketStatus::Closed),
+                        tickets::closed_at.eq(Some(now)),
+                        tickets::closed_by.eq(closed_by),
+                        tickets::updated_at.eq(now),
+                    ))
+                    .get_result::<Ticket>(conn)
+                    .optional()?;
+
+                    if let Some(ticket) = updated {
+                        closed.push(ticket);
+                    }
+                }
+            }
+
+            debug!(parent_id, closed = closed.len(), "Closed child tickets");
+            Ok(closed)
+        })
+    }
+
+    /// Directional types share one canonical edge direction for cycle detection
+    fn canonical_type(link_type: TicketLinkType) -> TicketLinkType {
+        match link_type {
+            TicketLinkType::ChildOf => TicketLinkType::ParentOf,
+            TicketLinkType::BlockedBy => TicketLinkType::Blocks,
+            TicketLinkType::DuplicatedBy => TicketLinkType::DuplicateOf,
+            TicketLinkType::Causes => TicketLinkType::CausedBy,
+            other => other,
+        }
+    }
+
+    /// The (from, to) edge in canonical direction, or None for undirected links
+    fn canonical_edge(ticket_id: i32, linked_ticket_id: i32, link_type: TicketLinkType) -> Option<(i32, i32)> {
+        match link_type {
+            TicketLinkType::RelatesTo => None,
+            TicketLinkType::ParentOf
+            | TicketLinkType::Blocks
+            | TicketLinkType::DuplicateOf
+            | TicketLinkType::CausedBy => Some((ticket_id, linked_ticket_id)),
+            TicketLinkType::ChildOf
+            | TicketLinkType::BlockedBy
+            | TicketLinkType::DuplicatedBy
+            | TicketLinkType::Causes => Some((linked_ticket_id, ticket_id)),
+        }
+    }
+}
+
+/// Whether adding the edge `from -> to` to a directed graph creates a cycle,
+/// i.e. whether `from` is already reachable from `to`
+pub fn would_create_cycle(edges: &[(i32, i32)], from: i32, to: i32) -> bool {
+    if from == to {
+        return true;
+    }
+
+    let mut adjacency: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (a, b) in edges {
+        adjacency.entry(*a).or_default().push(*b);
+    }
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([to]);
+    while let Some(node) = queue.pop_front() {
+        if node == from {
+            return true;
+        }
+        if !visited.insert(node) {
+            continue;
+        }
+        if let Some(next) = adjacency.get(&node) {
+            queue.extend(next.iter().copied());
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_edge_is_a_cycle() {
+        assert!(would_create_cycle(&[], 1, 1));
+    }
+
+    #[test]
+    fn test_direct_cycle() {
+        // 1 blocks 2; 2 blocks 1 would close the loop
+        assert!(would_create_cycle(&[(1, 2)], 2, 1));
+    }
+
+    #[test]
+    fn test_transitive_cycle() {
+        let edges = [(1, 2), (2, 3), (3, 4)];
+        assert!(would_create_cycle(&edges, 4, 1));
+        assert!(!would_create_cycle(&edges, 1, 4));
+    }
+
+    #[test]
+    fn test_unrelated_branches() {
+        let edges = [(1, 2), (3, 4)];
+        assert!(!would_create_cycle(&edges, 2, 3));
+    }
+
+    #[test]
+    fn test_canonical_edge_direction() {
+        assert_eq!(TicketRelationshipService::canonical_edge(1, 2, TicketLinkType::Blocks), Some((1, 2)));
+        assert_eq!(TicketRelationshipService::canonical_edge(1, 2, TicketLinkType::BlockedBy), Some((2, 1)));
+        assert_eq!(TicketRelationshipService::canonical_edge(1, 2, TicketLinkType::RelatesTo), None);
+    }
+}
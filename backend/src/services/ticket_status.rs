@@ -0,0 +1,59 @@
+//! Ticket Status Rules
+//!
+//! Rules shared by every path that changes a ticket's status: the REST and websocket
+//! updates, bulk actions and imports. A status change is held while the ticket awaits
+//! approval, and a parent ticket is not closed past its open children unless the caller
+//! says what should happen to them.
+
+use crate::db::DbConnection;
+use crate::models::{Ticket, TicketApprovalStatus, TicketStatus};
+use crate::services::ticket_relationships::TicketRelationshipService;
+
+/// Why a ticket's status can't be changed as requested
+#[derive(Debug)]
+pub enum StatusChangeBlocked {
+    ApprovalPending,
+    OpenChildren(Vec<Ticket>),
+}
+
+impl std::fmt::Display for StatusChangeBlocked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatusChangeBlocked::ApprovalPending => {
+                write!(f, "Ticket status cannot change until its approvals are decided")
+            }
+            StatusChangeBlocked::OpenChildren(open_children) => {
+                write!(f, "Ticket has {} open child ticket(s)", open_children.len())
+            }
+        }
+    }
+}
+
+/// Check a status change against the shared rules
+///
+/// `cascade_children` is the caller's choice for open child tickets when closing a parent
+/// (true closes them too, false closes only the parent); without one the close is refused.
+pub fn check_status_change(
+    conn: &mut DbConnection,
+    current: &Ticket,
+    new_status: TicketStatus,
+    cascade_children: Option<bool>,
+) -> Result<(), StatusChangeBlocked> {
+    if new_status == current.status {
+        return Ok(());
+    }
+
+    // Status changes are held while the ticket is awaiting approval
+    if current.approval_status.as_deref() == Some(TicketApprovalStatus::Pending.as_str()) {
+        return Err(StatusChangeBlocked::ApprovalPending);
+    }
+
+    if new_status == TicketStatus::Closed && cascade_children.is_none() {
+        let open_children = TicketRelationshipService::open_children(conn, current.id).unwrap_or_default();
+        if !open_children.is_empty() {
+            return Err(StatusChangeBlocked::OpenChildren(open_children));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,360 @@
+//! Redis pub/sub relay for running several backend instances behind a load balancer
+//!
+//! Collaboration rooms and SSE clients live in process memory, so without a relay two
+//! replicas split every room in half and each only sees its own events. The relay publishes
+//! Yjs sync/awareness messages per document and `TicketEvent`s cluster-wide, and hands
+//! messages from other instances to the local `YjsAppState` and `SseState`.
+//!
+//! Persistence of each document is owned by a single instance through a lease key in Redis.
+//! The owner renews the lease on every maintenance tick; if it goes away the lease expires
+//! and another instance holding the document takes over.
+//!
+//! Enabled with `CLUSTER_ENABLED=true`; it shares the Redis server used by `RedisYjsCache`.
+
+use futures::StreamExt;
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, RedisResult};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, OnceCell};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::utils::redis_yjs_cache::RedisYjsCache;
+
+/// Channel prefix for per-document Yjs traffic
+const DOCUMENT_CHANNEL_PREFIX: &str = "nosdesk:yjs:";
+
+/// Channel for cluster-wide SSE events
+const EVENTS_CHANNEL: &str = "nosdesk:events";
+
+/// Key prefix for document persistence leases
+const OWNER_KEY_PREFIX: &str = "nosdesk:yjs-owner:";
+
+/// Key prefix for per-instance document viewers
+const VIEWERS_KEY_PREFIX: &str = "nosdesk:viewers:";
+
+/// Lease length for document ownership (renewed every 30s maintenance tick)
+const OWNER_LEASE_MS: u64 = 90_000;
+
+/// Expiry for viewer hashes, so entries of crashed instances disappear
+const VIEWERS_TTL_SECS: i64 = 3600;
+
+/// Acquire the lease if free, or renew it if we already hold it
+const CLAIM_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return 1
+end
+return 0
+"#;
+
+/// Header length of a document frame: kind + origin instance + contributor
+const FRAME_HEADER_LEN: usize = 1 + 16 + 16;
+
+/// What a relayed document frame asks the receiving instance to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// A y-protocols message (sync step 1/2, update or awareness) to apply and fan out
+    Message,
+    /// A full document state that replaces the local document (revision restore)
+    Reset,
+}
+
+/// A Yjs message received from another instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentFrame {
+    pub doc_id: String,
+    pub kind: FrameKind,
+    pub origin: Uuid,
+    /// User whose edit produced the message, for revision attribution
+    pub contributor: Option<Uuid>,
+    pub payload: Vec<u8>,
+}
+
+/// Encode a document frame for publishing
+pub fn encode_frame(kind: FrameKind, origin: Uuid, contributor: Option<Uuid>, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.push(match kind {
+        FrameKind::Message => 0,
+        FrameKind::Reset => 1,
+    });
+    frame.extend_from_slice(origin.as_bytes());
+    frame.extend_from_slice(contributor.unwrap_or(Uuid::nil()).as_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Decode a document frame received on a document channel
+pub fn decode_frame(doc_id: &str, bytes: &[u8]) -> Option<DocumentFrame> {
+    if bytes.len() < FRAME_HEADER_LEN {
+        return None;
+    }
+    let kind = match bytes[0] {
+        0 => FrameKind::Message,
+        1 => FrameKind::Reset,
+        _ => return None,
+    };
+    let origin = Uuid::from_slice(&bytes[1..17]).ok()?;
+    let contributor = Uuid::from_slice(&bytes[17..33]).ok().filter(|uuid| !uuid.is_nil());
+
+    Some(DocumentFrame {
+        doc_id: doc_id.to_string(),
+        kind,
+        origin,
+        contributor,
+        payload: bytes[FRAME_HEADER_LEN..].to_vec(),
+    })
+}
+
+/// Whether a message from a local client should be relayed to other instances
+///
+/// Sync step 2, updates and awareness changes carry state every replica needs. Sync step 1
+/// and awareness queries are requests aimed at this server only.
+pub fn is_relayable(msg: &[u8]) -> bool {
+    matches!(msg, [0, 1, ..] | [0, 2, ..] | [1, ..])
+}
+
+/// Whether a message is a sync step 1 (state vector request)
+pub fn is_sync_step1(msg: &[u8]) -> bool {
+    matches!(msg, [0, 0, ..])
+}
+
+/// Redis pub/sub relay shared by collaboration and SSE
+pub struct ClusterRelay {
+    instance_id: Uuid,
+    client: redis::Client,
+    publisher: OnceCell<ConnectionManager>,
+    documents_tx: broadcast::Sender<DocumentFrame>,
+    events_tx: broadcast::Sender<Vec<u8>>,
+}
+
+impl ClusterRelay {
+    /// Create the relay when `CLUSTER_ENABLED` is set, sharing the Yjs cache's Redis server
+    pub fn from_env(cache: &RedisYjsCache) -> Option<Arc<Self>> {
+        let enabled = std::env::var("CLUSTER_ENABLED")
+            .map(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let (documents_tx, _) = broadcast::channel(4096);
+        let (events_tx, _) = broadcast::channel(1000);
+        Some(Arc::new(Self {
+            instance_id: Uuid::now_v7(),
+            client: cache.client().clone(),
+            publisher: OnceCell::new(),
+            documents_tx,
+            events_tx,
+        }))
+    }
+
+    /// Unique id of this backend process
+    pub fn instance_id(&self) -> Uuid {
+        self.instance_id
+    }
+
+    /// Yjs frames published by other instances
+    pub fn document_frames(&self) -> broadcast::Receiver<DocumentFrame> {
+        self.documents_tx.subscribe()
+    }
+
+    /// Serialized SSE events published by other instances
+    pub fn event_payloads(&self) -> broadcast::Receiver<Vec<u8>> {
+        self.events_tx.subscribe()
+    }
+
+    async fn connection(&self) -> RedisResult<ConnectionManager> {
+        self.publisher
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .cloned()
+    }
+
+    /// Publish a Yjs message or reset for a document
+    pub async fn publish_document(&self, doc_id: &str, kind: FrameKind, contributor: Option<Uuid>, payload: &[u8]) {
+        let channel = format!("{}{}", DOCUMENT_CHANNEL_PREFIX, doc_id);
+        let frame = encode_frame(kind, self.instance_id, contributor, payload);
+        match self.connection().await {
+            Ok(mut conn) => {
+                if let Err(e) = conn.publish::<_, _, ()>(&channel, frame).await {
+                    warn!(doc_id = %doc_id, error = ?e, "Failed to publish Yjs frame");
+                }
+            }
+            Err(e) => warn!(doc_id = %doc_id, error = ?e, "Redis connection failed when publishing Yjs frame"),
+        }
+    }
+
+    /// Publish a serialized SSE event to the other instances
+    pub async fn publish_event(&self, payload: &[u8]) {
+        let mut frame = Vec::with_capacity(16 + payload.len());
+        frame.extend_from_slice(self.instance_id.as_bytes());
+        frame.extend_from_slice(payload);
+        match self.connection().await {
+            Ok(mut conn) => {
+                if let Err(e) = conn.publish::<_, _, ()>(EVENTS_CHANNEL, frame).await {
+                    warn!(error = ?e, "Failed to publish SSE event");
+                }
+            }
+            Err(e) => warn!(error = ?e, "Redis connection failed when publishing SSE event"),
+        }
+    }
+
+    /// Acquire or renew the persistence lease for a document
+    ///
+    /// Fails open: if Redis is unreachable this instance keeps persisting, since a missed
+    /// save loses edits while a duplicate save of converged CRDT state does not.
+    pub async fn claim_document(&self, doc_id: &str) -> bool {
+        let key = format!("{}{}", OWNER_KEY_PREFIX, doc_id);
+        let result: RedisResult<i32> = match self.connection().await {
+            Ok(mut conn) => {
+                redis::Script::new(CLAIM_SCRIPT)
+                    .key(&key)
+                    .arg(self.instance_id.to_string())
+                    .arg(OWNER_LEASE_MS)
+                    .invoke_async(&mut conn)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(claimed) => claimed == 1,
+            Err(e) => {
+                warn!(doc_id = %doc_id, error = ?e, "Could not check document ownership, persisting locally");
+                true
+            }
+        }
+    }
+
+    /// Record this instance's viewers of a document and return the viewers across the cluster
+    ///
+    /// `local` holds one user per local session; the result does the same for all instances.
+    pub async fn cluster_viewers(&self, doc_id: &str, local: &[Uuid]) -> Vec<Uuid> {
+        let key = format!("{}{}", VIEWERS_KEY_PREFIX, doc_id);
+        let field = self.instance_id.to_string();
+
+        let viewers = async {
+            let mut conn = self.connection().await?;
+            if local.is_empty() {
+                conn.hdel::<_, _, ()>(&key, &field).await?;
+            } else {
+                let value = local.iter().map(Uuid::to_string).collect::<Vec<_>>().join(",");
+                conn.hset::<_, _, _, ()>(&key, &field, value).await?;
+                conn.expire::<_, ()>(&key, VIEWERS_TTL_SECS).await?;
+            }
+            let values: Vec<String> = conn.hvals(&key).await?;
+            Ok::<Vec<Uuid>, redis::RedisError>(
+                values
+                    .iter()
+                    .flat_map(|value| value.split(','))
+                    .filter_map(|id| Uuid::parse_str(id).ok())
+                    .collect(),
+            )
+        }
+        .await;
+
+        viewers.unwrap_or_else(|e| {
+            warn!(doc_id = %doc_id, error = ?e, "Could not read cluster viewers");
+            local.to_vec()
+        })
+    }
+
+    /// Start the subscriber task, reconnecting with backoff when Redis drops the connection
+    pub fn spawn_subscriber(self: &Arc<Self>) {
+        let relay = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut backoff = Duration::from_secs(1);
+            loop {
+                match relay.run_subscriber().await {
+                    Ok(()) => {
+                        warn!("Cluster relay subscription ended, reconnecting");
+                        backoff = Duration::from_secs(1);
+                    }
+                    Err(e) => {
+                        warn!(error = ?e, retry_secs = backoff.as_secs(), "Cluster relay subscription failed");
+                    }
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(30));
+            }
+        });
+    }
+
+    async fn run_subscriber(&self) -> RedisResult<()> {
+        let mut pubsub = self.client.get_async_connection().await?.into_pubsub();
+        pubsub.psubscribe(format!("{}*", DOCUMENT_CHANNEL_PREFIX)).await?;
+        pubsub.subscribe(EVENTS_CHANNEL).await?;
+        info!(instance_id = %self.instance_id, "Cluster relay subscribed");
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            let channel = msg.get_channel_name();
+            let payload = msg.get_payload_bytes();
+
+            if channel == EVENTS_CHANNEL {
+                if payload.len() < 16 || payload[..16] == *self.instance_id.as_bytes() {
+                    continue;
+                }
+                let _ = self.events_tx.send(payload[16..].to_vec());
+            } else if let Some(doc_id) = channel.strip_prefix(DOCUMENT_CHANNEL_PREFIX) {
+                match decode_frame(doc_id, payload) {
+                    Some(frame) if frame.origin != self.instance_id => {
+                        let _ = self.documents_tx.send(frame);
+                    }
+                    Some(_) => {}
+                    None => debug!(doc_id = %doc_id, "Ignoring malformed Yjs frame"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let origin = Uuid::now_v7();
+        let contributor = Uuid::new_v4();
+        let bytes = encode_frame(FrameKind::Message, origin, Some(contributor), &[0, 2, 7, 7]);
+        let frame = decode_frame("ticket-1", &bytes).expect("valid frame");
+
+        assert_eq!(frame.kind, FrameKind::Message);
+        assert_eq!(frame.origin, origin);
+        assert_eq!(frame.contributor, Some(contributor));
+        assert_eq!(frame.payload, vec![0, 2, 7, 7]);
+    }
+
+    #[test]
+    fn test_frame_without_contributor() {
+        let bytes = encode_frame(FrameKind::Reset, Uuid::now_v7(), None, &[1]);
+        let frame = decode_frame("doc-4", &bytes).unwrap();
+        assert_eq!(frame.kind, FrameKind::Reset);
+        assert_eq!(frame.contributor, None);
+    }
+
+    #[test]
+    fn test_decode_rejects_short_or_unknown_frames() {
+        assert!(decode_frame("doc-1", &[0; 10]).is_none());
+        let mut bytes = encode_frame(FrameKind::Message, Uuid::now_v7(), None, &[]);
+        bytes[0] = 9;
+        assert!(decode_frame("doc-1", &bytes).is_none());
+    }
+
+    #[test]
+    fn test_relayable_messages() {
+        assert!(!is_relayable(&[0, 0, 1])); // sync step 1
+        assert!(is_relayable(&[0, 1, 1])); // sync step 2
+        assert!(is_relayable(&[0, 2, 1])); // update
+        assert!(is_relayable(&[1, 5])); // awareness
+        assert!(!is_relayable(&[3])); // awareness query
+        assert!(is_sync_step1(&[0, 0, 1]));
+    }
+}
@@ -0,0 +1,192 @@
+//! Five-field cron expressions for scheduled jobs
+//!
+//! `minute hour day-of-month month day-of-week`, evaluated in UTC. Fields accept `*`,
+//! numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`), comma-separated lists and month and
+//! weekday names (`jan`, `mon`). Day-of-week 0 and 7 are both Sunday. As in Vixie cron, when
+//! both day fields are restricted a day matching either of them is a match. The macros
+//! `@hourly`, `@daily` (`@midnight`), `@weekly`, `@monthly` and `@yearly` (`@annually`) are
+//! accepted too.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+const MONTH_NAMES: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead to look for the next run before giving up (e.g. `0 0 30 2 *`)
+const SEARCH_YEARS: i32 = 5;
+
+/// Set of allowed values for one field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field(u64);
+
+impl Field {
+    fn contains(&self, value: u32) -> bool {
+        self.0 & (1 << value) != 0
+    }
+}
+
+/// A parsed cron expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str], name_base: u32) -> Result<u32, String> {
+    let lower = value.to_ascii_lowercase();
+    let parsed = match names.iter().position(|name| *name == lower) {
+        Some(index) => index as u32 + name_base,
+        None => value.parse::<u32>().map_err(|_| format!("invalid value '{}'", value))?,
+    };
+    if parsed < min || parsed > max {
+        return Err(format!("value {} is outside {}-{}", parsed, min, max));
+    }
+    Ok(parsed)
+}
+
+fn parse_field(spec: &str, min: u32, max: u32, names: &[&str], name_base: u32) -> Result<Field, String> {
+    let mut bits = 0u64;
+    for item in spec.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be at least 1".to_string());
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max, names, name_base)?, parse_value(end, min, max, names, name_base)?)
+        } else {
+            let value = parse_value(range, min, max, names, name_base)?;
+            // "5/15" means from 5 to the end of the range in steps of 15
+            (value, if item.contains('/') { max } else { value })
+        };
+        if start > end {
+            return Err(format!("range {}-{} is reversed", start, end));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(Field(bits))
+}
+
+impl CronSchedule {
+    /// Parse a five-field expression or one of the `@` macros
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expanded = match expression.trim().to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            _ => expression.trim().to_string(),
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7, WEEKDAY_NAMES, 0).map_err(|e| format!("day of week: {}", e))?;
+        if weekdays.contains(7) {
+            weekdays = Field((weekdays.0 | 1) & !(1 << 7));
+        }
+
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0).map_err(|e| format!("minute: {}", e))?,
+            hours: parse_field(hour, 0, 23, &[], 0).map_err(|e| format!("hour: {}", e))?,
+            days: parse_field(day, 1, 31, &[], 0).map_err(|e| format!("day of month: {}", e))?,
+            months: parse_field(month, 1, 12, MONTH_NAMES, 1).map_err(|e| format!("month: {}", e))?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days.contains(date.day());
+        let weekday = self.weekdays.contains(date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// First matching minute strictly after `after`, if there is one in the next few years
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let last_year = after.year() + SEARCH_YEARS;
+
+        while time.year() <= last_year {
+            if !self.months.contains(time.month()) {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(time.date()) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !self.hours.contains(time.hour()) {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + Duration::hours(1);
+            } else if !self.minutes.contains(time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_next_after() {
+        let daily = CronSchedule::parse("0 3 * * *").unwrap();
+        assert_eq!(daily.next_after(at(2026, 1, 1, 2, 59)), Some(at(2026, 1, 1, 3, 0)));
+        assert_eq!(daily.next_after(at(2026, 1, 1, 3, 0)), Some(at(2026, 1, 2, 3, 0)));
+        assert_eq!(daily.next_after(at(2026, 12, 31, 4, 0)), Some(at(2027, 1, 1, 3, 0)));
+
+        let quarter_hours = CronSchedule::parse("*/15 9-17 * * mon-fri").unwrap();
+        // Friday 2026-01-02 17:50 -> Monday 09:00
+        assert_eq!(quarter_hours.next_after(at(2026, 1, 2, 17, 50)), Some(at(2026, 1, 5, 9, 0)));
+
+        assert_eq!(CronSchedule::parse("@weekly").unwrap().next_after(at(2026, 1, 1, 0, 0)), Some(at(2026, 1, 4, 0, 0)));
+        assert_eq!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(at(2026, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_restricted_day_fields_match_either() {
+        // The 15th or any Sunday (7 is Sunday too)
+        let schedule = CronSchedule::parse("30 2 15 * 7").unwrap();
+        assert_eq!(schedule.next_after(at(2026, 1, 5, 0, 0)), Some(at(2026, 1, 11, 2, 30)));
+        assert_eq!(schedule.next_after(at(2026, 1, 12, 0, 0)), Some(at(2026, 1, 15, 2, 30)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CronSchedule::parse("0 3 * *").is_err());
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("0 5-2 * * *").is_err());
+        assert!(CronSchedule::parse("0 0 * foo *").is_err());
+    }
+}
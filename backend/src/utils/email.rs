@@ -256,6 +256,7 @@ impl<'a> EmailTemplate<'a> {
             NoticeType::Critical => ("#fee2e2", "#dc2626"),
             NoticeType::Info => (&light_color, &self.branding.primary_color),
             NoticeType::Success => ("#ecfdf5", "#059669"),
+            NoticeType::Action => (&light_color, &self.branding.primary_color),
         };
 
         let title = match notice_type {
@@ -263,6 +264,7 @@ impl<'a> EmailTemplate<'a> {
             NoticeType::Critical => "Critical Security Notice",
             NoticeType::Info => "Getting Started",
             NoticeType::Success => "Success",
+            NoticeType::Action => "Action Required",
         };
 
         let items_html: String = items
@@ -301,6 +303,7 @@ enum NoticeType {
     Critical,
     Info,
     Success,
+    Action,
 }
 
 /// Email configuration loaded from environment variables
@@ -654,6 +657,105 @@ impl EmailService {
         let subject = format!("You've Been Invited to {} - Set Up Your Account", branding.app_name);
         self.send_html_email(to, &subject, &html_body).await
     }
+
+    /// Send an approval request with a one-click link to approve or reject
+    pub async fn send_approval_request_email(
+        &self,
+        to: &str,
+        approver_name: &str,
+        ticket_label: &str,
+        requester_name: &str,
+        decision_token: &str,
+        branding: &EmailBranding,
+    ) -> Result<(), String> {
+        if !self.config.is_configured() {
+            return Err("Email is not configured".to_string());
+        }
+
+        let respond_link = format!("{}/approvals/respond?token={}", branding.base_url, decision_token);
+        let template = EmailTemplate::new(branding);
+
+        let content = format!(
+            r#"<p style="margin: 0 0 16px 0; color: #374151; font-size: 16px; line-height: 1.6;">
+                Hello <strong>{}</strong>,
+            </p>
+            <p style="margin: 0 0 16px 0; color: #374151; font-size: 16px; line-height: 1.6;">
+                <strong>{}</strong> has requested your approval for ticket <strong>{}</strong>.
+            </p>
+            <p style="margin: 0 0 8px 0; color: #374151; font-size: 16px; line-height: 1.6;">
+                Click the button below to review the request and approve or reject it:
+            </p>"#,
+            escape_html(approver_name),
+            escape_html(requester_name),
+            escape_html(ticket_label)
+        );
+
+        let html_body = template.build(
+            "Approval Requested",
+            &branding.primary_color,
+            &content,
+            "Review Request",
+            &respond_link,
+            &branding.primary_color,
+            NoticeType::Action,
+            &[
+                "This link will expire in <strong>7 days</strong>",
+                "The link can only be used once",
+                "You can also respond from the ticket in the app",
+            ],
+            "If you weren't expecting this request, please contact your system administrator.",
+        );
+
+        let subject = format!("Approval Requested: {}", ticket_label);
+        self.send_html_email(to, &subject, &html_body).await
+    }
+
+    /// Tell an administrator that a scheduled backup failed
+    pub async fn send_backup_failure_email(
+        &self,
+        to: &str,
+        schedule_name: &str,
+        error_message: &str,
+        branding: &EmailBranding,
+    ) -> Result<(), String> {
+        if !self.config.is_configured() {
+            return Err("Email is not configured".to_string());
+        }
+
+        let backups_link = format!("{}/admin/backup", branding.base_url);
+        let template = EmailTemplate::new(branding);
+
+        let content = format!(
+            r#"<p style="margin: 0 0 16px 0; color: #374151; font-size: 16px; line-height: 1.6;">
+                The scheduled backup <strong>{}</strong> did not complete.
+            </p>
+            <p style="margin: 0 0 16px 0; color: #374151; font-size: 14px; line-height: 1.6; font-family: monospace; white-space: pre-wrap;">{}</p>
+            <p style="margin: 0 0 8px 0; color: #374151; font-size: 16px; line-height: 1.6;">
+                Review the backup jobs and schedule settings:
+            </p>"#,
+            escape_html(schedule_name),
+            escape_html(error_message)
+        );
+
+        let critical_color = "#dc2626";
+        let html_body = template.build(
+            "Scheduled Backup Failed",
+            critical_color,
+            &content,
+            "View Backups",
+            &backups_link,
+            critical_color,
+            NoticeType::Action,
+            &[
+                "The failure has been recorded in the backup job history",
+                "The schedule will try again at its next run",
+            ],
+            "You are receiving this email because you are an administrator.",
+        );
+
+        let subject = format!("Scheduled Backup Failed: {} - {}", schedule_name, branding.app_name);
+        self.send_html_email(to, &subject, &html_body).await
+    }
 }
 
 #[cfg(test)]
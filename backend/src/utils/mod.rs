@@ -13,7 +13,13 @@ pub mod cookies;
 pub mod file_validation;
 pub mod rate_limit;
 pub mod redis_yjs_cache;
+pub mod cluster_relay;
+pub mod presence;
+pub mod cron;
 pub mod rbac;
+pub mod yjs;
+pub mod yjs_render;
+pub mod yjs_import;
 
 use uuid::Uuid;
 use crate::models::{UserRole, UserInfo};
@@ -0,0 +1,110 @@
+//! Ticket presence tracking
+//!
+//! Records which users are looking at which ticket, from both collaboration sessions and
+//! event websocket clients, and announces changes as `ViewerCountChanged` events. With the
+//! cluster relay enabled, viewers on other instances are included.
+
+use actix_web::web;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::handlers::sse::SseState;
+use crate::utils::cluster_relay::ClusterRelay;
+use crate::utils::sse::SseBroadcaster;
+
+/// Session id → viewing user, per ticket
+type TicketViewers = HashMap<i32, HashMap<String, Uuid>>;
+
+pub struct PresenceRegistry {
+    viewers: Mutex<TicketViewers>,
+    relay: Option<Arc<ClusterRelay>>,
+}
+
+impl PresenceRegistry {
+    pub fn new(relay: Option<Arc<ClusterRelay>>) -> Self {
+        Self {
+            viewers: Mutex::new(HashMap::new()),
+            relay,
+        }
+    }
+
+    /// Record a session viewing a ticket, returns false if it was already recorded
+    pub fn join(&self, ticket_id: i32, session_id: &str, user: Uuid) -> bool {
+        let mut viewers = self.viewers.lock().unwrap();
+        viewers
+            .entry(ticket_id)
+            .or_default()
+            .insert(session_id.to_string(), user)
+            .is_none()
+    }
+
+    /// Remove a session from a ticket, returns false if it wasn't viewing it
+    pub fn leave(&self, ticket_id: i32, session_id: &str) -> bool {
+        let mut viewers = self.viewers.lock().unwrap();
+        let Some(sessions) = viewers.get_mut(&ticket_id) else {
+            return false;
+        };
+        let removed = sessions.remove(session_id).is_some();
+        if sessions.is_empty() {
+            viewers.remove(&ticket_id);
+        }
+        removed
+    }
+
+    fn local_viewers(&self, ticket_id: i32) -> Vec<Uuid> {
+        let viewers = self.viewers.lock().unwrap();
+        viewers
+            .get(&ticket_id)
+            .map(|sessions| sessions.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Distinct users viewing a ticket, across the cluster when the relay is enabled
+    pub async fn viewers(&self, ticket_id: i32) -> Vec<Uuid> {
+        let local = self.local_viewers(ticket_id);
+        let all = match &self.relay {
+            Some(relay) => relay.cluster_viewers(&format!("ticket-{}", ticket_id), &local).await,
+            None => local,
+        };
+        all.into_iter().collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// Broadcast the current viewers of a ticket
+    pub async fn announce(&self, sse_state: &web::Data<SseState>, ticket_id: i32) {
+        let viewers = self.viewers(ticket_id).await;
+        SseBroadcaster::broadcast_viewer_count(
+            sse_state,
+            ticket_id,
+            viewers.len(),
+            viewers.iter().map(Uuid::to_string).collect(),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_viewers_are_distinct_users() {
+        let registry = PresenceRegistry::new(None);
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        assert!(registry.join(7, "a1", alice));
+        assert!(registry.join(7, "a2", alice));
+        assert!(registry.join(7, "b1", bob));
+        assert!(!registry.join(7, "b1", bob));
+        assert_eq!(registry.viewers(7).await.len(), 2);
+
+        assert!(registry.leave(7, "a1"));
+        assert!(!registry.leave(7, "a1"));
+        assert_eq!(registry.viewers(7).await.len(), 2);
+
+        registry.leave(7, "a2");
+        registry.leave(7, "b1");
+        assert!(registry.viewers(7).await.is_empty());
+    }
+}
@@ -157,7 +157,7 @@ impl RateLimiter {
             .await
             .map_err(|_| RateLimitError::ConnectionFailed)?;
 
-        con.del(key)
+        con.del::<_, ()>(key)
             .await
             .map_err(|e| RateLimitError::RedisError(e.to_string()))?;
 
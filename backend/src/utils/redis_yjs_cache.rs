@@ -25,6 +25,11 @@ impl RedisYjsCache {
         Ok(Self { client })
     }
 
+    /// Underlying Redis client (shared with the cluster relay)
+    pub fn client(&self) -> &redis::Client {
+        &self.client
+    }
+
     /// Generate Redis key for a document
     fn document_key(doc_id: &str) -> String {
         format!("{}:{}", KEY_PREFIX, doc_id)
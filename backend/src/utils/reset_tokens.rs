@@ -9,6 +9,7 @@ pub enum TokenType {
     PasswordReset,
     MfaReset,
     Invitation,
+    ApprovalDecision,
 }
 
 impl TokenType {
@@ -17,6 +18,7 @@ impl TokenType {
             TokenType::PasswordReset => "password_reset",
             TokenType::MfaReset => "mfa_reset",
             TokenType::Invitation => "invitation",
+            TokenType::ApprovalDecision => "approval_decision",
         }
     }
 
@@ -26,6 +28,7 @@ impl TokenType {
             TokenType::PasswordReset => Duration::hours(1),  // 1 hour for password resets
             TokenType::MfaReset => Duration::minutes(15),    // 15 minutes for MFA resets
             TokenType::Invitation => Duration::days(7),      // 7 days for user invitations
+            TokenType::ApprovalDecision => Duration::days(7), // 7 days to act on an approval request
         }
     }
 }
@@ -154,11 +154,13 @@ impl SseBroadcaster {
         state: &web::Data<SseState>,
         ticket_id: i32,
         linked_ticket_id: i32,
+        link_type: &str,
     ) {
         Self::broadcast_generic_event(state, |timestamp| {
             TicketEvent::TicketLinked {
                 ticket_id,
                 linked_ticket_id,
+                link_type: link_type.to_string(),
                 timestamp,
             }
         }).await;
@@ -233,11 +235,13 @@ impl SseBroadcaster {
         state: &web::Data<SseState>,
         ticket_id: i32,
         count: usize,
+        viewers: Vec<String>,
     ) {
         Self::broadcast_generic_event(state, |timestamp| {
             TicketEvent::ViewerCountChanged {
                 ticket_id,
                 count,
+                viewers,
                 timestamp,
             }
         }).await;
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use std::io;
 use std::path::Path;
+use tokio::io::AsyncRead;
 use uuid::Uuid;
 use actix_web::{HttpResponse, HttpRequest};
 use actix_web::http::header::{CONTENT_TYPE, CACHE_CONTROL, ACCEPT_RANGES};
@@ -32,6 +33,14 @@ pub struct StoredFile {
     pub content_type: String,
 }
 
+/// A file found by `Storage::list_files`
+#[derive(Debug, Clone)]
+pub struct ListedFile {
+    /// Path relative to the storage root, as accepted by `get_file`
+    pub path: String,
+    pub size: u64,
+}
+
 /// Error types for storage operations
 #[derive(Debug)]
 pub enum StorageError {
@@ -63,6 +72,18 @@ pub trait Storage: Send + Sync {
     /// Retrieve a file by path
     async fn get_file(&self, path: &str) -> Result<Vec<u8>, StorageError>;
 
+    /// Open a file for streaming reads, without loading it into memory
+    async fn open_file(&self, path: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError>;
+
+    /// Write a file at an exact path, replacing any existing file (e.g. when restoring)
+    async fn write_file(&self, path: &str, data: &[u8], content_type: &str) -> Result<(), StorageError>;
+
+    /// Like `write_file`, streaming the contents from a local file
+    async fn write_file_from_path(&self, path: &str, source: &Path, content_type: &str) -> Result<(), StorageError>;
+
+    /// List every file under a folder ("" for everything), sorted by path
+    async fn list_files(&self, prefix: &str) -> Result<Vec<ListedFile>, StorageError>;
+
     /// Delete a file by path
     async fn delete_file(&self, path: &str) -> Result<(), StorageError>;
 
@@ -142,6 +163,57 @@ impl Storage for LocalStorage {
         }
     }
 
+    async fn open_file(&self, path: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let full_path = self.get_full_path(path);
+        match tokio::fs::File::open(&full_path).await {
+            Ok(file) => Ok(Box::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(StorageError::NotFound(format!("File not found: {}", path)))
+            }
+            Err(e) => Err(StorageError::Io(e)),
+        }
+    }
+
+    async fn write_file(&self, path: &str, data: &[u8], _content_type: &str) -> Result<(), StorageError> {
+        let full_path = self.get_full_path(path);
+        self.ensure_directory_exists(&full_path)?;
+        std::fs::write(&full_path, data)?;
+        Ok(())
+    }
+
+    async fn write_file_from_path(&self, path: &str, source: &Path, _content_type: &str) -> Result<(), StorageError> {
+        let full_path = self.get_full_path(path);
+        self.ensure_directory_exists(&full_path)?;
+        tokio::fs::copy(source, &full_path).await?;
+        Ok(())
+    }
+
+    async fn list_files(&self, prefix: &str) -> Result<Vec<ListedFile>, StorageError> {
+        let base = Path::new(&self.base_path);
+        let root = self.get_full_path(prefix);
+        if !Path::new(&root).exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in walkdir::WalkDir::new(&root) {
+            let entry = entry.map_err(|e| StorageError::Io(io::Error::other(e.to_string())))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(base)
+                .map_err(|_| StorageError::InvalidPath(entry.path().display().to_string()))?;
+            files.push(ListedFile {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
     async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
         let full_path = self.get_full_path(path);
         match std::fs::remove_file(&full_path) {
@@ -175,13 +247,14 @@ impl Storage for LocalStorage {
     }
 }
 
-/// Future S3 storage implementation (placeholder)
+/// S3 (or S3-compatible, e.g. MinIO) storage implementation
+///
+/// Paths are object keys in the bucket. Public URLs point at the backend's `/uploads` routes,
+/// which stream objects through `serve_file_from_storage`, so the bucket can stay private.
 pub struct S3Storage {
-    _bucket: String,
-    _region: String,
-    _access_key: String,
-    _secret_key: String,
-    _endpoint: Option<String>,
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    public_url_base: String,
 }
 
 impl S3Storage {
@@ -192,52 +265,215 @@ impl S3Storage {
         secret_key: String,
         endpoint: Option<String>,
     ) -> Self {
+        let credentials = aws_sdk_s3::config::Credentials::new(access_key, secret_key, None, None, "nosdesk");
+        let mut config = aws_sdk_s3::config::Builder::new()
+            .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(region))
+            .credentials_provider(credentials);
+        if let Some(endpoint) = endpoint {
+            // S3-compatible services generally don't support virtual-hosted bucket names
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+
         Self {
-            _bucket: bucket,
-            _region: region,
-            _access_key: access_key,
-            _secret_key: secret_key,
-            _endpoint: endpoint,
+            client: aws_sdk_s3::Client::from_conf(config.build()),
+            bucket,
+            public_url_base: "/uploads".to_string(),
         }
     }
+
+    fn key(path: &str) -> &str {
+        path.trim_start_matches('/')
+    }
 }
 
 #[async_trait]
 impl Storage for S3Storage {
     async fn store_file(
         &self,
-        _data: &[u8],
-        _filename: &str,
-        _content_type: &str,
-        _folder: &str,
+        data: &[u8],
+        filename: &str,
+        content_type: &str,
+        folder: &str,
     ) -> Result<StoredFile, StorageError> {
-        // TODO: Implement S3 upload
-        Err(StorageError::ConfigError("S3 storage not implemented yet".to_string()))
+        // Generate unique filename to prevent collisions
+        let unique_filename = format!("{}_{}", Uuid::now_v7(), filename);
+        let relative_path = format!("{}/{}", folder.trim_matches('/'), unique_filename);
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&relative_path)
+            .content_type(content_type)
+            .body(aws_sdk_s3::primitives::ByteStream::from(data.to_vec()))
+            .send()
+            .await
+            .map_err(|e| StorageError::UploadFailed(format!("S3 upload of {} failed: {}", relative_path, e)))?;
+
+        Ok(StoredFile {
+            id: unique_filename,
+            url: self.get_public_url(&relative_path),
+            path: relative_path,
+            size: data.len() as u64,
+            content_type: content_type.to_string(),
+        })
     }
 
-    async fn get_file(&self, _path: &str) -> Result<Vec<u8>, StorageError> {
-        // TODO: Implement S3 download
-        Err(StorageError::ConfigError("S3 storage not implemented yet".to_string()))
+    async fn get_file(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        let output = self.client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(Self::key(path))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.as_service_error().is_some_and(|e| e.is_no_such_key()) {
+                    StorageError::NotFound(format!("File not found: {}", path))
+                } else {
+                    StorageError::Io(io::Error::other(format!("S3 download failed: {}", e)))
+                }
+            })?;
+
+        let data = output.body.collect().await.map_err(|e| {
+            StorageError::Io(io::Error::other(format!("S3 download failed: {}", e)))
+        })?;
+        Ok(data.into_bytes().to_vec())
     }
 
-    async fn delete_file(&self, _path: &str) -> Result<(), StorageError> {
-        // TODO: Implement S3 delete
-        Err(StorageError::ConfigError("S3 storage not implemented yet".to_string()))
+    async fn open_file(&self, path: &str) -> Result<Box<dyn AsyncRead + Send + Unpin>, StorageError> {
+        let output = self.client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(Self::key(path))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.as_service_error().is_some_and(|e| e.is_no_such_key()) {
+                    StorageError::NotFound(format!("File not found: {}", path))
+                } else {
+                    StorageError::Io(io::Error::other(format!("S3 download failed: {}", e)))
+                }
+            })?;
+        Ok(Box::new(output.body.into_async_read()))
     }
 
-    async fn file_exists(&self, _path: &str) -> Result<bool, StorageError> {
-        // TODO: Implement S3 exists check
-        Err(StorageError::ConfigError("S3 storage not implemented yet".to_string()))
+    async fn write_file(&self, path: &str, data: &[u8], content_type: &str) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(Self::key(path))
+            .content_type(content_type)
+            .body(aws_sdk_s3::primitives::ByteStream::from(data.to_vec()))
+            .send()
+            .await
+            .map_err(|e| StorageError::UploadFailed(format!("S3 upload of {} failed: {}", path, e)))?;
+        Ok(())
     }
 
-    fn get_public_url(&self, _path: &str) -> String {
-        // TODO: Implement S3 public URL generation
-        String::new()
+    async fn write_file_from_path(&self, path: &str, source: &Path, content_type: &str) -> Result<(), StorageError> {
+        let body = aws_sdk_s3::primitives::ByteStream::from_path(source)
+            .await
+            .map_err(|e| StorageError::Io(io::Error::other(e.to_string())))?;
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(Self::key(path))
+            .content_type(content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| StorageError::UploadFailed(format!("S3 upload of {} failed: {}", path, e)))?;
+        Ok(())
     }
 
-    async fn move_file(&self, _from_path: &str, _to_path: &str) -> Result<(), StorageError> {
-        // TODO: Implement S3 move (copy + delete)
-        Err(StorageError::ConfigError("S3 storage not implemented yet".to_string()))
+    async fn list_files(&self, prefix: &str) -> Result<Vec<ListedFile>, StorageError> {
+        let prefix = Self::key(prefix);
+        let mut files = Vec::new();
+        let mut continuation_token: Option<String> = None;
+
+        // ListObjectsV2 returns at most 1000 keys per page
+        loop {
+            let mut request = self.client.list_objects_v2().bucket(&self.bucket);
+            if !prefix.is_empty() {
+                request = request.prefix(format!("{}/", prefix.trim_end_matches('/')));
+            }
+            if let Some(token) = &continuation_token {
+                request = request.continuation_token(token);
+            }
+            let output = request
+                .send()
+                .await
+                .map_err(|e| StorageError::Io(io::Error::other(format!("S3 listing failed: {}", e))))?;
+
+            for object in output.contents() {
+                if let Some(key) = object.key().filter(|key| !key.ends_with('/')) {
+                    files.push(ListedFile {
+                        path: key.to_string(),
+                        size: object.size().unwrap_or(0).max(0) as u64,
+                    });
+                }
+            }
+
+            match output.next_continuation_token() {
+                Some(token) if output.is_truncated().unwrap_or(false) => continuation_token = Some(token.to_string()),
+                _ => break,
+            }
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), StorageError> {
+        // Deleting a missing key succeeds in S3, matching the local backend
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(Self::key(path))
+            .send()
+            .await
+            .map_err(|e| StorageError::Io(io::Error::other(format!("S3 delete failed: {}", e))))?;
+        Ok(())
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, StorageError> {
+        match self.client.head_object().bucket(&self.bucket).key(Self::key(path)).send().await {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(false),
+            Err(e) => Err(StorageError::Io(io::Error::other(format!("S3 lookup failed: {}", e)))),
+        }
+    }
+
+    fn get_public_url(&self, path: &str) -> String {
+        format!("{}/{}", self.public_url_base.trim_end_matches('/'), path.trim_start_matches('/'))
+    }
+
+    async fn move_file(&self, from_path: &str, to_path: &str) -> Result<(), StorageError> {
+        // S3 has no rename: copy, then delete the original
+        self.client
+            .copy_object()
+            .bucket(&self.bucket)
+            .copy_source(format!("{}/{}", self.bucket, Self::key(from_path)))
+            .key(Self::key(to_path))
+            .send()
+            .await
+            .map_err(|e| StorageError::Io(io::Error::other(format!("S3 copy failed: {}", e))))?;
+        self.delete_file(from_path).await
+    }
+}
+
+impl StorageConfig {
+    /// S3 configuration from `{prefix}_BUCKET`, `{prefix}_REGION`, `{prefix}_ACCESS_KEY`,
+    /// `{prefix}_SECRET_KEY` and optional `{prefix}_ENDPOINT`; `None` when no bucket is set
+    pub fn s3_from_env(prefix: &str) -> Option<StorageConfig> {
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok().filter(|v| !v.is_empty());
+        Some(StorageConfig::S3 {
+            bucket: var("BUCKET")?,
+            region: var("REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            access_key: var("ACCESS_KEY").unwrap_or_default(),
+            secret_key: var("SECRET_KEY").unwrap_or_default(),
+            endpoint: var("ENDPOINT"),
+        })
     }
 }
 
@@ -260,9 +496,17 @@ pub fn create_storage(config: StorageConfig) -> Arc<dyn Storage> {
 }
 
 /// Get storage configuration from environment variables
+///
+/// `STORAGE_TYPE=s3` stores files in the bucket configured by the `S3_*` variables;
+/// anything else (the default) uses local storage.
 pub fn get_storage_config() -> StorageConfig {
-    // For now, always use local storage
-    // Later, check env vars like STORAGE_TYPE, S3_BUCKET, etc.
+    if std::env::var("STORAGE_TYPE").is_ok_and(|kind| kind.eq_ignore_ascii_case("s3")) {
+        match StorageConfig::s3_from_env("S3") {
+            Some(config) => return config,
+            None => error!("STORAGE_TYPE=s3 but S3_BUCKET is not set; falling back to local storage"),
+        }
+    }
+
     StorageConfig::Local {
         base_path: "/app/uploads".to_string(), // Use Docker volume mount point
     }
@@ -337,7 +581,7 @@ pub async fn serve_file_from_storage(
 }
 
 /// Helper function to determine content type based on file extension
-fn get_content_type(filename: &str) -> &'static str {
+pub fn get_content_type(filename: &str) -> &'static str {
     let extension = filename.rsplit('.').next().unwrap_or("").to_lowercase();
     match extension.as_str() {
         "pdf" => "application/pdf",
@@ -0,0 +1,298 @@
+//! Helpers for working with the ProseMirror XML fragment stored in Yjs documents
+//!
+//! Ticket notes (`article_contents`) and documentation pages store their content as a
+//! Yjs update containing a single `prosemirror` XmlFragment. These helpers decode those
+//! snapshots outside of the live collaboration rooms, flatten them into blocks for diffing
+//! and copy content between documents.
+
+use serde::Serialize;
+use std::panic;
+use yrs::types::text::YChange;
+use yrs::{
+    Any, Doc, GetString, Options, Out, ReadTxn, StateVector, Text, Transact, TransactionMut, Update,
+    WriteTxn, Xml, XmlElementPrelim, XmlFragment, XmlOut, XmlTextPrelim,
+};
+use yrs::updates::decoder::Decode;
+
+/// Name of the root XmlFragment used by y-prosemirror
+pub const PROSEMIRROR_FRAGMENT: &str = "prosemirror";
+
+/// Decode a stored Yjs update into a standalone document
+/// Returns None if the bytes are empty or not a valid v1 update
+pub fn decode_document(yjs_document: &[u8]) -> Option<Doc> {
+    if yjs_document.is_empty() {
+        return None;
+    }
+
+    let options = Options {
+        skip_gc: true,
+        ..Default::default()
+    };
+    let doc = Doc::with_options(options);
+
+    // Define the root type up front so the update merges into the expected structure
+    {
+        let mut txn = doc.transact_mut();
+        let _ = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+    }
+
+    let update = Update::decode_v1(yjs_document).ok()?;
+    {
+        let mut txn = doc.transact_mut();
+        txn.apply_update(update).ok()?;
+    }
+
+    Some(doc)
+}
+
+/// Encode the full state of a document as a v1 update (the format stored in the database)
+pub fn encode_document(doc: &Doc) -> Vec<u8> {
+    let txn = doc.transact();
+    txn.encode_state_as_update_v1(&StateVector::default())
+}
+
+/// Count the top-level blocks in a document's ProseMirror fragment
+pub fn block_count(doc: &Doc) -> u32 {
+    let txn = doc.transact();
+    txn.get_xml_fragment(PROSEMIRROR_FRAGMENT)
+        .map(|fragment| fragment.len(&txn))
+        .unwrap_or(0)
+}
+
+/// Recursively extract plain text from an XmlOut node
+/// Text of sibling children is joined with a single space.
+pub fn node_text<T: ReadTxn>(node: &XmlOut, txn: &T) -> String {
+    match node {
+        XmlOut::Text(text_ref) => {
+            // XmlTextRef::get_string can panic on invalid UTF-8 chunks
+            panic::catch_unwind(panic::AssertUnwindSafe(|| text_ref.get_string(txn))).unwrap_or_default()
+        }
+        XmlOut::Element(elem_ref) => join_children_text(elem_ref.children(txn), txn),
+        XmlOut::Fragment(frag_ref) => join_children_text(frag_ref.children(txn), txn),
+    }
+}
+
+fn join_children_text<T: ReadTxn>(children: impl Iterator<Item = XmlOut>, txn: &T) -> String {
+    let mut text = String::new();
+    for child in children {
+        let child_text = node_text(&child, txn);
+        if !child_text.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&child_text);
+        }
+    }
+    text
+}
+
+/// Container nodes whose children are listed as separate blocks
+const CONTAINER_TAGS: &[&str] = &[
+    "bullet_list", "ordered_list", "task_list", "blockquote", "table", "table_row",
+];
+
+/// A block-level node of a ProseMirror document with its plain text
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct DocumentBlock {
+    /// Node type (paragraph, heading, code_block, list_item, ...)
+    pub kind: String,
+    /// Nesting depth inside containers (0 for top-level blocks)
+    pub depth: u8,
+    pub text: String,
+}
+
+/// Flatten a document into its blocks in document order
+/// Lists, blockquotes and tables are expanded so each item or cell is its own block.
+pub fn document_blocks(doc: &Doc) -> Vec<DocumentBlock> {
+    let txn = doc.transact();
+    let mut blocks = Vec::new();
+    if let Some(fragment) = txn.get_xml_fragment(PROSEMIRROR_FRAGMENT) {
+        for child in fragment.children(&txn) {
+            collect_blocks(&child, &txn, 0, &mut blocks);
+        }
+    }
+    blocks
+}
+
+fn collect_blocks<T: ReadTxn>(node: &XmlOut, txn: &T, depth: u8, blocks: &mut Vec<DocumentBlock>) {
+    match node {
+        XmlOut::Element(element) if CONTAINER_TAGS.contains(&element.tag().as_ref()) => {
+            for child in element.children(txn) {
+                collect_blocks(&child, txn, depth.saturating_add(1), blocks);
+            }
+        }
+        XmlOut::Element(element) => blocks.push(DocumentBlock {
+            kind: element.tag().to_string(),
+            depth,
+            text: node_text(node, txn),
+        }),
+        XmlOut::Text(_) => blocks.push(DocumentBlock {
+            kind: "text".to_string(),
+            depth,
+            text: node_text(node, txn),
+        }),
+        XmlOut::Fragment(fragment) => {
+            for child in fragment.children(txn) {
+                collect_blocks(&child, txn, depth, blocks);
+            }
+        }
+    }
+}
+
+/// Append a plain paragraph to the end of a fragment
+pub fn push_paragraph<F: XmlFragment>(target: &F, txn: &mut TransactionMut, text: &str) {
+    let paragraph = target.push_back(txn, XmlElementPrelim::empty("paragraph"));
+    if !text.is_empty() {
+        paragraph.push_back(txn, XmlTextPrelim::new(text));
+    }
+}
+
+/// Append a heading of the given level to the end of a fragment
+pub fn push_heading<F: XmlFragment>(target: &F, txn: &mut TransactionMut, level: u8, text: &str) {
+    let heading = target.push_back(txn, XmlElementPrelim::empty("heading"));
+    heading.insert_attribute(txn, "level", level.to_string());
+    if !text.is_empty() {
+        heading.push_back(txn, XmlTextPrelim::new(text));
+    }
+}
+
+/// Append a horizontal rule to the end of a fragment
+pub fn push_horizontal_rule<F: XmlFragment>(target: &F, txn: &mut TransactionMut) {
+    target.push_back(txn, XmlElementPrelim::empty("horizontal_rule"));
+}
+
+/// Deep-copy every child of `source` to the end of `target`
+///
+/// Source and target must belong to different documents: the source is read through
+/// its own read transaction while the target is written through `target_txn`.
+pub fn copy_children<S, T, F>(source: &S, source_txn: &T, target: &F, target_txn: &mut TransactionMut)
+where
+    S: XmlFragment,
+    T: ReadTxn,
+    F: XmlFragment,
+{
+    for child in source.children(source_txn) {
+        copy_node(&child, source_txn, target, target_txn);
+    }
+}
+
+/// Deep-copy a single XML node (element, text or nested fragment) to the end of `target`
+pub fn copy_node<T, F>(node: &XmlOut, source_txn: &T, target: &F, target_txn: &mut TransactionMut)
+where
+    T: ReadTxn,
+    F: XmlFragment,
+{
+    copy_node_with(node, source_txn, target, target_txn, &|text: &str| text.to_string());
+}
+
+/// Deep-copy a single XML node, passing every text run and attribute through `map`
+/// (used to fill in placeholders when instantiating templates)
+pub fn copy_node_with<T, F, M>(node: &XmlOut, source_txn: &T, target: &F, target_txn: &mut TransactionMut, map: &M)
+where
+    T: ReadTxn,
+    F: XmlFragment,
+    M: Fn(&str) -> String,
+{
+    match node {
+        XmlOut::Element(element) => {
+            let copy = target.push_back(target_txn, XmlElementPrelim::empty(element.tag().clone()));
+
+            // Node attributes (heading level, image src, ticket link id, ...)
+            for (name, value) in element.attributes(source_txn) {
+                copy.insert_attribute(target_txn, name, map(&value));
+            }
+
+            for child in element.children(source_txn) {
+                copy_node_with(&child, source_txn, &copy, target_txn, map);
+            }
+        }
+        XmlOut::Text(text) => {
+            let copy = target.push_back(target_txn, XmlTextPrelim::new(""));
+
+            // Copy chunk by chunk so marks (bold, links, code, ...) survive the copy
+            for chunk in text.diff(source_txn, YChange::identity) {
+                if let Out::Any(Any::String(value)) = &chunk.insert {
+                    let attributes = chunk.attributes.map(|attrs| *attrs).unwrap_or_default();
+                    let index = copy.len(target_txn);
+                    copy.insert_with_attributes(target_txn, index, &map(value), attributes);
+                }
+            }
+        }
+        XmlOut::Fragment(fragment) => {
+            for child in fragment.children(source_txn) {
+                copy_node_with(&child, source_txn, target, target_txn, map);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document_with_paragraphs(paragraphs: &[&str]) -> Vec<u8> {
+        let doc = Doc::new();
+        {
+            let mut txn = doc.transact_mut();
+            let fragment = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+            for text in paragraphs {
+                push_paragraph(&fragment, &mut txn, text);
+            }
+        }
+        encode_document(&doc)
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_input() {
+        assert!(decode_document(&[]).is_none());
+        assert!(decode_document(&[0xff, 0xff, 0xff]).is_none());
+    }
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let bytes = document_with_paragraphs(&["first", "second"]);
+        let doc = decode_document(&bytes).expect("valid document");
+        assert_eq!(block_count(&doc), 2);
+    }
+
+    #[test]
+    fn test_document_blocks_flattens_lists() {
+        let doc = Doc::new();
+        {
+            let mut txn = doc.transact_mut();
+            let fragment = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+            push_heading(&fragment, &mut txn, 1, "Title");
+            let list = fragment.push_back(&mut txn, XmlElementPrelim::empty("bullet_list"));
+            let item = list.push_back(&mut txn, XmlElementPrelim::empty("list_item"));
+            push_paragraph(&item, &mut txn, "first item");
+        }
+
+        let blocks = document_blocks(&doc);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].kind, "heading");
+        assert_eq!(blocks[0].text, "Title");
+        assert_eq!(blocks[1].kind, "list_item");
+        assert_eq!(blocks[1].depth, 1);
+        assert_eq!(blocks[1].text, "first item");
+    }
+
+    #[test]
+    fn test_copy_children_appends_content() {
+        let source = decode_document(&document_with_paragraphs(&["from source"])).unwrap();
+        let target = decode_document(&document_with_paragraphs(&["existing"])).unwrap();
+
+        {
+            let source_txn = source.transact();
+            let source_fragment = source_txn.get_xml_fragment(PROSEMIRROR_FRAGMENT).unwrap();
+            let mut target_txn = target.transact_mut();
+            let target_fragment = target_txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+            copy_children(&source_fragment, &source_txn, &target_fragment, &mut target_txn);
+        }
+
+        assert_eq!(block_count(&target), 2);
+        let txn = target.transact();
+        let text = txn.get_xml_fragment(PROSEMIRROR_FRAGMENT).unwrap().get_string(&txn);
+        assert!(text.contains("existing"));
+        assert!(text.contains("from source"));
+    }
+}
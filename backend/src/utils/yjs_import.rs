@@ -0,0 +1,880 @@
+//! Build ProseMirror Yjs documents from CommonMark or HTML
+//!
+//! The inverse of `yjs_render`: markup is parsed into a small block tree that mirrors the
+//! editor schema (`frontend/src/components/editor/schema.ts`), which is then written into
+//! the `prosemirror` XmlFragment the collaborative editor expects. Anything the schema
+//! can't represent (tables, strikethrough, raw inline HTML) degrades to its text.
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+use std::sync::Arc;
+use yrs::types::Attrs;
+use yrs::{Any, Doc, Text, Transact, TransactionMut, WriteTxn, Xml, XmlElementPrelim, XmlElementRef, XmlFragment, XmlTextPrelim, XmlTextRef};
+
+use crate::utils::yjs::{push_horizontal_rule, PROSEMIRROR_FRAGMENT};
+use crate::utils::yjs_render::is_safe_href;
+
+/// Block-level node
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    Heading(u8, Vec<Inline>),
+    CodeBlock { language: Option<String>, code: String },
+    Blockquote(Vec<Block>),
+    HorizontalRule,
+    BulletList(Vec<Vec<Block>>),
+    OrderedList { start: u64, items: Vec<Vec<Block>> },
+}
+
+/// Inline content of paragraphs and headings
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String, Marks),
+    Image { src: String, alt: String, title: Option<String> },
+    HardBreak,
+}
+
+/// Marks applied to a text run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Marks {
+    pub strong: bool,
+    pub em: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+/// An HTML file's body along with its `<title>` and `<meta name=… content=…>` tags
+#[derive(Debug, Clone, Default)]
+pub struct HtmlDocument {
+    pub title: Option<String>,
+    pub meta: HashMap<String, String>,
+    pub blocks: Vec<Block>,
+}
+
+/// Plain text of inline content
+pub fn inline_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text, _) => text.as_str(),
+            Inline::Image { alt, .. } => alt.as_str(),
+            Inline::HardBreak => " ",
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Remove a leading top-level heading and return its text
+///
+/// Exported pages start with their title as an `h1`; importing them shouldn't repeat it in
+/// the body.
+pub fn take_title(blocks: &mut Vec<Block>) -> Option<String> {
+    match blocks.first() {
+        Some(Block::Heading(1, inlines)) => {
+            let title = inline_text(inlines);
+            blocks.remove(0);
+            Some(title)
+        }
+        _ => None,
+    }
+}
+
+/// Sources of every image in a block tree, in document order (duplicates removed)
+pub fn image_sources(blocks: &[Block]) -> Vec<String> {
+    let mut sources = Vec::new();
+    visit_images(blocks, &mut |src| {
+        if !sources.iter().any(|existing| existing == src) {
+            sources.push(src.clone());
+        }
+    });
+    sources
+}
+
+/// Replace image sources found in `images`; unmapped sources are kept
+pub fn rewrite_images(blocks: &mut [Block], images: &HashMap<String, String>) {
+    visit_images_mut(blocks, &mut |src| {
+        if let Some(replacement) = images.get(src.as_str()) {
+            *src = replacement.clone();
+        }
+    });
+}
+
+fn visit_images(blocks: &[Block], f: &mut impl FnMut(&String)) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) | Block::Heading(_, inlines) => {
+                for inline in inlines {
+                    if let Inline::Image { src, .. } = inline {
+                        f(src);
+                    }
+                }
+            }
+            Block::Blockquote(children) => visit_images(children, f),
+            Block::BulletList(items) | Block::OrderedList { items, .. } => {
+                for item in items {
+                    visit_images(item, f);
+                }
+            }
+            Block::CodeBlock { .. } | Block::HorizontalRule => {}
+        }
+    }
+}
+
+fn visit_images_mut(blocks: &mut [Block], f: &mut impl FnMut(&mut String)) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) | Block::Heading(_, inlines) => {
+                for inline in inlines {
+                    if let Inline::Image { src, .. } = inline {
+                        f(src);
+                    }
+                }
+            }
+            Block::Blockquote(children) => visit_images_mut(children, f),
+            Block::BulletList(items) | Block::OrderedList { items, .. } => {
+                for item in items {
+                    visit_images_mut(item, f);
+                }
+            }
+            Block::CodeBlock { .. } | Block::HorizontalRule => {}
+        }
+    }
+}
+
+/// Append text to inline content, merging it into the previous run when the marks match
+fn push_text(inlines: &mut Vec<Inline>, text: &str, marks: &Marks) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(Inline::Text(previous, previous_marks)) = inlines.last_mut() {
+        if previous_marks == marks {
+            previous.push_str(text);
+            return;
+        }
+    }
+    inlines.push(Inline::Text(text.to_string(), marks.clone()));
+}
+
+/// Drop leading/trailing whitespace of a paragraph; None if nothing is left
+fn finish_inlines(mut inlines: Vec<Inline>) -> Option<Vec<Inline>> {
+    if let Some(Inline::Text(text, _)) = inlines.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(Inline::Text(text, _)) = inlines.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    inlines.retain(|inline| !matches!(inline, Inline::Text(text, _) if text.is_empty()));
+    while matches!(inlines.last(), Some(Inline::HardBreak)) {
+        inlines.pop();
+    }
+    (!inlines.is_empty()).then_some(inlines)
+}
+
+// ----------------------------------------------------------------------------
+// CommonMark
+// ----------------------------------------------------------------------------
+
+/// Parse CommonMark into blocks (front-matter must already be stripped)
+pub fn parse_markdown(markdown: &str) -> Vec<Block> {
+    let mut builder = MarkdownBuilder::default();
+    builder.run(markdown);
+    match builder.frames.pop() {
+        Some(Frame::Blocks(blocks)) => blocks,
+        _ => Vec::new(),
+    }
+}
+
+enum Frame {
+    /// Document root
+    Blocks(Vec<Block>),
+    Quote(Vec<Block>),
+    /// Paragraph or heading contents
+    Inlines { level: Option<u8>, inlines: Vec<Inline> },
+    Code { language: Option<String>, code: String },
+    Html(String),
+    List { start: Option<u64>, items: Vec<Vec<Block>> },
+    /// List item; tight lists put text directly in the item, collected in `loose`
+    Item { blocks: Vec<Block>, loose: Vec<Inline> },
+}
+
+struct PendingImage {
+    src: String,
+    title: Option<String>,
+    alt: String,
+}
+
+struct MarkdownBuilder {
+    frames: Vec<Frame>,
+    strong: u32,
+    em: u32,
+    /// Enclosing link targets; `None` for links whose scheme is not allowed
+    links: Vec<Option<String>>,
+    image: Option<PendingImage>,
+}
+
+impl Default for MarkdownBuilder {
+    fn default() -> Self {
+        Self {
+            frames: vec![Frame::Blocks(Vec::new())],
+            strong: 0,
+            em: 0,
+            links: Vec::new(),
+            image: None,
+        }
+    }
+}
+
+impl MarkdownBuilder {
+    fn marks(&self) -> Marks {
+        Marks {
+            strong: self.strong > 0,
+            em: self.em > 0,
+            code: false,
+            link: self.links.last().cloned().flatten(),
+        }
+    }
+
+    fn run(&mut self, markdown: &str) {
+        for event in Parser::new_ext(markdown, Options::empty()) {
+            // Everything inside an image is its alt text
+            if let Some(image) = &mut self.image {
+                match event {
+                    Event::End(TagEnd::Image) => {
+                        let image = self.image.take().expect("image in progress");
+                        self.push_inline(Inline::Image {
+                            src: image.src,
+                            alt: image.alt,
+                            title: image.title,
+                        });
+                    }
+                    Event::Text(text) | Event::Code(text) => image.alt.push_str(&text),
+                    _ => {}
+                }
+                continue;
+            }
+
+            match event {
+                Event::Start(Tag::Paragraph) => self.frames.push(Frame::Inlines { level: None, inlines: Vec::new() }),
+                Event::Start(Tag::Heading { level, .. }) => self.frames.push(Frame::Inlines {
+                    level: Some(level as u8),
+                    inlines: Vec::new(),
+                }),
+                Event::Start(Tag::BlockQuote { .. }) => self.frames.push(Frame::Quote(Vec::new())),
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_string),
+                        CodeBlockKind::Indented => None,
+                    };
+                    self.frames.push(Frame::Code { language, code: String::new() });
+                }
+                Event::Start(Tag::HtmlBlock) => self.frames.push(Frame::Html(String::new())),
+                Event::Start(Tag::List(start)) => self.frames.push(Frame::List { start, items: Vec::new() }),
+                Event::Start(Tag::Item) => self.frames.push(Frame::Item { blocks: Vec::new(), loose: Vec::new() }),
+                Event::Start(Tag::Emphasis) => self.em += 1,
+                Event::Start(Tag::Strong) => self.strong += 1,
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    self.links.push(is_safe_href(&dest_url).then(|| dest_url.to_string()))
+                }
+                Event::Start(Tag::Image { dest_url, title, .. }) => {
+                    self.image = Some(PendingImage {
+                        src: dest_url.to_string(),
+                        title: (!title.is_empty()).then(|| title.to_string()),
+                        alt: String::new(),
+                    });
+                }
+                Event::End(TagEnd::Emphasis) => self.em = self.em.saturating_sub(1),
+                Event::End(TagEnd::Strong) => self.strong = self.strong.saturating_sub(1),
+                Event::End(TagEnd::Link) => {
+                    self.links.pop();
+                }
+                Event::End(
+                    TagEnd::Paragraph
+                    | TagEnd::Heading { .. }
+                    | TagEnd::BlockQuote { .. }
+                    | TagEnd::CodeBlock
+                    | TagEnd::HtmlBlock
+                    | TagEnd::List { .. }
+                    | TagEnd::Item,
+                ) => self.close(),
+                Event::Text(text) => match self.frames.last_mut() {
+                    Some(Frame::Code { code, .. }) => code.push_str(&text),
+                    _ => {
+                        let marks = self.marks();
+                        self.push_text(&text, &marks);
+                    }
+                },
+                Event::Code(text) => {
+                    let marks = Marks { code: true, ..self.marks() };
+                    self.push_text(&text, &marks);
+                }
+                Event::Html(html) => {
+                    if let Some(Frame::Html(buffer)) = self.frames.last_mut() {
+                        buffer.push_str(&html);
+                    }
+                }
+                Event::InlineHtml(html) => {
+                    let tag = html.trim().to_ascii_lowercase();
+                    if tag.starts_with("<br") {
+                        self.push_inline(Inline::HardBreak);
+                    }
+                }
+                Event::SoftBreak => {
+                    let marks = self.marks();
+                    self.push_text(" ", &marks);
+                }
+                Event::HardBreak => self.push_inline(Inline::HardBreak),
+                Event::Rule => self.push_block(Block::HorizontalRule),
+                _ => {}
+            }
+        }
+    }
+
+    fn inlines(&mut self) -> Option<&mut Vec<Inline>> {
+        match self.frames.last_mut() {
+            Some(Frame::Inlines { inlines, .. }) => Some(inlines),
+            Some(Frame::Item { loose, .. }) => Some(loose),
+            _ => None,
+        }
+    }
+
+    fn push_text(&mut self, text: &str, marks: &Marks) {
+        match self.inlines() {
+            Some(inlines) => push_text(inlines, text, marks),
+            None => self.push_block(Block::Paragraph(vec![Inline::Text(text.to_string(), marks.clone())])),
+        }
+    }
+
+    fn push_inline(&mut self, inline: Inline) {
+        match self.inlines() {
+            Some(inlines) => inlines.push(inline),
+            None => self.push_block(Block::Paragraph(vec![inline])),
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        match self.frames.last_mut() {
+            Some(Frame::Blocks(blocks)) | Some(Frame::Quote(blocks)) => blocks.push(block),
+            Some(Frame::Item { blocks, loose }) => {
+                if let Some(inlines) = finish_inlines(std::mem::take(loose)) {
+                    blocks.push(Block::Paragraph(inlines));
+                }
+                blocks.push(block);
+            }
+            // Well-formed event streams never put blocks directly in these
+            _ => {}
+        }
+    }
+
+    fn close(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        match frame {
+            Frame::Inlines { level, inlines } => {
+                let inlines = finish_inlines(inlines);
+                match (level, inlines) {
+                    (Some(level), inlines) => self.push_block(Block::Heading(level, inlines.unwrap_or_default())),
+                    (None, Some(inlines)) => self.push_block(Block::Paragraph(inlines)),
+                    (None, None) => {}
+                }
+            }
+            Frame::Quote(blocks) => self.push_block(Block::Blockquote(blocks)),
+            Frame::Code { language, mut code } => {
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+                self.push_block(Block::CodeBlock { language, code });
+            }
+            Frame::Html(html) => {
+                for block in parse_html_fragment(&html) {
+                    self.push_block(block);
+                }
+            }
+            Frame::List { start, items } => self.push_block(match start {
+                Some(start) => Block::OrderedList { start, items },
+                None => Block::BulletList(items),
+            }),
+            Frame::Item { mut blocks, loose } => {
+                if let Some(inlines) = finish_inlines(loose) {
+                    blocks.push(Block::Paragraph(inlines));
+                }
+                if let Some(Frame::List { items, .. }) = self.frames.last_mut() {
+                    items.push(blocks);
+                }
+            }
+            // The root is never closed; put it back
+            Frame::Blocks(blocks) => self.frames.push(Frame::Blocks(blocks)),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// HTML
+// ----------------------------------------------------------------------------
+
+/// Parse a full HTML document
+pub fn parse_html(html: &str) -> HtmlDocument {
+    let document = Html::parse_document(html);
+    let title_selector = Selector::parse("title").expect("valid selector");
+    let meta_selector = Selector::parse("meta[name][content]").expect("valid selector");
+    let body_selector = Selector::parse("body").expect("valid selector");
+
+    let title = document
+        .select(&title_selector)
+        .next()
+        .map(|title| title.text().collect::<String>().trim().to_string())
+        .filter(|title| !title.is_empty());
+
+    let meta = document
+        .select(&meta_selector)
+        .filter_map(|meta| {
+            let name = meta.value().attr("name")?.trim().to_ascii_lowercase();
+            let content = meta.value().attr("content")?.trim().to_string();
+            Some((name, content))
+        })
+        .collect();
+
+    let blocks = document
+        .select(&body_selector)
+        .next()
+        .map(html_blocks)
+        .unwrap_or_default();
+
+    HtmlDocument { title, meta, blocks }
+}
+
+/// Parse an HTML snippet (raw HTML blocks embedded in Markdown)
+fn parse_html_fragment(html: &str) -> Vec<Block> {
+    let fragment = Html::parse_fragment(html);
+    html_blocks(fragment.root_element())
+}
+
+/// Elements whose children are laid out as blocks
+const CONTAINER_ELEMENTS: &[&str] = &[
+    "html", "body", "div", "section", "article", "main", "header", "footer", "nav", "aside",
+    "figure", "figcaption", "table", "thead", "tbody", "tfoot", "tr", "td", "th", "dl", "dt", "dd",
+    "details", "summary", "li", "center", "form", "fieldset",
+];
+
+/// Elements that never carry document content
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "template", "noscript", "title", "meta", "link", "iframe", "object",
+    "svg", "button", "input", "select", "textarea",
+];
+
+fn html_blocks(element: ElementRef) -> Vec<Block> {
+    let mut builder = HtmlBuilder::default();
+    builder.children(element);
+    builder.flush();
+    builder.blocks
+}
+
+#[derive(Default)]
+struct HtmlBuilder {
+    blocks: Vec<Block>,
+    /// Inline content outside of a paragraph element, wrapped when a block starts
+    pending: Vec<Inline>,
+}
+
+impl HtmlBuilder {
+    fn flush(&mut self) {
+        if let Some(inlines) = finish_inlines(std::mem::take(&mut self.pending)) {
+            self.blocks.push(Block::Paragraph(inlines));
+        }
+    }
+
+    fn children(&mut self, parent: ElementRef) {
+        for child in parent.children() {
+            if let Some(element) = ElementRef::wrap(child) {
+                self.element(element);
+            } else if let Node::Text(text) = child.value() {
+                push_html_text(&mut self.pending, text, &Marks::default());
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if SKIPPED_ELEMENTS.contains(&name) {
+            return;
+        }
+        if CONTAINER_ELEMENTS.contains(&name) {
+            self.flush();
+            self.children(element);
+            self.flush();
+            return;
+        }
+
+        let block = match name {
+            "p" => finish_inlines(html_inlines(element, &Marks::default())).map(Block::Paragraph),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                Some(Block::Heading(level, finish_inlines(html_inlines(element, &Marks::default())).unwrap_or_default()))
+            }
+            "pre" => {
+                let mut code = element.text().collect::<String>();
+                if code.ends_with('\n') {
+                    code.pop();
+                }
+                Some(Block::CodeBlock { language: code_language(element), code })
+            }
+            "blockquote" => Some(Block::Blockquote(html_blocks(element))),
+            "hr" => Some(Block::HorizontalRule),
+            "ul" => Some(Block::BulletList(html_list_items(element))),
+            "ol" => Some(Block::OrderedList {
+                start: element.value().attr("start").and_then(|start| start.trim().parse().ok()).unwrap_or(1),
+                items: html_list_items(element),
+            }),
+            _ => {
+                html_inline_element(element, &Marks::default(), &mut self.pending);
+                None
+            }
+        };
+
+        if let Some(block) = block {
+            self.flush();
+            self.blocks.push(block);
+        }
+    }
+}
+
+fn html_list_items(list: ElementRef) -> Vec<Vec<Block>> {
+    list.children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| child.value().name() == "li")
+        .map(html_blocks)
+        .collect()
+}
+
+/// Language of a `<pre>` block, from a `language-*` class on it or its `<code>` child
+fn code_language(pre: ElementRef) -> Option<String> {
+    let from_classes = |element: ElementRef| {
+        element
+            .value()
+            .classes()
+            .find_map(|class| class.strip_prefix("language-").map(str::to_string))
+    };
+    from_classes(pre).or_else(|| {
+        pre.children()
+            .filter_map(ElementRef::wrap)
+            .find(|child| child.value().name() == "code")
+            .and_then(from_classes)
+    })
+}
+
+fn html_inlines(element: ElementRef, marks: &Marks) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    html_inline_children(element, marks, &mut inlines);
+    inlines
+}
+
+fn html_inline_children(parent: ElementRef, marks: &Marks, out: &mut Vec<Inline>) {
+    for child in parent.children() {
+        if let Some(element) = ElementRef::wrap(child) {
+            html_inline_element(element, marks, out);
+        } else if let Node::Text(text) = child.value() {
+            push_html_text(out, text, marks);
+        }
+    }
+}
+
+fn html_inline_element(element: ElementRef, marks: &Marks, out: &mut Vec<Inline>) {
+    let name = element.value().name();
+    let mut marks = marks.clone();
+    match name {
+        "br" => {
+            out.push(Inline::HardBreak);
+            return;
+        }
+        "img" => {
+            if let Some(src) = element.value().attr("src") {
+                out.push(Inline::Image {
+                    src: src.to_string(),
+                    alt: element.value().attr("alt").unwrap_or_default().to_string(),
+                    title: element.value().attr("title").map(str::to_string),
+                });
+            }
+            return;
+        }
+        _ if SKIPPED_ELEMENTS.contains(&name) => return,
+        "strong" | "b" => marks.strong = true,
+        "em" | "i" => marks.em = true,
+        "code" | "kbd" | "samp" | "tt" => marks.code = true,
+        "a" => {
+            if let Some(href) = element.value().attr("href").filter(|href| is_safe_href(href)) {
+                marks.link = Some(href.to_string());
+            }
+        }
+        _ => {}
+    }
+    html_inline_children(element, &marks, out);
+}
+
+/// Append HTML text with whitespace collapsed the way a browser renders it
+fn push_html_text(inlines: &mut Vec<Inline>, text: &str, marks: &Marks) {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_space = true;
+        } else {
+            if in_space {
+                collapsed.push(' ');
+            }
+            in_space = false;
+            collapsed.push(c);
+        }
+    }
+    if in_space {
+        collapsed.push(' ');
+    }
+
+    // A space already ends the previous run
+    let ends_with_space = match inlines.last() {
+        Some(Inline::Text(previous, _)) => previous.ends_with(' '),
+        Some(Inline::HardBreak) | None => true,
+        Some(Inline::Image { .. }) => false,
+    };
+    if ends_with_space && collapsed.starts_with(' ') {
+        collapsed.remove(0);
+    }
+    push_text(inlines, &collapsed, marks);
+}
+
+// ----------------------------------------------------------------------------
+// Yjs
+// ----------------------------------------------------------------------------
+
+/// Build a Yjs document containing the blocks as its ProseMirror fragment
+pub fn build_document(blocks: &[Block]) -> Doc {
+    let doc = Doc::new();
+    {
+        let mut txn = doc.transact_mut();
+        let fragment = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+        write_blocks(&fragment, &mut txn, blocks);
+
+        // The schema requires at least one block
+        if blocks.is_empty() {
+            fragment.push_back(&mut txn, XmlElementPrelim::empty("paragraph"));
+        }
+    }
+    doc
+}
+
+fn write_blocks<F: XmlFragment>(target: &F, txn: &mut TransactionMut, blocks: &[Block]) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(inlines) => {
+                let paragraph = target.push_back(txn, XmlElementPrelim::empty("paragraph"));
+                write_inlines(&paragraph, txn, inlines);
+            }
+            Block::Heading(level, inlines) => {
+                let heading = target.push_back(txn, XmlElementPrelim::empty("heading"));
+                heading.insert_attribute(txn, "level", (*level).clamp(1, 6).to_string());
+                write_inlines(&heading, txn, inlines);
+            }
+            Block::CodeBlock { language, code } => {
+                let code_block = target.push_back(txn, XmlElementPrelim::empty("code_block"));
+                if let Some(language) = language {
+                    code_block.insert_attribute(txn, "language", language.as_str());
+                }
+                if !code.is_empty() {
+                    code_block.push_back(txn, XmlTextPrelim::new(code.as_str()));
+                }
+            }
+            Block::Blockquote(children) => {
+                let quote = target.push_back(txn, XmlElementPrelim::empty("blockquote"));
+                write_container(&quote, txn, children);
+            }
+            Block::HorizontalRule => push_horizontal_rule(target, txn),
+            Block::BulletList(items) => {
+                let list = target.push_back(txn, XmlElementPrelim::empty("bullet_list"));
+                write_items(&list, txn, items);
+            }
+            Block::OrderedList { start, items } => {
+                let list = target.push_back(txn, XmlElementPrelim::empty("ordered_list"));
+                list.insert_attribute(txn, "order", start.to_string());
+                write_items(&list, txn, items);
+            }
+        }
+    }
+}
+
+/// Children of a node whose content must start with a paragraph (list items, blockquotes)
+fn write_container(target: &XmlElementRef, txn: &mut TransactionMut, blocks: &[Block]) {
+    if !matches!(blocks.first(), Some(Block::Paragraph(_))) {
+        target.push_back(txn, XmlElementPrelim::empty("paragraph"));
+    }
+    write_blocks(target, txn, blocks);
+}
+
+fn write_items(list: &XmlElementRef, txn: &mut TransactionMut, items: &[Vec<Block>]) {
+    for item in items {
+        let list_item = list.push_back(txn, XmlElementPrelim::empty("list_item"));
+        write_container(&list_item, txn, item);
+    }
+}
+
+fn write_inlines(target: &XmlElementRef, txn: &mut TransactionMut, inlines: &[Inline]) {
+    // Consecutive text runs share one XmlText, with marks as formatting attributes
+    let mut text: Option<XmlTextRef> = None;
+    for inline in inlines {
+        match inline {
+            Inline::Text(value, marks) => {
+                if value.is_empty() {
+                    continue;
+                }
+                let run = match &text {
+                    Some(run) => run.clone(),
+                    None => {
+                        let run = target.push_back(txn, XmlTextPrelim::new(""));
+                        text = Some(run.clone());
+                        run
+                    }
+                };
+                let index = run.len(txn);
+                run.insert_with_attributes(txn, index, value, mark_attributes(marks));
+            }
+            Inline::Image { src, alt, title } => {
+                text = None;
+                let image = target.push_back(txn, XmlElementPrelim::empty("image"));
+                image.insert_attribute(txn, "src", src.as_str());
+                if !alt.is_empty() {
+                    image.insert_attribute(txn, "alt", alt.as_str());
+                }
+                if let Some(title) = title {
+                    image.insert_attribute(txn, "title", title.as_str());
+                }
+            }
+            Inline::HardBreak => {
+                text = None;
+                target.push_back(txn, XmlElementPrelim::empty("hard_break"));
+            }
+        }
+    }
+}
+
+/// Formatting attributes for a text run; always explicit so marks don't leak between runs
+fn mark_attributes(marks: &Marks) -> Attrs {
+    let empty = || Any::Map(Arc::new(HashMap::new()));
+    let mut attrs = Attrs::new();
+    if marks.strong {
+        attrs.insert(Arc::from("strong"), empty());
+    }
+    if marks.em {
+        attrs.insert(Arc::from("em"), empty());
+    }
+    if marks.code {
+        attrs.insert(Arc::from("code"), empty());
+    }
+    if let Some(href) = &marks.link {
+        let link = HashMap::from([
+            ("href".to_string(), Any::String(Arc::from(href.as_str()))),
+            ("title".to_string(), Any::Null),
+        ]);
+        attrs.insert(Arc::from("link"), Any::Map(Arc::new(link)));
+    }
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::yjs_render::{self, Markup};
+
+    fn text(value: &str) -> Inline {
+        Inline::Text(value.to_string(), Marks::default())
+    }
+
+    #[test]
+    fn test_parse_markdown_blocks() {
+        let blocks = parse_markdown(
+            "# Title\n\nRun **this** now.\n\n- one\n- two\n  1. nested\n\n```sh\necho hi\n```\n\n> quoted\n\n---\n",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(1, vec![text("Title")]),
+                Block::Paragraph(vec![
+                    text("Run "),
+                    Inline::Text("this".to_string(), Marks { strong: true, ..Marks::default() }),
+                    text(" now."),
+                ]),
+                Block::BulletList(vec![
+                    vec![Block::Paragraph(vec![text("one")])],
+                    vec![
+                        Block::Paragraph(vec![text("two")]),
+                        Block::OrderedList {
+                            start: 1,
+                            items: vec![vec![Block::Paragraph(vec![text("nested")])]],
+                        },
+                    ],
+                ]),
+                Block::CodeBlock { language: Some("sh".to_string()), code: "echo hi".to_string() },
+                Block::Blockquote(vec![Block::Paragraph(vec![text("quoted")])]),
+                Block::HorizontalRule,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_html_collapses_whitespace() {
+        let document = parse_html(
+            "<html><head><title>Guide</title><meta name=\"display_order\" content=\"3\"></head>\
+             <body><article><h1>Guide</h1>\n<p>Open   the\n <a href=\"https://example.com\">portal</a><br>\
+             then <img src=\"img/a.png\" alt=\"shot\"></p><ul><li>first</li></ul></article></body></html>",
+        );
+        assert_eq!(document.title.as_deref(), Some("Guide"));
+        assert_eq!(document.meta.get("display_order").map(String::as_str), Some("3"));
+        assert_eq!(
+            document.blocks[1],
+            Block::Paragraph(vec![
+                text("Open the "),
+                Inline::Text(
+                    "portal".to_string(),
+                    Marks { link: Some("https://example.com".to_string()), ..Marks::default() }
+                ),
+                Inline::HardBreak,
+                text("then "),
+                Inline::Image { src: "img/a.png".to_string(), alt: "shot".to_string(), title: None },
+            ])
+        );
+        assert_eq!(document.blocks[2], Block::BulletList(vec![vec![Block::Paragraph(vec![text("first")])]]));
+    }
+
+    #[test]
+    fn test_build_document_round_trips_through_render() {
+        let mut blocks = parse_markdown("## Setup\n\nRun **this** and `that`\n\n- first\n\n![diagram](img/a.png)\n");
+        rewrite_images(&mut blocks, &HashMap::from([("img/a.png".to_string(), "/uploads/docs/a.png".to_string())]));
+
+        let doc = build_document(&blocks);
+        let markdown = yjs_render::render(&doc, Markup::Markdown, &HashMap::new());
+        assert_eq!(
+            markdown,
+            "## Setup\n\nRun **this** and `that`\n\n- first\n\n![diagram](/uploads/docs/a.png)\n"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_link_schemes_are_stripped() {
+        assert_eq!(
+            parse_markdown("[run](javascript:alert(1)) [docs](https://example.com)"),
+            vec![Block::Paragraph(vec![
+                text("run "),
+                Inline::Text("docs".to_string(), Marks { link: Some("https://example.com".to_string()), ..Marks::default() }),
+            ])]
+        );
+        let document = parse_html("<p><a href=\"javascript:alert(1)\">run</a></p>");
+        assert_eq!(document.blocks, vec![Block::Paragraph(vec![text("run")])]);
+    }
+
+    #[test]
+    fn test_take_title() {
+        let mut blocks = parse_markdown("# Welcome\n\nBody");
+        assert_eq!(take_title(&mut blocks).as_deref(), Some("Welcome"));
+        assert_eq!(blocks, vec![Block::Paragraph(vec![text("Body")])]);
+        assert_eq!(take_title(&mut blocks), None);
+    }
+}
@@ -0,0 +1,575 @@
+//! Render the ProseMirror XML fragment of a Yjs document as CommonMark or HTML
+//!
+//! Follows the editor schema (`frontend/src/components/editor/schema.ts`): block nodes are
+//! XmlElements named after the node type, text runs are XmlText with marks stored as
+//! formatting attributes, and inline nodes (image, hard_break, ticket_link) are elements
+//! between text runs. Unknown nodes render their children so no text is lost.
+//!
+//! Image sources pass through a caller-provided mapper, which lets exports point images at
+//! bundled files or inline them as data URIs.
+
+use std::collections::HashMap;
+use std::panic;
+use yrs::types::text::YChange;
+use yrs::types::Attrs;
+use yrs::{Any, Doc, Out, ReadTxn, Text, Transact, Xml, XmlElementRef, XmlFragment, XmlOut};
+
+use crate::utils::yjs::PROSEMIRROR_FRAGMENT;
+
+/// Output flavour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Markdown,
+    Html,
+}
+
+/// Sources of every image in a document, in document order (duplicates removed)
+pub fn image_sources(doc: &Doc) -> Vec<String> {
+    fn collect<T: ReadTxn>(node: &XmlOut, txn: &T, sources: &mut Vec<String>) {
+        if let XmlOut::Element(element) = node {
+            if element.tag().as_ref() == "image" {
+                if let Some(src) = string_attribute(element, txn, "src") {
+                    if !sources.contains(&src) {
+                        sources.push(src);
+                    }
+                }
+            }
+            for child in element.children(txn) {
+                collect(&child, txn, sources);
+            }
+        }
+    }
+
+    let txn = doc.transact();
+    let mut sources = Vec::new();
+    if let Some(fragment) = txn.get_xml_fragment(PROSEMIRROR_FRAGMENT) {
+        for child in fragment.children(&txn) {
+            collect(&child, &txn, &mut sources);
+        }
+    }
+    sources
+}
+
+/// Render a document body (without title or page chrome)
+///
+/// `images` maps original image sources to the ones to emit; unmapped sources are kept.
+pub fn render(doc: &Doc, markup: Markup, images: &HashMap<String, String>) -> String {
+    let txn = doc.transact();
+    let Some(fragment) = txn.get_xml_fragment(PROSEMIRROR_FRAGMENT) else {
+        return String::new();
+    };
+
+    let renderer = Renderer { txn: &txn, images };
+    let blocks: Vec<XmlOut> = fragment.children(&txn).collect();
+    match markup {
+        Markup::Markdown => {
+            let mut out = renderer.markdown_blocks(&blocks);
+            out.truncate(out.trim_end().len());
+            out.push('\n');
+            out
+        }
+        Markup::Html => renderer.html_blocks(&blocks),
+    }
+}
+
+/// Wrap rendered HTML in a standalone, print-friendly page
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif; line-height: 1.6; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #1f2937; }}
+pre {{ background: #f3f4f6; padding: 0.75rem 1rem; overflow-x: auto; border-radius: 4px; }}
+code {{ font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }}
+blockquote {{ border-left: 3px solid #d1d5db; margin-left: 0; padding-left: 1rem; color: #4b5563; }}
+img {{ max-width: 100%; }}
+@media print {{
+  body {{ margin: 0; max-width: none; }}
+  pre, blockquote, img, table {{ page-break-inside: avoid; }}
+  h1, h2, h3 {{ page-break-after: avoid; }}
+  a {{ color: inherit; }}
+}}
+</style>
+</head>
+<body>
+<article>
+<h1>{title}</h1>
+{body}</article>
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        body = body
+    )
+}
+
+struct Renderer<'a, T: ReadTxn> {
+    txn: &'a T,
+    images: &'a HashMap<String, String>,
+}
+
+impl<T: ReadTxn> Renderer<'_, T> {
+    fn image_src(&self, src: &str) -> String {
+        self.images.get(src).cloned().unwrap_or_else(|| src.to_string())
+    }
+
+    // ----- Markdown -----
+
+    /// Blocks separated by blank lines, each ending with a newline
+    fn markdown_blocks(&self, nodes: &[XmlOut]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            let block = self.markdown_block(node);
+            if block.trim().is_empty() {
+                continue;
+            }
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(block.trim_end_matches('\n'));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn markdown_block(&self, node: &XmlOut) -> String {
+        let XmlOut::Element(element) = node else {
+            return self.markdown_inline(std::slice::from_ref(node));
+        };
+        let children: Vec<XmlOut> = element.children(self.txn).collect();
+
+        match element.tag().as_ref() {
+            "paragraph" => self.markdown_inline(&children),
+            "heading" => {
+                let level = number_attribute(element, self.txn, "level").unwrap_or(1).clamp(1, 6) as usize;
+                format!("{} {}", "#".repeat(level), self.markdown_inline(&children))
+            }
+            "code_block" => {
+                let language = string_attribute(element, self.txn, "language").unwrap_or_default();
+                let code = plain_text(&children, self.txn);
+                let fence = if code.contains("```") { "~~~~" } else { "```" };
+                format!("{fence}{language}\n{}\n{fence}", code.trim_end_matches('\n'))
+            }
+            "blockquote" => prefix_lines(&self.markdown_blocks(&children), "> ", "> "),
+            "horizontal_rule" => "---".to_string(),
+            "bullet_list" => self.markdown_list(&children, None),
+            "ordered_list" => {
+                let start = number_attribute(element, self.txn, "order").unwrap_or(1);
+                self.markdown_list(&children, Some(start))
+            }
+            "list_item" => self.markdown_blocks(&children),
+            _ if is_inline(element) => self.markdown_inline(std::slice::from_ref(node)),
+            _ => self.markdown_blocks(&children),
+        }
+    }
+
+    fn markdown_list(&self, items: &[XmlOut], start: Option<i64>) -> String {
+        let mut out = String::new();
+        for (index, item) in items.iter().enumerate() {
+            let marker = match start {
+                Some(start) => format!("{}. ", start + index as i64),
+                None => "- ".to_string(),
+            };
+            let indent = " ".repeat(marker.len());
+            let body = match item {
+                XmlOut::Element(element) => self.markdown_blocks(&element.children(self.txn).collect::<Vec<_>>()),
+                other => self.markdown_block(other),
+            };
+            out.push_str(&prefix_lines(body.trim_end(), &marker, &indent));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn markdown_inline(&self, nodes: &[XmlOut]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            match node {
+                XmlOut::Text(text) => {
+                    for (value, attrs) in text_chunks(text, self.txn) {
+                        out.push_str(&markdown_marks(&value, attrs.as_ref()));
+                    }
+                }
+                XmlOut::Element(element) => match element.tag().as_ref() {
+                    "image" => {
+                        let src = self.image_src(&string_attribute(element, self.txn, "src").unwrap_or_default());
+                        let alt = string_attribute(element, self.txn, "alt").unwrap_or_default();
+                        match string_attribute(element, self.txn, "title").filter(|t| !t.is_empty()) {
+                            Some(title) => out.push_str(&format!(
+                                "![{}]({} \"{}\")",
+                                escape_markdown(&alt),
+                                markdown_url(&src),
+                                title.replace('"', "\\\"")
+                            )),
+                            None => out.push_str(&format!("![{}]({})", escape_markdown(&alt), markdown_url(&src))),
+                        }
+                    }
+                    "hard_break" => out.push_str("\\\n"),
+                    "ticket_link" => {
+                        let ticket_id = string_attribute(element, self.txn, "ticketId").unwrap_or_default();
+                        match string_attribute(element, self.txn, "href").filter(|href| is_safe_href(href)) {
+                            Some(href) => out.push_str(&format!("[#{}]({})", ticket_id, markdown_url(&href))),
+                            None => out.push_str(&format!("#{}", ticket_id)),
+                        }
+                    }
+                    _ => out.push_str(&self.markdown_inline(&element.children(self.txn).collect::<Vec<_>>())),
+                },
+                XmlOut::Fragment(fragment) => {
+                    out.push_str(&self.markdown_inline(&fragment.children(self.txn).collect::<Vec<_>>()));
+                }
+            }
+        }
+        out
+    }
+
+    // ----- HTML -----
+
+    fn html_blocks(&self, nodes: &[XmlOut]) -> String {
+        nodes.iter().map(|node| self.html_block(node)).collect()
+    }
+
+    fn html_block(&self, node: &XmlOut) -> String {
+        let XmlOut::Element(element) = node else {
+            return self.html_inline(std::slice::from_ref(node));
+        };
+        let children: Vec<XmlOut> = element.children(self.txn).collect();
+
+        match element.tag().as_ref() {
+            "paragraph" => format!("<p>{}</p>\n", self.html_inline(&children)),
+            "heading" => {
+                let level = number_attribute(element, self.txn, "level").unwrap_or(1).clamp(1, 6);
+                format!("<h{level}>{}</h{level}>\n", self.html_inline(&children))
+            }
+            "code_block" => {
+                let code = escape_html(&plain_text(&children, self.txn));
+                match string_attribute(element, self.txn, "language").filter(|l| !l.is_empty()) {
+                    Some(language) => format!("<pre><code class=\"language-{}\">{}</code></pre>\n", escape_html(&language), code),
+                    None => format!("<pre><code>{}</code></pre>\n", code),
+                }
+            }
+            "blockquote" => format!("<blockquote>\n{}</blockquote>\n", self.html_blocks(&children)),
+            "horizontal_rule" => "<hr>\n".to_string(),
+            "bullet_list" => format!("<ul>\n{}</ul>\n", self.html_blocks(&children)),
+            "ordered_list" => match number_attribute(element, self.txn, "order").filter(|start| *start != 1) {
+                Some(start) => format!("<ol start=\"{}\">\n{}</ol>\n", start, self.html_blocks(&children)),
+                None => format!("<ol>\n{}</ol>\n", self.html_blocks(&children)),
+            },
+            "list_item" => format!("<li>{}</li>\n", self.html_blocks(&children).trim_end()),
+            _ if is_inline(element) => self.html_inline(std::slice::from_ref(node)),
+            _ => self.html_blocks(&children),
+        }
+    }
+
+    fn html_inline(&self, nodes: &[XmlOut]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            match node {
+                XmlOut::Text(text) => {
+                    for (value, attrs) in text_chunks(text, self.txn) {
+                        out.push_str(&html_marks(&value, attrs.as_ref()));
+                    }
+                }
+                XmlOut::Element(element) => match element.tag().as_ref() {
+                    "image" => {
+                        let src = self.image_src(&string_attribute(element, self.txn, "src").unwrap_or_default());
+                        let alt = string_attribute(element, self.txn, "alt").unwrap_or_default();
+                        out.push_str(&format!("<img src=\"{}\" alt=\"{}\"", escape_html(&src), escape_html(&alt)));
+                        if let Some(title) = string_attribute(element, self.txn, "title").filter(|t| !t.is_empty()) {
+                            out.push_str(&format!(" title=\"{}\"", escape_html(&title)));
+                        }
+                        out.push('>');
+                    }
+                    "hard_break" => out.push_str("<br>"),
+                    "ticket_link" => {
+                        let ticket_id = string_attribute(element, self.txn, "ticketId").unwrap_or_default();
+                        match string_attribute(element, self.txn, "href").filter(|href| is_safe_href(href)) {
+                            Some(href) => out.push_str(&format!(
+                                "<a class=\"ticket-link\" href=\"{}\">#{}</a>",
+                                escape_html(&href),
+                                escape_html(&ticket_id)
+                            )),
+                            None => out.push_str(&format!("<span class=\"ticket-link\">#{}</span>", escape_html(&ticket_id))),
+                        }
+                    }
+                    _ => out.push_str(&self.html_inline(&element.children(self.txn).collect::<Vec<_>>())),
+                },
+                XmlOut::Fragment(fragment) => {
+                    out.push_str(&self.html_inline(&fragment.children(self.txn).collect::<Vec<_>>()));
+                }
+            }
+        }
+        out
+    }
+}
+
+fn is_inline(element: &XmlElementRef) -> bool {
+    matches!(element.tag().as_ref(), "image" | "hard_break" | "ticket_link")
+}
+
+fn string_attribute<T: ReadTxn>(element: &XmlElementRef, txn: &T, name: &str) -> Option<String> {
+    element.get_attribute(txn, name)
+}
+
+fn number_attribute<T: ReadTxn>(element: &XmlElementRef, txn: &T, name: &str) -> Option<i64> {
+    let value = element.get_attribute(txn, name)?;
+    let value = value.trim();
+    value.parse().ok().or_else(|| value.parse::<f64>().ok().map(|number| number as i64))
+}
+
+/// Formatted runs of an XmlText
+fn text_chunks<T: ReadTxn>(text: &yrs::XmlTextRef, txn: &T) -> Vec<(String, Option<Attrs>)> {
+    // Reading text can panic on invalid UTF-8 chunks (see utils::yjs::node_text)
+    let chunks = panic::catch_unwind(panic::AssertUnwindSafe(|| text.diff(txn, YChange::identity)))
+        .unwrap_or_default();
+    chunks
+        .into_iter()
+        .filter_map(|chunk| match chunk.insert {
+            Out::Any(Any::String(value)) => Some((value.to_string(), chunk.attributes.map(|attrs| *attrs))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Unformatted text of inline content (code blocks)
+fn plain_text<T: ReadTxn>(nodes: &[XmlOut], txn: &T) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            XmlOut::Text(text) => {
+                for (value, _) in text_chunks(text, txn) {
+                    out.push_str(&value);
+                }
+            }
+            XmlOut::Element(element) if element.tag().as_ref() == "hard_break" => out.push('\n'),
+            XmlOut::Element(element) => out.push_str(&plain_text(&element.children(txn).collect::<Vec<_>>(), txn)),
+            XmlOut::Fragment(fragment) => out.push_str(&plain_text(&fragment.children(txn).collect::<Vec<_>>(), txn)),
+        }
+    }
+    out
+}
+
+/// Whether a link target may be rendered: http(s), mailto or a relative URL
+///
+/// Browsers ignore ASCII tabs and newlines inside a URL (`java\tscript:`), so control
+/// characters are dropped before the scheme is read.
+pub fn is_safe_href(href: &str) -> bool {
+    let href: String = href.trim().chars().filter(|c| !c.is_ascii_control()).collect();
+    match href.find([':', '/', '?', '#']) {
+        Some(index) if href[index..].starts_with(':') => {
+            matches!(href[..index].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// Link target of a text chunk; links with other schemes are dropped, keeping their text
+fn mark_href(attrs: &Attrs) -> Option<String> {
+    let href = match attrs.get("link")? {
+        Any::Map(map) => match map.get("href")? {
+            Any::String(href) => href.to_string(),
+            _ => return None,
+        },
+        Any::String(href) => href.to_string(),
+        _ => return None,
+    };
+    is_safe_href(&href).then_some(href)
+}
+
+fn markdown_marks(text: &str, attrs: Option<&Attrs>) -> String {
+    let Some(attrs) = attrs else {
+        return escape_markdown(text);
+    };
+
+    // Emphasis markers must hug the text, so keep surrounding whitespace outside them
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+
+    let mut out = if attrs.contains_key("code") {
+        let ticks = if trimmed.contains('`') { "``" } else { "`" };
+        format!("{ticks}{trimmed}{ticks}")
+    } else {
+        escape_markdown(trimmed)
+    };
+    if attrs.contains_key("em") {
+        out = format!("*{}*", out);
+    }
+    if attrs.contains_key("strong") {
+        out = format!("**{}**", out);
+    }
+    if let Some(href) = mark_href(attrs) {
+        out = format!("[{}]({})", out, markdown_url(&href));
+    }
+    format!("{}{}{}", leading, out, trailing)
+}
+
+fn html_marks(text: &str, attrs: Option<&Attrs>) -> String {
+    let mut out = escape_html(text);
+    let Some(attrs) = attrs else {
+        return out;
+    };
+    if attrs.contains_key("code") {
+        out = format!("<code>{}</code>", out);
+    }
+    if attrs.contains_key("em") {
+        out = format!("<em>{}</em>", out);
+    }
+    if attrs.contains_key("strong") {
+        out = format!("<strong>{}</strong>", out);
+    }
+    if let Some(href) = mark_href(attrs) {
+        out = format!("<a href=\"{}\">{}</a>", escape_html(&href), out);
+    }
+    out
+}
+
+/// Prefix the first line with `first` and the following lines with `rest`
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    let mut out = String::new();
+    for (index, line) in text.lines().enumerate() {
+        let prefix = if index == 0 { first } else { rest };
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// URLs with spaces or parentheses go in angle brackets
+fn markdown_url(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url.replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::yjs::{push_heading, push_paragraph};
+    use std::collections::HashMap as Map;
+    use std::sync::Arc;
+    use yrs::{WriteTxn, XmlElementPrelim, XmlTextPrelim};
+
+    fn sample_document() -> Doc {
+        let doc = Doc::new();
+        {
+            let mut txn = doc.transact_mut();
+            let fragment = txn.get_or_insert_xml_fragment(PROSEMIRROR_FRAGMENT);
+            push_heading(&fragment, &mut txn, 2, "Setup");
+
+            let paragraph = fragment.push_back(&mut txn, XmlElementPrelim::empty("paragraph"));
+            let text = paragraph.push_back(&mut txn, XmlTextPrelim::new("Run "));
+            let mut bold = Attrs::new();
+            bold.insert(Arc::from("strong"), Any::Map(Arc::new(Map::new())));
+            text.insert_with_attributes(&mut txn, 4, "this", bold);
+            let image = paragraph.push_back(&mut txn, XmlElementPrelim::empty("image"));
+            image.insert_attribute(&mut txn, "src", "/uploads/docs/a.png");
+            image.insert_attribute(&mut txn, "alt", "diagram");
+
+            let list = fragment.push_back(&mut txn, XmlElementPrelim::empty("bullet_list"));
+            let item = list.push_back(&mut txn, XmlElementPrelim::empty("list_item"));
+            push_paragraph(&item, &mut txn, "first");
+
+            let code = fragment.push_back(&mut txn, XmlElementPrelim::empty("code_block"));
+            code.insert_attribute(&mut txn, "language", "sh");
+            code.push_back(&mut txn, XmlTextPrelim::new("echo <hi>"));
+        }
+        doc
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let doc = sample_document();
+        let markdown = render(&doc, Markup::Markdown, &HashMap::new());
+        assert_eq!(
+            markdown,
+            "## Setup\n\nRun **this**![diagram](/uploads/docs/a.png)\n\n- first\n\n```sh\necho <hi>\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_render_html_with_mapped_images() {
+        let doc = sample_document();
+        let images = HashMap::from([("/uploads/docs/a.png".to_string(), "data:image/png;base64,AAAA".to_string())]);
+        let html = render(&doc, Markup::Html, &images);
+        assert!(html.contains("<h2>Setup</h2>"));
+        assert!(html.contains("Run <strong>this</strong><img src=\"data:image/png;base64,AAAA\" alt=\"diagram\">"));
+        assert!(html.contains("<ul>\n<li><p>first</p></li>\n</ul>"));
+        assert!(html.contains("<pre><code class=\"language-sh\">echo &lt;hi&gt;</code></pre>"));
+    }
+
+    #[test]
+    fn test_image_sources() {
+        assert_eq!(image_sources(&sample_document()), vec!["/uploads/docs/a.png".to_string()]);
+    }
+
+    #[test]
+    fn test_is_safe_href() {
+        assert!(is_safe_href("https://example.com/a"));
+        assert!(is_safe_href("HTTP://example.com"));
+        assert!(is_safe_href("mailto:help@example.com"));
+        assert!(is_safe_href("/kb/setup"));
+        assert!(is_safe_href("setup#install"));
+        assert!(is_safe_href("?q=a:b"));
+        assert!(!is_safe_href("javascript:alert(1)"));
+        assert!(!is_safe_href(" JavaScript:alert(1)"));
+        assert!(!is_safe_href("java\tscript:alert(1)"));
+        assert!(!is_safe_href("data:text/html,<script>"));
+        assert!(!is_safe_href("vbscript:msgbox"));
+    }
+
+    #[test]
+    fn test_unsafe_links_render_as_text() {
+        let mut attrs = Attrs::new();
+        attrs.insert(Arc::from("link"), Any::String(Arc::from("javascript:alert(1)")));
+        assert_eq!(html_marks("click", Some(&attrs)), "click");
+        assert_eq!(markdown_marks("click", Some(&attrs)), "click");
+
+        attrs.insert(Arc::from("link"), Any::String(Arc::from("https://example.com")));
+        assert_eq!(html_marks("click", Some(&attrs)), "<a href=\"https://example.com\">click</a>");
+    }
+
+    #[test]
+    fn test_escape_markdown_text() {
+        assert_eq!(markdown_marks("a*b_c", None), "a\\*b\\_c");
+    }
+}